@@ -0,0 +1,809 @@
+//! Typed wrappers over [`Request`]/[`Response`] pairs.
+//!
+//! Each method builds one request, sends it, and unwraps the response variant
+//! the daemon answers it with. `Response::Error` becomes
+//! [`ClientError::Daemon`]; any other variant is a protocol bug and becomes
+//! [`ClientError::UnexpectedResponse`].
+
+use crate::{ClientError, DaemonClient, Request, Response, Result};
+use ringlet_core::rpc::ExecutionContext;
+use ringlet_core::{
+    AgentInfo, AgentType, BudgetStatus, BundleReport, Diagnostics, HooksConfig,
+    LocalProviderRequest, ProfileCreateRequest, ProfileDoctorReport, ProfileInfo,
+    ProfileProxyConfig, ProviderCheck, ProviderInfo, ProxyInstanceInfo, RegistryStatus,
+    RoutingRule, RunHistoryEntry, StatsResponse, UsageImportFormat, UsagePeriod, UsageRecordsPage,
+    UsageStatsResponse,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Unwrap the expected response variant, mapping errors and anything else.
+macro_rules! expect {
+    ($response:expr, $pattern:pat => $value:expr) => {
+        match $response {
+            $pattern => Ok($value),
+            Response::Error { code, message } => Err(ClientError::Daemon { code, message }),
+            other => Err(ClientError::UnexpectedResponse(response_kind(&other))),
+        }
+    };
+}
+
+/// The `type` tag of a response, for error messages.
+fn response_kind(response: &Response) -> String {
+    serde_json::to_value(response)
+        .ok()
+        .and_then(|value| value["type"].as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn success(response: Response) -> Result<String> {
+    expect!(response, Response::Success { message } => message)
+}
+
+/// What a `hooks add --dry-run` would save and generate.
+#[derive(Debug, Clone)]
+pub struct HooksPreview {
+    /// The profile's hooks with the new rule added.
+    pub hooks: HooksConfig,
+    /// Agent config file the hooks are written into, if the script writes one.
+    pub file: Option<String>,
+    /// Generated contents of `file`.
+    pub generated: Option<String>,
+}
+
+/// A profile's hooks rendered in an agent's native config format.
+#[derive(Debug, Clone)]
+pub struct NativeHooks {
+    /// Agent config file the hooks belong in.
+    pub file: String,
+    /// The hooks section of that file.
+    pub content: String,
+}
+
+impl DaemonClient {
+    /// Agent detection and manifests.
+    pub fn agents(&self) -> Agents<'_> {
+        Agents { client: self }
+    }
+
+    /// Provider definitions.
+    pub fn providers(&self) -> Providers<'_> {
+        Providers { client: self }
+    }
+
+    /// Profile management and runs.
+    pub fn profiles(&self) -> Profiles<'_> {
+        Profiles { client: self }
+    }
+
+    /// Per-profile shell aliases.
+    pub fn aliases(&self) -> Aliases<'_> {
+        Aliases { client: self }
+    }
+
+    /// Registry sync and pinning.
+    pub fn registry(&self) -> Registry<'_> {
+        Registry { client: self }
+    }
+
+    /// Token usage, costs and budgets.
+    pub fn usage(&self) -> Usage<'_> {
+        Usage { client: self }
+    }
+
+    /// Profile hooks and per-agent default hooks.
+    pub fn hooks(&self) -> Hooks<'_> {
+        Hooks { client: self }
+    }
+
+    /// Per-profile proxy instances, routes and model aliases.
+    pub fn proxy(&self) -> Proxy<'_> {
+        Proxy { client: self }
+    }
+
+    /// Session counts and runtime, optionally filtered by agent or provider.
+    pub fn stats(
+        &self,
+        agent_id: Option<&str>,
+        provider_id: Option<&str>,
+    ) -> Result<StatsResponse> {
+        let response = self.request(&Request::Stats {
+            agent_id: agent_id.map(str::to_string),
+            provider_id: provider_id.map(str::to_string),
+        })?;
+        expect!(response, Response::Stats(stats) => stats)
+    }
+
+    /// Write all profiles, config files and the registry pin to a `.tar.gz`
+    /// at `path` on the daemon's machine. Uses the long-request timeout.
+    pub fn export_bundle(&self, path: &Path) -> Result<BundleReport> {
+        let response = self.request_long(&Request::BundleExport {
+            path: path.to_path_buf(),
+        })?;
+        expect!(response, Response::Bundle(report) => report)
+    }
+
+    /// Restore a bundle written by [`export_bundle`](Self::export_bundle),
+    /// replacing existing profiles and files when `overwrite` is set. A bundle
+    /// carrying hooks, scripts or other commands is refused unless `force` is set.
+    pub fn import_bundle(&self, path: &Path, overwrite: bool, force: bool) -> Result<BundleReport> {
+        let response = self.request_long(&Request::BundleImport {
+            path: path.to_path_buf(),
+            overwrite,
+            force,
+        })?;
+        expect!(response, Response::Bundle(report) => report)
+    }
+
+    /// Daemon-side facts checked by `ringlet doctor`.
+    pub fn diagnostics(&self) -> Result<Diagnostics> {
+        let response = self.request(&Request::Diagnostics)?;
+        expect!(response, Response::Diagnostics(diagnostics) => diagnostics)
+    }
+
+    /// Run an environment setup task for a profile.
+    pub fn env_setup(&self, alias: &str, task: &str) -> Result<String> {
+        success(self.request(&Request::EnvSetup {
+            alias: alias.to_string(),
+            task: task.to_string(),
+        })?)
+    }
+}
+
+/// Requests about agents. See [`DaemonClient::agents`].
+pub struct Agents<'a> {
+    client: &'a DaemonClient,
+}
+
+impl Agents<'_> {
+    pub fn list(&self) -> Result<Vec<AgentInfo>> {
+        let response = self.client.request(&Request::AgentsList)?;
+        expect!(response, Response::Agents(agents) => agents)
+    }
+
+    pub fn inspect(&self, id: &str) -> Result<AgentInfo> {
+        let response = self
+            .client
+            .request(&Request::AgentsInspect { id: id.to_string() })?;
+        expect!(response, Response::Agent(agent) => agent)
+    }
+}
+
+/// Requests about providers. See [`DaemonClient::providers`].
+pub struct Providers<'a> {
+    client: &'a DaemonClient,
+}
+
+impl Providers<'_> {
+    pub fn list(
+        &self,
+        provider_type: Option<&str>,
+        search: Option<&str>,
+    ) -> Result<Vec<ProviderInfo>> {
+        let response = self.client.request(&Request::ProvidersList {
+            provider_type: provider_type.map(str::to_string),
+            search: search.map(str::to_string),
+        })?;
+        expect!(response, Response::Providers(providers) => providers)
+    }
+
+    pub fn inspect(&self, id: &str) -> Result<ProviderInfo> {
+        let response = self
+            .client
+            .request(&Request::ProvidersInspect { id: id.to_string() })?;
+        expect!(response, Response::Provider(provider) => provider)
+    }
+
+    /// Check that a provider endpoint is reachable and, given a key, that it
+    /// accepts it.
+    pub fn check(
+        &self,
+        id: &str,
+        endpoint: Option<&str>,
+        api_key: Option<String>,
+    ) -> Result<ProviderCheck> {
+        let response = self.client.request(&Request::ProvidersCheck {
+            id: id.to_string(),
+            endpoint: endpoint.map(str::to_string),
+            api_key,
+        })?;
+        expect!(response, Response::ProviderCheck(check) => check)
+    }
+
+    /// Register a provider for a locally hosted model server.
+    pub fn add_local(&self, request: LocalProviderRequest) -> Result<ProviderInfo> {
+        let response = self.client.request(&Request::ProvidersAddLocal(request))?;
+        expect!(response, Response::Provider(provider) => provider)
+    }
+}
+
+/// Requests about profiles. See [`DaemonClient::profiles`].
+pub struct Profiles<'a> {
+    client: &'a DaemonClient,
+}
+
+impl Profiles<'_> {
+    pub fn create(&self, request: ProfileCreateRequest) -> Result<String> {
+        success(self.client.request(&Request::ProfilesCreate(request))?)
+    }
+
+    pub fn list(
+        &self,
+        agent_id: Option<&str>,
+        provider_id: Option<&str>,
+    ) -> Result<Vec<ProfileInfo>> {
+        let response = self.client.request(&Request::ProfilesList {
+            agent_id: agent_id.map(str::to_string),
+            provider_id: provider_id.map(str::to_string),
+        })?;
+        expect!(response, Response::Profiles(profiles) => profiles)
+    }
+
+    /// Profile aliases only, without loading each profile.
+    pub fn names(&self) -> Result<Vec<String>> {
+        let response = self.client.request(&Request::ProfilesNames)?;
+        expect!(response, Response::ProfileNames(names) => names)
+    }
+
+    pub fn inspect(&self, alias: &str, show_secrets: bool) -> Result<ProfileInfo> {
+        let response = self.client.request(&Request::ProfilesInspect {
+            alias: alias.to_string(),
+            show_secrets,
+        })?;
+        expect!(response, Response::Profile(profile) => profile)
+    }
+
+    /// Start the agent from the daemon, returning its pid.
+    ///
+    /// With `ephemeral_proxy`, a proxy started for the run is stopped when
+    /// the agent exits.
+    pub fn run(
+        &self,
+        alias: &str,
+        args: Vec<String>,
+        env: HashMap<String, String>,
+        ephemeral_proxy: bool,
+    ) -> Result<u32> {
+        let response = self.client.request(&Request::ProfilesRun {
+            alias: alias.to_string(),
+            args,
+            env,
+            ephemeral_proxy,
+        })?;
+        expect!(response, Response::RunStarted { pid } => pid)
+    }
+
+    /// Prepare a run for the caller to spawn itself.
+    ///
+    /// With `ephemeral_proxy`, a proxy started for the run is stopped by
+    /// [`complete`](Self::complete). `cwd` is where the agent runs when the
+    /// profile has no `working_dir` of its own.
+    pub fn prepare(
+        &self,
+        alias: &str,
+        args: Vec<String>,
+        env: HashMap<String, String>,
+        ephemeral_proxy: bool,
+        cwd: Option<PathBuf>,
+    ) -> Result<ExecutionContext> {
+        let response = self.client.request(&Request::ProfilesPrepare {
+            alias: alias.to_string(),
+            args,
+            env,
+            ephemeral_proxy,
+            cwd,
+            owner_pid: Some(std::process::id()),
+        })?;
+        expect!(response, Response::ExecutionContext(context) => context)
+    }
+
+    /// Record the outcome of a run started from [`prepare`](Self::prepare).
+    pub fn complete(
+        &self,
+        run_id: &str,
+        started_at: chrono::DateTime<chrono::Utc>,
+        ended_at: chrono::DateTime<chrono::Utc>,
+        exit_code: i32,
+        signal: Option<i32>,
+    ) -> Result<()> {
+        let response = self.client.request(&Request::ProfilesComplete {
+            run_id: run_id.to_string(),
+            started_at,
+            ended_at,
+            exit_code,
+            signal,
+        })?;
+        expect!(response, Response::RunCompleted { .. } => ())
+    }
+
+    /// Drop a run from [`prepare`](Self::prepare) that failed to start.
+    pub fn abandon(&self, run_id: &str) -> Result<String> {
+        success(self.client.request(&Request::ProfilesAbandon {
+            run_id: run_id.to_string(),
+        })?)
+    }
+
+    pub fn delete(&self, alias: &str) -> Result<String> {
+        success(self.client.request(&Request::ProfilesDelete {
+            alias: alias.to_string(),
+        })?)
+    }
+
+    /// Delete every profile using the given agent and/or provider.
+    pub fn delete_matching(
+        &self,
+        agent_id: Option<&str>,
+        provider_id: Option<&str>,
+    ) -> Result<String> {
+        success(self.client.request(&Request::ProfilesDeleteMatching {
+            agent_id: agent_id.map(str::to_string),
+            provider_id: provider_id.map(str::to_string),
+        })?)
+    }
+
+    pub fn rename(&self, old_alias: &str, new_alias: &str) -> Result<String> {
+        success(self.client.request(&Request::ProfilesRename {
+            old_alias: old_alias.to_string(),
+            new_alias: new_alias.to_string(),
+        })?)
+    }
+
+    /// Environment variables a run of the profile would get.
+    pub fn env(&self, alias: &str) -> Result<HashMap<String, String>> {
+        let response = self.client.request(&Request::ProfilesEnv {
+            alias: alias.to_string(),
+        })?;
+        expect!(response, Response::Env(env) => env)
+    }
+
+    pub fn history(&self, alias: &str, limit: usize) -> Result<Vec<RunHistoryEntry>> {
+        let response = self.client.request(&Request::ProfilesHistory {
+            alias: alias.to_string(),
+            limit,
+        })?;
+        expect!(response, Response::RunHistory(entries) => entries)
+    }
+
+    /// Set or, with `command: None`, clear a pre-run or post-run hook.
+    pub fn set_hook(&self, alias: &str, stage: &str, command: Option<&str>) -> Result<String> {
+        success(self.client.request(&Request::ProfilesSetHook {
+            alias: alias.to_string(),
+            stage: stage.to_string(),
+            command: command.map(str::to_string),
+        })?)
+    }
+
+    pub fn set_args(&self, alias: &str, args: Vec<String>) -> Result<String> {
+        success(self.client.request(&Request::ProfilesSetArgs {
+            alias: alias.to_string(),
+            args,
+        })?)
+    }
+
+    pub fn set_quota(
+        &self,
+        alias: &str,
+        daily_usd: Option<f64>,
+        monthly_usd: Option<f64>,
+    ) -> Result<String> {
+        success(self.client.request(&Request::ProfilesSetQuota {
+            alias: alias.to_string(),
+            daily_usd,
+            monthly_usd,
+        })?)
+    }
+
+    /// Set the command template the agent is launched through (`None` clears it).
+    pub fn set_launch_wrapper(&self, alias: &str, wrapper: Option<Vec<String>>) -> Result<String> {
+        success(self.client.request(&Request::ProfilesSetLaunchWrapper {
+            alias: alias.to_string(),
+            wrapper,
+        })?)
+    }
+
+    /// Turn saving every run's output to a log file on or off.
+    pub fn set_capture_logs(&self, alias: &str, enabled: bool) -> Result<String> {
+        success(self.client.request(&Request::ProfilesSetCaptureLogs {
+            alias: alias.to_string(),
+            enabled,
+        })?)
+    }
+
+    /// Store a new API key for a profile.
+    pub fn set_api_key(&self, alias: &str, api_key: &str) -> Result<String> {
+        success(self.client.request(&Request::ProfilesSetApiKey {
+            alias: alias.to_string(),
+            api_key: api_key.to_string(),
+        })?)
+    }
+
+    /// Find profiles with missing agents, providers or files, cleaning up
+    /// with `fix`. Orphaned homes are only deleted with `remove_homes` too.
+    pub fn doctor(&self, fix: bool, remove_homes: bool) -> Result<ProfileDoctorReport> {
+        let response = self
+            .client
+            .request(&Request::ProfilesDoctor { fix, remove_homes })?;
+        expect!(response, Response::ProfileDoctor(report) => report)
+    }
+}
+
+/// Requests about profile aliases. See [`DaemonClient::aliases`].
+pub struct Aliases<'a> {
+    client: &'a DaemonClient,
+}
+
+impl Aliases<'_> {
+    pub fn install(&self, alias: &str, bin_dir: Option<PathBuf>) -> Result<String> {
+        success(self.client.request(&Request::AliasesInstall {
+            alias: alias.to_string(),
+            bin_dir,
+        })?)
+    }
+
+    pub fn uninstall(&self, alias: &str) -> Result<String> {
+        success(self.client.request(&Request::AliasesUninstall {
+            alias: alias.to_string(),
+        })?)
+    }
+}
+
+/// Requests about the registry. See [`DaemonClient::registry`].
+pub struct Registry<'a> {
+    client: &'a DaemonClient,
+}
+
+impl Registry<'_> {
+    /// Sync the registry. Uses the client's long-request timeout.
+    pub fn sync(&self, force: bool, offline: bool) -> Result<RegistryStatus> {
+        let response = self
+            .client
+            .request_long(&Request::RegistrySync { force, offline })?;
+        expect!(response, Response::RegistryStatus(status) => status)
+    }
+
+    pub fn pin(&self, ref_: &str) -> Result<String> {
+        success(self.client.request(&Request::RegistryPin {
+            ref_: ref_.to_string(),
+        })?)
+    }
+
+    pub fn inspect(&self) -> Result<RegistryStatus> {
+        let response = self.client.request(&Request::RegistryInspect)?;
+        expect!(response, Response::RegistryStatus(status) => status)
+    }
+}
+
+/// Requests about usage. See [`DaemonClient::usage`].
+pub struct Usage<'a> {
+    client: &'a DaemonClient,
+}
+
+impl Usage<'_> {
+    /// Usage summary for a period, optionally filtered.
+    pub fn summary(
+        &self,
+        period: Option<UsagePeriod>,
+        profile: Option<&str>,
+        model: Option<&str>,
+        agent: Option<AgentType>,
+    ) -> Result<UsageStatsResponse> {
+        let response = self.client.request(&Request::Usage {
+            period,
+            profile: profile.map(str::to_string),
+            model: model.map(str::to_string),
+            agent,
+        })?;
+        expect!(response, Response::Usage(usage) => *usage)
+    }
+
+    /// One page of individual usage records.
+    pub fn records(
+        &self,
+        period: Option<UsagePeriod>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<UsageRecordsPage> {
+        let response = self.client.request(&Request::UsageRecords {
+            period,
+            offset,
+            limit,
+        })?;
+        expect!(response, Response::UsageRecords(page) => page)
+    }
+
+    /// Import Claude Code usage history. Uses the client's long-request timeout.
+    pub fn import_claude(&self, claude_dir: Option<PathBuf>) -> Result<String> {
+        success(
+            self.client
+                .request_long(&Request::UsageImportClaude { claude_dir })?,
+        )
+    }
+
+    /// Import externally computed usage rows. Uses the client's long-request
+    /// timeout.
+    pub fn import(&self, format: UsageImportFormat, content: String) -> Result<String> {
+        success(
+            self.client
+                .request_long(&Request::UsageImport { format, content })?,
+        )
+    }
+
+    pub fn budget(&self) -> Result<Vec<BudgetStatus>> {
+        let response = self.client.request(&Request::UsageBudget)?;
+        expect!(response, Response::UsageBudget(statuses) => statuses)
+    }
+
+    /// Delete recorded usage. With neither `telemetry` nor `cache`, both go.
+    pub fn reset(
+        &self,
+        telemetry: bool,
+        cache: bool,
+        before: Option<chrono::NaiveDate>,
+    ) -> Result<String> {
+        success(self.client.request(&Request::UsageReset {
+            telemetry,
+            cache,
+            before,
+        })?)
+    }
+}
+
+/// Requests about hooks. See [`DaemonClient::hooks`].
+pub struct Hooks<'a> {
+    client: &'a DaemonClient,
+}
+
+impl Hooks<'_> {
+    /// Add a command hook. Commands that look unsafe (see
+    /// [`lint_command`](ringlet_core::lint_command)) are refused unless `force` is set.
+    pub fn add(
+        &self,
+        alias: &str,
+        event: &str,
+        matcher: &str,
+        command: &str,
+        force: bool,
+    ) -> Result<String> {
+        success(
+            self.client
+                .request(&self.add_request(alias, event, matcher, command, false, force))?,
+        )
+    }
+
+    /// Show what [`add`](Self::add) would save without saving it.
+    pub fn preview_add(
+        &self,
+        alias: &str,
+        event: &str,
+        matcher: &str,
+        command: &str,
+        force: bool,
+    ) -> Result<HooksPreview> {
+        let response = self
+            .client
+            .request(&self.add_request(alias, event, matcher, command, true, force))?;
+        expect!(response, Response::HooksPreview { hooks, file, generated } => HooksPreview {
+            hooks,
+            file,
+            generated,
+        })
+    }
+
+    fn add_request(
+        &self,
+        alias: &str,
+        event: &str,
+        matcher: &str,
+        command: &str,
+        dry_run: bool,
+        force: bool,
+    ) -> Request {
+        Request::HooksAdd {
+            alias: alias.to_string(),
+            event: event.to_string(),
+            matcher: matcher.to_string(),
+            command: command.to_string(),
+            dry_run,
+            force,
+        }
+    }
+
+    pub fn list(&self, alias: &str) -> Result<HooksConfig> {
+        let response = self.client.request(&Request::HooksList {
+            alias: alias.to_string(),
+        })?;
+        expect!(response, Response::Hooks(hooks) => hooks)
+    }
+
+    pub fn remove(&self, alias: &str, event: &str, index: usize) -> Result<String> {
+        success(self.client.request(&Request::HooksRemove {
+            alias: alias.to_string(),
+            event: event.to_string(),
+            index,
+        })?)
+    }
+
+    /// Replace a profile's hooks, refusing commands that look unsafe unless
+    /// `force` is set.
+    pub fn import(&self, alias: &str, config: HooksConfig, force: bool) -> Result<String> {
+        success(self.client.request(&Request::HooksImport {
+            alias: alias.to_string(),
+            config,
+            force,
+        })?)
+    }
+
+    /// A profile's hooks in ringlet's own format.
+    pub fn export(&self, alias: &str) -> Result<HooksConfig> {
+        let response = self.client.request(&Request::HooksExport {
+            alias: alias.to_string(),
+            format: None,
+        })?;
+        expect!(response, Response::Hooks(hooks) => hooks)
+    }
+
+    /// A profile's hooks in an agent's native config format, e.g. `claude`.
+    pub fn export_native(&self, alias: &str, format: &str) -> Result<NativeHooks> {
+        let response = self.client.request(&Request::HooksExport {
+            alias: alias.to_string(),
+            format: Some(format.to_string()),
+        })?;
+        expect!(response, Response::HooksNative { file, content } => NativeHooks { file, content })
+    }
+
+    /// Default hooks, keyed by agent.
+    pub fn defaults(&self) -> Result<HashMap<String, HooksConfig>> {
+        let response = self.client.request(&Request::HooksDefaultsList)?;
+        expect!(response, Response::HooksDefaults(defaults) => defaults)
+    }
+
+    pub fn add_default(
+        &self,
+        agent: &str,
+        event: &str,
+        matcher: &str,
+        command: &str,
+        force: bool,
+    ) -> Result<String> {
+        success(self.client.request(&Request::HooksDefaultsAdd {
+            agent: agent.to_string(),
+            event: event.to_string(),
+            matcher: matcher.to_string(),
+            command: command.to_string(),
+            force,
+        })?)
+    }
+
+    /// Opt a profile out of (or back into) its agent's default hooks.
+    pub fn skip_defaults(&self, alias: &str, skip: bool) -> Result<String> {
+        success(self.client.request(&Request::HooksSkipDefaults {
+            alias: alias.to_string(),
+            skip,
+        })?)
+    }
+}
+
+/// Requests about proxies. See [`DaemonClient::proxy`].
+pub struct Proxy<'a> {
+    client: &'a DaemonClient,
+}
+
+impl Proxy<'_> {
+    pub fn enable(&self, alias: &str) -> Result<String> {
+        success(self.client.request(&Request::ProxyEnable {
+            alias: alias.to_string(),
+        })?)
+    }
+
+    pub fn disable(&self, alias: &str) -> Result<String> {
+        success(self.client.request(&Request::ProxyDisable {
+            alias: alias.to_string(),
+        })?)
+    }
+
+    pub fn start(&self, alias: &str) -> Result<String> {
+        success(self.client.request(&Request::ProxyStart {
+            alias: alias.to_string(),
+        })?)
+    }
+
+    pub fn stop(&self, alias: &str) -> Result<String> {
+        success(self.client.request(&Request::ProxyStop {
+            alias: alias.to_string(),
+        })?)
+    }
+
+    pub fn stop_all(&self) -> Result<String> {
+        success(self.client.request(&Request::ProxyStopAll)?)
+    }
+
+    pub fn restart(&self, alias: &str, verify: bool) -> Result<String> {
+        success(self.client.request(&Request::ProxyRestart {
+            alias: alias.to_string(),
+            verify,
+        })?)
+    }
+
+    /// Running proxies, or just the one for `alias`.
+    pub fn status(&self, alias: Option<&str>) -> Result<Vec<ProxyInstanceInfo>> {
+        let response = self.client.request(&Request::ProxyStatus {
+            alias: alias.map(str::to_string),
+        })?;
+        expect!(response, Response::ProxyStatus(instances) => instances)
+    }
+
+    pub fn config(&self, alias: &str) -> Result<ProfileProxyConfig> {
+        let response = self.client.request(&Request::ProxyConfig {
+            alias: alias.to_string(),
+        })?;
+        expect!(response, Response::ProxyConfig(config) => config)
+    }
+
+    /// The last `lines` lines of the proxy log, or all of it.
+    pub fn logs(&self, alias: &str, lines: Option<usize>) -> Result<String> {
+        let response = self.client.request(&Request::ProxyLogs {
+            alias: alias.to_string(),
+            lines,
+        })?;
+        expect!(response, Response::ProxyLogs(logs) => logs)
+    }
+
+    pub fn log_path(&self, alias: &str) -> Result<PathBuf> {
+        let response = self.client.request(&Request::ProxyLogPath {
+            alias: alias.to_string(),
+        })?;
+        expect!(response, Response::ProxyLogPath(path) => path)
+    }
+
+    /// Environment variables that point an agent at the profile's proxy.
+    pub fn env(&self, alias: &str) -> Result<HashMap<String, String>> {
+        let response = self.client.request(&Request::ProxyEnv {
+            alias: alias.to_string(),
+        })?;
+        expect!(response, Response::Env(env) => env)
+    }
+
+    pub fn route_add(&self, alias: &str, rule: RoutingRule) -> Result<String> {
+        success(self.client.request(&Request::ProxyRouteAdd {
+            alias: alias.to_string(),
+            rule,
+        })?)
+    }
+
+    pub fn route_remove(&self, alias: &str, rule_name: &str) -> Result<String> {
+        success(self.client.request(&Request::ProxyRouteRemove {
+            alias: alias.to_string(),
+            rule_name: rule_name.to_string(),
+        })?)
+    }
+
+    pub fn route_list(&self, alias: &str) -> Result<Vec<RoutingRule>> {
+        let response = self.client.request(&Request::ProxyRouteList {
+            alias: alias.to_string(),
+        })?;
+        expect!(response, Response::ProxyRoutes(rules) => rules)
+    }
+
+    /// Route requests for `from_model` to `to_target`.
+    pub fn alias_set(&self, alias: &str, from_model: &str, to_target: &str) -> Result<String> {
+        success(self.client.request(&Request::ProxyAliasSet {
+            alias: alias.to_string(),
+            from_model: from_model.to_string(),
+            to_target: to_target.to_string(),
+        })?)
+    }
+
+    pub fn alias_remove(&self, alias: &str, from_model: &str) -> Result<String> {
+        success(self.client.request(&Request::ProxyAliasRemove {
+            alias: alias.to_string(),
+            from_model: from_model.to_string(),
+        })?)
+    }
+
+    pub fn alias_list(&self, alias: &str) -> Result<HashMap<String, String>> {
+        let response = self.client.request(&Request::ProxyAliasList {
+            alias: alias.to_string(),
+        })?;
+        expect!(response, Response::ProxyAliases(aliases) => aliases)
+    }
+}
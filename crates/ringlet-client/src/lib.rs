@@ -0,0 +1,375 @@
+//! Client library for the ringlet daemon.
+//!
+//! Talks to a running daemon over its nng request socket using the
+//! [`Request`]/[`Response`] protocol from `ringlet-core`, so other Rust
+//! programs can drive ringlet without shelling out to the CLI.
+//!
+//! Requests are grouped by area (`client.profiles()`, `client.proxy()`, ...)
+//! and return typed results, with daemon errors as [`ClientError::Daemon`]:
+//!
+//! ```no_run
+//! use ringlet_client::DaemonClient;
+//! use ringlet_core::RingletPaths;
+//!
+//! let client = DaemonClient::connect(&RingletPaths::default())?;
+//! for profile in client.profiles().list(None, None)? {
+//!     println!("{} ({})", profile.alias, profile.agent_id);
+//! }
+//! # Ok::<(), ringlet_client::ClientError>(())
+//! ```
+//!
+//! [`DaemonClient::request`] sends a raw [`Request`] for anything else.
+//!
+//! [`DaemonClient::connect`] expects the daemon to be running already; use
+//! [`DaemonClient::start`] to launch one from a `ringlet` binary.
+
+mod api;
+
+use nng::options::Options;
+use nng::options::protocol::pubsub::Subscribe;
+use nng::{Protocol, Socket};
+use ringlet_core::RingletPaths;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use thiserror::Error;
+use tracing::debug;
+
+pub use api::{
+    Agents, Aliases, Hooks, HooksPreview, NativeHooks, Profiles, Providers, Proxy, Registry, Usage,
+};
+pub use ringlet_core::{DaemonEndpoint, Event, PROTOCOL_VERSION, Request, Response};
+
+/// How long to wait for the daemon to answer a request.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long [`DaemonClient::start`] waits for a new daemon to accept connections.
+const START_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Errors from talking to the daemon.
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("Failed to connect to {url}: {source}")]
+    Connect {
+        url: String,
+        #[source]
+        source: nng::Error,
+    },
+
+    #[error("Failed to start the ringlet daemon: {0}")]
+    Start(String),
+
+    #[error("Timed out after {}s waiting for the daemon", .0.as_secs())]
+    TimedOut(Duration),
+
+    #[error(
+        "The running ringlet daemon speaks protocol {}, but this client speaks v{}",
+        describe_version(*.daemon),
+        PROTOCOL_VERSION
+    )]
+    ProtocolMismatch {
+        /// Version the daemon reported, or `None` for daemons from before
+        /// protocol versioning.
+        daemon: Option<u32>,
+    },
+
+    /// The daemon answered with `Response::Error`.
+    #[error("{message}")]
+    Daemon { code: i32, message: String },
+
+    #[error("Unexpected response from daemon: {0}")]
+    UnexpectedResponse(String),
+
+    #[error("IPC error: {0}")]
+    Nng(#[from] nng::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+fn describe_version(version: Option<u32>) -> String {
+    version
+        .map(|v| format!("v{}", v))
+        .unwrap_or_else(|| "an older version".to_string())
+}
+
+/// Result type for daemon client operations.
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+/// Where the daemon listens: its endpoint file, or the default IPC socket.
+pub fn daemon_endpoint(paths: &RingletPaths) -> DaemonEndpoint {
+    std::fs::read_to_string(paths.daemon_endpoint())
+        .ok()
+        .and_then(|contents| DaemonEndpoint::parse(&contents))
+        .unwrap_or_else(|| DaemonEndpoint::Ipc(paths.ipc_socket()))
+}
+
+/// Connect a subscriber to the event stream at `endpoint`.
+///
+/// Each message on the returned socket is a JSON-encoded [`Event`].
+pub fn subscribe_events(endpoint: &DaemonEndpoint) -> Result<Socket> {
+    let socket = Socket::new(Protocol::Sub0)?;
+    socket.set_opt::<Subscribe>(Vec::new())?;
+    let url = endpoint.events_url();
+    socket
+        .dial(&url)
+        .map_err(|source| ClientError::Connect { url, source })?;
+    Ok(socket)
+}
+
+/// Client for the ringlet daemon.
+pub struct DaemonClient {
+    socket: Socket,
+    endpoint: DaemonEndpoint,
+    timeout: Option<Duration>,
+    long_timeout: Option<Duration>,
+}
+
+impl DaemonClient {
+    /// Connect to the running daemon and check that it speaks the same
+    /// protocol version.
+    pub fn connect(paths: &RingletPaths) -> Result<Self> {
+        let client = Self::connect_unchecked(paths)?;
+        client.check_protocol()?;
+        Ok(client)
+    }
+
+    /// Connect to the running daemon without the protocol check.
+    pub fn connect_unchecked(paths: &RingletPaths) -> Result<Self> {
+        Self::connect_to(&daemon_endpoint(paths))
+    }
+
+    /// Connect to a daemon listening at `endpoint`, without the protocol check.
+    pub fn connect_to(endpoint: &DaemonEndpoint) -> Result<Self> {
+        let socket = Socket::new(Protocol::Req0)?;
+
+        let url = endpoint.url();
+        socket
+            .dial(&url)
+            .map_err(|source| ClientError::Connect { url, source })?;
+
+        socket.set_opt::<nng::options::SendTimeout>(Some(DEFAULT_REQUEST_TIMEOUT))?;
+
+        Ok(Self {
+            socket,
+            endpoint: endpoint.clone(),
+            timeout: Some(DEFAULT_REQUEST_TIMEOUT),
+            long_timeout: None,
+        })
+    }
+
+    /// Start a daemon by running `<ringlet> daemon` and connect to it once it
+    /// is listening, without the protocol check.
+    pub fn start(paths: &RingletPaths, ringlet: &Path) -> Result<Self> {
+        debug!("Starting daemon: {} daemon", ringlet.display());
+
+        paths.ensure_dirs()?;
+        Command::new(ringlet)
+            .args(["daemon"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| ClientError::Start(e.to_string()))?;
+
+        let attempts = START_TIMEOUT.as_millis() / 100;
+        for i in 0..attempts {
+            std::thread::sleep(Duration::from_millis(100));
+            // The daemon records where it listens once it is bound
+            if let Ok(client) = Self::connect_unchecked(paths) {
+                debug!("Connected to daemon after {} attempts", i + 1);
+                return Ok(client);
+            }
+        }
+
+        Err(ClientError::Start(format!(
+            "not listening after {}s",
+            START_TIMEOUT.as_secs()
+        )))
+    }
+
+    /// The endpoint this client is connected to.
+    pub fn endpoint(&self) -> &DaemonEndpoint {
+        &self.endpoint
+    }
+
+    /// Set how long [`request`](Self::request) waits for an answer. `None`
+    /// waits forever.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Set how long [`request_long`](Self::request_long) waits for an
+    /// answer. Defaults to `None`, waiting forever.
+    pub fn set_long_timeout(&mut self, timeout: Option<Duration>) {
+        self.long_timeout = timeout;
+    }
+
+    /// Send a request and receive a response, giving up after the client's timeout.
+    pub fn request(&self, request: &Request) -> Result<Response> {
+        self.request_within(request, self.timeout)
+    }
+
+    /// Send a request that may legitimately take a long time (e.g. a registry
+    /// sync), giving up after the client's long-request timeout.
+    pub fn request_long(&self, request: &Request) -> Result<Response> {
+        self.request_within(request, self.long_timeout)
+    }
+
+    /// Send a request and receive a response, giving up after `timeout`.
+    pub fn request_within(&self, request: &Request, timeout: Option<Duration>) -> Result<Response> {
+        let json = serde_json::to_vec(request)?;
+        let framed = self.endpoint.frame_request(&json);
+
+        self.socket.set_opt::<nng::options::RecvTimeout>(timeout)?;
+        self.socket
+            .send(nng::Message::from(&framed[..]))
+            .map_err(|(_, e)| e)?;
+
+        let reply = match self.socket.recv() {
+            Ok(msg) => msg,
+            Err(nng::Error::TimedOut) => {
+                return Err(ClientError::TimedOut(timeout.unwrap_or_default()));
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(serde_json::from_slice(&reply)?)
+    }
+
+    /// Check if the daemon is answering.
+    pub fn ping(&self) -> bool {
+        let ping = Request::Ping {
+            protocol_version: Some(PROTOCOL_VERSION),
+        };
+        matches!(self.request(&ping), Ok(Response::Pong { .. }))
+    }
+
+    /// Refuse to talk to a daemon that speaks a different protocol version.
+    pub fn check_protocol(&self) -> Result<()> {
+        let ping = Request::Ping {
+            protocol_version: Some(PROTOCOL_VERSION),
+        };
+        let reply = match self.request(&ping) {
+            Ok(reply) => Some(reply),
+            // Daemons from before protocol versioning answer with a bare `pong`
+            Err(ClientError::Json(_)) => None,
+            Err(e) => return Err(e),
+        };
+
+        let daemon = match reply {
+            Some(Response::Pong { protocol_version }) => Some(protocol_version),
+            _ => None,
+        };
+        if daemon == Some(PROTOCOL_VERSION) {
+            Ok(())
+        } else {
+            Err(ClientError::ProtocolMismatch { daemon })
+        }
+    }
+
+    /// Subscribe to the daemon's event stream.
+    pub fn subscribe_events(&self) -> Result<Socket> {
+        subscribe_events(&self.endpoint)
+    }
+
+    /// Ask the daemon to shut down.
+    pub fn shutdown(&self) -> Result<()> {
+        self.request(&Request::Shutdown)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::JoinHandle;
+
+    /// Answer one request per entry in `replies` on a temporary IPC socket.
+    fn fake_daemon(replies: Vec<String>) -> (tempfile::TempDir, DaemonEndpoint, JoinHandle<()>) {
+        let dir = tempfile::tempdir().unwrap();
+        let endpoint = DaemonEndpoint::Ipc(dir.path().join("ringletd.sock"));
+        let socket = Socket::new(Protocol::Rep0).unwrap();
+        socket.listen(&endpoint.url()).unwrap();
+
+        let handle = std::thread::spawn(move || {
+            for reply in replies {
+                socket.recv().unwrap();
+                socket.send(nng::Message::from(reply.as_bytes())).unwrap();
+            }
+        });
+        (dir, endpoint, handle)
+    }
+
+    #[test]
+    fn test_request_round_trip() {
+        let (_dir, endpoint, daemon) = fake_daemon(vec![
+            format!(
+                r#"{{"type":"pong","data":{{"protocol_version":{}}}}}"#,
+                PROTOCOL_VERSION
+            ),
+            r#"{"type":"success","data":{"message":"Shutting down"}}"#.to_string(),
+        ]);
+        let client = DaemonClient::connect_to(&endpoint).unwrap();
+
+        client.check_protocol().unwrap();
+        assert!(matches!(
+            client.request(&Request::Shutdown).unwrap(),
+            Response::Success { .. }
+        ));
+        daemon.join().unwrap();
+    }
+
+    #[test]
+    fn test_protocol_mismatch() {
+        let (_dir, endpoint, daemon) = fake_daemon(vec![
+            r#"{"type":"pong","data":{"protocol_version":99}}"#.to_string(),
+            r#"{"type":"pong"}"#.to_string(),
+        ]);
+        let client = DaemonClient::connect_to(&endpoint).unwrap();
+
+        assert!(matches!(
+            client.check_protocol(),
+            Err(ClientError::ProtocolMismatch { daemon: Some(99) })
+        ));
+        assert!(matches!(
+            client.check_protocol(),
+            Err(ClientError::ProtocolMismatch { daemon: None })
+        ));
+        daemon.join().unwrap();
+    }
+
+    #[test]
+    fn test_typed_responses() {
+        let (_dir, endpoint, daemon) = fake_daemon(vec![
+            r#"{"type":"profile_names","data":["work","home"]}"#.to_string(),
+            r#"{"type":"error","data":{"code":1001,"message":"Profile not found: nope"}}"#
+                .to_string(),
+            r#"{"type":"success","data":{"message":"Deleted"}}"#.to_string(),
+        ]);
+        let client = DaemonClient::connect_to(&endpoint).unwrap();
+
+        assert_eq!(client.profiles().names().unwrap(), vec!["work", "home"]);
+        let err = client.profiles().inspect("nope", false).unwrap_err();
+        assert!(matches!(err, ClientError::Daemon { code: 1001, .. }));
+        assert_eq!(err.to_string(), "Profile not found: nope");
+        assert!(matches!(
+            client.proxy().status(None),
+            Err(ClientError::UnexpectedResponse(kind)) if kind == "success"
+        ));
+        daemon.join().unwrap();
+    }
+
+    #[test]
+    fn test_connect_without_daemon() {
+        let dir = tempfile::tempdir().unwrap();
+        let endpoint = DaemonEndpoint::Ipc(dir.path().join("missing.sock"));
+        assert!(matches!(
+            DaemonClient::connect_to(&endpoint),
+            Err(ClientError::Connect { .. })
+        ));
+    }
+}
@@ -1,5 +1,6 @@
 //! Agent manifest types.
 
+use crate::provider::{ProviderManifest, ProviderType};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -161,6 +162,27 @@ pub struct AgentInfo {
 
     /// Last used timestamp.
     pub last_used: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Providers this agent can be configured with.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compatible_providers: Vec<CompatibleProvider>,
+}
+
+/// A provider that an agent can use, as reported by agent inspection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompatibleProvider {
+    /// Provider ID (valid `--provider` value).
+    pub id: String,
+
+    /// Provider name.
+    pub name: String,
+
+    /// Provider type.
+    pub provider_type: ProviderType,
+
+    /// Extra requirements for using this provider with the agent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
 }
 
 /// Compatibility types for provider matching.
@@ -199,6 +221,39 @@ impl AgentManifest {
             ],
         }
     }
+
+    /// Check whether a provider can be used with this agent.
+    /// Self-authenticating providers work with every agent.
+    pub fn supports_provider(&self, provider: &ProviderManifest) -> bool {
+        provider.provider_type.is_self_auth()
+            || provider
+                .provider_type
+                .to_compatibility()
+                .is_some_and(|compat| self.supported_provider_types().contains(&compat))
+    }
+
+    /// List the providers compatible with this agent, sorted by ID.
+    pub fn compatible_providers<'a>(
+        &self,
+        providers: impl IntoIterator<Item = &'a ProviderManifest>,
+    ) -> Vec<CompatibleProvider> {
+        let mut compatible: Vec<CompatibleProvider> = providers
+            .into_iter()
+            .filter(|provider| self.supports_provider(provider))
+            .map(|provider| CompatibleProvider {
+                id: provider.id.clone(),
+                name: provider.name.clone(),
+                provider_type: provider.provider_type.clone(),
+                notes: provider
+                    .provider_type
+                    .compatibility_note()
+                    .map(String::from),
+            })
+            .collect();
+
+        compatible.sort_by(|a, b| a.id.cmp(&b.id));
+        compatible
+    }
 }
 
 #[cfg(test)]
@@ -236,4 +291,54 @@ mod tests {
         assert_eq!(manifest.name, "Claude Code");
         assert_eq!(manifest.profile.strategy, ProfileStrategy::HomeWrapper);
     }
+
+    #[test]
+    fn test_compatible_providers() {
+        let agent: AgentManifest = toml::from_str(
+            r#"
+            id = "codex"
+            name = "Codex CLI"
+            binary = "codex"
+
+            [detect]
+            [profile]
+            strategy = "home-wrapper"
+            source_home = "~/.codex-profiles/{alias}"
+            script = "codex.rhai"
+
+            [models]
+        "#,
+        )
+        .unwrap();
+
+        let provider = |id: &str, provider_type: &str| -> ProviderManifest {
+            toml::from_str(&format!(
+                r#"
+                id = "{id}"
+                name = "{id}"
+                type = "{provider_type}"
+
+                [endpoints]
+                default = "https://example.com"
+
+                [auth]
+                env_key = "KEY"
+                prompt = "Key"
+
+                [models]
+            "#
+            ))
+            .unwrap()
+        };
+        let providers = [
+            provider("zai-openai", "openai-compatible"),
+            provider("anthropic", "anthropic"),
+            provider("self", "self"),
+        ];
+
+        let compatible = agent.compatible_providers(&providers);
+        let ids: Vec<_> = compatible.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["self", "zai-openai"]);
+        assert!(compatible[1].notes.is_some());
+    }
 }
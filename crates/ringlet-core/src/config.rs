@@ -1,13 +1,28 @@
 //! User configuration types.
 
+use crate::hooks::HooksConfig;
+use crate::usage::BudgetPeriod;
 use serde::de::Error as _;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
 
+/// Current `config.toml` format version.
+///
+/// Bump this and add a step to `MIGRATIONS` when a key is renamed, moved or
+/// removed, or when an existing file should pick up a new default explicitly.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Upgrade steps, where `MIGRATIONS[n]` turns a version `n` file into version `n + 1`.
+const MIGRATIONS: [fn(&mut toml::Table); CONFIG_VERSION as usize] = [migrate_v0_to_v1];
+
 /// User configuration from ~/.config/ringlet/config.toml
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserConfig {
+    /// Format version; files written before versioning read as 0.
+    #[serde(default)]
+    pub version: u32,
+
     /// Default settings.
     #[serde(default)]
     pub defaults: DefaultsConfig,
@@ -27,6 +42,44 @@ pub struct UserConfig {
     /// Telemetry settings.
     #[serde(default)]
     pub telemetry: TelemetryConfig,
+
+    /// Usage tracking settings.
+    #[serde(default)]
+    pub usage: UsageConfig,
+
+    /// Remote terminal settings.
+    #[serde(default)]
+    pub terminal: TerminalConfig,
+
+    /// Security settings.
+    #[serde(default)]
+    pub security: SecurityConfig,
+
+    /// Registry sync settings.
+    #[serde(default)]
+    pub registry: RegistryConfig,
+
+    /// Proxy settings.
+    #[serde(default)]
+    pub proxy: ProxyConfig,
+}
+
+impl Default for UserConfig {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            defaults: DefaultsConfig::default(),
+            hooks: HooksPrefs::default(),
+            mcp_servers: McpServersPrefs::default(),
+            daemon: DaemonConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            usage: UsageConfig::default(),
+            terminal: TerminalConfig::default(),
+            security: SecurityConfig::default(),
+            registry: RegistryConfig::default(),
+            proxy: ProxyConfig::default(),
+        }
+    }
 }
 
 /// Default settings.
@@ -53,6 +106,24 @@ pub struct HooksPrefs {
     /// Custom hooks.
     #[serde(default)]
     pub custom: HashMap<String, Vec<CustomHook>>,
+
+    /// Default hooks merged into every profile's generated config, keyed by
+    /// agent id (`*` applies to every agent that supports hooks).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub defaults: HashMap<String, HooksConfig>,
+}
+
+impl HooksPrefs {
+    /// Default hooks for an agent: the `*` defaults followed by the agent's own.
+    pub fn defaults_for(&self, agent_id: &str) -> HooksConfig {
+        let mut hooks = HooksConfig::default();
+        for key in ["*", agent_id] {
+            if let Some(defaults) = self.defaults.get(key) {
+                hooks.merge(defaults);
+            }
+        }
+        hooks
+    }
 }
 
 /// Custom hook definition.
@@ -112,6 +183,15 @@ pub struct DaemonConfig {
     /// TCP port for HTTP API and web UI.
     #[serde(default = "default_http_port")]
     pub http_port: u16,
+
+    /// Expose Prometheus metrics at `/metrics` on the HTTP server.
+    #[serde(default)]
+    pub metrics_enabled: bool,
+
+    /// Extra origins allowed to call the HTTP API cross-origin
+    /// (e.g. `https://dashboard.example.com`). Localhost is always allowed.
+    #[serde(default)]
+    pub cors_allowed_origins: Vec<String>,
 }
 
 impl Default for DaemonConfig {
@@ -119,6 +199,8 @@ impl Default for DaemonConfig {
         Self {
             idle_timeout_secs: default_idle_timeout(),
             http_port: default_http_port(),
+            metrics_enabled: false,
+            cors_allowed_origins: Vec::new(),
         }
     }
 }
@@ -152,27 +234,362 @@ impl Default for TelemetryConfig {
     }
 }
 
+/// Remote terminal configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalConfig {
+    /// Maximum terminal sessions created per minute over HTTP (0 disables the limit).
+    #[serde(default = "default_create_rate_per_min")]
+    pub create_rate_per_min: u32,
+
+    /// Bytes of output kept per session and replayed to newly attached clients.
+    #[serde(default = "default_scrollback_bytes")]
+    pub scrollback_bytes: usize,
+}
+
+impl Default for TerminalConfig {
+    fn default() -> Self {
+        Self {
+            create_rate_per_min: default_create_rate_per_min(),
+            scrollback_bytes: default_scrollback_bytes(),
+        }
+    }
+}
+
+fn default_create_rate_per_min() -> u32 {
+    30
+}
+
+fn default_scrollback_bytes() -> usize {
+    1024 * 1024 // 1MB
+}
+
+/// Security configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecurityConfig {
+    /// Where profile API keys are stored.
+    #[serde(default)]
+    pub secret_backend: SecretBackend,
+}
+
+/// Storage backend for profile API keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretBackend {
+    /// OS keychain (macOS Keychain, Secret Service, Windows Credential Manager).
+    /// Falls back to `file` when no keychain is available.
+    #[default]
+    Keychain,
+    /// Owner-only files in the ringlet config directory.
+    File,
+}
+
+/// Registry sync configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryConfig {
+    /// Verify downloads against the registry's `manifest.sha256` before
+    /// replacing the cache.
+    #[serde(default = "default_true")]
+    pub verify: bool,
+
+    /// How long a sync stays fresh; `registry sync` without `--force` skips
+    /// fetching until the cache is older than this.
+    #[serde(default = "default_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+}
+
+impl Default for RegistryConfig {
+    fn default() -> Self {
+        Self {
+            verify: true,
+            cache_ttl_secs: default_cache_ttl_secs(),
+        }
+    }
+}
+
+fn default_cache_ttl_secs() -> u64 {
+    24 * 60 * 60 // 1 day
+}
+
+/// Proxy configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    /// Size at which a proxy's `proxy.log` is rotated.
+    #[serde(default = "default_log_max_bytes")]
+    pub log_max_bytes: u64,
+
+    /// Rotated log files kept alongside `proxy.log` (0 discards on rotation).
+    #[serde(default = "default_log_keep")]
+    pub log_keep: usize,
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        Self {
+            log_max_bytes: default_log_max_bytes(),
+            log_keep: default_log_keep(),
+        }
+    }
+}
+
+fn default_log_max_bytes() -> u64 {
+    10 * 1024 * 1024 // 10MB
+}
+
+fn default_log_keep() -> usize {
+    3
+}
+
+/// Usage tracking configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageConfig {
+    /// Daily spend limit in USD. Alerts fire once per UTC day when crossed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_daily_usd: Option<f64>,
+
+    /// Monthly spend limit in USD. Alerts fire once per UTC month when crossed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_monthly_usd: Option<f64>,
+
+    /// Claude Code data directory (default: `$CLAUDE_CONFIG_DIR` or `~/.claude`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claude_dir: Option<String>,
+
+    /// Codex CLI data directory (default: `$CODEX_HOME` or `~/.codex`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codex_dir: Option<String>,
+
+    /// OpenCode data directory (default: `$OPENCODE_DATA_DIR` or the platform data dir).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opencode_dir: Option<String>,
+
+    /// Model names to price as another model, such as a fine-tune as its base
+    /// model. Keys are names as agents record them; values are pricing table keys.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub model_aliases: HashMap<String, String>,
+}
+
+impl UsageConfig {
+    /// Configured budgets as `(period, limit)` pairs, daily first.
+    pub fn budgets(&self) -> Vec<(BudgetPeriod, f64)> {
+        [
+            (BudgetPeriod::Daily, self.budget_daily_usd),
+            (BudgetPeriod::Monthly, self.budget_monthly_usd),
+        ]
+        .into_iter()
+        .filter_map(|(period, limit)| limit.map(|limit| (period, limit)))
+        .collect()
+    }
+}
+
 fn default_true() -> bool {
     true
 }
 
 impl UserConfig {
     /// Load from a TOML file, returning default if file doesn't exist.
+    ///
+    /// Files from an older version are migrated, and the upgraded file is
+    /// written back after copying the original to `config.toml.v<N>.bak`.
+    /// Secrets the migration drops are left out of the backup too. The rewrite
+    /// happens under the lock [`update`](Self::update) takes.
     pub fn load(path: &Path) -> Result<Self, toml::de::Error> {
-        if path.exists() {
-            let content = std::fs::read_to_string(path)
-                .map_err(|e| toml::de::Error::custom(e.to_string()))?;
-            toml::from_str(&content)
-        } else {
-            Ok(Self::default())
+        Self::load_inner(path, false)
+    }
+
+    /// [`load`](Self::load), with `locked` set when the caller already holds
+    /// the config lock.
+    fn load_inner(path: &Path, locked: bool) -> Result<Self, toml::de::Error> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content =
+            std::fs::read_to_string(path).map_err(|e| toml::de::Error::custom(e.to_string()))?;
+        let original: toml::Table = toml::from_str(&content)?;
+        let mut table = original.clone();
+        let Some(from) = migrate(&mut table) else {
+            return table.try_into();
+        };
+
+        let config: Self = table.try_into()?;
+        if !locked {
+            // Another process may be migrating too; read the file again under
+            // the lock. Without the lock, use the migrated config unsaved.
+            return match lock_config(path) {
+                Ok(_lock) => Self::load_inner(path, true),
+                Err(_) => Ok(config),
+            };
+        }
+
+        // The migrated config is already in memory; if the rewrite fails the
+        // next load simply migrates again
+        let backup = path.with_file_name(format!(
+            "{}.v{}.bak",
+            path.file_name().unwrap_or_default().to_string_lossy(),
+            from
+        ));
+        let mut original = original;
+        remove_secrets(&mut original);
+        if let Ok(backup_content) = toml::to_string_pretty(&original)
+            && std::fs::write(&backup, backup_content).is_ok()
+        {
+            let _ = config.save(path);
         }
+        Ok(config)
     }
 
     /// Save to a TOML file.
+    ///
+    /// The file is replaced in one step, so readers never see it half-written.
     pub fn save(&self, path: &Path) -> Result<(), std::io::Error> {
         let content = toml::to_string_pretty(self)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-        std::fs::write(path, content)
+        let tmp_path = path.with_extension("toml.tmp");
+        std::fs::write(&tmp_path, content)?;
+        std::fs::rename(&tmp_path, path)
+    }
+
+    /// Load the config, apply `f` and save it if `f` succeeds, holding an
+    /// exclusive lock on a sibling lock file throughout.
+    ///
+    /// Both the CLI and the daemon edit the file, so without the lock one of
+    /// them could overwrite a change the other just made.
+    pub fn update<T, E>(path: &Path, f: impl FnOnce(&mut Self) -> Result<T, E>) -> Result<T, E>
+    where
+        E: From<std::io::Error>,
+    {
+        let _lock = lock_config(path)?;
+        let mut config = Self::load_inner(path, true)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        let result = f(&mut config)?;
+        config.save(path)?;
+        Ok(result)
+    }
+
+    /// Look up a setting by dotted key, e.g. `telemetry.enabled`.
+    ///
+    /// Unset optional settings read as `None`.
+    pub fn get(&self, key: &str) -> Option<toml::Value> {
+        let value = toml::Value::try_from(self).ok()?;
+        key.split('.')
+            .try_fold(&value, |value, part| value.get(part))
+            .cloned()
+    }
+
+    /// Set a setting by dotted key, e.g. `telemetry.enabled`.
+    ///
+    /// `value` is read as a boolean or number when it looks like one and as a
+    /// string otherwise. Unknown keys and values of the wrong type are errors.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let parsed = parse_config_value(value);
+        let updated = self
+            .with_value(key, parsed.clone())
+            .or_else(|err| match parsed {
+                // "8080" for a string setting, for example
+                toml::Value::String(_) => Err(err),
+                _ => self.with_value(key, toml::Value::String(value.to_string())),
+            })?;
+
+        // Serde ignores unknown keys, so a typo would otherwise be accepted silently
+        if updated.get(key).is_none() {
+            return Err(format!("Unknown config key '{}'", key));
+        }
+
+        *self = updated;
+        Ok(())
+    }
+
+    fn with_value(&self, key: &str, value: toml::Value) -> Result<Self, String> {
+        let mut root = toml::Value::try_from(self).map_err(|e| e.to_string())?;
+        let mut parts = key.split('.').peekable();
+        let mut table = root
+            .as_table_mut()
+            .ok_or_else(|| "Config is not a table".to_string())?;
+
+        while let Some(part) = parts.next() {
+            if part.is_empty() {
+                return Err(format!("Invalid config key '{}'", key));
+            }
+            if parts.peek().is_none() {
+                table.insert(part.to_string(), value);
+                break;
+            }
+            table = table
+                .entry(part.to_string())
+                .or_insert_with(|| toml::Value::Table(Default::default()))
+                .as_table_mut()
+                .ok_or_else(|| format!("Config key '{}' is not a table", part))?;
+        }
+
+        root.try_into()
+            .map_err(|e: toml::de::Error| format!("Invalid value for '{}': {}", key, e.message()))
+    }
+}
+
+/// Take the exclusive lock on the sibling lock file of the config at `path`,
+/// released when the returned file is dropped.
+fn lock_config(path: &Path) -> std::io::Result<std::fs::File> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let lock = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path.with_extension("lock"))?;
+    lock.lock()?;
+    Ok(lock)
+}
+
+/// Upgrade a raw config to `CONFIG_VERSION`, returning the version it started at.
+///
+/// Returns `None` when no migration was needed. Files from a newer version
+/// are left alone and read as well as serde defaults allow.
+fn migrate(table: &mut toml::Table) -> Option<u32> {
+    let from = match table.get("version") {
+        Some(version) => u32::try_from(version.as_integer()?).ok()?,
+        None => 0,
+    };
+    if from >= CONFIG_VERSION {
+        return None;
+    }
+
+    for step in &MIGRATIONS[from as usize..] {
+        step(table);
+    }
+    table.insert(
+        "version".to_string(),
+        toml::Value::Integer(CONFIG_VERSION.into()),
+    );
+    Some(from)
+}
+
+/// v0 → v1: drop the plaintext `mcp_servers.github_token`, which is no longer
+/// read, so the token doesn't linger on disk.
+fn migrate_v0_to_v1(table: &mut toml::Table) {
+    remove_secrets(table);
+}
+
+/// Drop the plaintext secrets older versions kept in the config, from the
+/// migrated file and from its backup alike.
+fn remove_secrets(table: &mut toml::Table) {
+    if let Some(mcp) = table
+        .get_mut("mcp_servers")
+        .and_then(toml::Value::as_table_mut)
+    {
+        mcp.remove("github_token");
+    }
+}
+
+fn parse_config_value(value: &str) -> toml::Value {
+    if let Ok(flag) = value.parse::<bool>() {
+        toml::Value::Boolean(flag)
+    } else if let Ok(int) = value.parse::<i64>() {
+        toml::Value::Integer(int)
+    } else if let Ok(float) = value.parse::<f64>() {
+        toml::Value::Float(float)
+    } else {
+        toml::Value::String(value.to_string())
     }
 }
 
@@ -185,6 +602,9 @@ mod tests {
         let config = UserConfig::default();
         assert!(!config.hooks.auto_format);
         assert!(config.telemetry.enabled);
+        assert!(config.registry.verify);
+        assert_eq!(config.registry.cache_ttl_secs, 86400);
+        assert_eq!(config.proxy.log_keep, 3);
     }
 
     #[test]
@@ -203,14 +623,203 @@ mod tests {
 
             [daemon]
             idle_timeout_secs = 600
+            cors_allowed_origins = ["https://dashboard.example.com"]
 
             [telemetry]
             enabled = true
+
+            [usage]
+            budget_monthly_usd = 50.0
+
+            [registry]
+            verify = false
+            cache_ttl_secs = 3600
+
+            [proxy]
+            log_max_bytes = 1024
         "#;
 
         let config: UserConfig = toml::from_str(toml).unwrap();
         assert_eq!(config.defaults.provider, Some("anthropic".to_string()));
         assert!(config.hooks.auto_format);
         assert!(config.mcp_servers.filesystem);
+        assert_eq!(
+            config.daemon.cors_allowed_origins,
+            ["https://dashboard.example.com"]
+        );
+        assert_eq!(config.usage.budgets(), vec![(BudgetPeriod::Monthly, 50.0)]);
+        assert!(!config.registry.verify);
+        assert_eq!(config.registry.cache_ttl_secs, 3600);
+        assert_eq!(config.proxy.log_max_bytes, 1024);
+        assert_eq!(config.proxy.log_keep, 3);
+    }
+
+    #[test]
+    fn test_get_and_set_by_key() {
+        let mut config = UserConfig::default();
+        assert_eq!(
+            config.get("telemetry.enabled"),
+            Some(toml::Value::Boolean(true))
+        );
+        assert_eq!(config.get("usage.budget_daily_usd"), None);
+
+        config.set("telemetry.enabled", "false").unwrap();
+        assert!(!config.telemetry.enabled);
+
+        config.set("usage.budget_daily_usd", "5").unwrap();
+        assert_eq!(config.usage.budget_daily_usd, Some(5.0));
+
+        config.set("defaults.provider", "anthropic").unwrap();
+        assert_eq!(config.defaults.provider, Some("anthropic".to_string()));
+
+        assert!(config.set("telemetry.enabled", "maybe").is_err());
+        assert!(config.set("telemetry.enable", "false").is_err());
+        assert!(config.set("telemetry..enabled", "false").is_err());
+        assert!(!config.telemetry.enabled);
+    }
+
+    #[test]
+    fn test_migrate_v0_to_v1() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let v0 = r#"
+            [mcp_servers]
+            filesystem = true
+            github_token = "ghp_secret"
+
+            [daemon]
+            http_port = 9000
+        "#;
+        std::fs::write(&path, v0).unwrap();
+
+        let config = UserConfig::load(&path).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert!(config.mcp_servers.filesystem);
+        assert_eq!(config.daemon.http_port, 9000);
+        assert_eq!(config.daemon.idle_timeout_secs, 300);
+
+        // The backup keeps the original settings but not the token
+        let backup = dir.path().join("config.toml.v0.bak");
+        let backed_up: toml::Table =
+            toml::from_str(&std::fs::read_to_string(&backup).unwrap()).unwrap();
+        assert_eq!(backed_up["daemon"]["http_port"].as_integer(), Some(9000));
+        assert_eq!(backed_up["mcp_servers"]["filesystem"].as_bool(), Some(true));
+        assert!(backed_up.get("version").is_none());
+        assert!(
+            !std::fs::read_to_string(&backup)
+                .unwrap()
+                .contains("ghp_secret")
+        );
+
+        let rewritten = std::fs::read_to_string(&path).unwrap();
+        assert!(rewritten.contains("version = 1"));
+        assert!(!rewritten.contains("github_token"));
+        assert!(rewritten.contains("idle_timeout_secs = 300"));
+
+        // Already current: loads without another backup
+        std::fs::remove_file(&backup).unwrap();
+        UserConfig::load(&path).unwrap();
+        assert!(!backup.exists());
+    }
+
+    #[test]
+    fn test_migration_waits_for_config_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[daemon]\nhttp_port = 9000\n").unwrap();
+
+        let lock = lock_config(&path).unwrap();
+        let loader = {
+            let path = path.clone();
+            std::thread::spawn(move || UserConfig::load(&path).unwrap())
+        };
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("version"));
+
+        drop(lock);
+        assert_eq!(loader.join().unwrap().version, CONFIG_VERSION);
+        assert!(
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .contains("version = 1")
+        );
+    }
+
+    #[test]
+    fn test_newer_config_version_is_not_migrated() {
+        let mut table: toml::Table = toml::from_str("version = 99").unwrap();
+        assert_eq!(migrate(&mut table), None);
+        assert_eq!(table.get("version"), Some(&toml::Value::Integer(99)));
+    }
+
+    #[test]
+    fn test_default_hooks_for_agent() {
+        let toml = r#"
+            [[hooks.defaults."*".PreToolUse]]
+            matcher = "Bash"
+            hooks = [{ type = "command", command = "guard" }]
+
+            [[hooks.defaults.claude.Stop]]
+            matcher = "*"
+            hooks = [{ type = "url", url = "http://localhost:9000" }]
+        "#;
+
+        let config: UserConfig = toml::from_str(toml).unwrap();
+        let claude = config.hooks.defaults_for("claude");
+        assert_eq!(claude.pre_tool_use.len(), 1);
+        assert_eq!(claude.stop.len(), 1);
+
+        let codex = config.hooks.defaults_for("codex");
+        assert_eq!(codex.pre_tool_use.len(), 1);
+        assert!(codex.stop.is_empty());
+
+        let saved = toml::to_string_pretty(&config).unwrap();
+        let reloaded: UserConfig = toml::from_str(&saved).unwrap();
+        assert_eq!(reloaded.hooks.defaults_for("claude"), claude);
+    }
+
+    #[test]
+    fn test_concurrent_updates_are_not_lost() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config").join("config.toml");
+
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let path = &path;
+                scope.spawn(move || {
+                    UserConfig::update(path, |config| {
+                        config
+                            .usage
+                            .model_aliases
+                            .insert(format!("model-{}", i), "gpt-4o".to_string());
+                        Ok::<_, std::io::Error>(())
+                    })
+                    .unwrap();
+                });
+            }
+        });
+
+        let config = UserConfig::load(&path).unwrap();
+        assert_eq!(config.usage.model_aliases.len(), 8);
+        assert!(!path.with_extension("toml.tmp").exists());
+    }
+
+    #[test]
+    fn test_failed_update_leaves_config_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let content = format!(
+            "version = {}\n\n[telemetry]\nenabled = false\n",
+            CONFIG_VERSION
+        );
+        std::fs::write(&path, &content).unwrap();
+
+        let result = UserConfig::update(&path, |config| {
+            config.telemetry.enabled = true;
+            Err::<(), _>(std::io::Error::other("refused"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
     }
 }
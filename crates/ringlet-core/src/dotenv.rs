@@ -0,0 +1,147 @@
+//! Dotenv (`.env`) file parsing.
+
+use std::collections::HashMap;
+
+/// Parse `KEY=VALUE` lines from a dotenv file.
+///
+/// Blank lines and `#` comments are skipped and an `export ` prefix is
+/// allowed. Single-quoted values are taken literally; double-quoted values
+/// understand `\n`, `\t`, `\"` and `\\`. Unquoted values are trimmed and end
+/// at a ` #` comment. Later lines override earlier ones.
+///
+/// Errors name the 1-based line that could not be parsed.
+pub fn parse(content: &str) -> Result<HashMap<String, String>, String> {
+    let mut vars = HashMap::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line_no = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {}: expected KEY=VALUE", line_no));
+        };
+        let key = key.trim();
+        if !is_valid_key(key) {
+            return Err(format!("line {}: invalid variable name '{}'", line_no, key));
+        }
+
+        let value = parse_value(value.trim()).map_err(|e| format!("line {}: {}", line_no, e))?;
+        vars.insert(key.to_string(), value);
+    }
+
+    Ok(vars)
+}
+
+fn is_valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_value(value: &str) -> Result<String, String> {
+    let Some(quote @ ('"' | '\'')) = value.chars().next() else {
+        let unquoted = value.split(" #").next().unwrap_or_default();
+        return Ok(unquoted.trim_end().to_string());
+    };
+
+    let rest = &value[1..];
+    let Some(end) = closing_quote(rest, quote) else {
+        return Err(format!("unterminated {} quote", quote));
+    };
+    let trailing = rest[end + 1..].trim_start();
+    if !(trailing.is_empty() || trailing.starts_with('#')) {
+        return Err(format!(
+            "unexpected text after quoted value: '{}'",
+            trailing
+        ));
+    }
+
+    let inner = &rest[..end];
+    if quote == '\'' {
+        return Ok(inner.to_string());
+    }
+
+    let mut unescaped = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some(other @ ('"' | '\\')) => unescaped.push(other),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    Ok(unescaped)
+}
+
+/// Byte offset of the quote closing a value, skipping `\"` inside double quotes.
+fn closing_quote(rest: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in rest.char_indices() {
+        match c {
+            '\\' if quote == '"' && !escaped => escaped = true,
+            c if c == quote && !escaped => return Some(i),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dotenv() {
+        let content = r#"
+# Project settings
+export API_URL=https://example.com # trailing comment
+EMPTY=
+SINGLE='raw \n $value'
+DOUBLE="line\nnext \"quoted\""
+HASH=abc#def
+SPACED = padded value
+API_URL=https://override.example.com
+"#;
+
+        let vars = parse(content).unwrap();
+        assert_eq!(vars["API_URL"], "https://override.example.com");
+        assert_eq!(vars["EMPTY"], "");
+        assert_eq!(vars["SINGLE"], r"raw \n $value");
+        assert_eq!(vars["DOUBLE"], "line\nnext \"quoted\"");
+        assert_eq!(vars["HASH"], "abc#def");
+        assert_eq!(vars["SPACED"], "padded value");
+        assert_eq!(vars.len(), 6);
+    }
+
+    #[test]
+    fn test_parse_dotenv_errors() {
+        assert_eq!(
+            parse("A=1\nnot a pair\n").unwrap_err(),
+            "line 2: expected KEY=VALUE"
+        );
+        assert_eq!(
+            parse("1KEY=value").unwrap_err(),
+            "line 1: invalid variable name '1KEY'"
+        );
+        assert_eq!(
+            parse("\n\nKEY=\"open").unwrap_err(),
+            "line 3: unterminated \" quote"
+        );
+        assert!(parse("KEY='a' b").is_err());
+    }
+}
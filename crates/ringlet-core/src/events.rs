@@ -1,10 +1,54 @@
 //! Event types for real-time notifications via WebSocket.
 
 use crate::proxy::ProxyStatus;
-use crate::usage::{AgentType, CostBreakdown, TokenUsage};
+use crate::usage::{AgentType, BudgetPeriod, CostBreakdown, TokenUsage};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+/// Topics events are published under, as returned by [`Event::topic`].
+pub const EVENT_TOPICS: &[&str] = &{
+    let mut names = [""; EventTopic::ALL.len()];
+    let mut i = 0;
+    while i < names.len() {
+        names[i] = EventTopic::ALL[i].as_str();
+        i += 1;
+    }
+    names
+};
+
+/// Subscription topic of an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EventTopic {
+    System,
+    Profiles,
+    Proxy,
+    Registry,
+    Usage,
+    Progress,
+}
+
+impl EventTopic {
+    const ALL: [EventTopic; 6] = [
+        EventTopic::System,
+        EventTopic::Profiles,
+        EventTopic::Proxy,
+        EventTopic::Registry,
+        EventTopic::Usage,
+        EventTopic::Progress,
+    ];
+
+    const fn as_str(self) -> &'static str {
+        match self {
+            EventTopic::System => "system",
+            EventTopic::Profiles => "profiles",
+            EventTopic::Proxy => "proxy",
+            EventTopic::Registry => "registry",
+            EventTopic::Usage => "usage",
+            EventTopic::Progress => "progress",
+        }
+    }
+}
+
 /// Events broadcast to WebSocket clients.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data", rename_all = "snake_case")]
@@ -26,7 +70,13 @@ pub enum Event {
     /// A profile run was started.
     ProfileRunStarted { alias: String, pid: u32 },
     /// A profile run completed.
-    ProfileRunCompleted { alias: String, exit_code: i32 },
+    ProfileRunCompleted {
+        alias: String,
+        exit_code: i32,
+        /// Signal that terminated the agent (Unix only).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signal: Option<i32>,
+    },
 
     // Proxy events
     /// A proxy instance was started.
@@ -55,23 +105,63 @@ pub enum Event {
         /// Cost breakdown if available.
         cost: Option<CostBreakdown>,
     },
+    /// Spend crossed a configured budget limit (sent once per budget window).
+    BudgetExceeded {
+        /// Budget window that was exceeded.
+        period: BudgetPeriod,
+        /// Configured limit in USD.
+        limit: f64,
+        /// Spend in the current window, in USD.
+        actual: f64,
+    },
+
+    // Progress events
+    /// Progress of a long-running operation such as a registry sync.
+    Progress {
+        /// Operation reporting progress: "registry_sync" or "usage_import".
+        op: String,
+        /// Steps completed so far.
+        current: u64,
+        /// Total number of steps, if known.
+        total: Option<u64>,
+        /// What the operation is working on.
+        message: Option<String>,
+    },
 }
 
 impl Event {
     /// Get the topic for this event (for subscription filtering).
     pub fn topic(&self) -> &'static str {
-        match self {
-            Event::Connected { .. } | Event::Heartbeat { .. } => "system",
+        let topic = match self {
+            Event::Connected { .. } | Event::Heartbeat { .. } => EventTopic::System,
             Event::ProfileCreated { .. }
             | Event::ProfileDeleted { .. }
             | Event::ProfileRunStarted { .. }
-            | Event::ProfileRunCompleted { .. } => "profiles",
+            | Event::ProfileRunCompleted { .. } => EventTopic::Profiles,
             Event::ProxyStarted { .. }
             | Event::ProxyStopped { .. }
-            | Event::ProxyStatusChanged { .. } => "proxy",
-            Event::RegistrySyncStarted | Event::RegistrySyncCompleted { .. } => "registry",
-            Event::UsageUpdated { .. } => "usage",
-        }
+            | Event::ProxyStatusChanged { .. } => EventTopic::Proxy,
+            Event::RegistrySyncStarted | Event::RegistrySyncCompleted { .. } => {
+                EventTopic::Registry
+            }
+            Event::UsageUpdated { .. } | Event::BudgetExceeded { .. } => EventTopic::Usage,
+            Event::Progress { .. } => EventTopic::Progress,
+        };
+        topic.as_str()
+    }
+
+    /// Whether this event matches a subscription: `*`, a topic, or
+    /// `<topic>:<alias>` for one profile's events.
+    pub fn matches_topic(&self, subscription: &str) -> bool {
+        let topic = self.topic();
+        subscription == "*"
+            || subscription == topic
+            || self.alias().is_some_and(|alias| {
+                subscription
+                    .strip_prefix(topic)
+                    .and_then(|rest| rest.strip_prefix(':'))
+                    == Some(alias)
+            })
     }
 
     /// Get the specific alias if this event is related to a profile/proxy.
@@ -95,7 +185,7 @@ impl Event {
 pub enum ClientMessage {
     /// Subscribe to event topics.
     Subscribe {
-        /// Topics to subscribe to: "profiles", "proxy", "registry", "*" (all)
+        /// Topics to subscribe to: "profiles", "proxy", "registry", "usage", "progress", "*" (all)
         topics: Vec<String>,
     },
     /// Unsubscribe from event topics.
@@ -109,7 +199,17 @@ pub enum ClientMessage {
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ServerMessage {
     /// An event occurred.
-    Event { event: Event },
+    Event {
+        event: Event,
+        /// Broadcast sequence number, for reconnecting with `?since=<seq>`.
+        /// Absent on events generated for this connection alone.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        seq: Option<u64>,
+    },
+    /// Events after the client's `?since` can't be replayed, because they
+    /// were dropped from the buffer or the daemon restarted. The client should
+    /// reload its state and reconnect with `seq` from now on.
+    Resync { seq: u64 },
     /// Response to ping.
     Pong,
     /// Error message.
@@ -118,6 +218,59 @@ pub enum ServerMessage {
 
 impl From<Event> for ServerMessage {
     fn from(event: Event) -> Self {
-        ServerMessage::Event { event }
+        ServerMessage::Event { event, seq: None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_topic() {
+        let event = Event::ProxyStopped {
+            alias: "work".to_string(),
+        };
+        assert!(event.matches_topic("*"));
+        assert!(event.matches_topic("proxy"));
+        assert!(event.matches_topic("proxy:work"));
+        assert!(!event.matches_topic("proxy:home"));
+        assert!(!event.matches_topic("profiles:work"));
+        assert!(!event.matches_topic("usage"));
+    }
+
+    #[test]
+    fn test_event_topics_match_event_topic() {
+        assert_eq!(
+            EVENT_TOPICS,
+            [
+                "system", "profiles", "proxy", "registry", "usage", "progress"
+            ]
+        );
+        let events = [
+            Event::Heartbeat { timestamp: 0 },
+            Event::ProfileCreated {
+                alias: "work".to_string(),
+            },
+            Event::ProxyStopped {
+                alias: "work".to_string(),
+            },
+            Event::RegistrySyncStarted,
+            Event::BudgetExceeded {
+                period: BudgetPeriod::Daily,
+                limit: 5.0,
+                actual: 6.0,
+            },
+            Event::Progress {
+                op: "registry_sync".to_string(),
+                current: 1,
+                total: None,
+                message: None,
+            },
+        ];
+        for event in &events {
+            assert!(EVENT_TOPICS.contains(&event.topic()), "{:?}", event);
+            assert!(event.matches_topic(event.topic()));
+        }
     }
 }
@@ -1,23 +1,50 @@
 //! Shared HTTP API contracts.
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 pub struct ListProfilesQuery {
     pub agent: Option<String>,
+    pub provider: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 pub struct RunRequest {
     #[serde(default)]
     pub args: Vec<String>,
+    /// Stop a proxy started for this run once the agent exits.
+    #[serde(default)]
+    pub ephemeral_proxy: bool,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(tag = "status", rename_all = "snake_case")]
 pub enum RunResponse {
-    Started { pid: u32 },
-    Completed { exit_code: i32 },
+    Started {
+        pid: u32,
+    },
+    Completed {
+        exit_code: i32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signal: Option<i32>,
+        duration_secs: u64,
+    },
+}
+
+/// Payload of the `stdout` and `stderr` events streamed by profile exec.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ExecOutput {
+    pub data: String,
+}
+
+/// Payload of the final `exit` event streamed by profile exec.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ExecExit {
+    pub exit_code: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal: Option<i32>,
+    pub duration_secs: u64,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
@@ -25,6 +52,9 @@ pub struct AddHookRequest {
     pub event: String,
     pub matcher: String,
     pub command: String,
+    /// Save the command even if it looks unsafe.
+    #[serde(default)]
+    pub force: bool,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
@@ -124,6 +154,9 @@ pub struct CreateTerminalSessionRequest {
     pub no_sandbox: bool,
     pub bwrap_flags: Option<Vec<String>>,
     pub sandbox_exec_profile: Option<String>,
+    /// Relaunch the session when the daemon restarts.
+    #[serde(default)]
+    pub persist: bool,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
@@ -146,6 +179,44 @@ pub struct CreateShellRequest {
     pub sandbox_exec_profile: Option<String>,
 }
 
+/// Overall daemon health reported by `/api/health`.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthState {
+    /// All subsystems healthy.
+    Ok,
+    /// Core subsystems healthy; optional ones are not.
+    Degraded,
+    /// At least one core subsystem is unhealthy.
+    Unhealthy,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SubsystemHealth {
+    pub name: String,
+    pub healthy: bool,
+    /// Whether the daemon is unhealthy when this subsystem is.
+    pub core: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RecentError {
+    pub subsystem: String,
+    pub message: String,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct HealthResponse {
+    pub status: HealthState,
+    pub version: String,
+    pub uptime_secs: u64,
+    pub subsystems: Vec<SubsystemHealth>,
+    pub recent_errors: Vec<RecentError>,
+}
+
 const fn default_cols() -> u16 {
     80
 }
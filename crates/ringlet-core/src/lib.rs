@@ -14,6 +14,7 @@
 pub mod agent;
 pub mod binary;
 pub mod config;
+pub mod dotenv;
 pub mod error;
 pub mod events;
 pub mod hooks;
@@ -22,27 +23,40 @@ pub mod paths;
 pub mod profile;
 pub mod provider;
 pub mod proxy;
+pub mod redact;
 pub mod rpc;
 pub mod typescript;
 pub mod usage;
 
-pub use agent::{AgentInfo, AgentManifest, ProviderCompatibility};
+pub use agent::{AgentInfo, AgentManifest, CompatibleProvider, ProviderCompatibility};
 pub use binary::{BinaryConfig, BinaryPaths};
-pub use config::UserConfig;
+pub use config::{ProxyConfig, RegistryConfig, UserConfig};
 pub use error::{Result, RingletError};
-pub use events::{ClientMessage, Event, ServerMessage};
-pub use hooks::{HookAction, HookRule, HooksConfig};
-pub use paths::{RingletPaths, expand_template, expand_tilde, home_dir};
-pub use profile::{Profile, ProfileCreateRequest, ProfileInfo, ProfileMetadata};
-pub use provider::{ProviderInfo, ProviderManifest, ProviderType};
+pub use events::{ClientMessage, EVENT_TOPICS, Event, ServerMessage};
+pub use hooks::{HookAction, HookRule, HooksConfig, lint_command, validate_matcher};
+pub use paths::{
+    DaemonEndpoint, RingletPaths, events_socket, expand_template, expand_tilde, home_dir,
+};
+pub use profile::{
+    Profile, ProfileCreateRequest, ProfileDoctorReport, ProfileInfo, ProfileMetadata,
+    RunHistoryEntry, expand_launch_wrapper, run_log_dir, run_log_path, validate_launch_wrapper,
+};
+pub use provider::{
+    AuthKind, DeviceAuthConfig, LocalProviderRequest, ProviderCheck, ProviderInfo,
+    ProviderManifest, ProviderType,
+};
 pub use proxy::{
     ModelTarget, ProfileProxyConfig, ProxyInstanceInfo, ProxyStatus, RoutingCondition,
     RoutingConfig, RoutingRule, RoutingStrategy,
 };
-pub use rpc::{RegistryStatus, Request, Response, StatsResponse, UsageStatsResponse};
+pub use rpc::{
+    BundleReport, Diagnostics, PROTOCOL_VERSION, RegistryStatus, Request, Response, StatsResponse,
+    UsageStatsResponse,
+};
 pub use usage::{
-    AgentType, AgentUsage, CostBreakdown, DailyUsage, LiteLLMModelPricing, ModelUsage,
-    ProfileUsage, SessionUsage, TokenUsage, UsageAggregates, UsagePeriod, UsageResponse,
+    AgentType, AgentUsage, BudgetPeriod, BudgetStatus, CostBreakdown, DailyUsage,
+    LiteLLMModelPricing, ModelUsage, ProfileUsage, SessionUsage, TokenUsage, UsageAggregates,
+    UsageImportFormat, UsagePeriod, UsageRecord, UsageRecordsPage, UsageResponse,
 };
 
 /// Ringlet version.
@@ -1,7 +1,7 @@
 //! Platform-aware path handling for ringlet.
 
 use directories::ProjectDirs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Provides platform-appropriate paths for ringlet data.
 #[derive(Debug, Clone)]
@@ -41,6 +41,21 @@ impl RingletPaths {
         self.config_dir.join("scripts")
     }
 
+    /// Per-profile run history logs (JSONL).
+    pub fn run_history_dir(&self) -> PathBuf {
+        self.data_dir.join("history")
+    }
+
+    /// Descriptors of terminal sessions relaunched when the daemon restarts.
+    pub fn terminal_sessions_dir(&self) -> PathBuf {
+        self.data_dir.join("terminal-sessions")
+    }
+
+    /// File-backed API key storage, used when no keychain is available.
+    pub fn secrets_dir(&self) -> PathBuf {
+        self.config_dir.join("secrets")
+    }
+
     /// Profiles storage directory.
     pub fn profiles_dir(&self) -> PathBuf {
         self.config_dir.join("profiles")
@@ -76,11 +91,21 @@ impl RingletPaths {
         self.telemetry_dir().join("aggregates.json")
     }
 
+    /// Usage rows imported from external files (JSONL).
+    pub fn imported_usage_log(&self) -> PathBuf {
+        self.telemetry_dir().join("imported-usage.jsonl")
+    }
+
     /// Usage aggregates file (token/cost tracking).
     pub fn usage_aggregates_file(&self) -> PathBuf {
         self.telemetry_dir().join("usage-aggregates.json")
     }
 
+    /// Budget alert state (windows already alerted, alerts not yet shown).
+    pub fn budget_alerts_file(&self) -> PathBuf {
+        self.telemetry_dir().join("budget-alerts.json")
+    }
+
     /// LiteLLM pricing cache file.
     pub fn litellm_pricing_cache(&self) -> PathBuf {
         self.registry_dir().join("litellm-pricing.json")
@@ -96,7 +121,7 @@ impl RingletPaths {
         self.config_dir.join("config.toml")
     }
 
-    /// Daemon endpoint file (stores IPC path).
+    /// Daemon endpoint file (see [`DaemonEndpoint`]).
     pub fn daemon_endpoint(&self) -> PathBuf {
         self.config_dir.join("daemon-endpoint")
     }
@@ -156,6 +181,92 @@ impl Default for RingletPaths {
     }
 }
 
+/// Event stream socket published next to the daemon's IPC socket.
+///
+/// The CLI subscribes here to follow progress of long-running requests.
+pub fn events_socket(ipc_socket: &Path) -> PathBuf {
+    ipc_socket.with_extension("events")
+}
+
+/// Where a running daemon accepts requests, as recorded in its endpoint file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DaemonEndpoint {
+    /// IPC socket path; the event stream sits next to it.
+    Ipc(PathBuf),
+    /// TCP loopback ports, used when the IPC socket can't be created.
+    ///
+    /// Any local user can connect to these, so every request must start with
+    /// `token` (see [`frame_request`](Self::frame_request)).
+    Tcp {
+        port: u16,
+        events_port: u16,
+        token: String,
+    },
+}
+
+impl DaemonEndpoint {
+    /// nng URL for requests.
+    pub fn url(&self) -> String {
+        match self {
+            Self::Ipc(path) => format!("ipc://{}", path.display()),
+            Self::Tcp { port, .. } => tcp_loopback_url(*port),
+        }
+    }
+
+    /// nng URL for the event stream.
+    pub fn events_url(&self) -> String {
+        match self {
+            Self::Ipc(path) => format!("ipc://{}", events_socket(path).display()),
+            Self::Tcp { events_port, .. } => tcp_loopback_url(*events_port),
+        }
+    }
+
+    /// Wire bytes for a serialized request.
+    ///
+    /// TCP requests are prefixed with the endpoint's token and a newline; IPC
+    /// requests are sent as-is, the socket's file permissions guarding them.
+    pub fn frame_request(&self, json: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Ipc(_) => json.to_vec(),
+            Self::Tcp { token, .. } => [token.as_bytes(), b"\n", json].concat(),
+        }
+    }
+
+    /// Parse endpoint file contents.
+    ///
+    /// An IPC endpoint is a bare socket path, as older daemons wrote it; a TCP
+    /// endpoint is the request URL, the event stream URL and the token.
+    pub fn parse(contents: &str) -> Option<Self> {
+        let mut lines = contents.lines().map(str::trim).filter(|l| !l.is_empty());
+        let first = lines.next()?;
+        if !first.starts_with("tcp://") {
+            return Some(Self::Ipc(PathBuf::from(first)));
+        }
+
+        let port = |url: &str| url.strip_prefix("tcp://127.0.0.1:")?.parse().ok();
+        Some(Self::Tcp {
+            port: port(first)?,
+            events_port: port(lines.next()?)?,
+            token: lines.next()?.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for DaemonEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ipc(path) => write!(f, "{}", path.display()),
+            Self::Tcp { token, .. } => {
+                write!(f, "{}\n{}\n{}", self.url(), self.events_url(), token)
+            }
+        }
+    }
+}
+
+fn tcp_loopback_url(port: u16) -> String {
+    format!("tcp://127.0.0.1:{}", port)
+}
+
 /// Expand ~ to home directory in a path string.
 pub fn expand_tilde(path: &str) -> PathBuf {
     if let Some(stripped) = path.strip_prefix("~/") {
@@ -201,4 +312,37 @@ mod tests {
         assert!(s.contains(".claude-profiles"));
         assert!(s.contains("work"));
     }
+
+    #[test]
+    fn test_events_socket() {
+        let path = events_socket(Path::new("/tmp/ringletd-1000.sock"));
+        assert_eq!(path, PathBuf::from("/tmp/ringletd-1000.events"));
+    }
+
+    #[test]
+    fn test_daemon_endpoint_round_trip() {
+        let ipc = DaemonEndpoint::Ipc(PathBuf::from("/tmp/ringletd-1000.sock"));
+        assert_eq!(ipc.to_string(), "/tmp/ringletd-1000.sock");
+        assert_eq!(ipc.events_url(), "ipc:///tmp/ringletd-1000.events");
+        assert_eq!(
+            DaemonEndpoint::parse("/tmp/ringletd-1000.sock\n"),
+            Some(ipc)
+        );
+
+        let tcp = DaemonEndpoint::Tcp {
+            port: 40001,
+            events_port: 40002,
+            token: "secret".to_string(),
+        };
+        assert_eq!(tcp.url(), "tcp://127.0.0.1:40001");
+        assert_eq!(tcp.frame_request(b"{}"), b"secret\n{}");
+        assert_eq!(DaemonEndpoint::parse(&tcp.to_string()), Some(tcp));
+
+        assert_eq!(DaemonEndpoint::parse("tcp://127.0.0.1:40001"), None);
+        assert_eq!(
+            DaemonEndpoint::parse("tcp://127.0.0.1:40001\ntcp://127.0.0.1:40002"),
+            None
+        );
+        assert_eq!(DaemonEndpoint::parse(""), None);
+    }
 }
@@ -1,11 +1,13 @@
 //! Profile types and management.
 
+use crate::error::{Result, RingletError};
 use crate::hooks::HooksConfig;
 use crate::proxy::ProfileProxyConfig;
+use crate::usage::BudgetPeriod;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// A profile binding an agent to a provider with specific configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,7 +31,7 @@ pub struct Profile {
     #[serde(default)]
     pub env: HashMap<String, String>,
 
-    /// Default CLI arguments.
+    /// Default agent arguments, passed before the arguments given at run time.
     #[serde(default)]
     pub args: Vec<String>,
 
@@ -70,6 +72,10 @@ pub struct ProfileMetadata {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub hooks_config: Option<HooksConfig>,
 
+    /// Leave out the default hooks from `hooks.defaults` in config.toml.
+    #[serde(default)]
+    pub skip_default_hooks: bool,
+
     /// Proxy configuration for this profile.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub proxy_config: Option<ProfileProxyConfig>,
@@ -77,6 +83,39 @@ pub struct ProfileMetadata {
     /// Path to installed CLI alias shim (if any).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub alias_path: Option<PathBuf>,
+
+    /// Shell command run before the agent is launched; a failure aborts the run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_run: Option<String>,
+
+    /// Shell command run after the agent exits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_run: Option<String>,
+
+    /// Command template the agent is launched through, e.g.
+    /// `["firejail", "--quiet", "{binary}", "{args}"]`.
+    ///
+    /// See [`expand_launch_wrapper`] for how placeholders are filled in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch_wrapper: Option<Vec<String>>,
+
+    /// When the stored access token expires, for providers issuing
+    /// short-lived tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_expires_at: Option<DateTime<Utc>>,
+
+    /// Spend cap for the current UTC day in USD; runs are refused once
+    /// the profile's tracked spend reaches it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota_daily_usd: Option<f64>,
+
+    /// Spend cap for the current UTC month in USD.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota_monthly_usd: Option<f64>,
+
+    /// Save the agent's output to a log file on every run; see [`run_log_path`].
+    #[serde(default)]
+    pub capture_logs: bool,
 }
 
 /// Summary information about a profile for listings.
@@ -102,6 +141,107 @@ pub struct ProfileInfo {
 
     /// Total runs.
     pub total_runs: u64,
+
+    /// Command run before the agent is launched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_run: Option<String>,
+
+    /// Command run after the agent exits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_run: Option<String>,
+
+    /// Command template the agent is launched through.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch_wrapper: Option<Vec<String>>,
+
+    /// Default agent arguments.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+
+    /// When the stored access token expires.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_expires_at: Option<DateTime<Utc>>,
+
+    /// Daily spend cap in USD.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota_daily_usd: Option<f64>,
+
+    /// Monthly spend cap in USD.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota_monthly_usd: Option<f64>,
+
+    /// Whether every run's output is saved to a log file.
+    #[serde(default)]
+    pub capture_logs: bool,
+
+    /// Profile environment variables.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+}
+
+impl ProfileInfo {
+    /// Mask secret values (API keys, tokens) so the info is safe to display.
+    pub fn redact_secrets(&mut self) {
+        crate::redact::redact_map(&mut self.env);
+    }
+}
+
+/// One completed run of a profile, as recorded in its run history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunHistoryEntry {
+    /// When the agent was launched.
+    pub started_at: DateTime<Utc>,
+
+    /// Arguments passed to `profiles run`.
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    /// Agent exit code (-1 when killed by a signal).
+    pub exit_code: i32,
+
+    /// Signal that terminated the agent (Unix only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal: Option<i32>,
+
+    /// Wall-clock run time.
+    pub duration_secs: u64,
+}
+
+/// Leftovers of deleted profiles found by `profiles doctor`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileDoctorReport {
+    /// Profile home directories no profile points at.
+    #[serde(default)]
+    pub orphaned_homes: Vec<PathBuf>,
+
+    /// Alias shims whose profile no longer exists.
+    #[serde(default)]
+    pub dangling_shims: Vec<PathBuf>,
+
+    /// Aliases of running proxies whose profile no longer exists.
+    #[serde(default)]
+    pub orphaned_proxies: Vec<String>,
+
+    /// Whether the leftovers were cleaned up.
+    #[serde(default)]
+    pub fixed: bool,
+
+    /// Whether the orphaned homes were deleted as part of the clean-up.
+    #[serde(default)]
+    pub homes_removed: bool,
+
+    /// Leftovers that could not be cleaned up, with the reason.
+    #[serde(default)]
+    pub failures: Vec<String>,
+}
+
+impl ProfileDoctorReport {
+    /// Whether nothing was found.
+    pub fn is_clean(&self) -> bool {
+        self.orphaned_homes.is_empty()
+            && self.dangling_shims.is_empty()
+            && self.orphaned_proxies.is_empty()
+    }
 }
 
 /// Request to create a new profile.
@@ -125,6 +265,14 @@ pub struct ProfileCreateRequest {
     /// API key (will be stored in keychain).
     pub api_key: String,
 
+    /// OAuth refresh token issued with `api_key`, stored alongside it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+
+    /// When `api_key` expires, if it is a short-lived token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_expires_at: Option<DateTime<Utc>>,
+
     /// Legacy simple hook names for compatibility-oriented profile creation.
     #[serde(default)]
     pub hooks: Vec<String>,
@@ -133,7 +281,7 @@ pub struct ProfileCreateRequest {
     #[serde(default)]
     pub mcp_servers: Vec<String>,
 
-    /// Extra arguments.
+    /// Default agent arguments, passed before the arguments given at run time.
     #[serde(default)]
     pub args: Vec<String>,
 
@@ -151,6 +299,10 @@ pub struct ProfileCreateRequest {
     /// Skip automatic alias installation.
     #[serde(default)]
     pub no_alias: bool,
+
+    /// Command template to launch the agent through.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch_wrapper: Option<Vec<String>>,
 }
 
 impl Profile {
@@ -175,6 +327,15 @@ impl Profile {
             model: self.model.clone(),
             last_used: self.metadata.last_used,
             total_runs: self.metadata.total_runs,
+            pre_run: self.metadata.pre_run.clone(),
+            post_run: self.metadata.post_run.clone(),
+            launch_wrapper: self.metadata.launch_wrapper.clone(),
+            args: self.args.clone(),
+            token_expires_at: self.metadata.token_expires_at,
+            quota_daily_usd: self.metadata.quota_daily_usd,
+            quota_monthly_usd: self.metadata.quota_monthly_usd,
+            capture_logs: self.metadata.capture_logs,
+            env: self.env.clone(),
         }
     }
 }
@@ -190,8 +351,16 @@ impl ProfileMetadata {
             enabled_hooks: Vec::new(),
             enabled_mcp_servers: Vec::new(),
             hooks_config: None,
+            skip_default_hooks: false,
             proxy_config: None,
             alias_path: None,
+            pre_run: None,
+            post_run: None,
+            launch_wrapper: None,
+            token_expires_at: None,
+            quota_daily_usd: None,
+            quota_monthly_usd: None,
+            capture_logs: false,
         }
     }
 
@@ -205,10 +374,104 @@ impl ProfileMetadata {
             enabled_hooks: Vec::new(),
             enabled_mcp_servers: Vec::new(),
             hooks_config: None,
+            skip_default_hooks: false,
             proxy_config: Some(ProfileProxyConfig::default()),
             alias_path: None,
+            pre_run: None,
+            post_run: None,
+            launch_wrapper: None,
+            token_expires_at: None,
+            quota_daily_usd: None,
+            quota_monthly_usd: None,
+            capture_logs: false,
+        }
+    }
+
+    /// The spend quotas set on this profile.
+    pub fn quotas(&self) -> Vec<(BudgetPeriod, f64)> {
+        [
+            (BudgetPeriod::Daily, self.quota_daily_usd),
+            (BudgetPeriod::Monthly, self.quota_monthly_usd),
+        ]
+        .into_iter()
+        .filter_map(|(period, limit)| limit.map(|limit| (period, limit)))
+        .collect()
+    }
+}
+
+/// Directory of a profile's captured run logs.
+pub fn run_log_dir(profile_home: &Path) -> PathBuf {
+    profile_home.join(".ringlet").join("logs")
+}
+
+/// Log file for the output of a run started at `started_at`.
+///
+/// A random suffix keeps runs started in the same second apart.
+pub fn run_log_path(profile_home: &Path, started_at: DateTime<Utc>) -> PathBuf {
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    run_log_dir(profile_home).join(format!(
+        "run-{}-{}.log",
+        started_at.format("%Y%m%d-%H%M%S"),
+        &suffix[..8]
+    ))
+}
+
+/// Placeholder in a launch wrapper replaced by the agent binary.
+const WRAPPER_BINARY: &str = "{binary}";
+
+/// Placeholder in a launch wrapper replaced by the agent arguments, one
+/// argument per placeholder element.
+const WRAPPER_ARGS: &str = "{args}";
+
+/// Check that a launch wrapper template names a command to run.
+///
+/// The first element must be a program, not the `{args}` placeholder, so the
+/// expanded command is never empty or led by an agent argument.
+pub fn validate_launch_wrapper(template: &[String]) -> Result<()> {
+    match template.first() {
+        None => Err(RingletError::Config(
+            "Launch wrapper must contain at least a command".to_string(),
+        )),
+        Some(first) if first == WRAPPER_ARGS => Err(RingletError::Config(format!(
+            "Launch wrapper must start with a command, not {}",
+            WRAPPER_ARGS
+        ))),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Build the final command from a launch wrapper template.
+///
+/// An element that is exactly `{args}` expands to all agent arguments;
+/// `{binary}` is substituted anywhere inside an element. A template without
+/// either placeholder gets the binary and arguments appended. Returns the
+/// program and its arguments, or an error for a template rejected by
+/// [`validate_launch_wrapper`].
+pub fn expand_launch_wrapper(
+    template: &[String],
+    binary: &str,
+    args: &[String],
+) -> Result<(String, Vec<String>)> {
+    validate_launch_wrapper(template)?;
+    let has_placeholder = template
+        .iter()
+        .any(|part| part.contains(WRAPPER_BINARY) || part == WRAPPER_ARGS);
+
+    let mut command = Vec::new();
+    for part in template {
+        if part == WRAPPER_ARGS {
+            command.extend(args.iter().cloned());
+        } else {
+            command.push(part.replace(WRAPPER_BINARY, binary));
         }
     }
+    if !has_placeholder {
+        command.push(binary.to_string());
+        command.extend(args.iter().cloned());
+    }
+
+    let program = command.remove(0);
+    Ok((program, command))
 }
 
 #[cfg(test)]
@@ -235,4 +498,48 @@ mod tests {
         let parsed: Profile = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.alias, "work-minimax");
     }
+
+    #[test]
+    fn test_expand_launch_wrapper() {
+        let strings = |parts: &[&str]| parts.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        let args = strings(&["--model", "opus"]);
+
+        let (program, rest) = expand_launch_wrapper(
+            &strings(&["nice", "-n", "10", "{binary}", "{args}", "--trailing"]),
+            "claude",
+            &args,
+        )
+        .unwrap();
+        assert_eq!(program, "nice");
+        assert_eq!(
+            rest,
+            strings(&["-n", "10", "claude", "--model", "opus", "--trailing"])
+        );
+
+        let (program, rest) =
+            expand_launch_wrapper(&strings(&["firejail", "--quiet"]), "claude", &args).unwrap();
+        assert_eq!(program, "firejail");
+        assert_eq!(rest, strings(&["--quiet", "claude", "--model", "opus"]));
+
+        let (_, rest) =
+            expand_launch_wrapper(&strings(&["env", "AGENT={binary}"]), "claude", &args).unwrap();
+        assert_eq!(rest, strings(&["AGENT=claude"]));
+    }
+
+    #[test]
+    fn test_launch_wrapper_without_a_command_is_rejected() {
+        let args = vec!["--model".to_string()];
+
+        assert!(expand_launch_wrapper(&[], "claude", &args).is_err());
+        assert!(expand_launch_wrapper(&["{args}".to_string()], "claude", &[]).is_err());
+        assert!(
+            expand_launch_wrapper(
+                &["{args}".to_string(), "{binary}".to_string()],
+                "claude",
+                &args
+            )
+            .is_err()
+        );
+        assert!(validate_launch_wrapper(&["{binary}".to_string(), "{args}".to_string()]).is_ok());
+    }
 }
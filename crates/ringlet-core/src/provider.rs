@@ -28,34 +28,106 @@ pub struct ProviderManifest {
 }
 
 /// Provider API type.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
+///
+/// Types this build doesn't know (e.g. from a newer registry) load as
+/// `Other` and keep their name, so one new provider can't break the list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
 pub enum ProviderType {
     Anthropic,
     AnthropicCompatible,
     Openai,
     OpenaiCompatible,
     /// Agent handles its own authentication.
-    #[serde(rename = "self")]
     SelfAuth,
+    /// A provider type this version of ringlet does not know.
+    Other(String),
 }
 
 impl ProviderType {
     /// Convert to agent compatibility type.
-    pub fn to_compatibility(self) -> ProviderCompatibility {
+    ///
+    /// Unknown types are compatible with no agent.
+    pub fn to_compatibility(&self) -> Option<ProviderCompatibility> {
         match self {
-            Self::Anthropic => ProviderCompatibility::Anthropic,
-            Self::AnthropicCompatible => ProviderCompatibility::AnthropicCompatible,
-            Self::Openai => ProviderCompatibility::OpenAi,
-            Self::OpenaiCompatible => ProviderCompatibility::OpenAiCompatible,
-            Self::SelfAuth => ProviderCompatibility::Anthropic, // Default for self-auth
+            Self::Anthropic => Some(ProviderCompatibility::Anthropic),
+            Self::AnthropicCompatible => Some(ProviderCompatibility::AnthropicCompatible),
+            Self::Openai => Some(ProviderCompatibility::OpenAi),
+            Self::OpenaiCompatible => Some(ProviderCompatibility::OpenAiCompatible),
+            Self::SelfAuth => Some(ProviderCompatibility::Anthropic), // Default for self-auth
+            Self::Other(_) => None,
         }
     }
 
     /// Check if this provider type is self-authenticating.
-    pub fn is_self_auth(self) -> bool {
+    pub fn is_self_auth(&self) -> bool {
         matches!(self, Self::SelfAuth)
     }
+
+    /// URL listing the endpoint's models, used as a cheap authenticated request.
+    ///
+    /// Anthropic-style endpoints are bare hosts; OpenAI-style endpoints already
+    /// include the API version path. Unknown types are assumed OpenAI-style.
+    pub fn models_url(&self, endpoint: &str) -> String {
+        let endpoint = endpoint.trim_end_matches('/');
+        match self {
+            Self::Anthropic | Self::AnthropicCompatible | Self::SelfAuth => {
+                format!("{}/v1/models", endpoint)
+            }
+            Self::Openai | Self::OpenaiCompatible | Self::Other(_) => {
+                format!("{}/models", endpoint)
+            }
+        }
+    }
+
+    /// Short note on what using this provider type requires, if anything notable.
+    pub fn compatibility_note(&self) -> Option<&'static str> {
+        match self {
+            Self::Anthropic | Self::Openai => None,
+            Self::AnthropicCompatible => Some("Anthropic-compatible endpoint required"),
+            Self::OpenaiCompatible => Some("OpenAI-compatible endpoint required"),
+            Self::SelfAuth => Some("Agent handles its own authentication"),
+            Self::Other(_) => Some("Unknown provider type; update ringlet to use it"),
+        }
+    }
+}
+
+impl From<String> for ProviderType {
+    /// Lenient parse used when loading manifests: unknown names become `Other`.
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "anthropic" => Self::Anthropic,
+            "anthropic-compatible" => Self::AnthropicCompatible,
+            "openai" => Self::Openai,
+            "openai-compatible" => Self::OpenaiCompatible,
+            "self" => Self::SelfAuth,
+            _ => Self::Other(s),
+        }
+    }
+}
+
+impl From<ProviderType> for String {
+    fn from(provider_type: ProviderType) -> Self {
+        match provider_type {
+            ProviderType::Other(name) => name,
+            known => known.to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for ProviderType {
+    type Err = String;
+
+    /// Strict parse used for user input: unknown names are rejected.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::from(s.to_string()) {
+            Self::Other(_) => Err(format!(
+                "unknown provider type '{}' (expected anthropic, anthropic-compatible, openai, openai-compatible or self)",
+                s
+            )),
+            known => Ok(known),
+        }
+    }
 }
 
 impl std::fmt::Display for ProviderType {
@@ -66,6 +138,7 @@ impl std::fmt::Display for ProviderType {
             Self::Openai => write!(f, "openai"),
             Self::OpenaiCompatible => write!(f, "openai-compatible"),
             Self::SelfAuth => write!(f, "self"),
+            Self::Other(name) => write!(f, "{}", name),
         }
     }
 }
@@ -73,6 +146,10 @@ impl std::fmt::Display for ProviderType {
 /// Authentication configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthConfig {
+    /// How the credential is obtained.
+    #[serde(default)]
+    pub kind: AuthKind,
+
     /// Environment variable name for the API key.
     pub env_key: String,
 
@@ -82,12 +159,61 @@ pub struct AuthConfig {
     /// Whether authentication is required (defaults to true).
     #[serde(default = "default_auth_required")]
     pub required: bool,
+
+    /// HTTP header carrying the key, for servers that don't use the provider
+    /// type's standard one (e.g. `X-API-Key` instead of `Authorization`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+
+    /// Text placed before the key in `header` (e.g. `"Bearer "`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+
+    /// Device login settings, required when `kind` is `oauth_device`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<DeviceAuthConfig>,
 }
 
 fn default_auth_required() -> bool {
     true
 }
 
+impl AuthConfig {
+    /// Whether profiles keep a credential for this provider: an API key when
+    /// one is required, or the token from a device login.
+    pub fn stores_credential(&self) -> bool {
+        self.required || self.kind == AuthKind::OauthDevice
+    }
+}
+
+/// How a provider's credential is obtained.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthKind {
+    /// A static API key entered by the user.
+    #[default]
+    ApiKey,
+    /// An access token from an OAuth 2.0 device authorization grant (RFC 8628).
+    OauthDevice,
+}
+
+/// OAuth 2.0 device authorization grant settings (`[auth.device]`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceAuthConfig {
+    /// URL that issues the device and user codes.
+    pub device_authorization_url: String,
+
+    /// URL polled for the access token.
+    pub token_url: String,
+
+    /// Public client ID registered with the provider.
+    pub client_id: String,
+
+    /// Space-separated scopes to request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
 /// Available models configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderModels {
@@ -98,6 +224,10 @@ pub struct ProviderModels {
     /// Default model for this provider.
     #[serde(default)]
     pub default: Option<String>,
+
+    /// Models offered by specific endpoints, where they differ from `available`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub endpoints: HashMap<String, Vec<String>>,
 }
 
 /// Endpoints configuration with default selection.
@@ -137,6 +267,108 @@ pub struct ProviderInfo {
 
     /// Authentication prompt message.
     pub auth_prompt: String,
+
+    /// How the credential is obtained.
+    #[serde(default)]
+    pub auth_kind: AuthKind,
+
+    /// Device login settings, for `oauth_device` providers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_auth: Option<DeviceAuthConfig>,
+}
+
+/// Request to register a self-hosted provider (Ollama, vLLM, LM Studio, ...).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalProviderRequest {
+    /// Provider ID.
+    pub id: String,
+
+    /// Base URL of the server's API.
+    pub url: String,
+
+    /// Display name; defaults to the ID.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// API type; `openai-compatible` when `None`.
+    #[serde(default)]
+    pub provider_type: Option<ProviderType>,
+
+    /// Custom auth header name.
+    #[serde(default)]
+    pub auth_header: Option<String>,
+
+    /// Text placed before the key in the auth header.
+    #[serde(default)]
+    pub auth_prefix: Option<String>,
+
+    /// Whether the server needs no API key.
+    #[serde(default)]
+    pub no_auth: bool,
+
+    /// Default model.
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+impl LocalProviderRequest {
+    /// Build the manifest written to `providers.d/`.
+    ///
+    /// The key is read from `<ID>_API_KEY`, with `-` mapped to `_`.
+    pub fn to_manifest(&self) -> ProviderManifest {
+        let name = self.name.clone().unwrap_or_else(|| self.id.clone());
+        ProviderManifest {
+            id: self.id.clone(),
+            name: name.clone(),
+            provider_type: self
+                .provider_type
+                .clone()
+                .unwrap_or(ProviderType::OpenaiCompatible),
+            endpoints: HashMap::from([("default".to_string(), self.url.clone())]),
+            auth: AuthConfig {
+                kind: AuthKind::ApiKey,
+                env_key: format!("{}_API_KEY", self.id.to_uppercase().replace('-', "_")),
+                prompt: format!("Enter your {} API key", name),
+                required: !self.no_auth,
+                header: self.auth_header.clone(),
+                prefix: self.auth_prefix.clone(),
+                device: None,
+            },
+            models: ProviderModels {
+                available: self.model.iter().cloned().collect(),
+                default: self.model.clone(),
+                endpoints: HashMap::new(),
+            },
+        }
+    }
+}
+
+/// Result of checking a provider endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderCheck {
+    /// Provider ID.
+    pub provider_id: String,
+
+    /// Endpoint ID that was checked.
+    pub endpoint_id: String,
+
+    /// URL that was requested.
+    pub url: String,
+
+    /// Whether the endpoint answered at all.
+    pub reachable: bool,
+
+    /// Whether the API key was accepted; `None` when it was not checked.
+    pub auth_ok: Option<bool>,
+
+    /// HTTP status of the response.
+    pub status: Option<u16>,
+
+    /// Round-trip time in milliseconds.
+    pub latency_ms: u64,
+
+    /// Why the endpoint was unreachable or the key was rejected.
+    pub error: Option<String>,
 }
 
 /// Endpoint information.
@@ -150,6 +382,10 @@ pub struct EndpointInfo {
 
     /// Whether this is the default endpoint.
     pub is_default: bool,
+
+    /// Models offered by this endpoint, when it has its own list.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub models: Option<Vec<String>>,
 }
 
 impl ProviderManifest {
@@ -174,6 +410,47 @@ impl ProviderManifest {
         self.get_endpoint(endpoint_id)
     }
 
+    /// Resolve an endpoint ID (or the default) to `(id, url)`, following a
+    /// `default = "<endpoint id>"` indirection.
+    pub fn endpoint_url<'a>(&'a self, id: Option<&'a str>) -> Option<(&'a str, &'a str)> {
+        let id = id.unwrap_or("default");
+        let url = self.get_endpoint(id)?;
+        match self.get_endpoint(url) {
+            Some(target) => Some((url, target)),
+            None => Some((id, url)),
+        }
+    }
+
+    /// Models offered by an endpoint, if it has its own list.
+    ///
+    /// Endpoints without one offer the provider-wide `models.available`.
+    pub fn endpoint_models(&self, endpoint_id: &str) -> Option<&[String]> {
+        self.models.endpoints.get(endpoint_id).map(Vec::as_slice)
+    }
+
+    /// Header name and value that authenticate `key` against this provider.
+    ///
+    /// Uses `auth.header`/`auth.prefix` when set, and otherwise the provider
+    /// type's standard header.
+    pub fn auth_header(&self, key: &str) -> (String, String) {
+        let (header, prefix) = match self.provider_type {
+            ProviderType::Openai | ProviderType::OpenaiCompatible | ProviderType::Other(_) => {
+                ("Authorization", "Bearer ")
+            }
+            _ => ("x-api-key", ""),
+        };
+        match &self.auth.header {
+            Some(header) => (
+                header.clone(),
+                format!("{}{}", self.auth.prefix.as_deref().unwrap_or(""), key),
+            ),
+            None => (
+                header.to_string(),
+                format!("{}{}", self.auth.prefix.as_deref().unwrap_or(prefix), key),
+            ),
+        }
+    }
+
     /// Convert to runtime info.
     pub fn to_info(&self) -> ProviderInfo {
         let default_endpoint = self.default_endpoint().unwrap_or("default").to_string();
@@ -186,18 +463,21 @@ impl ProviderManifest {
                 id: id.clone(),
                 url: url.clone(),
                 is_default: id == &default_endpoint,
+                models: self.endpoint_models(id).map(<[String]>::to_vec),
             })
             .collect();
 
         ProviderInfo {
             id: self.id.clone(),
             name: self.name.clone(),
-            provider_type: self.provider_type,
+            provider_type: self.provider_type.clone(),
             default_model: self.models.default.clone(),
             endpoints,
             default_endpoint,
             auth_required: self.auth.required,
             auth_prompt: self.auth.prompt.clone(),
+            auth_kind: self.auth.kind,
+            device_auth: self.auth.device.clone(),
         }
     }
 }
@@ -225,11 +505,189 @@ mod tests {
             [models]
             available = ["MiniMax-M2.1"]
             default = "MiniMax-M2.1"
+
+            [models.endpoints]
+            china = ["MiniMax-M2.1", "MiniMax-M2"]
         "#;
 
         let manifest: ProviderManifest = toml::from_str(toml).unwrap();
         assert_eq!(manifest.id, "minimax");
         assert_eq!(manifest.provider_type, ProviderType::AnthropicCompatible);
         assert_eq!(manifest.default_endpoint(), Some("international"));
+        assert_eq!(
+            manifest.endpoint_url(None),
+            Some(("international", "https://api.minimax.io/anthropic"))
+        );
+        assert_eq!(
+            manifest.endpoint_url(Some("china")),
+            Some(("china", "https://api.minimaxi.com/anthropic"))
+        );
+        assert_eq!(manifest.endpoint_url(Some("mars")), None);
+        assert_eq!(
+            manifest.endpoint_models("china"),
+            Some(&["MiniMax-M2.1".to_string(), "MiniMax-M2".to_string()][..])
+        );
+        assert_eq!(manifest.endpoint_models("international"), None);
+
+        let info = manifest.to_info();
+        let china = info.endpoints.iter().find(|e| e.id == "china").unwrap();
+        assert_eq!(china.models.as_ref().map(Vec::len), Some(2));
+    }
+
+    #[test]
+    fn test_auth_header() {
+        let toml = r#"
+            id = "vllm"
+            name = "vLLM"
+            type = "openai-compatible"
+
+            [endpoints]
+            default = "http://localhost:8000/v1"
+
+            [auth]
+            env_key = "VLLM_API_KEY"
+            prompt = "Enter your vLLM API key"
+
+            [models]
+        "#;
+        let mut manifest: ProviderManifest = toml::from_str(toml).unwrap();
+        assert_eq!(
+            manifest.auth_header("sk-test"),
+            ("Authorization".to_string(), "Bearer sk-test".to_string())
+        );
+
+        manifest.auth.header = Some("X-API-Key".to_string());
+        assert_eq!(
+            manifest.auth_header("sk-test"),
+            ("X-API-Key".to_string(), "sk-test".to_string())
+        );
+
+        manifest.auth.prefix = Some("Token ".to_string());
+        assert_eq!(
+            manifest.auth_header("sk-test"),
+            ("X-API-Key".to_string(), "Token sk-test".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_device_auth() {
+        let toml = r#"
+            id = "acme"
+            name = "Acme"
+            type = "openai-compatible"
+
+            [endpoints]
+            default = "https://api.acme.dev/v1"
+
+            [auth]
+            kind = "oauth_device"
+            env_key = "ACME_TOKEN"
+            prompt = "Sign in to Acme"
+
+            [auth.device]
+            device_authorization_url = "https://auth.acme.dev/device/code"
+            token_url = "https://auth.acme.dev/token"
+            client_id = "ringlet"
+
+            [models]
+        "#;
+        let manifest: ProviderManifest = toml::from_str(toml).unwrap();
+        assert_eq!(manifest.auth.kind, AuthKind::OauthDevice);
+        let device = manifest.auth.device.as_ref().unwrap();
+        assert_eq!(device.client_id, "ringlet");
+        assert_eq!(device.scope, None);
+
+        let info = manifest.to_info();
+        assert_eq!(info.auth_kind, AuthKind::OauthDevice);
+        assert_eq!(info.device_auth.as_ref(), Some(device));
+
+        // The login token is kept even when no API key is required
+        let mut auth = manifest.auth.clone();
+        auth.required = false;
+        assert!(auth.stores_credential());
+        auth.kind = AuthKind::ApiKey;
+        assert!(!auth.stores_credential());
+    }
+
+    #[test]
+    fn test_provider_type_from_str() {
+        for provider_type in [
+            ProviderType::Anthropic,
+            ProviderType::AnthropicCompatible,
+            ProviderType::Openai,
+            ProviderType::OpenaiCompatible,
+            ProviderType::SelfAuth,
+        ] {
+            assert_eq!(provider_type.to_string().parse(), Ok(provider_type.clone()));
+            let json = serde_json::to_string(&provider_type).unwrap();
+            assert_eq!(json, format!("\"{}\"", provider_type));
+        }
+        assert!("openai-compat".parse::<ProviderType>().is_err());
+    }
+
+    #[test]
+    fn test_unknown_provider_type() {
+        let toml = r#"
+            id = "future"
+            name = "Future"
+            type = "gemini"
+
+            [endpoints]
+            default = "https://api.example.com/v1"
+
+            [auth]
+            env_key = "FUTURE_API_KEY"
+            prompt = "Enter your Future API key"
+
+            [models]
+            available = ["gemini-pro"]
+        "#;
+
+        let manifest: ProviderManifest = toml::from_str(toml).unwrap();
+        let other = ProviderType::Other("gemini".to_string());
+        assert_eq!(manifest.provider_type, other);
+        assert_eq!(manifest.provider_type.to_string(), "gemini");
+        assert_eq!(manifest.provider_type.to_compatibility(), None);
+        assert!("gemini".parse::<ProviderType>().is_err());
+        assert_eq!(serde_json::to_string(&other).unwrap(), "\"gemini\"");
+    }
+
+    #[test]
+    fn test_local_provider_manifest() {
+        let request = LocalProviderRequest {
+            id: "lm-studio".to_string(),
+            url: "http://localhost:1234/v1".to_string(),
+            name: None,
+            provider_type: None,
+            auth_header: Some("X-API-Key".to_string()),
+            auth_prefix: None,
+            no_auth: false,
+            model: Some("qwen2.5-coder".to_string()),
+        };
+
+        let manifest = request.to_manifest();
+        assert_eq!(manifest.provider_type, ProviderType::OpenaiCompatible);
+        assert_eq!(manifest.auth.env_key, "LM_STUDIO_API_KEY");
+        assert_eq!(
+            manifest.endpoint_url(None),
+            Some(("default", "http://localhost:1234/v1"))
+        );
+
+        // Round-trips through the providers.d format
+        let parsed = ProviderManifest::from_toml(&toml::to_string(&manifest).unwrap()).unwrap();
+        assert_eq!(parsed.auth.header.as_deref(), Some("X-API-Key"));
+        assert_eq!(parsed.models.default.as_deref(), Some("qwen2.5-coder"));
+    }
+
+    #[test]
+    fn test_models_url() {
+        assert_eq!(
+            ProviderType::Anthropic.models_url("https://api.anthropic.com/"),
+            "https://api.anthropic.com/v1/models"
+        );
+        assert_eq!(
+            ProviderType::OpenaiCompatible.models_url("https://openrouter.ai/api/v1"),
+            "https://openrouter.ai/api/v1/models"
+        );
     }
 }
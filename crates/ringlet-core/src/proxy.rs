@@ -288,6 +288,9 @@ pub struct ProxyInstanceInfo {
     /// Port the proxy is listening on.
     pub port: u16,
 
+    /// Base URL clients should point at.
+    pub url: String,
+
     /// Process ID.
     pub pid: u32,
 
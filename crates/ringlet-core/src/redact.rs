@@ -0,0 +1,65 @@
+//! Redaction of secret values in user-facing output.
+
+use std::collections::HashMap;
+
+/// Replacement shown in place of a redacted value.
+pub const REDACTED: &str = "****";
+
+/// Name fragments that mark a field, header, or environment variable as secret.
+///
+/// Matching is case-insensitive and treats `-` like `_`, so `x-api-key`
+/// and `ANTHROPIC_API_KEY` are both caught by `API_KEY`.
+pub const SECRET_KEY_PATTERNS: &[&str] = &[
+    "API_KEY",
+    "APIKEY",
+    "SECRET",
+    "TOKEN",
+    "PASSWORD",
+    "CREDENTIAL",
+    "AUTHORIZATION",
+    "PRIVATE_KEY",
+];
+
+/// Check whether a field or variable name refers to a secret.
+pub fn is_secret_key(key: &str) -> bool {
+    let normalized = key.to_uppercase().replace('-', "_");
+    SECRET_KEY_PATTERNS
+        .iter()
+        .any(|pattern| normalized.contains(pattern))
+}
+
+/// Replace the values of secret entries in a map with [`REDACTED`].
+pub fn redact_map(map: &mut HashMap<String, String>) {
+    for (key, value) in map.iter_mut() {
+        if is_secret_key(key) {
+            *value = REDACTED.to_string();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_secret_key() {
+        assert!(is_secret_key("ANTHROPIC_API_KEY"));
+        assert!(is_secret_key("api_key"));
+        assert!(is_secret_key("x-api-key"));
+        assert!(is_secret_key("Authorization"));
+        assert!(is_secret_key("GITHUB_TOKEN"));
+        assert!(!is_secret_key("ANTHROPIC_BASE_URL"));
+        assert!(!is_secret_key("_RINGLET_KEYCHAIN_KEY"));
+    }
+
+    #[test]
+    fn test_redact_map() {
+        let mut env = HashMap::from([
+            ("OPENAI_API_KEY".to_string(), "sk-live".to_string()),
+            ("MODEL".to_string(), "gpt-5".to_string()),
+        ]);
+        redact_map(&mut env);
+        assert_eq!(env["OPENAI_API_KEY"], REDACTED);
+        assert_eq!(env["MODEL"], "gpt-5");
+    }
+}
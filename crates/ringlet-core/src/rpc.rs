@@ -2,14 +2,24 @@
 
 use crate::agent::AgentInfo;
 use crate::hooks::HooksConfig;
-use crate::profile::{ProfileCreateRequest, ProfileInfo};
-use crate::provider::ProviderInfo;
+use crate::profile::{ProfileCreateRequest, ProfileDoctorReport, ProfileInfo, RunHistoryEntry};
+use crate::provider::{LocalProviderRequest, ProviderCheck, ProviderInfo};
 use crate::proxy::{ProfileProxyConfig, ProxyInstanceInfo, RoutingRule};
-use crate::usage::{CostBreakdown, TokenUsage, UsageAggregates, UsagePeriod};
+use crate::usage::{
+    AgentType, BudgetStatus, CostBreakdown, TokenUsage, UsageAggregates, UsageImportFormat,
+    UsagePeriod, UsageRecordsPage,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+/// Version of the CLI ↔ daemon protocol.
+///
+/// Bump this whenever `Request` or `Response` change in a way an older peer
+/// cannot parse. The CLI compares it against the daemon's during the first
+/// `Ping`/`Pong` exchange.
+pub const PROTOCOL_VERSION: u32 = 2;
+
 /// Request from CLI to daemon.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -21,39 +31,146 @@ pub enum Request {
     },
 
     // Provider commands
-    ProvidersList,
+    ProvidersList {
+        /// Only include providers of this type (e.g. "openai", "anthropic-compatible").
+        #[serde(default)]
+        provider_type: Option<String>,
+        /// Only include providers whose ID or name contains this substring.
+        #[serde(default)]
+        search: Option<String>,
+    },
     ProvidersInspect {
         id: String,
     },
+    ProvidersCheck {
+        id: String,
+        /// Endpoint ID; the provider's default when `None`.
+        #[serde(default)]
+        endpoint: Option<String>,
+        /// Key to authenticate with; only reachability is checked when `None`.
+        #[serde(default)]
+        api_key: Option<String>,
+    },
+    ProvidersAddLocal(LocalProviderRequest),
 
     // Profile commands
     ProfilesCreate(ProfileCreateRequest),
     ProfilesList {
         agent_id: Option<String>,
+        #[serde(default)]
+        provider_id: Option<String>,
     },
+    /// List profile aliases only, for shell completion.
+    ProfilesNames,
     ProfilesInspect {
         alias: String,
+        /// Include secret values instead of masking them.
+        #[serde(default)]
+        show_secrets: bool,
     },
     ProfilesRun {
         alias: String,
         args: Vec<String>,
+        /// Extra variables (e.g. from `--env-file`); the profile's own env wins.
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        env: HashMap<String, String>,
+        /// Stop a proxy started for this run once the agent exits.
+        #[serde(default)]
+        ephemeral_proxy: bool,
     },
     ProfilesPrepare {
         alias: String,
         args: Vec<String>,
+        /// Extra variables (e.g. from `--env-file`); the profile's own env wins.
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        env: HashMap<String, String>,
+        /// Stop a proxy started for this run once the agent exits.
+        #[serde(default)]
+        ephemeral_proxy: bool,
+        /// Caller's working directory, used when the profile doesn't set one.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cwd: Option<PathBuf>,
+        /// Process that will spawn the agent; the run is abandoned if it dies
+        /// without completing it.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        owner_pid: Option<u32>,
     },
     ProfilesComplete {
         run_id: String,
         started_at: chrono::DateTime<chrono::Utc>,
         ended_at: chrono::DateTime<chrono::Utc>,
         exit_code: i32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signal: Option<i32>,
+    },
+    /// Drop a prepared run that never started, releasing its ephemeral proxy.
+    ProfilesAbandon {
+        run_id: String,
     },
     ProfilesDelete {
         alias: String,
     },
+    /// Move a profile to a new alias.
+    ProfilesRename {
+        old_alias: String,
+        new_alias: String,
+    },
+    /// Delete every profile matching the filters (at least one is required).
+    ProfilesDeleteMatching {
+        agent_id: Option<String>,
+        provider_id: Option<String>,
+    },
     ProfilesEnv {
         alias: String,
     },
+    ProfilesHistory {
+        alias: String,
+        /// Maximum number of entries to return, newest first.
+        limit: usize,
+    },
+    ProfilesSetHook {
+        alias: String,
+        /// `pre-run` or `post-run`.
+        stage: String,
+        /// Shell command to run; `None` clears the hook.
+        command: Option<String>,
+    },
+    /// Replace a profile's default agent arguments (empty clears them).
+    ProfilesSetArgs {
+        alias: String,
+        args: Vec<String>,
+    },
+    /// Replace a profile's spend quotas; a quota left out is removed.
+    ProfilesSetQuota {
+        alias: String,
+        daily_usd: Option<f64>,
+        monthly_usd: Option<f64>,
+    },
+    /// Set or clear the command template a profile's agent is launched through.
+    ProfilesSetLaunchWrapper {
+        alias: String,
+        /// Wrapper template; `None` runs the agent directly.
+        wrapper: Option<Vec<String>>,
+    },
+    /// Turn saving every run's output to a log file on or off.
+    ProfilesSetCaptureLogs {
+        alias: String,
+        enabled: bool,
+    },
+    /// Store a new API key for a profile.
+    ProfilesSetApiKey {
+        alias: String,
+        api_key: String,
+    },
+    /// Find leftovers of deleted profiles, removing them when `fix` is set.
+    ///
+    /// Orphaned homes may hold agent history, so they are only deleted when
+    /// `remove_homes` is set as well.
+    ProfilesDoctor {
+        fix: bool,
+        #[serde(default)]
+        remove_homes: bool,
+    },
 
     // Alias commands
     AliasesInstall {
@@ -74,6 +191,22 @@ pub enum Request {
     },
     RegistryInspect,
 
+    // Configuration bundle commands
+    /// Write all profiles, config files and the registry pin to a `.tar.gz`.
+    BundleExport {
+        path: PathBuf,
+    },
+    /// Restore a bundle written by `BundleExport`.
+    BundleImport {
+        path: PathBuf,
+        /// Replace profiles and files that already exist.
+        #[serde(default)]
+        overwrite: bool,
+        /// Import hooks, scripts, provider manifests and other settings that need review.
+        #[serde(default)]
+        force: bool,
+    },
+
     // Stats commands
     Stats {
         agent_id: Option<String>,
@@ -85,10 +218,35 @@ pub enum Request {
         period: Option<UsagePeriod>,
         profile: Option<String>,
         model: Option<String>,
+        #[serde(default)]
+        agent: Option<AgentType>,
     },
     UsageImportClaude {
         claude_dir: Option<PathBuf>,
     },
+    /// Store usage rows computed outside ringlet so they count in aggregates.
+    UsageImport {
+        format: UsageImportFormat,
+        content: String,
+    },
+    UsageBudget,
+    UsageRecords {
+        period: Option<UsagePeriod>,
+        offset: usize,
+        limit: usize,
+    },
+    /// Delete recorded usage data. With neither flag set, both are cleared.
+    UsageReset {
+        /// Delete telemetry sessions.
+        #[serde(default)]
+        telemetry: bool,
+        /// Clear cached usage data and budget alert state.
+        #[serde(default)]
+        cache: bool,
+        /// Only delete telemetry sessions from before this date.
+        #[serde(default)]
+        before: Option<chrono::NaiveDate>,
+    },
 
     // Env setup commands
     EnvSetup {
@@ -102,6 +260,12 @@ pub enum Request {
         event: String,
         matcher: String,
         command: String,
+        /// Preview the result without saving it.
+        #[serde(default)]
+        dry_run: bool,
+        /// Save the command even if it looks unsafe.
+        #[serde(default)]
+        force: bool,
     },
     HooksList {
         alias: String,
@@ -114,9 +278,30 @@ pub enum Request {
     HooksImport {
         alias: String,
         config: HooksConfig,
+        /// Save commands even if they look unsafe.
+        #[serde(default)]
+        force: bool,
     },
     HooksExport {
         alias: String,
+        /// Agent whose native config format to export in; `None` for ringlet's own format.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+    },
+    HooksDefaultsList,
+    HooksDefaultsAdd {
+        /// Agent id, or `*` for every agent.
+        agent: String,
+        event: String,
+        matcher: String,
+        command: String,
+        /// Save the command even if it looks unsafe.
+        #[serde(default)]
+        force: bool,
+    },
+    HooksSkipDefaults {
+        alias: String,
+        skip: bool,
     },
 
     // Proxy commands
@@ -135,6 +320,9 @@ pub enum Request {
     ProxyStopAll,
     ProxyRestart {
         alias: String,
+        /// Health-check the new configuration on a spare port before restarting.
+        #[serde(default)]
+        verify: bool,
     },
     ProxyStatus {
         alias: Option<String>,
@@ -169,9 +357,23 @@ pub enum Request {
         alias: String,
         lines: Option<usize>,
     },
+    /// Location of a profile's current proxy log file.
+    ProxyLogPath {
+        alias: String,
+    },
+    /// Base-URL environment variables pointing a profile's agent at its running proxy.
+    ProxyEnv {
+        alias: String,
+    },
 
     // Daemon commands
-    Ping,
+    Ping {
+        /// Protocol version spoken by the client (absent from pre-versioning clients).
+        #[serde(default)]
+        protocol_version: Option<u32>,
+    },
+    /// Daemon-side facts for `ringlet doctor`.
+    Diagnostics,
     Shutdown,
 }
 
@@ -191,12 +393,24 @@ pub enum Response {
     /// Single provider details.
     Provider(ProviderInfo),
 
+    /// Result of an endpoint reachability/auth check.
+    ProviderCheck(ProviderCheck),
+
     /// List of profiles.
     Profiles(Vec<ProfileInfo>),
 
     /// Single profile details.
     Profile(ProfileInfo),
 
+    /// Profile aliases, sorted.
+    ProfileNames(Vec<String>),
+
+    /// Recent runs of a profile, newest first.
+    RunHistory(Vec<RunHistoryEntry>),
+
+    /// Leftovers of deleted profiles.
+    ProfileDoctor(ProfileDoctorReport),
+
     /// Hooks configuration.
     Hooks(HooksConfig),
 
@@ -215,6 +429,29 @@ pub enum Response {
     /// Proxy logs.
     ProxyLogs(String),
 
+    /// Path of a proxy's current log file.
+    ProxyLogPath(PathBuf),
+
+    /// Default hooks from config.toml, keyed by agent id.
+    HooksDefaults(HashMap<String, HooksConfig>),
+
+    /// Preview of a hook change that was not saved.
+    HooksPreview {
+        /// The profile's hooks configuration after the change.
+        hooks: HooksConfig,
+        /// Generated agent config file holding the hooks, if the script writes them.
+        file: Option<String>,
+        /// Hooks section of that file.
+        generated: Option<String>,
+    },
+
+    /// Hooks rendered in an agent's native config format.
+    HooksNative {
+        /// Config file the snippet belongs in, relative to the profile home.
+        file: String,
+        content: String,
+    },
+
     /// Environment variables for shell export.
     Env(HashMap<String, String>),
 
@@ -224,12 +461,24 @@ pub enum Response {
     /// Registry status.
     RegistryStatus(RegistryStatus),
 
+    /// Result of exporting or importing a configuration bundle.
+    Bundle(BundleReport),
+
+    /// Daemon-side facts for `ringlet doctor`.
+    Diagnostics(Diagnostics),
+
     /// Usage statistics (legacy).
     Stats(StatsResponse),
 
     /// Token/cost usage statistics.
     Usage(Box<UsageStatsResponse>),
 
+    /// Current spend against configured budgets.
+    UsageBudget(Vec<BudgetStatus>),
+
+    /// A page of individual usage records.
+    UsageRecords(UsageRecordsPage),
+
     /// Generic success message.
     Success { message: String },
 
@@ -237,10 +486,21 @@ pub enum Response {
     RunStarted { pid: u32 },
 
     /// Profile run completed.
-    RunCompleted { exit_code: i32 },
+    RunCompleted {
+        exit_code: i32,
+        /// Signal that terminated the agent (Unix only).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signal: Option<i32>,
+        /// Wall-clock run time.
+        #[serde(default)]
+        duration_secs: u64,
+    },
 
     /// Pong response.
-    Pong,
+    Pong {
+        /// Protocol version spoken by the daemon.
+        protocol_version: u32,
+    },
 
     /// Error response.
     Error { code: i32, message: String },
@@ -267,6 +527,61 @@ pub struct ExecutionContext {
     /// Daemon-owned run identifier for CLI-attached profile execution.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub run_id: Option<String>,
+
+    /// Number of generated config files that were written because they changed.
+    #[serde(default)]
+    pub files_changed: usize,
+
+    /// Number of generated config files left untouched because they matched disk.
+    #[serde(default)]
+    pub files_unchanged: usize,
+
+    /// Profile command to run before spawning the agent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_run: Option<String>,
+
+    /// Profile command to run after the agent exits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_run: Option<String>,
+
+    /// File the agent's stdout and stderr are copied to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_path: Option<PathBuf>,
+}
+
+/// What the daemon knows that `ringlet doctor` checks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostics {
+    /// Version of ringlet the daemon runs.
+    pub version: String,
+    /// ultrallm binary proxies are started from, if one was found.
+    pub proxy_binary: Option<PathBuf>,
+    /// Profiles with the proxy enabled.
+    #[serde(default)]
+    pub proxy_profiles: Vec<String>,
+    /// Alias shims installed for profiles.
+    #[serde(default)]
+    pub shims: Vec<PathBuf>,
+}
+
+/// What a configuration bundle export or import covered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleReport {
+    /// The bundle file.
+    pub path: PathBuf,
+    /// Profiles written to or restored from the bundle.
+    pub profiles: Vec<String>,
+    /// Config files in the bundle, relative to the config directory.
+    pub files: Vec<String>,
+    /// Profiles and files left alone because they already exist.
+    #[serde(default)]
+    pub skipped: Vec<String>,
+    /// Profiles whose API keys are not in the bundle and must be set again.
+    #[serde(default)]
+    pub needs_api_key: Vec<String>,
+    /// Registry ref the bundle pins.
+    #[serde(default)]
+    pub registry_pin: Option<String>,
 }
 
 /// Registry sync status.
@@ -292,6 +607,26 @@ pub struct RegistryStatus {
 
     /// Number of cached scripts.
     pub cached_scripts: usize,
+
+    /// Seconds since the last successful sync.
+    #[serde(default)]
+    pub cache_age_secs: Option<u64>,
+
+    /// Configured cache TTL (`registry.cache_ttl_secs`).
+    #[serde(default)]
+    pub cache_ttl_secs: u64,
+
+    /// Whether the cache is older than the TTL (or was never synced).
+    #[serde(default)]
+    pub stale: bool,
+
+    /// Whether `sync` skipped fetching because the cache was fresh.
+    #[serde(default)]
+    pub from_cache: bool,
+
+    /// Set when the registry could not be reached and cached data is served.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
 }
 
 /// Usage statistics response (legacy, without token/cost).
@@ -333,6 +668,25 @@ pub struct UsageStatsResponse {
 
     /// Total runtime (seconds).
     pub total_runtime_secs: u64,
+
+    /// Share of prompt tokens read from cache (see [`TokenUsage::cache_hit_ratio`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_hit_ratio: Option<f64>,
+
+    /// Estimated USD saved by cache reads versus the full input price.
+    ///
+    /// Only covers models with known pricing; `None` when there are none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_savings_usd: Option<f64>,
+
+    /// Models missing from the pricing table whose usage has no cost, so
+    /// `total_cost` leaves it out. Sorted by name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unpriced_models: Vec<String>,
+
+    /// Number of usage entries (sessions or records) using `unpriced_models`.
+    #[serde(default)]
+    pub unpriced_entries: u64,
 }
 
 /// Per-agent statistics.
@@ -389,9 +743,29 @@ pub mod error_codes {
     pub const PROXY_NOT_SUPPORTED: i32 = 1014;
     pub const ROUTE_NOT_FOUND: i32 = 1015;
     pub const ALIAS_NOT_FOUND: i32 = 1016;
+    pub const INVALID_RUN_HOOK: i32 = 1017;
+    pub const INVALID_HOOK_CONFIG: i32 = 1018;
+    pub const PROVIDER_EXISTS: i32 = 1019;
+    pub const INVALID_MODEL: i32 = 1020;
+    pub const INVALID_LAUNCH_WRAPPER: i32 = 1021;
+    pub const TOKEN_EXPIRED: i32 = 1022;
+    pub const QUOTA_EXCEEDED: i32 = 1023;
+    pub const INVALID_QUOTA: i32 = 1024;
+    pub const INVALID_USAGE_IMPORT: i32 = 1025;
+    pub const UNSAFE_HOOK_COMMAND: i32 = 1026;
+    pub const INVALID_BUNDLE: i32 = 1027;
+    /// A TCP request without the endpoint file's token.
+    pub const UNAUTHORIZED: i32 = 1028;
+    /// A bundle that would install commands, imported without `force`.
+    pub const UNTRUSTED_BUNDLE: i32 = 1029;
+    /// A request whose parameters fail validation (JSON-RPC "invalid params").
+    pub const INVALID_PARAMS: i32 = -32602;
     pub const SCRIPT_ERROR: i32 = 2001;
     pub const EXECUTION_ERROR: i32 = 2002;
     pub const REGISTRY_ERROR: i32 = 3001;
+    pub const RATE_LIMITED: i32 = 4001;
+    /// The daemon does not know the request type (usually an older daemon).
+    pub const NOT_IMPLEMENTED: i32 = 9998;
     pub const INTERNAL_ERROR: i32 = 9999;
 }
 
@@ -431,6 +805,17 @@ mod tests {
         assert!(matches!(parsed, Request::AgentsList));
     }
 
+    #[test]
+    fn test_unversioned_ping_still_parses() {
+        let parsed: Request = serde_json::from_str(r#"{"type":"ping"}"#).unwrap();
+        assert!(matches!(
+            parsed,
+            Request::Ping {
+                protocol_version: None
+            }
+        ));
+    }
+
     #[test]
     fn test_response_serialization() {
         let resp = Response::success("Profile created");
@@ -16,14 +16,39 @@ use std::ops::AddAssign;
 #[serde(rename_all = "snake_case")]
 pub enum AgentType {
     /// Claude Code CLI.
+    #[serde(alias = "claude-code", alias = "claude_code")]
     Claude,
     /// OpenAI Codex CLI.
+    #[serde(alias = "codex-cli", alias = "openai-codex")]
     Codex,
     /// OpenCode editor.
-    #[serde(rename = "opencode")]
+    #[serde(rename = "opencode", alias = "open-code", alias = "open_code")]
     OpenCode,
 }
 
+impl AgentType {
+    /// All agent types, in display order.
+    pub const ALL: [AgentType; 3] = [AgentType::Claude, AgentType::Codex, AgentType::OpenCode];
+}
+
+impl std::str::FromStr for AgentType {
+    type Err = String;
+
+    /// Parse an agent name case-insensitively, accepting the same aliases as serde.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "claude" | "claude-code" | "claude_code" => Ok(Self::Claude),
+            "codex" | "codex-cli" | "openai-codex" => Ok(Self::Codex),
+            "opencode" | "open-code" | "open_code" => Ok(Self::OpenCode),
+            _ => Err(format!(
+                "unknown agent '{}' (expected one of: {})",
+                s,
+                Self::ALL.map(|agent| agent.to_string()).join(", ")
+            )),
+        }
+    }
+}
+
 impl std::fmt::Display for AgentType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -64,6 +89,13 @@ impl TokenUsage {
     pub fn total_input(&self) -> u64 {
         self.input_tokens + self.cache_creation_input_tokens + self.cache_read_input_tokens
     }
+
+    /// Share of prompt tokens served from cache: cache reads over uncached
+    /// input plus cache reads. `None` when there were no such tokens.
+    pub fn cache_hit_ratio(&self) -> Option<f64> {
+        let prompt = self.input_tokens + self.cache_read_input_tokens;
+        (prompt > 0).then(|| self.cache_read_input_tokens as f64 / prompt as f64)
+    }
 }
 
 impl AddAssign for TokenUsage {
@@ -120,6 +152,35 @@ impl CostBreakdown {
             total_cost: input_cost + output_cost + cache_creation_cost + cache_read_cost,
         }
     }
+
+    /// Rescale the split so it sums to `total_cost`, keeping each category's share.
+    ///
+    /// Used to attribute a cost reported by an agent across token categories.
+    /// A zero breakdown has no shares to keep, so only the total is set.
+    pub fn scaled_to(&self, total_cost: f64) -> Self {
+        if self.total_cost <= 0.0 {
+            return Self {
+                total_cost,
+                ..Default::default()
+            };
+        }
+
+        let ratio = total_cost / self.total_cost;
+        Self {
+            input_cost: self.input_cost * ratio,
+            output_cost: self.output_cost * ratio,
+            cache_creation_cost: self.cache_creation_cost * ratio,
+            cache_read_cost: self.cache_read_cost * ratio,
+            total_cost,
+        }
+    }
+
+    /// Part of the total not attributed to any token category.
+    pub fn unattributed_cost(&self) -> f64 {
+        let attributed =
+            self.input_cost + self.output_cost + self.cache_creation_cost + self.cache_read_cost;
+        (self.total_cost - attributed).max(0.0)
+    }
 }
 
 impl AddAssign for CostBreakdown {
@@ -166,6 +227,14 @@ impl LiteLLMModelPricing {
             self.cache_read_input_token_cost.unwrap_or(0.0),
         )
     }
+
+    /// What the cache reads in `tokens` cost less than the same tokens at the
+    /// full input price.
+    pub fn cache_savings(&self, tokens: &TokenUsage) -> f64 {
+        let discount = self.input_cost_per_token.unwrap_or(0.0)
+            - self.cache_read_input_token_cost.unwrap_or(0.0);
+        tokens.cache_read_input_tokens as f64 * discount.max(0.0)
+    }
 }
 
 /// Usage period for queries.
@@ -191,6 +260,54 @@ pub enum UsagePeriod {
     All,
 }
 
+impl std::str::FromStr for UsagePeriod {
+    type Err = String;
+
+    /// Parse a named period case-insensitively.
+    ///
+    /// Custom ranges have no string form; build `DateRange` from explicit dates.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "today" => Ok(Self::Today),
+            "yesterday" => Ok(Self::Yesterday),
+            "week" | "this_week" | "thisweek" => Ok(Self::ThisWeek),
+            "month" | "this_month" | "thismonth" => Ok(Self::ThisMonth),
+            "7d" | "7days" | "last7days" | "last_7_days" => Ok(Self::Last7Days),
+            "30d" | "30days" | "last30days" | "last_30_days" => Ok(Self::Last30Days),
+            "all" | "alltime" | "all_time" => Ok(Self::All),
+            _ => Err(format!(
+                "unknown period '{}' (expected one of: today, yesterday, week, month, 7d, 30d, all)",
+                s
+            )),
+        }
+    }
+}
+
+/// File format accepted by `usage import`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageImportFormat {
+    /// Comma-separated values with a header row.
+    Csv,
+    /// A JSON array of records, or one record per line.
+    Json,
+}
+
+impl std::str::FromStr for UsageImportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "json" | "jsonl" => Ok(Self::Json),
+            _ => Err(format!(
+                "unknown import format '{}' (expected one of: csv, json)",
+                s
+            )),
+        }
+    }
+}
+
 /// Daily usage statistics.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DailyUsage {
@@ -215,6 +332,9 @@ pub struct ModelUsage {
     pub cost: Option<CostBreakdown>,
     /// Number of sessions.
     pub sessions: u64,
+    /// Runtime of Ringlet-launched sessions using this model, in seconds.
+    #[serde(default)]
+    pub runtime_secs: u64,
 }
 
 /// Per-profile usage statistics.
@@ -302,10 +422,148 @@ pub struct SessionUsage {
     pub duration_secs: Option<u64>,
 }
 
+/// A single usage record read from an agent's native files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// When the usage was recorded.
+    pub timestamp: DateTime<Utc>,
+    /// Agent that generated the usage.
+    pub agent: AgentType,
+    /// Model used for the request.
+    pub model: String,
+    /// Token usage.
+    pub tokens: TokenUsage,
+    /// Cost in USD, if reported by the agent.
+    pub cost_usd: Option<f64>,
+}
+
+/// One page of usage records, ordered oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecordsPage {
+    /// Records in this page.
+    pub records: Vec<UsageRecord>,
+    /// Total records matching the query.
+    pub total: usize,
+    /// Offset of the next page, or `None` if this is the last page.
+    pub next_offset: Option<usize>,
+}
+
+/// Budget window that a spend limit applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPeriod {
+    /// Current UTC day.
+    Daily,
+    /// Current UTC calendar month.
+    Monthly,
+}
+
+impl BudgetPeriod {
+    /// Key identifying the budget window containing `date`, e.g. `daily:2025-01-20`.
+    pub fn window_key(&self, date: chrono::NaiveDate) -> String {
+        match self {
+            BudgetPeriod::Daily => format!("daily:{}", date.format("%Y-%m-%d")),
+            BudgetPeriod::Monthly => format!("monthly:{}", date.format("%Y-%m")),
+        }
+    }
+}
+
+impl std::fmt::Display for BudgetPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetPeriod::Daily => write!(f, "daily"),
+            BudgetPeriod::Monthly => write!(f, "monthly"),
+        }
+    }
+}
+
+/// Current spend measured against a configured budget.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    /// Budget window.
+    pub period: BudgetPeriod,
+    /// Configured limit in USD.
+    pub limit_usd: f64,
+    /// Spend so far in the current window, in USD.
+    pub spent_usd: f64,
+    /// Whether spend has reached the limit.
+    pub exceeded: bool,
+}
+
+impl BudgetStatus {
+    /// Build a status, deriving `exceeded` from spend and limit.
+    pub fn new(period: BudgetPeriod, limit_usd: f64, spent_usd: f64) -> Self {
+        Self {
+            period,
+            limit_usd,
+            spent_usd,
+            exceeded: spent_usd >= limit_usd,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_agent_type_from_str() {
+        assert_eq!("claude".parse(), Ok(AgentType::Claude));
+        assert_eq!("Claude-Code".parse(), Ok(AgentType::Claude));
+        assert_eq!("CODEX".parse(), Ok(AgentType::Codex));
+        assert_eq!("open-code".parse(), Ok(AgentType::OpenCode));
+        for agent in AgentType::ALL {
+            assert_eq!(agent.to_string().parse(), Ok(agent));
+        }
+
+        let err = "cursor".parse::<AgentType>().unwrap_err();
+        assert_eq!(
+            err,
+            "unknown agent 'cursor' (expected one of: claude, codex, opencode)"
+        );
+
+        let agent: AgentType = serde_json::from_str("\"claude-code\"").unwrap();
+        assert_eq!(agent, AgentType::Claude);
+        assert_eq!(serde_json::to_string(&agent).unwrap(), "\"claude\"");
+    }
+
+    #[test]
+    fn test_usage_period_from_str() {
+        assert!(matches!("today".parse(), Ok(UsagePeriod::Today)));
+        assert!(matches!("Week".parse(), Ok(UsagePeriod::ThisWeek)));
+        assert!(matches!("this_month".parse(), Ok(UsagePeriod::ThisMonth)));
+        assert!(matches!("7d".parse(), Ok(UsagePeriod::Last7Days)));
+        assert!(matches!(
+            "last_30_days".parse(),
+            Ok(UsagePeriod::Last30Days)
+        ));
+        assert!(matches!("all".parse(), Ok(UsagePeriod::All)));
+
+        let err = "tody".parse::<UsagePeriod>().unwrap_err();
+        assert!(err.starts_with("unknown period 'tody'"), "{}", err);
+    }
+
+    #[test]
+    fn test_cost_breakdown_scaled_to() {
+        let tokens = TokenUsage {
+            input_tokens: 1000,
+            output_tokens: 500,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 2000,
+        };
+        let priced = CostBreakdown::from_tokens(&tokens, 0.000003, 0.000015, 0.0, 0.0000003);
+        let scaled = priced.scaled_to(priced.total_cost * 2.0);
+        assert!((scaled.input_cost - priced.input_cost * 2.0).abs() < 1e-12);
+        assert!((scaled.output_cost - priced.output_cost * 2.0).abs() < 1e-12);
+        assert!((scaled.cache_read_cost - priced.cache_read_cost * 2.0).abs() < 1e-12);
+        assert!(scaled.unattributed_cost() < 1e-12);
+
+        let total_only = CostBreakdown::new().scaled_to(0.5);
+        assert_eq!(total_only.total_cost, 0.5);
+        assert_eq!(total_only.input_cost, 0.0);
+        assert_eq!(total_only.unattributed_cost(), 0.5);
+    }
+
     #[test]
     fn test_token_usage_add() {
         let mut a = TokenUsage {
@@ -373,9 +631,39 @@ mod tests {
         assert!((cost.output_cost - 0.0075).abs() < 0.0001);
     }
 
+    #[test]
+    fn test_cache_hit_ratio_and_savings() {
+        let pricing = LiteLLMModelPricing {
+            input_cost_per_token: Some(0.000003),
+            cache_read_input_token_cost: Some(0.0000003),
+            ..Default::default()
+        };
+
+        let tokens = TokenUsage {
+            input_tokens: 1_000,
+            output_tokens: 500,
+            cache_creation_input_tokens: 2_000,
+            cache_read_input_tokens: 3_000,
+        };
+
+        assert_eq!(tokens.cache_hit_ratio(), Some(0.75));
+        // 3000 reads at $2.70/MTok less than full input price
+        assert!((pricing.cache_savings(&tokens) - 0.0081).abs() < 1e-12);
+        assert_eq!(TokenUsage::new().cache_hit_ratio(), None);
+    }
+
     #[test]
     fn test_agent_type_opencode_serialization_matches_agent_id() {
         let json = serde_json::to_string(&AgentType::OpenCode).unwrap();
         assert_eq!(json, "\"opencode\"");
     }
+
+    #[test]
+    fn test_budget_window_key() {
+        let date = chrono::NaiveDate::from_ymd_opt(2025, 1, 20).unwrap();
+        assert_eq!(BudgetPeriod::Daily.window_key(date), "daily:2025-01-20");
+        assert_eq!(BudgetPeriod::Monthly.window_key(date), "monthly:2025-01");
+        assert!(BudgetStatus::new(BudgetPeriod::Daily, 5.0, 5.0).exceeded);
+        assert!(!BudgetStatus::new(BudgetPeriod::Monthly, 5.0, 4.99).exceeded);
+    }
 }
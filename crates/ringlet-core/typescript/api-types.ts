@@ -18,6 +18,14 @@ export interface AgentInfo {
   default_provider: string | null
   supports_hooks: boolean
   last_used: string | null
+  compatible_providers?: CompatibleProvider[]
+}
+
+export interface CompatibleProvider {
+  id: string
+  name: string
+  provider_type: ProviderType
+  notes?: string
 }
 
 export type ProviderType =
@@ -26,6 +34,8 @@ export type ProviderType =
   | 'openai'
   | 'openai-compatible'
   | 'self'
+  // Types unknown to this daemon version are passed through by name
+  | (string & {})
 
 export interface ProviderInfo {
   id: string
@@ -36,12 +46,24 @@ export interface ProviderInfo {
   default_endpoint: string
   auth_required: boolean
   auth_prompt: string
+  auth_kind: AuthKind
+  device_auth?: DeviceAuthConfig
+}
+
+export type AuthKind = 'api_key' | 'oauth_device'
+
+export interface DeviceAuthConfig {
+  device_authorization_url: string
+  token_url: string
+  client_id: string
+  scope?: string
 }
 
 export interface EndpointInfo {
   id: string
   url: string
   is_default: boolean
+  models?: string[]
 }
 
 export interface ProfileInfo {
@@ -52,6 +74,15 @@ export interface ProfileInfo {
   model: string
   last_used: string | null
   total_runs: number
+  pre_run?: string
+  post_run?: string
+  launch_wrapper?: string[]
+  args?: string[]
+  token_expires_at?: string
+  quota_daily_usd?: number
+  quota_monthly_usd?: number
+  capture_logs?: boolean
+  env?: Record<string, string>
 }
 
 export interface ProfileCreateRequest {
@@ -61,6 +92,8 @@ export interface ProfileCreateRequest {
   endpoint_id?: string | null
   model?: string | null
   api_key: string
+  refresh_token?: string
+  token_expires_at?: string
   hooks?: string[]
   mcp_servers?: string[]
   args?: string[]
@@ -68,15 +101,27 @@ export interface ProfileCreateRequest {
   bare?: boolean
   proxy?: boolean
   no_alias?: boolean
+  launch_wrapper?: string[]
 }
 
 export interface RunRequest {
   args?: string[]
+  ephemeral_proxy?: boolean
 }
 
 export type RunResponse =
   | { status: 'started'; pid: number }
-  | { status: 'completed'; exit_code: number }
+  | { status: 'completed'; exit_code: number; signal?: number; duration_secs: number }
+
+export interface ExecOutput {
+  data: string
+}
+
+export interface ExecExit {
+  exit_code: number
+  signal?: number
+  duration_secs: number
+}
 
 export interface HooksConfig {
   PreToolUse?: HookRule[]
@@ -98,11 +143,13 @@ export interface AddHookRequest {
   event: string
   matcher: string
   command: string
+  force?: boolean
 }
 
 export interface ProxyInstanceInfo {
   alias: string
   port: number
+  url: string
   pid: number
   status: ProxyStatus
   started_at: string
@@ -190,6 +237,8 @@ export interface ProfileStats {
 
 export type AgentType = 'claude' | 'codex' | 'opencode'
 
+export type UsageImportFormat = 'csv' | 'json'
+
 export interface TokenUsage {
   input_tokens: number
   output_tokens: number
@@ -211,6 +260,10 @@ export interface UsageStatsResponse {
   total_cost: CostBreakdown | null
   total_sessions: number
   total_runtime_secs: number
+  cache_hit_ratio?: number
+  cache_savings_usd?: number
+  unpriced_models?: string[]
+  unpriced_entries: number
   aggregates: UsageAggregates
 }
 
@@ -243,6 +296,7 @@ export interface ModelUsage {
   tokens: TokenUsage
   cost: CostBreakdown | null
   sessions: number
+  runtime_secs: number
 }
 
 export interface ProfileUsage {
@@ -255,6 +309,15 @@ export interface ProfileUsage {
   last_used: string | null
 }
 
+export type BudgetPeriod = 'daily' | 'monthly'
+
+export interface BudgetStatus {
+  period: BudgetPeriod
+  limit_usd: number
+  spent_usd: number
+  exceeded: boolean
+}
+
 export interface RegistryStatus {
   commit: string | null
   channel: string
@@ -263,6 +326,11 @@ export interface RegistryStatus {
   cached_agents: number
   cached_providers: number
   cached_scripts: number
+  cache_age_secs: number | null
+  cache_ttl_secs: number
+  stale: boolean
+  from_cache: boolean
+  warning?: string
 }
 
 export interface SyncRequest {
@@ -280,16 +348,19 @@ export type Event =
   | { type: 'profile_created'; data: { alias: string } }
   | { type: 'profile_deleted'; data: { alias: string } }
   | { type: 'profile_run_started'; data: { alias: string; pid: number } }
-  | { type: 'profile_run_completed'; data: { alias: string; exit_code: number } }
+  | { type: 'profile_run_completed'; data: { alias: string; exit_code: number; signal?: number } }
   | { type: 'proxy_started'; data: { alias: string; port: number } }
   | { type: 'proxy_stopped'; data: { alias: string } }
   | { type: 'proxy_status_changed'; data: { alias: string; status: ProxyStatus } }
   | { type: 'registry_sync_started' }
   | { type: 'registry_sync_completed'; data: { commit: string | null } }
   | { type: 'usage_updated'; data: { agent: AgentType; profile: string | null; tokens: TokenUsage; cost: CostBreakdown | null } }
+  | { type: 'budget_exceeded'; data: { period: BudgetPeriod; limit: number; actual: number } }
+  | { type: 'progress'; data: { op: string; current: number; total: number | null; message: string | null } }
 
 export type ServerMessage =
-  | { type: 'event'; event: Event }
+  | { type: 'event'; event: Event; seq?: number }
+  | { type: 'resync'; seq: number }
   | { type: 'pong' }
   | { type: 'error'; message: string }
 
@@ -312,8 +383,13 @@ export interface TerminalSessionInfo {
   cols: number
   rows: number
   client_count: number
+  interactive_clients: number
+  view_clients: number
+  persist: boolean
 }
 
+export type TerminalClientMode = 'interactive' | 'view'
+
 export interface CreateTerminalSessionRequest {
   profile_alias: string
   args?: string[]
@@ -323,6 +399,7 @@ export interface CreateTerminalSessionRequest {
   no_sandbox?: boolean
   bwrap_flags?: string[] | null
   sandbox_exec_profile?: string | null
+  persist?: boolean
 }
 
 export interface CreateTerminalSessionResponse {
@@ -335,7 +412,7 @@ export type TerminalClientMessage =
   | { type: 'signal'; signal: number }
 
 export type TerminalServerMessage =
-  | { type: 'connected'; session_id: string }
+  | { type: 'connected'; session_id: string; mode: TerminalClientMode }
   | { type: 'state_changed'; state: string; exit_code: number | null }
   | { type: 'resized'; cols: number; rows: number }
   | { type: 'error'; message: string }
@@ -391,3 +468,26 @@ export interface PingResponse {
   status: string
   version: string
 }
+
+export type HealthState = 'ok' | 'degraded' | 'unhealthy'
+
+export interface SubsystemHealth {
+  name: string
+  healthy: boolean
+  core: boolean
+  detail?: string
+}
+
+export interface RecentError {
+  subsystem: string
+  message: string
+  at: string
+}
+
+export interface HealthResponse {
+  status: HealthState
+  version: string
+  uptime_secs: number
+  subsystems: SubsystemHealth[]
+  recent_errors: RecentError[]
+}
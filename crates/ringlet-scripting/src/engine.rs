@@ -3,13 +3,14 @@
 use crate::functions;
 use anyhow::{Result, anyhow};
 use rhai::{AST, Dynamic, Engine, Map, Scope};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use tracing::debug;
 
 /// Script execution context.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ScriptContext {
     /// Profile information.
     pub profile: ProfileContext,
@@ -19,10 +20,13 @@ pub struct ScriptContext {
     pub agent: AgentContext,
     /// User preferences.
     pub prefs: PrefsContext,
+    /// Versions of ringlet and the registry the script runs under.
+    #[serde(default)]
+    pub meta: MetaContext,
 }
 
 /// Profile context for scripts.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ProfileContext {
     pub alias: String,
     pub home: PathBuf,
@@ -39,16 +43,24 @@ pub struct ProfileContext {
 }
 
 /// Provider context for scripts.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ProviderContext {
     pub id: String,
     pub name: String,
+    /// Exposed to scripts as `provider.type`.
+    #[schemars(rename = "type")]
     pub provider_type: String,
     pub auth_env_key: String,
+    /// Custom auth header name; `None` for the provider type's standard one.
+    #[serde(default)]
+    pub auth_header: Option<String>,
+    /// Text placed before the key in the auth header.
+    #[serde(default)]
+    pub auth_prefix: Option<String>,
 }
 
 /// Agent context for scripts.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AgentContext {
     pub id: String,
     pub name: String,
@@ -56,15 +68,34 @@ pub struct AgentContext {
 }
 
 /// User preferences context for scripts.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct PrefsContext {
     /// Custom preferences map.
     #[serde(flatten)]
     pub custom: HashMap<String, String>,
 }
 
+/// Version information for scripts.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MetaContext {
+    /// Running ringlet version, e.g. `0.1.0`.
+    pub ringlet_version: String,
+    /// Registry commit the cache was synced to, if any.
+    pub registry_commit: Option<String>,
+}
+
+impl Default for MetaContext {
+    fn default() -> Self {
+        Self {
+            ringlet_version: ringlet_core::VERSION.to_string(),
+            registry_commit: None,
+        }
+    }
+}
+
 /// Script execution output.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct ScriptOutput {
     /// Files to write (relative path -> content).
     pub files: HashMap<String, String>,
@@ -72,9 +103,70 @@ pub struct ScriptOutput {
     pub env: HashMap<String, String>,
     /// Additional command-line arguments to pass to the agent.
     pub args: Vec<String>,
+    /// Where each file in `files` is written; files not listed go under the profile home.
+    pub file_roots: HashMap<String, FileRoot>,
+}
+
+impl ScriptOutput {
+    /// Directory a file from `files` is written relative to.
+    pub fn file_root(&self, path: &str) -> FileRoot {
+        self.file_roots.get(path).copied().unwrap_or_default()
+    }
+}
+
+/// Base directory for a generated file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FileRoot {
+    /// The profile's home directory.
+    #[default]
+    Home,
+    /// The working directory the agent runs in, for per-project files.
+    Cwd,
+}
+
+/// Check that a path from `files` stays inside the directory it is written to.
+///
+/// Absolute paths, `..` components and empty paths are refused, so a script
+/// can't write outside the profile home or working directory.
+pub fn relative_file_path(path: &str) -> Result<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(anyhow!(
+                    "files[\"{}\"] must be a relative path without '..'",
+                    path
+                ));
+            }
+        }
+    }
+    if relative.as_os_str().is_empty() {
+        return Err(anyhow!("files[\"{}\"] must name a file", path));
+    }
+    Ok(relative)
+}
+
+/// JSON Schema for the `ctx` object scripts receive.
+pub fn context_schema() -> serde_json::Value {
+    schemars::schema_for!(ScriptContext).to_value()
+}
+
+/// JSON Schema for the map scripts return.
+pub fn output_schema() -> serde_json::Value {
+    schemars::schema_for!(ScriptOutput).to_value()
 }
 
 /// Rhai script engine.
+///
+/// Scripts are pure functions of their context: no registered function
+/// touches the filesystem, network or environment, and `import` is
+/// disabled so a script can't load code from disk. `print`/`debug` go to the
+/// daemon log. Registry-distributed scripts can therefore only influence the
+/// files and env they return, which ringlet writes under the profile home (or
+/// the working directory, per `file_roots`).
 pub struct ScriptEngine {
     engine: Engine,
 }
@@ -91,6 +183,13 @@ impl ScriptEngine {
         engine.set_max_map_size(10_000);
         engine.set_max_call_levels(64);
 
+        // The default resolver loads `import`ed modules from the filesystem
+        engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+
+        // Keep script output off the daemon's stdout
+        engine.on_print(|text| debug!("script print: {}", text));
+        engine.on_debug(|text, _, pos| debug!("script debug at {}: {}", pos, text));
+
         // Register custom functions
         functions::register_all(&mut engine);
 
@@ -194,6 +293,23 @@ fn context_to_dynamic(context: &ScriptContext) -> Result<Dynamic> {
         "auth_env_key".into(),
         context.provider.auth_env_key.clone().into(),
     );
+    provider.insert(
+        "auth_header".into(),
+        context
+            .provider
+            .auth_header
+            .clone()
+            .map_or(Dynamic::UNIT, Dynamic::from),
+    );
+    provider.insert(
+        "auth_prefix".into(),
+        context
+            .provider
+            .auth_prefix
+            .clone()
+            .unwrap_or_default()
+            .into(),
+    );
     map.insert("provider".into(), provider.into());
 
     // Agent
@@ -210,6 +326,22 @@ fn context_to_dynamic(context: &ScriptContext) -> Result<Dynamic> {
     }
     map.insert("prefs".into(), prefs.into());
 
+    // Meta
+    let mut meta = Map::new();
+    meta.insert(
+        "ringlet_version".into(),
+        context.meta.ringlet_version.clone().into(),
+    );
+    meta.insert(
+        "registry_commit".into(),
+        context
+            .meta
+            .registry_commit
+            .clone()
+            .map_or(Dynamic::UNIT, Dynamic::from),
+    );
+    map.insert("meta".into(), meta.into());
+
     Ok(map.into())
 }
 
@@ -227,6 +359,7 @@ fn dynamic_to_output(result: Dynamic) -> Result<ScriptOutput> {
     {
         for (key, value) in files_map {
             if let Some(content) = value.clone().try_cast::<String>() {
+                relative_file_path(&key)?;
                 output.files.insert(key.to_string(), content);
             }
         }
@@ -254,10 +387,34 @@ fn dynamic_to_output(result: Dynamic) -> Result<ScriptOutput> {
         }
     }
 
+    // Extract file roots
+    if let Some(roots_dynamic) = map.get("file_roots")
+        && let Some(roots_map) = roots_dynamic.clone().try_cast::<Map>()
+    {
+        for (path, root) in roots_map {
+            let root = match root.clone().try_cast::<String>().as_deref() {
+                Some("home") => FileRoot::Home,
+                Some("cwd") => FileRoot::Cwd,
+                _ => {
+                    return Err(anyhow!(
+                        "file_roots[\"{}\"] must be \"home\" or \"cwd\", got {}",
+                        path,
+                        root
+                    ));
+                }
+            };
+            output.file_roots.insert(path.to_string(), root);
+        }
+    }
+
     Ok(output)
 }
 
 /// Convert serde_json::Value to Rhai Dynamic.
+///
+/// Integers become `i64` and other numbers `f64`. Integers too large for
+/// `i64` stay as an opaque `serde_json::Number` rather than being rounded to a
+/// float, so scripts can pass them through but not do arithmetic on them.
 fn json_to_dynamic(value: serde_json::Value) -> Result<Dynamic> {
     match value {
         serde_json::Value::Null => Ok(Dynamic::UNIT),
@@ -265,10 +422,10 @@ fn json_to_dynamic(value: serde_json::Value) -> Result<Dynamic> {
         serde_json::Value::Number(n) => {
             if let Some(i) = n.as_i64() {
                 Ok(Dynamic::from(i))
-            } else if let Some(f) = n.as_f64() {
-                Ok(Dynamic::from(f))
+            } else if n.is_f64() {
+                Ok(Dynamic::from(n.as_f64().unwrap_or_default()))
             } else {
-                Ok(Dynamic::UNIT)
+                Ok(Dynamic::from(n))
             }
         }
         serde_json::Value::String(s) => Ok(Dynamic::from(s)),
@@ -292,6 +449,104 @@ fn json_to_dynamic(value: serde_json::Value) -> Result<Dynamic> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::functions::dynamic_to_json;
+    use proptest::prelude::*;
+
+    fn round_trip(value: &serde_json::Value) -> serde_json::Value {
+        dynamic_to_json(&json_to_dynamic(value.clone()).unwrap()).unwrap()
+    }
+
+    fn arb_json() -> impl Strategy<Value = serde_json::Value> {
+        let leaf = prop_oneof![
+            Just(serde_json::Value::Null),
+            any::<bool>().prop_map(serde_json::Value::Bool),
+            any::<i64>().prop_map(serde_json::Value::from),
+            any::<u64>().prop_map(serde_json::Value::from),
+            any::<f64>()
+                .prop_filter("JSON has no NaN or infinity", |f| f.is_finite())
+                .prop_map(serde_json::Value::from),
+            ".*".prop_map(serde_json::Value::String),
+        ];
+        leaf.prop_recursive(4, 64, 8, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..8).prop_map(serde_json::Value::Array),
+                prop::collection::btree_map(".*", inner, 0..8)
+                    .prop_map(|map| serde_json::Value::Object(map.into_iter().collect())),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_json_survives_dynamic_round_trip(value in arb_json()) {
+            prop_assert_eq!(round_trip(&value), value);
+        }
+    }
+
+    #[test]
+    fn test_scripts_cannot_reach_outside() {
+        let engine = ScriptEngine::new();
+        let dir = tempfile::tempdir().unwrap();
+        let module = dir.path().join("leak.rhai");
+        std::fs::write(&module, "export const SECRET = \"leaked\";").unwrap();
+
+        let import = format!(
+            "import \"{}\" as leak; leak::SECRET",
+            module.with_extension("").display()
+        );
+        assert!(engine.engine.eval::<String>(&import).is_err());
+
+        for call in [
+            "read_file(\"/etc/passwd\")",
+            "open(\"/etc/passwd\")",
+            "http_get(\"http://example.com\")",
+            "env(\"HOME\")",
+            "system(\"true\")",
+        ] {
+            let error = engine.engine.eval::<Dynamic>(call).unwrap_err();
+            assert!(
+                matches!(*error, rhai::EvalAltResult::ErrorFunctionNotFound(..)),
+                "{} should not be callable: {}",
+                call,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip_edge_cases() {
+        let value = serde_json::json!({
+            "big": u64::MAX,
+            "min": i64::MIN,
+            "float": 1.5,
+            "whole_float": 2.0,
+            "nested": { "null": null, "list": [null, [], {}] },
+            "empty": {}
+        });
+        assert_eq!(round_trip(&value), value);
+
+        // Documented lossy case: only scripts can produce NaN
+        assert_eq!(
+            dynamic_to_json(&Dynamic::from(f64::NAN)).unwrap(),
+            serde_json::Value::Null
+        );
+    }
+
+    #[test]
+    fn test_schemas_describe_script_contract() {
+        let context = context_schema();
+        assert_eq!(context["title"], "ScriptContext");
+        let provider = &context["$defs"]["ProviderContext"]["properties"];
+        assert!(provider.get("type").is_some());
+        assert!(provider.get("provider_type").is_none());
+
+        let output = output_schema();
+        let properties = output["properties"].as_object().unwrap();
+        let mut keys: Vec<_> = properties.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(keys, ["args", "env", "file_roots", "files"]);
+        assert!(output.get("required").is_none());
+    }
 
     #[test]
     fn test_simple_script() {
@@ -300,10 +555,14 @@ mod tests {
         let script = r#"
             #{
                 files: #{
-                    "test.txt": "Hello, " + ctx.profile.alias
+                    "test.txt": "Hello, " + ctx.profile.alias,
+                    ".mcp.json": "{}"
                 },
                 env: #{
                     "TEST_VAR": "test_value"
+                },
+                file_roots: #{
+                    ".mcp.json": "cwd"
                 }
             }
         "#;
@@ -324,6 +583,8 @@ mod tests {
                 name: "Test Provider".to_string(),
                 provider_type: "anthropic".to_string(),
                 auth_env_key: "TEST_API_KEY".to_string(),
+                auth_header: None,
+                auth_prefix: None,
             },
             agent: AgentContext {
                 id: "test".to_string(),
@@ -331,6 +592,7 @@ mod tests {
                 binary: "test".to_string(),
             },
             prefs: PrefsContext::default(),
+            meta: MetaContext::default(),
         };
 
         let output = engine.run(script, &context).unwrap();
@@ -340,6 +602,28 @@ mod tests {
             Some(&"Hello, myprofile".to_string())
         );
         assert_eq!(output.env.get("TEST_VAR"), Some(&"test_value".to_string()));
+        assert_eq!(output.file_root("test.txt"), FileRoot::Home);
+        assert_eq!(output.file_root(".mcp.json"), FileRoot::Cwd);
+
+        let versioned = r#"
+            let commit = if ctx.meta.registry_commit == () { "none" } else { ctx.meta.registry_commit };
+            #{ env: #{ "VERSION": ctx.meta.ringlet_version, "COMMIT": commit } }
+        "#;
+        let output = engine.run(versioned, &context).unwrap();
+        assert_eq!(output.env["VERSION"], ringlet_core::VERSION);
+        assert_eq!(output.env["COMMIT"], "none");
+
+        let bad_root = r#"#{ files: #{ "a": "" }, file_roots: #{ "a": "tmp" } }"#;
+        assert!(engine.run(bad_root, &context).is_err());
+
+        for path in ["../../x", "/etc/passwd", "a/../../x", ".", ""] {
+            let escaping = format!(r#"#{{ files: #{{ "{}": "" }} }}"#, path);
+            assert!(
+                engine.run(&escaping, &context).is_err(),
+                "{:?} should be refused",
+                path
+            );
+        }
     }
 
     #[test]
@@ -372,6 +656,8 @@ mod tests {
                 name: "Test".to_string(),
                 provider_type: "anthropic".to_string(),
                 auth_env_key: "KEY".to_string(),
+                auth_header: None,
+                auth_prefix: None,
             },
             agent: AgentContext {
                 id: "test".to_string(),
@@ -379,6 +665,7 @@ mod tests {
                 binary: "test".to_string(),
             },
             prefs: PrefsContext::default(),
+            meta: MetaContext::default(),
         };
 
         let output = engine.run(script, &context).unwrap();
@@ -386,4 +673,56 @@ mod tests {
         assert!(json_content.contains("\"name\""));
         assert!(json_content.contains("\"test\""));
     }
+
+    #[test]
+    fn test_codex_custom_auth_header() {
+        let engine = ScriptEngine::new();
+
+        let mut context = ScriptContext {
+            profile: ProfileContext {
+                alias: "local".to_string(),
+                home: PathBuf::from("/home/test"),
+                model: "llama3".to_string(),
+                endpoint: "http://localhost:8000/v1".to_string(),
+                hooks: vec![],
+                mcp_servers: vec![],
+                hooks_config: None,
+                proxy_url: None,
+            },
+            provider: ProviderContext {
+                id: "vllm".to_string(),
+                name: "vLLM".to_string(),
+                provider_type: "openai-compatible".to_string(),
+                auth_env_key: "VLLM_API_KEY".to_string(),
+                auth_header: None,
+                auth_prefix: None,
+            },
+            agent: AgentContext {
+                id: "codex".to_string(),
+                name: "Codex CLI".to_string(),
+                binary: "codex".to_string(),
+            },
+            prefs: PrefsContext::default(),
+            meta: MetaContext::default(),
+        };
+
+        let output = engine.run(crate::scripts::CODEX, &context).unwrap();
+        let config = output.files.get(".codex/config.toml").unwrap();
+        assert!(config.contains("env_key = \"VLLM_API_KEY\""));
+        assert_eq!(
+            output.env.get("VLLM_API_KEY"),
+            Some(&"${API_KEY}".to_string())
+        );
+
+        context.provider.auth_header = Some("X-API-Key".to_string());
+        context.provider.auth_prefix = Some("Token ".to_string());
+        let output = engine.run(crate::scripts::CODEX, &context).unwrap();
+        let config = output.files.get(".codex/config.toml").unwrap();
+        assert!(config.contains("env_http_headers = { \"X-API-Key\" = \"VLLM_API_KEY\" }"));
+        assert!(!config.contains("env_key"));
+        assert_eq!(
+            output.env.get("VLLM_API_KEY"),
+            Some(&"Token ${API_KEY}".to_string())
+        );
+    }
 }
@@ -89,7 +89,12 @@ fn trim_lines(s: String) -> String {
 }
 
 /// Convert Rhai Dynamic to serde_json::Value.
-fn dynamic_to_json(value: &Dynamic) -> Result<serde_json::Value, Box<EvalAltResult>> {
+///
+/// Inverse of `json_to_dynamic`: any JSON value survives the round trip.
+/// Values only a script can produce are converted lossily on purpose:
+/// NaN and infinite floats become `null`, and other Rhai types (characters,
+/// function pointers, timestamps, ...) become their display string.
+pub(crate) fn dynamic_to_json(value: &Dynamic) -> Result<serde_json::Value, Box<EvalAltResult>> {
     if value.is::<()>() {
         Ok(serde_json::Value::Null)
     } else if value.is::<bool>() {
@@ -103,6 +108,11 @@ fn dynamic_to_json(value: &Dynamic) -> Result<serde_json::Value, Box<EvalAltResu
         Ok(serde_json::Number::from_f64(f)
             .map(serde_json::Value::Number)
             .unwrap_or(serde_json::Value::Null))
+    } else if value.is::<serde_json::Number>() {
+        // Integers beyond i64, passed through untouched from JSON input
+        Ok(serde_json::Value::Number(
+            value.clone().cast::<serde_json::Number>(),
+        ))
     } else if value.is::<String>() {
         Ok(serde_json::Value::String(value.clone().cast::<String>()))
     } else if value.is::<rhai::Array>() {
@@ -113,9 +123,12 @@ fn dynamic_to_json(value: &Dynamic) -> Result<serde_json::Value, Box<EvalAltResu
         }
         Ok(serde_json::Value::Array(json_arr))
     } else if value.is::<Map>() {
-        let map = value.clone().cast::<Map>();
+        // Insert keys in sorted order so output is byte-identical across runs,
+        // even if serde_json's `preserve_order` feature is enabled elsewhere.
+        let mut entries: Vec<_> = value.clone().cast::<Map>().into_iter().collect();
+        entries.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
         let mut json_obj = serde_json::Map::new();
-        for (k, v) in map {
+        for (k, v) in entries {
             json_obj.insert(k.to_string(), dynamic_to_json(&v)?);
         }
         Ok(serde_json::Value::Object(json_obj))
@@ -138,6 +151,28 @@ mod tests {
         assert!(result.contains("\"value\""));
     }
 
+    #[test]
+    fn test_encode_is_deterministic() {
+        let mut inner = Map::new();
+        inner.insert("zeta".into(), 1_i64.into());
+        inner.insert("alpha".into(), true.into());
+        let mut map = Map::new();
+        map.insert("model".into(), "m".into());
+        map.insert("env".into(), inner.into());
+        map.insert("api_key".into(), "k".into());
+        let value: Dynamic = map.into();
+
+        let first = json_encode_pretty(value.clone()).unwrap();
+        let second = json_encode_pretty(value.clone()).unwrap();
+        assert_eq!(first, second);
+        assert!(first.find("\"api_key\"").unwrap() < first.find("\"model\"").unwrap());
+        assert!(first.find("\"alpha\"").unwrap() < first.find("\"zeta\"").unwrap());
+
+        let toml_first = toml_encode(value.clone()).unwrap();
+        assert_eq!(toml_first, toml_encode(value).unwrap());
+        assert!(toml_first.find("api_key").unwrap() < toml_first.find("model").unwrap());
+    }
+
     #[test]
     fn test_indent() {
         let result = indent_string("line1\nline2".to_string(), 2);
@@ -12,6 +12,7 @@
 //! - `provider`: Provider information (type, endpoints, auth)
 //! - `agent`: Agent information (binary, profile strategy)
 //! - `prefs`: User preferences (from config.toml)
+//! - `meta`: Running ringlet version and synced registry commit
 //!
 //! ## Script Output
 //!
@@ -19,13 +20,17 @@
 //! - `files`: Map of relative paths to file contents
 //! - `env`: Map of environment variables to set
 //! - `args`: Optional extra command-line arguments
+//! - `file_roots`: Optional map of file paths to `"home"` (default) or `"cwd"`,
+//!   the directory each file is written relative to
+//!
+//! [`context_schema`] and [`output_schema`] describe both as JSON Schema.
 
 mod engine;
 mod functions;
 
 pub use engine::{
-    AgentContext, PrefsContext, ProfileContext, ProviderContext, ScriptContext, ScriptEngine,
-    ScriptOutput,
+    AgentContext, FileRoot, MetaContext, PrefsContext, ProfileContext, ProviderContext,
+    ScriptContext, ScriptEngine, ScriptOutput, context_schema, output_schema, relative_file_path,
 };
 
 /// Built-in scripts for each agent.
@@ -48,3 +53,125 @@ pub mod scripts {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::fmt::Write as _;
+    use std::path::{Path, PathBuf};
+
+    /// Set to rewrite `src/snapshots/` from the current scripts instead of comparing.
+    const UPDATE_SNAPSHOTS: &str = "RINGLET_UPDATE_SNAPSHOTS";
+
+    fn snapshot_context(agent: &str, openai_style: bool, proxy: bool) -> ScriptContext {
+        let (provider_id, provider_type, endpoint, model) = if openai_style {
+            (
+                "openrouter",
+                "openai-compatible",
+                "https://openrouter.ai/api/v1",
+                "openai/gpt-5",
+            )
+        } else {
+            (
+                "minimax",
+                "anthropic-compatible",
+                "https://api.minimax.io/anthropic",
+                "MiniMax-M2.1",
+            )
+        };
+
+        ScriptContext {
+            profile: ProfileContext {
+                alias: format!("{}-work", agent),
+                home: PathBuf::from(format!("/home/user/.{}-profiles/work", agent)),
+                model: model.to_string(),
+                endpoint: endpoint.to_string(),
+                hooks: vec![],
+                mcp_servers: vec![],
+                hooks_config: Some(serde_json::json!({
+                    "PreToolUse": [{
+                        "matcher": "Bash",
+                        "hooks": [{ "type": "command", "command": "guard" }]
+                    }]
+                })),
+                proxy_url: proxy.then(|| "http://127.0.0.1:8080".to_string()),
+            },
+            provider: ProviderContext {
+                id: provider_id.to_string(),
+                name: provider_id.to_string(),
+                provider_type: provider_type.to_string(),
+                auth_env_key: format!("{}_API_KEY", provider_id.to_uppercase()),
+                auth_header: None,
+                auth_prefix: None,
+            },
+            agent: AgentContext {
+                id: agent.to_string(),
+                name: agent.to_string(),
+                binary: agent.to_string(),
+            },
+            prefs: PrefsContext::default(),
+            meta: MetaContext {
+                ringlet_version: "0.0.0-snapshot".to_string(),
+                registry_commit: None,
+            },
+        }
+    }
+
+    /// Render script output as sorted, diff-friendly text.
+    fn render_snapshot(output: &ScriptOutput) -> String {
+        let mut snapshot = String::new();
+        for (path, content) in output.files.iter().collect::<BTreeMap<_, _>>() {
+            let root = format!("{:?}", output.file_root(path)).to_lowercase();
+            writeln!(snapshot, "--- files/{} ({}) ---", path, root).unwrap();
+            writeln!(snapshot, "{}", content.trim_end()).unwrap();
+        }
+        writeln!(snapshot, "--- env ---").unwrap();
+        for (key, value) in output.env.iter().collect::<BTreeMap<_, _>>() {
+            writeln!(snapshot, "{}={}", key, value).unwrap();
+        }
+        if !output.args.is_empty() {
+            writeln!(snapshot, "--- args ---").unwrap();
+            for arg in &output.args {
+                writeln!(snapshot, "{}", arg).unwrap();
+            }
+        }
+        snapshot
+    }
+
+    #[test]
+    fn test_builtin_script_snapshots() {
+        let engine = ScriptEngine::new();
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/snapshots");
+        let update = std::env::var_os(UPDATE_SNAPSHOTS).is_some();
+
+        for (agent, script, openai_style) in [
+            ("claude", scripts::CLAUDE, false),
+            ("codex", scripts::CODEX, true),
+            ("droid", scripts::DROID, false),
+            ("grok", scripts::GROK, true),
+            ("opencode", scripts::OPENCODE, false),
+        ] {
+            for proxy in [false, true] {
+                let context = snapshot_context(agent, openai_style, proxy);
+                let output = engine.run(script, &context).unwrap();
+                let actual = render_snapshot(&output);
+
+                let name = format!("{}-{}.snap", agent, if proxy { "proxy" } else { "direct" });
+                let path = dir.join(&name);
+                if update {
+                    std::fs::create_dir_all(&dir).unwrap();
+                    std::fs::write(&path, &actual).unwrap();
+                    continue;
+                }
+
+                let expected = std::fs::read_to_string(&path).unwrap_or_default();
+                assert_eq!(
+                    actual, expected,
+                    "{} output changed; if intended, rerun with {}=1 and commit src/snapshots/{}",
+                    agent, UPDATE_SNAPSHOTS, name
+                );
+            }
+        }
+    }
+}
@@ -1,116 +1,182 @@
 //! Client for communicating with the ringlet daemon.
+//!
+//! Wraps [`ringlet_client::DaemonClient`] with the CLI's behaviour: starting
+//! the daemon on demand, the `--timeout` flag, and progress bars. Requests go
+//! through the library's typed API, reached by deref.
 
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Result, anyhow};
+use indicatif::{ProgressBar, ProgressStyle};
 use nng::options::Options;
-use nng::{Protocol, Socket};
-use ringlet_core::{Request, Response, RingletPaths};
-use std::process::{Command, Stdio};
+use ringlet_client::{ClientError, DEFAULT_REQUEST_TIMEOUT, subscribe_events};
+use ringlet_core::{DaemonEndpoint, Event, RingletPaths};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::thread::JoinHandle;
 use std::time::Duration;
 use tracing::{debug, info};
 
+/// Request timeout chosen with `--timeout`; `None` inside means wait forever.
+static REQUEST_TIMEOUT: OnceLock<Option<Duration>> = OnceLock::new();
+
+/// Override the request timeout for this process. `0` disables it.
+pub fn set_request_timeout(secs: u64) {
+    let timeout = (secs > 0).then(|| Duration::from_secs(secs));
+    let _ = REQUEST_TIMEOUT.set(timeout);
+}
+
+/// Describe a command error, with a hint for request timeouts.
+pub fn error_message(error: &anyhow::Error) -> String {
+    match error.downcast_ref::<ClientError>() {
+        Some(ClientError::TimedOut(_)) => format!(
+            "{}. Use --timeout <SECS> to wait longer, or --timeout 0 to wait indefinitely.",
+            error
+        ),
+        _ => error.to_string(),
+    }
+}
+
 /// Client for the ringlet daemon.
 pub struct DaemonClient {
-    socket: Socket,
+    inner: ringlet_client::DaemonClient,
+}
+
+impl Deref for DaemonClient {
+    type Target = ringlet_client::DaemonClient;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
 }
 
 impl DaemonClient {
-    /// Connect to the daemon, starting it if necessary.
+    /// Connect to the daemon, starting it if necessary, and check that it
+    /// speaks the same protocol version.
     pub fn connect() -> Result<Self> {
-        let paths = RingletPaths::default();
+        let client = Self::connect_unchecked()?;
+        client.inner.check_protocol().map_err(|e| match e {
+            ClientError::ProtocolMismatch { .. } => anyhow!(
+                "{}. Restart it with `ringlet daemon stop` and run the command again.",
+                e
+            ),
+            e => e.into(),
+        })?;
+        Ok(client)
+    }
 
-        // Check if daemon is running
-        let socket_path = if paths.daemon_endpoint().exists() {
-            let endpoint = std::fs::read_to_string(paths.daemon_endpoint())?;
-            std::path::PathBuf::from(endpoint.trim())
-        } else {
-            paths.ipc_socket()
-        };
+    /// Connect to the daemon without the protocol check.
+    ///
+    /// Used by `daemon stop`/`daemon status`, which must keep working against
+    /// a daemon left over from another ringlet version.
+    pub fn connect_unchecked() -> Result<Self> {
+        let paths = RingletPaths::default();
 
-        // Try to connect
-        match Self::try_connect(&socket_path) {
+        let mut inner = match ringlet_client::DaemonClient::connect_unchecked(&paths) {
             Ok(client) => {
                 debug!("Connected to existing daemon");
-                Ok(client)
+                client
             }
             Err(_) => {
-                // Start daemon
                 info!("Starting daemon...");
-                Self::start_daemon(&paths)?;
-
-                // Wait for daemon to be ready
-                for i in 0..50 {
-                    std::thread::sleep(Duration::from_millis(100));
-                    if let Ok(client) = Self::try_connect(&socket_path) {
-                        debug!("Connected to daemon after {} attempts", i + 1);
-                        return Ok(client);
-                    }
-                }
-
-                Err(anyhow!("Failed to connect to daemon after starting it"))
+                ringlet_client::DaemonClient::start(&paths, &std::env::current_exe()?)?
             }
-        }
-    }
-
-    /// Try to connect to existing daemon.
-    fn try_connect(socket_path: &std::path::Path) -> Result<Self> {
-        let socket = Socket::new(Protocol::Req0).context("Failed to create nng socket")?;
-
-        let url = format!("ipc://{}", socket_path.display());
-        socket
-            .dial(&url)
-            .context(format!("Failed to connect to {}", url))?;
-
-        // Set timeouts
-        socket.set_opt::<nng::options::SendTimeout>(Some(Duration::from_secs(30)))?;
-        socket.set_opt::<nng::options::RecvTimeout>(Some(Duration::from_secs(60)))?;
+        };
 
-        Ok(Self { socket })
+        // Long requests (e.g. a registry sync) wait indefinitely unless a
+        // timeout was set explicitly with `--timeout`
+        let explicit = REQUEST_TIMEOUT.get().copied();
+        inner.set_timeout(explicit.unwrap_or(Some(DEFAULT_REQUEST_TIMEOUT)));
+        inner.set_long_timeout(explicit.flatten());
+        Ok(Self { inner })
     }
 
-    /// Start the daemon process via `ringlet daemon`.
-    fn start_daemon(paths: &RingletPaths) -> Result<()> {
-        let ringlet = std::env::current_exe()?;
-
-        debug!("Starting daemon: {} daemon", ringlet.display());
-
-        // Ensure directories exist
-        paths.ensure_dirs()?;
-
-        // Start daemon in background
-        Command::new(&ringlet)
-            .args(["daemon"])
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn()
-            .context("Failed to start ringlet daemon")?;
-
-        Ok(())
+    /// Run a long-running request, showing the daemon's progress events for
+    /// `op` as a progress bar, or a spinner while the total is unknown.
+    pub fn with_progress<T>(&self, op: &str, request: impl FnOnce(&Self) -> T) -> T {
+        let listener = ProgressListener::start(self.inner.endpoint(), op);
+        let result = request(self);
+        if let Some(listener) = listener {
+            listener.finish();
+        }
+        result
     }
+}
 
-    /// Send a request and receive a response.
-    pub fn request(&self, request: &Request) -> Result<Response> {
-        let json = serde_json::to_vec(request)?;
-        let msg = nng::Message::from(&json[..]);
+/// Renders `Event::Progress` from the daemon's event stream on stderr.
+struct ProgressListener {
+    done: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
 
-        self.socket
-            .send(msg)
-            .map_err(|(_, e)| anyhow!("Send failed: {}", e))?;
+impl ProgressListener {
+    /// Subscribe to the event stream. Returns `None` if the daemon does not
+    /// publish one, in which case the request simply runs without progress.
+    fn start(endpoint: &DaemonEndpoint, op: &str) -> Option<Self> {
+        let socket = match subscribe_events(endpoint) {
+            Ok(socket) => socket,
+            Err(e) => {
+                debug!("No event stream: {:#}", e);
+                return None;
+            }
+        };
+        socket
+            .set_opt::<nng::options::RecvTimeout>(Some(Duration::from_millis(100)))
+            .ok()?;
+
+        let done = Arc::new(AtomicBool::new(false));
+        let stop = done.clone();
+        let op = op.to_string();
+        let handle = std::thread::spawn(move || {
+            let bar = ProgressBar::new_spinner();
+            bar.enable_steady_tick(Duration::from_millis(100));
+
+            while !stop.load(Ordering::Relaxed) {
+                let msg = match socket.recv() {
+                    Ok(msg) => msg,
+                    Err(nng::Error::TimedOut) => continue,
+                    Err(_) => break,
+                };
+                if let Ok(Event::Progress {
+                    op: event_op,
+                    current,
+                    total,
+                    message,
+                }) = serde_json::from_slice(&msg)
+                    && event_op == op
+                {
+                    update_bar(&bar, current, total, message);
+                }
+            }
 
-        let response_msg = self.socket.recv().context("Failed to receive response")?;
+            bar.finish_and_clear();
+        });
 
-        let response: Response = serde_json::from_slice(&response_msg)?;
-        Ok(response)
+        Some(Self { done, handle })
     }
 
-    /// Check if daemon is running.
-    pub fn ping(&self) -> bool {
-        matches!(self.request(&Request::Ping), Ok(Response::Pong))
+    fn finish(self) {
+        self.done.store(true, Ordering::Relaxed);
+        let _ = self.handle.join();
     }
+}
 
-    /// Shutdown the daemon.
-    pub fn shutdown(&self) -> Result<()> {
-        self.request(&Request::Shutdown)?;
-        Ok(())
+fn update_bar(bar: &ProgressBar, current: u64, total: Option<u64>, message: Option<String>) {
+    match total {
+        Some(total) if bar.length() != Some(total) => {
+            bar.set_style(
+                ProgressStyle::with_template("{bar:40.cyan/blue} {pos}/{len} {msg}")
+                    .unwrap_or_else(|_| ProgressStyle::default_bar()),
+            );
+            bar.set_length(total);
+        }
+        None if bar.length().is_some() => {
+            bar.set_style(ProgressStyle::default_spinner());
+            bar.unset_length();
+        }
+        _ => {}
+    }
+    bar.set_position(current);
+    if let Some(message) = message {
+        bar.set_message(message);
     }
 }
@@ -0,0 +1,69 @@
+//! Provider login flows run when a profile is created.
+
+use crate::daemon::oauth::{self, TokenGrant, TokenPoll};
+use anyhow::{Result, anyhow};
+use ringlet_core::ProviderInfo;
+use std::time::{Duration, Instant};
+
+/// Sign in to a device-login provider and return the issued tokens.
+///
+/// Shows the verification URL and user code, tries to open the URL in a
+/// browser, then polls until the user approves, denies or the code expires.
+pub fn device_login(provider: &ProviderInfo) -> Result<TokenGrant> {
+    let config = provider.device_auth.as_ref().ok_or_else(|| {
+        anyhow!(
+            "Provider '{}' uses device login but its manifest has no [auth.device] section",
+            provider.id
+        )
+    })?;
+
+    let code = oauth::request_device_code(config)?;
+    let url = code
+        .verification_uri_complete
+        .as_deref()
+        .unwrap_or(&code.verification_uri);
+
+    eprintln!("To sign in to {}, open:", provider.name);
+    eprintln!("  {}", console::style(url).for_stderr().cyan());
+    eprintln!(
+        "and enter the code {}",
+        console::style(&code.user_code).for_stderr().bold()
+    );
+    open_in_browser(url);
+    eprintln!("Waiting for authorization...");
+
+    let deadline = Instant::now() + Duration::from_secs(code.expires_in);
+    let mut interval = Duration::from_secs(code.interval);
+    loop {
+        std::thread::sleep(interval);
+        if Instant::now() >= deadline {
+            return Err(anyhow!("The login code expired; run the command again"));
+        }
+        match oauth::poll_device_token(config, &code.device_code)? {
+            TokenPoll::Granted(grant) => return Ok(grant),
+            TokenPoll::Pending => {}
+            TokenPoll::SlowDown => {
+                interval += Duration::from_secs(oauth::DEFAULT_POLL_INTERVAL);
+            }
+            TokenPoll::Failed(error) => return Err(anyhow!("Login failed: {}", error)),
+        }
+    }
+}
+
+/// Best-effort attempt to open `url` in the user's browser.
+fn open_in_browser(url: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    let _ = command
+        .arg(url)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn();
+}
@@ -0,0 +1,511 @@
+//! `ringlet doctor` - one health report across the daemon, registry, agents,
+//! proxies, alias shims and ringlet's own directories.
+
+use crate::output;
+use anyhow::{Result, anyhow};
+use ringlet_client::{ClientError, DaemonClient};
+use ringlet_core::{AgentInfo, Diagnostics, PROTOCOL_VERSION, RegistryStatus, RingletPaths};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// One line of the doctor report.
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    /// Area the check belongs to, e.g. `daemon` or `registry`.
+    pub area: &'static str,
+    pub status: Status,
+    pub message: String,
+    /// What to do about a warning or failure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(area: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            area,
+            status: Status::Pass,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(area: &'static str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            area,
+            status: Status::Warn,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(area: &'static str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            area,
+            status: Status::Fail,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Run every check and print the report. Fails when any check failed.
+///
+/// Never starts the daemon: a stopped daemon is one of the things reported.
+pub fn run_doctor(json: bool) -> Result<()> {
+    let paths = RingletPaths::default();
+    let mut checks = Vec::new();
+
+    if let Some((client, diagnostics)) = daemon_check(&paths, &mut checks) {
+        match client.registry().inspect() {
+            Ok(status) => checks.push(registry_check(&status)),
+            Err(e) => checks.push(Check::fail(
+                "registry",
+                format!("Failed to read registry status: {}", e),
+                "Run `ringlet registry sync`",
+            )),
+        }
+        match client.agents().list() {
+            Ok(agents) => checks.extend(agent_checks(&agents)),
+            Err(e) => checks.push(Check::fail(
+                "agents",
+                format!("Failed to detect agents: {}", e),
+                "Run `ringlet agents list` for details",
+            )),
+        }
+        if let Some(diagnostics) = diagnostics {
+            checks.push(proxy_check(&diagnostics));
+            let path = std::env::var_os("PATH").unwrap_or_default();
+            let path_dirs: Vec<PathBuf> = std::env::split_paths(&path).collect();
+            checks.extend(shim_checks(&diagnostics.shims, &path_dirs));
+        }
+    }
+
+    checks.extend(directory_checks(&paths));
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&checks)?);
+    } else {
+        println!("{}", report(&checks));
+    }
+
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    if failed > 0 {
+        return Err(anyhow!("{} of {} checks failed", failed, checks.len()));
+    }
+    Ok(())
+}
+
+/// Format the checks, one per line with the hint underneath.
+fn report(checks: &[Check]) -> String {
+    let mut lines = Vec::new();
+    for check in checks {
+        let tag = match check.status {
+            Status::Pass => console::style("pass").green(),
+            Status::Warn => console::style("warn").yellow(),
+            Status::Fail => console::style("fail").red().bold(),
+        };
+        lines.push(format!("[{}] {}", tag, check.message));
+        if let Some(hint) = &check.hint {
+            lines.push(format!("       {}", console::style(hint).dim()));
+        }
+    }
+
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    lines.push(String::new());
+    lines.push(format!(
+        "{} passed, {} warnings, {} failed",
+        count(Status::Pass),
+        count(Status::Warn),
+        count(Status::Fail)
+    ));
+    lines.join("\n")
+}
+
+/// Check the daemon answers and runs this version.
+///
+/// Returns a client when it does, with the daemon's diagnostics if it reports them.
+fn daemon_check(
+    paths: &RingletPaths,
+    checks: &mut Vec<Check>,
+) -> Option<(DaemonClient, Option<Diagnostics>)> {
+    let Ok(client) = DaemonClient::connect_unchecked(paths) else {
+        checks.push(Check::fail(
+            "daemon",
+            "Daemon is not running; skipped the registry, agent, proxy and shim checks",
+            "Start it with `ringlet daemon`, or `ringlet daemon install-service` to start it at login",
+        ));
+        return None;
+    };
+
+    match client.check_protocol() {
+        Ok(()) => {}
+        Err(e @ ClientError::ProtocolMismatch { .. }) => {
+            checks.push(Check::fail(
+                "daemon",
+                e.to_string(),
+                "Restart it with `ringlet daemon stop`, then run any command",
+            ));
+            return None;
+        }
+        Err(e) => {
+            checks.push(Check::fail(
+                "daemon",
+                format!("Daemon is not responding: {}", e),
+                format!("Check its log at {}", paths.daemon_log().display()),
+            ));
+            return None;
+        }
+    }
+
+    let diagnostics = match client.diagnostics() {
+        Ok(diagnostics) => {
+            checks.push(version_check(&diagnostics.version, ringlet_core::VERSION));
+            Some(diagnostics)
+        }
+        Err(e) => {
+            checks.push(Check::warn(
+                "daemon",
+                format!("Daemon did not report its version: {}", e),
+                "Restart it with `ringlet daemon stop`, then run any command",
+            ));
+            None
+        }
+    };
+    Some((client, diagnostics))
+}
+
+fn version_check(daemon: &str, cli: &str) -> Check {
+    if daemon == cli {
+        Check::pass(
+            "daemon",
+            format!(
+                "Daemon running ringlet {} (protocol v{})",
+                daemon, PROTOCOL_VERSION
+            ),
+        )
+    } else {
+        Check::warn(
+            "daemon",
+            format!("Daemon runs ringlet {}, this CLI is {}", daemon, cli),
+            "Restart it with `ringlet daemon stop`, then run any command",
+        )
+    }
+}
+
+fn registry_check(status: &RegistryStatus) -> Check {
+    let age = output::registry_cache_age(status);
+    if status.last_sync.is_none() {
+        Check::warn(
+            "registry",
+            "Registry has never been synced",
+            "Run `ringlet registry sync`",
+        )
+    } else if status.stale {
+        Check::warn(
+            "registry",
+            format!("Registry cache age: {}", age),
+            "Run `ringlet registry sync`",
+        )
+    } else {
+        Check::pass("registry", format!("Registry cache age: {}", age))
+    }
+}
+
+/// Installed agents and agents that profiles need but are missing.
+fn agent_checks(agents: &[AgentInfo]) -> Vec<Check> {
+    let mut checks = Vec::new();
+    for agent in agents {
+        match (agent.installed, &agent.version) {
+            (true, Some(version)) => {
+                checks.push(Check::pass("agents", format!("{} {}", agent.name, version)));
+            }
+            (true, None) => checks.push(Check::warn(
+                "agents",
+                format!(
+                    "{} is installed but its version was not detected",
+                    agent.name
+                ),
+                format!(
+                    "Check that `{} --version` works",
+                    agent.binary_path.as_deref().unwrap_or(&agent.id)
+                ),
+            )),
+            (false, _) if agent.profile_count > 0 => checks.push(Check::fail(
+                "agents",
+                format!(
+                    "{} is not installed, but {} profile(s) use it",
+                    agent.name, agent.profile_count
+                ),
+                format!(
+                    "Install it or put it on PATH; see `ringlet agents inspect {}`",
+                    agent.id
+                ),
+            )),
+            (false, _) => {}
+        }
+    }
+    if !agents.iter().any(|a| a.installed) {
+        checks.push(Check::warn(
+            "agents",
+            "No coding agents are installed",
+            "Install one, then see `ringlet agents list`",
+        ));
+    }
+    checks
+}
+
+fn proxy_check(diagnostics: &Diagnostics) -> Check {
+    const HINT: &str = "Install ultrallm to ~/.local/bin or ~/.cargo/bin, then restart the daemon";
+    match (
+        &diagnostics.proxy_binary,
+        diagnostics.proxy_profiles.is_empty(),
+    ) {
+        (Some(binary), _) => {
+            Check::pass("proxy", format!("ultrallm found at {}", binary.display()))
+        }
+        (None, true) => Check::warn(
+            "proxy",
+            "ultrallm not found; proxy routing is unavailable",
+            HINT,
+        ),
+        (None, false) => Check::fail(
+            "proxy",
+            format!(
+                "ultrallm not found, but the proxy is enabled for: {}",
+                diagnostics.proxy_profiles.join(", ")
+            ),
+            HINT,
+        ),
+    }
+}
+
+/// Check installed shims still exist and that their directories are on `PATH`.
+fn shim_checks(shims: &[PathBuf], path_dirs: &[PathBuf]) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let mut dirs: BTreeMap<&Path, usize> = BTreeMap::new();
+    for shim in shims {
+        if !shim.exists() {
+            let alias = shim.file_name().unwrap_or_default().to_string_lossy();
+            checks.push(Check::warn(
+                "shims",
+                format!("Alias shim {} is missing", shim.display()),
+                format!("Run `ringlet aliases install {}`", alias),
+            ));
+        } else if let Some(dir) = shim.parent() {
+            *dirs.entry(dir).or_default() += 1;
+        }
+    }
+
+    for (dir, count) in dirs {
+        if path_dirs.iter().any(|p| p == dir) {
+            checks.push(Check::pass(
+                "shims",
+                format!(
+                    "{} alias shim(s) in {}, which is on PATH",
+                    count,
+                    dir.display()
+                ),
+            ));
+        } else {
+            checks.push(Check::warn(
+                "shims",
+                format!(
+                    "{} alias shim(s) in {}, which is not on PATH",
+                    count,
+                    dir.display()
+                ),
+                format!(
+                    "Add `export PATH=\"{}:$PATH\"` to your shell profile",
+                    dir.display()
+                ),
+            ));
+        }
+    }
+    checks
+}
+
+/// Check ringlet can write to its config, data and cache directories.
+fn directory_checks(paths: &RingletPaths) -> Vec<Check> {
+    let mut dirs = vec![("Config", &paths.config_dir)];
+    if paths.data_dir != paths.config_dir {
+        dirs.push(("Data", &paths.data_dir));
+    }
+    dirs.push(("Cache", &paths.cache_dir));
+
+    dirs.into_iter()
+        .map(|(label, dir)| directory_check(label, dir))
+        .collect()
+}
+
+fn directory_check(label: &str, dir: &Path) -> Check {
+    if !dir.exists() {
+        return Check::warn(
+            "directories",
+            format!("{} directory {} does not exist", label, dir.display()),
+            "It is created when the daemon starts; run `ringlet daemon`",
+        );
+    }
+
+    let probe = dir.join(format!(".ringlet-doctor-{}", std::process::id()));
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            Check::pass(
+                "directories",
+                format!("{} directory {} is writable", label, dir.display()),
+            )
+        }
+        Err(e) => Check::fail(
+            "directories",
+            format!(
+                "Cannot write to {} directory {}: {}",
+                label,
+                dir.display(),
+                e
+            ),
+            format!("Fix its ownership, e.g. `chown -R $USER {}`", dir.display()),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shim_checks() {
+        let dir = tempfile::tempdir().unwrap();
+        let shim = dir.path().join("work");
+        std::fs::write(&shim, "").unwrap();
+        let missing = dir.path().join("gone");
+
+        let checks = shim_checks(&[shim.clone(), missing], &[]);
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].status, Status::Warn);
+        assert!(checks[0].message.contains("gone is missing"));
+        assert!(checks[1].message.contains("not on PATH"));
+
+        let checks = shim_checks(&[shim], &[dir.path().to_path_buf()]);
+        assert_eq!(checks[0].status, Status::Pass);
+    }
+
+    #[test]
+    fn test_proxy_check() {
+        let mut diagnostics = Diagnostics {
+            version: "1.0.0".to_string(),
+            proxy_binary: None,
+            proxy_profiles: Vec::new(),
+            shims: Vec::new(),
+        };
+        assert_eq!(proxy_check(&diagnostics).status, Status::Warn);
+
+        diagnostics.proxy_profiles.push("work".to_string());
+        assert_eq!(proxy_check(&diagnostics).status, Status::Fail);
+
+        diagnostics.proxy_binary = Some(PathBuf::from("/usr/bin/ultrallm"));
+        assert_eq!(proxy_check(&diagnostics).status, Status::Pass);
+    }
+
+    fn agent(id: &str, installed: bool, profile_count: usize) -> AgentInfo {
+        AgentInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            installed,
+            version: installed.then(|| "1.0.0".to_string()),
+            binary_path: None,
+            profile_count,
+            default_model: None,
+            default_provider: None,
+            supports_hooks: false,
+            last_used: None,
+            compatible_providers: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_agent_checks() {
+        let checks = agent_checks(&[agent("claude", true, 1), agent("codex", false, 0)]);
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].status, Status::Pass);
+
+        // A profile needs an agent that isn't installed
+        let checks = agent_checks(&[agent("claude", true, 0), agent("codex", false, 2)]);
+        assert_eq!(checks[1].status, Status::Fail);
+        assert!(checks[1].message.contains("2 profile(s) use it"));
+        assert!(checks[1].hint.as_deref().unwrap().contains("inspect codex"));
+
+        let checks = agent_checks(&[agent("codex", false, 0)]);
+        assert_eq!(checks.len(), 1);
+        assert!(checks[0].message.contains("No coding agents"));
+    }
+
+    #[test]
+    fn test_registry_check() {
+        let mut status = RegistryStatus {
+            commit: None,
+            channel: "stable".to_string(),
+            last_sync: None,
+            offline: false,
+            cached_agents: 0,
+            cached_providers: 0,
+            cached_scripts: 0,
+            cache_age_secs: None,
+            cache_ttl_secs: 86400,
+            stale: true,
+            from_cache: false,
+            warning: None,
+        };
+        let check = registry_check(&status);
+        assert_eq!(check.status, Status::Warn);
+        assert_eq!(check.message, "Registry has never been synced");
+
+        status.last_sync = Some(chrono::Utc::now());
+        status.cache_age_secs = Some(2 * 86400);
+        let check = registry_check(&status);
+        assert_eq!(check.status, Status::Warn);
+        assert!(check.message.contains("stale"));
+        assert!(check.hint.as_deref().unwrap().contains("registry sync"));
+
+        status.cache_age_secs = Some(60);
+        status.stale = false;
+        assert_eq!(registry_check(&status).status, Status::Pass);
+    }
+
+    #[test]
+    fn test_directory_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let check = directory_check("Data", dir.path());
+        assert_eq!(check.status, Status::Pass);
+        // The write probe is cleaned up
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+        let missing = dir.path().join("missing");
+        let check = directory_check("Data", &missing);
+        assert_eq!(check.status, Status::Warn);
+        assert!(check.message.contains("does not exist"));
+
+        // Permissions don't stop root, so use a file where the directory should be
+        let unwritable = dir.path().join("file");
+        std::fs::write(&unwritable, "").unwrap();
+        let check = directory_check("Data", &unwritable);
+        assert_eq!(check.status, Status::Fail);
+        assert!(check.message.starts_with("Cannot write to Data directory"));
+    }
+
+    #[test]
+    fn test_version_check() {
+        assert_eq!(version_check("1.2.0", "1.2.0").status, Status::Pass);
+        assert_eq!(version_check("1.1.0", "1.2.0").status, Status::Warn);
+    }
+}
@@ -3,7 +3,8 @@
 use crate::client::DaemonClient;
 use anyhow::{Result, anyhow};
 use dialoguer::{Confirm, Input, Password, Select, theme::ColorfulTheme};
-use ringlet_core::{AgentInfo, ProfileCreateRequest, ProviderInfo, Request, Response};
+use ringlet_client::ClientError;
+use ringlet_core::{AgentInfo, AuthKind, ProfileCreateRequest, ProviderInfo};
 
 /// Run the interactive init wizard.
 pub async fn run_init(
@@ -178,22 +179,18 @@ pub async fn run_init(
 
 /// Fetch agents from daemon.
 fn fetch_agents(client: &DaemonClient) -> Result<Vec<AgentInfo>> {
-    let response = client.request(&Request::AgentsList)?;
-    match response {
-        Response::Agents(agents) => Ok(agents),
-        Response::Error { message, .. } => Err(anyhow!("Failed to list agents: {}", message)),
-        _ => Err(anyhow!("Unexpected response from daemon")),
-    }
+    client.agents().list().map_err(|e| match e {
+        ClientError::Daemon { message, .. } => anyhow!("Failed to list agents: {}", message),
+        e => e.into(),
+    })
 }
 
 /// Fetch providers from daemon.
 fn fetch_providers(client: &DaemonClient) -> Result<Vec<ProviderInfo>> {
-    let response = client.request(&Request::ProvidersList)?;
-    match response {
-        Response::Providers(providers) => Ok(providers),
-        Response::Error { message, .. } => Err(anyhow!("Failed to list providers: {}", message)),
-        _ => Err(anyhow!("Unexpected response from daemon")),
-    }
+    client.providers().list(None, None).map_err(|e| match e {
+        ClientError::Daemon { message, .. } => anyhow!("Failed to list providers: {}", message),
+        e => e.into(),
+    })
 }
 
 /// Create the user's first profile interactively.
@@ -260,8 +257,15 @@ async fn create_first_profile(
         .default(default_alias)
         .interact_text()?;
 
-    // Get API key if required
-    let api_key = if selected_provider.auth_required {
+    // Get credentials if required
+    let mut refresh_token = None;
+    let mut token_expires_at = None;
+    let api_key = if selected_provider.auth_kind == AuthKind::OauthDevice {
+        let grant = super::auth::device_login(selected_provider)?;
+        token_expires_at = grant.expires_at(chrono::Utc::now());
+        refresh_token = grant.refresh_token;
+        grant.access_token
+    } else if selected_provider.auth_required {
         let prompt = if selected_provider.auth_prompt.is_empty() {
             format!("Enter {} API key", selected_provider.name)
         } else {
@@ -283,6 +287,8 @@ async fn create_first_profile(
         endpoint_id: None,
         model: None,
         api_key,
+        refresh_token,
+        token_expires_at,
         hooks: vec![],
         mcp_servers: vec![],
         args: vec![],
@@ -290,31 +296,27 @@ async fn create_first_profile(
         bare: false,
         proxy: false,
         no_alias: false, // Auto-install alias for init-created profiles
+        launch_wrapper: None,
     };
 
-    let response = client.request(&Request::ProfilesCreate(request))?;
-    match response {
-        Response::Success { message } => {
-            if json {
-                println!(
-                    "{}",
-                    serde_json::json!({
-                        "profile_created": true,
-                        "alias": alias,
-                        "message": message
-                    })
-                );
-            } else {
-                println!();
-                println!("Profile '{}' created successfully!", alias);
-                println!();
-                println!("Run it with: ringlet profiles run {}", alias);
-            }
-        }
-        Response::Error { message, .. } => {
-            return Err(anyhow!("Failed to create profile: {}", message));
-        }
-        _ => return Err(anyhow!("Unexpected response")),
+    let message = client.profiles().create(request).map_err(|e| match e {
+        ClientError::Daemon { message, .. } => anyhow!("Failed to create profile: {}", message),
+        e => e.into(),
+    })?;
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "profile_created": true,
+                "alias": alias,
+                "message": message
+            })
+        );
+    } else {
+        println!();
+        println!("Profile '{}' created successfully!", alias);
+        println!();
+        println!("Run it with: ringlet profiles run {}", alias);
     }
 
     Ok(())
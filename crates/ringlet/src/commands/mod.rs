@@ -1,20 +1,37 @@
 //! Command implementations.
 
+mod auth;
+mod doctor;
 mod init;
+mod service;
 
 use crate::client::DaemonClient;
+use crate::daemon::budget;
+use crate::daemon::execution::{self, AgentProcess};
+use crate::daemon::pricing::PricingLoader;
 use crate::output;
 use crate::{
-    AgentsCommands, AliasesCommands, Commands, DaemonCommands, EnvCommands, HooksCommands,
-    ProfilesCommands, ProvidersCommands, ProxyAliasCommands, ProxyCommands, ProxyRouteCommands,
-    RegistryCommands, TerminalCommands, UsageCommands,
+    AgentsCommands, AliasesCommands, Commands, ConfigCommands, DaemonCommands, EnvCommands,
+    EventsCommands, HooksCommands, HooksDefaultsCommands, PricingCommands, ProfilesCommands,
+    ProvidersCommands, ProxyAliasCommands, ProxyCommands, ProxyRouteCommands, RegistryCommands,
+    ScriptsCommands, TerminalCommands, UsageCommands,
 };
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result, anyhow};
+use futures_util::{SinkExt, StreamExt};
+use ringlet_client::{ClientError, NativeHooks};
 use ringlet_core::{
-    HooksConfig, ProfileCreateRequest, Request, Response, RingletPaths, RoutingCondition,
-    RoutingRule, UsagePeriod, UserConfig,
+    AgentType, AuthKind, ClientMessage, EVENT_TOPICS, HooksConfig, LocalProviderRequest,
+    ProfileCreateRequest, RingletPaths, RoutingCondition, RoutingRule, ServerMessage, TokenUsage,
+    UsagePeriod, UsageStatsResponse, UserConfig,
 };
+use std::collections::HashMap;
+use std::io::IsTerminal;
 use std::process::{Command, Stdio};
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::{Message, client::IntoClientRequest};
+
+/// How often `events watch` pings the daemon so it doesn't idle out.
+const EVENTS_KEEPALIVE: Duration = Duration::from_secs(60);
 
 /// Get the HTTP API base URL from config.
 fn get_http_api_base() -> String {
@@ -34,6 +51,10 @@ fn load_http_token() -> Option<String> {
 
 /// Execute a command.
 pub async fn execute(command: &Commands, json: bool) -> Result<()> {
+    if !json && !matches!(command, Commands::Daemon { .. }) {
+        show_budget_alerts();
+    }
+
     match command {
         Commands::Init {
             skip_daemon,
@@ -49,14 +70,26 @@ pub async fn execute(command: &Commands, json: bool) -> Result<()> {
         Commands::Usage {
             command,
             period,
+            from,
+            to,
             profile,
             model,
+            agent,
         } => {
+            // clap guarantees --from and --to are given together
+            let period = match (from, to) {
+                (Some(start), Some(end)) => UsagePeriod::DateRange {
+                    start: start.clone(),
+                    end: end.clone(),
+                },
+                _ => period.clone(),
+            };
             execute_usage(
                 command.as_ref(),
                 period,
                 profile.as_deref(),
                 model.as_deref(),
+                *agent,
                 json,
             )
             .await
@@ -67,6 +100,7 @@ pub async fn execute(command: &Commands, json: bool) -> Result<()> {
             socket,
             foreground,
             daemon_log_level,
+            ..
         } => {
             execute_daemon(
                 command,
@@ -79,9 +113,20 @@ pub async fn execute(command: &Commands, json: bool) -> Result<()> {
             .await
         }
         Commands::Env { command } => execute_env(command, json).await,
+        Commands::Config { command } => execute_config(command, json),
+        Commands::Doctor => doctor::run_doctor(json),
+        Commands::Export { out } => execute_export(out, json),
+        Commands::Import {
+            bundle,
+            overwrite,
+            force,
+        } => execute_import(bundle, *overwrite, *force, json),
+        Commands::Pricing { command } => execute_pricing(command, json),
+        Commands::Scripts { command } => execute_scripts(command),
         Commands::Hooks { command } => execute_hooks(command, json).await,
         Commands::Proxy { command } => execute_proxy(command, json).await,
         Commands::Terminal { command } => execute_terminal(command, json).await,
+        Commands::Events { command } => execute_events(command, json).await,
         #[cfg(feature = "gui")]
         Commands::Gui {
             standalone,
@@ -95,36 +140,38 @@ pub async fn execute(command: &Commands, json: bool) -> Result<()> {
     }
 }
 
+/// Print budget alerts raised by the daemon since the last command.
+fn show_budget_alerts() {
+    let paths = RingletPaths::default();
+    for alert in budget::take_pending_alerts(&paths) {
+        eprintln!(
+            "{}",
+            output::budget_alert(alert.period, alert.limit, alert.actual)
+        );
+    }
+}
+
 async fn execute_agents(command: &AgentsCommands, json: bool) -> Result<()> {
     let client = DaemonClient::connect()?;
 
     match command {
-        AgentsCommands::List => {
-            let response = client.request(&Request::AgentsList)?;
-            match response {
-                Response::Agents(agents) => {
-                    if json {
-                        println!("{}", serde_json::to_string_pretty(&agents)?);
-                    } else {
-                        println!("{}", output::agents_table(&agents));
-                    }
-                }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
+        AgentsCommands::List { installed_only } => {
+            let mut agents = client.agents().list()?;
+            if *installed_only {
+                agents.retain(|agent| agent.installed);
+            }
+            if json {
+                println!("{}", serde_json::to_string_pretty(&agents)?);
+            } else {
+                println!("{}", output::agents_table(&agents));
             }
         }
         AgentsCommands::Inspect { id } => {
-            let response = client.request(&Request::AgentsInspect { id: id.clone() })?;
-            match response {
-                Response::Agent(agent) => {
-                    if json {
-                        println!("{}", serde_json::to_string_pretty(&agent)?);
-                    } else {
-                        println!("{}", output::agent_detail(&agent));
-                    }
-                }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
+            let agent = client.agents().inspect(id)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&agent)?);
+            } else {
+                println!("{}", output::agent_detail(&agent));
             }
         }
     }
@@ -136,32 +183,84 @@ async fn execute_providers(command: &ProvidersCommands, json: bool) -> Result<()
     let client = DaemonClient::connect()?;
 
     match command {
-        ProvidersCommands::List => {
-            let response = client.request(&Request::ProvidersList)?;
-            match response {
-                Response::Providers(providers) => {
-                    if json {
-                        println!("{}", serde_json::to_string_pretty(&providers)?);
-                    } else {
-                        println!("{}", output::providers_table(&providers));
-                    }
-                }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
+        ProvidersCommands::List {
+            provider_type,
+            search,
+        } => {
+            let providers = client
+                .providers()
+                .list(provider_type.as_deref(), search.as_deref())?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&providers)?);
+            } else {
+                println!("{}", output::providers_table(&providers));
             }
         }
         ProvidersCommands::Inspect { id } => {
-            let response = client.request(&Request::ProvidersInspect { id: id.clone() })?;
-            match response {
-                Response::Provider(provider) => {
-                    if json {
-                        println!("{}", serde_json::to_string_pretty(&provider)?);
+            let provider = client.providers().inspect(id)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&provider)?);
+            } else {
+                println!("{}", output::provider_detail(&provider));
+            }
+        }
+        ProvidersCommands::Check {
+            id,
+            endpoint,
+            api_key,
+        } => {
+            let info = client.providers().inspect(id)?;
+
+            // Without a key only reachability is checked
+            let api_key = match api_key {
+                Some(key) => Some(key.clone()),
+                None if info.auth_required && !json && std::io::stdin().is_terminal() => {
+                    let prompt = if info.auth_prompt.is_empty() {
+                        "Enter API key".to_string()
                     } else {
-                        println!("{}", output::provider_detail(&provider));
-                    }
+                        info.auth_prompt
+                    };
+                    Some(dialoguer::Password::new().with_prompt(&prompt).interact()?)
+                        .filter(|key: &String| !key.is_empty())
                 }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
+                None => None,
+            };
+
+            let check = client.providers().check(id, endpoint.as_deref(), api_key)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&check)?);
+            } else {
+                println!("{}", output::provider_check(&check));
+            }
+            if !check.reachable || check.auth_ok == Some(false) {
+                std::process::exit(1);
+            }
+        }
+        ProvidersCommands::AddLocal {
+            id,
+            url,
+            name,
+            provider_type,
+            auth_header,
+            auth_prefix,
+            no_auth,
+            model,
+        } => {
+            let provider = client.providers().add_local(LocalProviderRequest {
+                id: id.clone(),
+                url: url.clone(),
+                name: name.clone(),
+                provider_type: Some(provider_type.clone()),
+                auth_header: auth_header.clone(),
+                auth_prefix: auth_prefix.clone(),
+                no_auth: *no_auth,
+                model: model.clone(),
+            })?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&provider)?);
+            } else {
+                println!("Registered provider '{}'", provider.id);
+                println!("{}", output::provider_detail(&provider));
             }
         }
     }
@@ -185,33 +284,34 @@ async fn execute_profiles(command: &ProfilesCommands, json: bool) -> Result<()>
             bare,
             proxy,
             no_alias,
+            wrapper,
+            default_args,
         } => {
             // Get provider info to check if auth is required
-            let provider_response = client.request(&Request::ProvidersInspect {
-                id: provider.clone(),
-            })?;
-            let (auth_required, auth_prompt) = match provider_response {
-                Response::Provider(info) => (info.auth_required, info.auth_prompt),
-                Response::Error { message, .. } => return Err(anyhow!("{}", message)),
-                _ => return Err(anyhow!("Unexpected response")),
-            };
-
-            // Only prompt for API key if auth is required
-            let api_key = if auth_required {
-                match api_key {
-                    Some(key) => key.clone(),
-                    None => {
-                        let prompt = if auth_prompt.is_empty() {
-                            "Enter API key".to_string()
-                        } else {
-                            auth_prompt
-                        };
-                        dialoguer::Password::new().with_prompt(&prompt).interact()?
-                    }
+            let provider_info = client.providers().inspect(provider)?;
+
+            // Providers declaring a device login always log in; others only
+            // ask for a key when auth is required
+            let mut refresh_token = None;
+            let mut token_expires_at = None;
+            let api_key = match (api_key, provider_info.auth_kind) {
+                (Some(key), _) => key.clone(),
+                (None, AuthKind::OauthDevice) => {
+                    let grant = auth::device_login(&provider_info)?;
+                    token_expires_at = grant.expires_at(chrono::Utc::now());
+                    refresh_token = grant.refresh_token;
+                    grant.access_token
+                }
+                (None, AuthKind::ApiKey) if provider_info.auth_required => {
+                    let prompt = if provider_info.auth_prompt.is_empty() {
+                        "Enter API key".to_string()
+                    } else {
+                        provider_info.auth_prompt
+                    };
+                    dialoguer::Password::new().with_prompt(&prompt).interact()?
                 }
-            } else {
                 // Self-authenticating provider, no API key needed
-                String::new()
+                (None, AuthKind::ApiKey) => String::new(),
             };
 
             let hooks_vec = hooks
@@ -231,60 +331,54 @@ async fn execute_profiles(command: &ProfilesCommands, json: bool) -> Result<()>
                 endpoint_id: endpoint.clone(),
                 model: model.clone(),
                 api_key,
+                refresh_token,
+                token_expires_at,
                 hooks: hooks_vec,
                 mcp_servers: mcp_vec,
-                args: vec![],
+                args: default_args
+                    .as_ref()
+                    .map(|a| a.split_whitespace().map(str::to_string).collect())
+                    .unwrap_or_default(),
                 working_dir: None,
                 bare: *bare,
                 proxy: *proxy,
                 no_alias: *no_alias,
+                launch_wrapper: wrapper.as_deref().map(split_wrapper).transpose()?,
             };
 
-            let response = client.request(&Request::ProfilesCreate(request))?;
-            match response {
-                Response::Success { message } => {
-                    if json {
-                        println!("{}", serde_json::json!({"success": message}));
-                    } else {
-                        output::success(&message);
-                    }
-                }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
-            }
+            print_success(&client.profiles().create(request)?, json);
         }
-        ProfilesCommands::List { agent } => {
-            let response = client.request(&Request::ProfilesList {
-                agent_id: agent.clone(),
-            })?;
-            match response {
-                Response::Profiles(profiles) => {
-                    if json {
-                        println!("{}", serde_json::to_string_pretty(&profiles)?);
-                    } else if profiles.is_empty() {
-                        println!("No profiles found");
-                    } else {
-                        println!("{}", output::profiles_table(&profiles));
-                    }
-                }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
+        ProfilesCommands::List {
+            agent,
+            provider,
+            names_only,
+        } => {
+            if *names_only && agent.is_none() && provider.is_none() {
+                return print_names(&client.profiles().names()?, json);
+            }
+            let profiles = client
+                .profiles()
+                .list(agent.as_deref(), provider.as_deref())?;
+            if *names_only {
+                let names: Vec<String> = profiles.into_iter().map(|p| p.alias).collect();
+                print_names(&names, json)?;
+            } else if json {
+                println!("{}", serde_json::to_string_pretty(&profiles)?);
+            } else if profiles.is_empty() {
+                println!("No profiles found");
+            } else {
+                println!("{}", output::profiles_table(&profiles));
             }
         }
-        ProfilesCommands::Inspect { alias } => {
-            let response = client.request(&Request::ProfilesInspect {
-                alias: alias.clone(),
-            })?;
-            match response {
-                Response::Profile(profile) => {
-                    if json {
-                        println!("{}", serde_json::to_string_pretty(&profile)?);
-                    } else {
-                        println!("{}", output::profile_detail(&profile));
-                    }
-                }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
+        ProfilesCommands::Inspect {
+            alias,
+            show_secrets,
+        } => {
+            let profile = client.profiles().inspect(alias, *show_secrets)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&profile)?);
+            } else {
+                println!("{}", output::profile_detail(&profile));
             }
         }
         ProfilesCommands::Run {
@@ -294,6 +388,10 @@ async fn execute_profiles(command: &ProfilesCommands, json: bool) -> Result<()>
             rows,
             no_sandbox,
             bwrap_flags,
+            persist,
+            env_file,
+            ephemeral_proxy,
+            log,
             args,
         } => {
             if *remote {
@@ -305,22 +403,44 @@ async fn execute_profiles(command: &ProfilesCommands, json: bool) -> Result<()>
                     *rows,
                     *no_sandbox,
                     bwrap_flags.as_deref(),
+                    *persist,
                     json,
                 )
                 .await;
             }
 
             // Get execution context from daemon (prepares config files, env, etc.)
-            let response = client.request(&Request::ProfilesPrepare {
-                alias: alias.clone(),
-                args: args.clone(),
-            })?;
+            let env = match env_file {
+                Some(path) => {
+                    let content = std::fs::read_to_string(path)
+                        .with_context(|| format!("Failed to read {}", path.display()))?;
+                    ringlet_core::dotenv::parse(&content)
+                        .map_err(|e| anyhow!("{}: {}", path.display(), e))?
+                }
+                None => HashMap::new(),
+            };
 
-            let context = match response {
-                Response::ExecutionContext(ctx) => ctx,
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
+            let context = client.profiles().prepare(
+                alias,
+                args.clone(),
+                env,
+                *ephemeral_proxy,
+                std::env::current_dir().ok(),
+            )?;
+            let mut prepared = PreparedRun {
+                client: &client,
+                run_id: context.run_id.clone(),
             };
+
+            if !json
+                && let Some(summary) =
+                    output::config_write_summary(context.files_changed, context.files_unchanged)
+            {
+                eprintln!("{}", summary);
+            }
+
+            execution::run_pre_run_hook(&context)?;
+
             let started_at = chrono::Utc::now();
 
             // Spawn the agent directly in CLI process (inherits our TTY)
@@ -338,35 +458,68 @@ async fn execute_profiles(command: &ProfilesCommands, json: bool) -> Result<()>
             // Add arguments
             cmd.args(&context.args);
 
-            // Spawn and wait
-            let mut child = cmd
-                .spawn()
-                .map_err(|e| anyhow!("Failed to spawn {}: {}", context.binary, e))?;
+            // An explicit --log file is replaced; captured run logs never are
+            let log_path = log.clone().or_else(|| context.log_path.clone());
+            let log_file = match &log_path {
+                Some(path) if log.is_some() => {
+                    Some(std::fs::File::create(path).map_err(|e| {
+                        anyhow!("Failed to create log file {}: {}", path.display(), e)
+                    })?)
+                }
+                Some(path) => Some(execution::create_run_log(path)?),
+                None => None,
+            };
 
-            let status = child
-                .wait()
-                .map_err(|e| anyhow!("Failed to wait for process: {}", e))?;
+            // Spawn in its own process group and wait, forwarding Ctrl-C/SIGTERM
+            let agent = match log_file {
+                Some(log_file) => AgentProcess::spawn_logged(&cmd, log_file),
+                None => AgentProcess::spawn(&mut cmd),
+            }
+            .map_err(|e| anyhow!("Failed to spawn {}: {}", context.binary, e))?;
+            let status = agent.wait().await?;
 
-            let exit_code = status.code().unwrap_or(-1);
+            // The agent's run ends here; the post-run hook's time isn't part of it
             let ended_at = chrono::Utc::now();
-
-            if let Some(run_id) = &context.run_id {
-                match client.request(&Request::ProfilesComplete {
-                    run_id: run_id.clone(),
-                    started_at,
-                    ended_at,
-                    exit_code,
-                })? {
-                    Response::RunCompleted { .. } => {}
-                    Response::Error { message, .. } => {
-                        return Err(anyhow!("Failed to record run telemetry: {}", message));
-                    }
-                    _ => return Err(anyhow!("Unexpected response")),
-                }
+            let (exit_code, signal) = execution::exit_details(&status);
+            if let Err(e) = execution::run_post_run_hook(&context) {
+                eprintln!("Warning: {:#}", e);
+            }
+            let duration_secs = ended_at
+                .signed_duration_since(started_at)
+                .num_seconds()
+                .max(0) as u64;
+
+            if let Some(run_id) = prepared.run_id.take() {
+                client
+                    .profiles()
+                    .complete(&run_id, started_at, ended_at, exit_code, signal)
+                    .map_err(|e| match e {
+                        ClientError::Daemon { message, .. } => {
+                            anyhow!("Failed to record run telemetry: {}", message)
+                        }
+                        e => e.into(),
+                    })?;
             }
 
             if json {
-                println!("{}", serde_json::json!({"exit_code": exit_code}));
+                println!(
+                    "{}",
+                    serde_json::json!({
+                        "exit_code": exit_code,
+                        "signal": signal,
+                        "duration_secs": duration_secs,
+                        "files_changed": context.files_changed,
+                        "files_unchanged": context.files_unchanged,
+                        "log_path": log_path,
+                    })
+                );
+            } else {
+                if let Some(summary) = output::run_exit_summary(exit_code, signal, duration_secs) {
+                    eprintln!("{}", summary);
+                }
+                if let Some(path) = &log_path {
+                    eprintln!("Output saved to {}", path.display());
+                }
             }
 
             // Exit with the agent's exit code
@@ -374,79 +527,251 @@ async fn execute_profiles(command: &ProfilesCommands, json: bool) -> Result<()>
                 std::process::exit(exit_code);
             }
         }
-        ProfilesCommands::Delete { alias } => {
-            let response = client.request(&Request::ProfilesDelete {
-                alias: alias.clone(),
-            })?;
-            match response {
-                Response::Success { message } => {
-                    if json {
-                        println!("{}", serde_json::json!({"success": message}));
-                    } else {
-                        output::success(&message);
+        ProfilesCommands::Delete {
+            alias,
+            agent,
+            provider,
+            yes,
+        } => {
+            let message = match alias {
+                Some(alias) => {
+                    if !*yes && !confirm_delete(alias, json)? {
+                        return Ok(());
                     }
+                    client.profiles().delete(alias)?
                 }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
-            }
+                None => {
+                    if !*yes && !confirm_bulk_delete(&client, agent, provider, json)? {
+                        return Ok(());
+                    }
+                    client
+                        .profiles()
+                        .delete_matching(agent.as_deref(), provider.as_deref())?
+                }
+            };
+            print_success(&message, json);
         }
         ProfilesCommands::Env { alias } => {
-            let response = client.request(&Request::ProfilesEnv {
-                alias: alias.clone(),
-            })?;
-            match response {
-                Response::Env(env) => {
-                    if json {
-                        println!("{}", serde_json::to_string_pretty(&env)?);
+            let env = client.profiles().env(alias)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&env)?);
+            } else {
+                println!("{}", output::env_export(&env));
+            }
+        }
+        ProfilesCommands::Rename { old, new } => {
+            print_success(&client.profiles().rename(old, new)?, json);
+        }
+        ProfilesCommands::History { alias, limit } => {
+            let entries = client.profiles().history(alias, *limit)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&entries)?);
+            } else if entries.is_empty() {
+                println!("No runs recorded for '{}'", alias);
+            } else {
+                println!("{}", output::run_history_table(&entries));
+            }
+        }
+        ProfilesCommands::SetHook {
+            alias,
+            stage,
+            command,
+        } => {
+            let message = client
+                .profiles()
+                .set_hook(alias, stage, command.as_deref())?;
+            print_success(&message, json);
+        }
+        ProfilesCommands::SetArgs { alias, args } => {
+            print_success(&client.profiles().set_args(alias, args.clone())?, json);
+        }
+        ProfilesCommands::SetQuota {
+            alias,
+            daily,
+            monthly,
+        } => {
+            print_success(&client.profiles().set_quota(alias, *daily, *monthly)?, json);
+        }
+        ProfilesCommands::SetWrapper { alias, command } => {
+            let wrapper = command.as_deref().map(split_wrapper).transpose()?;
+            print_success(&client.profiles().set_launch_wrapper(alias, wrapper)?, json);
+        }
+        ProfilesCommands::SetCaptureLogs { alias, enabled } => {
+            print_success(&client.profiles().set_capture_logs(alias, *enabled)?, json);
+        }
+        ProfilesCommands::SetApiKey { alias, api_key } => {
+            let api_key = match api_key {
+                Some(key) => key.clone(),
+                None => {
+                    let profile = client.profiles().inspect(alias, false)?;
+                    let provider = client.providers().inspect(&profile.provider_id)?;
+                    let prompt = if provider.auth_prompt.is_empty() {
+                        "Enter API key".to_string()
                     } else {
-                        println!("{}", output::env_export(&env));
-                    }
+                        provider.auth_prompt
+                    };
+                    dialoguer::Password::new().with_prompt(&prompt).interact()?
                 }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
+            };
+            print_success(&client.profiles().set_api_key(alias, &api_key)?, json);
+        }
+        ProfilesCommands::Doctor { fix, yes } => {
+            let remove_homes = match (*fix, *yes) {
+                (true, false) => {
+                    let found = client.profiles().doctor(false, false)?;
+                    confirm_remove_homes(&found.orphaned_homes, json)?
+                }
+                (fix, yes) => fix && yes,
+            };
+            let report = client.profiles().doctor(*fix, remove_homes)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!("{}", output::profile_doctor_report(&report));
             }
+            if !report.failures.is_empty() {
+                return Err(anyhow!(
+                    "Failed to clean up: {}",
+                    report.failures.join("; ")
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A run from `profiles().prepare` that is abandoned on drop unless completed,
+/// so an agent that never starts doesn't keep its ephemeral proxy running.
+struct PreparedRun<'a> {
+    client: &'a DaemonClient,
+    /// Taken when the run is completed.
+    run_id: Option<String>,
+}
+
+impl Drop for PreparedRun<'_> {
+    fn drop(&mut self) {
+        if let Some(run_id) = self.run_id.take()
+            && let Err(e) = self.client.profiles().abandon(&run_id)
+        {
+            eprintln!("Warning: failed to abandon run {}: {}", run_id, e);
         }
     }
+}
+
+/// Ask before `profiles doctor --fix` deletes orphaned homes.
+///
+/// Without a terminal to ask on, the homes are kept.
+fn confirm_remove_homes(homes: &[std::path::PathBuf], json: bool) -> Result<bool> {
+    if homes.is_empty() || json || !std::io::stdin().is_terminal() {
+        return Ok(false);
+    }
 
+    eprintln!("Orphaned homes:");
+    for home in homes {
+        eprintln!("  {}", home.display());
+    }
+    Ok(dialoguer::Confirm::new()
+        .with_prompt(format!(
+            "Delete {} orphaned home(s)? They may contain agent history.",
+            homes.len()
+        ))
+        .default(false)
+        .interact()?)
+}
+
+/// Print bare names, one per line, for scripts and shell completion.
+/// Split a launch wrapper command line into its template, honouring quotes.
+fn split_wrapper(command: &str) -> Result<Vec<String>> {
+    shell_words::split(command).map_err(|e| anyhow!("Invalid wrapper command: {}", e))
+}
+
+fn print_names(names: &[String], json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string(names)?);
+    } else {
+        for name in names {
+            println!("{}", name);
+        }
+    }
     Ok(())
 }
 
+/// Ask before deleting a single profile.
+fn confirm_delete(alias: &str, json: bool) -> Result<bool> {
+    require_prompt(
+        json,
+        format!(
+            "Pass --yes to delete profile '{}' without confirmation",
+            alias
+        ),
+    )?;
+
+    Ok(dialoguer::Confirm::new()
+        .with_prompt(format!("Delete profile '{}'?", alias))
+        .default(false)
+        .interact()?)
+}
+
+/// List the profiles a filtered delete would remove and ask before proceeding.
+fn confirm_bulk_delete(
+    client: &DaemonClient,
+    agent: &Option<String>,
+    provider: &Option<String>,
+    json: bool,
+) -> Result<bool> {
+    require_prompt(
+        json,
+        "Pass --yes to delete multiple profiles without confirmation".to_string(),
+    )?;
+
+    let profiles = client
+        .profiles()
+        .list(agent.as_deref(), provider.as_deref())?;
+
+    let Some(prompt) = bulk_delete_prompt(profiles.len()) else {
+        println!("No profiles found");
+        return Ok(false);
+    };
+
+    println!("{}", output::profiles_table(&profiles));
+    Ok(dialoguer::Confirm::new()
+        .with_prompt(prompt)
+        .default(false)
+        .interact()?)
+}
+
+/// Fail with `refusal` when a confirmation prompt can't be shown: in JSON
+/// mode, or when stdin isn't a terminal.
+fn require_prompt(json: bool, refusal: String) -> Result<()> {
+    can_prompt(json, std::io::stdin().is_terminal())
+        .then_some(())
+        .ok_or_else(|| anyhow!(refusal))
+}
+
+fn can_prompt(json: bool, interactive: bool) -> bool {
+    !json && interactive
+}
+
+/// The confirmation prompt for deleting `count` profiles, or `None` when no
+/// profile matches.
+fn bulk_delete_prompt(count: usize) -> Option<String> {
+    match count {
+        0 => None,
+        1 => Some("Delete 1 profile?".to_string()),
+        n => Some(format!("Delete {} profiles?", n)),
+    }
+}
+
 async fn execute_aliases(command: &AliasesCommands, json: bool) -> Result<()> {
     let client = DaemonClient::connect()?;
 
     match command {
         AliasesCommands::Install { alias, bin_dir } => {
-            let response = client.request(&Request::AliasesInstall {
-                alias: alias.clone(),
-                bin_dir: bin_dir.clone(),
-            })?;
-            match response {
-                Response::Success { message } => {
-                    if json {
-                        println!("{}", serde_json::json!({"success": message}));
-                    } else {
-                        output::success(&message);
-                    }
-                }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
-            }
+            print_success(&client.aliases().install(alias, bin_dir.clone())?, json);
         }
         AliasesCommands::Uninstall { alias } => {
-            let response = client.request(&Request::AliasesUninstall {
-                alias: alias.clone(),
-            })?;
-            match response {
-                Response::Success { message } => {
-                    if json {
-                        println!("{}", serde_json::json!({"success": message}));
-                    } else {
-                        output::success(&message);
-                    }
-                }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
-            }
+            print_success(&client.aliases().uninstall(alias)?, json);
         }
     }
 
@@ -458,71 +783,54 @@ async fn execute_registry(command: &RegistryCommands, json: bool) -> Result<()>
 
     match command {
         RegistryCommands::Sync { force, offline } => {
-            let response = client.request(&Request::RegistrySync {
-                force: *force,
-                offline: *offline,
-            })?;
-            match response {
-                Response::RegistryStatus(status) => {
-                    if json {
-                        println!("{}", serde_json::to_string_pretty(&status)?);
-                    } else {
-                        println!("Channel: {}", status.channel);
-                        if let Some(commit) = &status.commit {
-                            println!("Commit: {}", commit);
-                        }
-                        if let Some(last_sync) = &status.last_sync {
-                            println!("Last Sync: {}", last_sync);
-                        }
-                        println!("Offline: {}", status.offline);
-                    }
+            let status = if json {
+                client.registry().sync(*force, *offline)?
+            } else {
+                client.with_progress("registry_sync", |client| {
+                    client.registry().sync(*force, *offline)
+                })?
+            };
+            if json {
+                println!("{}", serde_json::to_string_pretty(&status)?);
+            } else {
+                if status.from_cache {
+                    println!(
+                        "Cache fresh: {}; use --force to refetch",
+                        output::registry_cache_age(&status)
+                    );
                 }
-                Response::Success { message } => {
-                    if json {
-                        println!("{}", serde_json::json!({"success": message}));
-                    } else {
-                        output::success(&message);
-                    }
+                println!("Channel: {}", status.channel);
+                if let Some(commit) = &status.commit {
+                    println!("Commit: {}", commit);
+                }
+                if let Some(last_sync) = &status.last_sync {
+                    println!("Last Sync: {}", last_sync);
                 }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
+                println!("Offline: {}", status.offline);
+            }
+            if let Some(warning) = &status.warning {
+                eprintln!("Warning: {}", warning);
             }
         }
         RegistryCommands::Pin { ref_ } => {
-            let response = client.request(&Request::RegistryPin { ref_: ref_.clone() })?;
-            match response {
-                Response::Success { message } => {
-                    if json {
-                        println!("{}", serde_json::json!({"success": message}));
-                    } else {
-                        output::success(&message);
-                    }
-                }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
-            }
+            print_success(&client.registry().pin(ref_)?, json);
         }
         RegistryCommands::Inspect => {
-            let response = client.request(&Request::RegistryInspect)?;
-            match response {
-                Response::RegistryStatus(status) => {
-                    if json {
-                        println!("{}", serde_json::to_string_pretty(&status)?);
-                    } else {
-                        println!("Channel: {}", status.channel);
-                        if let Some(commit) = &status.commit {
-                            println!("Commit: {}", commit);
-                        }
-                        if let Some(last_sync) = &status.last_sync {
-                            println!("Last Sync: {}", last_sync);
-                        }
-                        println!("Cached Agents: {}", status.cached_agents);
-                        println!("Cached Providers: {}", status.cached_providers);
-                        println!("Cached Scripts: {}", status.cached_scripts);
-                    }
+            let status = client.registry().inspect()?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&status)?);
+            } else {
+                println!("Channel: {}", status.channel);
+                if let Some(commit) = &status.commit {
+                    println!("Commit: {}", commit);
+                }
+                if let Some(last_sync) = &status.last_sync {
+                    println!("Last Sync: {}", last_sync);
                 }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
+                println!("Cache Age: {}", output::registry_cache_age(&status));
+                println!("Cached Agents: {}", status.cached_agents);
+                println!("Cached Providers: {}", status.cached_providers);
+                println!("Cached Scripts: {}", status.cached_scripts);
             }
         }
     }
@@ -537,42 +845,33 @@ async fn execute_stats(
 ) -> Result<()> {
     let client = DaemonClient::connect()?;
 
-    let response = client.request(&Request::Stats {
-        agent_id: agent.clone(),
-        provider_id: provider.clone(),
-    })?;
+    let stats = client.stats(agent.as_deref(), provider.as_deref())?;
 
-    match response {
-        Response::Stats(stats) => {
-            if json {
-                println!("{}", serde_json::to_string_pretty(&stats)?);
-            } else {
-                println!("Total Sessions: {}", stats.total_sessions);
-                println!("Total Runtime: {}s", stats.total_runtime_secs);
-
-                if !stats.by_agent.is_empty() {
-                    println!("\nBy Agent:");
-                    for (id, s) in &stats.by_agent {
-                        println!(
-                            "  {}: {} sessions, {}s runtime",
-                            id, s.sessions, s.runtime_secs
-                        );
-                    }
-                }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        println!("Total Sessions: {}", stats.total_sessions);
+        println!("Total Runtime: {}s", stats.total_runtime_secs);
 
-                if !stats.by_provider.is_empty() {
-                    println!("\nBy Provider:");
-                    for (id, s) in &stats.by_provider {
-                        println!(
-                            "  {}: {} sessions, {}s runtime",
-                            id, s.sessions, s.runtime_secs
-                        );
-                    }
-                }
+        if !stats.by_agent.is_empty() {
+            println!("\nBy Agent:");
+            for (id, s) in &stats.by_agent {
+                println!(
+                    "  {}: {} sessions, {}s runtime",
+                    id, s.sessions, s.runtime_secs
+                );
+            }
+        }
+
+        if !stats.by_provider.is_empty() {
+            println!("\nBy Provider:");
+            for (id, s) in &stats.by_provider {
+                println!(
+                    "  {}: {} sessions, {}s runtime",
+                    id, s.sessions, s.runtime_secs
+                );
             }
         }
-        Response::Error { message, .. } => return Err(anyhow!(message)),
-        _ => return Err(anyhow!("Unexpected response")),
     }
 
     Ok(())
@@ -580,123 +879,271 @@ async fn execute_stats(
 
 async fn execute_usage(
     command: Option<&UsageCommands>,
-    period: &str,
+    period: UsagePeriod,
     profile: Option<&str>,
     model: Option<&str>,
+    agent: Option<AgentType>,
     json: bool,
 ) -> Result<()> {
-    let client = DaemonClient::connect()?;
-
-    // Parse period string to UsagePeriod
-    let usage_period = parse_period(period);
-
     match command {
+        // Estimates only need local pricing data, not the daemon
+        Some(UsageCommands::Estimate {
+            model,
+            input,
+            output,
+            cache_write,
+            cache_read,
+        }) => {
+            let tokens = TokenUsage {
+                input_tokens: *input,
+                output_tokens: *output,
+                cache_creation_input_tokens: *cache_write,
+                cache_read_input_tokens: *cache_read,
+            };
+            execute_usage_estimate(model, &tokens, json)?;
+        }
         Some(UsageCommands::Daily { period }) => {
-            let response = client.request(&Request::Usage {
-                period: Some(parse_period(period)),
-                profile: None,
-                model: None,
-            })?;
-            handle_usage_response(response, json)?;
+            let client = DaemonClient::connect()?;
+            let usage = client
+                .usage()
+                .summary(Some(period.clone()), None, None, agent)?;
+            print_usage(&usage, json)?;
         }
         Some(UsageCommands::Models) => {
-            let response = client.request(&Request::Usage {
-                period: Some(UsagePeriod::All),
-                profile: None,
-                model: None,
-            })?;
-            handle_usage_response(response, json)?;
+            let client = DaemonClient::connect()?;
+            let usage = client
+                .usage()
+                .summary(Some(UsagePeriod::All), None, None, agent)?;
+            print_usage(&usage, json)?;
         }
         Some(UsageCommands::Profiles) => {
-            let response = client.request(&Request::Usage {
-                period: Some(UsagePeriod::All),
-                profile: None,
-                model: None,
-            })?;
-            handle_usage_response(response, json)?;
+            let client = DaemonClient::connect()?;
+            let usage = client
+                .usage()
+                .summary(Some(UsagePeriod::All), None, None, agent)?;
+            print_usage(&usage, json)?;
         }
-        Some(UsageCommands::Export { format, period }) => {
-            let response = client.request(&Request::Usage {
-                period: Some(parse_period(period)),
-                profile: None,
-                model: None,
-            })?;
-            match response {
-                Response::Usage(usage) => {
-                    // Always output as requested format
-                    if format == "csv" {
-                        println!(
-                            "period,total_sessions,total_runtime_secs,input_tokens,output_tokens,cache_creation_tokens,cache_read_tokens,total_cost"
-                        );
-                        println!(
-                            "{},{},{},{},{},{},{},{}",
-                            usage.period,
-                            usage.total_sessions,
-                            usage.total_runtime_secs,
-                            usage.total_tokens.input_tokens,
-                            usage.total_tokens.output_tokens,
-                            usage.total_tokens.cache_creation_input_tokens,
-                            usage.total_tokens.cache_read_input_tokens,
-                            usage
-                                .total_cost
-                                .as_ref()
-                                .map(|c| c.total_cost)
-                                .unwrap_or(0.0)
-                        );
-                    } else {
-                        println!("{}", serde_json::to_string_pretty(&usage)?);
-                    }
+        Some(UsageCommands::Top { by, limit, period }) => {
+            let client = DaemonClient::connect()?;
+            let by = match by.to_lowercase().as_str() {
+                "model" => output::TopBy::Model,
+                "profile" => output::TopBy::Profile,
+                other => {
+                    return Err(anyhow!(
+                        "Unknown ranking '{}': expected 'model' or 'profile'",
+                        other
+                    ));
                 }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
+            };
+            let usage = client
+                .usage()
+                .summary(Some(period.clone()), None, None, agent)?;
+            let entries = output::usage_top(&usage, by, *limit);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&entries)?);
+            } else if entries.is_empty() {
+                println!("No usage recorded for {}", usage.period);
+            } else {
+                println!("Top usage: {}", usage.period);
+                println!("{}", output::usage_top_table(&entries, by));
+            }
+        }
+        Some(UsageCommands::Export { format, period }) if format == "jsonl" => {
+            let client = DaemonClient::connect()?;
+            export_usage_jsonl(&client, period.clone())?;
+        }
+        Some(UsageCommands::Export { format, period }) => {
+            let client = DaemonClient::connect()?;
+            let usage = client
+                .usage()
+                .summary(Some(period.clone()), None, None, agent)?;
+            // Always output as requested format
+            if format == "csv" {
+                println!(
+                    "period,total_sessions,total_runtime_secs,input_tokens,output_tokens,cache_creation_tokens,cache_read_tokens,total_cost"
+                );
+                println!(
+                    "{},{},{},{},{},{},{},{}",
+                    usage.period,
+                    usage.total_sessions,
+                    usage.total_runtime_secs,
+                    usage.total_tokens.input_tokens,
+                    usage.total_tokens.output_tokens,
+                    usage.total_tokens.cache_creation_input_tokens,
+                    usage.total_tokens.cache_read_input_tokens,
+                    usage
+                        .total_cost
+                        .as_ref()
+                        .map(|c| c.total_cost)
+                        .unwrap_or(0.0)
+                );
+            } else {
+                println!("{}", serde_json::to_string_pretty(&usage)?);
             }
         }
         Some(UsageCommands::ImportClaude { claude_dir }) => {
-            let response = client.request(&Request::UsageImportClaude {
-                claude_dir: claude_dir.clone(),
-            })?;
-            handle_success_response(response, json)?;
+            let client = DaemonClient::connect()?;
+            let message = if json {
+                client.usage().import_claude(claude_dir.clone())?
+            } else {
+                client.with_progress("usage_import", |client| {
+                    client.usage().import_claude(claude_dir.clone())
+                })?
+            };
+            print_success(&message, json);
+        }
+        Some(UsageCommands::Import { file, format }) => {
+            let client = DaemonClient::connect()?;
+            let content = std::fs::read_to_string(file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            print_success(&client.usage().import(*format, content)?, json);
+        }
+        Some(UsageCommands::Budget) => {
+            let client = DaemonClient::connect()?;
+            let statuses = client.usage().budget()?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&statuses)?);
+            } else if statuses.is_empty() {
+                println!(
+                    "No budgets configured. Set usage.budget_daily_usd or usage.budget_monthly_usd in config.toml."
+                );
+            } else {
+                println!("{}", output::budget_table(&statuses));
+            }
+        }
+        Some(UsageCommands::Reset {
+            telemetry,
+            cache,
+            before,
+            yes,
+        }) => {
+            let client = DaemonClient::connect()?;
+            if !*yes && !confirm_usage_reset(*telemetry, *cache, *before, json)? {
+                return Ok(());
+            }
+            let message = client.usage().reset(*telemetry, *cache, *before)?;
+            print_success(&message, json);
         }
         None => {
             // Default: show usage summary
-            let response = client.request(&Request::Usage {
-                period: Some(usage_period),
-                profile: profile.map(|s| s.to_string()),
-                model: model.map(|s| s.to_string()),
-            })?;
-            handle_usage_response(response, json)?;
+            let client = DaemonClient::connect()?;
+            let usage = client
+                .usage()
+                .summary(Some(period), profile, model, agent)?;
+            print_usage(&usage, json)?;
         }
     }
 
-    Ok(())
-}
+    Ok(())
+}
+
+/// Describe what `usage reset` will delete and ask before proceeding.
+fn confirm_usage_reset(
+    telemetry: bool,
+    cache: bool,
+    before: Option<chrono::NaiveDate>,
+    json: bool,
+) -> Result<bool> {
+    if json {
+        return Err(anyhow!("Pass --yes to reset usage data in JSON mode"));
+    }
+
+    let all = !telemetry && !cache;
+    let mut targets = Vec::new();
+    if telemetry || all {
+        targets.push(match before {
+            Some(before) => format!("telemetry sessions and imported usage before {}", before),
+            None => "all telemetry sessions and imported usage".to_string(),
+        });
+    }
+    if cache || all {
+        targets.push("the usage cache and budget alert state".to_string());
+    }
+
+    Ok(dialoguer::Confirm::new()
+        .with_prompt(format!("Delete {}?", targets.join(" and ")))
+        .default(false)
+        .interact()?)
+}
+
+/// Number of usage records fetched per request when streaming an export.
+const EXPORT_PAGE_SIZE: usize = 1000;
+
+/// Stream usage records as JSON Lines, one record per line, paging through the daemon.
+fn export_usage_jsonl(client: &DaemonClient, period: UsagePeriod) -> Result<()> {
+    use std::io::Write;
+
+    let stdout = std::io::stdout();
+    let mut out = std::io::BufWriter::new(stdout.lock());
+    let mut offset = 0;
+
+    loop {
+        let page = client
+            .usage()
+            .records(Some(period.clone()), offset, EXPORT_PAGE_SIZE)?;
+
+        for record in &page.records {
+            let written = serde_json::to_writer(&mut out, record)
+                .map_err(std::io::Error::from)
+                .and_then(|()| writeln!(out));
+            match written {
+                // Downstream consumer (e.g. `head`) closed the pipe; stop quietly.
+                Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => return Ok(()),
+                other => other?,
+            }
+        }
+
+        match page.next_offset {
+            Some(next) => offset = next,
+            None => break,
+        }
+    }
+
+    match out.flush() {
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        other => Ok(other?),
+    }
+}
+
+/// Price a hypothetical run using the cached LiteLLM pricing table.
+fn execute_usage_estimate(model: &str, tokens: &TokenUsage, json: bool) -> Result<()> {
+    let paths = RingletPaths::default();
+    let pricing = PricingLoader::new(paths.clone());
+
+    if !pricing.has_cache() {
+        paths.ensure_dirs()?;
+        pricing.sync()?;
+    }
+
+    let model_pricing = pricing
+        .get_model_pricing(model)
+        .ok_or_else(|| anyhow!("No pricing data for model: {}", model))?;
+    let cost = model_pricing.calculate_cost(tokens);
 
-fn parse_period(period: &str) -> UsagePeriod {
-    match period.to_lowercase().as_str() {
-        "today" => UsagePeriod::Today,
-        "yesterday" => UsagePeriod::Yesterday,
-        "week" | "thisweek" | "this_week" => UsagePeriod::ThisWeek,
-        "month" | "thismonth" | "this_month" => UsagePeriod::ThisMonth,
-        "7d" | "7days" | "last7days" => UsagePeriod::Last7Days,
-        "30d" | "30days" | "last30days" => UsagePeriod::Last30Days,
-        "all" | "alltime" | "all_time" => UsagePeriod::All,
-        _ => UsagePeriod::Today,
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "model": model,
+                "tokens": tokens,
+                "cost": cost,
+            }))?
+        );
+    } else {
+        println!("Estimated cost for {}:", model);
+        println!("{}", output::cost_table(&cost));
     }
+
+    Ok(())
 }
 
-fn handle_usage_response(response: Response, json: bool) -> Result<()> {
-    match response {
-        Response::Usage(usage) => {
-            if json {
-                println!("{}", serde_json::to_string_pretty(&usage)?);
-            } else {
-                output::usage_summary(&usage);
-            }
-            Ok(())
-        }
-        Response::Error { message, .. } => Err(anyhow!(message)),
-        _ => Err(anyhow!("Unexpected response")),
+fn print_usage(usage: &UsageStatsResponse, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(usage)?);
+    } else {
+        output::usage_summary(usage);
     }
+    Ok(())
 }
 
 async fn execute_daemon(
@@ -719,7 +1166,7 @@ async fn execute_daemon(
             .await
         }
         Some(DaemonCommands::Stop) => {
-            match DaemonClient::connect() {
+            match DaemonClient::connect_unchecked() {
                 Ok(client) => {
                     client.shutdown()?;
                     if json {
@@ -739,7 +1186,7 @@ async fn execute_daemon(
             Ok(())
         }
         Some(DaemonCommands::Status) => {
-            match DaemonClient::connect() {
+            match DaemonClient::connect_unchecked() {
                 Ok(client) => {
                     if client.ping() {
                         if json {
@@ -765,6 +1212,8 @@ async fn execute_daemon(
             }
             Ok(())
         }
+        Some(DaemonCommands::InstallService) => service::install(json),
+        Some(DaemonCommands::UninstallService) => service::uninstall(json),
     }
 }
 
@@ -773,21 +1222,118 @@ async fn execute_env(command: &EnvCommands, json: bool) -> Result<()> {
 
     match command {
         EnvCommands::Setup { alias, task } => {
-            let response = client.request(&Request::EnvSetup {
-                alias: alias.clone(),
-                task: task.clone(),
+            print_success(&client.env_setup(alias, task)?, json);
+        }
+    }
+
+    Ok(())
+}
+
+/// Read or change config.toml directly; the daemon reloads it on use.
+fn execute_config(command: &ConfigCommands, json: bool) -> Result<()> {
+    let paths = RingletPaths::default();
+    let config_file = paths.config_file();
+
+    match command {
+        ConfigCommands::Get { key } => {
+            let value = UserConfig::load(&config_file)?.get(key);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&value)?);
+            } else {
+                match value {
+                    Some(toml::Value::String(value)) => println!("{}", value),
+                    Some(value) => println!("{}", value),
+                    None => println!("(not set)"),
+                }
+            }
+        }
+        ConfigCommands::Set { key, value } => {
+            UserConfig::update(&config_file, |config| {
+                config.set(key, value).map_err(|e| anyhow!(e))
             })?;
-            match response {
-                Response::Success { message } => {
-                    if json {
-                        println!("{}", serde_json::json!({"success": message}));
-                    } else {
-                        output::success(&message);
-                    }
+            if json {
+                println!("{}", serde_json::json!({"success": true, "key": key}));
+            } else {
+                output::success(&format!("Set {} = {}", key, value));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Manage `[usage.model_aliases]` in config.toml.
+fn execute_pricing(command: &PricingCommands, json: bool) -> Result<()> {
+    let paths = RingletPaths::default();
+    let config_file = paths.config_file();
+
+    let message = match command {
+        PricingCommands::Sync => {
+            paths.ensure_dirs()?;
+            let pricing = PricingLoader::new(paths);
+            pricing.sync()?;
+            format!("Synced pricing for {} models", pricing.model_count())
+        }
+        PricingCommands::Alias { from, to } => {
+            if !PricingLoader::new(paths.clone()).has_model(to)? {
+                return Err(anyhow!(
+                    "'{}' is not in the pricing table; alias to a model it lists",
+                    to
+                ));
+            }
+            UserConfig::update(&config_file, |config| {
+                config.usage.model_aliases.insert(from.clone(), to.clone());
+                Ok::<_, anyhow::Error>(())
+            })?;
+            format!("Pricing '{}' as '{}'", from, to)
+        }
+        PricingCommands::Unalias { from } => {
+            UserConfig::update(&config_file, |config| {
+                match config.usage.model_aliases.remove(from) {
+                    Some(_) => Ok(()),
+                    None => Err(anyhow!("No pricing alias for '{}'", from)),
+                }
+            })?;
+            format!("Removed pricing alias for '{}'", from)
+        }
+        PricingCommands::Aliases => {
+            let config = UserConfig::load(&config_file)?;
+            let mut aliases: Vec<_> = config.usage.model_aliases.iter().collect();
+            aliases.sort();
+            if json {
+                let map: serde_json::Map<_, _> = aliases
+                    .into_iter()
+                    .map(|(from, to)| (from.clone(), serde_json::Value::from(to.as_str())))
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&map)?);
+            } else if aliases.is_empty() {
+                println!("No pricing aliases. Add one with `ringlet pricing alias <FROM> <TO>`.");
+            } else {
+                for (from, to) in aliases {
+                    println!("{} -> {}", from, to);
                 }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
             }
+            return Ok(());
+        }
+    };
+
+    if json {
+        println!("{}", serde_json::json!({"success": message}));
+    } else {
+        output::success(&message);
+    }
+    Ok(())
+}
+
+/// Describe the script interface; the schemas are JSON whether or not `--json` is set.
+fn execute_scripts(command: &ScriptsCommands) -> Result<()> {
+    match command {
+        ScriptsCommands::Schema => {
+            let schemas = serde_json::json!({
+                "context": ringlet_scripting::context_schema(),
+                "output": ringlet_scripting::output_schema(),
+            });
+            println!("{}", serde_json::to_string_pretty(&schemas)?);
         }
     }
 
@@ -803,39 +1349,52 @@ async fn execute_hooks(command: &HooksCommands, json: bool) -> Result<()> {
             event,
             matcher,
             command,
+            dry_run,
+            force,
         } => {
-            let response = client.request(&Request::HooksAdd {
-                alias: alias.clone(),
-                event: event.clone(),
-                matcher: matcher.clone(),
-                command: command.clone(),
-            })?;
-            match response {
-                Response::Success { message } => {
-                    if json {
-                        println!("{}", serde_json::json!({"success": message}));
-                    } else {
-                        output::success(&message);
+            if *force && !json {
+                print_hook_warnings(&ringlet_core::lint_command(command));
+            }
+            if !*dry_run {
+                let message = client.hooks().add(alias, event, matcher, command, *force)?;
+                print_success(&message, json);
+                return Ok(());
+            }
+
+            let preview = client
+                .hooks()
+                .preview_add(alias, event, matcher, command, *force)?;
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({
+                        "hooks": preview.hooks,
+                        "file": preview.file,
+                        "generated": preview.generated,
+                    })
+                );
+            } else {
+                println!("Profile hooks (not saved):");
+                println!("{}", serde_json::to_string_pretty(&preview.hooks)?);
+                match (preview.file, preview.generated) {
+                    (Some(file), Some(generated)) => {
+                        println!();
+                        println!("Generated {}:", file);
+                        println!("{}", generated.trim_end());
+                    }
+                    _ => {
+                        println!();
+                        println!("The agent's script does not write hooks into its config");
                     }
                 }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
             }
         }
         HooksCommands::List { alias } => {
-            let response = client.request(&Request::HooksList {
-                alias: alias.clone(),
-            })?;
-            match response {
-                Response::Hooks(hooks) => {
-                    if json {
-                        println!("{}", serde_json::to_string_pretty(&hooks)?);
-                    } else {
-                        print_hooks(&hooks);
-                    }
-                }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
+            let hooks = client.hooks().list(alias)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&hooks)?);
+            } else {
+                print_hooks(&hooks);
             }
         }
         HooksCommands::Remove {
@@ -843,63 +1402,110 @@ async fn execute_hooks(command: &HooksCommands, json: bool) -> Result<()> {
             event,
             index,
         } => {
-            let response = client.request(&Request::HooksRemove {
-                alias: alias.clone(),
-                event: event.clone(),
-                index: *index,
-            })?;
-            match response {
-                Response::Success { message } => {
-                    if json {
-                        println!("{}", serde_json::json!({"success": message}));
-                    } else {
-                        output::success(&message);
-                    }
-                }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
-            }
+            print_success(&client.hooks().remove(alias, event, *index)?, json);
         }
-        HooksCommands::Import { alias, file } => {
+        HooksCommands::Import { alias, file, force } => {
             let content =
                 std::fs::read_to_string(file).map_err(|e| anyhow!("Failed to read file: {}", e))?;
-            let config: HooksConfig =
-                serde_json::from_str(&content).map_err(|e| anyhow!("Invalid hooks JSON: {}", e))?;
-
-            let response = client.request(&Request::HooksImport {
-                alias: alias.clone(),
-                config,
+            let config = HooksConfig::from_json_strict(&content).map_err(|e| {
+                anyhow!(
+                    "{}\n{}",
+                    output::annotate_json_error("hooks JSON", &content, &e),
+                    HOOKS_JSON_HINT
+                )
             })?;
-            match response {
-                Response::Success { message } => {
-                    if json {
-                        println!("{}", serde_json::json!({"success": message}));
-                    } else {
-                        output::success(&message);
-                    }
+            config
+                .validate()
+                .map_err(|e| anyhow!("Invalid hooks: {}", e))?;
+
+            if *force && !json {
+                print_hook_warnings(&config.lint());
+            }
+            print_success(&client.hooks().import(alias, config, *force)?, json);
+        }
+        HooksCommands::Export { alias, format } => {
+            if format == "ringlet" {
+                // Always output JSON for export (pipe-friendly)
+                let hooks = client.hooks().export(alias)?;
+                println!("{}", serde_json::to_string_pretty(&hooks)?);
+                return Ok(());
+            }
+
+            let NativeHooks { file, content } = client.hooks().export_native(alias, format)?;
+            if json {
+                println!("{}", serde_json::json!({"file": file, "content": content}));
+            } else {
+                eprintln!("# Hooks section for {}", file);
+                print!("{}", content);
+                if !content.ends_with('\n') {
+                    println!();
                 }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
             }
         }
-        HooksCommands::Export { alias } => {
-            let response = client.request(&Request::HooksExport {
-                alias: alias.clone(),
-            })?;
-            match response {
-                Response::Hooks(hooks) => {
-                    // Always output JSON for export (pipe-friendly)
-                    println!("{}", serde_json::to_string_pretty(&hooks)?);
+        HooksCommands::Defaults { command } => execute_hooks_defaults(command, &client, json)?,
+    }
+
+    Ok(())
+}
+
+fn execute_hooks_defaults(
+    command: &HooksDefaultsCommands,
+    client: &DaemonClient,
+    json: bool,
+) -> Result<()> {
+    match command {
+        HooksDefaultsCommands::List => {
+            let defaults = client.hooks().defaults()?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&defaults)?);
+            } else if defaults.is_empty() {
+                println!("No default hooks configured");
+            } else {
+                let mut agents: Vec<_> = defaults.iter().collect();
+                agents.sort_by_key(|(agent, _)| agent.as_str());
+                for (agent, hooks) in agents {
+                    println!("[{}]", agent);
+                    print_hooks(hooks);
                 }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
             }
         }
+        HooksDefaultsCommands::Add {
+            agent,
+            event,
+            matcher,
+            command,
+            force,
+        } => {
+            if *force && !json {
+                print_hook_warnings(&ringlet_core::lint_command(command));
+            }
+            let message = client
+                .hooks()
+                .add_default(agent, event, matcher, command, *force)?;
+            print_success(&message, json);
+        }
+        HooksDefaultsCommands::OptOut { alias } | HooksDefaultsCommands::OptIn { alias } => {
+            let skip = matches!(command, HooksDefaultsCommands::OptOut { .. });
+            print_success(&client.hooks().skip_defaults(alias, skip)?, json);
+        }
     }
 
     Ok(())
 }
 
+/// Shown after a hooks import parse error.
+const HOOKS_JSON_HINT: &str = "Expected an object keyed by event (PreToolUse, PostToolUse, \
+Notification, Stop), each a list of rules such as \
+{\"matcher\": \"Bash|Write\", \"hooks\": [{\"type\": \"command\", \"command\": \"...\"}]}. \
+Hooks are {\"type\": \"command\", \"command\", \"timeout\"?} or {\"type\": \"url\", \"url\"}.";
+
+/// Show what `--force` is saving past the unsafe-command check.
+fn print_hook_warnings(warnings: &[String]) {
+    for warning in warnings {
+        eprintln!("Warning: {}", warning);
+    }
+}
+
 fn print_hooks(hooks: &HooksConfig) {
     let events = [
         ("PreToolUse", &hooks.pre_tool_use),
@@ -944,84 +1550,53 @@ async fn execute_proxy(command: &ProxyCommands, json: bool) -> Result<()> {
 
     match command {
         ProxyCommands::Enable { alias } => {
-            let response = client.request(&Request::ProxyEnable {
-                alias: alias.clone(),
-            })?;
-            handle_success_response(response, json)?;
+            print_success(&client.proxy().enable(alias)?, json);
         }
         ProxyCommands::Disable { alias } => {
-            let response = client.request(&Request::ProxyDisable {
-                alias: alias.clone(),
-            })?;
-            handle_success_response(response, json)?;
+            print_success(&client.proxy().disable(alias)?, json);
         }
         ProxyCommands::Start { alias } => {
-            let response = client.request(&Request::ProxyStart {
-                alias: alias.clone(),
-            })?;
-            handle_success_response(response, json)?;
+            print_success(&client.proxy().start(alias)?, json);
         }
         ProxyCommands::Stop { alias } => {
-            let response = client.request(&Request::ProxyStop {
-                alias: alias.clone(),
-            })?;
-            handle_success_response(response, json)?;
+            print_success(&client.proxy().stop(alias)?, json);
         }
         ProxyCommands::StopAll => {
-            let response = client.request(&Request::ProxyStopAll)?;
-            handle_success_response(response, json)?;
+            print_success(&client.proxy().stop_all()?, json);
         }
-        ProxyCommands::Restart { alias } => {
-            // Stop then start
-            let _ = client.request(&Request::ProxyStop {
-                alias: alias.clone(),
-            });
-            let response = client.request(&Request::ProxyStart {
-                alias: alias.clone(),
-            })?;
-            handle_success_response(response, json)?;
+        ProxyCommands::Restart { alias, verify } => {
+            print_success(&client.proxy().restart(alias, *verify)?, json);
         }
         ProxyCommands::Status { alias } => {
-            let response = client.request(&Request::ProxyStatus {
-                alias: alias.clone(),
-            })?;
-            match response {
-                Response::ProxyStatus(instances) => {
-                    if json {
-                        println!("{}", serde_json::to_string_pretty(&instances)?);
-                    } else {
-                        output::proxy_status(&instances);
-                    }
-                }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
+            let instances = client.proxy().status(alias.as_deref())?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&instances)?);
+            } else {
+                output::proxy_status(&instances);
             }
         }
         ProxyCommands::Config { alias } => {
-            let response = client.request(&Request::ProxyConfig {
-                alias: alias.clone(),
-            })?;
-            match response {
-                Response::ProxyConfig(config) => {
-                    if json {
-                        println!("{}", serde_json::to_string_pretty(&config)?);
-                    } else {
-                        output::proxy_config(&config);
-                    }
-                }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
+            let config = client.proxy().config(alias)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&config)?);
+            } else {
+                output::proxy_config(&config);
             }
         }
-        ProxyCommands::Logs { alias, lines } => {
-            let response = client.request(&Request::ProxyLogs {
-                alias: alias.clone(),
-                lines: Some(*lines),
-            })?;
-            match response {
-                Response::ProxyLogs(logs) => println!("{}", logs),
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
+        ProxyCommands::Logs {
+            alias, path: true, ..
+        } => {
+            println!("{}", client.proxy().log_path(alias)?.display());
+        }
+        ProxyCommands::Logs { alias, lines, .. } => {
+            println!("{}", client.proxy().logs(alias, Some(*lines))?);
+        }
+        ProxyCommands::Env { alias } => {
+            let env = client.proxy().env(alias)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&env)?);
+            } else {
+                println!("{}", output::env_export(&env));
             }
         }
         ProxyCommands::Route { command } => execute_proxy_route(command, &client, json)?,
@@ -1051,34 +1626,18 @@ fn execute_proxy_route(
             let rule = RoutingRule::new(name.clone(), parsed_condition, target.clone())
                 .with_priority(*priority);
 
-            let response = client.request(&Request::ProxyRouteAdd {
-                alias: alias.clone(),
-                rule,
-            })?;
-            handle_success_response(response, json)?;
+            print_success(&client.proxy().route_add(alias, rule)?, json);
         }
         ProxyRouteCommands::List { alias } => {
-            let response = client.request(&Request::ProxyRouteList {
-                alias: alias.clone(),
-            })?;
-            match response {
-                Response::ProxyRoutes(rules) => {
-                    if json {
-                        println!("{}", serde_json::to_string_pretty(&rules)?);
-                    } else {
-                        output::proxy_routes(&rules);
-                    }
-                }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
+            let rules = client.proxy().route_list(alias)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&rules)?);
+            } else {
+                output::proxy_routes(&rules);
             }
         }
         ProxyRouteCommands::Remove { alias, name } => {
-            let response = client.request(&Request::ProxyRouteRemove {
-                alias: alias.clone(),
-                rule_name: name.clone(),
-            })?;
-            handle_success_response(response, json)?;
+            print_success(&client.proxy().route_remove(alias, name)?, json);
         }
     }
 
@@ -1092,57 +1651,66 @@ fn execute_proxy_alias(
 ) -> Result<()> {
     match command {
         ProxyAliasCommands::Set { alias, from, to } => {
-            let response = client.request(&Request::ProxyAliasSet {
-                alias: alias.clone(),
-                from_model: from.clone(),
-                to_target: to.clone(),
-            })?;
-            handle_success_response(response, json)?;
+            print_success(&client.proxy().alias_set(alias, from, to)?, json);
         }
         ProxyAliasCommands::List { alias } => {
-            let response = client.request(&Request::ProxyAliasList {
-                alias: alias.clone(),
-            })?;
-            match response {
-                Response::ProxyAliases(aliases) => {
-                    if json {
-                        println!("{}", serde_json::to_string_pretty(&aliases)?);
-                    } else {
-                        output::proxy_aliases(&aliases);
-                    }
-                }
-                Response::Error { message, .. } => return Err(anyhow!(message)),
-                _ => return Err(anyhow!("Unexpected response")),
+            let aliases = client.proxy().alias_list(alias)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&aliases)?);
+            } else {
+                output::proxy_aliases(&aliases);
             }
         }
         ProxyAliasCommands::Remove { alias, from } => {
-            let response = client.request(&Request::ProxyAliasRemove {
-                alias: alias.clone(),
-                from_model: from.clone(),
-            })?;
-            handle_success_response(response, json)?;
+            print_success(&client.proxy().alias_remove(alias, from)?, json);
         }
     }
 
     Ok(())
 }
 
-fn handle_success_response(response: Response, json: bool) -> Result<()> {
-    match response {
-        Response::Success { message } => {
-            if json {
-                println!("{}", serde_json::json!({"success": message}));
-            } else {
-                output::success(&message);
-            }
-            Ok(())
-        }
-        Response::Error { message, .. } => Err(anyhow!(message)),
-        _ => Err(anyhow!("Unexpected response")),
+/// Write a configuration bundle through the daemon.
+fn execute_export(out: &std::path::Path, json: bool) -> Result<()> {
+    let client = DaemonClient::connect()?;
+    // The daemon resolves paths against its own working directory
+    let out = std::path::absolute(out)?;
+    let report = client.export_bundle(&out)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}", output::bundle_report(&report, true));
+    }
+    Ok(())
+}
+
+/// Restore a configuration bundle through the daemon.
+fn execute_import(
+    bundle: &std::path::Path,
+    overwrite: bool,
+    force: bool,
+    json: bool,
+) -> Result<()> {
+    let client = DaemonClient::connect()?;
+    let bundle = std::path::absolute(bundle)?;
+    let report = client.import_bundle(&bundle, overwrite, force)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}", output::bundle_report(&report, false));
+    }
+    Ok(())
+}
+
+fn print_success(message: &str, json: bool) {
+    if json {
+        println!("{}", serde_json::json!({"success": message}));
+    } else {
+        output::success(message);
     }
 }
 
 /// Execute remote run - creates a terminal session via HTTP API.
+#[allow(clippy::too_many_arguments)]
 async fn execute_remote_run(
     alias: &str,
     args: &[String],
@@ -1150,6 +1718,7 @@ async fn execute_remote_run(
     rows: u16,
     no_sandbox: bool,
     bwrap_flags: Option<&str>,
+    persist: bool,
     json: bool,
 ) -> Result<()> {
     let api_base = get_http_api_base();
@@ -1163,6 +1732,7 @@ async fn execute_remote_run(
         "cols": cols,
         "rows": rows,
         "no_sandbox": no_sandbox,
+        "persist": persist,
     });
 
     // Add bwrap_flags if provided
@@ -1205,6 +1775,9 @@ async fn execute_remote_run(
         println!("Terminal session created:");
         println!("  Session ID: {}", session_id);
         println!("  Web UI: {}/terminal/{}", api_base, session_id);
+        if persist {
+            println!("  Relaunched when the daemon restarts");
+        }
         println!(
             "\nTo attach from CLI: ringlet terminal attach {}",
             session_id
@@ -1257,7 +1830,7 @@ async fn execute_terminal(command: &TerminalCommands, json: bool) -> Result<()>
                         session["id"].as_str().unwrap_or("-"),
                         session["profile_alias"].as_str().unwrap_or("-"),
                         session["state"].as_str().unwrap_or("-"),
-                        session["client_count"].as_u64().unwrap_or(0),
+                        client_summary(session),
                     );
                 }
             }
@@ -1300,8 +1873,11 @@ async fn execute_terminal(command: &TerminalCommands, json: bool) -> Result<()>
                     session["cols"].as_u64().unwrap_or(0),
                     session["rows"].as_u64().unwrap_or(0)
                 );
-                println!("Clients: {}", session["client_count"].as_u64().unwrap_or(0));
+                println!("Clients: {}", client_summary(session));
                 println!("Created: {}", session["created_at"].as_str().unwrap_or("-"));
+                if session["persist"].as_bool() == Some(true) {
+                    println!("Persistent: relaunched when the daemon restarts");
+                }
             }
         }
         TerminalCommands::Kill { id } => {
@@ -1350,3 +1926,159 @@ async fn execute_terminal(command: &TerminalCommands, json: bool) -> Result<()>
 
     Ok(())
 }
+
+async fn execute_events(command: &EventsCommands, json: bool) -> Result<()> {
+    match command {
+        EventsCommands::Watch { kind } => {
+            let topics = watch_topics(kind)?;
+
+            let client = DaemonClient::connect()?;
+            let token = load_http_token()
+                .ok_or_else(|| anyhow!("HTTP auth token not found. Is the daemon running?"))?;
+            let url = format!("{}/ws", get_http_api_base().replace("http://", "ws://"));
+            let mut request = url.as_str().into_client_request()?;
+            request
+                .headers_mut()
+                .insert("Authorization", format!("Bearer {}", token).parse()?);
+            let (mut socket, _) = tokio_tungstenite::connect_async(request)
+                .await
+                .with_context(|| format!("Failed to connect to {}", url))?;
+
+            // The daemon starts every connection subscribed to everything
+            if topics != ["*"] {
+                for msg in [
+                    ClientMessage::Unsubscribe {
+                        topics: vec!["*".to_string()],
+                    },
+                    ClientMessage::Subscribe { topics },
+                ] {
+                    socket
+                        .send(Message::Text(serde_json::to_string(&msg)?))
+                        .await?;
+                }
+            }
+            if !json {
+                eprintln!("Watching daemon events (Ctrl-C to stop)...");
+            }
+
+            let mut keepalive = tokio::time::interval(EVENTS_KEEPALIVE);
+            loop {
+                let msg = tokio::select! {
+                    _ = keepalive.tick() => {
+                        // Watching counts as activity for the idle timeout
+                        client.ping();
+                        continue;
+                    }
+                    msg = socket.next() => msg,
+                };
+                let text = match msg {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => {
+                        return Err(anyhow!("The daemon closed the event stream"));
+                    }
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(e).context("Failed to receive event"),
+                };
+
+                // Events without a sequence number (connected, heartbeats)
+                // belong to this connection, not the daemon's event stream
+                let event = match serde_json::from_str::<ServerMessage>(&text) {
+                    Ok(ServerMessage::Event {
+                        event,
+                        seq: Some(_),
+                    }) => event,
+                    Ok(ServerMessage::Error { message }) => {
+                        eprintln!("Warning: {}", message);
+                        continue;
+                    }
+                    _ => continue,
+                };
+
+                if json {
+                    println!("{}", serde_json::to_string(&event)?);
+                } else {
+                    println!("{}", output::event_line(&event, chrono::Local::now()));
+                }
+            }
+        }
+    }
+}
+
+/// Validate `events watch --kind` values, returning the topics to subscribe to.
+fn watch_topics(kind: &[String]) -> Result<Vec<String>> {
+    if kind.is_empty() {
+        return Ok(vec!["*".to_string()]);
+    }
+    for k in kind {
+        let topic = k.split_once(':').map_or(k.as_str(), |(topic, _)| topic);
+        if !EVENT_TOPICS.contains(&topic) {
+            return Err(anyhow!(
+                "Unknown event kind '{}'. Expected one of: {}",
+                topic,
+                EVENT_TOPICS.join(", ")
+            ));
+        }
+    }
+    Ok(kind.to_vec())
+}
+
+/// Describe a session's connected clients, noting view-only ones.
+fn client_summary(session: &serde_json::Value) -> String {
+    let total = session["client_count"].as_u64().unwrap_or(0);
+    match session["view_clients"].as_u64().unwrap_or(0) {
+        0 => total.to_string(),
+        view => format!("{} ({} view-only)", total, view),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ringlet_core::Event;
+
+    fn kinds(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_bulk_delete_prompt_counts_profiles() {
+        assert_eq!(bulk_delete_prompt(0), None);
+        assert_eq!(bulk_delete_prompt(1).unwrap(), "Delete 1 profile?");
+        assert_eq!(bulk_delete_prompt(3).unwrap(), "Delete 3 profiles?");
+    }
+
+    #[test]
+    fn test_delete_prompts_need_an_interactive_terminal() {
+        assert!(can_prompt(false, true));
+        assert!(!can_prompt(true, true));
+        assert!(!can_prompt(false, false));
+    }
+
+    #[test]
+    fn test_watch_topics_default_to_everything() {
+        assert_eq!(watch_topics(&[]).unwrap(), ["*"]);
+    }
+
+    #[test]
+    fn test_watch_topics_accept_kinds_and_aliases() {
+        let topics = watch_topics(&kinds(&["usage", "proxy:work"])).unwrap();
+        assert_eq!(topics, ["usage", "proxy:work"]);
+
+        let proxy = Event::ProxyStopped {
+            alias: "work".to_string(),
+        };
+        let other = Event::ProxyStopped {
+            alias: "home".to_string(),
+        };
+        assert!(topics.iter().any(|t| proxy.matches_topic(t)));
+        assert!(!topics.iter().any(|t| other.matches_topic(t)));
+    }
+
+    #[test]
+    fn test_watch_topics_reject_unknown_kinds() {
+        let err = watch_topics(&kinds(&["usage", "proxies:work"])).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("'proxies'"), "{}", message);
+        assert!(message.contains(&EVENT_TOPICS.join(", ")), "{}", message);
+    }
+}
@@ -0,0 +1,231 @@
+//! Service manager integration for running the daemon at login.
+//!
+//! Writes a systemd user unit on Linux or a launchd agent on macOS that runs
+//! `ringlet daemon --stay-alive` from the current executable.
+
+use anyhow::{Context, Result, anyhow};
+use std::path::{Path, PathBuf};
+
+/// systemd unit name.
+const SYSTEMD_UNIT: &str = "ringlet.service";
+
+/// launchd job label.
+const LAUNCHD_LABEL: &str = "com.neullabs.ringlet.daemon";
+
+/// A service definition for the current platform.
+struct ServiceFile {
+    /// Where the definition is installed.
+    path: PathBuf,
+    /// File contents.
+    contents: String,
+    /// Commands to activate the service after installing.
+    enable: Vec<String>,
+    /// Commands to deactivate the service before removing.
+    disable: Vec<String>,
+}
+
+/// Install the daemon as a user service.
+pub fn install(json: bool) -> Result<()> {
+    let exe = std::env::current_exe().context("Failed to locate ringlet executable")?;
+    let service = service_file(&exe)?;
+
+    if let Some(parent) = service.path.parent() {
+        std::fs::create_dir_all(parent)
+            .context(format!("Failed to create directory: {:?}", parent))?;
+    }
+    std::fs::write(&service.path, &service.contents)
+        .context(format!("Failed to write service file: {:?}", service.path))?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "success": "Service installed",
+                "path": service.path,
+                "commands": service.enable,
+            }))?
+        );
+    } else {
+        println!("Service installed: {}", service.path.display());
+        println!();
+        println!("To start it now and at every login, run:");
+        for command in &service.enable {
+            println!("  {}", command);
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove the daemon user service.
+pub fn uninstall(json: bool) -> Result<()> {
+    let exe = std::env::current_exe().context("Failed to locate ringlet executable")?;
+    let service = service_file(&exe)?;
+
+    if !service.path.exists() {
+        if json {
+            println!(
+                "{}",
+                serde_json::json!({"success": "Service not installed"})
+            );
+        } else {
+            println!("Service not installed");
+        }
+        return Ok(());
+    }
+
+    std::fs::remove_file(&service.path)
+        .context(format!("Failed to remove service file: {:?}", service.path))?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "success": "Service removed",
+                "path": service.path,
+                "commands": service.disable,
+            }))?
+        );
+    } else {
+        println!("Service removed: {}", service.path.display());
+        println!();
+        println!("If it is still loaded, stop it with:");
+        for command in &service.disable {
+            println!("  {}", command);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn service_file(exe: &Path) -> Result<ServiceFile> {
+    let unit_dir = dirs::config_dir()
+        .ok_or_else(|| anyhow!("Could not determine config directory"))?
+        .join("systemd")
+        .join("user");
+
+    Ok(ServiceFile {
+        path: unit_dir.join(SYSTEMD_UNIT),
+        contents: systemd_unit(exe),
+        enable: vec![
+            "systemctl --user daemon-reload".to_string(),
+            format!("systemctl --user enable --now {}", SYSTEMD_UNIT),
+        ],
+        disable: vec![
+            format!("systemctl --user disable --now {}", SYSTEMD_UNIT),
+            "systemctl --user daemon-reload".to_string(),
+        ],
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn service_file(exe: &Path) -> Result<ServiceFile> {
+    let home = ringlet_core::home_dir().ok_or_else(|| anyhow!("Could not determine home"))?;
+    let path = home
+        .join("Library")
+        .join("LaunchAgents")
+        .join(format!("{}.plist", LAUNCHD_LABEL));
+    let log = ringlet_core::RingletPaths::default().daemon_log();
+
+    Ok(ServiceFile {
+        enable: vec![format!(
+            "launchctl bootstrap gui/$(id -u) {}",
+            path.display()
+        )],
+        disable: vec![format!("launchctl bootout gui/$(id -u)/{}", LAUNCHD_LABEL)],
+        contents: launchd_plist(exe, &log),
+        path,
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn service_file(_exe: &Path) -> Result<ServiceFile> {
+    Err(anyhow!(
+        "Service installation is only supported on Linux (systemd) and macOS (launchd)"
+    ))
+}
+
+/// Render a systemd user unit.
+fn systemd_unit(exe: &Path) -> String {
+    format!(
+        r#"[Unit]
+Description=Ringlet daemon
+After=network.target
+
+[Service]
+Type=simple
+ExecStart="{}" daemon --stay-alive
+Restart=on-failure
+RestartSec=5
+
+[Install]
+WantedBy=default.target
+"#,
+        exe.display()
+    )
+}
+
+/// Render a launchd agent plist.
+fn launchd_plist(exe: &Path, log: &Path) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{label}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{exe}</string>
+        <string>daemon</string>
+        <string>--stay-alive</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <dict>
+        <key>SuccessfulExit</key>
+        <false/>
+    </dict>
+    <key>StandardOutPath</key>
+    <string>{log}</string>
+    <key>StandardErrorPath</key>
+    <string>{log}</string>
+</dict>
+</plist>
+"#,
+        label = LAUNCHD_LABEL,
+        exe = xml_escape(&exe.display().to_string()),
+        log = xml_escape(&log.display().to_string()),
+    )
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_systemd_unit_runs_the_daemon() {
+        let unit = systemd_unit(Path::new("/opt/my tools/ringlet"));
+        assert!(unit.contains("ExecStart=\"/opt/my tools/ringlet\" daemon --stay-alive\n"));
+        assert!(unit.contains("Restart=on-failure"));
+        assert!(unit.contains("WantedBy=default.target"));
+    }
+
+    #[test]
+    fn test_launchd_plist_escapes_paths() {
+        let plist = launchd_plist(Path::new("/Users/a&b/bin/ringlet"), Path::new("/tmp/<log>"));
+        assert!(plist.contains(&format!("<string>{}</string>", LAUNCHD_LABEL)));
+        assert!(plist.contains("<string>/Users/a&amp;b/bin/ringlet</string>"));
+        assert!(plist.contains("<string>/tmp/&lt;log&gt;</string>"));
+        assert!(plist.contains("<string>--stay-alive</string>"));
+    }
+}
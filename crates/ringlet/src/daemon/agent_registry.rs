@@ -133,6 +133,7 @@ impl AgentRegistry {
                     default_provider: manifest.profile.default_provider.clone(),
                     supports_hooks: manifest.supports_hooks,
                     last_used: None, // TODO: track from telemetry
+                    compatible_providers: Vec::new(),
                 }
             })
             .collect();
@@ -162,6 +163,7 @@ impl AgentRegistry {
             default_provider: manifest.profile.default_provider.clone(),
             supports_hooks: manifest.supports_hooks,
             last_used: None,
+            compatible_providers: Vec::new(),
         })
     }
 }
@@ -12,8 +12,11 @@ use chrono::{DateTime, Utc};
 use ringlet_core::AgentType;
 use ringlet_core::TokenUsage;
 use serde::Deserialize;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 use tracing::{debug, trace, warn};
 use walkdir::WalkDir;
 
@@ -30,7 +33,19 @@ pub fn get_data_dir() -> PathBuf {
     }
 }
 
+/// Maximum number of JSONL files parsed at once.
+const MAX_OPEN_FILES: usize = 8;
+
+/// Lines longer than this are skipped without being buffered.
+///
+/// Usage lines are small; multi-megabyte lines are tool output or pasted
+/// images that carry no usage data.
+const MAX_LINE_BYTES: u64 = 8 * 1024 * 1024;
+
 /// Scan Claude's projects directory for usage data.
+///
+/// Files are streamed line by line on blocking threads, at most
+/// `MAX_OPEN_FILES` at a time, and only parsed entries are retained.
 pub async fn scan_usage(claude_dir: &Path) -> Result<Vec<UsageEntry>> {
     let projects_dir = claude_dir.join("projects");
     if !projects_dir.exists() {
@@ -38,74 +53,121 @@ pub async fn scan_usage(claude_dir: &Path) -> Result<Vec<UsageEntry>> {
         return Ok(Vec::new());
     }
 
-    let mut entries = Vec::new();
-
     // Walk through all subdirectories looking for .jsonl files
-    for entry in WalkDir::new(&projects_dir)
+    let files: Vec<PathBuf> = WalkDir::new(&projects_dir)
         .follow_links(true)
         .into_iter()
         .filter_map(|e| e.ok())
-    {
-        let path = entry.path();
-        if path.is_file() && path.extension().is_some_and(|ext| ext == "jsonl") {
+        .map(|entry| entry.into_path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect();
+
+    let permits = Arc::new(Semaphore::new(MAX_OPEN_FILES));
+    let mut tasks = JoinSet::new();
+    for (index, path) in files.into_iter().enumerate() {
+        let permit = permits
+            .clone()
+            .acquire_owned()
+            .await
+            .context("Claude scan was cancelled")?;
+        tasks.spawn_blocking(move || {
+            let _permit = permit;
             trace!("Parsing Claude JSONL file: {:?}", path);
-            match parse_jsonl_file(path) {
-                Ok(file_entries) => {
-                    debug!(
-                        "Parsed {} entries from {:?}",
-                        file_entries.len(),
-                        path.file_name()
-                    );
-                    entries.extend(file_entries);
-                }
-                Err(e) => {
-                    warn!("Failed to parse {:?}: {}", path, e);
-                }
+            let result = parse_jsonl_file(&path);
+            (index, path, result)
+        });
+    }
+
+    let mut parsed = Vec::new();
+    while let Some(task) = tasks.join_next().await {
+        let (index, path, result) = task.context("Claude scan task failed")?;
+        match result {
+            Ok(file_entries) => {
+                debug!(
+                    "Parsed {} entries from {:?}",
+                    file_entries.len(),
+                    path.file_name()
+                );
+                parsed.push((index, file_entries));
+            }
+            Err(e) => {
+                warn!("Failed to parse {:?}: {}", path, e);
             }
         }
     }
 
-    Ok(entries)
+    // Keep walk order so results don't depend on which file finished first
+    parsed.sort_by_key(|(index, _)| *index);
+    Ok(parsed
+        .into_iter()
+        .flat_map(|(_, entries)| entries)
+        .collect())
 }
 
 /// Parse a single Claude JSONL file.
 fn parse_jsonl_file(path: &Path) -> Result<Vec<UsageEntry>> {
     let file =
         std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
-    let reader = BufReader::new(file);
-    let mut entries = Vec::new();
 
     // Extract project path from file path for attribution
     let project_path = extract_project_path(path);
+    let session_id = super::session_id_from_path(path);
+
+    parse_jsonl(
+        BufReader::new(file),
+        &project_path,
+        session_id.as_deref(),
+        path,
+    )
+}
 
-    for (line_num, line) in reader.lines().enumerate() {
-        let line = match line {
-            Ok(l) => l,
-            Err(e) => {
-                trace!("Failed to read line {} in {:?}: {}", line_num + 1, path, e);
-                continue;
-            }
-        };
+/// Parse Claude JSONL from a reader, one line at a time.
+///
+/// A single line buffer is reused, so memory stays bounded by the longest
+/// line kept (at most `MAX_LINE_BYTES`) plus the parsed entries.
+fn parse_jsonl<R: BufRead>(
+    mut reader: R,
+    project_path: &str,
+    session_id: Option<&str>,
+    path: &Path,
+) -> Result<Vec<UsageEntry>> {
+    let mut entries = Vec::new();
+    let mut line = Vec::new();
+    let mut line_num = 0;
+
+    loop {
+        line.clear();
+        let read = (&mut reader)
+            .take(MAX_LINE_BYTES)
+            .read_until(b'\n', &mut line)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        if read == 0 {
+            break;
+        }
+        line_num += 1;
+
+        if read as u64 == MAX_LINE_BYTES && line.last() != Some(&b'\n') {
+            trace!("Skipping oversized line {} in {:?}", line_num, path);
+            reader
+                .skip_until(b'\n')
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            continue;
+        }
 
-        if line.trim().is_empty() {
+        if line.trim_ascii().is_empty() {
             continue;
         }
 
         // Try to parse as a Claude usage entry
-        match serde_json::from_str::<ClaudeEntry>(&line) {
+        match serde_json::from_slice::<ClaudeEntry>(&line) {
             Ok(claude_entry) => {
-                if let Some(entry) = claude_entry.to_usage_entry(&project_path) {
+                if let Some(entry) = claude_entry.to_usage_entry(project_path, session_id) {
                     entries.push(entry);
                 }
             }
             Err(e) => {
                 // Not all lines contain usage data, this is expected
-                trace!(
-                    "Skipping non-usage line {} in {:?}: {}",
-                    line_num + 1,
-                    path,
-                    e
-                );
+                trace!("Skipping non-usage line {} in {:?}: {}", line_num, path, e);
             }
         }
     }
@@ -200,7 +262,7 @@ struct ClaudeUsage {
 
 impl ClaudeEntry {
     /// Convert to a UsageEntry if this entry contains usage data.
-    fn to_usage_entry(&self, project_path: &str) -> Option<UsageEntry> {
+    fn to_usage_entry(&self, project_path: &str, session_id: Option<&str>) -> Option<UsageEntry> {
         // Must have message with usage data
         let usage = self.message.as_ref()?.usage.as_ref()?;
 
@@ -239,6 +301,7 @@ impl ClaudeEntry {
             },
             cost_usd: self.cost_usd,
             project_path: project_path.to_string(),
+            session_id: session_id.map(str::to_string),
         })
     }
 }
@@ -266,7 +329,9 @@ mod tests {
         }"#;
 
         let entry: ClaudeEntry = serde_json::from_str(json).unwrap();
-        let usage_entry = entry.to_usage_entry("/project/test").unwrap();
+        let usage_entry = entry
+            .to_usage_entry("/project/test", Some("session"))
+            .unwrap();
 
         assert_eq!(usage_entry.agent, AgentType::Claude);
         assert_eq!(usage_entry.message_id, "msg_123");
@@ -289,7 +354,7 @@ mod tests {
         }"#;
 
         let entry: ClaudeEntry = serde_json::from_str(json).unwrap();
-        assert!(entry.to_usage_entry("/project").is_none());
+        assert!(entry.to_usage_entry("/project", None).is_none());
     }
 
     #[test]
@@ -300,4 +365,84 @@ mod tests {
         let path2 = PathBuf::from("/home/user/.claude/projects/work/sub/session.jsonl");
         assert_eq!(extract_project_path(&path2), "work");
     }
+
+    /// Generates JSONL on the fly and records how it is read.
+    struct SyntheticJsonl {
+        lines_left: usize,
+        oversized_line_at: usize,
+        pending: Vec<u8>,
+        pos: usize,
+        bytes_read: u64,
+        largest_read: usize,
+    }
+
+    impl SyntheticJsonl {
+        fn next_line(&mut self) -> Option<Vec<u8>> {
+            if self.lines_left == 0 {
+                return None;
+            }
+            self.lines_left -= 1;
+            if self.lines_left == self.oversized_line_at {
+                let mut line = vec![b'x'; MAX_LINE_BYTES as usize + 1024];
+                line.push(b'\n');
+                return Some(line);
+            }
+            Some(
+                format!(
+                    r#"{{"timestamp":"2025-01-20T10:30:00.000Z","message":{{"usage":{{"input_tokens":10,"output_tokens":5}}}},"messageId":"msg_{}"}}"#,
+                    self.lines_left
+                )
+                .into_bytes()
+                .into_iter()
+                .chain(*b"\n")
+                .collect(),
+            )
+        }
+    }
+
+    impl Read for SyntheticJsonl {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.pos == self.pending.len() {
+                match self.next_line() {
+                    Some(line) => {
+                        self.pending = line;
+                        self.pos = 0;
+                    }
+                    None => return Ok(0),
+                }
+            }
+            let n = buf.len().min(self.pending.len() - self.pos);
+            buf[..n].copy_from_slice(&self.pending[self.pos..self.pos + n]);
+            self.pos += n;
+            self.bytes_read += n as u64;
+            self.largest_read = self.largest_read.max(buf.len());
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_parse_jsonl_streams_large_input() {
+        let mut source = SyntheticJsonl {
+            lines_left: 50_001,
+            oversized_line_at: 25_000,
+            pending: Vec::new(),
+            pos: 0,
+            bytes_read: 0,
+            largest_read: 0,
+        };
+
+        let entries = parse_jsonl(
+            BufReader::new(&mut source),
+            "big-project",
+            Some("big"),
+            Path::new("big.jsonl"),
+        )
+        .unwrap();
+
+        // Every usage line is parsed and the oversized line is skipped
+        assert_eq!(entries.len(), 50_000);
+        assert!(source.bytes_read > MAX_LINE_BYTES);
+        // The input is consumed in small chunks rather than loaded whole
+        assert!(source.largest_read <= 64 * 1024, "{}", source.largest_read);
+    }
 }
@@ -78,6 +78,7 @@ fn parse_jsonl_file(path: &Path) -> Result<Vec<UsageEntry>> {
 
     // Extract session path from file path for attribution
     let session_path = extract_session_path(path);
+    let session_id = super::session_id_from_path(path);
     let mut entry_counter = 0u64;
 
     for (line_num, line) in reader.lines().enumerate() {
@@ -98,8 +99,11 @@ fn parse_jsonl_file(path: &Path) -> Result<Vec<UsageEntry>> {
             Ok(codex_entry) => {
                 // Only process token_count entries
                 if codex_entry.entry_type.as_deref() == Some("token_count")
-                    && let Some(entry) =
-                        codex_entry.to_usage_entry(&session_path, &mut entry_counter)
+                    && let Some(entry) = codex_entry.to_usage_entry(
+                        &session_path,
+                        session_id.as_deref(),
+                        &mut entry_counter,
+                    )
                 {
                     entries.push(entry);
                 }
@@ -221,7 +225,12 @@ struct CodexMetadata {
 
 impl CodexEntry {
     /// Convert to a UsageEntry if this entry contains usage data.
-    fn to_usage_entry(&self, session_path: &str, counter: &mut u64) -> Option<UsageEntry> {
+    fn to_usage_entry(
+        &self,
+        session_path: &str,
+        session_id: Option<&str>,
+        counter: &mut u64,
+    ) -> Option<UsageEntry> {
         let payload = self.payload.as_ref()?;
         let info = payload.info.as_ref()?;
         let usage = info.usage.as_ref()?;
@@ -281,6 +290,7 @@ impl CodexEntry {
             },
             cost_usd: None, // Codex doesn't pre-calculate costs
             project_path: session_path.to_string(),
+            session_id: session_id.map(str::to_string),
         })
     }
 }
@@ -312,7 +322,9 @@ mod tests {
 
         let entry: CodexEntry = serde_json::from_str(json).unwrap();
         let mut counter = 0;
-        let usage_entry = entry.to_usage_entry("session123", &mut counter).unwrap();
+        let usage_entry = entry
+            .to_usage_entry("session123", Some("rollout"), &mut counter)
+            .unwrap();
 
         assert_eq!(usage_entry.agent, AgentType::Codex);
         assert_eq!(usage_entry.model, "gpt-4o");
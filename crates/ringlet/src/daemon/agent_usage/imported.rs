@@ -0,0 +1,450 @@
+//! Usage imported from files computed outside ringlet.
+//!
+//! Rows are validated up front and stored as [`UsageEntry`] lines in
+//! `imported-usage.jsonl`, then merged with the native agent scans so they
+//! count in aggregates, budgets and exports like any other usage.
+
+use super::UsageEntry;
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use ringlet_core::{AgentType, TokenUsage, UsageImportFormat};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use tracing::warn;
+
+/// CSV columns every row must have.
+const REQUIRED_COLUMNS: [&str; 3] = ["timestamp", "agent", "model"];
+
+/// CSV columns that may be left out, defaulting to zero or none.
+const OPTIONAL_COLUMNS: [&str; 6] = [
+    "input_tokens",
+    "output_tokens",
+    "cache_creation_input_tokens",
+    "cache_read_input_tokens",
+    "cost_usd",
+    "id",
+];
+
+/// A JSON record, shaped like the `usage export --format jsonl` output.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonRow {
+    timestamp: String,
+    agent: String,
+    model: String,
+    #[serde(default)]
+    tokens: Option<TokenUsage>,
+    #[serde(default)]
+    cost_usd: Option<f64>,
+    #[serde(default)]
+    id: Option<String>,
+}
+
+/// Parse `content`, returning every malformed row as `line N: reason`.
+///
+/// Nothing is accepted unless every row is valid, so a bad file can be fixed
+/// and imported again without leaving part of it behind.
+pub fn parse(content: &str, format: UsageImportFormat) -> Result<Vec<UsageEntry>, Vec<String>> {
+    match format {
+        UsageImportFormat::Csv => parse_csv(content),
+        UsageImportFormat::Json => parse_json(content),
+    }
+}
+
+fn parse_csv(content: &str) -> Result<Vec<UsageEntry>, Vec<String>> {
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty());
+
+    let Some((header_line, header)) = lines.next() else {
+        return Err(vec!["file is empty".to_string()]);
+    };
+    let columns: Vec<&str> = header.split(',').map(str::trim).collect();
+
+    let mut errors = Vec::new();
+    for column in &columns {
+        if !REQUIRED_COLUMNS.contains(column) && !OPTIONAL_COLUMNS.contains(column) {
+            errors.push(format!("line {}: unknown column '{}'", header_line, column));
+        }
+    }
+    for column in REQUIRED_COLUMNS {
+        if !columns.contains(&column) {
+            errors.push(format!(
+                "line {}: missing required column '{}'",
+                header_line, column
+            ));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut entries = Vec::new();
+    for (line_number, line) in lines {
+        let values: Vec<&str> = line.split(',').map(str::trim).collect();
+        if values.len() != columns.len() {
+            errors.push(format!(
+                "line {}: expected {} values, found {}",
+                line_number,
+                columns.len(),
+                values.len()
+            ));
+            continue;
+        }
+
+        let field = |name: &str| {
+            columns
+                .iter()
+                .position(|column| *column == name)
+                .map(|index| values[index])
+                .filter(|value| !value.is_empty())
+        };
+        let row = csv_tokens(&field).and_then(|tokens| {
+            let cost_usd = field("cost_usd")
+                .map(|value| {
+                    value
+                        .parse::<f64>()
+                        .map_err(|_| format!("invalid cost_usd '{}'", value))
+                })
+                .transpose()?;
+            to_entry(
+                field("timestamp").unwrap_or_default(),
+                field("agent").unwrap_or_default(),
+                field("model").unwrap_or_default(),
+                tokens,
+                cost_usd,
+                field("id"),
+            )
+        });
+        match row {
+            Ok(entry) => entries.push(entry),
+            Err(e) => errors.push(format!("line {}: {}", line_number, e)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(entries)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Read the token columns of a CSV row.
+fn csv_tokens<'a>(field: &impl Fn(&str) -> Option<&'a str>) -> Result<TokenUsage, String> {
+    let count = |name: &str| {
+        field(name).map_or(Ok(0), |value| {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("invalid {} '{}'", name, value))
+        })
+    };
+    Ok(TokenUsage {
+        input_tokens: count("input_tokens")?,
+        output_tokens: count("output_tokens")?,
+        cache_creation_input_tokens: count("cache_creation_input_tokens")?,
+        cache_read_input_tokens: count("cache_read_input_tokens")?,
+    })
+}
+
+fn parse_json(content: &str) -> Result<Vec<UsageEntry>, Vec<String>> {
+    let trimmed = content.trim_start();
+    let rows: Vec<(String, serde_json::Value)> = if trimmed.starts_with('[') {
+        // An array has no useful line per record, so errors name the record
+        match serde_json::from_str::<Vec<serde_json::Value>>(trimmed) {
+            Ok(values) => values
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| (format!("record {}", index + 1), value))
+                .collect(),
+            Err(e) => return Err(vec![format!("line {}: {}", e.line(), e)]),
+        }
+    } else {
+        let mut rows = Vec::new();
+        let mut errors = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(value) => rows.push((format!("line {}", index + 1), value)),
+                Err(e) => errors.push(format!("line {}: {}", index + 1, e)),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        rows
+    };
+
+    if rows.is_empty() {
+        return Err(vec!["file is empty".to_string()]);
+    }
+
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    for (location, value) in rows {
+        let entry = serde_json::from_value::<JsonRow>(value)
+            .map_err(|e| e.to_string())
+            .and_then(|row| {
+                to_entry(
+                    &row.timestamp,
+                    &row.agent,
+                    &row.model,
+                    row.tokens.unwrap_or_default(),
+                    row.cost_usd,
+                    row.id.as_deref().filter(|id| !id.is_empty()),
+                )
+            });
+        match entry {
+            Ok(entry) => entries.push(entry),
+            Err(e) => errors.push(format!("{}: {}", location, e)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(entries)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Validate a row and turn it into a usage entry.
+///
+/// Rows without an `id` are keyed on all their values, so importing the same
+/// file twice doesn't count it twice.
+fn to_entry(
+    timestamp: &str,
+    agent: &str,
+    model: &str,
+    tokens: TokenUsage,
+    cost_usd: Option<f64>,
+    id: Option<&str>,
+) -> Result<UsageEntry, String> {
+    let timestamp = parse_timestamp(timestamp)?;
+    let agent: AgentType = agent.parse()?;
+    if model.is_empty() {
+        return Err("model is empty".to_string());
+    }
+    if let Some(cost) = cost_usd
+        && (!cost.is_finite() || cost < 0.0)
+    {
+        return Err(format!("invalid cost_usd '{}'", cost));
+    }
+
+    let message_id = match id {
+        Some(id) => id.to_string(),
+        None => format!(
+            "import:{}:{}:{}:{}:{}:{}:{}",
+            timestamp.to_rfc3339(),
+            model,
+            tokens.input_tokens,
+            tokens.output_tokens,
+            tokens.cache_creation_input_tokens,
+            tokens.cache_read_input_tokens,
+            cost_usd.map(|cost| cost.to_string()).unwrap_or_default()
+        ),
+    };
+
+    Ok(UsageEntry {
+        timestamp,
+        agent,
+        message_id,
+        request_id: None,
+        model: model.to_string(),
+        tokens,
+        cost_usd,
+        project_path: String::new(),
+        session_id: None,
+    })
+}
+
+/// Accept an RFC 3339 timestamp, or a bare date for daily totals (midnight UTC).
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, String> {
+    if value.is_empty() {
+        return Err("timestamp is empty".to_string());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc())
+        })
+        .map_err(|_| {
+            format!(
+                "invalid timestamp '{}' (expected RFC 3339 or YYYY-MM-DD)",
+                value
+            )
+        })
+}
+
+/// Load all imported entries.
+pub fn load(path: &Path) -> Result<Vec<UsageEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let reader = BufReader::new(File::open(path)?);
+    Ok(reader
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| match serde_json::from_str(&line) {
+            Ok(entry) => Some(entry),
+            Err(err) => {
+                warn!("Skipping invalid imported usage record: {}", err);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Store entries not imported before. Returns how many were added.
+pub fn append(path: &Path, entries: &[UsageEntry]) -> Result<usize> {
+    let mut seen: HashSet<String> = load(path)?.iter().map(UsageEntry::dedup_key).collect();
+
+    let mut content = String::new();
+    let mut added = 0;
+    for entry in entries {
+        if seen.insert(entry.dedup_key()) {
+            content.push_str(&serde_json::to_string(entry)?);
+            content.push('\n');
+            added += 1;
+        }
+    }
+
+    if added > 0 {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(content.as_bytes()))
+            .context("Failed to write imported usage")?;
+    }
+    Ok(added)
+}
+
+/// Delete imported entries, keeping those dated on or after `before`.
+///
+/// Returns the number removed.
+pub fn purge(path: &Path, before: Option<NaiveDate>) -> Result<usize> {
+    let entries = load(path)?;
+    let total = entries.len();
+    let kept: Vec<UsageEntry> = match before {
+        Some(before) => entries
+            .into_iter()
+            .filter(|entry| entry.timestamp.date_naive() >= before)
+            .collect(),
+        None => Vec::new(),
+    };
+
+    if kept.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(path).context("Failed to remove imported usage")?;
+        }
+    } else {
+        let mut content = String::new();
+        for entry in &kept {
+            content.push_str(&serde_json::to_string(entry)?);
+            content.push('\n');
+        }
+        let tmp_path = path.with_extension("jsonl.tmp");
+        std::fs::write(&tmp_path, content).context("Failed to write imported usage")?;
+        std::fs::rename(&tmp_path, path).context("Failed to replace imported usage")?;
+    }
+
+    Ok(total - kept.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let content = "timestamp,agent,model,input_tokens,output_tokens,cost_usd\n\
+                       2026-01-05T10:00:00Z,claude,claude-sonnet-4,1000,200,0.01\n\
+                       2026-01-06,codex,gpt-5,500,,\n";
+        let entries = parse(content, UsageImportFormat::Csv).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].agent, AgentType::Claude);
+        assert_eq!(entries[0].tokens.input_tokens, 1000);
+        assert_eq!(entries[0].cost_usd, Some(0.01));
+        assert_eq!(
+            entries[1].timestamp.to_rfc3339(),
+            "2026-01-06T00:00:00+00:00"
+        );
+        assert_eq!(entries[1].tokens.output_tokens, 0);
+        assert_eq!(entries[1].cost_usd, None);
+    }
+
+    #[test]
+    fn test_parse_csv_reports_malformed_rows() {
+        let errors = parse("timestamp,agent,tokens\n", UsageImportFormat::Csv).unwrap_err();
+        assert_eq!(
+            errors,
+            [
+                "line 1: unknown column 'tokens'",
+                "line 1: missing required column 'model'"
+            ]
+        );
+
+        let content = "timestamp,agent,model,input_tokens\n\
+                       2026-01-05,claude,m,10\n\
+                       yesterday,claude,m,10\n\
+                       2026-01-05,cursor,m,10\n\
+                       2026-01-05,claude,m,-3\n\
+                       2026-01-05,claude,m\n";
+        let errors = parse(content, UsageImportFormat::Csv).unwrap_err();
+        assert_eq!(errors.len(), 4);
+        assert!(
+            errors[0].starts_with("line 3: invalid timestamp"),
+            "{:?}",
+            errors
+        );
+        assert!(errors[1].starts_with("line 4: unknown agent 'cursor'"));
+        assert_eq!(errors[2], "line 5: invalid input_tokens '-3'");
+        assert_eq!(errors[3], "line 6: expected 4 values, found 3");
+    }
+
+    #[test]
+    fn test_parse_json_lines_and_array() {
+        let lines = r#"{"timestamp":"2026-01-05T10:00:00Z","agent":"claude","model":"m","tokens":{"input_tokens":1,"output_tokens":2,"cache_creation_input_tokens":0,"cache_read_input_tokens":0},"cost_usd":null}
+
+{"timestamp":"2026-01-05","agent":"opencode","model":"m","id":"row-2"}"#;
+        let entries = parse(lines, UsageImportFormat::Json).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].dedup_key(), "opencode:row-2");
+
+        let array = r#"[{"timestamp":"2026-01-05","agent":"claude","model":"m"},
+                        {"timestamp":"2026-01-05","agent":"claude"}]"#;
+        let errors = parse(array, UsageImportFormat::Json).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("record 2: missing field `model`"));
+    }
+
+    #[test]
+    fn test_append_skips_rows_already_imported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("imported-usage.jsonl");
+        let content = "timestamp,agent,model,input_tokens\n\
+                       2026-01-05,claude,m,10\n\
+                       2026-01-06,claude,m,10\n";
+        let entries = parse(content, UsageImportFormat::Csv).unwrap();
+
+        assert_eq!(append(&path, &entries).unwrap(), 2);
+        assert_eq!(append(&path, &entries).unwrap(), 0);
+        assert_eq!(load(&path).unwrap().len(), 2);
+
+        let before = NaiveDate::from_ymd_opt(2026, 1, 6).unwrap();
+        assert_eq!(purge(&path, Some(before)).unwrap(), 1);
+        assert_eq!(load(&path).unwrap().len(), 1);
+    }
+}
@@ -6,17 +6,21 @@
 //! - **Claude Code**: `~/.claude/projects/**/*.jsonl`
 //! - **Codex CLI**: `~/.codex/sessions/**/*.jsonl`
 //! - **OpenCode**: `~/.local/share/opencode/storage/**/*.json`
+//!
+//! Usage computed elsewhere can be added with `ringlet usage import` (see [`imported`]).
 
 pub mod claude;
 pub mod codex;
+pub mod imported;
 pub mod opencode;
 
 use crate::daemon::pricing::PricingLoader;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use ringlet_core::{AgentType, CostBreakdown, RingletPaths, TokenUsage};
+use ringlet_core::config::UsageConfig;
+use ringlet_core::{AgentType, CostBreakdown, RingletPaths, TokenUsage, UserConfig, expand_tilde};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use tracing::{debug, warn};
 
@@ -39,6 +43,9 @@ pub struct UsageEntry {
     pub cost_usd: Option<f64>,
     /// Project or session path (for profile attribution).
     pub project_path: String,
+    /// Agent session the entry belongs to (for session runtimes).
+    #[serde(default)]
+    pub session_id: Option<String>,
 }
 
 impl UsageEntry {
@@ -49,6 +56,61 @@ impl UsageEntry {
             None => format!("{}:{}", self.agent, self.message_id),
         }
     }
+
+    /// Cost of this entry split by token category.
+    ///
+    /// The agent-reported cost stays authoritative; model pricing only decides
+    /// how it divides between input, output and cache tokens. Entries without a
+    /// reported cost have none.
+    pub fn cost_breakdown(&self, pricing: &PricingLoader) -> Option<CostBreakdown> {
+        self.cost_usd
+            .map(|cost_usd| split_cost(&self.tokens, &self.model, cost_usd, pricing))
+    }
+}
+
+/// Name a session after its log file, as Claude and Codex keep one file per session.
+fn session_id_from_path(path: &Path) -> Option<String> {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .map(str::to_string)
+}
+
+/// First and last entry time of an agent session.
+pub type SessionSpan = (DateTime<Utc>, DateTime<Utc>);
+
+/// Span of each agent session, keyed by agent and session ID.
+///
+/// Entries without a session ID are not counted.
+pub fn session_spans(entries: &[UsageEntry]) -> HashMap<(AgentType, &str), SessionSpan> {
+    let mut spans = HashMap::<_, SessionSpan>::new();
+    for entry in entries {
+        let Some(session_id) = entry.session_id.as_deref() else {
+            continue;
+        };
+        spans
+            .entry((entry.agent, session_id))
+            .and_modify(|(first, last)| {
+                *first = (*first).min(entry.timestamp);
+                *last = (*last).max(entry.timestamp);
+            })
+            .or_insert((entry.timestamp, entry.timestamp));
+    }
+
+    spans
+}
+
+/// Split an agent-reported total across token categories using model pricing.
+fn split_cost(
+    tokens: &TokenUsage,
+    model: &str,
+    total_cost: f64,
+    pricing: &PricingLoader,
+) -> CostBreakdown {
+    pricing
+        .get_model_pricing(model)
+        .map(|model_pricing| model_pricing.calculate_cost(tokens))
+        .unwrap_or_default()
+        .scaled_to(total_cost)
 }
 
 /// Result of scanning all agents.
@@ -92,6 +154,19 @@ impl ScanResult {
         self.warnings.push(warning);
     }
 
+    /// Add entries imported from external files.
+    ///
+    /// Call [`Self::deduplicate`] afterwards, as they may repeat native entries.
+    pub fn add_imported_entries(&mut self, entries: Vec<UsageEntry>) {
+        for entry in &entries {
+            self.by_agent
+                .entry(entry.agent)
+                .or_default()
+                .push(entry.clone());
+        }
+        self.entries.extend(entries);
+    }
+
     /// Total number of entries.
     pub fn total_entries(&self) -> usize {
         self.entries.len()
@@ -110,68 +185,73 @@ impl ScanResult {
     }
 }
 
+/// Data directories scanned for each supported agent.
+#[derive(Debug, Clone)]
+pub struct AgentDataDirs {
+    pub claude: PathBuf,
+    pub codex: PathBuf,
+    pub opencode: PathBuf,
+}
+
+impl AgentDataDirs {
+    /// Resolve directories from `[usage]` overrides, falling back to each
+    /// agent's env var or default location.
+    pub fn from_config(config: &UsageConfig) -> Self {
+        let resolve = |dir: &Option<String>, default: fn() -> PathBuf| {
+            dir.as_deref().map(expand_tilde).unwrap_or_else(default)
+        };
+
+        Self {
+            claude: resolve(&config.claude_dir, claude::get_data_dir),
+            codex: resolve(&config.codex_dir, codex::get_data_dir),
+            opencode: resolve(&config.opencode_dir, opencode::get_data_dir),
+        }
+    }
+
+    /// Resolve directories from the user config file.
+    pub fn load(paths: &RingletPaths) -> Self {
+        let config = UserConfig::load(&paths.config_file()).unwrap_or_default();
+        Self::from_config(&config.usage)
+    }
+}
+
 /// Scan all supported agents for usage data.
 ///
 /// This is the main entry point for usage tracking. It scans data directories
 /// for all supported agents and returns aggregated usage entries.
-pub async fn scan_all_agents() -> Result<ScanResult> {
+pub async fn scan_all_agents(dirs: &AgentDataDirs) -> Result<ScanResult> {
     let mut result = ScanResult::new();
 
-    // Scan Claude
-    let claude_dir = claude::get_data_dir();
-    if claude_dir.exists() {
-        debug!("Scanning Claude usage from {:?}", claude_dir);
-        match claude::scan_usage(&claude_dir).await {
-            Ok(entries) => {
-                debug!("Found {} Claude entries", entries.len());
-                result.add_agent_entries(AgentType::Claude, entries);
-            }
-            Err(e) => {
-                let warning = format!("Failed to scan Claude usage: {}", e);
-                warn!("{}", warning);
-                result.add_warning(warning);
-            }
-        }
-    } else {
-        debug!("Claude data directory not found: {:?}", claude_dir);
-    }
-
-    // Scan Codex
-    let codex_dir = codex::get_data_dir();
-    if codex_dir.exists() {
-        debug!("Scanning Codex usage from {:?}", codex_dir);
-        match codex::scan_usage(&codex_dir).await {
-            Ok(entries) => {
-                debug!("Found {} Codex entries", entries.len());
-                result.add_agent_entries(AgentType::Codex, entries);
+    // The scans are independent and I/O bound, so run them side by side
+    let (claude, codex, opencode) = tokio::join!(
+        spawn_scan(AgentType::Claude, "Claude", dirs.claude.clone()),
+        spawn_scan(AgentType::Codex, "Codex", dirs.codex.clone()),
+        spawn_scan(AgentType::OpenCode, "OpenCode", dirs.opencode.clone()),
+    );
+
+    // Combine in a fixed order so results don't depend on which scan finished first
+    for (agent, name, scan) in [
+        (AgentType::Claude, "Claude", claude),
+        (AgentType::Codex, "Codex", codex),
+        (AgentType::OpenCode, "OpenCode", opencode),
+    ] {
+        match scan {
+            Ok(Some(Ok(entries))) => {
+                debug!("Found {} {} entries", entries.len(), name);
+                result.add_agent_entries(agent, entries);
             }
-            Err(e) => {
-                let warning = format!("Failed to scan Codex usage: {}", e);
+            Ok(Some(Err(e))) => {
+                let warning = format!("Failed to scan {} usage: {}", name, e);
                 warn!("{}", warning);
                 result.add_warning(warning);
             }
-        }
-    } else {
-        debug!("Codex data directory not found: {:?}", codex_dir);
-    }
-
-    // Scan OpenCode
-    let opencode_dir = opencode::get_data_dir();
-    if opencode_dir.exists() {
-        debug!("Scanning OpenCode usage from {:?}", opencode_dir);
-        match opencode::scan_usage(&opencode_dir).await {
-            Ok(entries) => {
-                debug!("Found {} OpenCode entries", entries.len());
-                result.add_agent_entries(AgentType::OpenCode, entries);
-            }
+            Ok(None) => {}
             Err(e) => {
-                let warning = format!("Failed to scan OpenCode usage: {}", e);
+                let warning = format!("{} usage scan did not complete: {}", name, e);
                 warn!("{}", warning);
                 result.add_warning(warning);
             }
         }
-    } else {
-        debug!("OpenCode data directory not found: {:?}", opencode_dir);
     }
 
     // Deduplicate all entries
@@ -180,6 +260,29 @@ pub async fn scan_all_agents() -> Result<ScanResult> {
     Ok(result)
 }
 
+/// Scan one agent's data directory on its own task.
+///
+/// Resolves to `None` when the directory does not exist.
+fn spawn_scan(
+    agent: AgentType,
+    name: &'static str,
+    dir: PathBuf,
+) -> tokio::task::JoinHandle<Option<Result<Vec<UsageEntry>>>> {
+    tokio::spawn(async move {
+        if !dir.exists() {
+            debug!("{} data directory not found: {:?}", name, dir);
+            return None;
+        }
+
+        debug!("Scanning {} usage from {:?}", name, dir);
+        Some(match agent {
+            AgentType::Claude => claude::scan_usage(&dir).await,
+            AgentType::Codex => codex::scan_usage(&dir).await,
+            AgentType::OpenCode => opencode::scan_usage(&dir).await,
+        })
+    })
+}
+
 /// Capture a baseline snapshot of native usage entries for a specific profile home.
 pub async fn snapshot_for_profile(
     agent_id: &str,
@@ -230,25 +333,18 @@ pub async fn delta_for_profile(
         }
     }
 
+    let pricing = PricingLoader::new(paths.clone());
     let cost = if has_native_cost {
-        Some(CostBreakdown {
-            total_cost,
-            ..Default::default()
-        })
+        Some(split_cost(&tokens, model, total_cost, &pricing))
     } else {
-        PricingLoader::new(paths.clone()).calculate_cost(&tokens, model, provider_id)
+        pricing.calculate_cost(&tokens, model, provider_id)
     };
 
     Ok(Some(UsageDelta { tokens, cost }))
 }
 
 fn agent_type_for_id(agent_id: &str) -> Option<AgentType> {
-    match agent_id {
-        "claude" => Some(AgentType::Claude),
-        "codex" => Some(AgentType::Codex),
-        "opencode" => Some(AgentType::OpenCode),
-        _ => None,
-    }
+    agent_id.parse().ok()
 }
 
 async fn scan_agent_profile_home(agent: AgentType, profile_home: &Path) -> Result<Vec<UsageEntry>> {
@@ -300,6 +396,7 @@ mod tests {
             tokens: TokenUsage::default(),
             cost_usd: None,
             project_path: "/project".to_string(),
+            session_id: None,
         };
 
         let entry2 = UsageEntry {
@@ -311,12 +408,26 @@ mod tests {
             tokens: TokenUsage::default(),
             cost_usd: None,
             project_path: "/project".to_string(),
+            session_id: None,
         };
 
         assert_eq!(entry1.dedup_key(), "claude:msg_123:req_456");
         assert_eq!(entry2.dedup_key(), "codex:msg_789");
     }
 
+    #[test]
+    fn test_agent_data_dirs_overrides() {
+        let config = UsageConfig {
+            claude_dir: Some("/data/claude".to_string()),
+            ..Default::default()
+        };
+        let dirs = AgentDataDirs::from_config(&config);
+
+        assert_eq!(dirs.claude, PathBuf::from("/data/claude"));
+        assert_eq!(dirs.codex, codex::get_data_dir());
+        assert_eq!(dirs.opencode, opencode::get_data_dir());
+    }
+
     #[test]
     fn test_scan_result_deduplicate() {
         let mut result = ScanResult::new();
@@ -330,6 +441,7 @@ mod tests {
             tokens: TokenUsage::default(),
             cost_usd: None,
             project_path: "/project".to_string(),
+            session_id: None,
         };
 
         // Add the same entry twice
@@ -341,6 +453,65 @@ mod tests {
         assert_eq!(result.entries.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_scan_all_agents_combines_concurrent_scans() {
+        let dir = tempfile::tempdir().unwrap();
+        let message_dir = dir.path().join("opencode/storage/message/sess_456");
+        std::fs::create_dir_all(&message_dir).unwrap();
+        std::fs::write(
+            message_dir.join("msg_123.json"),
+            r#"{
+                "id": "msg_123",
+                "sessionId": "sess_456",
+                "model": "claude-sonnet-4",
+                "created_at": "2025-01-20T10:30:00Z",
+                "tokens": { "input_tokens": 1000, "output_tokens": 500 }
+            }"#,
+        )
+        .unwrap();
+
+        let dirs = AgentDataDirs {
+            claude: dir.path().join("missing-claude"),
+            codex: dir.path().join("missing-codex"),
+            opencode: dir.path().join("opencode"),
+        };
+        let result = scan_all_agents(&dirs).await.unwrap();
+
+        assert_eq!(result.entries.len(), 1);
+        assert_eq!(result.entries[0].agent, AgentType::OpenCode);
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_session_spans() {
+        let entry = |session_id: Option<&str>, minute: u32| UsageEntry {
+            timestamp: chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 1, 20, 10, minute, 0)
+                .unwrap(),
+            agent: AgentType::Claude,
+            message_id: format!("msg_{}", minute),
+            request_id: None,
+            model: "claude-sonnet-4".to_string(),
+            tokens: TokenUsage::default(),
+            cost_usd: None,
+            project_path: "/project".to_string(),
+            session_id: session_id.map(str::to_string),
+        };
+        let entries = vec![
+            entry(Some("a"), 30),
+            entry(Some("a"), 0),
+            entry(Some("a"), 10),
+            entry(Some("b"), 5),
+            entry(None, 59),
+        ];
+
+        let spans = session_spans(&entries);
+        assert_eq!(spans.len(), 2);
+        let (first, last) = spans[&(AgentType::Claude, "a")];
+        assert_eq!((last - first).num_minutes(), 30);
+        let (first, last) = spans[&(AgentType::Claude, "b")];
+        assert_eq!(first, last);
+    }
+
     #[test]
     fn test_agent_type_for_id() {
         assert_eq!(agent_type_for_id("claude"), Some(AgentType::Claude));
@@ -199,6 +199,7 @@ impl OpenCodeEntry {
             },
             cost_usd: self.cost_usd,
             project_path,
+            session_id: self.session_id.clone(),
         })
     }
 }
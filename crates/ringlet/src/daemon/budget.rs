@@ -0,0 +1,259 @@
+//! Spend budget monitoring.
+//!
+//! Compares current spend against the `[usage]` budget limits in the user
+//! config whenever new usage is observed. The first time a limit is crossed
+//! within a budget window the monitor broadcasts `BudgetExceeded` and records
+//! a pending alert that the CLI prints on its next invocation.
+
+use crate::daemon::events::SequencedEvent;
+use crate::daemon::handlers;
+use crate::daemon::server::ServerState;
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use ringlet_core::{BudgetPeriod, BudgetStatus, Event, RingletPaths, UserConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+
+/// Quiet period used to coalesce bursts of usage updates into one check.
+const CHECK_DEBOUNCE: Duration = Duration::from_secs(5);
+
+/// A budget limit that was crossed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetAlert {
+    /// Budget window that was exceeded.
+    pub period: BudgetPeriod,
+    /// Configured limit in USD.
+    pub limit: f64,
+    /// Spend when the limit was crossed, in USD.
+    pub actual: f64,
+    /// When the alert was raised.
+    pub raised_at: DateTime<Utc>,
+}
+
+/// Persisted alert state.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AlertLog {
+    /// Budget windows that have already alerted (see `BudgetPeriod::window_key`).
+    #[serde(default)]
+    notified: BTreeSet<String>,
+    /// Alerts not yet shown by the CLI.
+    #[serde(default)]
+    pending: Vec<BudgetAlert>,
+}
+
+impl AlertLog {
+    fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the log to a temporary file and rename it over `path`, so a
+    /// reader never sees a half-written log.
+    fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Load the log, apply `f` and save it, holding an exclusive lock on a
+    /// sibling lock file throughout.
+    ///
+    /// Both the daemon and the CLI update the log, so without the lock one of
+    /// them could overwrite alerts the other just recorded or cleared.
+    fn update<T>(path: &Path, f: impl FnOnce(&mut Self) -> T) -> Result<T> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let lock = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path.with_extension("lock"))?;
+        lock.lock()?;
+
+        let mut log = Self::load(path);
+        let result = f(&mut log);
+        log.save(path)?;
+        Ok(result)
+    }
+
+    /// Record newly exceeded budgets, returning alerts for windows not seen before.
+    fn record(
+        &mut self,
+        statuses: &[BudgetStatus],
+        today: NaiveDate,
+        now: DateTime<Utc>,
+    ) -> Vec<BudgetAlert> {
+        // Forget windows that have rolled over so the log stays small.
+        let current: BTreeSet<String> = [BudgetPeriod::Daily, BudgetPeriod::Monthly]
+            .iter()
+            .map(|period| period.window_key(today))
+            .collect();
+        self.notified.retain(|key| current.contains(key));
+
+        let mut alerts = Vec::new();
+        for status in statuses.iter().filter(|status| status.exceeded) {
+            if self.notified.insert(status.period.window_key(today)) {
+                let alert = BudgetAlert {
+                    period: status.period,
+                    limit: status.limit_usd,
+                    actual: status.spent_usd,
+                    raised_at: now,
+                };
+                self.pending.push(alert.clone());
+                alerts.push(alert);
+            }
+        }
+        alerts
+    }
+}
+
+/// Start the background budget monitor.
+///
+/// Checks once at startup and again after each burst of `UsageUpdated` events.
+pub fn spawn(state: Arc<ServerState>) {
+    let mut rx = state.events.subscribe();
+
+    tokio::spawn(async move {
+        check(&state).await;
+
+        loop {
+            match rx.recv().await {
+                Ok(SequencedEvent {
+                    event: Event::UsageUpdated { .. },
+                    ..
+                })
+                | Err(RecvError::Lagged(_)) => {}
+                Ok(_) => continue,
+                Err(RecvError::Closed) => break,
+            }
+
+            // Let the burst settle, then drain whatever queued up meanwhile.
+            tokio::time::sleep(CHECK_DEBOUNCE).await;
+            while rx.try_recv().is_ok() {}
+
+            check(&state).await;
+        }
+    });
+}
+
+/// Compare current spend with the configured budgets and raise new alerts.
+async fn check(state: &ServerState) {
+    let config = UserConfig::load(&state.paths.config_file()).unwrap_or_default();
+    if config.usage.budgets().is_empty() {
+        return;
+    }
+
+    let statuses = match handlers::usage::budget_statuses(&config.usage, state).await {
+        Ok(statuses) => statuses,
+        Err(e) => {
+            warn!("Failed to compute budget spend: {}", e);
+            state
+                .errors
+                .record("budget", format!("Failed to compute spend: {}", e));
+            return;
+        }
+    };
+
+    let now = Utc::now();
+    let alerts = match AlertLog::update(&state.paths.budget_alerts_file(), |log| {
+        log.record(&statuses, now.date_naive(), now)
+    }) {
+        Ok(alerts) => alerts,
+        Err(e) => {
+            warn!("Failed to save budget alerts: {}", e);
+            return;
+        }
+    };
+
+    if alerts.is_empty() {
+        debug!("No new budget alerts");
+    }
+    for alert in alerts {
+        info!(
+            "{} budget exceeded: ${:.2} of ${:.2}",
+            alert.period, alert.actual, alert.limit
+        );
+        state.broadcast(Event::BudgetExceeded {
+            period: alert.period,
+            limit: alert.limit,
+            actual: alert.actual,
+        });
+    }
+}
+
+/// Take alerts that have not been shown yet, clearing them from disk.
+pub fn take_pending_alerts(paths: &RingletPaths) -> Vec<BudgetAlert> {
+    let path = paths.budget_alerts_file();
+    if AlertLog::load(&path).pending.is_empty() {
+        return Vec::new();
+    }
+
+    match AlertLog::update(&path, |log| std::mem::take(&mut log.pending)) {
+        Ok(pending) => pending,
+        Err(e) => {
+            warn!("Failed to clear budget alerts: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::profile_store::test_paths;
+
+    #[test]
+    fn test_record_alerts_once_per_window() {
+        let mut log = AlertLog::default();
+        let statuses = vec![
+            BudgetStatus::new(BudgetPeriod::Daily, 5.0, 6.0),
+            BudgetStatus::new(BudgetPeriod::Monthly, 50.0, 6.0),
+        ];
+        let day = NaiveDate::from_ymd_opt(2025, 1, 20).unwrap();
+        let now = Utc::now();
+
+        let alerts = log.record(&statuses, day, now);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].period, BudgetPeriod::Daily);
+
+        // Same window: no repeat alert.
+        assert!(log.record(&statuses, day, now).is_empty());
+
+        // Next day is a new daily window.
+        let next_day = day.succ_opt().unwrap();
+        assert_eq!(log.record(&statuses, next_day, now).len(), 1);
+        assert_eq!(log.pending.len(), 2);
+    }
+
+    #[test]
+    fn test_pending_alerts_are_taken_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = test_paths(dir.path());
+        let path = paths.budget_alerts_file();
+        let statuses = vec![BudgetStatus::new(BudgetPeriod::Daily, 5.0, 6.0)];
+        let day = NaiveDate::from_ymd_opt(2025, 1, 20).unwrap();
+
+        let raised = AlertLog::update(&path, |log| log.record(&statuses, day, Utc::now())).unwrap();
+        assert_eq!(raised.len(), 1);
+
+        assert_eq!(take_pending_alerts(&paths).len(), 1);
+        assert!(take_pending_alerts(&paths).is_empty());
+
+        // The window stays marked as notified after the alert is shown.
+        let log = AlertLog::load(&path);
+        assert!(log.notified.contains(&BudgetPeriod::Daily.window_key(day)));
+        assert!(!path.with_extension("json.tmp").exists());
+    }
+}
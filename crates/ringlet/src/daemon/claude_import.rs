@@ -25,10 +25,33 @@ pub struct ClaudeImportResult {
 }
 
 /// Import all available Claude usage data.
-pub fn import_all(claude_dir: &Path) -> Result<ClaudeImportResult> {
+///
+/// `on_progress` is called with `(current, total, message)` before each step:
+/// reading stats-cache.json, then one step per project directory.
+pub fn import_all(
+    claude_dir: &Path,
+    on_progress: impl Fn(u64, Option<u64>, &str),
+) -> Result<ClaudeImportResult> {
     let mut result = ClaudeImportResult::default();
 
+    let projects_dir = claude_dir.join("projects");
+    let project_dirs = if projects_dir.exists() {
+        match project_dirs(&projects_dir) {
+            Ok(dirs) => dirs,
+            Err(e) => {
+                let warning = format!("Failed to import session files: {}", e);
+                warn!("{}", warning);
+                result.warnings.push(warning);
+                Vec::new()
+            }
+        }
+    } else {
+        Vec::new()
+    };
+    let total = project_dirs.len() as u64 + 1;
+
     // Import from stats-cache.json
+    on_progress(0, Some(total), "stats-cache.json");
     let stats_cache = claude_dir.join("stats-cache.json");
     if stats_cache.exists() {
         match import_stats_cache(&stats_cache) {
@@ -49,20 +72,25 @@ pub fn import_all(claude_dir: &Path) -> Result<ClaudeImportResult> {
     }
 
     // Import from session JSONL files
-    let projects_dir = claude_dir.join("projects");
-    if projects_dir.exists() {
-        match import_sessions(&projects_dir) {
-            Ok(count) => {
-                result.sessions_imported = count;
-                info!("Imported {} sessions from JSONL files", count);
-            }
+    for (i, dir) in project_dirs.iter().enumerate() {
+        let name = dir.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        on_progress(i as u64 + 1, Some(total), name);
+        match import_project_sessions(dir) {
+            Ok(count) => result.sessions_imported += count,
             Err(e) => {
-                let warning = format!("Failed to import session files: {}", e);
+                let warning = format!("Failed to import sessions from {}: {}", dir.display(), e);
                 warn!("{}", warning);
                 result.warnings.push(warning);
             }
         }
     }
+    if !project_dirs.is_empty() {
+        info!(
+            "Imported {} sessions from JSONL files",
+            result.sessions_imported
+        );
+    }
+    on_progress(total, Some(total), "Import complete");
 
     Ok(result)
 }
@@ -114,49 +142,52 @@ fn import_stats_cache(path: &Path) -> Result<(TokenUsage, HashMap<String, TokenU
     Ok((total, by_model))
 }
 
-/// Import sessions from JSONL files in projects directory.
+/// List the project directories under Claude's projects directory.
+fn project_dirs(projects_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    for entry in std::fs::read_dir(projects_dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Import sessions from the JSONL files in one project directory.
 ///
 /// Returns the number of sessions imported.
-fn import_sessions(projects_dir: &Path) -> Result<usize> {
+fn import_project_sessions(path: &Path) -> Result<usize> {
     let mut count = 0;
 
-    // Find all session.jsonl files
-    for entry in std::fs::read_dir(projects_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-
-        if !path.is_dir() {
-            continue;
+    // Look for session.jsonl in the project directory
+    let session_file = path.join("session.jsonl");
+    if session_file.exists() {
+        match import_session_file(&session_file) {
+            Ok(session_count) => {
+                count += session_count;
+            }
+            Err(e) => {
+                debug!("Failed to import {}: {}", session_file.display(), e);
+            }
         }
+    }
 
-        // Look for session.jsonl in each project directory
-        let session_file = path.join("session.jsonl");
-        if session_file.exists() {
-            match import_session_file(&session_file) {
+    // Also check for .session.jsonl files (alternate naming)
+    for file in std::fs::read_dir(path)? {
+        let file = file?;
+        let file_path = file.path();
+        if file_path.extension().is_some_and(|ext| ext == "jsonl")
+            && let Some(name) = file_path.file_name().and_then(|n| n.to_str())
+            && name.contains("session")
+        {
+            match import_session_file(&file_path) {
                 Ok(session_count) => {
                     count += session_count;
                 }
                 Err(e) => {
-                    debug!("Failed to import {}: {}", session_file.display(), e);
-                }
-            }
-        }
-
-        // Also check for .session.jsonl files (alternate naming)
-        for file in std::fs::read_dir(&path)? {
-            let file = file?;
-            let file_path = file.path();
-            if file_path.extension().is_some_and(|ext| ext == "jsonl")
-                && let Some(name) = file_path.file_name().and_then(|n| n.to_str())
-                && name.contains("session")
-            {
-                match import_session_file(&file_path) {
-                    Ok(session_count) => {
-                        count += session_count;
-                    }
-                    Err(e) => {
-                        debug!("Failed to import {}: {}", file_path.display(), e);
-                    }
+                    debug!("Failed to import {}: {}", file_path.display(), e);
                 }
             }
         }
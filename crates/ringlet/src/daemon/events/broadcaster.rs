@@ -1,31 +1,116 @@
 //! Event broadcaster using tokio broadcast channels.
 
 use ringlet_core::Event;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
 use tokio::sync::broadcast;
 use tracing::debug;
 
+/// An event tagged with its position in the broadcast order.
+///
+/// Sequence numbers start at 1 and increase by one per broadcast event.
+#[derive(Debug, Clone)]
+pub struct SequencedEvent {
+    pub seq: u64,
+    pub event: Event,
+}
+
+/// What a reconnecting client gets before live events.
+#[derive(Debug)]
+pub enum Replay {
+    /// Every event after the client's `since`, in order.
+    Missed(Vec<SequencedEvent>),
+    /// Events after `since` are no longer buffered, or `since` is ahead of
+    /// the last event because the daemon restarted. The client should reload
+    /// its state and continue from `last_seq`.
+    Resync { last_seq: u64 },
+}
+
+/// Recently broadcast events, kept so reconnecting clients can catch up.
+#[derive(Debug)]
+struct History {
+    /// Sequence number of the last broadcast event (0 before the first).
+    last_seq: u64,
+    events: VecDeque<SequencedEvent>,
+    capacity: usize,
+}
+
 /// Broadcasts events to all subscribed WebSocket clients.
 #[derive(Debug)]
 pub struct EventBroadcaster {
-    sender: broadcast::Sender<Event>,
+    sender: broadcast::Sender<SequencedEvent>,
+    history: Arc<Mutex<History>>,
 }
 
 impl EventBroadcaster {
     /// Create a new event broadcaster with the given channel capacity.
+    ///
+    /// The same number of recent events is kept for replay.
     pub fn new(capacity: usize) -> Self {
         let (sender, _) = broadcast::channel(capacity);
-        Self { sender }
+        let history = History {
+            last_seq: 0,
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+        };
+        Self {
+            sender,
+            history: Arc::new(Mutex::new(history)),
+        }
     }
 
     /// Subscribe to receive events.
-    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+    pub fn subscribe(&self) -> broadcast::Receiver<SequencedEvent> {
         self.sender.subscribe()
     }
 
+    /// Subscribe to receive events, first returning what the client missed
+    /// after sequence number `since`.
+    ///
+    /// Every later event arrives on the receiver, so nothing is missed or
+    /// delivered twice.
+    pub fn subscribe_since(&self, since: u64) -> (Replay, broadcast::Receiver<SequencedEvent>) {
+        let history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        let oldest = history
+            .events
+            .front()
+            .map_or(history.last_seq + 1, |e| e.seq);
+
+        let replay = if since > history.last_seq || since + 1 < oldest {
+            Replay::Resync {
+                last_seq: history.last_seq,
+            }
+        } else {
+            Replay::Missed(
+                history
+                    .events
+                    .iter()
+                    .filter(|e| e.seq > since)
+                    .cloned()
+                    .collect(),
+            )
+        };
+        (replay, self.sender.subscribe())
+    }
+
     /// Broadcast an event to all subscribers.
     /// Returns the number of receivers that received the event.
     pub fn broadcast(&self, event: Event) -> usize {
         debug!("Broadcasting event: {:?}", event);
+
+        // Held while sending so `subscribe_since` sees each event either in
+        // the history or on its new receiver
+        let mut history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        history.last_seq += 1;
+        let event = SequencedEvent {
+            seq: history.last_seq,
+            event,
+        };
+        if history.events.len() == history.capacity {
+            history.events.pop_front();
+        }
+        history.events.push_back(event.clone());
+
         // send() returns error if there are no receivers, which is fine
         self.sender.send(event).unwrap_or(0)
     }
@@ -46,6 +131,72 @@ impl Clone for EventBroadcaster {
     fn clone(&self) -> Self {
         Self {
             sender: self.sender.clone(),
+            history: self.history.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heartbeat(timestamp: i64) -> Event {
+        Event::Heartbeat { timestamp }
+    }
+
+    fn replayed(replay: Replay) -> Vec<u64> {
+        match replay {
+            Replay::Missed(events) => events.iter().map(|e| e.seq).collect(),
+            Replay::Resync { .. } => panic!("expected a replay, got {:?}", replay),
+        }
+    }
+
+    #[test]
+    fn test_subscribe_since_replays_missed_events() {
+        let broadcaster = EventBroadcaster::new(2);
+        for timestamp in 1..=3 {
+            broadcaster.broadcast(heartbeat(timestamp));
         }
+
+        let (replay, mut rx) = broadcaster.subscribe_since(1);
+        assert_eq!(replayed(replay), [2, 3]);
+
+        let (replay, _) = broadcaster.subscribe_since(2);
+        assert_eq!(replayed(replay), [3]);
+
+        let (replay, _) = broadcaster.subscribe_since(3);
+        assert!(replayed(replay).is_empty());
+
+        broadcaster.broadcast(heartbeat(4));
+        assert_eq!(rx.try_recv().unwrap().seq, 4);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_subscribe_since_asks_for_resync_after_a_gap() {
+        let broadcaster = EventBroadcaster::new(2);
+        for timestamp in 1..=3 {
+            broadcaster.broadcast(heartbeat(timestamp));
+        }
+
+        // Event 1 has been evicted from the buffer
+        let (replay, _) = broadcaster.subscribe_since(0);
+        assert!(matches!(replay, Replay::Resync { last_seq: 3 }));
+    }
+
+    #[test]
+    fn test_subscribe_since_asks_for_resync_after_a_restart() {
+        let broadcaster = EventBroadcaster::new(2);
+        broadcaster.broadcast(heartbeat(1));
+
+        // A client that saw seq 40 from the previous daemon
+        let (replay, _) = broadcaster.subscribe_since(40);
+        assert!(matches!(replay, Replay::Resync { last_seq: 1 }));
+
+        let fresh = EventBroadcaster::new(2);
+        let (replay, _) = fresh.subscribe_since(40);
+        assert!(matches!(replay, Replay::Resync { last_seq: 0 }));
+        let (replay, _) = fresh.subscribe_since(0);
+        assert!(replayed(replay).is_empty());
     }
 }
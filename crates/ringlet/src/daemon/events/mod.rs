@@ -2,4 +2,4 @@
 
 mod broadcaster;
 
-pub use broadcaster::EventBroadcaster;
+pub use broadcaster::{EventBroadcaster, Replay, SequencedEvent};
@@ -5,14 +5,24 @@
 //! the final process from a prepared execution context.
 
 use anyhow::{Context, Result, anyhow};
+use chrono::Utc;
+use portable_pty::{CommandBuilder, MasterPty, PtySize, native_pty_system};
 use ringlet_core::rpc::ExecutionContext;
-use ringlet_core::{AgentManifest, Profile, ProviderManifest, RingletPaths};
+use ringlet_core::{
+    AgentManifest, HooksConfig, Profile, ProviderManifest, RingletPaths, UserConfig,
+    expand_launch_wrapper, run_log_path,
+};
 use ringlet_scripting::{
-    AgentContext, PrefsContext, ProfileContext, ProviderContext, ScriptContext, ScriptEngine,
-    ScriptOutput, scripts,
+    AgentContext, FileRoot, MetaContext, PrefsContext, ProfileContext, ProviderContext,
+    ScriptContext, ScriptEngine, ScriptOutput, relative_file_path, scripts,
 };
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
 use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
 use tracing::{debug, info};
 
 use crate::daemon::registry_client::RegistryLock;
@@ -31,6 +41,584 @@ pub struct RunResult {
     pub child: Child,
 }
 
+/// Split a wait status into an exit code and, on Unix, the terminating signal.
+///
+/// The exit code is -1 when the process did not exit normally.
+pub fn exit_details(status: &std::process::ExitStatus) -> (i32, Option<i32>) {
+    #[cfg(unix)]
+    let signal = {
+        use std::os::unix::process::ExitStatusExt;
+        status.signal()
+    };
+    #[cfg(not(unix))]
+    let signal = None;
+
+    (status.code().unwrap_or(-1), signal)
+}
+
+/// Run the profile's `pre_run` command, failing if it does not succeed.
+pub fn run_pre_run_hook(context: &ExecutionContext) -> Result<()> {
+    match context.pre_run.as_deref() {
+        Some(command) => run_hook_command("pre_run", command, context),
+        None => Ok(()),
+    }
+}
+
+/// Run the profile's `post_run` command.
+pub fn run_post_run_hook(context: &ExecutionContext) -> Result<()> {
+    match context.post_run.as_deref() {
+        Some(command) => run_hook_command("post_run", command, context),
+        None => Ok(()),
+    }
+}
+
+/// Run a profile hook through the shell with the agent's environment.
+fn run_hook_command(name: &str, command: &str, context: &ExecutionContext) -> Result<()> {
+    info!("Running {} hook for profile '{}'", name, context.alias);
+
+    #[cfg(unix)]
+    let mut cmd = {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    #[cfg(not(unix))]
+    let mut cmd = {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    };
+
+    let status = cmd
+        .current_dir(&context.working_dir)
+        .envs(&context.env)
+        .status()
+        .with_context(|| format!("Failed to run {} hook", name))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "{} hook `{}` failed with exit code {}",
+            name,
+            command,
+            exit_details(&status).0
+        ))
+    }
+}
+
+/// Agent process spawned by the CLI in its own process group.
+///
+/// Keeping the agent (and anything it spawns) in a separate group lets ringlet
+/// signal the whole tree, so interrupted runs do not leave orphaned processes.
+pub struct AgentProcess {
+    child: AgentChild,
+    pid: u32,
+    /// Pseudo-terminal the agent runs in when ringlet has a terminal or logs
+    /// its output.
+    pty: Option<AgentPty>,
+    #[cfg(unix)]
+    signals: ForwardedSignals,
+}
+
+/// Signals ringlet passes on to the agent while waiting for it.
+///
+/// They are registered before the agent is spawned: a signal arriving before
+/// [`AgentProcess::wait`] is then queued for forwarding instead of killing
+/// ringlet and leaving the agent running.
+#[cfg(unix)]
+struct ForwardedSignals {
+    interrupt: tokio::signal::unix::Signal,
+    terminate: tokio::signal::unix::Signal,
+    hangup: tokio::signal::unix::Signal,
+    resize: tokio::signal::unix::Signal,
+}
+
+#[cfg(unix)]
+impl ForwardedSignals {
+    fn register() -> std::io::Result<Self> {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        Ok(Self {
+            interrupt: signal(SignalKind::interrupt())?,
+            terminate: signal(SignalKind::terminate())?,
+            hangup: signal(SignalKind::hangup())?,
+            resize: signal(SignalKind::window_change())?,
+        })
+    }
+}
+
+enum AgentChild {
+    Direct(Child),
+    Pty(Box<dyn portable_pty::Child + Send + Sync>),
+}
+
+struct AgentPty {
+    master: Box<dyn MasterPty + Send>,
+    /// Signalled once everything the agent wrote has been copied out.
+    drained: mpsc::Receiver<()>,
+    /// Restores ringlet's terminal when dropped.
+    #[cfg(unix)]
+    raw_mode: Option<RawMode>,
+    /// Receives a message for each double Ctrl-C typed at the agent.
+    #[cfg(unix)]
+    double_interrupts: tokio::sync::mpsc::UnboundedReceiver<()>,
+}
+
+/// How long to keep copying output after the agent exits.
+///
+/// A background process started by the agent can hold the terminal open
+/// indefinitely, so ringlet stops waiting for the end of the output.
+const OUTPUT_DRAIN_GRACE: Duration = Duration::from_millis(500);
+
+/// The byte a terminal sends for Ctrl-C.
+const CTRL_C: u8 = 0x03;
+
+/// Two Ctrl-C keypresses at most this far apart count as a double Ctrl-C.
+const DOUBLE_INTERRUPT_WINDOW: Duration = Duration::from_secs(1);
+
+/// How long an agent gets to exit by itself after a double Ctrl-C before it
+/// is killed. Agents that exit on a double Ctrl-C of their own get to do so
+/// cleanly.
+const FORCE_KILL_GRACE: Duration = Duration::from_secs(2);
+
+/// Spots two Ctrl-C keypresses in quick succession in the agent's input.
+#[derive(Debug, Default)]
+struct DoubleInterrupt {
+    last: Option<std::time::Instant>,
+}
+
+impl DoubleInterrupt {
+    /// Look at input read at `now`, returning whether it completes a double Ctrl-C.
+    fn feed(&mut self, input: &[u8], now: std::time::Instant) -> bool {
+        let mut double = false;
+        for _ in input.iter().filter(|&&byte| byte == CTRL_C) {
+            match self.last {
+                Some(last) if now.duration_since(last) <= DOUBLE_INTERRUPT_WINDOW => {
+                    double = true;
+                    self.last = None;
+                }
+                _ => self.last = Some(now),
+            }
+        }
+        double
+    }
+}
+
+impl AgentProcess {
+    /// Spawn `cmd` as the leader of a new process group.
+    ///
+    /// When ringlet is the terminal's foreground job, the agent runs on a
+    /// pseudo-terminal of its own fed from ringlet's terminal (see
+    /// [`spawn_logged`](Self::spawn_logged)). Ringlet then stays the
+    /// foreground job and sees the keys typed at the agent, so a double Ctrl-C
+    /// can force-kill it.
+    pub fn spawn(cmd: &mut Command) -> std::io::Result<Self> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+
+            if owns_terminal() {
+                return Self::spawn_on_terminal(cmd, None);
+            }
+
+            let signals = ForwardedSignals::register()?;
+            // SAFETY: only async-signal-safe libc calls run between fork and exec.
+            unsafe {
+                cmd.pre_exec(|| {
+                    if libc::setpgid(0, 0) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+
+            let child = cmd.spawn()?;
+            Ok(Self {
+                pid: child.id(),
+                child: AgentChild::Direct(child),
+                pty: None,
+                signals,
+            })
+        }
+        #[cfg(not(unix))]
+        {
+            let child = cmd.spawn()?;
+            Ok(Self {
+                pid: child.id(),
+                child: AgentChild::Direct(child),
+                pty: None,
+            })
+        }
+    }
+
+    /// Spawn `cmd` on a pseudo-terminal, copying everything it prints to `log`
+    /// while still passing it through to ringlet's stdout.
+    ///
+    /// The agent keeps a TTY, so interactive agents behave as they do without
+    /// logging. Its stdout and stderr both go to the terminal and the log holds
+    /// the raw terminal output. The stdio settings of `cmd` are ignored.
+    pub fn spawn_logged(cmd: &Command, log: File) -> std::io::Result<Self> {
+        Self::spawn_on_terminal(cmd, Some(log))
+    }
+
+    /// Spawn `cmd` on a pseudo-terminal fed from ringlet's stdin, putting
+    /// ringlet's terminal in raw mode while the agent runs.
+    ///
+    /// A background job leaves stdin alone, as reading it would stop ringlet.
+    fn spawn_on_terminal(cmd: &Command, log: Option<File>) -> std::io::Result<Self> {
+        #[cfg(unix)]
+        {
+            let on_terminal = unsafe { libc::isatty(libc::STDIN_FILENO) } == 1;
+            let input: Option<Box<dyn Read + Send>> = if on_terminal && !owns_terminal() {
+                None
+            } else {
+                Some(Box::new(std::io::stdin()))
+            };
+            let mut agent = Self::spawn_pty(cmd, log, input)?;
+            if on_terminal
+                && owns_terminal()
+                && let Some(pty) = &mut agent.pty
+            {
+                pty.raw_mode = RawMode::enable();
+            }
+            Ok(agent)
+        }
+        #[cfg(not(unix))]
+        Self::spawn_pty(cmd, log, Some(Box::new(std::io::stdin())))
+    }
+
+    /// Spawn `cmd` on a pseudo-terminal, writing `input` to it and copying its
+    /// output to ringlet's stdout and `log`.
+    fn spawn_pty(
+        cmd: &Command,
+        log: Option<File>,
+        input: Option<Box<dyn Read + Send>>,
+    ) -> std::io::Result<Self> {
+        #[cfg(unix)]
+        let signals = ForwardedSignals::register()?;
+        let pair = native_pty_system()
+            .openpty(terminal_size())
+            .map_err(std::io::Error::other)?;
+
+        let mut builder = CommandBuilder::new(cmd.get_program());
+        builder.args(cmd.get_args());
+        for (key, value) in cmd.get_envs() {
+            match value {
+                Some(value) => builder.env(key, value),
+                None => builder.env_remove(key),
+            }
+        }
+        // Without a directory the agent would start in $HOME
+        match cmd.get_current_dir() {
+            Some(dir) => builder.cwd(dir),
+            None => builder.cwd(std::env::current_dir()?),
+        }
+
+        // The agent becomes the leader of a new session on the terminal
+        let child = pair
+            .slave
+            .spawn_command(builder)
+            .map_err(std::io::Error::other)?;
+        drop(pair.slave);
+        let pid = child.process_id().unwrap_or_default();
+
+        let reader = pair
+            .master
+            .try_clone_reader()
+            .map_err(std::io::Error::other)?;
+        let writer = pair.master.take_writer().map_err(std::io::Error::other)?;
+        #[cfg(unix)]
+        let (interrupts, double_interrupts) = tokio::sync::mpsc::unbounded_channel();
+        if let Some(input) = input {
+            #[cfg(unix)]
+            forward_input(input, writer, interrupts);
+            #[cfg(not(unix))]
+            std::thread::spawn(move || {
+                let (mut input, mut writer) = (input, writer);
+                let _ = std::io::copy(&mut input, &mut writer);
+            });
+        }
+
+        let (done, drained) = mpsc::channel();
+        std::thread::spawn(move || {
+            tee(reader, std::io::stdout(), log);
+            let _ = done.send(());
+        });
+
+        Ok(Self {
+            child: AgentChild::Pty(child),
+            pid,
+            pty: Some(AgentPty {
+                master: pair.master,
+                drained,
+                #[cfg(unix)]
+                raw_mode: None,
+                #[cfg(unix)]
+                double_interrupts,
+            }),
+            #[cfg(unix)]
+            signals,
+        })
+    }
+
+    /// Wait for the agent to exit, forwarding termination signals to it.
+    ///
+    /// SIGINT, SIGTERM and SIGHUP received by ringlet are sent to the agent's
+    /// process group; a second signal force-kills the group with SIGKILL. A
+    /// double Ctrl-C typed at the agent force-kills it too, if it hasn't exited
+    /// by itself [`FORCE_KILL_GRACE`] later.
+    pub async fn wait(self) -> Result<std::process::ExitStatus> {
+        let Self {
+            child,
+            pid,
+            mut pty,
+            #[cfg(unix)]
+            mut signals,
+        } = self;
+        #[cfg(unix)]
+        let raw_mode = pty.as_mut().and_then(|pty| pty.raw_mode.take());
+        #[cfg(unix)]
+        let (master, drained, mut double_interrupts) = match pty {
+            Some(pty) => (
+                Some(pty.master),
+                Some(pty.drained),
+                Some(pty.double_interrupts),
+            ),
+            None => (None, None, None),
+        };
+        #[cfg(not(unix))]
+        let (master, drained) = pty.map(|pty| (pty.master, pty.drained)).unzip();
+        let wait_all = move || {
+            let status = child.wait();
+            if let Some(drained) = drained {
+                let _ = drained.recv_timeout(OUTPUT_DRAIN_GRACE);
+            }
+            status
+        };
+
+        #[cfg(unix)]
+        let status = {
+            let pgid = pid as libc::pid_t;
+            let mut wait = tokio::task::spawn_blocking(wait_all);
+            let mut forwarded = false;
+            let mut force_kill: Option<std::pin::Pin<Box<tokio::time::Sleep>>> = None;
+
+            let result = loop {
+                let received = tokio::select! {
+                    result = &mut wait => break result,
+                    Some(()) = async {
+                        match &mut double_interrupts {
+                            Some(interrupts) => interrupts.recv().await,
+                            None => std::future::pending().await,
+                        }
+                    } => {
+                        if force_kill.is_none() {
+                            debug!("Double Ctrl-C; killing agent process group {} unless it exits", pgid);
+                            force_kill = Some(Box::pin(tokio::time::sleep(FORCE_KILL_GRACE)));
+                        }
+                        continue;
+                    }
+                    _ = async { force_kill.as_mut().expect("checked").await }, if force_kill.is_some() => {
+                        force_kill = None;
+                        libc::SIGKILL
+                    }
+                    _ = signals.interrupt.recv() => libc::SIGINT,
+                    _ = signals.terminate.recv() => libc::SIGTERM,
+                    _ = signals.hangup.recv() => libc::SIGHUP,
+                    _ = signals.resize.recv() => {
+                        if let Some(master) = &master {
+                            let _ = master.resize(terminal_size());
+                        }
+                        continue;
+                    }
+                };
+                let signal = if forwarded { libc::SIGKILL } else { received };
+                debug!(
+                    "Forwarding signal {} to agent process group {}",
+                    signal, pgid
+                );
+                unsafe {
+                    libc::kill(-pgid, signal);
+                }
+                forwarded = true;
+            };
+
+            drop(raw_mode);
+            result
+        };
+        #[cfg(not(unix))]
+        let status = {
+            let _ = (pid, master);
+            tokio::task::spawn_blocking(wait_all).await
+        };
+
+        status
+            .context("Agent wait task failed")?
+            .context("Failed to wait for process")
+    }
+}
+
+impl AgentChild {
+    fn wait(self) -> std::io::Result<std::process::ExitStatus> {
+        match self {
+            Self::Direct(mut child) => child.wait(),
+            // portable-pty only reports signals by name, so reap the agent
+            // directly to keep the signal number
+            #[cfg(unix)]
+            Self::Pty(child) => {
+                use std::os::unix::process::ExitStatusExt;
+
+                let pid = child
+                    .process_id()
+                    .ok_or_else(|| std::io::Error::other("agent has no process id"))?;
+                let mut status = 0;
+                loop {
+                    if unsafe { libc::waitpid(pid as libc::pid_t, &mut status, 0) } != -1 {
+                        return Ok(std::process::ExitStatus::from_raw(status));
+                    }
+                    let err = std::io::Error::last_os_error();
+                    if err.kind() != std::io::ErrorKind::Interrupted {
+                        return Err(err);
+                    }
+                }
+            }
+            #[cfg(windows)]
+            Self::Pty(mut child) => {
+                use std::os::windows::process::ExitStatusExt;
+
+                let status = child.wait()?;
+                Ok(std::process::ExitStatus::from_raw(status.exit_code()))
+            }
+        }
+    }
+}
+
+/// Copy everything read from `source` to `sink`, and to `log` when given.
+fn tee(mut source: impl Read, mut sink: impl Write, mut log: Option<File>) {
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = match source.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            // Reading a terminal fails with EIO once every writer has closed it
+            Err(_) => break,
+        };
+        let _ = sink.write_all(&buf[..n]).and_then(|()| sink.flush());
+        if let Some(log) = &mut log {
+            let _ = log.write_all(&buf[..n]);
+        }
+    }
+}
+
+/// Create a new log file for a run's output, along with its directory.
+///
+/// Fails rather than overwriting when the file already exists.
+pub fn create_run_log(path: &Path) -> Result<File> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .with_context(|| format!("Failed to create log file {}", path.display()))
+}
+
+/// Size of ringlet's terminal, or 80x24 when stdout is not one.
+fn terminal_size() -> PtySize {
+    #[cfg(unix)]
+    unsafe {
+        let mut size: libc::winsize = std::mem::zeroed();
+        if libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) == 0 && size.ws_row > 0 {
+            return PtySize {
+                rows: size.ws_row,
+                cols: size.ws_col,
+                pixel_width: size.ws_xpixel,
+                pixel_height: size.ws_ypixel,
+            };
+        }
+    }
+    PtySize::default()
+}
+
+/// Send ringlet's stdin to the agent's terminal.
+///
+/// When stdin is ringlet's own terminal it is switched to raw mode, so keys
+/// such as Ctrl-C reach the agent's terminal, which turns them into signals
+/// itself. A background ringlet leaves the terminal alone.
+#[cfg(unix)]
+fn forward_input(
+    mut input: Box<dyn Read + Send>,
+    mut writer: Box<dyn Write + Send>,
+    double_interrupts: tokio::sync::mpsc::UnboundedSender<()>,
+) {
+    std::thread::spawn(move || {
+        let mut interrupts = DoubleInterrupt::default();
+        let mut buf = [0u8; 1024];
+        loop {
+            let n = match input.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            };
+            if interrupts.feed(&buf[..n], std::time::Instant::now()) {
+                let _ = double_interrupts.send(());
+            }
+            if writer
+                .write_all(&buf[..n])
+                .and_then(|()| writer.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+}
+
+/// Terminal settings to restore once the agent exits.
+#[cfg(unix)]
+struct RawMode(libc::termios);
+
+#[cfg(unix)]
+impl RawMode {
+    fn enable() -> Option<Self> {
+        unsafe {
+            let mut saved: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut saved) != 0 {
+                return None;
+            }
+            let mut raw = saved;
+            libc::cfmakeraw(&mut raw);
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) != 0 {
+                return None;
+            }
+            Some(Self(saved))
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.0);
+        }
+    }
+}
+
+/// Whether ringlet is the foreground job of its controlling terminal.
+#[cfg(unix)]
+fn owns_terminal() -> bool {
+    unsafe {
+        libc::isatty(libc::STDIN_FILENO) == 1
+            && libc::tcgetpgrp(libc::STDIN_FILENO) == libc::getpgrp()
+    }
+}
+
 /// Builds an execution context from profile, agent, and provider inputs.
 struct ExecutionPlanner {
     renderer: ConfigRenderer,
@@ -47,6 +635,14 @@ struct ProcessLauncher;
 struct RenderedExecution {
     env: HashMap<String, String>,
     script_output: ScriptOutput,
+    writes: WriteSummary,
+}
+
+/// Counts of generated config files written vs. skipped as unchanged.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct WriteSummary {
+    changed: usize,
+    unchanged: usize,
 }
 
 impl ExecutionAdapter {
@@ -73,10 +669,29 @@ impl ExecutionAdapter {
             .prepare(profile, agent, provider, api_key, args, proxy_url)
     }
 
+    /// Render the agent's config files without writing them.
+    ///
+    /// `${API_KEY}` placeholders are left unresolved, so the output is safe to show.
+    pub fn render_files(
+        &self,
+        profile: &Profile,
+        agent: &AgentManifest,
+        provider: &ProviderManifest,
+    ) -> Result<HashMap<String, String>> {
+        self.planner.renderer.render_files(profile, agent, provider)
+    }
+
     /// Spawn a process from a prepared execution context.
     pub fn spawn_prepared(&self, context: &ExecutionContext) -> Result<RunResult> {
         self.launcher.spawn_prepared(context)
     }
+
+    /// Spawn a process from a prepared execution context with its output piped.
+    ///
+    /// Stdin is closed; the child is killed if its handle is dropped.
+    pub fn spawn_piped(&self, context: &ExecutionContext) -> Result<tokio::process::Child> {
+        self.launcher.spawn_piped(context)
+    }
 }
 
 impl ExecutionPlanner {
@@ -95,9 +710,18 @@ impl ExecutionPlanner {
         args: &[String],
         proxy_url: Option<&str>,
     ) -> Result<ExecutionContext> {
-        let rendered = self
-            .renderer
-            .render(profile, agent, provider, api_key, proxy_url)?;
+        let rendered = self.renderer.render(
+            profile,
+            agent,
+            provider,
+            api_key,
+            proxy_url,
+            profile.working_dir.as_deref(),
+        )?;
+        let working_dir = profile
+            .working_dir
+            .clone()
+            .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
         let mut env = rendered.env;
         for key in &["PATH", "TERM", "LANG", "LC_ALL", "USER", "SHELL"] {
@@ -111,18 +735,26 @@ impl ExecutionPlanner {
         combined_args.extend(rendered.script_output.args);
         combined_args.extend(args.to_vec());
 
-        let working_dir = profile
-            .working_dir
-            .clone()
-            .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+        let (binary, combined_args) = match &profile.metadata.launch_wrapper {
+            Some(template) => expand_launch_wrapper(template, &agent.binary, &combined_args)?,
+            None => (agent.binary.clone(), combined_args),
+        };
 
         Ok(ExecutionContext {
-            binary: agent.binary.clone(),
+            binary,
             working_dir,
             env,
             args: combined_args,
             alias: profile.alias.clone(),
             run_id: None,
+            files_changed: rendered.writes.changed,
+            files_unchanged: rendered.writes.unchanged,
+            pre_run: profile.metadata.pre_run.clone(),
+            post_run: profile.metadata.post_run.clone(),
+            log_path: profile
+                .metadata
+                .capture_logs
+                .then(|| run_log_path(&profile.metadata.home, Utc::now())),
         })
     }
 }
@@ -139,13 +771,65 @@ impl ConfigRenderer {
         provider: &ProviderManifest,
         api_key: &str,
         proxy_url: Option<&str>,
+        working_dir: Option<&Path>,
     ) -> Result<RenderedExecution> {
-        let context = build_script_context(profile, agent, provider, proxy_url)?;
+        let hooks = self.effective_hooks(profile, agent);
+        let context = build_script_context(
+            profile,
+            agent,
+            provider,
+            hooks.as_ref(),
+            proxy_url,
+            self.script_meta(),
+        )?;
         let script_output = self.run_script(&agent.profile.script, &context)?;
-        self.write_config_files(profile, &script_output, api_key)?;
+        let writes = self.write_config_files(profile, &script_output, api_key, working_dir)?;
         let env = self.build_environment(profile, api_key, &script_output);
 
-        Ok(RenderedExecution { env, script_output })
+        Ok(RenderedExecution {
+            env,
+            script_output,
+            writes,
+        })
+    }
+
+    fn render_files(
+        &self,
+        profile: &Profile,
+        agent: &AgentManifest,
+        provider: &ProviderManifest,
+    ) -> Result<HashMap<String, String>> {
+        let hooks = self.effective_hooks(profile, agent);
+        let context = build_script_context(
+            profile,
+            agent,
+            provider,
+            hooks.as_ref(),
+            None,
+            self.script_meta(),
+        )?;
+        Ok(self.run_script(&agent.profile.script, &context)?.files)
+    }
+
+    /// The user's default hooks for the agent followed by the profile's own.
+    fn effective_hooks(&self, profile: &Profile, agent: &AgentManifest) -> Option<HooksConfig> {
+        let mut hooks = HooksConfig::default();
+        if agent.supports_hooks && !profile.metadata.skip_default_hooks {
+            let config = UserConfig::load(&self.paths.config_file()).unwrap_or_default();
+            hooks = config.hooks.defaults_for(&agent.id);
+        }
+        if let Some(profile_hooks) = &profile.metadata.hooks_config {
+            hooks.merge(profile_hooks);
+        }
+        (!hooks.is_empty()).then_some(hooks)
+    }
+
+    /// Version information passed to scripts as `ctx.meta`.
+    fn script_meta(&self) -> MetaContext {
+        MetaContext {
+            registry_commit: self.load_registry_lock().ok().and_then(|lock| lock.commit),
+            ..MetaContext::default()
+        }
     }
 
     /// Run the configuration script.
@@ -195,27 +879,57 @@ impl ConfigRenderer {
         }
     }
 
+    /// Write the script's files under the profile home, or under the working
+    /// directory for files the script roots at `cwd`.
     fn write_config_files(
         &self,
         profile: &Profile,
         output: &ScriptOutput,
         api_key: &str,
-    ) -> Result<()> {
+        working_dir: Option<&Path>,
+    ) -> Result<WriteSummary> {
         let home = &profile.metadata.home;
+        let mut summary = WriteSummary::default();
 
         for (relative_path, content) in &output.files {
-            let full_path = home.join(relative_path);
-
-            if let Some(parent) = full_path.parent() {
-                std::fs::create_dir_all(parent)
-                    .context(format!("Failed to create directory: {:?}", parent))?;
-            }
+            let base = match output.file_root(relative_path) {
+                FileRoot::Home => home.as_path(),
+                FileRoot::Cwd => {
+                    // Project directories get committed and shared, so keep keys out
+                    if content.contains("${API_KEY}") {
+                        return Err(anyhow!(
+                            "Refusing to write the API key into {:?} in the working directory",
+                            relative_path
+                        ));
+                    }
+                    working_dir.ok_or_else(|| {
+                        anyhow!(
+                            "{:?} belongs in the working directory, but this run has none; set the profile's working_dir",
+                            relative_path
+                        )
+                    })?
+                }
+            };
+            let full_path = resolve_output_path(base, relative_path)?;
 
             let resolved_content = content.replace("${API_KEY}", api_key);
             let contains_sensitive_data = content.contains("${API_KEY}") && !api_key.is_empty();
 
-            std::fs::write(&full_path, &resolved_content)
-                .context(format!("Failed to write file: {:?}", full_path))?;
+            // Skip identical files so repeated runs don't touch mtimes and
+            // wake up editors or file watchers.
+            let unchanged = std::fs::read(&full_path)
+                .map(|existing| existing == resolved_content.as_bytes())
+                .unwrap_or(false);
+
+            if unchanged {
+                summary.unchanged += 1;
+                debug!("Config file unchanged: {:?}", full_path);
+            } else {
+                std::fs::write(&full_path, &resolved_content)
+                    .context(format!("Failed to write file: {:?}", full_path))?;
+                summary.changed += 1;
+                debug!("Wrote config file: {:?}", full_path);
+            }
 
             #[cfg(unix)]
             if contains_sensitive_data {
@@ -224,11 +938,9 @@ impl ConfigRenderer {
                     .context(format!("Failed to set permissions on: {:?}", full_path))?;
                 debug!("Set 0o600 permissions on sensitive file: {:?}", full_path);
             }
-
-            debug!("Wrote config file: {:?}", full_path);
         }
 
-        Ok(())
+        Ok(summary)
     }
 
     fn build_environment(
@@ -266,11 +978,22 @@ impl ProcessLauncher {
             context.binary, context.alias, context.working_dir
         );
 
+        run_pre_run_hook(context)?;
+
         let mut cmd = Command::new(&context.binary);
         cmd.current_dir(&context.working_dir);
         cmd.stdin(Stdio::inherit());
-        cmd.stdout(Stdio::inherit());
-        cmd.stderr(Stdio::inherit());
+        match &context.log_path {
+            Some(path) => {
+                let log = create_run_log(path)?;
+                cmd.stderr(log.try_clone()?);
+                cmd.stdout(log);
+            }
+            None => {
+                cmd.stdout(Stdio::inherit());
+                cmd.stderr(Stdio::inherit());
+            }
+        }
         cmd.env_clear();
         cmd.envs(&context.env);
         cmd.args(&context.args);
@@ -286,6 +1009,30 @@ impl ProcessLauncher {
 
         Ok(RunResult { pid, child })
     }
+
+    fn spawn_piped(&self, context: &ExecutionContext) -> Result<tokio::process::Child> {
+        info!(
+            "Spawning piped command '{}' for profile '{}' in {:?}",
+            context.binary, context.alias, context.working_dir
+        );
+
+        run_pre_run_hook(context)?;
+
+        let mut cmd = tokio::process::Command::new(&context.binary);
+        cmd.current_dir(&context.working_dir);
+        cmd.stdin(Stdio::null());
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+        cmd.env_clear();
+        cmd.envs(&context.env);
+        cmd.args(&context.args);
+        cmd.kill_on_drop(true);
+
+        debug!("Command: {:?}", cmd);
+
+        cmd.spawn()
+            .context(format!("Failed to spawn: {}", context.binary))
+    }
 }
 
 /// Build script context from profile, agent, and provider.
@@ -293,7 +1040,9 @@ fn build_script_context(
     profile: &Profile,
     agent: &AgentManifest,
     provider: &ProviderManifest,
+    hooks_config: Option<&HooksConfig>,
     proxy_url: Option<&str>,
+    meta: MetaContext,
 ) -> Result<ScriptContext> {
     // Resolve endpoint URL - handle indirection (e.g., "default" -> "international" -> URL)
     let endpoint_id = &profile.endpoint_id;
@@ -314,11 +1063,7 @@ fn build_script_context(
     }
 
     // Convert hooks_config to JSON value for script context
-    let hooks_config = profile
-        .metadata
-        .hooks_config
-        .as_ref()
-        .and_then(|h| serde_json::to_value(h).ok());
+    let hooks_config = hooks_config.and_then(|h| serde_json::to_value(h).ok());
 
     Ok(ScriptContext {
         profile: ProfileContext {
@@ -336,6 +1081,8 @@ fn build_script_context(
             name: provider.name.clone(),
             provider_type: provider.provider_type.to_string(),
             auth_env_key: provider.auth.env_key.clone(),
+            auth_header: provider.auth.header.clone(),
+            auth_prefix: provider.auth.prefix.clone(),
         },
         agent: AgentContext {
             id: agent.id.clone(),
@@ -343,5 +1090,383 @@ fn build_script_context(
             binary: agent.binary.clone(),
         },
         prefs: PrefsContext::default(),
+        meta,
     })
 }
+
+/// Join a script-generated path onto `base`, creating its parent directories.
+///
+/// The path must be relative without `..`, and neither it nor its parent may
+/// resolve outside `base` through a symlink.
+fn resolve_output_path(base: &Path, relative_path: &str) -> Result<std::path::PathBuf> {
+    let full_path = base.join(relative_file_path(relative_path)?);
+    let parent = full_path
+        .parent()
+        .ok_or_else(|| anyhow!("Invalid file path: {:?}", full_path))?;
+    std::fs::create_dir_all(parent).context(format!("Failed to create directory: {:?}", parent))?;
+
+    let base = base
+        .canonicalize()
+        .context(format!("Failed to resolve directory: {:?}", base))?;
+    let parent = parent
+        .canonicalize()
+        .context(format!("Failed to resolve directory: {:?}", parent))?;
+    let target = full_path.canonicalize().unwrap_or_else(|_| parent.clone());
+    if !parent.starts_with(&base) || !target.starts_with(&base) {
+        return Err(anyhow!(
+            "Refusing to write {:?}: it resolves outside {:?}",
+            relative_path,
+            base
+        ));
+    }
+    Ok(full_path)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::daemon::profile_store::{test_paths, test_profile};
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    #[test]
+    fn test_exit_details_distinguishes_signals() {
+        // Raw wait statuses: exit code in the high byte, signal in the low bits.
+        assert_eq!(exit_details(&ExitStatus::from_raw(1 << 8)), (1, None));
+        assert_eq!(exit_details(&ExitStatus::from_raw(9)), (-1, Some(9)));
+    }
+
+    #[test]
+    fn test_pre_run_hook_failure_aborts() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut context = ExecutionContext {
+            binary: "true".to_string(),
+            working_dir: dir.path().to_path_buf(),
+            env: HashMap::from([("RINGLET_TEST_VALUE".to_string(), "ok".to_string())]),
+            args: Vec::new(),
+            alias: "test".to_string(),
+            run_id: None,
+            files_changed: 0,
+            files_unchanged: 0,
+            pre_run: Some("test \"$RINGLET_TEST_VALUE\" = ok".to_string()),
+            post_run: Some("exit 4".to_string()),
+            log_path: None,
+        };
+
+        assert!(run_pre_run_hook(&context).is_ok());
+        assert!(run_post_run_hook(&context).is_err());
+
+        context.pre_run = Some("exit 1".to_string());
+        assert!(run_pre_run_hook(&context).is_err());
+        assert!(ProcessLauncher.spawn_prepared(&context).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_agent_process_runs_in_own_group() {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "exit 3"]);
+        let agent = AgentProcess::spawn(&mut cmd).unwrap();
+
+        let pid = agent.pid as libc::pid_t;
+        assert_eq!(unsafe { libc::getpgid(pid) }, pid);
+
+        let status = agent.wait().await.unwrap();
+        assert_eq!(exit_details(&status), (3, None));
+    }
+
+    /// Set in the environment of a test binary re-run to wait on an agent, so
+    /// it can be signalled without signalling the tests.
+    const SIGNAL_HELPER_ENV: &str = "RINGLET_TEST_SIGNAL_HELPER";
+
+    /// Spawns an agent, waits on it and prints how it ended; only runs when
+    /// started by `test_signal_before_wait_is_forwarded`.
+    #[tokio::test]
+    async fn signal_helper() {
+        if std::env::var_os(SIGNAL_HELPER_ENV).is_none() {
+            return;
+        }
+        let mut cmd = Command::new("sleep");
+        cmd.arg("5");
+        let agent = AgentProcess::spawn(&mut cmd).unwrap();
+        println!("spawned");
+        std::io::stdout().flush().unwrap();
+
+        // Leave time for the signal to arrive before waiting starts
+        std::thread::sleep(Duration::from_millis(300));
+        let status = agent.wait().await.unwrap();
+        println!("exit {:?}", exit_details(&status));
+    }
+
+    #[test]
+    fn test_signal_before_wait_is_forwarded() {
+        use std::io::BufRead;
+
+        let mut helper = std::process::Command::new(std::env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "daemon::execution::tests::signal_helper",
+                "--nocapture",
+            ])
+            .env(SIGNAL_HELPER_ENV, "1")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut lines = std::io::BufReader::new(helper.stdout.take().unwrap()).lines();
+        assert!(
+            lines
+                .by_ref()
+                .map_while(Result::ok)
+                .any(|line| line.ends_with("spawned"))
+        );
+
+        unsafe {
+            libc::kill(helper.id() as libc::pid_t, libc::SIGTERM);
+        }
+        let ended: Vec<_> = lines
+            .map_while(Result::ok)
+            .filter(|line| line.starts_with("exit "))
+            .collect();
+        assert!(helper.wait().unwrap().success());
+        assert_eq!(ended, [format!("exit (-1, Some({}))", libc::SIGTERM)]);
+    }
+
+    #[test]
+    fn test_double_interrupt_needs_two_quick_presses() {
+        let start = std::time::Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        let mut interrupts = DoubleInterrupt::default();
+
+        assert!(!interrupts.feed(b"abc", at(0)));
+        assert!(!interrupts.feed(b"\x03", at(0)));
+        assert!(interrupts.feed(b"\x03", at(500)));
+        // A third press starts over
+        assert!(!interrupts.feed(b"\x03", at(600)));
+        assert!(!interrupts.feed(b"\x03", at(2000)));
+        assert!(interrupts.feed(b"x\x03", at(2100)));
+        assert!(interrupts.feed(b"\x03\x03", at(5000)));
+    }
+
+    #[tokio::test]
+    async fn test_double_ctrl_c_typed_at_agent_kills_it() {
+        let (input, mut keyboard) = std::io::pipe().unwrap();
+
+        // The agent ignores the SIGINT its terminal sends for each Ctrl-C
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "trap '' INT; sleep 30"]);
+        let agent = AgentProcess::spawn_pty(&cmd, None, Some(Box::new(input))).unwrap();
+
+        let typing = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(500));
+            keyboard.write_all(b"\x03\x03").unwrap();
+            keyboard
+        });
+        let started = std::time::Instant::now();
+        let status = agent.wait().await.unwrap();
+
+        assert_eq!(exit_details(&status), (-1, Some(libc::SIGKILL)));
+        assert!(started.elapsed() < Duration::from_secs(10));
+        drop(typing.join().unwrap());
+    }
+
+    #[test]
+    fn test_output_paths_stay_inside_base() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("home");
+        std::fs::create_dir(&base).unwrap();
+
+        assert_eq!(
+            resolve_output_path(&base, "./config/settings.json").unwrap(),
+            base.join("config/settings.json")
+        );
+        assert!(base.join("config").is_dir());
+
+        assert!(resolve_output_path(&base, "../../x").is_err());
+        assert!(resolve_output_path(&base, "/tmp/x").is_err());
+        assert!(!dir.path().join("x").exists());
+
+        std::os::unix::fs::symlink(dir.path(), base.join("link")).unwrap();
+        assert!(resolve_output_path(&base, "link/x").is_err());
+        std::os::unix::fs::symlink(dir.path().join("x"), base.join("file")).unwrap();
+        std::fs::write(dir.path().join("x"), "").unwrap();
+        assert!(resolve_output_path(&base, "file").is_err());
+    }
+
+    #[test]
+    fn test_cwd_files_need_a_directory_and_no_api_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("home");
+        let project = dir.path().join("project");
+        std::fs::create_dir_all(&project).unwrap();
+        let renderer = ConfigRenderer::new(test_paths(dir.path()));
+        let profile = test_profile("test", &home);
+
+        let mut output = ScriptOutput::default();
+        output
+            .files
+            .insert(".mcp.json".to_string(), "{}".to_string());
+        output
+            .file_roots
+            .insert(".mcp.json".to_string(), FileRoot::Cwd);
+
+        assert!(
+            renderer
+                .write_config_files(&profile, &output, "sk-test", None)
+                .is_err()
+        );
+        renderer
+            .write_config_files(&profile, &output, "sk-test", Some(&project))
+            .unwrap();
+        assert!(project.join(".mcp.json").exists());
+
+        output
+            .files
+            .insert(".mcp.json".to_string(), "${API_KEY}".to_string());
+        assert!(
+            renderer
+                .write_config_files(&profile, &output, "sk-test", Some(&project))
+                .is_err()
+        );
+        assert_eq!(
+            std::fs::read_to_string(project.join(".mcp.json")).unwrap(),
+            "{}"
+        );
+    }
+
+    #[test]
+    fn test_unchanged_config_files_are_not_rewritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("home");
+        std::fs::create_dir_all(&home).unwrap();
+        let renderer = ConfigRenderer::new(test_paths(dir.path()));
+        let profile = test_profile("test", &home);
+
+        let mut output = ScriptOutput::default();
+        output
+            .files
+            .insert("settings.json".to_string(), "{}".to_string());
+        output
+            .files
+            .insert("auth.json".to_string(), "${API_KEY}".to_string());
+
+        let first = renderer
+            .write_config_files(&profile, &output, "sk-one", None)
+            .unwrap();
+        assert_eq!(
+            first,
+            WriteSummary {
+                changed: 2,
+                unchanged: 0
+            }
+        );
+
+        // Backdate the files so a rewrite would show up in their mtime
+        let old = std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        for name in ["settings.json", "auth.json"] {
+            std::fs::File::options()
+                .write(true)
+                .open(home.join(name))
+                .unwrap()
+                .set_modified(old)
+                .unwrap();
+        }
+
+        let second = renderer
+            .write_config_files(&profile, &output, "sk-one", None)
+            .unwrap();
+        assert_eq!(
+            second,
+            WriteSummary {
+                changed: 0,
+                unchanged: 2
+            }
+        );
+        let mtime = |name: &str| {
+            std::fs::metadata(home.join(name))
+                .unwrap()
+                .modified()
+                .unwrap()
+        };
+        assert_eq!(mtime("settings.json"), old);
+        assert_eq!(mtime("auth.json"), old);
+
+        // A new API key changes the resolved content
+        let third = renderer
+            .write_config_files(&profile, &output, "sk-two", None)
+            .unwrap();
+        assert_eq!(
+            third,
+            WriteSummary {
+                changed: 1,
+                unchanged: 1
+            }
+        );
+        assert_eq!(
+            std::fs::read_to_string(home.join("auth.json")).unwrap(),
+            "sk-two"
+        );
+        assert_eq!(mtime("settings.json"), old);
+    }
+
+    #[tokio::test]
+    async fn test_agent_output_is_copied_to_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = run_log_path(dir.path(), Utc::now());
+
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "echo out; echo err >&2"]);
+        let agent = AgentProcess::spawn_logged(&cmd, create_run_log(&path).unwrap()).unwrap();
+        agent.wait().await.unwrap();
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        let mut lines: Vec<_> = log
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .collect();
+        lines.sort();
+        assert_eq!(lines, ["err", "out"]);
+    }
+
+    #[tokio::test]
+    async fn test_logged_agent_has_a_terminal_and_keeps_its_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.log");
+
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "test -t 0 && test -t 1 && echo tty; exit 3"]);
+        let agent = AgentProcess::spawn_logged(&cmd, create_run_log(&path).unwrap()).unwrap();
+        let status = agent.wait().await.unwrap();
+
+        assert_eq!(status.code(), Some(3));
+        assert!(std::fs::read_to_string(&path).unwrap().contains("tty"));
+    }
+
+    #[tokio::test]
+    async fn test_logged_wait_ignores_background_children() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.log");
+
+        // The background sleep survives the hangup and keeps the terminal open
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "trap '' HUP; sleep 5 & echo out"]);
+        let started = std::time::Instant::now();
+        let agent = AgentProcess::spawn_logged(&cmd, create_run_log(&path).unwrap()).unwrap();
+        agent.wait().await.unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(4));
+        assert!(std::fs::read_to_string(&path).unwrap().contains("out"));
+    }
+
+    #[test]
+    fn test_run_logs_are_never_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let started_at = Utc::now();
+        let first = run_log_path(dir.path(), started_at);
+        let second = run_log_path(dir.path(), started_at);
+        assert_ne!(first, second);
+
+        create_run_log(&first).unwrap();
+        assert!(create_run_log(&first).is_err());
+    }
+}
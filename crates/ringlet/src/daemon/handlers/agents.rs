@@ -24,7 +24,13 @@ pub async fn inspect(id: &str, state: &ServerState) -> Response {
     let profile_count = *profile_counts.get(id).unwrap_or(&0);
 
     match agent_registry.get_info(id, profile_count) {
-        Some(agent) => Response::Agent(agent),
+        Some(mut agent) => {
+            if let Some(manifest) = agent_registry.get(id) {
+                agent.compatible_providers =
+                    manifest.compatible_providers(state.provider_registry.lock().await.manifests());
+            }
+            Response::Agent(agent)
+        }
         None => Response::error(
             error_codes::AGENT_NOT_FOUND,
             format!("Agent not found: {}", id),
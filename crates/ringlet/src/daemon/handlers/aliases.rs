@@ -3,9 +3,12 @@
 use crate::daemon::server::ServerState;
 use ringlet_core::Response;
 use ringlet_core::rpc::error_codes;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::info;
 
+/// Upper bound on the size of a generated shim script.
+const MAX_SHIM_LEN: u64 = 1024;
+
 /// Install an alias shim script (sync version for internal use).
 /// Returns the path to the installed shim on success, or an error message.
 pub fn install_alias_sync(alias: &str) -> Result<PathBuf, String> {
@@ -13,8 +16,20 @@ pub fn install_alias_sync(alias: &str) -> Result<PathBuf, String> {
     let target_dir =
         default_bin_dir().ok_or_else(|| "Could not determine bin directory".to_string())?;
 
+    write_shim(&target_dir, alias)
+}
+
+/// Move an installed alias shim to a new alias, keeping its directory.
+/// Returns `None` when no shim was installed for `old_alias`.
+pub fn rename_alias_sync(old_alias: &str, new_alias: &str) -> Option<Result<PathBuf, String>> {
+    let removed = uninstall_alias_sync(old_alias)?;
+    let target_dir = removed.parent().unwrap_or(Path::new("."));
+    Some(write_shim(target_dir, new_alias))
+}
+
+fn write_shim(target_dir: &Path, alias: &str) -> Result<PathBuf, String> {
     // Ensure target directory exists
-    std::fs::create_dir_all(&target_dir)
+    std::fs::create_dir_all(target_dir)
         .map_err(|e| format!("Failed to create bin directory: {}", e))?;
 
     // Generate and write the shim script
@@ -49,6 +64,63 @@ pub fn uninstall_alias_sync(alias: &str) -> Option<PathBuf> {
     None
 }
 
+/// Remove the alias shim of a profile being deleted.
+///
+/// Tries the recorded shim path, then the default locations, and only removes
+/// files ringlet generated, so an unrelated program with the alias's name is
+/// left alone. Returns the path that was removed, if any.
+pub fn remove_profile_shim_sync(alias: &str, recorded: Option<&Path>) -> Option<PathBuf> {
+    let shim_content = generate_shim_script(alias);
+    let locations = [default_bin_dir(), Some(PathBuf::from("/usr/local/bin"))];
+    let candidates = recorded
+        .map(Path::to_path_buf)
+        .into_iter()
+        .chain(locations.into_iter().flatten().map(|dir| dir.join(alias)));
+
+    for shim_path in candidates {
+        let is_shim = std::fs::read_to_string(&shim_path).is_ok_and(|c| c == shim_content);
+        if is_shim && std::fs::remove_file(&shim_path).is_ok() {
+            return Some(shim_path);
+        }
+    }
+    None
+}
+
+/// Find ringlet-generated alias shims in the default locations and `extra_dirs`.
+/// Returns each shim's alias and path.
+pub fn find_shims_sync(extra_dirs: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let mut dirs: Vec<PathBuf> = [default_bin_dir(), Some(PathBuf::from("/usr/local/bin"))]
+        .into_iter()
+        .flatten()
+        .chain(extra_dirs.iter().cloned())
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+
+    let mut shims = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(alias) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            // Shims are a few lines long; skip reading real binaries
+            let small = entry
+                .metadata()
+                .is_ok_and(|m| m.is_file() && m.len() <= MAX_SHIM_LEN);
+            if small
+                && std::fs::read_to_string(&path).is_ok_and(|c| c == generate_shim_script(alias))
+            {
+                shims.push((alias.to_string(), path.clone()));
+            }
+        }
+    }
+    shims
+}
+
 /// Install an alias shim script.
 pub async fn install(alias: &str, bin_dir: Option<&PathBuf>, state: &ServerState) -> Response {
     // Verify profile exists
@@ -0,0 +1,423 @@
+//! Configuration bundle request handlers.
+
+use crate::daemon::agent_registry::AgentRegistry;
+use crate::daemon::bundle::{self, Bundle};
+use crate::daemon::provider_registry::ProviderRegistry;
+use crate::daemon::server::ServerState;
+use ringlet_core::rpc::error_codes;
+use ringlet_core::{BundleReport, Event, Profile, Response, expand_template};
+use std::path::Path;
+use tracing::{info, warn};
+
+/// Write all profiles, config files and the registry pin to a bundle.
+pub async fn export(path: &Path, state: &ServerState) -> Response {
+    info!("Exporting configuration bundle to {:?}", path);
+
+    let profiles = match load_profiles(state) {
+        Ok(profiles) => profiles,
+        Err(e) => return Response::error(error_codes::INTERNAL_ERROR, e.to_string()),
+    };
+    let registry_pin = match state.registry_client.pinned() {
+        Ok(pin) => pin,
+        Err(e) => {
+            return Response::error(
+                error_codes::REGISTRY_ERROR,
+                format!("Failed to read registry pin: {}", e),
+            );
+        }
+    };
+
+    let bundle = match Bundle::collect(&state.paths, &profiles, registry_pin) {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            return Response::error(
+                error_codes::INTERNAL_ERROR,
+                format!("Failed to collect configuration: {}", e),
+            );
+        }
+    };
+    if let Err(e) = bundle.write(path) {
+        return Response::error(
+            error_codes::INTERNAL_ERROR,
+            format!("Failed to write bundle: {}", e),
+        );
+    }
+
+    let needs_api_key = requiring_api_key(&profiles, state).await;
+    info!(
+        "Exported {} profiles and {} files to {:?}",
+        profiles.len(),
+        bundle.files.len(),
+        path
+    );
+
+    Response::Bundle(BundleReport {
+        path: path.to_path_buf(),
+        profiles: profiles.into_iter().map(|p| p.alias).collect(),
+        files: bundle.files.into_keys().collect(),
+        skipped: Vec::new(),
+        needs_api_key,
+        registry_pin: bundle.manifest.registry_pin,
+    })
+}
+
+/// Restore a bundle, keeping existing profiles and files unless `overwrite` is set.
+///
+/// A bundle that would install hooks, scripts or other commands, provider
+/// manifests, profile environment variables or profile homes outside the
+/// usual place is refused unless `force` is set. Nothing is changed if restoring any file or profile
+/// fails.
+pub async fn import(path: &Path, overwrite: bool, force: bool, state: &ServerState) -> Response {
+    info!("Importing configuration bundle from {:?}", path);
+
+    let bundle = match Bundle::read(path) {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            return Response::error(
+                error_codes::INVALID_BUNDLE,
+                format!("Invalid bundle {}: {:#}", path.display(), e),
+            );
+        }
+    };
+
+    // Work out what would change before touching anything
+    let (files, mut skipped) = bundle.plan_files(&state.paths, overwrite);
+    let mut profiles = Vec::new();
+    for profile in &bundle.profiles {
+        let existing = match state.profile_store.get(&profile.alias) {
+            Ok(existing) => existing,
+            Err(e) => return Response::error(error_codes::INTERNAL_ERROR, e.to_string()),
+        };
+        if existing.is_some() && !overwrite {
+            skipped.push(profile.alias.clone());
+            continue;
+        }
+        profiles.push((bundle::localize_profile(profile.clone()), existing));
+    }
+
+    let mut commands = {
+        let providers = state.provider_registry.lock().await;
+        let known_providers: Vec<&str> = providers.ids().collect();
+        bundle.review_files(&files, &known_providers)
+    };
+    {
+        let agents = state.agent_registry.lock().await;
+        for (profile, _) in &profiles {
+            let expected_home = agents.get(&profile.agent_id).map(|agent| {
+                expand_template(
+                    &agent.profile.source_home,
+                    &profile.alias,
+                    &profile.agent_id,
+                )
+            });
+            commands.extend(bundle::review_profile(profile, expected_home.as_deref()));
+        }
+    }
+    if !commands.is_empty() {
+        if !force {
+            return Response::error(
+                error_codes::UNTRUSTED_BUNDLE,
+                format!(
+                    "Bundle {} would install commands, providers or settings that need review:\n  - {}\n\
+                     Check them and use --force to import it anyway.",
+                    path.display(),
+                    commands.join("\n  - ")
+                ),
+            );
+        }
+        for command in &commands {
+            warn!("Importing bundle command: {}", command);
+        }
+    }
+
+    let mut staged = match bundle
+        .stage_files(&state.paths, &files)
+        .and_then(|mut staged| staged.commit(&state.paths).map(|()| staged))
+    {
+        Ok(staged) => staged,
+        Err(e) => {
+            return Response::error(
+                error_codes::INTERNAL_ERROR,
+                format!("Failed to restore config files: {:#}", e),
+            );
+        }
+    };
+
+    let mut restored: Vec<(Profile, Option<Profile>)> = Vec::new();
+    for (profile, existing) in profiles {
+        if let Err(e) = state.profile_manager.restore(profile.clone()) {
+            for (profile, existing) in restored.iter().rev() {
+                undo_restore(profile, existing.as_ref(), state);
+            }
+            staged.rollback(&state.paths);
+            return Response::error(
+                error_codes::INTERNAL_ERROR,
+                format!("Failed to restore profile '{}': {}", profile.alias, e),
+            );
+        }
+        restored.push((profile, existing));
+    }
+    drop(staged);
+
+    if files.iter().any(|f| f.starts_with("agents.d/")) {
+        match AgentRegistry::new(&state.paths) {
+            Ok(registry) => *state.agent_registry.lock().await = registry,
+            Err(e) => warn!("Failed to reload agent manifests: {}", e),
+        }
+    }
+    if files.iter().any(|f| f.starts_with("providers.d/")) {
+        match ProviderRegistry::new(&state.paths) {
+            Ok(registry) => *state.provider_registry.lock().await = registry,
+            Err(e) => warn!("Failed to reload provider manifests: {}", e),
+        }
+    }
+
+    for (profile, existing) in &restored {
+        if profile.metadata.alias_path.is_some() {
+            install_alias(&profile.alias, state);
+        }
+        if existing.is_none() {
+            state.broadcast(Event::ProfileCreated {
+                alias: profile.alias.clone(),
+            });
+        }
+    }
+    let restored: Vec<Profile> = restored.into_iter().map(|(profile, _)| profile).collect();
+
+    let registry_pin = match (
+        bundle.manifest.registry_pin,
+        bundle.manifest.registry_commit,
+    ) {
+        (Some(ref_), commit) => match state.registry_client.restore_pin(&ref_, commit.as_deref()) {
+            Ok(_) => Some(ref_),
+            Err(e) => {
+                warn!("Failed to restore registry pin {}: {}", ref_, e);
+                None
+            }
+        },
+        (None, _) => None,
+    };
+
+    let needs_api_key = requiring_api_key(&restored, state)
+        .await
+        .into_iter()
+        .filter(|alias| state.secret_store.get_api_key(alias).is_err())
+        .collect();
+    info!(
+        "Imported {} profiles and {} files from {:?}",
+        restored.len(),
+        files.len(),
+        path
+    );
+
+    Response::Bundle(BundleReport {
+        path: path.to_path_buf(),
+        profiles: restored.into_iter().map(|p| p.alias).collect(),
+        files,
+        skipped,
+        needs_api_key,
+        registry_pin,
+    })
+}
+
+/// Put back the profile a failed import replaced, or remove the one it added.
+fn undo_restore(profile: &Profile, previous: Option<&Profile>, state: &ServerState) {
+    let result = match previous {
+        Some(previous) => state.profile_store.update(previous),
+        None => state.profile_store.delete(&profile.alias).map(drop),
+    };
+    if let Err(e) = result {
+        warn!("Failed to roll back profile '{}': {}", profile.alias, e);
+    }
+}
+
+/// Install the shell alias of a restored profile and record where it went.
+fn install_alias(alias: &str, state: &ServerState) {
+    let alias_path = match super::aliases::install_alias_sync(alias) {
+        Ok(path) => Some(path),
+        Err(e) => {
+            warn!("Failed to install alias for '{}': {}", alias, e);
+            None
+        }
+    };
+    let result = state
+        .profile_store
+        .get(alias)
+        .and_then(|profile| match profile {
+            Some(mut profile) => {
+                profile.metadata.alias_path = alias_path;
+                state.profile_store.update(&profile)
+            }
+            None => Ok(()),
+        });
+    if let Err(e) = result {
+        warn!("Failed to record alias for '{}': {}", alias, e);
+    }
+}
+
+fn load_profiles(state: &ServerState) -> anyhow::Result<Vec<Profile>> {
+    let mut profiles = Vec::new();
+    for alias in state.profile_store.names()? {
+        if let Some(profile) = state.profile_store.get(&alias)? {
+            profiles.push(profile);
+        }
+    }
+    Ok(profiles)
+}
+
+/// Aliases of the profiles whose provider needs an API key.
+///
+/// Providers this daemon does not know are assumed to need one.
+async fn requiring_api_key(profiles: &[Profile], state: &ServerState) -> Vec<String> {
+    let providers = state.provider_registry.lock().await;
+    profiles
+        .iter()
+        .filter(|profile| {
+            providers
+                .get(&profile.provider_id)
+                .is_none_or(|provider| provider.auth.stores_credential())
+        })
+        .map(|profile| profile.alias.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::profile_store::test_profile;
+
+    fn save_profile(state: &ServerState, alias: &str, home: &Path) -> Profile {
+        let profile = test_profile(alias, home);
+        state.profile_store.save_new(&profile).unwrap();
+        profile
+    }
+
+    async fn bundle_of(state: &ServerState, path: &Path) {
+        match export(path, state).await {
+            Response::Bundle(_) => {}
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_and_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = ServerState::for_tests(&dir.path().join("source"));
+        save_profile(&source, "work", &dir.path().join("homes/work"));
+        std::fs::write(
+            source.paths.providers_d().join("local.toml"),
+            "id = \"local\"\n",
+        )
+        .unwrap();
+        let archive = dir.path().join("bundle.tar.gz");
+        bundle_of(&source, &archive).await;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&archive).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let target = ServerState::for_tests(&dir.path().join("target"));
+        // The provider manifest and the home outside ~/.claude-profiles need review
+        match import(&archive, false, false, &target).await {
+            Response::Error { code, message } => {
+                assert_eq!(code, error_codes::UNTRUSTED_BUNDLE);
+                assert!(message.contains("providers.d/local.toml"), "{}", message);
+                assert!(
+                    message.contains("is not where ringlet keeps claude profiles"),
+                    "{}",
+                    message
+                );
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+        assert!(target.profile_store.get("work").unwrap().is_none());
+
+        match import(&archive, false, true, &target).await {
+            Response::Bundle(report) => {
+                assert_eq!(report.profiles, ["work"]);
+                assert_eq!(report.files, ["providers.d/local.toml"]);
+                assert_eq!(report.skipped, ["config.toml"]);
+                assert_eq!(report.needs_api_key, ["work"]);
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+        assert!(target.profile_store.get("work").unwrap().is_some());
+        assert!(target.paths.providers_d().join("local.toml").exists());
+    }
+
+    #[tokio::test]
+    async fn test_import_with_commands_needs_force() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = ServerState::for_tests(&dir.path().join("source"));
+        let mut profile = save_profile(&source, "work", &dir.path().join("homes/work"));
+        profile.metadata.pre_run = Some("make setup".to_string());
+        source.profile_store.update(&profile).unwrap();
+        let archive = dir.path().join("bundle.tar.gz");
+        bundle_of(&source, &archive).await;
+
+        let target = ServerState::for_tests(&dir.path().join("target"));
+        match import(&archive, false, false, &target).await {
+            Response::Error { code, message } => {
+                assert_eq!(code, error_codes::UNTRUSTED_BUNDLE);
+                assert!(message.contains("pre-run runs `make setup`"), "{}", message);
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+        assert!(target.profile_store.get("work").unwrap().is_none());
+
+        assert!(matches!(
+            import(&archive, false, true, &target).await,
+            Response::Bundle(_)
+        ));
+        let imported = target.profile_store.get("work").unwrap().unwrap();
+        assert_eq!(imported.metadata.pre_run.as_deref(), Some("make setup"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_import_changes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = ServerState::for_tests(&dir.path().join("source"));
+        save_profile(&source, "a", &dir.path().join("homes/a"));
+        // The home of `b` cannot be created, so restoring it fails
+        std::fs::write(dir.path().join("blocker"), "").unwrap();
+        save_profile(&source, "b", &dir.path().join("blocker/b"));
+        std::fs::write(
+            source.paths.config_file(),
+            "[security]\nsecret_backend = \"file\"\n\n[telemetry]\nenabled = false\n",
+        )
+        .unwrap();
+        std::fs::write(
+            source.paths.providers_d().join("local.toml"),
+            "id = \"local\"\n",
+        )
+        .unwrap();
+        let archive = dir.path().join("bundle.tar.gz");
+        bundle_of(&source, &archive).await;
+
+        let target = ServerState::for_tests(&dir.path().join("target"));
+        let config = std::fs::read_to_string(target.paths.config_file()).unwrap();
+        match import(&archive, true, true, &target).await {
+            Response::Error { code, message } => {
+                assert_eq!(code, error_codes::INTERNAL_ERROR);
+                assert!(message.contains("'b'"), "{}", message);
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+
+        assert!(target.profile_store.names().unwrap().is_empty());
+        assert_eq!(
+            std::fs::read_to_string(target.paths.config_file()).unwrap(),
+            config
+        );
+        assert!(!target.paths.providers_d().join("local.toml").exists());
+        let leftovers: Vec<_> = std::fs::read_dir(&target.paths.config_dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(".import-"))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+}
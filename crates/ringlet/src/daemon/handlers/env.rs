@@ -9,10 +9,11 @@ use tracing::info;
 
 /// Run a manifest-defined setup task for a profile.
 pub async fn setup(alias: &str, task: &str, state: &ServerState) -> Response {
-    let prepared = match prepare_execution_context(alias, &[], state, false, false).await {
-        Ok(prepared) => prepared,
-        Err(response) => return response,
-    };
+    let prepared =
+        match prepare_execution_context(alias, &[], state, false, false, None, None).await {
+            Ok(prepared) => prepared,
+            Err(response) => return response,
+        };
 
     let agent_registry = state.agent_registry.lock().await;
     let agent = match agent_registry.get(&prepared.profile.agent_id) {
@@ -1,15 +1,26 @@
 //! Hooks management handlers.
 
 use crate::daemon::server::ServerState;
-use ringlet_core::{HookAction, HookRule, HooksConfig, Response, rpc::error_codes};
-use tracing::info;
+use ringlet_core::{
+    AgentManifest, HookAction, HookRule, HooksConfig, Profile, Response, UserConfig, lint_command,
+    rpc::error_codes, validate_matcher,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use tracing::{info, warn};
 
 /// Add a hook rule to a profile.
+///
+/// With `dry_run`, nothing is saved; the resulting hooks and the generated
+/// agent config section are returned instead. A command [`lint_command`]
+/// warns about is refused unless `force` is set.
 pub async fn add(
     alias: &str,
     event: &str,
     matcher: &str,
     command: &str,
+    dry_run: bool,
+    force: bool,
     state: &ServerState,
 ) -> Response {
     // Validate event type
@@ -24,6 +35,18 @@ pub async fn add(
         );
     }
 
+    if let Err(e) = validate_matcher(matcher) {
+        return Response::error(
+            error_codes::INVALID_HOOK_CONFIG,
+            format!("Invalid matcher '{}': {}", matcher, e),
+        );
+    }
+
+    let owner = format!("profile '{}'", alias);
+    if let Some(response) = unsafe_commands(&owner, &lint_command(command), force) {
+        return response;
+    }
+
     // Load profile
     let profile = match state.profile_store.get(alias) {
         Ok(Some(p)) => p,
@@ -39,7 +62,7 @@ pub async fn add(
     // Load agent to check supports_hooks
     let agent_registry = state.agent_registry.lock().await;
     let agent = match agent_registry.get(&profile.agent_id) {
-        Some(a) => a,
+        Some(a) => a.clone(),
         None => {
             return Response::error(
                 error_codes::AGENT_NOT_FOUND,
@@ -47,6 +70,7 @@ pub async fn add(
             );
         }
     };
+    drop(agent_registry);
 
     if !agent.supports_hooks {
         return Response::error(
@@ -54,7 +78,6 @@ pub async fn add(
             format!("Agent '{}' does not support hooks", agent.id),
         );
     }
-    drop(agent_registry);
 
     // Get or create hooks config
     let mut hooks_config = profile.metadata.hooks_config.clone().unwrap_or_default();
@@ -75,7 +98,21 @@ pub async fn add(
 
     // Update profile
     let mut updated_profile = profile.clone();
-    updated_profile.metadata.hooks_config = Some(hooks_config);
+    updated_profile.metadata.hooks_config = Some(hooks_config.clone());
+
+    if dry_run {
+        return match render_hooks(&updated_profile, &agent, state).await {
+            Ok(rendered) => {
+                let (file, generated) = rendered.unzip();
+                Response::HooksPreview {
+                    hooks: hooks_config,
+                    file,
+                    generated,
+                }
+            }
+            Err(response) => *response,
+        };
+    }
 
     if let Err(e) = state.profile_store.update(&updated_profile) {
         return Response::error(error_codes::INTERNAL_ERROR, e.to_string());
@@ -92,6 +129,29 @@ pub async fn add(
     ))
 }
 
+/// The error refusing hook commands that look unsafe, or `None` to go ahead.
+///
+/// With `force` the commands are saved anyway and the warnings only logged.
+/// `owner` names where the hooks go, for the log.
+fn unsafe_commands(owner: &str, warnings: &[String], force: bool) -> Option<Response> {
+    if warnings.is_empty() {
+        return None;
+    }
+    if force {
+        for warning in warnings {
+            warn!("Saving unsafe hook for {}: {}", owner, warning);
+        }
+        return None;
+    }
+    Some(Response::error(
+        error_codes::UNSAFE_HOOK_COMMAND,
+        format!(
+            "Hook command looks unsafe:\n  - {}\nUse --force to save it anyway.",
+            warnings.join("\n  - ")
+        ),
+    ))
+}
+
 /// List hooks for a profile.
 pub async fn list(alias: &str, state: &ServerState) -> Response {
     let profile = match state.profile_store.get(alias) {
@@ -187,7 +247,20 @@ pub async fn remove(alias: &str, event: &str, index: usize, state: &ServerState)
 }
 
 /// Import hooks configuration for a profile.
-pub async fn import(alias: &str, config: &HooksConfig, state: &ServerState) -> Response {
+pub async fn import(
+    alias: &str,
+    config: &HooksConfig,
+    force: bool,
+    state: &ServerState,
+) -> Response {
+    if let Err(e) = config.validate() {
+        return Response::error(error_codes::INVALID_HOOK_CONFIG, e);
+    }
+    let owner = format!("profile '{}'", alias);
+    if let Some(response) = unsafe_commands(&owner, &config.lint(), force) {
+        return response;
+    }
+
     // Load profile
     let profile = match state.profile_store.get(alias) {
         Ok(Some(p)) => p,
@@ -238,7 +311,251 @@ pub async fn import(alias: &str, config: &HooksConfig, state: &ServerState) -> R
 }
 
 /// Export hooks configuration for a profile.
-pub async fn export(alias: &str, state: &ServerState) -> Response {
-    // Same as list - returns the hooks config
-    list(alias, state).await
+///
+/// With a `format` naming an agent, the agent's script is run against the
+/// profile and the hooks section of the config it renders is returned.
+pub async fn export(alias: &str, format: Option<&str>, state: &ServerState) -> Response {
+    let agent_id = match format {
+        None | Some("ringlet") => return list(alias, state).await,
+        Some(agent_id) => agent_id,
+    };
+
+    let profile = match state.profile_store.get(alias) {
+        Ok(Some(p)) => p,
+        Ok(None) => {
+            return Response::error(
+                error_codes::PROFILE_NOT_FOUND,
+                format!("Profile not found: {}", alias),
+            );
+        }
+        Err(e) => return Response::error(error_codes::INTERNAL_ERROR, e.to_string()),
+    };
+
+    let agent_registry = state.agent_registry.lock().await;
+    let agent = match agent_registry.get(agent_id) {
+        Some(a) => a.clone(),
+        None => {
+            return Response::error(
+                error_codes::AGENT_NOT_FOUND,
+                format!("Agent not found: {}", agent_id),
+            );
+        }
+    };
+    drop(agent_registry);
+
+    match render_hooks(&profile, &agent, state).await {
+        Ok(Some((file, content))) => Response::HooksNative { file, content },
+        Ok(None) => Response::error(
+            error_codes::HOOKS_NOT_SUPPORTED,
+            format!(
+                "No hooks in the config generated by {} for profile '{}'",
+                agent.profile.script, alias
+            ),
+        ),
+        Err(response) => *response,
+    }
+}
+
+/// Run the agent's script against `profile` and pick out the hooks section
+/// of the generated config, as `(file, snippet)`.
+async fn render_hooks(
+    profile: &Profile,
+    agent: &AgentManifest,
+    state: &ServerState,
+) -> Result<Option<(String, String)>, Box<Response>> {
+    let provider = state
+        .provider_registry
+        .lock()
+        .await
+        .get(&profile.provider_id)
+        .cloned()
+        .ok_or_else(|| {
+            Box::new(Response::error(
+                error_codes::PROVIDER_NOT_FOUND,
+                format!("Provider not found: {}", profile.provider_id),
+            ))
+        })?;
+
+    let files = state
+        .execution_adapter
+        .render_files(profile, agent, &provider)
+        .map_err(|e| {
+            Box::new(Response::error(
+                error_codes::SCRIPT_ERROR,
+                format!("Failed to run {}: {}", agent.profile.script, e),
+            ))
+        })?;
+
+    Ok(native_hooks(&files))
+}
+
+/// List the default hooks from config.toml.
+pub async fn defaults_list(state: &ServerState) -> Response {
+    match UserConfig::load(&state.paths.config_file()) {
+        Ok(config) => Response::HooksDefaults(config.hooks.defaults),
+        Err(e) => Response::error(
+            error_codes::INTERNAL_ERROR,
+            format!("Failed to read config: {}", e),
+        ),
+    }
+}
+
+/// Add a default hook for an agent (or `*` for every agent) to config.toml.
+pub async fn defaults_add(
+    agent: &str,
+    event: &str,
+    matcher: &str,
+    command: &str,
+    force: bool,
+    state: &ServerState,
+) -> Response {
+    if HooksConfig::event_types().iter().all(|&e| e != event) {
+        return Response::error(
+            error_codes::INVALID_HOOK_EVENT,
+            format!(
+                "Invalid event type '{}'. Valid types: {:?}",
+                event,
+                HooksConfig::event_types()
+            ),
+        );
+    }
+
+    if let Err(e) = validate_matcher(matcher) {
+        return Response::error(
+            error_codes::INVALID_HOOK_CONFIG,
+            format!("Invalid matcher '{}': {}", matcher, e),
+        );
+    }
+
+    if agent != "*" && state.agent_registry.lock().await.get(agent).is_none() {
+        return Response::error(
+            error_codes::AGENT_NOT_FOUND,
+            format!("Agent not found: {}", agent),
+        );
+    }
+
+    let owner = format!("the default hooks of agent '{}'", agent);
+    if let Some(response) = unsafe_commands(&owner, &lint_command(command), force) {
+        return response;
+    }
+
+    let added = UserConfig::update(&state.paths.config_file(), |config| {
+        let defaults = config.hooks.defaults.entry(agent.to_string()).or_default();
+        if let Some(rules) = defaults.get_rules_mut(event) {
+            rules.push(HookRule {
+                matcher: matcher.to_string(),
+                hooks: vec![HookAction::Command {
+                    command: command.to_string(),
+                    timeout: None,
+                }],
+            });
+        }
+        Ok::<_, std::io::Error>(())
+    });
+    if let Err(e) = added {
+        return Response::error(
+            error_codes::INTERNAL_ERROR,
+            format!("Failed to update config: {}", e),
+        );
+    }
+
+    info!(
+        "Added default hook for agent '{}' event '{}' with matcher '{}'",
+        agent, event, matcher
+    );
+
+    Response::success(format!(
+        "Default hook added for agent '{}' event '{}'",
+        agent, event
+    ))
+}
+
+/// Choose whether a profile's generated config includes the default hooks.
+pub async fn skip_defaults(alias: &str, skip: bool, state: &ServerState) -> Response {
+    let mut profile = match state.profile_store.get(alias) {
+        Ok(Some(p)) => p,
+        Ok(None) => {
+            return Response::error(
+                error_codes::PROFILE_NOT_FOUND,
+                format!("Profile not found: {}", alias),
+            );
+        }
+        Err(e) => return Response::error(error_codes::INTERNAL_ERROR, e.to_string()),
+    };
+
+    profile.metadata.skip_default_hooks = skip;
+    if let Err(e) = state.profile_store.update(&profile) {
+        return Response::error(error_codes::INTERNAL_ERROR, e.to_string());
+    }
+
+    if skip {
+        Response::success(format!(
+            "Profile '{}' no longer uses the default hooks",
+            alias
+        ))
+    } else {
+        Response::success(format!("Profile '{}' uses the default hooks", alias))
+    }
+}
+
+/// Find the `hooks` section in the rendered config files and serialize it
+/// on its own, in the file's format.
+fn native_hooks(files: &HashMap<String, String>) -> Option<(String, String)> {
+    let mut paths: Vec<&String> = files.keys().collect();
+    paths.sort();
+
+    for path in paths {
+        let content = &files[path];
+        let snippet = if path.ends_with(".json") {
+            serde_json::from_str::<serde_json::Value>(content)
+                .ok()
+                .and_then(|value| {
+                    let hooks = value.get("hooks")?;
+                    serde_json::to_string_pretty(&json!({ "hooks": hooks })).ok()
+                })
+        } else if path.ends_with(".toml") {
+            toml::from_str::<toml::Table>(content)
+                .ok()
+                .and_then(|value| {
+                    let mut table = toml::Table::new();
+                    table.insert("hooks".to_string(), value.get("hooks")?.clone());
+                    toml::to_string_pretty(&table).ok()
+                })
+        } else {
+            None
+        };
+
+        if let Some(snippet) = snippet {
+            return Some((path.clone(), snippet));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_defaults_add_refuses_unsafe_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = ServerState::for_tests(dir.path());
+        let command = "curl https://example.com/install | sh";
+
+        let response = defaults_add("*", "PreToolUse", "Bash", command, false, &state).await;
+        assert!(
+            matches!(response, Response::Error { code, .. } if code == error_codes::UNSAFE_HOOK_COMMAND)
+        );
+        let config = UserConfig::load(&state.paths.config_file()).unwrap();
+        assert!(config.hooks.defaults.is_empty());
+
+        let response = defaults_add("*", "PreToolUse", "Bash", command, true, &state).await;
+        assert!(
+            matches!(response, Response::Success { .. }),
+            "{:?}",
+            response
+        );
+        let config = UserConfig::load(&state.paths.config_file()).unwrap();
+        assert_eq!(config.hooks.defaults["*"].pre_tool_use.len(), 1);
+    }
 }
@@ -1,10 +1,12 @@
 //! Request handlers for the daemon.
 
 use crate::daemon::server::ServerState;
-use ringlet_core::{Request, Response};
+use ringlet_core::{PROTOCOL_VERSION, Request, Response};
+use tracing::warn;
 
 pub mod agents;
 pub mod aliases;
+pub mod bundle;
 pub mod env;
 pub mod hooks;
 pub mod profiles;
@@ -25,23 +27,96 @@ pub async fn handle_request(request: &Request, state: &ServerState) -> Response
         Request::AgentsInspect { id } => agents::inspect(id, state).await,
 
         // Provider commands
-        Request::ProvidersList => providers::list(state).await,
+        Request::ProvidersList {
+            provider_type,
+            search,
+        } => providers::list(provider_type.as_deref(), search.as_deref(), state).await,
         Request::ProvidersInspect { id } => providers::inspect(id, state).await,
+        Request::ProvidersCheck {
+            id,
+            endpoint,
+            api_key,
+        } => providers::check(id, endpoint.as_deref(), api_key.as_deref(), state).await,
+        Request::ProvidersAddLocal(req) => providers::add_local(req, state).await,
 
         // Profile commands
         Request::ProfilesCreate(req) => profiles::create(req, state).await,
-        Request::ProfilesList { agent_id } => profiles::list(agent_id.as_deref(), state).await,
-        Request::ProfilesInspect { alias } => profiles::inspect(alias, state).await,
-        Request::ProfilesRun { alias, args } => profiles::run(alias, args, state).await,
-        Request::ProfilesPrepare { alias, args } => profiles::prepare(alias, args, state).await,
+        Request::ProfilesList {
+            agent_id,
+            provider_id,
+        } => profiles::list(agent_id.as_deref(), provider_id.as_deref(), state).await,
+        Request::ProfilesNames => profiles::names(state).await,
+        Request::ProfilesInspect {
+            alias,
+            show_secrets,
+        } => profiles::inspect(alias, *show_secrets, state).await,
+        Request::ProfilesRun {
+            alias,
+            args,
+            env,
+            ephemeral_proxy,
+        } => profiles::run(alias, args, env, *ephemeral_proxy, state).await,
+        Request::ProfilesPrepare {
+            alias,
+            args,
+            env,
+            ephemeral_proxy,
+            cwd,
+            owner_pid,
+        } => {
+            profiles::prepare(
+                alias,
+                args,
+                env,
+                *ephemeral_proxy,
+                cwd.as_deref(),
+                *owner_pid,
+                state,
+            )
+            .await
+        }
         Request::ProfilesComplete {
             run_id,
             started_at,
             ended_at,
             exit_code,
-        } => profiles::complete(run_id, *started_at, *ended_at, *exit_code, state).await,
+            signal,
+        } => profiles::complete(run_id, *started_at, *ended_at, *exit_code, *signal, state).await,
+        Request::ProfilesAbandon { run_id } => profiles::abandon(run_id, state).await,
         Request::ProfilesDelete { alias } => profiles::delete(alias, state).await,
+        Request::ProfilesRename {
+            old_alias,
+            new_alias,
+        } => profiles::rename(old_alias, new_alias, state).await,
+        Request::ProfilesDeleteMatching {
+            agent_id,
+            provider_id,
+        } => profiles::delete_matching(agent_id.as_deref(), provider_id.as_deref(), state).await,
         Request::ProfilesEnv { alias } => profiles::env(alias, state).await,
+        Request::ProfilesHistory { alias, limit } => profiles::history(alias, *limit, state).await,
+        Request::ProfilesSetHook {
+            alias,
+            stage,
+            command,
+        } => profiles::set_hook(alias, stage, command.as_deref(), state).await,
+        Request::ProfilesSetArgs { alias, args } => profiles::set_args(alias, args, state).await,
+        Request::ProfilesSetQuota {
+            alias,
+            daily_usd,
+            monthly_usd,
+        } => profiles::set_quota(alias, *daily_usd, *monthly_usd, state).await,
+        Request::ProfilesSetLaunchWrapper { alias, wrapper } => {
+            profiles::set_launch_wrapper(alias, wrapper.as_deref(), state).await
+        }
+        Request::ProfilesSetCaptureLogs { alias, enabled } => {
+            profiles::set_capture_logs(alias, *enabled, state).await
+        }
+        Request::ProfilesSetApiKey { alias, api_key } => {
+            profiles::set_api_key(alias, api_key, state).await
+        }
+        Request::ProfilesDoctor { fix, remove_homes } => {
+            profiles::doctor(*fix, *remove_homes, state).await
+        }
 
         // Alias commands
         Request::AliasesInstall { alias, bin_dir } => {
@@ -54,6 +129,14 @@ pub async fn handle_request(request: &Request, state: &ServerState) -> Response
         Request::RegistryPin { ref_ } => registry::pin(ref_, state).await,
         Request::RegistryInspect => registry::inspect(state).await,
 
+        // Configuration bundle commands
+        Request::BundleExport { path } => bundle::export(path, state).await,
+        Request::BundleImport {
+            path,
+            overwrite,
+            force,
+        } => bundle::import(path, *overwrite, *force, state).await,
+
         // Stats commands
         Request::Stats {
             agent_id,
@@ -65,10 +148,32 @@ pub async fn handle_request(request: &Request, state: &ServerState) -> Response
             period,
             profile,
             model,
-        } => usage::get_usage(period.as_ref(), profile.as_deref(), model.as_deref(), state).await,
+            agent,
+        } => {
+            usage::get_usage(
+                period.as_ref(),
+                profile.as_deref(),
+                model.as_deref(),
+                *agent,
+                state,
+            )
+            .await
+        }
         Request::UsageImportClaude { claude_dir } => {
             usage::import_claude(claude_dir.as_ref(), state).await
         }
+        Request::UsageImport { format, content } => usage::import(*format, content, state).await,
+        Request::UsageBudget => usage::get_budget(state).await,
+        Request::UsageRecords {
+            period,
+            offset,
+            limit,
+        } => usage::get_records(period.as_ref(), *offset, *limit, state).await,
+        Request::UsageReset {
+            telemetry,
+            cache,
+            before,
+        } => usage::reset(*telemetry, *cache, *before, state).await,
 
         // Env setup commands
         Request::EnvSetup { alias, task } => env::setup(alias, task, state).await,
@@ -79,15 +184,34 @@ pub async fn handle_request(request: &Request, state: &ServerState) -> Response
             event,
             matcher,
             command,
-        } => hooks::add(alias, event, matcher, command, state).await,
+            dry_run,
+            force,
+        } => hooks::add(alias, event, matcher, command, *dry_run, *force, state).await,
         Request::HooksList { alias } => hooks::list(alias, state).await,
         Request::HooksRemove {
             alias,
             event,
             index,
         } => hooks::remove(alias, event, *index, state).await,
-        Request::HooksImport { alias, config } => hooks::import(alias, config, state).await,
-        Request::HooksExport { alias } => hooks::export(alias, state).await,
+        Request::HooksImport {
+            alias,
+            config,
+            force,
+        } => hooks::import(alias, config, *force, state).await,
+        Request::HooksExport { alias, format } => {
+            hooks::export(alias, format.as_deref(), state).await
+        }
+        Request::HooksDefaultsList => hooks::defaults_list(state).await,
+        Request::HooksDefaultsAdd {
+            agent,
+            event,
+            matcher,
+            command,
+            force,
+        } => hooks::defaults_add(agent, event, matcher, command, *force, state).await,
+        Request::HooksSkipDefaults { alias, skip } => {
+            hooks::skip_defaults(alias, *skip, state).await
+        }
 
         // Proxy commands
         Request::ProxyEnable { alias } => proxy::enable(alias, state).await,
@@ -95,10 +219,12 @@ pub async fn handle_request(request: &Request, state: &ServerState) -> Response
         Request::ProxyStart { alias } => proxy::start(alias, state).await,
         Request::ProxyStop { alias } => proxy::stop(alias, state).await,
         Request::ProxyStopAll => proxy::stop_all(state).await,
-        Request::ProxyRestart { alias } => proxy::restart(alias, state).await,
+        Request::ProxyRestart { alias, verify } => proxy::restart(alias, *verify, state).await,
         Request::ProxyStatus { alias } => proxy::status(alias.as_deref(), state).await,
         Request::ProxyConfig { alias } => proxy::config(alias, state).await,
         Request::ProxyLogs { alias, lines } => proxy::logs(alias, *lines, state).await,
+        Request::ProxyLogPath { alias } => proxy::log_path(alias, state),
+        Request::ProxyEnv { alias } => proxy::env(alias, state).await,
         Request::ProxyRouteAdd { alias, rule } => proxy::route_add(alias, rule, state).await,
         Request::ProxyRouteRemove { alias, rule_name } => {
             proxy::route_remove(alias, rule_name, state).await
@@ -115,7 +241,21 @@ pub async fn handle_request(request: &Request, state: &ServerState) -> Response
         Request::ProxyAliasList { alias } => proxy::alias_list(alias, state).await,
 
         // Ping
-        Request::Ping => Response::Pong,
+        Request::Ping { protocol_version } => {
+            if let Some(version) = protocol_version
+                && *version != PROTOCOL_VERSION
+            {
+                warn!(
+                    "Client speaks protocol v{}, daemon speaks v{}",
+                    version, PROTOCOL_VERSION
+                );
+            }
+            Response::Pong {
+                protocol_version: PROTOCOL_VERSION,
+            }
+        }
+
+        Request::Diagnostics => system::diagnostics(state),
 
         // Shutdown is handled in server.rs
         Request::Shutdown => Response::success("Shutdown handled by server"),
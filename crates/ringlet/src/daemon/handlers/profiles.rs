@@ -1,13 +1,26 @@
 //! Profile-related request handlers.
 
 use crate::daemon::agent_usage;
+use crate::daemon::execution::{exit_details, run_post_run_hook};
+use crate::daemon::oauth;
+use crate::daemon::run_history::RunHistory;
 use crate::daemon::server::{PendingPreparedRun, ServerState};
+use chrono::Utc;
+use ringlet_core::redact::is_secret_key;
 use ringlet_core::rpc::ExecutionContext;
 use ringlet_core::rpc::error_codes;
-use ringlet_core::{Event, Profile, ProfileCreateRequest, Response};
+use ringlet_core::{
+    Event, Profile, ProfileCreateRequest, ProfileDoctorReport, ProfileInfo, ProviderManifest,
+    Response, RunHistoryEntry, expand_template, validate_launch_wrapper,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use tracing::{info, warn};
 use uuid::Uuid;
 
+/// Endpoint ID that asks `create` to pick the fastest endpoint.
+const AUTO_ENDPOINT: &str = "auto";
+
 /// Create a new profile.
 pub async fn create(req: &ProfileCreateRequest, state: &ServerState) -> Response {
     info!("Creating profile: {} for agent {}", req.alias, req.agent_id);
@@ -35,10 +48,11 @@ pub async fn create(req: &ProfileCreateRequest, state: &ServerState) -> Response
     let agent = agent_registry.get(&req.agent_id).unwrap();
     let agent_default_model = agent.models.default.clone();
     let source_home = agent.profile.source_home.clone();
+    drop(agent_registry);
 
     // Validate provider exists
-    let provider = match state.provider_registry.get(&req.provider_id) {
-        Some(p) => p,
+    let provider = match state.provider_registry.lock().await.get(&req.provider_id) {
+        Some(p) => p.clone(),
         None => {
             return Response::error(
                 error_codes::PROVIDER_NOT_FOUND,
@@ -47,6 +61,29 @@ pub async fn create(req: &ProfileCreateRequest, state: &ServerState) -> Response
         }
     };
 
+    // `--endpoint auto` probes every endpoint and keeps the fastest
+    let auto_req;
+    let mut auto_endpoint = None;
+    let req = if req.endpoint_id.as_deref() == Some(AUTO_ENDPOINT)
+        && !provider.endpoints.contains_key(AUTO_ENDPOINT)
+    {
+        let Some(fastest) = super::providers::fastest_endpoint(&provider).await else {
+            return Response::error(
+                error_codes::INVALID_ENDPOINT,
+                format!("No endpoint of provider '{}' responded", provider.id),
+            );
+        };
+        info!("Selected endpoint '{}' for {}", fastest, req.alias);
+        auto_endpoint = Some(fastest.clone());
+        auto_req = ProfileCreateRequest {
+            endpoint_id: Some(fastest),
+            ..req.clone()
+        };
+        &auto_req
+    } else {
+        req
+    };
+
     // Resolve endpoint
     let default_endpoint = provider.default_endpoint().unwrap_or("default");
     let endpoint_id = req.endpoint_id.as_deref().unwrap_or(default_endpoint);
@@ -57,6 +94,25 @@ pub async fn create(req: &ProfileCreateRequest, state: &ServerState) -> Response
         );
     }
 
+    // An explicitly requested model must be offered by an endpoint with its own list
+    if let (Some(model), Some(models)) = (&req.model, provider.endpoint_models(endpoint_id))
+        && !models.contains(model)
+    {
+        return Response::error(
+            error_codes::INVALID_MODEL,
+            format!(
+                "Model '{}' is not available on endpoint '{}' (available: {})",
+                model,
+                endpoint_id,
+                models.join(", ")
+            ),
+        );
+    }
+
+    if let Some(Err(e)) = req.launch_wrapper.as_deref().map(validate_launch_wrapper) {
+        return Response::error(error_codes::INVALID_LAUNCH_WRAPPER, e.to_string());
+    }
+
     // Resolve model - use request model, or agent default, or provider default
     // But validate that the model is compatible with the provider's available models
     let candidate_model = req
@@ -68,8 +124,11 @@ pub async fn create(req: &ProfileCreateRequest, state: &ServerState) -> Response
 
     // If provider has an explicit list of available models, validate compatibility
     // Fall back to provider's default model if candidate isn't supported
-    let resolved_model = if !provider.models.available.is_empty() {
-        if provider.models.available.contains(&candidate_model) {
+    let available = provider
+        .endpoint_models(endpoint_id)
+        .unwrap_or(&provider.models.available);
+    let resolved_model = if !available.is_empty() {
+        if available.contains(&candidate_model) {
             candidate_model
         } else {
             // Candidate model not supported by provider, use provider's default
@@ -116,7 +175,7 @@ pub async fn create(req: &ProfileCreateRequest, state: &ServerState) -> Response
             });
 
             // Build response message
-            let message = if alias_installed {
+            let mut message = if alias_installed {
                 format!(
                     "Profile '{}' created. Run with: {}",
                     profile.alias, profile.alias
@@ -127,6 +186,9 @@ pub async fn create(req: &ProfileCreateRequest, state: &ServerState) -> Response
                     profile.alias, profile.alias
                 )
             };
+            if let Some(endpoint) = auto_endpoint {
+                message.push_str(&format!(" (fastest endpoint: {})", endpoint));
+            }
 
             Response::success(message)
         }
@@ -137,10 +199,28 @@ pub async fn create(req: &ProfileCreateRequest, state: &ServerState) -> Response
     }
 }
 
-/// List profiles, optionally filtered by agent.
-pub async fn list(agent_id: Option<&str>, state: &ServerState) -> Response {
-    match state.profile_store.list(agent_id) {
-        Ok(profiles) => Response::Profiles(profiles),
+/// List profiles, optionally filtered by agent and provider.
+pub async fn list(
+    agent_id: Option<&str>,
+    provider_id: Option<&str>,
+    state: &ServerState,
+) -> Response {
+    match state.profile_store.list(agent_id, provider_id) {
+        Ok(mut profiles) => {
+            profiles.iter_mut().for_each(ProfileInfo::redact_secrets);
+            Response::Profiles(profiles)
+        }
+        Err(e) => Response::error(
+            error_codes::INTERNAL_ERROR,
+            format!("Failed to list profiles: {}", e),
+        ),
+    }
+}
+
+/// List profile aliases.
+pub async fn names(state: &ServerState) -> Response {
+    match state.profile_store.names() {
+        Ok(names) => Response::ProfileNames(names),
         Err(e) => Response::error(
             error_codes::INTERNAL_ERROR,
             format!("Failed to list profiles: {}", e),
@@ -148,10 +228,16 @@ pub async fn list(agent_id: Option<&str>, state: &ServerState) -> Response {
     }
 }
 
-/// Inspect a specific profile.
-pub async fn inspect(alias: &str, state: &ServerState) -> Response {
+/// Inspect a specific profile, masking secrets unless `show_secrets` is set.
+pub async fn inspect(alias: &str, show_secrets: bool, state: &ServerState) -> Response {
     match state.profile_store.get(alias) {
-        Ok(Some(profile)) => Response::Profile(profile.to_info()),
+        Ok(Some(profile)) => {
+            let mut info = profile.to_info();
+            if !show_secrets {
+                info.redact_secrets();
+            }
+            Response::Profile(info)
+        }
         Ok(None) => Response::error(
             error_codes::PROFILE_NOT_FOUND,
             format!("Profile not found: {}", alias),
@@ -164,14 +250,35 @@ pub async fn inspect(alias: &str, state: &ServerState) -> Response {
 }
 
 /// Run a profile (non-blocking for HTTP - returns immediately with PID).
-pub async fn run(alias: &str, args: &[String], state: &ServerState) -> Response {
-    let prepared = match prepare_execution_context(alias, args, state, true, true).await {
+///
+/// With `ephemeral_proxy`, a proxy started for the run is stopped when the
+/// agent exits.
+pub async fn run(
+    alias: &str,
+    args: &[String],
+    env: &HashMap<String, String>,
+    ephemeral_proxy: bool,
+    state: &ServerState,
+) -> Response {
+    let session_id = Uuid::new_v4().to_string();
+    let ephemeral_run = ephemeral_proxy.then_some(session_id.as_str());
+    let mut prepared = match prepare_execution_context(
+        alias,
+        args,
+        state,
+        true,
+        true,
+        ephemeral_run,
+        None,
+    )
+    .await
+    {
         Ok(prepared) => prepared,
         Err(response) => return response,
     };
+    add_missing_env(&mut prepared.context, env);
 
     let profile = prepared.profile;
-    let session_id = Uuid::new_v4().to_string();
     let usage_baseline =
         match agent_usage::snapshot_for_profile(&profile.agent_id, &profile.metadata.home).await {
             Ok(snapshot) => snapshot,
@@ -205,19 +312,40 @@ pub async fn run(alias: &str, args: &[String], state: &ServerState) -> Response
             let profile_home = profile.metadata.home.clone();
             let paths = state.paths.clone();
             let events = state.events.clone();
+            let proxy_manager = state.proxy_manager.clone();
             let mut child = result.child;
+            let context = prepared.context;
+            let run_args = args.to_vec();
 
             tokio::spawn(async move {
                 match tokio::task::spawn_blocking(move || child.wait()).await {
                     Ok(Ok(status)) => {
-                        let exit_code = status.code().unwrap_or(-1);
+                        let (exit_code, signal) = exit_details(&status);
                         let ended_at = chrono::Utc::now();
                         let duration = ended_at.signed_duration_since(started_at);
 
-                        info!(
-                            "Profile '{}' completed with exit code {}",
-                            alias_owned, exit_code
-                        );
+                        match signal {
+                            Some(signal) => {
+                                info!("Profile '{}' terminated by signal {}", alias_owned, signal)
+                            }
+                            None => info!(
+                                "Profile '{}' completed with exit code {}",
+                                alias_owned, exit_code
+                            ),
+                        }
+
+                        match tokio::task::spawn_blocking(move || run_post_run_hook(&context)).await
+                        {
+                            Ok(Ok(())) => {}
+                            Ok(Err(e)) => warn!("Profile '{}': {:#}", alias_owned, e),
+                            Err(e) => warn!("Failed to join post_run hook task: {}", e),
+                        }
+
+                        if proxy_manager.release_run(&alias_owned, &session_id).await {
+                            events.broadcast(Event::ProxyStopped {
+                                alias: alias_owned.clone(),
+                            });
+                        }
 
                         let usage_delta = match usage_baseline.as_ref() {
                             Some(baseline) => match agent_usage::delta_for_profile(
@@ -242,6 +370,18 @@ pub async fn run(alias: &str, args: &[String], state: &ServerState) -> Response
                             None => None,
                         };
 
+                        let entry = RunHistoryEntry {
+                            started_at,
+                            args: run_args,
+                            exit_code,
+                            signal,
+                            duration_secs: duration.num_seconds().max(0) as u64,
+                        };
+                        if let Err(e) = RunHistory::new(paths.clone()).append(&alias_owned, &entry)
+                        {
+                            warn!("Failed to record run history: {}", e);
+                        }
+
                         let telemetry = crate::daemon::telemetry::TelemetryCollector::new(paths);
                         let session = crate::daemon::telemetry::Session {
                             session_id,
@@ -264,13 +404,20 @@ pub async fn run(alias: &str, args: &[String], state: &ServerState) -> Response
                         events.broadcast(Event::ProfileRunCompleted {
                             alias: alias_owned,
                             exit_code,
+                            signal,
                         });
                     }
                     Ok(Err(e)) => {
                         tracing::error!("Failed to wait for process: {}", e);
+                        if proxy_manager.release_run(&alias_owned, &session_id).await {
+                            events.broadcast(Event::ProxyStopped { alias: alias_owned });
+                        }
                     }
                     Err(e) => {
                         tracing::error!("Failed to join profile wait task: {}", e);
+                        if proxy_manager.release_run(&alias_owned, &session_id).await {
+                            events.broadcast(Event::ProxyStopped { alias: alias_owned });
+                        }
                     }
                 }
             });
@@ -278,10 +425,13 @@ pub async fn run(alias: &str, args: &[String], state: &ServerState) -> Response
             // Return immediately with the PID
             Response::RunStarted { pid }
         }
-        Err(e) => Response::error(
-            error_codes::EXECUTION_ERROR,
-            format!("Failed to run profile: {}", e),
-        ),
+        Err(e) => {
+            release_run_proxy(alias, &session_id, state).await;
+            Response::error(
+                error_codes::EXECUTION_ERROR,
+                format!("Failed to run profile: {}", e),
+            )
+        }
     }
 }
 
@@ -291,14 +441,48 @@ pub(crate) struct PreparedProfileExecution {
 }
 
 /// Build a prepared execution context for a profile.
+///
+/// When `ephemeral_run` is set, a proxy started here belongs to that run and
+/// must be released with `ProxyManager::release_run` once it is over. `cwd`
+/// is the run's working directory when the profile doesn't set one.
 pub(crate) async fn prepare_execution_context(
     alias: &str,
     args: &[String],
     state: &ServerState,
     mark_used: bool,
     start_proxy: bool,
+    ephemeral_run: Option<&str>,
+    cwd: Option<&Path>,
 ) -> Result<PreparedProfileExecution, Response> {
-    let profile = match state.profile_store.get(alias) {
+    let prepared = prepare_execution_context_inner(
+        alias,
+        args,
+        state,
+        mark_used,
+        start_proxy,
+        ephemeral_run,
+        cwd,
+    )
+    .await;
+    // Don't leave a proxy behind for a run that never started
+    if prepared.is_err()
+        && let Some(run_id) = ephemeral_run
+    {
+        state.proxy_manager.release_run(alias, run_id).await;
+    }
+    prepared
+}
+
+async fn prepare_execution_context_inner(
+    alias: &str,
+    args: &[String],
+    state: &ServerState,
+    mark_used: bool,
+    start_proxy: bool,
+    ephemeral_run: Option<&str>,
+    cwd: Option<&Path>,
+) -> Result<PreparedProfileExecution, Response> {
+    let mut profile = match state.profile_store.get(alias) {
         Ok(Some(p)) => p,
         Ok(None) => {
             return Err(Response::error(
@@ -316,6 +500,10 @@ pub(crate) async fn prepare_execution_context(
 
     info!("Preparing profile: {} (agent: {})", alias, profile.agent_id);
 
+    if mark_used && let Some(refusal) = quota_refusal(&profile, state) {
+        return Err(refusal);
+    }
+
     let agent_registry = state.agent_registry.lock().await;
     let agent = match agent_registry.get(&profile.agent_id) {
         Some(a) => a.clone(),
@@ -328,7 +516,12 @@ pub(crate) async fn prepare_execution_context(
     };
     drop(agent_registry);
 
-    let provider = match state.provider_registry.get(&profile.provider_id) {
+    let provider = match state
+        .provider_registry
+        .lock()
+        .await
+        .get(&profile.provider_id)
+    {
         Some(p) => p.clone(),
         None => {
             return Err(Response::error(
@@ -338,7 +531,7 @@ pub(crate) async fn prepare_execution_context(
         }
     };
 
-    let api_key = if provider.auth.required {
+    let api_key = if provider.auth.stores_credential() {
         match state.secret_store.get_api_key(alias) {
             Ok(key) => key,
             Err(e) => {
@@ -351,19 +544,28 @@ pub(crate) async fn prepare_execution_context(
     } else {
         String::new()
     };
+    let api_key = refresh_expiring_token(&mut profile, &provider, api_key, state).await?;
 
-    let proxy_url = if start_proxy {
-        if let Some(ref proxy_config) = profile.metadata.proxy_config {
-            if proxy_config.enabled {
-                match state
-                    .proxy_manager
-                    .start(alias, &profile.metadata.home, proxy_config)
-                    .await
-                {
-                    Ok(port) => {
-                        info!("Proxy started for '{}' on port {}", alias, port);
-                        Some(format!("http://127.0.0.1:{}", port))
+    // Route the agent through the profile's proxy, starting it when running the agent
+    let proxy_url = match profile.metadata.proxy_config {
+        Some(ref proxy_config) if proxy_config.enabled => {
+            if start_proxy {
+                let started = match ephemeral_run {
+                    Some(run_id) => {
+                        state
+                            .proxy_manager
+                            .start_for_run(alias, run_id, &profile.metadata.home, proxy_config)
+                            .await
+                    }
+                    None => {
+                        state
+                            .proxy_manager
+                            .start(alias, &profile.metadata.home, proxy_config)
+                            .await
                     }
+                };
+                match started {
+                    Ok(port) => info!("Proxy started for '{}' on port {}", alias, port),
                     Err(e) => {
                         return Err(Response::error(
                             error_codes::EXECUTION_ERROR,
@@ -371,25 +573,47 @@ pub(crate) async fn prepare_execution_context(
                         ));
                     }
                 }
-            } else {
-                None
             }
-        } else {
-            None
+
+            let proxy_url = state.proxy_manager.proxy_url(alias).await;
+            if start_proxy && proxy_url.is_none() {
+                return Err(Response::error(
+                    error_codes::PROXY_START_FAILED,
+                    format!(
+                        "Proxy for '{}' did not become ready. Check 'ringlet proxy logs {}'.",
+                        alias, alias
+                    ),
+                ));
+            }
+            proxy_url
         }
-    } else {
-        None
+        _ => None,
     };
 
+    // Only the run sees the caller's directory; the stored profile keeps none
+    let mut run_profile = profile.clone();
+    if run_profile.working_dir.is_none() {
+        run_profile.working_dir = cwd.map(Path::to_path_buf);
+    }
+
     match state.execution_adapter.prepare(
-        &profile,
+        &run_profile,
         &agent,
         &provider,
         &api_key,
         args,
         proxy_url.as_deref(),
     ) {
-        Ok(context) => {
+        Ok(mut context) => {
+            // Not every agent script consumes `proxy_url`, so point the agent's base URL at it here
+            if let Some(ref url) = proxy_url {
+                for var in
+                    super::proxy::base_url_vars(&profile.agent_id, Some(provider.provider_type))
+                {
+                    context.env.insert(var.to_string(), url.clone());
+                }
+            }
+
             if mark_used && let Err(e) = state.profile_store.mark_used(alias) {
                 tracing::warn!("Failed to mark profile as used: {}", e);
             }
@@ -403,11 +627,31 @@ pub(crate) async fn prepare_execution_context(
     }
 }
 
+/// Add caller-supplied variables that the profile's scripts didn't set.
+fn add_missing_env(context: &mut ExecutionContext, env: &HashMap<String, String>) {
+    for (key, value) in env {
+        context
+            .env
+            .entry(key.clone())
+            .or_insert_with(|| value.clone());
+    }
+}
+
 /// Prepare execution context for CLI-side spawning.
-pub async fn prepare(alias: &str, args: &[String], state: &ServerState) -> Response {
-    match prepare_execution_context(alias, args, state, true, true).await {
-        Ok(prepared) => {
-            let run_id = Uuid::new_v4().to_string();
+pub async fn prepare(
+    alias: &str,
+    args: &[String],
+    env: &HashMap<String, String>,
+    ephemeral_proxy: bool,
+    cwd: Option<&Path>,
+    owner_pid: Option<u32>,
+    state: &ServerState,
+) -> Response {
+    let run_id = Uuid::new_v4().to_string();
+    let ephemeral_run = ephemeral_proxy.then_some(run_id.as_str());
+    match prepare_execution_context(alias, args, state, true, true, ephemeral_run, cwd).await {
+        Ok(mut prepared) => {
+            add_missing_env(&mut prepared.context, env);
             let usage_baseline = match agent_usage::snapshot_for_profile(
                 &prepared.profile.agent_id,
                 &prepared.profile.metadata.home,
@@ -434,6 +678,9 @@ pub async fn prepare(alias: &str, args: &[String], state: &ServerState) -> Respo
                     model: prepared.profile.model.clone(),
                     profile_home: prepared.profile.metadata.home.clone(),
                     usage_baseline,
+                    args: args.to_vec(),
+                    prepared_at: std::time::Instant::now(),
+                    owner_pid,
                 },
             );
 
@@ -445,12 +692,83 @@ pub async fn prepare(alias: &str, args: &[String], state: &ServerState) -> Respo
     }
 }
 
+/// Drop a prepared run that never started, stopping its ephemeral proxy.
+pub async fn abandon(run_id: &str, state: &ServerState) -> Response {
+    let pending = state.pending_prepared_runs.lock().await.remove(run_id);
+    let Some(pending) = pending else {
+        return Response::error(
+            error_codes::PROFILE_NOT_FOUND,
+            format!("Prepared run not found: {}", run_id),
+        );
+    };
+
+    info!("Abandoned prepared run {} of '{}'", run_id, pending.profile);
+    release_run_proxy(&pending.profile, run_id, state).await;
+    Response::success(format!("Abandoned run {}", run_id))
+}
+
+/// How long a prepared run without a known owner may stay pending.
+const PENDING_RUN_TTL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+/// Abandon prepared runs whose owner died without completing them, or that
+/// have been pending longer than [`PENDING_RUN_TTL`] with no owner to check.
+pub async fn expire_pending_runs(state: &ServerState) {
+    let expired: Vec<(String, String)> = {
+        let mut pending = state.pending_prepared_runs.lock().await;
+        let expired: Vec<String> = pending
+            .iter()
+            .filter(|(_, run)| match run.owner_pid {
+                Some(pid) => !process_alive(pid),
+                None => run.prepared_at.elapsed() > PENDING_RUN_TTL,
+            })
+            .map(|(run_id, _)| run_id.clone())
+            .collect();
+        expired
+            .into_iter()
+            .filter_map(|run_id| {
+                let run = pending.remove(&run_id)?;
+                Some((run_id, run.profile))
+            })
+            .collect()
+    };
+
+    for (run_id, alias) in expired {
+        warn!(
+            "Expiring prepared run {} of '{}': it never completed",
+            run_id, alias
+        );
+        release_run_proxy(&alias, &run_id, state).await;
+    }
+}
+
+/// Release a run's hold on an ephemeral proxy, announcing it if that stopped the proxy.
+pub(crate) async fn release_run_proxy(alias: &str, run_id: &str, state: &ServerState) {
+    if state.proxy_manager.release_run(alias, run_id).await {
+        state.broadcast(Event::ProxyStopped {
+            alias: alias.to_string(),
+        });
+    }
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    // Signal 0 only checks that the process exists; EPERM means it does
+    let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    true
+}
+
 /// Complete a CLI-attached prepared run and record telemetry in the daemon.
 pub async fn complete(
     run_id: &str,
     started_at: chrono::DateTime<chrono::Utc>,
     ended_at: chrono::DateTime<chrono::Utc>,
     exit_code: i32,
+    signal: Option<i32>,
     state: &ServerState,
 ) -> Response {
     let pending = state.pending_prepared_runs.lock().await.remove(run_id);
@@ -461,6 +779,8 @@ pub async fn complete(
         );
     };
 
+    release_run_proxy(&pending.profile, run_id, state).await;
+
     let usage_delta = match pending.usage_baseline.as_ref() {
         Some(baseline) => match agent_usage::delta_for_profile(
             &pending.agent_id,
@@ -484,6 +804,22 @@ pub async fn complete(
         None => None,
     };
 
+    let duration_secs = ended_at
+        .signed_duration_since(started_at)
+        .num_seconds()
+        .max(0) as u64;
+
+    let entry = RunHistoryEntry {
+        started_at,
+        args: pending.args,
+        exit_code,
+        signal,
+        duration_secs,
+    };
+    if let Err(e) = RunHistory::new(state.paths.clone()).append(&pending.profile, &entry) {
+        warn!("Failed to record run history: {}", e);
+    }
+
     let telemetry = crate::daemon::telemetry::TelemetryCollector::new(state.paths.clone());
     let session = crate::daemon::telemetry::Session {
         session_id: pending.session_id,
@@ -492,12 +828,7 @@ pub async fn complete(
         provider_id: pending.provider_id,
         started_at,
         ended_at: Some(ended_at),
-        duration_secs: Some(
-            ended_at
-                .signed_duration_since(started_at)
-                .num_seconds()
-                .max(0) as u64,
-        ),
+        duration_secs: Some(duration_secs),
         exit_code: Some(exit_code),
         source: crate::daemon::telemetry::SessionSource::ProfileRun,
         model: Some(pending.model),
@@ -506,7 +837,11 @@ pub async fn complete(
     };
 
     match telemetry.record_session(&session) {
-        Ok(()) => Response::RunCompleted { exit_code },
+        Ok(()) => Response::RunCompleted {
+            exit_code,
+            signal,
+            duration_secs,
+        },
         Err(e) => Response::error(
             error_codes::INTERNAL_ERROR,
             format!("Failed to record run telemetry: {}", e),
@@ -514,7 +849,7 @@ pub async fn complete(
     }
 }
 
-/// Delete a profile.
+/// Delete a profile, stopping its proxy and removing its alias shim.
 pub async fn delete(alias: &str, state: &ServerState) -> Response {
     // First, get the profile to check for alias_path
     let alias_path = match state.profile_store.get(alias) {
@@ -522,13 +857,28 @@ pub async fn delete(alias: &str, state: &ServerState) -> Response {
         _ => None,
     };
 
+    // Stop the proxy first; it is tracked by alias and writes into the profile home
+    let mut cleaned_up = Vec::new();
+    if state.proxy_manager.status_for(alias).await.is_some() {
+        if let Err(e) = state.proxy_manager.stop(alias).await {
+            return Response::error(
+                error_codes::INTERNAL_ERROR,
+                format!("Failed to stop proxy for '{}': {}", alias, e),
+            );
+        }
+        state.broadcast(Event::ProxyStopped {
+            alias: alias.to_string(),
+        });
+        cleaned_up.push("stopped its proxy".to_string());
+    }
+
     match state.profile_manager.delete(alias) {
         Ok(()) => {
-            // Try to remove alias if it was installed
-            if alias_path.is_some()
-                && let Some(removed) = super::aliases::uninstall_alias_sync(alias)
+            if let Some(removed) =
+                super::aliases::remove_profile_shim_sync(alias, alias_path.as_deref())
             {
                 info!("Removed alias at {:?}", removed);
+                cleaned_up.push(format!("removed alias {}", removed.display()));
             }
 
             // Broadcast event
@@ -536,7 +886,15 @@ pub async fn delete(alias: &str, state: &ServerState) -> Response {
                 alias: alias.to_string(),
             });
 
-            Response::success(format!("Profile '{}' deleted", alias))
+            if cleaned_up.is_empty() {
+                Response::success(format!("Profile '{}' deleted", alias))
+            } else {
+                Response::success(format!(
+                    "Profile '{}' deleted ({})",
+                    alias,
+                    cleaned_up.join(", ")
+                ))
+            }
         }
         Err(e) => {
             // Check if it's a "not found" error
@@ -550,37 +908,1102 @@ pub async fn delete(alias: &str, state: &ServerState) -> Response {
     }
 }
 
-/// Sensitive environment variable keys that should never be exposed via HTTP.
-const SENSITIVE_ENV_KEYS: &[&str] = &[
-    "ANTHROPIC_AUTH_TOKEN",
-    "ANTHROPIC_API_KEY",
-    "OPENAI_API_KEY",
-    "CLAUDE_API_KEY",
-    "API_KEY",
-    "SECRET",
-    "TOKEN",
-    "PASSWORD",
-    "CREDENTIAL",
-];
+/// Rename a profile, carrying its home, secrets, history and shim along.
+pub async fn rename(old_alias: &str, new_alias: &str, state: &ServerState) -> Response {
+    match state.profile_store.get(old_alias) {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Response::error(
+                error_codes::PROFILE_NOT_FOUND,
+                format!("Profile not found: {}", old_alias),
+            );
+        }
+        Err(e) => return Response::error(error_codes::INTERNAL_ERROR, e.to_string()),
+    }
+    match state.profile_store.get(new_alias) {
+        Ok(None) => {}
+        Ok(Some(_)) => {
+            return Response::error(
+                error_codes::PROFILE_EXISTS,
+                format!("Profile already exists: {}", new_alias),
+            );
+        }
+        Err(e) => return Response::error(error_codes::INTERNAL_ERROR, e.to_string()),
+    }
+
+    // The proxy is tracked by alias and keeps its files in the profile home
+    if state.proxy_manager.status_for(old_alias).await.is_some() {
+        if let Err(e) = state.proxy_manager.stop(old_alias).await {
+            return Response::error(
+                error_codes::INTERNAL_ERROR,
+                format!("Failed to stop proxy for '{}': {}", old_alias, e),
+            );
+        }
+        state.broadcast(Event::ProxyStopped {
+            alias: old_alias.to_string(),
+        });
+    }
+
+    let mut profile = match state.profile_manager.rename(old_alias, new_alias) {
+        Ok(profile) => profile,
+        Err(e) => return Response::error(error_codes::INTERNAL_ERROR, e.to_string()),
+    };
+
+    if let Err(e) = RunHistory::new(state.paths.clone()).rename(old_alias, new_alias) {
+        warn!("Failed to move run history for '{}': {}", new_alias, e);
+    }
+
+    if let Some(result) = super::aliases::rename_alias_sync(old_alias, new_alias) {
+        match result {
+            Ok(path) => {
+                info!("Moved alias shim to {:?}", path);
+                if profile.metadata.alias_path.is_some() {
+                    profile.metadata.alias_path = Some(path);
+                }
+            }
+            Err(e) => {
+                warn!("Failed to reinstall alias '{}': {}", new_alias, e);
+                profile.metadata.alias_path = None;
+            }
+        }
+        if let Err(e) = state.profile_store.update(&profile) {
+            warn!("Failed to record alias path for '{}': {}", new_alias, e);
+        }
+    }
+
+    state.broadcast(Event::ProfileDeleted {
+        alias: old_alias.to_string(),
+    });
+    state.broadcast(Event::ProfileCreated {
+        alias: new_alias.to_string(),
+    });
+
+    Response::success(format!(
+        "Profile '{}' renamed to '{}'",
+        old_alias, new_alias
+    ))
+}
+
+/// Show a profile's most recent runs.
+pub async fn history(alias: &str, limit: usize, state: &ServerState) -> Response {
+    match state.profile_store.get(alias) {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Response::error(
+                error_codes::PROFILE_NOT_FOUND,
+                format!("Profile not found: {}", alias),
+            );
+        }
+        Err(e) => return Response::error(error_codes::INTERNAL_ERROR, e.to_string()),
+    }
+
+    match RunHistory::new(state.paths.clone()).recent(alias, limit) {
+        Ok(entries) => Response::RunHistory(entries),
+        Err(e) => Response::error(
+            error_codes::INTERNAL_ERROR,
+            format!("Failed to read run history: {}", e),
+        ),
+    }
+}
+
+/// Delete all profiles matching the agent and/or provider filters.
+pub async fn delete_matching(
+    agent_id: Option<&str>,
+    provider_id: Option<&str>,
+    state: &ServerState,
+) -> Response {
+    if agent_id.is_none() && provider_id.is_none() {
+        return Response::error(
+            error_codes::INVALID_PARAMS,
+            "Refusing to delete all profiles: specify an agent or provider filter",
+        );
+    }
+
+    let matched = match state.profile_store.list(agent_id, provider_id) {
+        Ok(profiles) => profiles,
+        Err(e) => {
+            return Response::error(
+                error_codes::INTERNAL_ERROR,
+                format!("Failed to list profiles: {}", e),
+            );
+        }
+    };
+
+    let mut deleted = 0;
+    let mut failures = Vec::new();
+    for profile in &matched {
+        match delete(&profile.alias, state).await {
+            Response::Error { message, .. } => {
+                failures.push(format!("{}: {}", profile.alias, message))
+            }
+            _ => deleted += 1,
+        }
+    }
+
+    let summary = format!(
+        "Deleted {} profile{}",
+        deleted,
+        if deleted == 1 { "" } else { "s" }
+    );
+    if failures.is_empty() {
+        Response::success(summary)
+    } else {
+        Response::error(
+            error_codes::INTERNAL_ERROR,
+            format!("{}; failed to delete {}", summary, failures.join(", ")),
+        )
+    }
+}
+
+/// Set or clear a profile's `pre-run`/`post-run` command.
+pub async fn set_hook(
+    alias: &str,
+    stage: &str,
+    command: Option<&str>,
+    state: &ServerState,
+) -> Response {
+    let mut profile = match state.profile_store.get(alias) {
+        Ok(Some(p)) => p,
+        Ok(None) => {
+            return Response::error(
+                error_codes::PROFILE_NOT_FOUND,
+                format!("Profile not found: {}", alias),
+            );
+        }
+        Err(e) => return Response::error(error_codes::INTERNAL_ERROR, e.to_string()),
+    };
+
+    let command = command.map(str::trim).filter(|c| !c.is_empty());
+    let slot = match stage {
+        "pre-run" => &mut profile.metadata.pre_run,
+        "post-run" => &mut profile.metadata.post_run,
+        _ => {
+            return Response::error(
+                error_codes::INVALID_RUN_HOOK,
+                format!(
+                    "Invalid hook stage '{}'. Valid stages: pre-run, post-run",
+                    stage
+                ),
+            );
+        }
+    };
+    *slot = command.map(str::to_string);
+
+    if let Err(e) = state.profile_store.update(&profile) {
+        return Response::error(error_codes::INTERNAL_ERROR, e.to_string());
+    }
+
+    match command {
+        Some(command) => {
+            info!("Set {} hook for profile '{}': {}", stage, alias, command);
+            Response::success(format!("Set {} hook for profile '{}'", stage, alias))
+        }
+        None => {
+            info!("Cleared {} hook for profile '{}'", stage, alias);
+            Response::success(format!("Cleared {} hook for profile '{}'", stage, alias))
+        }
+    }
+}
+
+/// Replace a profile's default agent arguments.
+pub async fn set_args(alias: &str, args: &[String], state: &ServerState) -> Response {
+    let mut profile = match state.profile_store.get(alias) {
+        Ok(Some(p)) => p,
+        Ok(None) => {
+            return Response::error(
+                error_codes::PROFILE_NOT_FOUND,
+                format!("Profile not found: {}", alias),
+            );
+        }
+        Err(e) => return Response::error(error_codes::INTERNAL_ERROR, e.to_string()),
+    };
+
+    profile.args = args.to_vec();
+    if let Err(e) = state.profile_store.update(&profile) {
+        return Response::error(error_codes::INTERNAL_ERROR, e.to_string());
+    }
+
+    if args.is_empty() {
+        info!("Cleared default args for profile '{}'", alias);
+        Response::success(format!("Cleared default args for profile '{}'", alias))
+    } else {
+        info!("Set default args for profile '{}': {:?}", alias, args);
+        Response::success(format!(
+            "Set default args for profile '{}': {}",
+            alias,
+            args.join(" ")
+        ))
+    }
+}
+
+/// Set or clear a profile's spend quotas.
+pub async fn set_quota(
+    alias: &str,
+    daily_usd: Option<f64>,
+    monthly_usd: Option<f64>,
+    state: &ServerState,
+) -> Response {
+    if let Some(limit) = [daily_usd, monthly_usd]
+        .into_iter()
+        .flatten()
+        .find(|limit| !limit.is_finite() || *limit <= 0.0)
+    {
+        return Response::error(
+            error_codes::INVALID_QUOTA,
+            format!("Quota must be a positive amount in USD, got {}", limit),
+        );
+    }
+
+    let mut profile = match state.profile_store.get(alias) {
+        Ok(Some(p)) => p,
+        Ok(None) => {
+            return Response::error(
+                error_codes::PROFILE_NOT_FOUND,
+                format!("Profile not found: {}", alias),
+            );
+        }
+        Err(e) => return Response::error(error_codes::INTERNAL_ERROR, e.to_string()),
+    };
+
+    profile.metadata.quota_daily_usd = daily_usd;
+    profile.metadata.quota_monthly_usd = monthly_usd;
+    if let Err(e) = state.profile_store.update(&profile) {
+        return Response::error(error_codes::INTERNAL_ERROR, e.to_string());
+    }
+
+    let quotas = profile.metadata.quotas();
+    if quotas.is_empty() {
+        info!("Cleared quotas for profile '{}'", alias);
+        return Response::success(format!("Cleared quotas for profile '{}'", alias));
+    }
+    let summary = quotas
+        .iter()
+        .map(|(period, limit)| format!("${:.2} {}", limit, period))
+        .collect::<Vec<_>>()
+        .join(", ");
+    info!("Set quotas for profile '{}': {}", alias, summary);
+    Response::success(format!("Set quotas for profile '{}': {}", alias, summary))
+}
+
+/// Set or clear the command template a profile's agent is launched through.
+pub async fn set_launch_wrapper(
+    alias: &str,
+    wrapper: Option<&[String]>,
+    state: &ServerState,
+) -> Response {
+    if let Some(Err(e)) = wrapper.map(validate_launch_wrapper) {
+        return Response::error(error_codes::INVALID_LAUNCH_WRAPPER, e.to_string());
+    }
+
+    let mut profile = match state.profile_store.get(alias) {
+        Ok(Some(p)) => p,
+        Ok(None) => {
+            return Response::error(
+                error_codes::PROFILE_NOT_FOUND,
+                format!("Profile not found: {}", alias),
+            );
+        }
+        Err(e) => return Response::error(error_codes::INTERNAL_ERROR, e.to_string()),
+    };
+
+    profile.metadata.launch_wrapper = wrapper.map(<[String]>::to_vec);
+    if let Err(e) = state.profile_store.update(&profile) {
+        return Response::error(error_codes::INTERNAL_ERROR, e.to_string());
+    }
+
+    match wrapper {
+        Some(wrapper) => {
+            info!(
+                "Set launch wrapper for profile '{}': {}",
+                alias,
+                wrapper.join(" ")
+            );
+            Response::success(format!("Set launch wrapper for profile '{}'", alias))
+        }
+        None => {
+            info!("Cleared launch wrapper for profile '{}'", alias);
+            Response::success(format!("Cleared launch wrapper for profile '{}'", alias))
+        }
+    }
+}
+
+/// Turn saving every run's output to a log file on or off.
+pub async fn set_capture_logs(alias: &str, enabled: bool, state: &ServerState) -> Response {
+    let mut profile = match state.profile_store.get(alias) {
+        Ok(Some(p)) => p,
+        Ok(None) => {
+            return Response::error(
+                error_codes::PROFILE_NOT_FOUND,
+                format!("Profile not found: {}", alias),
+            );
+        }
+        Err(e) => return Response::error(error_codes::INTERNAL_ERROR, e.to_string()),
+    };
+
+    profile.metadata.capture_logs = enabled;
+    if let Err(e) = state.profile_store.update(&profile) {
+        return Response::error(error_codes::INTERNAL_ERROR, e.to_string());
+    }
+
+    if enabled {
+        let dir = ringlet_core::run_log_dir(&profile.metadata.home);
+        info!("Enabled log capture for profile '{}'", alias);
+        Response::success(format!(
+            "Saving output of runs of '{}' to {}",
+            alias,
+            dir.display()
+        ))
+    } else {
+        info!("Disabled log capture for profile '{}'", alias);
+        Response::success(format!("Stopped saving output of runs of '{}'", alias))
+    }
+}
+
+/// The error refusing to launch a profile whose tracked spend has reached one of its quotas.
+///
+/// Spend that can't be computed is logged and the run goes ahead.
+fn quota_refusal(profile: &Profile, state: &ServerState) -> Option<Response> {
+    let quotas = profile.metadata.quotas();
+    let statuses = match super::usage::quota_statuses(&profile.alias, &quotas, state) {
+        Ok(statuses) => statuses,
+        Err(e) => {
+            warn!(
+                "Failed to check quotas for profile '{}': {}",
+                profile.alias, e
+            );
+            return None;
+        }
+    };
+
+    let status = statuses.into_iter().find(|status| status.exceeded)?;
+    Some(Response::error(
+        error_codes::QUOTA_EXCEEDED,
+        format!(
+            "Profile '{}' has reached its {} quota: ${:.2} of ${:.2} spent. \
+             Raise or clear it with `ringlet profiles set-quota {}`",
+            profile.alias, status.period, status.spent_usd, status.limit_usd, profile.alias
+        ),
+    ))
+}
 
-/// Check if an environment variable key is sensitive.
-fn is_sensitive_key(key: &str) -> bool {
-    let key_upper = key.to_uppercase();
-    SENSITIVE_ENV_KEYS
+/// Refresh a profile's access token when it expires within
+/// [`oauth::REFRESH_MARGIN`], returning the token to launch with.
+///
+/// A token that can't be refreshed is still used until it has expired.
+/// Refreshes of one profile run one at a time; a run that waited for another
+/// to refresh the token uses the token it stored.
+async fn refresh_expiring_token(
+    profile: &mut Profile,
+    provider: &ProviderManifest,
+    api_key: String,
+    state: &ServerState,
+) -> Result<String, Response> {
+    let Some(expires_at) = profile.metadata.token_expires_at else {
+        return Ok(api_key);
+    };
+    if !oauth::needs_refresh(expires_at, Utc::now()) {
+        return Ok(api_key);
+    }
+
+    let alias = profile.alias.clone();
+    let lock = state.token_refresh_lock(&alias);
+    let _guard = lock.lock().await;
+
+    // The stored expiry moves on if another run refreshed the token meanwhile
+    let now = Utc::now();
+    let expires_at = match state.profile_store.get(&alias) {
+        Ok(Some(stored)) => stored.metadata.token_expires_at.unwrap_or(expires_at),
+        _ => expires_at,
+    };
+    if !oauth::needs_refresh(expires_at, now) {
+        profile.metadata.token_expires_at = Some(expires_at);
+        return state.secret_store.get_api_key(&alias).map_err(|e| {
+            Response::error(
+                error_codes::INTERNAL_ERROR,
+                format!("Failed to retrieve API key: {}", e),
+            )
+        });
+    }
+
+    let refresh_token = state.secret_store.get_refresh_token(&alias);
+    let refreshed = match (provider.auth.device.clone(), refresh_token) {
+        (Some(config), Some(refresh_token)) => {
+            tokio::task::spawn_blocking(move || oauth::refresh_token(&config, &refresh_token))
+                .await
+                .unwrap_or_else(|e| Err(anyhow::anyhow!("refresh task failed: {}", e)))
+        }
+        _ => Err(anyhow::anyhow!("no refresh token is stored")),
+    };
+
+    let grant = match refreshed {
+        Ok(grant) => grant,
+        Err(e) if expires_at > now => {
+            warn!(
+                "Failed to refresh access token for '{}' ({:#}); using it until it expires",
+                alias, e
+            );
+            return Ok(api_key);
+        }
+        Err(e) => {
+            return Err(Response::error(
+                error_codes::TOKEN_EXPIRED,
+                format!(
+                    "Access token for '{}' expired and could not be refreshed ({:#}). Recreate the profile to sign in again.",
+                    alias, e
+                ),
+            ));
+        }
+    };
+
+    if let Err(e) = state
+        .secret_store
+        .store_api_key(&alias, &grant.access_token)
+    {
+        return Err(Response::error(
+            error_codes::INTERNAL_ERROR,
+            format!("Failed to store refreshed token: {}", e),
+        ));
+    }
+    if let Some(refresh_token) = &grant.refresh_token
+        && let Err(e) = state
+            .secret_store
+            .store_refresh_token(&alias, refresh_token)
+    {
+        warn!("Failed to store new refresh token for '{}': {}", alias, e);
+    }
+    profile.metadata.token_expires_at = grant.expires_at(now);
+    if let Err(e) = state.profile_store.update(profile) {
+        warn!("Failed to record token expiry for '{}': {}", alias, e);
+    }
+
+    info!("Refreshed access token for '{}'", alias);
+    Ok(grant.access_token)
+}
+
+/// Find leftovers of deleted profiles: homes no profile points at, alias
+/// shims for missing profiles and proxies still running for them. With
+/// `fix`, remove the shims and proxies, and the homes too if `remove_homes`.
+pub async fn doctor(fix: bool, remove_homes: bool, state: &ServerState) -> Response {
+    // Aliases come from the file names so an unreadable profile still
+    // protects its home and shim
+    let names = match state.profile_store.names() {
+        Ok(names) => names,
+        Err(e) => {
+            return Response::error(
+                error_codes::INTERNAL_ERROR,
+                format!("Failed to list profiles: {}", e),
+            );
+        }
+    };
+    let profiles: Vec<Profile> = names
+        .iter()
+        .filter_map(|alias| state.profile_store.get(alias).ok().flatten())
+        .collect();
+    let aliases: HashSet<&str> = names.iter().map(String::as_str).collect();
+    let homes: HashSet<&Path> = profiles.iter().map(|p| p.metadata.home.as_path()).collect();
+
+    let mut report = ProfileDoctorReport {
+        fixed: fix,
+        ..Default::default()
+    };
+
+    let roots: Vec<PathBuf> = {
+        let agent_registry = state.agent_registry.lock().await;
+        agent_registry
+            .ids()
+            .filter_map(|id| {
+                let agent = agent_registry.get(id)?;
+                profile_home_root(&agent.profile.source_home, id)
+            })
+            .collect()
+    };
+    for root in roots {
+        let Ok(entries) = std::fs::read_dir(&root) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let named_after_profile = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| aliases.contains(n));
+            if path.is_dir() && !named_after_profile && !homes.contains(path.as_path()) {
+                report.orphaned_homes.push(path);
+            }
+        }
+    }
+    report.orphaned_homes.sort();
+
+    let shim_dirs: Vec<PathBuf> = profiles
         .iter()
-        .any(|&sensitive| key_upper.contains(sensitive))
+        .filter_map(|p| {
+            p.metadata
+                .alias_path
+                .as_ref()?
+                .parent()
+                .map(Path::to_path_buf)
+        })
+        .collect();
+    report.dangling_shims = super::aliases::find_shims_sync(&shim_dirs)
+        .into_iter()
+        .filter(|(alias, _)| !aliases.contains(alias.as_str()))
+        .map(|(_, path)| path)
+        .collect();
+
+    report.orphaned_proxies = state
+        .proxy_manager
+        .status()
+        .await
+        .into_iter()
+        .map(|proxy| proxy.alias)
+        .filter(|alias| !aliases.contains(alias.as_str()))
+        .collect();
+
+    if fix {
+        for proxy in &report.orphaned_proxies {
+            match state.proxy_manager.stop(proxy).await {
+                Ok(()) => state.broadcast(Event::ProxyStopped {
+                    alias: proxy.clone(),
+                }),
+                Err(e) => report.failures.push(format!("proxy {}: {}", proxy, e)),
+            }
+        }
+        remove_leftover_files(&mut report, remove_homes);
+        info!(
+            "Cleaned up {} dangling shims and {} orphaned proxies{}",
+            report.dangling_shims.len(),
+            report.orphaned_proxies.len(),
+            if report.homes_removed {
+                format!(
+                    ", and deleted {} orphaned homes",
+                    report.orphaned_homes.len()
+                )
+            } else {
+                String::new()
+            }
+        );
+    }
+
+    Response::ProfileDoctor(report)
+}
+
+/// Delete the dangling shims in `report`, and its orphaned homes if `remove_homes`.
+fn remove_leftover_files(report: &mut ProfileDoctorReport, remove_homes: bool) {
+    for shim in &report.dangling_shims {
+        if let Err(e) = std::fs::remove_file(shim) {
+            report.failures.push(format!("{}: {}", shim.display(), e));
+        }
+    }
+    if !remove_homes {
+        return;
+    }
+    for home in &report.orphaned_homes {
+        if let Err(e) = std::fs::remove_dir_all(home) {
+            report.failures.push(format!("{}: {}", home.display(), e));
+        }
+    }
+    report.homes_removed = true;
+}
+
+/// The directory holding all of an agent's profile homes, for home templates
+/// of the form `<root>/{alias}`.
+///
+/// Only roots named like `*profiles` count, so a custom manifest placing homes
+/// in a shared directory (say `~/.config/{alias}`) never has it swept.
+fn profile_home_root(template: &str, agent_id: &str) -> Option<PathBuf> {
+    let root = template.strip_suffix("/{alias}")?;
+    if root.contains("{alias}") {
+        return None;
+    }
+    let root = expand_template(root, "", agent_id);
+    root.file_name()?
+        .to_str()?
+        .ends_with("profiles")
+        .then_some(root)
 }
 
 /// Get environment variables for shell export.
 /// NOTE: Sensitive keys (API keys, tokens) are filtered out for security.
 pub async fn env(alias: &str, state: &ServerState) -> Response {
-    match prepare_execution_context(alias, &[], state, false, false).await {
+    match prepare_execution_context(alias, &[], state, false, false, None, None).await {
         Ok(prepared) => {
             let mut env = prepared.context.env;
             // Filter out sensitive environment variables to prevent credential leakage
-            env.retain(|key, _| !is_sensitive_key(key));
+            env.retain(|key, _| !is_secret_key(key));
             Response::Env(env)
         }
         Err(response) => response,
     }
 }
+
+/// Store a new API key for a profile.
+pub async fn set_api_key(alias: &str, api_key: &str, state: &ServerState) -> Response {
+    match state.profile_store.get(alias) {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Response::error(
+                error_codes::PROFILE_NOT_FOUND,
+                format!("Profile not found: {}", alias),
+            );
+        }
+        Err(e) => return Response::error(error_codes::INTERNAL_ERROR, e.to_string()),
+    }
+
+    match state.profile_manager.set_api_key(alias, api_key) {
+        Ok(_) => Response::success(format!("Stored new API key for profile '{}'", alias)),
+        Err(e) => Response::error(
+            error_codes::INTERNAL_ERROR,
+            format!("Failed to store API key: {}", e),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::profile_store::test_profile;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn leftovers(dir: &Path) -> ProfileDoctorReport {
+        let home = dir.join("claude-profiles").join("old");
+        std::fs::create_dir_all(home.join(".claude")).unwrap();
+        let shim = dir.join("bin").join("old");
+        std::fs::create_dir_all(shim.parent().unwrap()).unwrap();
+        std::fs::write(&shim, "#!/bin/sh\n").unwrap();
+
+        ProfileDoctorReport {
+            orphaned_homes: vec![home],
+            dangling_shims: vec![shim],
+            fixed: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_doctor_fix_keeps_homes_unless_confirmed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut report = leftovers(dir.path());
+
+        remove_leftover_files(&mut report, false);
+        assert!(report.failures.is_empty());
+        assert!(!report.dangling_shims[0].exists());
+        assert!(report.orphaned_homes[0].exists());
+        assert!(!report.homes_removed);
+    }
+
+    #[test]
+    fn test_doctor_fix_removes_confirmed_homes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut report = leftovers(dir.path());
+
+        remove_leftover_files(&mut report, true);
+        assert!(report.failures.is_empty());
+        assert!(!report.dangling_shims[0].exists());
+        assert!(!report.orphaned_homes[0].exists());
+        assert!(report.homes_removed);
+    }
+
+    /// State with a plain `work` profile whose home is inside `dir`.
+    fn profile_state(dir: &Path) -> ServerState {
+        let state = ServerState::for_tests(dir);
+        let mut profile = test_profile("work", &dir.join("home"));
+        profile.working_dir = Some(dir.to_path_buf());
+        state.profile_store.save_new(&profile).unwrap();
+        state.secret_store.store_api_key("work", "sk-test").unwrap();
+        state
+    }
+
+    #[test]
+    fn test_quota_refuses_runs_at_and_over_the_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = profile_state(dir.path());
+        let mut profile = state.profile_store.get("work").unwrap().unwrap();
+        profile.metadata.quota_daily_usd = Some(5.0);
+
+        let spend = |usd: f64| {
+            let now = Utc::now();
+            let session = crate::daemon::telemetry::Session {
+                session_id: String::new(),
+                profile: "work".to_string(),
+                agent_id: "claude".to_string(),
+                provider_id: "self".to_string(),
+                started_at: now,
+                ended_at: Some(now),
+                duration_secs: Some(1),
+                exit_code: Some(0),
+                source: crate::daemon::telemetry::SessionSource::ProfileRun,
+                model: None,
+                tokens: None,
+                cost: Some(ringlet_core::CostBreakdown {
+                    total_cost: usd,
+                    ..Default::default()
+                }),
+            };
+            state.telemetry.record_session(&session).unwrap();
+        };
+        let refused = |profile: &Profile| match quota_refusal(profile, &state) {
+            Some(Response::Error { code, message }) => {
+                assert_eq!(code, error_codes::QUOTA_EXCEEDED);
+                assert!(message.contains("daily"), "{}", message);
+                true
+            }
+            Some(other) => panic!("unexpected response: {:?}", other),
+            None => false,
+        };
+
+        spend(4.0);
+        assert!(!refused(&profile));
+
+        // Reaching the limit exactly is enough to refuse
+        spend(1.0);
+        assert!(refused(&profile));
+
+        spend(2.5);
+        assert!(refused(&profile));
+
+        // Raising the quota above the spend lets the profile run again
+        profile.metadata.quota_daily_usd = Some(10.0);
+        assert!(!refused(&profile));
+    }
+
+    #[tokio::test]
+    async fn test_set_capture_logs_gives_runs_a_log_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = profile_state(dir.path());
+        let log_path = |state| async move {
+            match prepare("work", &[], &HashMap::new(), false, None, None, state).await {
+                Response::ExecutionContext(context) => context.log_path,
+                other => panic!("unexpected response: {:?}", other),
+            }
+        };
+
+        assert!(matches!(
+            set_capture_logs("work", true, &state).await,
+            Response::Success { .. }
+        ));
+        let stored = state.profile_store.get("work").unwrap().unwrap();
+        assert!(stored.metadata.capture_logs);
+        let first = log_path(&state).await.unwrap();
+        assert!(first.starts_with(ringlet_core::run_log_dir(&dir.path().join("home"))));
+        assert_ne!(log_path(&state).await.unwrap(), first);
+
+        set_capture_logs("work", false, &state).await;
+        assert!(log_path(&state).await.is_none());
+
+        match set_capture_logs("missing", true, &state).await {
+            Response::Error { code, .. } => assert_eq!(code, error_codes::PROFILE_NOT_FOUND),
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_matching_removes_only_matching_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = profile_state(dir.path());
+        for (alias, agent_id, provider_id) in [
+            ("minimax-claude", "claude", "minimax"),
+            ("minimax-codex", "codex", "minimax"),
+            ("openai-codex", "codex", "openai"),
+        ] {
+            let mut profile = test_profile(alias, &dir.path().join(alias));
+            profile.agent_id = agent_id.to_string();
+            profile.provider_id = provider_id.to_string();
+            state.profile_store.save_new(&profile).unwrap();
+        }
+        let aliases = |agent_id, provider_id| {
+            let mut aliases: Vec<_> = state
+                .profile_store
+                .list(agent_id, provider_id)
+                .unwrap()
+                .into_iter()
+                .map(|p| p.alias)
+                .collect();
+            aliases.sort();
+            aliases
+        };
+
+        assert_eq!(
+            aliases(None, Some("minimax")),
+            ["minimax-claude", "minimax-codex"]
+        );
+        assert_eq!(aliases(Some("codex"), Some("minimax")), ["minimax-codex"]);
+
+        // Without a filter nothing is deleted
+        match delete_matching(None, None, &state).await {
+            Response::Error { code, .. } => assert_eq!(code, error_codes::INVALID_PARAMS),
+            other => panic!("unexpected response: {:?}", other),
+        }
+        assert_eq!(aliases(None, None).len(), 4);
+
+        match delete_matching(None, Some("minimax"), &state).await {
+            Response::Success { message } => assert_eq!(message, "Deleted 2 profiles"),
+            other => panic!("unexpected response: {:?}", other),
+        }
+        assert_eq!(aliases(None, None), ["openai-codex", "work"]);
+
+        match delete_matching(Some("claude"), Some("openai"), &state).await {
+            Response::Success { message } => assert_eq!(message, "Deleted 0 profiles"),
+            other => panic!("unexpected response: {:?}", other),
+        }
+        assert_eq!(aliases(None, None).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_set_args_are_passed_before_run_args() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = profile_state(dir.path());
+        let strings = |parts: &[&str]| parts.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        let run_args = |state| async move {
+            match prepare(
+                "work",
+                &strings(&["--resume"]),
+                &HashMap::new(),
+                false,
+                None,
+                None,
+                state,
+            )
+            .await
+            {
+                Response::ExecutionContext(context) => context.args,
+                other => panic!("unexpected response: {:?}", other),
+            }
+        };
+
+        let defaults = strings(&["--verbose", "--max-turns", "5"]);
+        assert!(matches!(
+            set_args("work", &defaults, &state).await,
+            Response::Success { .. }
+        ));
+        assert_eq!(
+            state.profile_store.get("work").unwrap().unwrap().args,
+            defaults
+        );
+        let args = run_args(&state).await;
+        assert!(args.starts_with(&defaults), "{:?}", args);
+        assert!(args.ends_with(&strings(&["--resume"])), "{:?}", args);
+
+        set_args("work", &[], &state).await;
+        let args = run_args(&state).await;
+        assert!(!args.contains(&"--verbose".to_string()), "{:?}", args);
+        assert!(args.ends_with(&strings(&["--resume"])), "{:?}", args);
+
+        match set_args("missing", &defaults, &state).await {
+            Response::Error { code, .. } => assert_eq!(code, error_codes::PROFILE_NOT_FOUND),
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_set_launch_wrapper_validates_and_clears() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = profile_state(dir.path());
+        let wrapper = |parts: &[&str]| parts.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        let stored = |state: &ServerState| {
+            let profile = state.profile_store.get("work").unwrap().unwrap();
+            profile.metadata.launch_wrapper
+        };
+
+        for invalid in [wrapper(&[]), wrapper(&["{args}"])] {
+            match set_launch_wrapper("work", Some(&invalid), &state).await {
+                Response::Error { code, .. } => {
+                    assert_eq!(code, error_codes::INVALID_LAUNCH_WRAPPER)
+                }
+                other => panic!("unexpected response: {:?}", other),
+            }
+        }
+        assert_eq!(stored(&state), None);
+
+        let nice = wrapper(&["nice", "-n", "10", "{binary}", "{args}"]);
+        set_launch_wrapper("work", Some(&nice), &state).await;
+        assert_eq!(stored(&state), Some(nice));
+
+        set_launch_wrapper("work", None, &state).await;
+        assert_eq!(stored(&state), None);
+    }
+
+    /// Token endpoint granting `fresh-N` on its Nth request, after `delay`.
+    async fn serve_tokens(delay: Duration) -> (String, Arc<AtomicUsize>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let served = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let n = served.fetch_add(1, Ordering::SeqCst) + 1;
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    let _ = stream.read(&mut buf).await;
+                    tokio::time::sleep(delay).await;
+                    let body = format!(
+                        r#"{{"access_token":"fresh-{}","refresh_token":"refresh-{}","expires_in":3600}}"#,
+                        n, n
+                    );
+                    let reply = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    let _ = stream.write_all(reply.as_bytes()).await;
+                });
+            }
+        });
+        (format!("http://127.0.0.1:{}/token", port), requests)
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_runs_refresh_a_token_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = profile_state(dir.path());
+        state
+            .secret_store
+            .store_refresh_token("work", "refresh-0")
+            .unwrap();
+        let mut profile = state.profile_store.get("work").unwrap().unwrap();
+        profile.metadata.token_expires_at = Some(Utc::now() + chrono::Duration::minutes(1));
+        state.profile_store.update(&profile).unwrap();
+
+        let (token_url, requests) = serve_tokens(Duration::from_millis(200)).await;
+        let provider: ProviderManifest = toml::from_str(&format!(
+            r#"
+            id = "acme"
+            name = "Acme"
+            type = "openai-compatible"
+
+            [endpoints]
+            default = "https://api.acme.dev/v1"
+
+            [auth]
+            kind = "oauth_device"
+            env_key = "ACME_TOKEN"
+            prompt = "Sign in to Acme"
+
+            [auth.device]
+            device_authorization_url = "{token_url}"
+            token_url = "{token_url}"
+            client_id = "ringlet"
+
+            [models]
+            "#
+        ))
+        .unwrap();
+
+        let (mut first, mut second) = (profile.clone(), profile.clone());
+        let (first_key, second_key) = tokio::join!(
+            refresh_expiring_token(&mut first, &provider, "sk-test".to_string(), &state),
+            refresh_expiring_token(&mut second, &provider, "sk-test".to_string(), &state),
+        );
+
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert_eq!(first_key.unwrap(), "fresh-1");
+        assert_eq!(second_key.unwrap(), "fresh-1");
+        assert_eq!(
+            first.metadata.token_expires_at,
+            second.metadata.token_expires_at
+        );
+        assert_eq!(
+            state.secret_store.get_refresh_token("work").as_deref(),
+            Some("refresh-1")
+        );
+    }
+}
+
+#[cfg(all(test, unix))]
+mod run_tests {
+    use super::*;
+    use crate::daemon::profile_store::test_profile;
+    use crate::daemon::proxy_manager::fake_manager;
+    use std::sync::Arc;
+
+    /// State with a proxied profile `work` and a fake proxy answering on a free port.
+    fn proxied_state(dir: &Path) -> (ServerState, std::net::TcpListener) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut state = ServerState::for_tests(dir);
+        state.proxy_manager = Arc::new(fake_manager(dir, port));
+
+        let mut profile = test_profile("work", &dir.join("home"));
+        profile.working_dir = Some(dir.to_path_buf());
+        profile.metadata.proxy_config = Some(ringlet_core::ProfileProxyConfig::default());
+        profile.metadata.launch_wrapper = Some(vec![
+            "/nonexistent/ringlet-wrapper".to_string(),
+            "{args}".to_string(),
+        ]);
+        state.profile_store.save_new(&profile).unwrap();
+        state.secret_store.store_api_key("work", "sk-test").unwrap();
+        (state, listener)
+    }
+
+    #[tokio::test]
+    async fn test_spawn_failure_releases_ephemeral_proxy() {
+        let dir = tempfile::tempdir().unwrap();
+        let (state, _listener) = proxied_state(dir.path());
+
+        match run("work", &[], &HashMap::new(), true, &state).await {
+            Response::Error { code, message } => {
+                assert_eq!(code, error_codes::EXECUTION_ERROR);
+                assert!(message.starts_with("Failed to run profile"), "{}", message);
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+        assert!(state.proxy_manager.status().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_and_orphaned_runs_release_ephemeral_proxy() {
+        let dir = tempfile::tempdir().unwrap();
+        let (state, _listener) = proxied_state(dir.path());
+
+        let run_id = match prepare(
+            "work",
+            &[],
+            &HashMap::new(),
+            true,
+            None,
+            Some(std::process::id()),
+            &state,
+        )
+        .await
+        {
+            Response::ExecutionContext(context) => context.run_id.unwrap(),
+            other => panic!("unexpected response: {:?}", other),
+        };
+        assert_eq!(state.proxy_manager.status().await.len(), 1);
+
+        // A live owner keeps the run pending
+        expire_pending_runs(&state).await;
+        assert_eq!(state.pending_prepared_runs.lock().await.len(), 1);
+
+        assert!(matches!(
+            abandon(&run_id, &state).await,
+            Response::Success { .. }
+        ));
+        assert!(state.pending_prepared_runs.lock().await.is_empty());
+        assert!(state.proxy_manager.status().await.is_empty());
+
+        // A run whose CLI died without completing it expires
+        let mut owner = std::process::Command::new("true").spawn().unwrap();
+        let dead_pid = owner.id();
+        owner.wait().unwrap();
+        let response = prepare(
+            "work",
+            &[],
+            &HashMap::new(),
+            true,
+            None,
+            Some(dead_pid),
+            &state,
+        )
+        .await;
+        assert!(matches!(response, Response::ExecutionContext(_)));
+        assert_eq!(state.proxy_manager.status().await.len(), 1);
+
+        expire_pending_runs(&state).await;
+        assert!(state.pending_prepared_runs.lock().await.is_empty());
+        assert!(state.proxy_manager.status().await.is_empty());
+    }
+}
@@ -1,17 +1,53 @@
 //! Provider-related request handlers.
 
 use crate::daemon::server::ServerState;
-use ringlet_core::{Response, rpc::error_codes};
+use ringlet_core::{
+    LocalProviderRequest, ProviderCheck, ProviderManifest, ProviderType, Response, rpc::error_codes,
+};
+use std::time::{Duration, Instant};
 
-/// List all providers.
-pub async fn list(state: &ServerState) -> Response {
-    let providers = state.provider_registry.list_all();
+/// How long an endpoint check waits for a response.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `anthropic-version` header sent with Anthropic-style checks.
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// How long `--endpoint auto` waits for each endpoint before skipping it.
+const AUTO_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// List providers, optionally filtered by type and a name/ID substring.
+pub async fn list(
+    provider_type: Option<&str>,
+    search: Option<&str>,
+    state: &ServerState,
+) -> Response {
+    let provider_type = match provider_type
+        .map(|t| t.to_ascii_lowercase().parse::<ProviderType>())
+        .transpose()
+    {
+        Ok(provider_type) => provider_type,
+        Err(message) => return Response::error(error_codes::INVALID_PARAMS, message),
+    };
+    let search = search.map(str::to_lowercase);
+    let providers = state
+        .provider_registry
+        .lock()
+        .await
+        .list_all()
+        .into_iter()
+        .filter(|p| provider_type.as_ref().is_none_or(|t| p.provider_type == *t))
+        .filter(|p| {
+            search.as_deref().is_none_or(|s| {
+                p.id.to_lowercase().contains(s) || p.name.to_lowercase().contains(s)
+            })
+        })
+        .collect();
     Response::Providers(providers)
 }
 
 /// Inspect a specific provider.
 pub async fn inspect(id: &str, state: &ServerState) -> Response {
-    match state.provider_registry.get_info(id) {
+    match state.provider_registry.lock().await.get_info(id) {
         Some(provider) => Response::Provider(provider),
         None => Response::error(
             error_codes::PROVIDER_NOT_FOUND,
@@ -19,3 +55,362 @@ pub async fn inspect(id: &str, state: &ServerState) -> Response {
         ),
     }
 }
+
+/// Register a self-hosted provider by writing a manifest to `providers.d/`.
+pub async fn add_local(req: &LocalProviderRequest, state: &ServerState) -> Response {
+    if req.id.is_empty()
+        || !req
+            .id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Response::error(
+            error_codes::INVALID_PARAMS,
+            format!(
+                "Invalid provider ID '{}': only alphanumeric characters, underscores, and hyphens allowed",
+                req.id
+            ),
+        );
+    }
+    if !(req.url.starts_with("http://") || req.url.starts_with("https://")) {
+        return Response::error(
+            error_codes::INVALID_ENDPOINT,
+            format!(
+                "Endpoint URL must start with http:// or https://: {}",
+                req.url
+            ),
+        );
+    }
+
+    let mut registry = state.provider_registry.lock().await;
+    if registry.get(&req.id).is_some() {
+        return Response::error(
+            error_codes::PROVIDER_EXISTS,
+            format!("Provider already exists: {}", req.id),
+        );
+    }
+
+    let manifest = req.to_manifest();
+    let providers_d = state.paths.providers_d();
+    let path = providers_d.join(format!("{}.toml", manifest.id));
+    let written = toml::to_string_pretty(&manifest)
+        .map_err(anyhow::Error::from)
+        .and_then(|content| {
+            std::fs::create_dir_all(&providers_d)?;
+            std::fs::write(&path, content)?;
+            Ok(())
+        });
+    if let Err(e) = written {
+        return Response::error(
+            error_codes::INTERNAL_ERROR,
+            format!("Failed to write {:?}: {}", path, e),
+        );
+    }
+
+    let info = manifest.to_info();
+    registry.insert(manifest);
+    Response::Provider(info)
+}
+
+/// Probe a provider endpoint for reachability and, given a key, auth.
+///
+/// Sends a `GET` to the endpoint's models listing. Self-auth providers and
+/// providers without required auth are only checked for reachability.
+pub async fn check(
+    id: &str,
+    endpoint: Option<&str>,
+    api_key: Option<&str>,
+    state: &ServerState,
+) -> Response {
+    let Some(manifest) = state.provider_registry.lock().await.get(id).cloned() else {
+        return Response::error(
+            error_codes::PROVIDER_NOT_FOUND,
+            format!("Provider not found: {}", id),
+        );
+    };
+    let Some((endpoint_id, url)) = manifest.endpoint_url(endpoint) else {
+        return Response::error(
+            error_codes::INVALID_ENDPOINT,
+            format!(
+                "Provider '{}' has no endpoint '{}'",
+                id,
+                endpoint.unwrap_or("default")
+            ),
+        );
+    };
+
+    let provider_type = manifest.provider_type.clone();
+    let auth = api_key
+        .filter(|_| !provider_type.is_self_auth() && manifest.auth.required)
+        .map(|key| manifest.auth_header(key));
+    let mut check = ProviderCheck {
+        provider_id: manifest.id.clone(),
+        endpoint_id: endpoint_id.to_string(),
+        url: provider_type.models_url(url),
+        reachable: false,
+        auth_ok: None,
+        status: None,
+        latency_ms: 0,
+        error: None,
+    };
+
+    let probe = match probe("GET", check.url.clone(), auth, provider_type, CHECK_TIMEOUT).await {
+        Ok(probe) => probe,
+        Err(e) => {
+            return Response::error(
+                error_codes::INTERNAL_ERROR,
+                format!("Endpoint check failed: {}", e),
+            );
+        }
+    };
+    check.latency_ms = probe.latency.as_millis() as u64;
+
+    let status = match probe.result {
+        Ok(status) => status,
+        Err(e) => {
+            check.error = Some(e);
+            return Response::ProviderCheck(check);
+        }
+    };
+    check.reachable = true;
+    check.status = Some(status);
+    if probe.authenticated {
+        check.auth_ok = match status {
+            200..=299 => Some(true),
+            401 | 403 => Some(false),
+            _ => None,
+        };
+    }
+
+    Response::ProviderCheck(check)
+}
+
+/// Pick the endpoint whose models listing answers a `HEAD` fastest.
+///
+/// Any HTTP status counts as an answer; endpoints that fail to connect or
+/// time out are skipped. Returns `None` when no endpoint answers.
+pub async fn fastest_endpoint(manifest: &ProviderManifest) -> Option<String> {
+    let probes = manifest
+        .endpoints
+        .iter()
+        // `default = "<endpoint id>"` names another endpoint rather than a URL
+        .filter(|(_, url)| !manifest.endpoints.contains_key(url.as_str()))
+        .map(|(id, url)| async move {
+            let url = manifest.provider_type.models_url(url);
+            let probe = probe(
+                "HEAD",
+                url,
+                None,
+                manifest.provider_type.clone(),
+                AUTO_PROBE_TIMEOUT,
+            )
+            .await;
+            (id, probe)
+        });
+
+    futures_util::future::join_all(probes)
+        .await
+        .into_iter()
+        .filter_map(|(id, probe)| match probe {
+            Ok(probe) if probe.result.is_ok() => Some((probe.latency, id)),
+            _ => None,
+        })
+        .min()
+        .map(|(_, id)| id.clone())
+}
+
+/// Result of a single request to an endpoint.
+struct Probe {
+    /// HTTP status, or the transport error when no response arrived.
+    result: Result<u16, String>,
+    latency: Duration,
+    authenticated: bool,
+}
+
+/// Send one request, timing how long the endpoint takes to respond.
+async fn probe(
+    method: &'static str,
+    url: String,
+    auth: Option<(String, String)>,
+    provider_type: ProviderType,
+    timeout: Duration,
+) -> Result<Probe, tokio::task::JoinError> {
+    tokio::task::spawn_blocking(move || {
+        let mut request = ureq::request(method, &url).timeout(timeout);
+        if let Some((header, value)) = &auth {
+            request = request.set(header, value);
+            if !matches!(
+                provider_type,
+                ProviderType::Openai | ProviderType::OpenaiCompatible | ProviderType::Other(_)
+            ) {
+                request = request.set("anthropic-version", ANTHROPIC_VERSION);
+            }
+        }
+        let started = Instant::now();
+        let result = match request.call() {
+            Ok(response) => Ok(response.status()),
+            Err(ureq::Error::Status(status, _)) => Ok(status),
+            Err(e) => Err(e.to_string()),
+        };
+        Probe {
+            result,
+            latency: started.elapsed(),
+            authenticated: auth.is_some(),
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serve `200 OK` to every request after waiting `delay`.
+    async fn serve_after(delay: Duration) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 1024];
+                    let _ = stream.read(&mut buf).await;
+                    tokio::time::sleep(delay).await;
+                    let reply = "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+                    let _ = stream.write_all(reply.as_bytes()).await;
+                });
+            }
+        });
+        format!("http://127.0.0.1:{}", port)
+    }
+
+    /// A URL nothing is listening on.
+    fn closed_url() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        format!("http://127.0.0.1:{}", port)
+    }
+
+    fn manifest(endpoints: &[(&str, String)]) -> ProviderManifest {
+        let endpoints: toml::Table = endpoints
+            .iter()
+            .map(|(id, url)| (id.to_string(), toml::Value::String(url.clone())))
+            .collect();
+        let toml = format!(
+            r#"
+            id = "test"
+            name = "Test"
+            type = "anthropic-compatible"
+
+            [endpoints]
+            {}
+
+            [auth]
+            env_key = "TEST_API_KEY"
+            prompt = "Enter your API key"
+
+            [models]
+            available = ["test-model"]
+            default = "test-model"
+            "#,
+            endpoints
+        );
+        toml::from_str(&toml).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_fastest_endpoint_picks_quickest_answer() {
+        let slow = serve_after(Duration::from_millis(300)).await;
+        let fast = serve_after(Duration::ZERO).await;
+        let manifest = manifest(&[
+            ("slow", slow),
+            ("fast", fast),
+            ("down", closed_url()),
+            ("default", "slow".to_string()),
+        ]);
+
+        assert_eq!(fastest_endpoint(&manifest).await.as_deref(), Some("fast"));
+    }
+
+    #[tokio::test]
+    async fn test_fastest_endpoint_skips_unreachable_endpoints() {
+        let slow = serve_after(Duration::from_millis(100)).await;
+        let manifest = manifest(&[("down", closed_url()), ("slow", slow)]);
+
+        assert_eq!(fastest_endpoint(&manifest).await.as_deref(), Some("slow"));
+    }
+
+    #[tokio::test]
+    async fn test_fastest_endpoint_none_when_nothing_answers() {
+        let manifest = manifest(&[("down", closed_url()), ("default", "down".to_string())]);
+
+        assert_eq!(fastest_endpoint(&manifest).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_add_local_validates_and_registers() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = ServerState::for_tests(dir.path());
+        let request = |id: &str, url: &str| -> LocalProviderRequest {
+            serde_json::from_value(serde_json::json!({ "id": id, "url": url })).unwrap()
+        };
+        let code = |response| match response {
+            Response::Error { code, .. } => code,
+            other => panic!("unexpected response: {:?}", other),
+        };
+
+        assert_eq!(
+            code(add_local(&request("../evil", "http://localhost:8000/v1"), &state).await),
+            error_codes::INVALID_PARAMS
+        );
+        assert_eq!(
+            code(add_local(&request("ollama", "localhost:8000"), &state).await),
+            error_codes::INVALID_ENDPOINT
+        );
+
+        let ollama = request("ollama", "http://localhost:11434/v1");
+        assert!(matches!(
+            add_local(&ollama, &state).await,
+            Response::Provider(_)
+        ));
+        assert!(state.paths.providers_d().join("ollama.toml").exists());
+        assert_eq!(
+            code(add_local(&ollama, &state).await),
+            error_codes::PROVIDER_EXISTS
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_rejects_unknown_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = ServerState::for_tests(dir.path());
+
+        match list(Some("openai-compat"), None, &state).await {
+            Response::Error { code, message } => {
+                assert_eq!(code, error_codes::INVALID_PARAMS);
+                assert!(message.contains("openai-compatible"), "{}", message);
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+
+        let ollama: LocalProviderRequest = serde_json::from_value(serde_json::json!({
+            "id": "ollama",
+            "url": "http://localhost:11434/v1",
+        }))
+        .unwrap();
+        add_local(&ollama, &state).await;
+        match list(Some("OpenAI-Compatible"), None, &state).await {
+            Response::Providers(providers) => {
+                assert!(providers.iter().any(|p| p.id == "ollama"));
+                assert!(
+                    providers
+                        .iter()
+                        .all(|p| p.provider_type == ProviderType::OpenaiCompatible)
+                );
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+}
@@ -1,12 +1,14 @@
 //! Proxy management handlers.
 
+use crate::daemon::proxy_manager::ProxyManager;
 use crate::daemon::server::ServerState;
 use ringlet_core::{
-    Event, Response,
+    Event, ProviderType, Response,
     proxy::{ModelTarget, ProfileProxyConfig, RoutingRule},
     rpc::error_codes,
 };
 use std::collections::HashMap;
+use std::path::PathBuf;
 use tracing::info;
 
 /// Enable proxy for a profile.
@@ -72,82 +74,102 @@ pub async fn disable(alias: &str, state: &ServerState) -> Response {
 
 /// Start proxy for a profile.
 pub async fn start(alias: &str, state: &ServerState) -> Response {
+    let (profile_home, proxy_config) = match proxy_target(alias, state) {
+        Ok(target) => target,
+        Err(response) => return *response,
+    };
+
+    // Start proxy
+    match state
+        .proxy_manager
+        .start(alias, &profile_home, &proxy_config)
+        .await
+    {
+        Ok(port) => {
+            info!("Started proxy for profile '{}' on port {}", alias, port);
+
+            // Broadcast event
+            state.broadcast(Event::ProxyStarted {
+                alias: alias.to_string(),
+                port,
+            });
+
+            Response::success(format!(
+                "Proxy started for profile '{}' on port {}",
+                alias, port
+            ))
+        }
+        Err(e) => {
+            state
+                .errors
+                .record("proxy", format!("Failed to start proxy '{}': {}", alias, e));
+            Response::error(error_codes::PROXY_START_FAILED, e.to_string())
+        }
+    }
+}
+
+/// Resolve the profile home and proxy config needed to launch a profile's proxy.
+fn proxy_target(
+    alias: &str,
+    state: &ServerState,
+) -> Result<(PathBuf, ProfileProxyConfig), Box<Response>> {
     // Check if proxy manager is available
     if !state.proxy_manager.is_available() {
-        return Response::error(
+        return Err(Box::new(Response::error(
             error_codes::PROXY_NOT_SUPPORTED,
             "ultrallm binary not found. Install ultrallm to use proxy features.",
-        );
+        )));
     }
 
     // Load profile
     let profile = match state.profile_store.get(alias) {
         Ok(Some(p)) => p,
         Ok(None) => {
-            return Response::error(
+            return Err(Box::new(Response::error(
                 error_codes::PROFILE_NOT_FOUND,
                 format!("Profile not found: {}", alias),
-            );
+            )));
+        }
+        Err(e) => {
+            return Err(Box::new(Response::error(
+                error_codes::INTERNAL_ERROR,
+                e.to_string(),
+            )));
         }
-        Err(e) => return Response::error(error_codes::INTERNAL_ERROR, e.to_string()),
     };
 
     // Check if proxy is enabled
     let proxy_config = match &profile.metadata.proxy_config {
         Some(c) if c.enabled => c.clone(),
         Some(_) => {
-            return Response::error(
+            return Err(Box::new(Response::error(
                 error_codes::PROXY_NOT_ENABLED,
                 format!(
                     "Proxy not enabled for profile '{}'. Run 'ringlet proxy enable {}' first.",
                     alias, alias
                 ),
-            );
+            )));
         }
         None => {
-            return Response::error(
+            return Err(Box::new(Response::error(
                 error_codes::PROXY_NOT_ENABLED,
                 format!(
                     "Proxy not configured for profile '{}'. Run 'ringlet proxy enable {}' first.",
                     alias, alias
                 ),
-            );
+            )));
         }
     };
 
     // Get profile home
-    let profile_home = match state.profile_store.get_home(alias) {
-        Ok(home) => home,
-        Err(e) => {
-            return Response::error(
-                error_codes::INTERNAL_ERROR,
-                format!("Failed to get profile home: {}", e),
-            );
-        }
-    };
-
-    // Start proxy
-    match state
-        .proxy_manager
-        .start(alias, &profile_home, &proxy_config)
-        .await
-    {
-        Ok(port) => {
-            info!("Started proxy for profile '{}' on port {}", alias, port);
-
-            // Broadcast event
-            state.broadcast(Event::ProxyStarted {
-                alias: alias.to_string(),
-                port,
-            });
+    let profile_home = state.profile_store.get_home(alias).map_err(|e| {
+        Box::new(Response::error(
+            error_codes::INTERNAL_ERROR,
+            format!("Failed to get profile home: {}", e),
+        ))
+    })?;
 
-            Response::success(format!(
-                "Proxy started for profile '{}' on port {}",
-                alias, port
-            ))
-        }
-        Err(e) => Response::error(error_codes::PROXY_START_FAILED, e.to_string()),
-    }
+    Ok((profile_home, proxy_config))
 }
 
 /// Stop proxy for a profile.
@@ -167,10 +189,39 @@ pub async fn stop(alias: &str, state: &ServerState) -> Response {
     }
 }
 
-/// Restart proxy for a profile.
-pub async fn restart(alias: &str, state: &ServerState) -> Response {
-    let _ = stop(alias, state).await;
-    start(alias, state).await
+/// Restart proxy for a profile, keeping its port.
+pub async fn restart(alias: &str, verify: bool, state: &ServerState) -> Response {
+    let (profile_home, proxy_config) = match proxy_target(alias, state) {
+        Ok(target) => target,
+        Err(response) => return *response,
+    };
+
+    match state
+        .proxy_manager
+        .restart(alias, &profile_home, &proxy_config, verify)
+        .await
+    {
+        Ok(port) => {
+            info!("Restarted proxy for profile '{}' on port {}", alias, port);
+
+            state.broadcast(Event::ProxyStarted {
+                alias: alias.to_string(),
+                port,
+            });
+
+            Response::success(format!(
+                "Proxy restarted for profile '{}' on port {}",
+                alias, port
+            ))
+        }
+        Err(e) => {
+            state.errors.record(
+                "proxy",
+                format!("Failed to restart proxy '{}': {}", alias, e),
+            );
+            Response::error(error_codes::PROXY_START_FAILED, e.to_string())
+        }
+    }
 }
 
 /// Stop all proxies.
@@ -184,16 +235,9 @@ pub async fn stop_all(state: &ServerState) -> Response {
     }
 }
 
-/// Get proxy status.
+/// Get proxy status, health-checking each running proxy.
 pub async fn status(alias: Option<&str>, state: &ServerState) -> Response {
-    let instances = state.proxy_manager.status().await;
-
-    if let Some(a) = alias {
-        let filtered: Vec<_> = instances.into_iter().filter(|i| i.alias == a).collect();
-        Response::ProxyStatus(filtered)
-    } else {
-        Response::ProxyStatus(instances)
-    }
+    Response::ProxyStatus(state.proxy_manager.probe_status(alias).await)
 }
 
 /// Get proxy configuration for a profile.
@@ -226,6 +270,74 @@ pub async fn logs(alias: &str, lines: Option<usize>, state: &ServerState) -> Res
     }
 }
 
+/// Get the location of a profile's proxy log file.
+///
+/// Works whether or not the proxy is running, so the log of one that failed
+/// to start can still be found.
+pub fn log_path(alias: &str, state: &ServerState) -> Response {
+    match state.profile_store.get(alias) {
+        Ok(Some(profile)) => Response::ProxyLogPath(ProxyManager::log_path(&profile.metadata.home)),
+        Ok(None) => Response::error(
+            error_codes::PROFILE_NOT_FOUND,
+            format!("Profile not found: {}", alias),
+        ),
+        Err(e) => Response::error(error_codes::INTERNAL_ERROR, e.to_string()),
+    }
+}
+
+/// Get the environment variables that point a profile's agent at its running proxy.
+pub async fn env(alias: &str, state: &ServerState) -> Response {
+    let profile = match state.profile_store.get(alias) {
+        Ok(Some(p)) => p,
+        Ok(None) => {
+            return Response::error(
+                error_codes::PROFILE_NOT_FOUND,
+                format!("Profile not found: {}", alias),
+            );
+        }
+        Err(e) => return Response::error(error_codes::INTERNAL_ERROR, e.to_string()),
+    };
+
+    let Some(url) = state.proxy_manager.proxy_url(alias).await else {
+        return Response::error(
+            error_codes::PROXY_NOT_RUNNING,
+            format!(
+                "Proxy for '{}' is not running. Run 'ringlet proxy start {}' first.",
+                alias, alias
+            ),
+        );
+    };
+
+    let provider_type = state
+        .provider_registry
+        .lock()
+        .await
+        .get(&profile.provider_id)
+        .map(|p| p.provider_type.clone());
+
+    let env = base_url_vars(&profile.agent_id, provider_type)
+        .iter()
+        .map(|var| (var.to_string(), url.clone()))
+        .collect();
+    Response::Env(env)
+}
+
+/// Environment variables an agent reads its API base URL from.
+///
+/// Claude Code always talks to the Anthropic API; other agents follow the
+/// API style of their provider.
+pub(crate) fn base_url_vars(
+    agent_id: &str,
+    provider_type: Option<ProviderType>,
+) -> &'static [&'static str] {
+    match (agent_id, provider_type) {
+        ("claude", _)
+        | (_, Some(ProviderType::Anthropic))
+        | (_, Some(ProviderType::AnthropicCompatible)) => &["ANTHROPIC_BASE_URL"],
+        _ => &["OPENAI_API_BASE", "OPENAI_BASE_URL"],
+    }
+}
+
 /// Add a routing rule to a profile.
 pub async fn route_add(alias: &str, rule: &RoutingRule, state: &ServerState) -> Response {
     // Load profile
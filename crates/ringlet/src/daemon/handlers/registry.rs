@@ -1,28 +1,36 @@
 //! Registry-related request handlers.
 
+use crate::daemon::registry_client::SyncStatus;
 use crate::daemon::server::ServerState;
-use ringlet_core::Response;
 use ringlet_core::rpc::{RegistryStatus, error_codes};
+use ringlet_core::{Event, Response};
 use tracing::info;
 
 /// Sync registry from remote.
 pub async fn sync(force: bool, offline: bool, state: &ServerState) -> Response {
     info!("Syncing registry (force={}, offline={})", force, offline);
 
-    match state.registry_client.sync(force, offline) {
-        Ok(status) => Response::RegistryStatus(RegistryStatus {
-            commit: status.commit,
-            channel: status.channel,
-            last_sync: status.last_sync,
-            offline: status.offline,
-            cached_agents: status.cached_agents,
-            cached_providers: status.cached_providers,
-            cached_scripts: status.cached_scripts,
-        }),
-        Err(e) => Response::error(
-            error_codes::REGISTRY_ERROR,
-            format!("Failed to sync registry: {}", e),
-        ),
+    let on_progress = |current, total, message: &str| {
+        state.broadcast(Event::Progress {
+            op: "registry_sync".to_string(),
+            current,
+            total,
+            message: Some(message.to_string()),
+        });
+    };
+
+    match state.registry_client.sync(force, offline, on_progress) {
+        Ok(status) => {
+            if let Some(warning) = &status.warning {
+                state.errors.record("registry", warning);
+            }
+            Response::RegistryStatus(registry_status(status))
+        }
+        Err(e) => {
+            let message = format!("Failed to sync registry: {}", e);
+            state.errors.record("registry", &message);
+            Response::error(error_codes::REGISTRY_ERROR, message)
+        }
     }
 }
 
@@ -31,7 +39,7 @@ pub async fn pin(ref_: &str, state: &ServerState) -> Response {
     info!("Pinning to ref: {}", ref_);
 
     match state.registry_client.pin(ref_) {
-        Ok(()) => Response::success(format!("Pinned to: {}", ref_)),
+        Ok(commit) => Response::success(format!("Pinned to: {} ({})", ref_, commit)),
         Err(e) => Response::error(error_codes::REGISTRY_ERROR, format!("Failed to pin: {}", e)),
     }
 }
@@ -39,18 +47,27 @@ pub async fn pin(ref_: &str, state: &ServerState) -> Response {
 /// Inspect registry status.
 pub async fn inspect(state: &ServerState) -> Response {
     match state.registry_client.get_status(false) {
-        Ok(status) => Response::RegistryStatus(RegistryStatus {
-            commit: status.commit,
-            channel: status.channel,
-            last_sync: status.last_sync,
-            offline: status.offline,
-            cached_agents: status.cached_agents,
-            cached_providers: status.cached_providers,
-            cached_scripts: status.cached_scripts,
-        }),
+        Ok(status) => Response::RegistryStatus(registry_status(status)),
         Err(e) => Response::error(
             error_codes::REGISTRY_ERROR,
             format!("Failed to get registry status: {}", e),
         ),
     }
 }
+
+fn registry_status(status: SyncStatus) -> RegistryStatus {
+    RegistryStatus {
+        commit: status.commit,
+        channel: status.channel,
+        last_sync: status.last_sync,
+        offline: status.offline,
+        cached_agents: status.cached_agents,
+        cached_providers: status.cached_providers,
+        cached_scripts: status.cached_scripts,
+        cache_age_secs: status.cache_age_secs,
+        cache_ttl_secs: status.cache_ttl_secs,
+        stale: status.stale,
+        from_cache: status.from_cache,
+        warning: status.warning,
+    }
+}
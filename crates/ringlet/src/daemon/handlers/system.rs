@@ -1,9 +1,36 @@
-//! System-level handlers used by the HTTP layer.
+//! System-level handlers.
 
 use crate::daemon::server::ServerState;
+use ringlet_core::{Diagnostics, Response};
 
 pub async fn shutdown(state: &ServerState) {
     if let Some(tx) = state.shutdown_tx.lock().await.take() {
         let _ = tx.send(());
     }
 }
+
+/// Collect what `ringlet doctor` checks on the daemon's side.
+pub fn diagnostics(state: &ServerState) -> Response {
+    let mut proxy_profiles = Vec::new();
+    let mut shims = Vec::new();
+    if let Ok(aliases) = state.profile_store.names() {
+        for alias in aliases {
+            let Ok(Some(profile)) = state.profile_store.get(&alias) else {
+                continue;
+            };
+            if profile.metadata.proxy_config.is_some_and(|c| c.enabled) {
+                proxy_profiles.push(alias);
+            }
+            if let Some(path) = profile.metadata.alias_path {
+                shims.push(path);
+            }
+        }
+    }
+
+    Response::Diagnostics(Diagnostics {
+        version: ringlet_core::VERSION.to_string(),
+        proxy_binary: state.proxy_manager.binary_path().cloned(),
+        proxy_profiles,
+        shims,
+    })
+}
@@ -5,11 +5,12 @@ use crate::daemon::handlers::profiles::prepare_execution_context;
 use crate::daemon::server::ServerState;
 use crate::daemon::telemetry::SessionSource;
 use crate::daemon::terminal::{
-    SandboxConfig, SessionId, SessionTelemetryContext, TerminalSessionInfo,
+    SandboxConfig, SessionDescriptor, SessionId, SessionTelemetryContext, TerminalSessionInfo,
 };
 use portable_pty::PtySize;
 use std::collections::HashMap;
 use std::path::Path;
+use tracing::{info, warn};
 use uuid::Uuid;
 
 pub struct CreatedTerminalSession {
@@ -26,6 +27,10 @@ pub async fn get(session_id: &str, state: &ServerState) -> Option<TerminalSessio
     Some(session.info().await)
 }
 
+/// Create a terminal session running a profile's agent.
+///
+/// With `persist` the session is relaunched after a daemon restart.
+#[allow(clippy::too_many_arguments)]
 pub async fn create_profile_session(
     profile_alias: &str,
     args: &[String],
@@ -33,14 +38,99 @@ pub async fn create_profile_session(
     initial_size: PtySize,
     sandbox_config: SandboxConfig,
     owner_token_hash: String,
+    persist: bool,
     state: &ServerState,
 ) -> Result<CreatedTerminalSession, String> {
-    let prepared = prepare_execution_context(profile_alias, args, state, true, true)
+    let descriptor = persist.then(|| SessionDescriptor {
+        profile_alias: profile_alias.to_string(),
+        args: args.to_vec(),
+        working_dir: working_dir_override.map(Path::to_path_buf),
+        cols: initial_size.cols,
+        rows: initial_size.rows,
+        sandbox: sandbox_config.clone(),
+        created_at: chrono::Utc::now(),
+    });
+    launch_profile_session(
+        profile_alias,
+        args,
+        working_dir_override,
+        initial_size,
+        sandbox_config,
+        owner_token_hash,
+        descriptor,
+        state,
+    )
+    .await
+}
+
+/// Relaunch the persistent sessions left over from the previous daemon run.
+///
+/// Sessions that can't be relaunched, e.g. because their profile was
+/// deleted, are dropped and reported in the health error log.
+pub async fn restore_persisted(owner_token_hash: &str, state: &ServerState) {
+    for (old_id, descriptor) in state.terminal_sessions.take_persisted() {
+        let alias = descriptor.profile_alias.clone();
+        let size = PtySize {
+            rows: descriptor.rows,
+            cols: descriptor.cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        };
+        match launch_profile_session(
+            &alias,
+            &descriptor.args,
+            descriptor.working_dir.as_deref(),
+            size,
+            descriptor.sandbox.clone(),
+            owner_token_hash.to_string(),
+            Some(descriptor.clone()),
+            state,
+        )
         .await
-        .map_err(|response| match response {
-            ringlet_core::Response::Error { message, .. } => message,
-            _ => "Unexpected response type".to_string(),
-        })?;
+        {
+            Ok(created) => info!(
+                "Relaunched terminal session {} for profile '{}' as {}",
+                old_id, alias, created.session_id
+            ),
+            Err(message) => {
+                warn!(
+                    "Failed to relaunch terminal session {} for profile '{}': {}",
+                    old_id, alias, message
+                );
+                state.errors.record(
+                    "terminal",
+                    format!("Failed to relaunch session for '{}': {}", alias, message),
+                );
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn launch_profile_session(
+    profile_alias: &str,
+    args: &[String],
+    working_dir_override: Option<&Path>,
+    initial_size: PtySize,
+    sandbox_config: SandboxConfig,
+    owner_token_hash: String,
+    persist: Option<SessionDescriptor>,
+    state: &ServerState,
+) -> Result<CreatedTerminalSession, String> {
+    let prepared = prepare_execution_context(
+        profile_alias,
+        args,
+        state,
+        true,
+        true,
+        None,
+        working_dir_override,
+    )
+    .await
+    .map_err(|response| match response {
+        ringlet_core::Response::Error { message, .. } => message,
+        _ => "Unexpected response type".to_string(),
+    })?;
 
     let working_dir = working_dir_override.unwrap_or(prepared.context.working_dir.as_path());
 
@@ -53,10 +143,9 @@ pub async fn create_profile_session(
     {
         Ok(snapshot) => snapshot,
         Err(e) => {
-            tracing::warn!(
+            warn!(
                 "Failed to capture terminal usage baseline for profile '{}': {}",
-                prepared.profile.alias,
-                e
+                prepared.profile.alias, e
             );
             None
         }
@@ -84,6 +173,7 @@ pub async fn create_profile_session(
                 usage_baseline,
                 paths: state.paths.clone(),
             }),
+            persist,
         )
         .await
         .map_err(|e| e.to_string())?;
@@ -114,6 +204,7 @@ pub async fn create_shell_session(
             sandbox_config,
             owner_token_hash,
             None,
+            None,
         )
         .await
         .map_err(|e| e.to_string())?;
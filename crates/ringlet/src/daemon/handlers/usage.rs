@@ -5,14 +5,21 @@
 //! Claude, Codex, and OpenCode agents.
 
 use crate::daemon::agent_usage;
+use crate::daemon::pricing::PricingLoader;
 use crate::daemon::server::ServerState;
+use crate::daemon::telemetry::Session;
 use chrono::{Datelike, Duration, NaiveDate, Utc};
+use ringlet_core::config::UsageConfig;
 use ringlet_core::rpc::error_codes;
 use ringlet_core::{
-    AgentUsage, CostBreakdown, DailyUsage, ModelUsage, Response, TokenUsage, UsageAggregates,
-    UsagePeriod, UsageStatsResponse,
+    AgentType, AgentUsage, BudgetPeriod, BudgetStatus, CostBreakdown, DailyUsage, Event,
+    ModelUsage, Response, TokenUsage, UsageAggregates, UsageImportFormat, UsagePeriod, UsageRecord,
+    UsageRecordsPage, UsageStatsResponse, UserConfig,
 };
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
 use tracing::{debug, info, warn};
 
 /// Get token/cost usage statistics.
@@ -24,6 +31,7 @@ pub async fn get_usage(
     period: Option<&UsagePeriod>,
     profile: Option<&str>,
     model: Option<&str>,
+    agent: Option<AgentType>,
     state: &ServerState,
 ) -> Response {
     let period = period.cloned().unwrap_or_default();
@@ -36,12 +44,12 @@ pub async fn get_usage(
     };
 
     debug!(
-        "Getting usage for period={:?}, profile={:?}, model={:?}",
-        period, profile, model
+        "Getting usage for period={:?}, profile={:?}, model={:?}, agent={:?}",
+        period, profile, model, agent
     );
 
     // Scan agent native files for usage data
-    let agent_scan = match agent_usage::scan_all_agents().await {
+    let agent_scan = match scan_usage(state).await {
         Ok(result) => {
             if !result.warnings.is_empty() {
                 for warning in &result.warnings {
@@ -72,6 +80,8 @@ pub async fn get_usage(
                         && model.is_none_or(|session_model| {
                             session.model.as_deref() == Some(session_model)
                         })
+                        && agent
+                            .is_none_or(|agent| session.agent_id.parse::<AgentType>() == Ok(agent))
                 })
                 .collect();
 
@@ -80,6 +90,20 @@ pub async fn get_usage(
                     &filtered_sessions,
                 );
             let mut aggregates = convert_to_usage_aggregates(&telemetry_aggregates);
+            let mut total_runtime_secs = telemetry_aggregates.total_runtime_secs;
+            let pricing = PricingLoader::new(state.paths.clone());
+
+            // Count usage left without a cost, by model, to find the gaps in the pricing table
+            let mut uncosted = BTreeMap::<String, u64>::new();
+            for session in &filtered_sessions {
+                if session.provider_id == "self"
+                    && session.cost.is_none()
+                    && session.tokens.is_some()
+                    && let Some(model) = &session.model
+                {
+                    *uncosted.entry(model.clone()).or_default() += 1;
+                }
+            }
 
             if let Some(scan) = agent_scan {
                 let filtered_entries = scan
@@ -92,17 +116,34 @@ pub async fn get_usage(
                         profile.is_none()
                             && matches_period(entry.timestamp.date_naive(), period_range)
                             && model.is_none_or(|model_filter| entry.model == model_filter)
+                            && agent.is_none_or(|agent| entry.agent == agent)
                     })
                     .collect::<Vec<_>>();
-                merge_agent_scan_entries(&mut aggregates, &filtered_entries);
+                for entry in filtered_entries
+                    .iter()
+                    .filter(|entry| entry.cost_usd.is_none())
+                {
+                    *uncosted.entry(entry.model.clone()).or_default() += 1;
+                }
+                total_runtime_secs += merge_agent_scan_entries(
+                    &mut aggregates,
+                    &filtered_entries,
+                    &filtered_sessions,
+                    &pricing,
+                );
             }
 
+            let unpriced = unpriced_models(uncosted, &pricing);
             Response::Usage(Box::new(UsageStatsResponse {
                 period: period_desc,
                 total_tokens: aggregates.total_tokens.clone(),
                 total_cost: aggregates.total_cost.clone(),
                 total_sessions: telemetry_aggregates.total_sessions,
-                total_runtime_secs: telemetry_aggregates.total_runtime_secs,
+                total_runtime_secs,
+                cache_hit_ratio: aggregates.total_tokens.cache_hit_ratio(),
+                cache_savings_usd: cache_savings(&aggregates.by_model, &pricing),
+                unpriced_entries: unpriced.values().sum(),
+                unpriced_models: unpriced.into_keys().collect(),
                 aggregates,
             }))
         }
@@ -113,9 +154,243 @@ pub async fn get_usage(
     }
 }
 
+/// Estimated savings from cache reads, summed over the models with known pricing.
+fn cache_savings(by_model: &HashMap<String, ModelUsage>, pricing: &PricingLoader) -> Option<f64> {
+    by_model
+        .iter()
+        .filter(|(_, usage)| usage.tokens.cache_read_input_tokens > 0)
+        .filter_map(|(model, usage)| {
+            pricing
+                .get_model_pricing(model)
+                .map(|model_pricing| model_pricing.cache_savings(&usage.tokens))
+        })
+        .reduce(|total, savings| total + savings)
+}
+
+/// Keep the models of uncosted usage that have no pricing data.
+///
+/// Usage of these models counts towards tokens but not `total_cost`.
+fn unpriced_models(
+    uncosted: BTreeMap<String, u64>,
+    pricing: &PricingLoader,
+) -> BTreeMap<String, u64> {
+    uncosted
+        .into_iter()
+        .filter(|(model, _)| pricing.get_model_pricing(model).is_none())
+        .collect()
+}
+
+/// Scan agent native files and add the usage imported from external files.
+async fn scan_usage(state: &ServerState) -> anyhow::Result<agent_usage::ScanResult> {
+    let mut scan =
+        agent_usage::scan_all_agents(&agent_usage::AgentDataDirs::load(&state.paths)).await?;
+    match agent_usage::imported::load(&state.paths.imported_usage_log()) {
+        Ok(entries) if !entries.is_empty() => {
+            scan.add_imported_entries(entries);
+            scan.deduplicate();
+        }
+        Ok(_) => {}
+        Err(e) => scan.add_warning(format!("Failed to load imported usage: {}", e)),
+    }
+    Ok(scan)
+}
+
+/// How long a scan is reused while a client pages through usage records.
+const RECORDS_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Scan result reused across consecutive `UsageRecords` pages.
+pub struct UsageRecordsCache {
+    period: String,
+    scanned_at: Instant,
+    /// Pricing aliases the costs were computed with.
+    model_aliases: HashMap<String, String>,
+    records: Arc<Vec<UsageRecord>>,
+}
+
+/// Get one page of individual usage records from agent native files.
+///
+/// Records are sorted oldest first so offsets stay stable between pages.
+/// The first page (offset 0) always rescans; later pages reuse that scan
+/// for a short while so exporting N pages doesn't cost N full scans.
+pub async fn get_records(
+    period: Option<&UsagePeriod>,
+    offset: usize,
+    limit: usize,
+    state: &ServerState,
+) -> Response {
+    let period = period.cloned().unwrap_or_default();
+    let period_key = format_period(&period);
+    let period_range = match period_range(&period) {
+        Ok(range) => range,
+        Err(message) => {
+            return Response::error(error_codes::INTERNAL_ERROR, message);
+        }
+    };
+
+    // Changed pricing aliases change the costs, so they invalidate the scan
+    let model_aliases = UserConfig::load(&state.paths.config_file())
+        .map(|config| config.usage.model_aliases)
+        .unwrap_or_default();
+    let mut cache = state.usage_records_cache.lock().await;
+    let cached = cache.as_ref().filter(|cached| {
+        offset > 0
+            && cached.period == period_key
+            && cached.model_aliases == model_aliases
+            && cached.scanned_at.elapsed() < RECORDS_CACHE_TTL
+    });
+
+    let records = match cached {
+        Some(cached) => cached.records.clone(),
+        None => {
+            let scan = match scan_usage(state).await {
+                Ok(scan) => scan,
+                Err(e) => {
+                    return Response::error(
+                        error_codes::INTERNAL_ERROR,
+                        format!("Failed to scan usage: {}", e),
+                    );
+                }
+            };
+            let mut records: Vec<UsageRecord> = scan
+                .entries
+                .into_iter()
+                .filter(|entry| matches_period(entry.timestamp.date_naive(), period_range))
+                .map(|entry| UsageRecord {
+                    timestamp: entry.timestamp,
+                    agent: entry.agent,
+                    model: entry.model,
+                    tokens: entry.tokens,
+                    cost_usd: entry.cost_usd,
+                })
+                .collect();
+            records.sort_by_key(|record| record.timestamp);
+
+            let records = Arc::new(records);
+            *cache = Some(UsageRecordsCache {
+                period: period_key,
+                scanned_at: Instant::now(),
+                model_aliases,
+                records: records.clone(),
+            });
+            records
+        }
+    };
+    drop(cache);
+
+    let total = records.len();
+    let end = offset.saturating_add(limit.max(1)).min(total);
+    let page = records.get(offset..end).unwrap_or_default().to_vec();
+
+    Response::UsageRecords(UsageRecordsPage {
+        records: page,
+        total,
+        next_offset: (end < total).then_some(end),
+    })
+}
+
+/// Get current spend against the budgets configured in `[usage]`.
+pub async fn get_budget(state: &ServerState) -> Response {
+    let config = UserConfig::load(&state.paths.config_file()).unwrap_or_default();
+    match budget_statuses(&config.usage, state).await {
+        Ok(statuses) => Response::UsageBudget(statuses),
+        Err(message) => Response::error(error_codes::INTERNAL_ERROR, message),
+    }
+}
+
+/// Compute spend for each configured budget.
+///
+/// A single month-to-date query covers both windows: today's spend is read
+/// from the month's per-date breakdown.
+pub(crate) async fn budget_statuses(
+    config: &UsageConfig,
+    state: &ServerState,
+) -> Result<Vec<BudgetStatus>, String> {
+    let budgets = config.budgets();
+    if budgets.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let usage = match get_usage(Some(&UsagePeriod::ThisMonth), None, None, None, state).await {
+        Response::Usage(usage) => usage,
+        Response::Error { message, .. } => return Err(message),
+        _ => return Err("Unexpected response while computing spend".to_string()),
+    };
+
+    let today = Utc::now().date_naive().to_string();
+    let spent_today = usage
+        .aggregates
+        .by_date
+        .get(&today)
+        .and_then(|day| day.cost.as_ref())
+        .map_or(0.0, |cost| cost.total_cost);
+    let spent_month = usage
+        .total_cost
+        .as_ref()
+        .map_or(0.0, |cost| cost.total_cost);
+
+    Ok(budgets
+        .into_iter()
+        .map(|(period, limit)| {
+            let spent = match period {
+                BudgetPeriod::Daily => spent_today,
+                BudgetPeriod::Monthly => spent_month,
+            };
+            BudgetStatus::new(period, limit, spent)
+        })
+        .collect())
+}
+
+/// Compute a profile's spend against its quotas.
+///
+/// Only the profile's recorded sessions count: agent-native usage files can't
+/// be attributed to a profile (see [`get_usage`]). Spend comes from the
+/// telemetry aggregates, so this doesn't read the whole sessions log.
+pub(crate) fn quota_statuses(
+    alias: &str,
+    quotas: &[(BudgetPeriod, f64)],
+    state: &ServerState,
+) -> Result<Vec<BudgetStatus>, String> {
+    if quotas.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let daily_costs = state
+        .telemetry
+        .profile_daily_costs(alias)
+        .map_err(|e| format!("Failed to load usage aggregates: {}", e))?;
+    let today = Utc::now().date_naive();
+
+    Ok(quotas
+        .iter()
+        .map(|&(period, limit)| {
+            let window = period.window_key(today);
+            let spent = daily_costs
+                .iter()
+                .filter(|(date, _)| {
+                    date.parse::<NaiveDate>()
+                        .is_ok_and(|date| period.window_key(date) == window)
+                })
+                .map(|(_, cost)| cost)
+                .sum();
+            BudgetStatus::new(period, limit, spent)
+        })
+        .collect())
+}
+
 /// Merge filtered agent-native usage data into usage aggregates.
-fn merge_agent_scan_entries(aggregates: &mut UsageAggregates, entries: &[agent_usage::UsageEntry]) {
+///
+/// Returns the runtime of the sessions the entries belong to. Agent sessions
+/// that ran inside one of the telemetry `sessions` are left out, as that run
+/// already counts their runtime.
+fn merge_agent_scan_entries(
+    aggregates: &mut UsageAggregates,
+    entries: &[agent_usage::UsageEntry],
+    sessions: &[Session],
+    pricing: &PricingLoader,
+) -> u64 {
     for entry in entries {
+        let cost = entry.cost_breakdown(pricing);
+
         let model_usage = aggregates
             .by_model
             .entry(entry.model.clone())
@@ -124,12 +399,13 @@ fn merge_agent_scan_entries(aggregates: &mut UsageAggregates, entries: &[agent_u
                 tokens: TokenUsage::default(),
                 cost: None,
                 sessions: 0,
+                runtime_secs: 0,
             });
         model_usage.tokens += entry.tokens.clone();
         model_usage.sessions += 1;
 
-        if let Some(cost_usd) = entry.cost_usd {
-            add_cost(&mut model_usage.cost, cost_usd);
+        if let Some(cost) = &cost {
+            add_cost(&mut model_usage.cost, cost);
         }
 
         let date_key = entry.timestamp.date_naive().to_string();
@@ -142,8 +418,8 @@ fn merge_agent_scan_entries(aggregates: &mut UsageAggregates, entries: &[agent_u
             });
         daily_usage.tokens += entry.tokens.clone();
         daily_usage.sessions += 1;
-        if let Some(cost_usd) = entry.cost_usd {
-            add_cost(&mut daily_usage.cost, cost_usd);
+        if let Some(cost) = &cost {
+            add_cost(&mut daily_usage.cost, cost);
         }
 
         let agent_id = entry.agent.to_string();
@@ -156,21 +432,48 @@ fn merge_agent_scan_entries(aggregates: &mut UsageAggregates, entries: &[agent_u
             });
         agent_usage.tokens += entry.tokens.clone();
         agent_usage.sessions += 1;
-        if let Some(cost_usd) = entry.cost_usd {
-            add_cost(&mut agent_usage.cost, cost_usd);
+        if let Some(cost) = &cost {
+            add_cost(&mut agent_usage.cost, cost);
         }
 
         aggregates.total_tokens += entry.tokens.clone();
-        if let Some(cost_usd) = entry.cost_usd {
-            add_cost(&mut aggregates.total_cost, cost_usd);
+        if let Some(cost) = &cost {
+            add_cost(&mut aggregates.total_cost, cost);
         }
     }
+
+    let mut runtime_secs = 0;
+    for ((agent, _), (first, last)) in agent_usage::session_spans(entries) {
+        let counted = sessions.iter().any(|session| {
+            session.duration_secs.is_some()
+                && session.agent_id.parse::<AgentType>() == Ok(agent)
+                && session.started_at <= first
+                && session.ended_at.is_some_and(|ended_at| last <= ended_at)
+        });
+        if counted {
+            continue;
+        }
+        let secs = (last - first).num_seconds().max(0) as u64;
+        if let Some(agent_usage) = aggregates.by_agent.get_mut(&agent.to_string()) {
+            agent_usage.runtime_secs += secs;
+        }
+        runtime_secs += secs;
+    }
+    runtime_secs
 }
 
 /// Import usage data from Claude's native files.
-pub async fn import_claude(claude_dir: Option<&PathBuf>, _state: &ServerState) -> Response {
+pub async fn import_claude(claude_dir: Option<&PathBuf>, state: &ServerState) -> Response {
+    let config = UserConfig::load(&state.paths.config_file()).unwrap_or_default();
     let claude_home = claude_dir
         .cloned()
+        .or_else(|| {
+            config
+                .usage
+                .claude_dir
+                .as_deref()
+                .map(ringlet_core::expand_tilde)
+        })
         .or_else(crate::daemon::claude_import::default_claude_dir);
 
     let Some(claude_path) = claude_home else {
@@ -189,7 +492,16 @@ pub async fn import_claude(claude_dir: Option<&PathBuf>, _state: &ServerState) -
 
     info!("Importing Claude usage data from {:?}", claude_path);
 
-    match crate::daemon::claude_import::import_all(&claude_path) {
+    let on_progress = |current, total, message: &str| {
+        state.broadcast(Event::Progress {
+            op: "usage_import".to_string(),
+            current,
+            total,
+            message: Some(message.to_string()),
+        });
+    };
+
+    match crate::daemon::claude_import::import_all(&claude_path, on_progress) {
         Ok(result) => {
             let mut message = format!(
                 "Imported {} input tokens, {} output tokens from stats-cache.json",
@@ -213,6 +525,126 @@ pub async fn import_claude(claude_dir: Option<&PathBuf>, _state: &ServerState) -
     }
 }
 
+/// Most malformed rows listed when an import is rejected.
+const MAX_IMPORT_ERRORS: usize = 20;
+
+/// Import usage rows computed outside ringlet.
+pub async fn import(format: UsageImportFormat, content: &str, state: &ServerState) -> Response {
+    let entries = match agent_usage::imported::parse(content, format) {
+        Ok(entries) => entries,
+        Err(errors) => {
+            let mut message = format!(
+                "Rejected import: {} malformed row{}",
+                errors.len(),
+                if errors.len() == 1 { "" } else { "s" }
+            );
+            for error in errors.iter().take(MAX_IMPORT_ERRORS) {
+                message.push_str("\n  ");
+                message.push_str(error);
+            }
+            if errors.len() > MAX_IMPORT_ERRORS {
+                message.push_str(&format!(
+                    "\n  ... and {} more",
+                    errors.len() - MAX_IMPORT_ERRORS
+                ));
+            }
+            return Response::error(error_codes::INVALID_USAGE_IMPORT, message);
+        }
+    };
+
+    let added = match agent_usage::imported::append(&state.paths.imported_usage_log(), &entries) {
+        Ok(added) => added,
+        Err(e) => {
+            return Response::error(error_codes::INTERNAL_ERROR, format!("Import failed: {}", e));
+        }
+    };
+    *state.usage_records_cache.lock().await = None;
+    info!("Imported {} of {} usage rows", added, entries.len());
+
+    let mut message = format!(
+        "Imported {} usage row{}",
+        added,
+        if added == 1 { "" } else { "s" }
+    );
+    if added < entries.len() {
+        message.push_str(&format!(" ({} already imported)", entries.len() - added));
+    }
+    Response::success(message)
+}
+
+/// Delete telemetry sessions and/or cached usage data.
+pub async fn reset(
+    telemetry: bool,
+    cache: bool,
+    before: Option<NaiveDate>,
+    state: &ServerState,
+) -> Response {
+    // Neither flag means everything
+    let (telemetry, cache) = if telemetry || cache {
+        (telemetry, cache)
+    } else {
+        (true, true)
+    };
+    let mut cleared = Vec::new();
+
+    if telemetry {
+        match state.telemetry.purge_sessions(before) {
+            Ok(removed) => {
+                info!("Removed {} telemetry sessions", removed);
+                cleared.push(format!(
+                    "removed {} telemetry session{}",
+                    removed,
+                    if removed == 1 { "" } else { "s" }
+                ));
+            }
+            Err(e) => {
+                return Response::error(
+                    error_codes::INTERNAL_ERROR,
+                    format!("Failed to purge telemetry: {}", e),
+                );
+            }
+        }
+
+        match agent_usage::imported::purge(&state.paths.imported_usage_log(), before) {
+            Ok(0) => {}
+            Ok(removed) => {
+                info!("Removed {} imported usage rows", removed);
+                cleared.push(format!(
+                    "removed {} imported usage row{}",
+                    removed,
+                    if removed == 1 { "" } else { "s" }
+                ));
+            }
+            Err(e) => {
+                return Response::error(
+                    error_codes::INTERNAL_ERROR,
+                    format!("Failed to purge imported usage: {}", e),
+                );
+            }
+        }
+    }
+
+    if cache {
+        let path = state.paths.budget_alerts_file();
+        if path.exists()
+            && let Err(e) = std::fs::remove_file(&path)
+        {
+            return Response::error(
+                error_codes::INTERNAL_ERROR,
+                format!("Failed to remove {}: {}", path.display(), e),
+            );
+        }
+        *state.usage_records_cache.lock().await = None;
+        cleared.push("cleared usage cache and budget alert state".to_string());
+    }
+
+    let mut message = cleared.join("; ");
+    if let Some(first) = message.get_mut(..1) {
+        first.make_ascii_uppercase();
+    }
+    Response::success(message)
+}
+
 /// Format period for display.
 fn format_period(period: &UsagePeriod) -> String {
     match period {
@@ -301,13 +733,265 @@ fn matches_period(date: NaiveDate, range: Option<(NaiveDate, NaiveDate)>) -> boo
     }
 }
 
-fn add_cost(cost: &mut Option<CostBreakdown>, total_cost: f64) {
-    if let Some(existing) = cost {
-        existing.total_cost += total_cost;
-    } else {
-        *cost = Some(CostBreakdown {
-            total_cost,
-            ..Default::default()
-        });
+fn add_cost(total: &mut Option<CostBreakdown>, cost: &CostBreakdown) {
+    match total {
+        Some(existing) => *existing += cost.clone(),
+        None => *total = Some(cost.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// State whose agent data directories are empty, with `count` imported
+    /// usage entries one minute apart.
+    fn records_state(dir: &std::path::Path) -> ServerState {
+        let state = ServerState::for_tests(dir);
+        let agents = dir.join("agents");
+        std::fs::write(
+            state.paths.config_file(),
+            format!(
+                "[security]\nsecret_backend = \"file\"\n\n[usage]\n\
+                 claude_dir = {:?}\ncodex_dir = {:?}\nopencode_dir = {:?}\n",
+                agents.join("claude"),
+                agents.join("codex"),
+                agents.join("opencode"),
+            ),
+        )
+        .unwrap();
+        state
+    }
+
+    fn usage_entry(i: u32, model: &str, cost_usd: Option<f64>) -> agent_usage::UsageEntry {
+        let start = Utc.with_ymd_and_hms(2025, 1, 20, 12, 0, 0).unwrap();
+        agent_usage::UsageEntry {
+            timestamp: start + Duration::minutes(i.into()),
+            agent: ringlet_core::AgentType::Claude,
+            message_id: format!("msg-{}", i),
+            request_id: None,
+            model: model.to_string(),
+            tokens: TokenUsage {
+                input_tokens: 10,
+                ..Default::default()
+            },
+            cost_usd,
+            project_path: String::new(),
+            session_id: None,
+        }
+    }
+
+    fn import(state: &ServerState, ids: std::ops::Range<u32>) {
+        let entries: Vec<_> = ids
+            .map(|i| usage_entry(i, "claude-sonnet-4", Some(0.01)))
+            .collect();
+        agent_usage::imported::append(&state.paths.imported_usage_log(), &entries).unwrap();
+    }
+
+    async fn page(state: &ServerState, offset: usize, limit: usize) -> UsageRecordsPage {
+        match get_records(Some(&UsagePeriod::All), offset, limit, state).await {
+            Response::UsageRecords(page) => page,
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_records_are_paged_oldest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = records_state(dir.path());
+        import(&state, 0..5);
+
+        let first = page(&state, 0, 2).await;
+        assert_eq!(first.total, 5);
+        assert_eq!(first.next_offset, Some(2));
+        assert!(first.records[0].timestamp < first.records[1].timestamp);
+
+        let last = page(&state, 4, 2).await;
+        assert_eq!(last.records.len(), 1);
+        assert_eq!(last.next_offset, None);
+        assert!(first.records[1].timestamp < last.records[0].timestamp);
+    }
+
+    #[tokio::test]
+    async fn test_later_pages_reuse_the_first_scan() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = records_state(dir.path());
+        import(&state, 0..3);
+        assert_eq!(page(&state, 0, 2).await.total, 3);
+
+        // Usage recorded while paging doesn't shift the offsets
+        import(&state, 3..6);
+        assert_eq!(page(&state, 2, 2).await.total, 3);
+
+        // A new export starts with a fresh scan
+        assert_eq!(page(&state, 0, 2).await.total, 6);
+    }
+
+    #[tokio::test]
+    async fn test_unpriced_models_are_counted() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = records_state(dir.path());
+        let cache = state.paths.litellm_pricing_cache();
+        std::fs::create_dir_all(cache.parent().unwrap()).unwrap();
+        std::fs::write(
+            &cache,
+            r#"{"claude-sonnet-4": {"input_cost_per_token": 0.000003}}"#,
+        )
+        .unwrap();
+
+        let entries = [
+            usage_entry(0, "gpt-x", None),
+            usage_entry(1, "gpt-x", None),
+            // Priced from the table
+            usage_entry(2, "claude-sonnet-4", None),
+            // Cost reported by the agent
+            usage_entry(3, "my-finetune", Some(0.5)),
+        ];
+        agent_usage::imported::append(&state.paths.imported_usage_log(), &entries).unwrap();
+
+        let now = Utc::now();
+        let session = |provider_id: &str, model: &str| crate::daemon::telemetry::Session {
+            session_id: String::new(),
+            profile: "work".to_string(),
+            agent_id: "claude".to_string(),
+            provider_id: provider_id.to_string(),
+            started_at: now,
+            ended_at: Some(now),
+            duration_secs: Some(1),
+            exit_code: Some(0),
+            source: crate::daemon::telemetry::SessionSource::ProfileRun,
+            model: Some(model.to_string()),
+            tokens: Some(TokenUsage {
+                input_tokens: 10,
+                ..Default::default()
+            }),
+            cost: None,
+        };
+        state
+            .telemetry
+            .record_session(&session("self", "local-llama"))
+            .unwrap();
+        // Only `self` sessions are priced, so others aren't missing anything
+        state
+            .telemetry
+            .record_session(&session("minimax", "MiniMax-M2"))
+            .unwrap();
+
+        let usage = match get_usage(Some(&UsagePeriod::All), None, None, None, &state).await {
+            Response::Usage(usage) => usage,
+            other => panic!("unexpected response: {:?}", other),
+        };
+        assert_eq!(usage.unpriced_models, ["gpt-x", "local-llama"]);
+        assert_eq!(usage.unpriced_entries, 3);
+    }
+
+    #[tokio::test]
+    async fn test_runtime_of_a_ringlet_run_is_counted_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = records_state(dir.path());
+        let in_session = |i, session_id: &str| agent_usage::UsageEntry {
+            session_id: Some(session_id.to_string()),
+            ..usage_entry(i, "claude-sonnet-4", Some(0.01))
+        };
+        // Session "run" is the agent's log of the ringlet run below; "own"
+        // was started outside ringlet
+        let entries = [
+            in_session(0, "run"),
+            in_session(10, "run"),
+            in_session(30, "own"),
+            in_session(35, "own"),
+        ];
+        agent_usage::imported::append(&state.paths.imported_usage_log(), &entries).unwrap();
+
+        let started_at = Utc.with_ymd_and_hms(2025, 1, 20, 11, 55, 0).unwrap();
+        let session = Session {
+            session_id: String::new(),
+            profile: "work".to_string(),
+            agent_id: "claude".to_string(),
+            provider_id: "anthropic".to_string(),
+            started_at,
+            ended_at: Some(started_at + Duration::minutes(25)),
+            duration_secs: Some(25 * 60),
+            exit_code: Some(0),
+            source: crate::daemon::telemetry::SessionSource::ProfileRun,
+            model: None,
+            tokens: None,
+            cost: None,
+        };
+        state.telemetry.record_session(&session).unwrap();
+
+        let usage = match get_usage(Some(&UsagePeriod::All), None, None, None, &state).await {
+            Response::Usage(usage) => usage,
+            other => panic!("unexpected response: {:?}", other),
+        };
+        assert_eq!(usage.total_runtime_secs, 25 * 60 + 5 * 60);
+        assert_eq!(
+            usage.aggregates.by_agent["claude"].runtime_secs,
+            25 * 60 + 5 * 60
+        );
+    }
+
+    #[tokio::test]
+    async fn test_models_of_ringlet_runs_come_from_telemetry() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = records_state(dir.path());
+        // The agent has logged one request of the run so far
+        let entries = [agent_usage::UsageEntry {
+            session_id: Some("run".to_string()),
+            ..usage_entry(5, "claude-opus-4", None)
+        }];
+        agent_usage::imported::append(&state.paths.imported_usage_log(), &entries).unwrap();
+
+        let started_at = Utc.with_ymd_and_hms(2025, 1, 20, 12, 0, 0).unwrap();
+        let session = |model: &str, minutes: i64| Session {
+            session_id: String::new(),
+            profile: "work".to_string(),
+            agent_id: "claude".to_string(),
+            provider_id: "anthropic".to_string(),
+            started_at,
+            ended_at: Some(started_at + Duration::minutes(minutes)),
+            duration_secs: Some(minutes as u64 * 60),
+            exit_code: Some(0),
+            source: crate::daemon::telemetry::SessionSource::ProfileRun,
+            model: Some(model.to_string()),
+            tokens: None,
+            cost: None,
+        };
+        state
+            .telemetry
+            .record_session(&session("claude-opus-4", 10))
+            .unwrap();
+        // No agent files were written for this run
+        state
+            .telemetry
+            .record_session(&session("claude-haiku-4", 3))
+            .unwrap();
+
+        let usage = match get_usage(Some(&UsagePeriod::All), None, None, None, &state).await {
+            Response::Usage(usage) => usage,
+            other => panic!("unexpected response: {:?}", other),
+        };
+        let by_model = &usage.aggregates.by_model;
+        assert_eq!(by_model["claude-haiku-4"].sessions, 1);
+        assert_eq!(by_model["claude-haiku-4"].runtime_secs, 3 * 60);
+        assert_eq!(by_model["claude-opus-4"].runtime_secs, 10 * 60);
+        assert_eq!(by_model["claude-opus-4"].tokens.input_tokens, 10);
+
+        let usage = match get_usage(
+            Some(&UsagePeriod::All),
+            None,
+            Some("claude-haiku-4"),
+            None,
+            &state,
+        )
+        .await
+        {
+            Response::Usage(usage) => usage,
+            other => panic!("unexpected response: {:?}", other),
+        };
+        assert_eq!(usage.total_sessions, 1);
+        assert_eq!(usage.total_runtime_secs, 3 * 60);
+        assert!(!usage.aggregates.by_model.contains_key("claude-opus-4"));
     }
 }
@@ -0,0 +1,163 @@
+//! Daemon health reporting.
+//!
+//! Collects per-subsystem status for `/api/health` and keeps a short log of
+//! recent subsystem errors so monitoring can see why a check is failing.
+
+use crate::daemon::server::ServerState;
+use chrono::{Duration as ChronoDuration, Utc};
+use ringlet_core::http_api::{HealthResponse, HealthState, RecentError, SubsystemHealth};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::atomic::Ordering;
+
+/// Maximum number of errors kept in the log.
+const MAX_ERRORS: usize = 20;
+
+/// Errors older than this are not reported.
+const ERROR_WINDOW_MINS: i64 = 60;
+
+/// Bounded log of recent subsystem errors.
+#[derive(Default)]
+pub struct ErrorLog {
+    errors: Mutex<VecDeque<RecentError>>,
+}
+
+impl ErrorLog {
+    /// Record an error for a subsystem.
+    pub fn record(&self, subsystem: &str, message: impl Into<String>) {
+        let mut errors = self.errors.lock().unwrap_or_else(|e| e.into_inner());
+        if errors.len() == MAX_ERRORS {
+            errors.pop_front();
+        }
+        errors.push_back(RecentError {
+            subsystem: subsystem.to_string(),
+            message: message.into(),
+            at: Utc::now(),
+        });
+    }
+
+    /// Errors recorded within the reporting window, newest first.
+    pub fn recent(&self) -> Vec<RecentError> {
+        let cutoff = Utc::now() - ChronoDuration::minutes(ERROR_WINDOW_MINS);
+        let errors = self.errors.lock().unwrap_or_else(|e| e.into_inner());
+        errors
+            .iter()
+            .rev()
+            .filter(|error| error.at >= cutoff)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Build a health report for the daemon.
+pub fn report(state: &ServerState) -> HealthResponse {
+    let subsystems = vec![
+        usage_watcher_health(state),
+        registry_health(state),
+        proxy_health(state),
+    ];
+
+    HealthResponse {
+        status: overall(&subsystems),
+        version: ringlet_core::VERSION.to_string(),
+        uptime_secs: state.started_at.elapsed().as_secs(),
+        subsystems,
+        recent_errors: state.errors.recent(),
+    }
+}
+
+fn usage_watcher_health(state: &ServerState) -> SubsystemHealth {
+    let running = state.usage_watcher_running.load(Ordering::Relaxed);
+    SubsystemHealth {
+        name: "usage_watcher".to_string(),
+        healthy: running,
+        core: true,
+        detail: (!running).then(|| "Usage watcher is not running".to_string()),
+    }
+}
+
+fn registry_health(state: &ServerState) -> SubsystemHealth {
+    let (healthy, detail) = match state.registry_client.get_status(true) {
+        Ok(status) => match status.last_sync {
+            Some(last) if !status.stale => {
+                (true, Some(format!("Last synced {}", last.to_rfc3339())))
+            }
+            Some(last) => (
+                true,
+                Some(format!("Stale: last synced {}", last.to_rfc3339())),
+            ),
+            None => (true, Some("Never synced".to_string())),
+        },
+        Err(e) => (false, Some(format!("Failed to read registry: {}", e))),
+    };
+
+    SubsystemHealth {
+        name: "registry".to_string(),
+        healthy,
+        core: true,
+        detail,
+    }
+}
+
+fn proxy_health(state: &ServerState) -> SubsystemHealth {
+    let available = state.proxy_manager.is_available();
+    SubsystemHealth {
+        name: "proxy".to_string(),
+        healthy: available,
+        core: false,
+        detail: (!available).then(|| "ultrallm binary not found".to_string()),
+    }
+}
+
+/// Combine subsystem checks into an overall state.
+fn overall(subsystems: &[SubsystemHealth]) -> HealthState {
+    if subsystems.iter().any(|s| s.core && !s.healthy) {
+        HealthState::Unhealthy
+    } else if subsystems.iter().any(|s| !s.healthy) {
+        HealthState::Degraded
+    } else {
+        HealthState::Ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subsystem(healthy: bool, core: bool) -> SubsystemHealth {
+        SubsystemHealth {
+            name: "test".to_string(),
+            healthy,
+            core,
+            detail: None,
+        }
+    }
+
+    #[test]
+    fn test_overall_state() {
+        assert_eq!(
+            overall(&[subsystem(true, true), subsystem(true, false)]),
+            HealthState::Ok
+        );
+        assert_eq!(
+            overall(&[subsystem(true, true), subsystem(false, false)]),
+            HealthState::Degraded
+        );
+        assert_eq!(
+            overall(&[subsystem(false, true), subsystem(true, false)]),
+            HealthState::Unhealthy
+        );
+    }
+
+    #[test]
+    fn test_error_log_is_bounded() {
+        let log = ErrorLog::default();
+        for i in 0..MAX_ERRORS + 5 {
+            log.record("registry", format!("error {}", i));
+        }
+
+        let recent = log.recent();
+        assert_eq!(recent.len(), MAX_ERRORS);
+        assert_eq!(recent[0].message, format!("error {}", MAX_ERRORS + 4));
+    }
+}
@@ -5,10 +5,11 @@
 use axum::{
     body::Body,
     extract::Path,
-    http::{Response, StatusCode, header},
+    http::{HeaderMap, Response, StatusCode, header},
     response::IntoResponse,
 };
 use rust_embed::Embed;
+use std::fmt::Write;
 
 /// Embedded UI assets from the ui-dist directory.
 #[derive(Embed)]
@@ -16,67 +17,141 @@ use rust_embed::Embed;
 struct Assets;
 
 /// Serve a static file from the embedded assets.
-pub async fn serve_static(Path(path): Path<String>) -> impl IntoResponse {
+pub async fn serve_static(Path(path): Path<String>, headers: HeaderMap) -> impl IntoResponse {
     // The path parameter doesn't include "assets/", so we need to add it
     let full_path = format!("assets/{}", path);
-    serve_file(&full_path)
+    serve_file(&full_path, &headers)
 }
 
 /// Serve the index.html for SPA routing.
-pub async fn serve_index() -> impl IntoResponse {
-    serve_file("index.html")
+pub async fn serve_index(headers: HeaderMap) -> impl IntoResponse {
+    serve_file("index.html", &headers)
 }
 
 /// Serve the favicon.
-pub async fn serve_favicon() -> impl IntoResponse {
-    serve_file("favicon.svg")
+pub async fn serve_favicon(headers: HeaderMap) -> impl IntoResponse {
+    serve_file("favicon.svg", &headers)
 }
 
-/// Serve a file by path, with proper content type.
-fn serve_file(path: &str) -> Response<Body> {
+/// Serve a file by path, with proper content type and a content-hash ETag.
+///
+/// Answers `304 Not Modified` when the request's `If-None-Match` matches.
+fn serve_file(path: &str, headers: &HeaderMap) -> Response<Body> {
     // Try to get the file from embedded assets
-    match Assets::get(path) {
-        Some(content) => {
-            let mime = mime_guess::from_path(path).first_or_octet_stream();
-
-            Response::builder()
-                .status(StatusCode::OK)
-                .header(header::CONTENT_TYPE, mime.as_ref())
-                .header(header::CACHE_CONTROL, cache_control_for(path))
-                .body(Body::from(content.data.into_owned()))
-                .unwrap()
-        }
+    let (path, content) = match Assets::get(path) {
+        Some(content) => (path, content),
         None => {
             // For SPA routing: if file not found and not an API/asset request,
             // serve index.html
-            if !path.starts_with("api/")
-                && !path.contains('.')
-                && let Some(content) = Assets::get("index.html")
-            {
-                return Response::builder()
-                    .status(StatusCode::OK)
-                    .header(header::CONTENT_TYPE, "text/html")
-                    .header(header::CACHE_CONTROL, "no-cache")
-                    .body(Body::from(content.data.into_owned()))
-                    .unwrap();
+            match Assets::get("index.html") {
+                Some(content) if !path.starts_with("api/") && !path.contains('.') => {
+                    ("index.html", content)
+                }
+                _ => {
+                    return Response::builder()
+                        .status(StatusCode::NOT_FOUND)
+                        .body(Body::from("Not Found"))
+                        .unwrap();
+                }
             }
-
-            Response::builder()
-                .status(StatusCode::NOT_FOUND)
-                .body(Body::from("Not Found"))
-                .unwrap()
         }
+    };
+
+    let etag = etag_for(&content.metadata.sha256_hash());
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| etag_matches(value, &etag));
+
+    let builder = Response::builder()
+        .header(header::ETAG, &etag)
+        .header(header::CACHE_CONTROL, cache_control_for(path));
+    if not_modified {
+        return builder
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Body::empty())
+            .unwrap();
+    }
+
+    let mime = mime_guess::from_path(path).first_or_octet_stream();
+    builder
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, mime.as_ref())
+        .body(Body::from(content.data.into_owned()))
+        .unwrap()
+}
+
+/// Strong ETag from the first 16 bytes of a file's SHA-256 hash.
+fn etag_for(hash: &[u8; 32]) -> String {
+    let mut etag = String::from("\"");
+    for byte in &hash[..16] {
+        let _ = write!(etag, "{:02x}", byte);
     }
+    etag.push('"');
+    etag
+}
+
+/// Whether an `If-None-Match` header value matches `etag`.
+///
+/// Weak comparison, as RFC 9110 requires for `If-None-Match`.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
+    })
 }
 
 /// Determine cache control header based on file type.
 fn cache_control_for(path: &str) -> &'static str {
-    // Assets with hashes in filename can be cached forever
-    if path.starts_with("assets/") && (path.contains("-") || path.contains(".")) {
+    // Assets with a content hash in their filename change name on upgrade
+    if is_hashed_asset(path) {
         "public, max-age=31536000, immutable"
-    } else if path.ends_with(".html") {
-        "no-cache"
     } else {
-        "public, max-age=3600"
+        // Revalidate with the ETag so an upgrade is picked up right away
+        "no-cache"
+    }
+}
+
+/// Whether `path` is a build asset named `<name>-<8 char hash>.<ext>`.
+fn is_hashed_asset(path: &str) -> bool {
+    let Some(file) = path.strip_prefix("assets/") else {
+        return false;
+    };
+    let stem = file.rsplit_once('.').map_or(file, |(stem, _)| stem);
+    let Some(split) = stem.len().checked_sub(9) else {
+        return false;
+    };
+    stem.as_bytes()[split] == b'-'
+        && stem.as_bytes()[split + 1..]
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || *b == b'_' || *b == b'-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_control_for() {
+        assert_eq!(
+            cache_control_for("assets/AgentsView-vQ5kJX_f.js"),
+            "public, max-age=31536000, immutable"
+        );
+        assert_eq!(
+            cache_control_for("assets/agents-D-cRERJQ.js"),
+            "public, max-age=31536000, immutable"
+        );
+        assert_eq!(cache_control_for("assets/logo.svg"), "no-cache");
+        assert_eq!(cache_control_for("index.html"), "no-cache");
+        assert_eq!(cache_control_for("favicon.svg"), "no-cache");
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        let etag = etag_for(&[0xab; 32]);
+        assert_eq!(etag, format!("\"{}\"", "ab".repeat(16)));
+        assert!(etag_matches(&etag, &etag));
+        assert!(etag_matches(&format!("\"old\", W/{}", etag), &etag));
+        assert!(etag_matches("*", &etag));
+        assert!(!etag_matches("\"old\"", &etag));
     }
 }
@@ -63,15 +63,26 @@ impl ApiError {
             | error_codes::ROUTE_NOT_FOUND
             | error_codes::ALIAS_NOT_FOUND => StatusCode::NOT_FOUND,
 
-            error_codes::PROFILE_EXISTS | error_codes::PROXY_ALREADY_RUNNING => {
-                StatusCode::CONFLICT
-            }
+            error_codes::PROFILE_EXISTS
+            | error_codes::PROVIDER_EXISTS
+            | error_codes::PROXY_ALREADY_RUNNING => StatusCode::CONFLICT,
 
             error_codes::AGENT_NOT_INSTALLED
             | error_codes::INCOMPATIBLE_PROVIDER
             | error_codes::INVALID_ENDPOINT
+            | error_codes::INVALID_MODEL
+            | error_codes::INVALID_LAUNCH_WRAPPER
+            | error_codes::INVALID_QUOTA
+            | error_codes::INVALID_USAGE_IMPORT
+            | error_codes::TOKEN_EXPIRED
             | error_codes::HOOKS_NOT_SUPPORTED
             | error_codes::INVALID_HOOK_EVENT
+            | error_codes::INVALID_RUN_HOOK
+            | error_codes::INVALID_HOOK_CONFIG
+            | error_codes::UNSAFE_HOOK_COMMAND
+            | error_codes::INVALID_BUNDLE
+            | error_codes::UNTRUSTED_BUNDLE
+            | error_codes::INVALID_PARAMS
             | error_codes::PROXY_NOT_ENABLED
             | error_codes::PROXY_NOT_RUNNING
             | error_codes::PROXY_NOT_SUPPORTED => StatusCode::BAD_REQUEST,
@@ -81,6 +92,14 @@ impl ApiError {
             | error_codes::EXECUTION_ERROR
             | error_codes::REGISTRY_ERROR => StatusCode::INTERNAL_SERVER_ERROR,
 
+            error_codes::RATE_LIMITED | error_codes::QUOTA_EXCEEDED => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+
+            error_codes::UNAUTHORIZED => StatusCode::UNAUTHORIZED,
+
+            error_codes::NOT_IMPLEMENTED => StatusCode::NOT_IMPLEMENTED,
+
             error_codes::INTERNAL_ERROR => StatusCode::INTERNAL_SERVER_ERROR,
 
             _ => StatusCode::INTERNAL_SERVER_ERROR,
@@ -116,6 +135,11 @@ impl HttpError {
         Self::new(error_codes::PROFILE_NOT_FOUND, message)
     }
 
+    /// A malformed request parameter (JSON-RPC "invalid params").
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(error_codes::INVALID_PARAMS, message)
+    }
+
     pub fn forbidden(message: impl Into<String>) -> Self {
         Self {
             status: StatusCode::FORBIDDEN,
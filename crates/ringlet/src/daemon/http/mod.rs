@@ -7,6 +7,7 @@ pub mod assets;
 pub mod auth;
 pub mod error;
 pub mod path_access;
+pub mod rate_limit;
 pub mod routes;
 pub mod server;
 pub mod terminal_policy;
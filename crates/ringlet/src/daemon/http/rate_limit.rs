@@ -0,0 +1,123 @@
+//! Token-bucket rate limiting for expensive endpoints.
+
+use crate::daemon::http::error::HttpError;
+use axum::{
+    extract::{Request, State},
+    http::{HeaderValue, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use ringlet_core::rpc::error_codes;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// A global token bucket that refills continuously.
+pub struct TokenBucket {
+    /// Maximum tokens (also the burst size).
+    capacity: f64,
+    /// Tokens added per second.
+    refill_per_sec: f64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Create a bucket allowing `per_min` requests per minute, starting full.
+    pub fn per_minute(per_min: u32) -> Self {
+        let capacity = f64::from(per_min);
+        Self {
+            capacity,
+            refill_per_sec: capacity / 60.0,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Take a token, or return how long until one is available.
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        self.try_acquire_at(Instant::now())
+    }
+
+    fn try_acquire_at(&self, now: Instant) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let elapsed = now.saturating_duration_since(state.last_refill);
+        state.tokens =
+            (state.tokens + elapsed.as_secs_f64() * self.refill_per_sec).min(self.capacity);
+        state.last_refill = now;
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            Ok(())
+        } else {
+            let missing = 1.0 - state.tokens;
+            Err(Duration::from_secs_f64(missing / self.refill_per_sec))
+        }
+    }
+}
+
+/// Reject requests with 429 once the bucket is empty.
+pub async fn rate_limit_middleware(
+    State(bucket): State<Arc<TokenBucket>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match bucket.try_acquire() {
+        Ok(()) => next.run(request).await,
+        Err(wait) => {
+            let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+            warn!(
+                "Rate limit exceeded for {} {}, retry after {}s",
+                request.method(),
+                request.uri().path(),
+                retry_after
+            );
+
+            let mut response = HttpError::new(
+                error_codes::RATE_LIMITED,
+                format!("Too many requests, retry after {}s", retry_after),
+            )
+            .into_response();
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let bucket = TokenBucket::per_minute(2);
+        let start = Instant::now();
+
+        assert!(bucket.try_acquire_at(start).is_ok());
+        assert!(bucket.try_acquire_at(start).is_ok());
+
+        // Empty: one token takes 30s at 2/min.
+        let wait = bucket.try_acquire_at(start).unwrap_err();
+        assert_eq!(wait.as_secs(), 30);
+
+        assert!(
+            bucket
+                .try_acquire_at(start + Duration::from_secs(30))
+                .is_ok()
+        );
+        assert!(
+            bucket
+                .try_acquire_at(start + Duration::from_secs(30))
+                .is_err()
+        );
+    }
+}
@@ -5,12 +5,20 @@ use crate::daemon::http::error::{ApiResponse, HttpError};
 use crate::daemon::server::ServerState;
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
 };
 use ringlet_core::http_api::AddHookRequest;
 use ringlet_core::{HooksConfig, Response};
+use serde::Deserialize;
 use std::sync::Arc;
 
+#[derive(Debug, Deserialize)]
+pub struct ImportHooksQuery {
+    /// Save commands even if they look unsafe.
+    #[serde(default)]
+    pub force: bool,
+}
+
 /// GET /api/profiles/:alias/hooks - List hooks.
 pub async fn list(
     State(state): State<Arc<ServerState>>,
@@ -36,6 +44,8 @@ pub async fn add(
         &request.event,
         &request.matcher,
         &request.command,
+        false,
+        request.force,
         &state,
     )
     .await;
@@ -65,9 +75,10 @@ pub async fn remove(
 pub async fn import(
     State(state): State<Arc<ServerState>>,
     Path(alias): Path<String>,
+    Query(query): Query<ImportHooksQuery>,
     Json(config): Json<HooksConfig>,
 ) -> Result<Json<ApiResponse<()>>, HttpError> {
-    let response = handlers::hooks::import(&alias, &config, &state).await;
+    let response = handlers::hooks::import(&alias, &config, query.force, &state).await;
 
     match response {
         Response::Success { .. } => Ok(Json(ApiResponse::ok())),
@@ -81,7 +92,7 @@ pub async fn export(
     State(state): State<Arc<ServerState>>,
     Path(alias): Path<String>,
 ) -> Result<Json<ApiResponse<HooksConfig>>, HttpError> {
-    let response = handlers::hooks::export(&alias, &state).await;
+    let response = handlers::hooks::export(&alias, None, &state).await;
 
     match response {
         Response::Hooks(hooks) => Ok(Json(ApiResponse::success(hooks))),
@@ -0,0 +1,168 @@
+//! Prometheus metrics HTTP handler.
+
+use crate::daemon::server::ServerState;
+use crate::daemon::telemetry::Aggregates;
+use axum::{extract::State, http::header, response::IntoResponse};
+use ringlet_core::proxy::ProxyStatus;
+use std::fmt::Write;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Content type for the Prometheus text exposition format.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Point-in-time daemon metrics.
+#[derive(Debug, Default)]
+struct Snapshot {
+    uptime_secs: u64,
+    terminal_sessions: usize,
+    proxies_running: usize,
+    aggregates: Aggregates,
+}
+
+/// GET /metrics - Prometheus metrics.
+pub async fn metrics(State(state): State<Arc<ServerState>>) -> impl IntoResponse {
+    let proxies_running = state
+        .proxy_manager
+        .status()
+        .await
+        .iter()
+        .filter(|instance| instance.status == ProxyStatus::Running)
+        .count();
+
+    let aggregates = state.telemetry.load_aggregates().unwrap_or_else(|e| {
+        warn!("Failed to load telemetry aggregates for metrics: {}", e);
+        Aggregates::default()
+    });
+
+    let snapshot = Snapshot {
+        uptime_secs: state.started_at.elapsed().as_secs(),
+        terminal_sessions: state.terminal_sessions.active_session_count().await,
+        proxies_running,
+        aggregates,
+    };
+
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], render(&snapshot))
+}
+
+/// Render a snapshot in the Prometheus text exposition format.
+fn render(snapshot: &Snapshot) -> String {
+    let mut out = String::new();
+
+    metric_header(
+        &mut out,
+        "ringlet_uptime_seconds",
+        "gauge",
+        "Seconds since the daemon started.",
+    );
+    let _ = writeln!(out, "ringlet_uptime_seconds {}", snapshot.uptime_secs);
+
+    metric_header(
+        &mut out,
+        "ringlet_terminal_sessions_active",
+        "gauge",
+        "Terminal sessions that have not terminated.",
+    );
+    let _ = writeln!(
+        out,
+        "ringlet_terminal_sessions_active {}",
+        snapshot.terminal_sessions
+    );
+
+    metric_header(
+        &mut out,
+        "ringlet_proxies_running",
+        "gauge",
+        "Proxy instances in the running state.",
+    );
+    let _ = writeln!(out, "ringlet_proxies_running {}", snapshot.proxies_running);
+
+    metric_header(
+        &mut out,
+        "ringlet_profile_runs_total",
+        "counter",
+        "Profile runs recorded by telemetry.",
+    );
+    let _ = writeln!(
+        out,
+        "ringlet_profile_runs_total {}",
+        snapshot.aggregates.total_sessions
+    );
+
+    metric_header(
+        &mut out,
+        "ringlet_usage_tokens_total",
+        "counter",
+        "Tokens used, by agent and token type.",
+    );
+    let mut agents: Vec<_> = snapshot.aggregates.by_agent.iter().collect();
+    agents.sort_by(|a, b| a.0.cmp(b.0));
+    for (agent, stats) in agents {
+        let agent = escape_label(agent);
+        for (kind, count) in [
+            ("input", stats.tokens.input_tokens),
+            ("output", stats.tokens.output_tokens),
+            ("cache_creation", stats.tokens.cache_creation_input_tokens),
+            ("cache_read", stats.tokens.cache_read_input_tokens),
+        ] {
+            let _ = writeln!(
+                out,
+                "ringlet_usage_tokens_total{{agent=\"{}\",type=\"{}\"}} {}",
+                agent, kind, count
+            );
+        }
+    }
+
+    out
+}
+
+fn metric_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Escape a label value per the exposition format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::telemetry::AgentStats;
+    use ringlet_core::TokenUsage;
+
+    #[test]
+    fn test_render_exposition_format() {
+        let mut snapshot = Snapshot {
+            uptime_secs: 42,
+            terminal_sessions: 2,
+            proxies_running: 1,
+            ..Default::default()
+        };
+        snapshot.aggregates.total_sessions = 7;
+        snapshot.aggregates.by_agent.insert(
+            "claude".to_string(),
+            AgentStats {
+                sessions: 7,
+                tokens: TokenUsage {
+                    input_tokens: 100,
+                    output_tokens: 50,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+
+        let text = render(&snapshot);
+        assert!(text.contains("# TYPE ringlet_uptime_seconds gauge\nringlet_uptime_seconds 42\n"));
+        assert!(text.contains("ringlet_terminal_sessions_active 2\n"));
+        assert!(text.contains("ringlet_proxies_running 1\n"));
+        assert!(text.contains("ringlet_profile_runs_total 7\n"));
+        assert!(text.contains("ringlet_usage_tokens_total{agent=\"claude\",type=\"input\"} 100\n"));
+        assert!(text.contains("ringlet_usage_tokens_total{agent=\"claude\",type=\"output\"} 50\n"));
+    }
+}
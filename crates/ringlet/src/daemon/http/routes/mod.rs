@@ -4,6 +4,7 @@ pub mod agents;
 pub mod fs;
 pub mod git;
 pub mod hooks;
+pub mod metrics;
 pub mod profiles;
 pub mod providers;
 pub mod proxy;
@@ -13,12 +14,30 @@ pub mod system;
 pub mod terminal;
 pub mod usage;
 
+use crate::daemon::http::rate_limit::{TokenBucket, rate_limit_middleware};
 use crate::daemon::server::ServerState;
-use axum::{Router, routing::delete, routing::get, routing::post};
+use axum::{Router, middleware, routing::delete, routing::get, routing::post};
+use ringlet_core::UserConfig;
 use std::sync::Arc;
 
 /// Build all API routes.
-pub fn api_routes() -> Router<Arc<ServerState>> {
+pub fn api_routes(config: &UserConfig) -> Router<Arc<ServerState>> {
+    // Terminal creation spawns a PTY per request, so it is rate limited.
+    let mut create_terminal = post(terminal::create_session);
+    let mut create_shell = post(terminal::create_shell_session);
+    let rate = config.terminal.create_rate_per_min;
+    if rate > 0 {
+        let bucket = Arc::new(TokenBucket::per_minute(rate));
+        create_terminal = create_terminal.route_layer(middleware::from_fn_with_state(
+            bucket.clone(),
+            rate_limit_middleware,
+        ));
+        create_shell = create_shell.route_layer(middleware::from_fn_with_state(
+            bucket,
+            rate_limit_middleware,
+        ));
+    }
+
     Router::new()
         // Agents
         .route("/agents", get(agents::list))
@@ -33,6 +52,7 @@ pub fn api_routes() -> Router<Arc<ServerState>> {
             get(profiles::inspect).delete(profiles::delete),
         )
         .route("/profiles/{alias}/run", post(profiles::run))
+        .route("/profiles/{alias}/exec", post(profiles::exec))
         .route("/profiles/{alias}/env", get(profiles::env))
         // Hooks
         .route("/profiles/{alias}/hooks", get(hooks::list).post(hooks::add))
@@ -75,21 +95,24 @@ pub fn api_routes() -> Router<Arc<ServerState>> {
         .route("/stats", get(stats::get_stats))
         // Usage
         .route("/usage", get(usage::get_usage))
+        .route("/usage/budget", get(usage::get_budget))
+        .route("/usage/import", post(usage::import))
         .route("/usage/import-claude", post(usage::import_claude))
         // System
         .route("/ping", get(system::ping))
+        .route("/health", get(system::health))
         .route("/shutdown", post(system::shutdown))
         // Terminal sessions
         .route(
             "/terminal/sessions",
-            get(terminal::list_sessions).post(terminal::create_session),
+            get(terminal::list_sessions).merge(create_terminal),
         )
         .route(
             "/terminal/sessions/{id}",
             get(terminal::get_session).delete(terminal::terminate_session),
         )
         .route("/terminal/cleanup", post(terminal::cleanup_sessions))
-        .route("/terminal/shell", post(terminal::create_shell_session))
+        .route("/terminal/shell", create_shell)
         // Filesystem
         .route("/fs/list", get(fs::list_directory))
         .route("/fs/complete", get(fs::path_complete))
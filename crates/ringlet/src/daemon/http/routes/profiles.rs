@@ -1,23 +1,37 @@
 //! Profile HTTP handlers.
 
+use crate::daemon::execution::{exit_details, run_post_run_hook};
 use crate::daemon::handlers;
 use crate::daemon::http::error::{ApiResponse, HttpError};
 use crate::daemon::server::ServerState;
 use axum::{
     Json,
     extract::{Path, Query, State},
+    response::sse::{self, KeepAlive, Sse},
 };
-use ringlet_core::http_api::{ListProfilesQuery, RunRequest, RunResponse};
-use ringlet_core::{ProfileCreateRequest, ProfileInfo, Response};
+use futures_util::Stream;
+use ringlet_core::http_api::{ExecExit, ExecOutput, ListProfilesQuery, RunRequest, RunResponse};
+use ringlet_core::rpc::error_codes;
+use ringlet_core::{Event, ProfileCreateRequest, ProfileInfo, Response};
+use serde::Serialize;
 use std::collections::HashMap;
+use std::convert::Infallible;
 use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Child;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Size of each read from the agent's stdout or stderr.
+const EXEC_READ_SIZE: usize = 8192;
 
 /// GET /api/profiles - List all profiles.
 pub async fn list(
     State(state): State<Arc<ServerState>>,
     Query(query): Query<ListProfilesQuery>,
 ) -> Result<Json<ApiResponse<Vec<ProfileInfo>>>, HttpError> {
-    let response = handlers::profiles::list(query.agent.as_deref(), &state).await;
+    let response =
+        handlers::profiles::list(query.agent.as_deref(), query.provider.as_deref(), &state).await;
 
     match response {
         Response::Profiles(profiles) => Ok(Json(ApiResponse::success(profiles))),
@@ -45,7 +59,7 @@ pub async fn inspect(
     State(state): State<Arc<ServerState>>,
     Path(alias): Path<String>,
 ) -> Result<Json<ApiResponse<ProfileInfo>>, HttpError> {
-    let response = handlers::profiles::inspect(&alias, &state).await;
+    let response = handlers::profiles::inspect(&alias, false, &state).await;
 
     match response {
         Response::Profile(profile) => Ok(Json(ApiResponse::success(profile))),
@@ -74,22 +88,210 @@ pub async fn run(
     Path(alias): Path<String>,
     Json(request): Json<RunRequest>,
 ) -> Result<Json<ApiResponse<RunResponse>>, HttpError> {
-    let response = handlers::profiles::run(&alias, &request.args, &state).await;
+    let response = handlers::profiles::run(
+        &alias,
+        &request.args,
+        &HashMap::new(),
+        request.ephemeral_proxy,
+        &state,
+    )
+    .await;
 
     match response {
         Response::RunStarted { pid } => {
             Ok(Json(ApiResponse::success(RunResponse::Started { pid })))
         }
-        Response::RunCompleted { exit_code } => {
-            Ok(Json(ApiResponse::success(RunResponse::Completed {
-                exit_code,
-            })))
-        }
+        Response::RunCompleted {
+            exit_code,
+            signal,
+            duration_secs,
+        } => Ok(Json(ApiResponse::success(RunResponse::Completed {
+            exit_code,
+            signal,
+            duration_secs,
+        }))),
         Response::Error { code, message } => Err(HttpError::new(code, message)),
         _ => Err(HttpError::internal("Unexpected response type")),
     }
 }
 
+/// POST /api/profiles/:alias/exec - Run a profile and stream its output.
+///
+/// The response is a server-sent event stream of `stdout` and `stderr` chunks
+/// followed by a final `exit` event. Closing the stream kills the agent.
+pub async fn exec(
+    State(state): State<Arc<ServerState>>,
+    Path(alias): Path<String>,
+    Json(request): Json<RunRequest>,
+) -> Result<Sse<impl Stream<Item = Result<sse::Event, Infallible>>>, HttpError> {
+    // Reuse the CLI-attached run flow so history and telemetry are recorded
+    let context = match handlers::profiles::prepare(
+        &alias,
+        &request.args,
+        &HashMap::new(),
+        request.ephemeral_proxy,
+        None,
+        // The daemon spawns and completes the run itself
+        Some(std::process::id()),
+        &state,
+    )
+    .await
+    {
+        Response::ExecutionContext(context) => context,
+        Response::Error { code, message } => return Err(HttpError::new(code, message)),
+        _ => return Err(HttpError::internal("Unexpected response type")),
+    };
+    let run_id = context.run_id.clone().unwrap_or_default();
+
+    let started_at = chrono::Utc::now();
+    let child = match state.execution_adapter.spawn_piped(&context) {
+        Ok(child) => child,
+        Err(e) => {
+            handlers::profiles::abandon(&run_id, &state).await;
+            return Err(HttpError::new(
+                error_codes::EXECUTION_ERROR,
+                format!("Failed to run profile: {}", e),
+            ));
+        }
+    };
+    if let Some(pid) = child.id() {
+        state.broadcast(Event::ProfileRunStarted {
+            alias: alias.clone(),
+            pid,
+        });
+    }
+
+    let (tx, rx) = mpsc::channel(64);
+    tokio::spawn(async move {
+        let (exit_code, signal) = stream_output(child, &tx).await;
+        let ended_at = chrono::Utc::now();
+
+        match tokio::task::spawn_blocking(move || run_post_run_hook(&context)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!("Profile '{}': {:#}", alias, e),
+            Err(e) => warn!("Failed to join post_run hook task: {}", e),
+        }
+
+        if let Response::Error { message, .. } =
+            handlers::profiles::complete(&run_id, started_at, ended_at, exit_code, signal, &state)
+                .await
+        {
+            warn!("Profile '{}': {}", alias, message);
+        }
+        state.broadcast(Event::ProfileRunCompleted {
+            alias,
+            exit_code,
+            signal,
+        });
+
+        let exit = ExecExit {
+            exit_code,
+            signal,
+            duration_secs: ended_at
+                .signed_duration_since(started_at)
+                .num_seconds()
+                .max(0) as u64,
+        };
+        let _ = tx.send(exec_event("exit", &exit)).await;
+    });
+
+    let stream = futures_util::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|event| (Ok(event), rx))
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Forward the child's output as events until both pipes close, then wait for it.
+///
+/// The child is killed if the client goes away first.
+async fn stream_output(mut child: Child, tx: &mpsc::Sender<sse::Event>) -> (i32, Option<i32>) {
+    let mut stdout = child.stdout.take();
+    let mut stderr = child.stderr.take();
+    let mut stdout_pending = Vec::new();
+    let mut stderr_pending = Vec::new();
+
+    while stdout.is_some() || stderr.is_some() {
+        tokio::select! {
+            chunk = read_chunk(&mut stdout) => {
+                forward_chunk("stdout", chunk, &mut stdout, &mut stdout_pending, tx).await;
+            }
+            chunk = read_chunk(&mut stderr) => {
+                forward_chunk("stderr", chunk, &mut stderr, &mut stderr_pending, tx).await;
+            }
+            _ = tx.closed() => {
+                let _ = child.start_kill();
+                break;
+            }
+        }
+    }
+
+    match child.wait().await {
+        Ok(status) => exit_details(&status),
+        Err(e) => {
+            warn!("Failed to wait for process: {}", e);
+            (-1, None)
+        }
+    }
+}
+
+/// Read the next chunk from a pipe; never resolves once the pipe is closed.
+async fn read_chunk<R: AsyncRead + Unpin>(pipe: &mut Option<R>) -> std::io::Result<Vec<u8>> {
+    let Some(reader) = pipe.as_mut() else {
+        return std::future::pending().await;
+    };
+    let mut buf = vec![0; EXEC_READ_SIZE];
+    let n = reader.read(&mut buf).await?;
+    buf.truncate(n);
+    Ok(buf)
+}
+
+/// Send a chunk read from `pipe` as an event, closing the pipe at EOF.
+async fn forward_chunk<R>(
+    name: &'static str,
+    chunk: std::io::Result<Vec<u8>>,
+    pipe: &mut Option<R>,
+    pending: &mut Vec<u8>,
+    tx: &mpsc::Sender<sse::Event>,
+) {
+    let data = match chunk {
+        Ok(bytes) if !bytes.is_empty() => {
+            pending.extend_from_slice(&bytes);
+            take_utf8(pending)
+        }
+        result => {
+            if let Err(e) = result {
+                warn!("Failed to read agent {}: {}", name, e);
+            }
+            *pipe = None;
+            String::from_utf8_lossy(&std::mem::take(pending)).into_owned()
+        }
+    };
+    if !data.is_empty() {
+        let _ = tx.send(exec_event(name, &ExecOutput { data })).await;
+    }
+}
+
+/// Take the decodable prefix of `pending`, leaving an incomplete trailing
+/// UTF-8 sequence for the next read.
+fn take_utf8(pending: &mut Vec<u8>) -> String {
+    let complete = match std::str::from_utf8(pending) {
+        Ok(_) => pending.len(),
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        Err(_) => pending.len(),
+    };
+    let rest = pending.split_off(complete);
+    let data = String::from_utf8_lossy(pending).into_owned();
+    *pending = rest;
+    data
+}
+
+fn exec_event<T: Serialize>(name: &str, payload: &T) -> sse::Event {
+    sse::Event::default()
+        .event(name)
+        .json_data(payload)
+        .unwrap_or_default()
+}
+
 /// GET /api/profiles/:alias/env - Get profile environment variables.
 pub async fn env(
     State(state): State<Arc<ServerState>>,
@@ -103,3 +305,124 @@ pub async fn env(
         _ => Err(HttpError::internal("Unexpected response type")),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::profile_store::test_profile;
+    use axum::response::IntoResponse;
+    use futures_util::StreamExt;
+    use std::time::Duration;
+
+    /// State with a profile `work` whose agent is replaced by a shell `script`.
+    fn exec_state(dir: &std::path::Path, script: &str) -> Arc<ServerState> {
+        let state = ServerState::for_tests(dir);
+        let mut profile = test_profile("work", &dir.join("home"));
+        profile.working_dir = Some(dir.to_path_buf());
+        profile.metadata.launch_wrapper = Some(
+            ["sh", "-c", script, "sh", "{args}"]
+                .iter()
+                .map(|part| part.to_string())
+                .collect(),
+        );
+        state.profile_store.save_new(&profile).unwrap();
+        state.secret_store.store_api_key("work", "sk-test").unwrap();
+        Arc::new(state)
+    }
+
+    async fn exec_body(state: &Arc<ServerState>) -> axum::body::Body {
+        let request = RunRequest {
+            args: Vec::new(),
+            ephemeral_proxy: false,
+        };
+        let Ok(sse) = exec(
+            State(state.clone()),
+            Path("work".to_string()),
+            Json(request),
+        )
+        .await
+        else {
+            panic!("exec failed");
+        };
+        sse.into_response().into_body()
+    }
+
+    /// The `(event, data)` pairs of a server-sent event stream.
+    fn sse_events(body: &str) -> Vec<(String, serde_json::Value)> {
+        body.split("\n\n")
+            .filter_map(|frame| {
+                let mut name = None;
+                let mut data = None;
+                for line in frame.lines() {
+                    if let Some(value) = line.strip_prefix("event: ") {
+                        name = Some(value.to_string());
+                    } else if let Some(value) = line.strip_prefix("data: ") {
+                        data = Some(serde_json::from_str(value).unwrap());
+                    }
+                }
+                Some((name?, data?))
+            })
+            .collect()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_exec_streams_output_then_exit() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = exec_state(dir.path(), "echo out; sleep 0.1; echo err >&2; exit 3");
+
+        let body = axum::body::to_bytes(exec_body(&state).await, usize::MAX)
+            .await
+            .unwrap();
+        let events = sse_events(std::str::from_utf8(&body).unwrap());
+
+        let names: Vec<_> = events.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["stdout", "stderr", "exit"]);
+        assert_eq!(events[0].1["data"], "out\n");
+        assert_eq!(events[1].1["data"], "err\n");
+        assert_eq!(events[2].1["exit_code"], 3);
+        assert!(events[2].1["signal"].is_null());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_closing_exec_stream_kills_agent() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = exec_state(dir.path(), "echo started; exec sleep 30");
+        let mut events = state.events.subscribe();
+
+        let mut body = exec_body(&state).await.into_data_stream();
+        let first = body.next().await.unwrap().unwrap();
+        assert!(String::from_utf8_lossy(&first).contains("started"));
+        drop(body);
+
+        let completed = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Event::ProfileRunCompleted { signal, .. } =
+                    events.recv().await.unwrap().event
+                {
+                    return signal;
+                }
+            }
+        })
+        .await
+        .expect("agent still running after the client went away");
+        assert_eq!(completed, Some(libc::SIGKILL));
+    }
+
+    #[test]
+    fn test_take_utf8_keeps_split_sequences() {
+        let mut pending = "héllo".as_bytes()[..2].to_vec();
+        assert_eq!(take_utf8(&mut pending), "h");
+        assert_eq!(pending, [0xc3]);
+
+        pending.extend_from_slice(&"héllo".as_bytes()[2..]);
+        assert_eq!(take_utf8(&mut pending), "éllo");
+        assert!(pending.is_empty());
+
+        // Invalid bytes are replaced rather than held back
+        let mut pending = vec![b'a', 0xff, b'b'];
+        assert_eq!(take_utf8(&mut pending), "a\u{fffd}b");
+        assert!(pending.is_empty());
+    }
+}
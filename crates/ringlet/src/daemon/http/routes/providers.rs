@@ -5,16 +5,30 @@ use crate::daemon::http::error::{ApiResponse, HttpError};
 use crate::daemon::server::ServerState;
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
 };
 use ringlet_core::{ProviderInfo, Response};
+use serde::Deserialize;
 use std::sync::Arc;
 
-/// GET /api/providers - List all providers.
+#[derive(Debug, Deserialize)]
+pub struct ProvidersQuery {
+    #[serde(rename = "type")]
+    pub provider_type: Option<String>,
+    pub search: Option<String>,
+}
+
+/// GET /api/providers - List providers, optionally filtered by type or search term.
 pub async fn list(
     State(state): State<Arc<ServerState>>,
+    Query(query): Query<ProvidersQuery>,
 ) -> Result<Json<ApiResponse<Vec<ProviderInfo>>>, HttpError> {
-    let response = handlers::providers::list(&state).await;
+    let response = handlers::providers::list(
+        query.provider_type.as_deref(),
+        query.search.as_deref(),
+        &state,
+    )
+    .await;
 
     match response {
         Response::Providers(providers) => Ok(Json(ApiResponse::success(providers))),
@@ -69,12 +69,19 @@ pub async fn stop(
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RestartQuery {
+    #[serde(default)]
+    pub verify: bool,
+}
+
 /// POST /api/profiles/:alias/proxy/restart - Restart proxy for profile.
 pub async fn restart(
     State(state): State<Arc<ServerState>>,
     Path(alias): Path<String>,
+    Query(query): Query<RestartQuery>,
 ) -> Result<Json<ApiResponse<()>>, HttpError> {
-    let response = handlers::proxy::restart(&alias, &state).await;
+    let response = handlers::proxy::restart(&alias, query.verify, &state).await;
 
     match response {
         Response::Success { .. } => Ok(Json(ApiResponse::ok())),
@@ -1,10 +1,11 @@
 //! System HTTP handlers.
 
 use crate::daemon::handlers;
+use crate::daemon::health;
 use crate::daemon::http::error::{ApiResponse, HttpError};
 use crate::daemon::server::ServerState;
-use axum::{Json, extract::State};
-use ringlet_core::http_api::PingResponse;
+use axum::{Json, extract::State, http::StatusCode};
+use ringlet_core::http_api::{HealthResponse, HealthState, PingResponse};
 use std::sync::Arc;
 
 /// GET /api/ping - Health check.
@@ -15,6 +16,21 @@ pub async fn ping(State(_state): State<Arc<ServerState>>) -> Json<ApiResponse<Pi
     }))
 }
 
+/// GET /api/health - Subsystem health.
+///
+/// Returns 503 when a core subsystem is unhealthy.
+pub async fn health(
+    State(state): State<Arc<ServerState>>,
+) -> (StatusCode, Json<ApiResponse<HealthResponse>>) {
+    let report = health::report(&state);
+    let code = if report.status == HealthState::Unhealthy {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+    (code, Json(ApiResponse::success(report)))
+}
+
 /// POST /api/shutdown - Shutdown the daemon.
 pub async fn shutdown(
     State(state): State<Arc<ServerState>>,
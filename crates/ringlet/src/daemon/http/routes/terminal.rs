@@ -72,6 +72,7 @@ pub async fn create_session(
         initial_size,
         sandbox_config,
         token_hash.0,
+        request.persist,
         &state,
     )
     .await
@@ -7,7 +7,9 @@ use axum::{
     Json,
     extract::{Query, State},
 };
-use ringlet_core::{Response, UsagePeriod, UsageStatsResponse};
+use ringlet_core::{
+    AgentType, BudgetStatus, Response, UsageImportFormat, UsagePeriod, UsageStatsResponse,
+};
 use serde::Deserialize;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -20,20 +22,8 @@ pub struct UsageQuery {
     pub profile: Option<String>,
     /// Filter by model
     pub model: Option<String>,
-}
-
-/// Parse period string into UsagePeriod enum.
-fn parse_period(s: &str) -> UsagePeriod {
-    match s.to_lowercase().as_str() {
-        "today" => UsagePeriod::Today,
-        "yesterday" => UsagePeriod::Yesterday,
-        "week" | "this_week" | "thisweek" => UsagePeriod::ThisWeek,
-        "month" | "this_month" | "thismonth" => UsagePeriod::ThisMonth,
-        "7d" | "last7days" | "last_7_days" => UsagePeriod::Last7Days,
-        "30d" | "last30days" | "last_30_days" => UsagePeriod::Last30Days,
-        "all" => UsagePeriod::All,
-        _ => UsagePeriod::Today,
-    }
+    /// Filter by agent (claude, codex, opencode)
+    pub agent: Option<AgentType>,
 }
 
 /// GET /api/usage - Get usage statistics.
@@ -41,11 +31,17 @@ pub async fn get_usage(
     State(state): State<Arc<ServerState>>,
     Query(query): Query<UsageQuery>,
 ) -> Result<Json<ApiResponse<UsageStatsResponse>>, HttpError> {
-    let period = query.period.as_deref().map(parse_period);
+    let period = query
+        .period
+        .as_deref()
+        .map(str::parse::<UsagePeriod>)
+        .transpose()
+        .map_err(HttpError::bad_request)?;
     let response = handlers::usage::get_usage(
         period.as_ref(),
         query.profile.as_deref(),
         query.model.as_deref(),
+        query.agent,
         &state,
     )
     .await;
@@ -57,6 +53,17 @@ pub async fn get_usage(
     }
 }
 
+/// GET /api/usage/budget - Get current spend against configured budgets.
+pub async fn get_budget(
+    State(state): State<Arc<ServerState>>,
+) -> Result<Json<ApiResponse<Vec<BudgetStatus>>>, HttpError> {
+    match handlers::usage::get_budget(&state).await {
+        Response::UsageBudget(statuses) => Ok(Json(ApiResponse::success(statuses))),
+        Response::Error { code, message } => Err(HttpError::new(code, message)),
+        _ => Err(HttpError::internal("Unexpected response type")),
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ImportClaudeQuery {
     /// Path to Claude home directory
@@ -76,3 +83,24 @@ pub async fn import_claude(
         _ => Err(HttpError::internal("Unexpected response type")),
     }
 }
+
+#[derive(Debug, Deserialize)]
+pub struct ImportQuery {
+    /// Format of the request body
+    pub format: UsageImportFormat,
+}
+
+/// POST /api/usage/import - Import usage rows computed outside ringlet.
+pub async fn import(
+    State(state): State<Arc<ServerState>>,
+    Query(query): Query<ImportQuery>,
+    body: String,
+) -> Result<Json<ApiResponse<String>>, HttpError> {
+    let response = handlers::usage::import(query.format, &body, &state).await;
+
+    match response {
+        Response::Success { message } => Ok(Json(ApiResponse::success(message))),
+        Response::Error { code, message } => Err(HttpError::new(code, message)),
+        _ => Err(HttpError::internal("Unexpected response type")),
+    }
+}
@@ -2,20 +2,22 @@
 
 use crate::daemon::http::{AuthState, assets, auth, routes, terminal_ws, websocket};
 use crate::daemon::server::ServerState;
-use axum::{Router, middleware, routing::get};
+use axum::{Router, http::HeaderValue, middleware, routing::get};
+use ringlet_core::UserConfig;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::net::TcpListener;
 use tower_governor::{GovernorLayer, governor::GovernorConfigBuilder};
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 /// Run the HTTP server.
 pub async fn run_http_server(
     state: Arc<ServerState>,
     port: u16,
     token: String,
+    config: UserConfig,
     shutdown_rx: tokio::sync::oneshot::Receiver<()>,
 ) {
     let addr = SocketAddr::from(([127, 0, 0, 1], port));
@@ -33,15 +35,23 @@ pub async fn run_http_server(
     );
 
     // Routes that require authentication
-    let authenticated_routes = Router::new()
+    let mut authenticated_routes = Router::new()
         // API routes
-        .nest("/api", routes::api_routes())
+        .nest("/api", routes::api_routes(&config))
         // WebSocket endpoints
         .route("/ws", get(websocket::ws_handler))
         .route(
             "/ws/terminal/{session_id}",
             get(terminal_ws::terminal_ws_handler),
-        )
+        );
+
+    // Prometheus metrics (opt-in via `daemon.metrics_enabled`)
+    if config.daemon.metrics_enabled {
+        authenticated_routes =
+            authenticated_routes.route("/metrics", get(routes::metrics::metrics));
+    }
+
+    let authenticated_routes = authenticated_routes
         .layer(GovernorLayer::new(governor_config))
         .layer(middleware::from_fn_with_state(
             auth_state,
@@ -61,14 +71,29 @@ pub async fn run_http_server(
         .fallback(get(assets::serve_index))
         .with_state(state);
 
-    // CORS configuration - restrict to localhost origins only
+    // CORS configuration - localhost origins plus any configured in `daemon.cors_allowed_origins`
+    let mut origins: Vec<HeaderValue> = vec![
+        "http://127.0.0.1".parse().unwrap(),
+        "http://localhost".parse().unwrap(),
+        format!("http://127.0.0.1:{}", port).parse().unwrap(),
+        format!("http://localhost:{}", port).parse().unwrap(),
+    ];
+    for origin in &config.daemon.cors_allowed_origins {
+        let origin = origin.trim_end_matches('/');
+        if origin == "*" {
+            warn!("Ignoring wildcard CORS origin; list each allowed origin explicitly");
+            continue;
+        }
+        match origin.parse() {
+            Ok(value) => {
+                info!("Allowing cross-origin requests from {}", origin);
+                origins.push(value);
+            }
+            Err(_) => warn!("Ignoring invalid CORS origin '{}'", origin),
+        }
+    }
     let cors = CorsLayer::new()
-        .allow_origin([
-            "http://127.0.0.1".parse().unwrap(),
-            "http://localhost".parse().unwrap(),
-            format!("http://127.0.0.1:{}", port).parse().unwrap(),
-            format!("http://localhost:{}", port).parse().unwrap(),
-        ])
+        .allow_origin(origins)
         .allow_methods(Any)
         .allow_headers(Any);
 
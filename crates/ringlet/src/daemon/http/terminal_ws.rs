@@ -3,23 +3,27 @@
 //! Provides bidirectional terminal I/O over WebSocket:
 //! - Binary messages: raw terminal data (input/output)
 //! - Text messages: JSON control messages (resize, state changes)
+//!
+//! Clients connecting with `?mode=view` receive output only; anything they
+//! send other than pings is dropped.
 
 use crate::daemon::http::auth::hash_token;
 use crate::daemon::server::ServerState;
-use crate::daemon::terminal::{SessionId, SessionState};
+use crate::daemon::terminal::{ClientMode, SessionId, SessionState};
 use axum::{
     extract::{
-        Path, State, WebSocketUpgrade,
+        Path, Query, State, WebSocketUpgrade,
         ws::{Message, WebSocket},
     },
     http::{HeaderMap, StatusCode, header},
     response::Response,
 };
+use futures_util::stream::SplitSink;
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tokio::sync::broadcast;
-use tracing::{debug, error, info, warn};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, info, warn};
 
 /// Allowed origins for WebSocket connections.
 const ALLOWED_ORIGINS: &[&str] = &["http://127.0.0.1", "http://localhost"];
@@ -56,9 +60,12 @@ fn extract_ws_token(headers: &HeaderMap) -> Option<String> {
     None
 }
 
-/// Maximum allowed WebSocket message size (256KB).
+/// Maximum allowed inbound WebSocket message size (256KB).
 const MAX_MESSAGE_SIZE: usize = 256 * 1024;
 
+/// Outbound messages queued per client before it is considered too slow.
+const SEND_BUFFER_MESSAGES: usize = 256;
+
 /// Allowed signal numbers for terminal sessions.
 /// - SIGINT (2): Interrupt (Ctrl+C)
 /// - SIGQUIT (3): Quit (Ctrl+\)
@@ -66,6 +73,14 @@ const MAX_MESSAGE_SIZE: usize = 256 * 1024;
 /// - SIGKILL (9): Kill (forceful)
 const ALLOWED_SIGNALS: &[i32] = &[2, 3, 9, 15];
 
+/// Query parameters for the terminal WebSocket.
+#[derive(Debug, Default, Deserialize)]
+pub struct TerminalWsQuery {
+    /// `view` attaches read-only; defaults to interactive.
+    #[serde(default)]
+    pub mode: ClientMode,
+}
+
 /// Control messages from client (JSON).
 #[derive(Debug, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -90,7 +105,10 @@ pub enum TerminalServerMessage {
     /// Error occurred.
     Error { message: String },
     /// Session connected successfully.
-    Connected { session_id: String },
+    Connected {
+        session_id: String,
+        mode: ClientMode,
+    },
 }
 
 /// WebSocket upgrade handler for terminal sessions.
@@ -98,6 +116,7 @@ pub async fn terminal_ws_handler(
     headers: HeaderMap,
     ws: WebSocketUpgrade,
     Path(session_id): Path<String>,
+    Query(query): Query<TerminalWsQuery>,
     State(state): State<Arc<ServerState>>,
 ) -> Result<Response, StatusCode> {
     // Validate Origin header to prevent cross-origin WebSocket hijacking
@@ -127,11 +146,82 @@ pub async fn terminal_ws_handler(
     }
     // If session doesn't exist, we'll handle it in handle_terminal_socket
 
-    Ok(ws.on_upgrade(move |socket| handle_terminal_socket(socket, session_id, state)))
+    // Reject oversized input at the protocol layer, before it is buffered
+    Ok(ws
+        .max_message_size(MAX_MESSAGE_SIZE)
+        .max_frame_size(MAX_MESSAGE_SIZE)
+        .on_upgrade(move |socket| handle_terminal_socket(socket, session_id, query.mode, state)))
+}
+
+/// Why a terminal client was disconnected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DisconnectReason {
+    /// Client closed the connection.
+    ClientClosed,
+    /// The socket could not be written to or read from.
+    SocketError(String),
+    /// Client fell too far behind the terminal output.
+    SlowClient,
+    /// The session's input channel is gone.
+    InputFailed(String),
+    /// The session's output broadcaster closed.
+    SessionEnded,
+}
+
+impl std::fmt::Display for DisconnectReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ClientClosed => write!(f, "client closed"),
+            Self::SocketError(e) => write!(f, "socket error: {}", e),
+            Self::SlowClient => write!(
+                f,
+                "client fell behind by more than {} messages",
+                SEND_BUFFER_MESSAGES
+            ),
+            Self::InputFailed(e) => write!(f, "input failed: {}", e),
+            Self::SessionEnded => write!(f, "session ended"),
+        }
+    }
+}
+
+/// Queue a message for the client without waiting on the socket.
+fn enqueue(tx: &mpsc::Sender<Message>, msg: Message) -> Result<(), DisconnectReason> {
+    tx.try_send(msg).map_err(|e| match e {
+        mpsc::error::TrySendError::Full(_) => DisconnectReason::SlowClient,
+        mpsc::error::TrySendError::Closed(_) => {
+            DisconnectReason::SocketError("send failed".to_string())
+        }
+    })
+}
+
+/// Queue a JSON control message for the client.
+fn enqueue_json(
+    tx: &mpsc::Sender<Message>,
+    msg: &TerminalServerMessage,
+) -> Result<(), DisconnectReason> {
+    match serde_json::to_string(msg) {
+        Ok(json) => enqueue(tx, Message::Text(json.into())),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Drain queued messages to the socket.
+async fn write_loop(mut sender: SplitSink<WebSocket, Message>, mut rx: mpsc::Receiver<Message>) {
+    while let Some(msg) = rx.recv().await {
+        if sender.send(msg).await.is_err() {
+            return;
+        }
+    }
+    let _ = sender.close().await;
 }
 
 /// Handle a terminal WebSocket connection.
-async fn handle_terminal_socket(socket: WebSocket, session_id: SessionId, state: Arc<ServerState>) {
+async fn handle_terminal_socket(
+    socket: WebSocket,
+    session_id: SessionId,
+    mode: ClientMode,
+    state: Arc<ServerState>,
+) {
     let (mut sender, mut receiver) = socket.split();
 
     // Get the session
@@ -160,26 +250,29 @@ async fn handle_terminal_socket(socket: WebSocket, session_id: SessionId, state:
     }
 
     // Register this client
-    session.add_client().await;
+    session.add_client(mode).await;
     info!(
-        "Terminal client connected to session {} (clients: {})",
+        "Terminal client connected to session {} as {:?} (clients: {})",
         session_id,
+        mode,
         session.client_count().await
     );
 
     // Send connected message
     let connected_msg = TerminalServerMessage::Connected {
         session_id: session_id.clone(),
+        mode,
     };
     if let Ok(json) = serde_json::to_string(&connected_msg)
         && sender.send(Message::Text(json.into())).await.is_err()
     {
-        session.remove_client().await;
+        session.remove_client(mode).await;
         return;
     }
 
-    // Send scrollback buffer (terminal history) to the new client
-    let scrollback = session.get_scrollback().await;
+    // Send scrollback buffer (terminal history) to the new client, then stream
+    // live output picking up exactly where the scrollback ends
+    let (scrollback, mut output_rx) = session.attach();
     if !scrollback.is_empty() {
         debug!(
             "Sending {} bytes of scrollback to client for session {}",
@@ -191,37 +284,30 @@ async fn handle_terminal_socket(socket: WebSocket, session_id: SessionId, state:
             .await
             .is_err()
         {
-            session.remove_client().await;
+            session.remove_client(mode).await;
             return;
         }
     }
 
-    // Subscribe to terminal output
-    let mut output_rx = session.subscribe();
+    // Writes go through a bounded queue so a slow client cannot stall this
+    // loop or make the daemon buffer output without limit.
+    let (out_tx, out_rx) = mpsc::channel(SEND_BUFFER_MESSAGES);
+    let writer = tokio::spawn(write_loop(sender, out_rx));
 
-    loop {
+    let reason = loop {
         tokio::select! {
             // Handle incoming messages from client
             Some(msg) = receiver.next() => {
                 match msg {
+                    // View-only clients never reach the PTY
+                    Ok(Message::Binary(_) | Message::Text(_)) if mode == ClientMode::View => {
+                        debug!("Dropping input from view-only client for session {}", session_id);
+                    }
                     Ok(Message::Binary(data)) => {
-                        // Check message size limit
-                        if data.len() > MAX_MESSAGE_SIZE {
-                            warn!("Message too large ({} bytes) for session {}, max {} bytes",
-                                  data.len(), session_id, MAX_MESSAGE_SIZE);
-                            let error_msg = TerminalServerMessage::Error {
-                                message: format!("Message too large: {} bytes (max {})", data.len(), MAX_MESSAGE_SIZE),
-                            };
-                            if let Ok(json) = serde_json::to_string(&error_msg) {
-                                let _ = sender.send(Message::Text(json.into())).await;
-                            }
-                            continue;
-                        }
                         // Raw terminal input data
                         debug!("Received {} bytes of input for session {}", data.len(), session_id);
                         if let Err(e) = session.send_input(crate::daemon::terminal::session::TerminalInput::Data(data.to_vec())).await {
-                            warn!("Failed to send input to session {}: {}", session_id, e);
-                            break;
+                            break DisconnectReason::InputFailed(e.to_string());
                         }
                     }
                     Ok(Message::Text(text)) => {
@@ -239,8 +325,8 @@ async fn handle_terminal_socket(socket: WebSocket, session_id: SessionId, state:
                                     let error_msg = TerminalServerMessage::Error {
                                         message: format!("Signal {} not allowed", signal),
                                     };
-                                    if let Ok(json) = serde_json::to_string(&error_msg) {
-                                        let _ = sender.send(Message::Text(json.into())).await;
+                                    if let Err(reason) = enqueue_json(&out_tx, &error_msg) {
+                                        break reason;
                                     }
                                     continue;
                                 }
@@ -253,82 +339,96 @@ async fn handle_terminal_socket(socket: WebSocket, session_id: SessionId, state:
                                 let error_msg = TerminalServerMessage::Error {
                                     message: format!("Invalid message: {}", e),
                                 };
-                                if let Ok(json) = serde_json::to_string(&error_msg) {
-                                    let _ = sender.send(Message::Text(json.into())).await;
+                                if let Err(reason) = enqueue_json(&out_tx, &error_msg) {
+                                    break reason;
                                 }
                             }
                         }
                     }
-                    Ok(Message::Close(_)) => {
-                        debug!("Terminal client sent close for session {}", session_id);
-                        break;
-                    }
-                    Ok(Message::Ping(data))
-                        if sender.send(Message::Pong(data.clone())).await.is_err() =>
-                    {
-                        break;
-                    }
-                    Err(e) => {
-                        error!("WebSocket receive error for session {}: {}", session_id, e);
-                        break;
+                    Ok(Message::Close(_)) => break DisconnectReason::ClientClosed,
+                    Ok(Message::Ping(data)) => {
+                        if let Err(reason) = enqueue(&out_tx, Message::Pong(data)) {
+                            break reason;
+                        }
                     }
+                    // Includes messages over MAX_MESSAGE_SIZE, rejected by the protocol layer
+                    Err(e) => break DisconnectReason::SocketError(e.to_string()),
                     _ => {}
                 }
             }
 
             // Forward terminal output to client
             result = output_rx.recv() => {
-                match result {
+                let queued = match result {
                     Ok(output) => {
                         use crate::daemon::terminal::session::TerminalOutput;
                         match output {
-                            TerminalOutput::Data(data) => {
-                                // Send raw binary data
-                                if sender.send(Message::Binary(data.into())).await.is_err() {
-                                    break;
-                                }
-                            }
+                            TerminalOutput::Data(data) => enqueue(&out_tx, Message::Binary(data.into())),
                             TerminalOutput::StateChanged(state) => {
                                 let (state_str, exit_code) = match state {
                                     SessionState::Starting => ("starting".to_string(), None),
                                     SessionState::Running => ("running".to_string(), None),
                                     SessionState::Terminated { exit_code } => ("terminated".to_string(), exit_code),
                                 };
-                                let msg = TerminalServerMessage::StateChanged {
+                                enqueue_json(&out_tx, &TerminalServerMessage::StateChanged {
                                     state: state_str,
                                     exit_code,
-                                };
-                                if let Ok(json) = serde_json::to_string(&msg)
-                                    && sender.send(Message::Text(json.into())).await.is_err() {
-                                        break;
-                                    }
+                                })
                             }
                             TerminalOutput::Resized { cols, rows } => {
-                                let msg = TerminalServerMessage::Resized { cols, rows };
-                                if let Ok(json) = serde_json::to_string(&msg)
-                                    && sender.send(Message::Text(json.into())).await.is_err() {
-                                        break;
-                                    }
+                                enqueue_json(&out_tx, &TerminalServerMessage::Resized { cols, rows })
                             }
                         }
                     }
-                    Err(broadcast::error::RecvError::Lagged(n)) => {
-                        warn!("Terminal client lagged for session {}, missed {} events", session_id, n);
-                    }
-                    Err(broadcast::error::RecvError::Closed) => {
-                        debug!("Terminal output broadcaster closed for session {}", session_id);
-                        break;
-                    }
+                    // Dropped output would corrupt the client's screen; it can
+                    // reconnect and replay the scrollback instead.
+                    Err(broadcast::error::RecvError::Lagged(_)) => Err(DisconnectReason::SlowClient),
+                    Err(broadcast::error::RecvError::Closed) => Err(DisconnectReason::SessionEnded),
+                };
+                if let Err(reason) = queued {
+                    break reason;
                 }
             }
         }
+    };
+
+    // A slow client would never drain its queue; drop it immediately.
+    if reason == DisconnectReason::SlowClient {
+        writer.abort();
     }
+    drop(out_tx);
 
     // Unregister this client
-    session.remove_client().await;
-    info!(
-        "Terminal client disconnected from session {} (clients: {})",
-        session_id,
-        session.client_count().await
-    );
+    session.remove_client(mode).await;
+    match reason {
+        DisconnectReason::ClientClosed | DisconnectReason::SessionEnded => info!(
+            "Terminal client disconnected from session {} ({}, clients: {})",
+            session_id,
+            reason,
+            session.client_count().await
+        ),
+        _ => warn!(
+            "Terminal client disconnected from session {} ({}, clients: {})",
+            session_id,
+            reason,
+            session.client_count().await
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enqueue_reports_slow_client_when_full() {
+        let (tx, _rx) = mpsc::channel(SEND_BUFFER_MESSAGES);
+        for _ in 0..SEND_BUFFER_MESSAGES {
+            enqueue(&tx, Message::Binary(vec![0u8].into())).unwrap();
+        }
+        assert_eq!(
+            enqueue(&tx, Message::Binary(vec![0u8].into())),
+            Err(DisconnectReason::SlowClient)
+        );
+    }
 }
@@ -1,9 +1,10 @@
 //! WebSocket handler for real-time event streaming.
 
+use crate::daemon::events::Replay;
 use crate::daemon::server::ServerState;
 use axum::{
     extract::{
-        State, WebSocketUpgrade,
+        Query, State, WebSocketUpgrade,
         ws::{Message, WebSocket},
     },
     http::{HeaderMap, StatusCode, header},
@@ -12,6 +13,7 @@ use axum::{
 use chrono::Utc;
 use futures_util::{SinkExt, StreamExt};
 use ringlet_core::{ClientMessage, Event, ServerMessage, VERSION};
+use serde::Deserialize;
 use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::broadcast;
@@ -38,10 +40,18 @@ fn validate_origin(headers: &HeaderMap) -> bool {
     }
 }
 
+/// Query parameters for the WebSocket endpoint.
+#[derive(Debug, Deserialize)]
+pub struct WsQuery {
+    /// Replay buffered events with a sequence number after this one.
+    pub since: Option<u64>,
+}
+
 /// WebSocket upgrade handler.
 pub async fn ws_handler(
     headers: HeaderMap,
     ws: WebSocketUpgrade,
+    Query(query): Query<WsQuery>,
     State(state): State<Arc<ServerState>>,
 ) -> Result<Response, StatusCode> {
     // Validate Origin header to prevent cross-origin WebSocket hijacking
@@ -49,27 +59,30 @@ pub async fn ws_handler(
         warn!("WebSocket connection rejected: invalid origin");
         return Err(StatusCode::FORBIDDEN);
     }
-    Ok(ws.on_upgrade(|socket| handle_socket(socket, state)))
+    Ok(ws.on_upgrade(move |socket| handle_socket(socket, query.since, state)))
 }
 
 /// Handle a WebSocket connection.
-async fn handle_socket(socket: WebSocket, state: Arc<ServerState>) {
+///
+/// With `since`, buffered events the client missed are sent before live ones.
+async fn handle_socket(socket: WebSocket, since: Option<u64>, state: Arc<ServerState>) {
     let (mut sender, mut receiver) = socket.split();
 
     // Subscribe to events
-    let mut event_rx = state.events.subscribe();
+    let (replay, mut event_rx) = match since {
+        Some(since) => state.events.subscribe_since(since),
+        None => (Replay::Missed(Vec::new()), state.events.subscribe()),
+    };
 
     // Track subscribed topics (default: subscribe to all)
     let mut subscribed_topics: HashSet<String> = HashSet::new();
     subscribed_topics.insert("*".to_string());
 
     // Send connected event
-    let connected_msg = ServerMessage::Event {
-        event: Event::Connected {
-            version: VERSION.to_string(),
-            timestamp: Utc::now(),
-        },
-    };
+    let connected_msg = ServerMessage::from(Event::Connected {
+        version: VERSION.to_string(),
+        timestamp: Utc::now(),
+    });
     if let Ok(json) = serde_json::to_string(&connected_msg)
         && sender.send(Message::Text(json.into())).await.is_err()
     {
@@ -78,6 +91,32 @@ async fn handle_socket(socket: WebSocket, state: Arc<ServerState>) {
 
     info!("WebSocket client connected");
 
+    let replayed = match replay {
+        Replay::Missed(missed) => {
+            if !missed.is_empty() {
+                debug!("Replaying {} missed events", missed.len());
+            }
+            missed
+                .into_iter()
+                .map(|sequenced| ServerMessage::Event {
+                    event: sequenced.event,
+                    seq: Some(sequenced.seq),
+                })
+                .collect()
+        }
+        Replay::Resync { last_seq } => {
+            debug!("Client is out of sync, asking it to reload");
+            vec![ServerMessage::Resync { seq: last_seq }]
+        }
+    };
+    for msg in replayed {
+        if let Ok(json) = serde_json::to_string(&msg)
+            && sender.send(Message::Text(json.into())).await.is_err()
+        {
+            return;
+        }
+    }
+
     // Create heartbeat interval
     let mut heartbeat_interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
 
@@ -85,11 +124,9 @@ async fn handle_socket(socket: WebSocket, state: Arc<ServerState>) {
         tokio::select! {
             // Send periodic heartbeat
             _ = heartbeat_interval.tick() => {
-                let msg = ServerMessage::Event {
-                    event: Event::Heartbeat {
-                        timestamp: Utc::now().timestamp(),
-                    },
-                };
+                let msg = ServerMessage::from(Event::Heartbeat {
+                    timestamp: Utc::now().timestamp(),
+                });
                 if let Ok(json) = serde_json::to_string(&msg)
                     && sender.send(Message::Text(json.into())).await.is_err() {
                         break;
@@ -151,17 +188,12 @@ async fn handle_socket(socket: WebSocket, state: Arc<ServerState>) {
             // Forward events from broadcaster to client
             result = event_rx.recv() => {
                 match result {
-                    Ok(event) => {
-                        // Check if client is subscribed to this topic
-                        let topic = event.topic();
-                        let alias = event.alias();
-
-                        let should_send = subscribed_topics.contains("*")
-                            || subscribed_topics.contains(topic)
-                            || alias.map(|a| subscribed_topics.contains(&format!("{}:{}", topic, a))).unwrap_or(false);
-
-                        if should_send {
-                            let msg = ServerMessage::Event { event };
+                    Ok(sequenced) => {
+                        if subscribed_topics.iter().any(|t| sequenced.event.matches_topic(t)) {
+                            let msg = ServerMessage::Event {
+                                event: sequenced.event,
+                                seq: Some(sequenced.seq),
+                            };
                             if let Ok(json) = serde_json::to_string(&msg)
                                 && sender.send(Message::Text(json.into())).await.is_err() {
                                     break;
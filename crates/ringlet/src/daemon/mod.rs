@@ -5,17 +5,22 @@
 
 mod agent_registry;
 mod agent_usage;
+pub(crate) mod budget;
+mod bundle;
 mod claude_import;
 mod events;
-mod execution;
+pub(crate) mod execution;
 mod handlers;
+mod health;
 mod http;
-mod pricing;
+pub(crate) mod oauth;
+pub(crate) mod pricing;
 mod profile_manager;
 mod profile_store;
 mod provider_registry;
 mod proxy_manager;
 mod registry_client;
+mod run_history;
 mod secret_store;
 pub(crate) mod server;
 mod telemetry;
@@ -57,12 +62,6 @@ pub async fn run_daemon(args: DaemonArgs) -> Result<()> {
     std::fs::write(paths.daemon_pid(), pid.to_string())?;
     info!("PID {} written to {}", pid, paths.daemon_pid().display());
 
-    // Write endpoint file for CLI discovery
-    std::fs::write(
-        paths.daemon_endpoint(),
-        socket_path.to_string_lossy().as_ref(),
-    )?;
-
     // Load user config
     let config = ringlet_core::UserConfig::load(&paths.config_file()).unwrap_or_default();
 
@@ -82,6 +81,18 @@ pub async fn run_daemon(args: DaemonArgs) -> Result<()> {
     // Create shared state
     let state = Arc::new(ServerState::new(paths.clone(), shutdown_tx)?);
 
+    // Watch spend against configured budgets
+    budget::spawn(state.clone());
+
+    // Take the normal shutdown path when a service manager stops the daemon,
+    // so terminal sessions and proxies are cleaned up below
+    let signal_state = state.clone();
+    tokio::spawn(async move {
+        wait_for_shutdown_signal().await;
+        info!("Shutdown signal received");
+        handlers::system::shutdown(&signal_state).await;
+    });
+
     // Get HTTP port from config
     let http_port = config.daemon.http_port;
 
@@ -99,10 +110,25 @@ pub async fn run_daemon(args: DaemonArgs) -> Result<()> {
         info!("HTTP auth token saved to {:?}", http::token_file_path());
     }
 
+    // Relaunch terminal sessions created with `persist: true`, owned by the new token
+    let restore_state = state.clone();
+    let owner_token_hash = http::auth::hash_token(&http_token);
+    tokio::spawn(async move {
+        handlers::terminal::restore_persisted(&owner_token_hash, &restore_state).await;
+    });
+
     // Start HTTP server in background task
     let http_state = state.clone();
+    let http_config = config.clone();
     let http_handle = tokio::spawn(async move {
-        http::run_http_server(http_state, http_port, http_token, http_shutdown_rx).await;
+        http::run_http_server(
+            http_state,
+            http_port,
+            http_token,
+            http_config,
+            http_shutdown_rx,
+        )
+        .await;
     });
 
     // Run the IPC server (blocks until shutdown)
@@ -147,3 +173,34 @@ pub async fn run_daemon(args: DaemonArgs) -> Result<()> {
 
     Ok(())
 }
+
+/// Wait for SIGTERM or SIGINT (Ctrl-C on Windows).
+async fn wait_for_shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        match (
+            signal(SignalKind::terminate()),
+            signal(SignalKind::interrupt()),
+        ) {
+            (Ok(mut terminate), Ok(mut interrupt)) => {
+                tokio::select! {
+                    _ = terminate.recv() => {}
+                    _ = interrupt.recv() => {}
+                }
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                error!("Failed to install signal handlers: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to install Ctrl-C handler: {}", e);
+            std::future::pending::<()>().await;
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! OAuth 2.0 token requests for device-login providers.
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use ringlet_core::DeviceAuthConfig;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Grant type for polling the token endpoint (RFC 8628 section 3.4).
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Seconds between polls when the provider doesn't say.
+pub const DEFAULT_POLL_INTERVAL: u64 = 5;
+
+/// Timeout for each request to the provider's auth endpoints.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Tokens expiring within this window are refreshed before a run.
+pub const REFRESH_MARGIN: chrono::Duration = chrono::Duration::minutes(5);
+
+/// Response of the device authorization endpoint.
+#[derive(Debug, Deserialize)]
+pub struct DeviceCode {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    #[serde(default)]
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+    #[serde(default = "default_poll_interval")]
+    pub interval: u64,
+}
+
+fn default_poll_interval() -> u64 {
+    DEFAULT_POLL_INTERVAL
+}
+
+/// Tokens issued by the token endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenGrant {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    /// Lifetime of the access token in seconds, if the provider says.
+    pub expires_in: Option<u64>,
+}
+
+impl TokenGrant {
+    /// When the access token expires, counting from `issued_at`.
+    pub fn expires_at(&self, issued_at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let secs = i64::try_from(self.expires_in?).ok()?;
+        issued_at.checked_add_signed(chrono::Duration::seconds(secs))
+    }
+}
+
+/// Outcome of one request to the token endpoint.
+#[derive(Debug, PartialEq, Eq)]
+pub enum TokenPoll {
+    Granted(TokenGrant),
+    Pending,
+    SlowDown,
+    Failed(String),
+}
+
+/// Whether a token expiring at `expires_at` should be refreshed at `now`.
+pub fn needs_refresh(expires_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    expires_at - now <= REFRESH_MARGIN
+}
+
+/// Ask the device authorization endpoint for a device and user code.
+pub fn request_device_code(config: &DeviceAuthConfig) -> Result<DeviceCode> {
+    let mut form = vec![("client_id", config.client_id.as_str())];
+    if let Some(scope) = &config.scope {
+        form.push(("scope", scope.as_str()));
+    }
+
+    let response = ureq::post(&config.device_authorization_url)
+        .timeout(REQUEST_TIMEOUT)
+        .set("Accept", "application/json")
+        .send_form(&form)
+        .with_context(|| {
+            format!(
+                "Failed to request a login code from {}",
+                config.device_authorization_url
+            )
+        })?;
+    response
+        .into_json()
+        .context("Invalid response from the device authorization endpoint")
+}
+
+/// Poll the token endpoint once for a device code.
+pub fn poll_device_token(config: &DeviceAuthConfig, device_code: &str) -> Result<TokenPoll> {
+    request_token(
+        config,
+        &[
+            ("grant_type", DEVICE_CODE_GRANT),
+            ("device_code", device_code),
+            ("client_id", config.client_id.as_str()),
+        ],
+    )
+}
+
+/// Exchange a refresh token for a new access token.
+pub fn refresh_token(config: &DeviceAuthConfig, refresh_token: &str) -> Result<TokenGrant> {
+    let poll = request_token(
+        config,
+        &[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+            ("client_id", config.client_id.as_str()),
+        ],
+    )?;
+    match poll {
+        TokenPoll::Granted(grant) => Ok(grant),
+        TokenPoll::Failed(error) => Err(anyhow!("Token refresh failed: {}", error)),
+        TokenPoll::Pending | TokenPoll::SlowDown => {
+            Err(anyhow!("Token refresh failed: unexpected pending response"))
+        }
+    }
+}
+
+fn request_token(config: &DeviceAuthConfig, form: &[(&str, &str)]) -> Result<TokenPoll> {
+    let result = ureq::post(&config.token_url)
+        .timeout(REQUEST_TIMEOUT)
+        .set("Accept", "application/json")
+        .send_form(form);
+
+    // Pending and failed requests come back as 400 with an `error` field
+    let body = match result {
+        Ok(response) => response.into_string()?,
+        Err(ureq::Error::Status(_, response)) => response.into_string()?,
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to reach {}", config.token_url));
+        }
+    };
+    Ok(parse_token_response(&body))
+}
+
+fn parse_token_response(body: &str) -> TokenPoll {
+    #[derive(Deserialize)]
+    struct TokenResponse {
+        access_token: Option<String>,
+        refresh_token: Option<String>,
+        expires_in: Option<u64>,
+        error: Option<String>,
+        error_description: Option<String>,
+    }
+
+    let Ok(response) = serde_json::from_str::<TokenResponse>(body) else {
+        return TokenPoll::Failed(format!("unexpected token response: {}", body.trim()));
+    };
+    if let Some(access_token) = response.access_token.filter(|t| !t.is_empty()) {
+        return TokenPoll::Granted(TokenGrant {
+            access_token,
+            refresh_token: response.refresh_token.filter(|t| !t.is_empty()),
+            expires_in: response.expires_in,
+        });
+    }
+    match response.error.as_deref() {
+        Some("authorization_pending") => TokenPoll::Pending,
+        Some("slow_down") => TokenPoll::SlowDown,
+        Some("access_denied") => TokenPoll::Failed("access was denied".to_string()),
+        Some("expired_token") => TokenPoll::Failed("the login code expired".to_string()),
+        Some(error) => TokenPoll::Failed(
+            response
+                .error_description
+                .unwrap_or_else(|| error.to_string()),
+        ),
+        None => TokenPoll::Failed("no access token in response".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_token_response() {
+        assert_eq!(
+            parse_token_response(r#"{"access_token":"tok","token_type":"bearer"}"#),
+            TokenPoll::Granted(TokenGrant {
+                access_token: "tok".to_string(),
+                refresh_token: None,
+                expires_in: None,
+            })
+        );
+        assert_eq!(
+            parse_token_response(
+                r#"{"access_token":"tok","refresh_token":"ref","expires_in":3600}"#
+            ),
+            TokenPoll::Granted(TokenGrant {
+                access_token: "tok".to_string(),
+                refresh_token: Some("ref".to_string()),
+                expires_in: Some(3600),
+            })
+        );
+        assert_eq!(
+            parse_token_response(r#"{"error":"authorization_pending"}"#),
+            TokenPoll::Pending
+        );
+        assert_eq!(
+            parse_token_response(r#"{"error":"slow_down","interval":10}"#),
+            TokenPoll::SlowDown
+        );
+        assert_eq!(
+            parse_token_response(
+                r#"{"error":"invalid_client","error_description":"Unknown client"}"#
+            ),
+            TokenPoll::Failed("Unknown client".to_string())
+        );
+        assert!(matches!(
+            parse_token_response("<html>"),
+            TokenPoll::Failed(_)
+        ));
+    }
+
+    #[test]
+    fn test_needs_refresh() {
+        let now = Utc::now();
+        assert!(needs_refresh(now - chrono::Duration::minutes(1), now));
+        assert!(needs_refresh(now + chrono::Duration::minutes(4), now));
+        assert!(!needs_refresh(now + chrono::Duration::hours(1), now));
+
+        let grant = TokenGrant {
+            access_token: "tok".to_string(),
+            refresh_token: None,
+            expires_in: Some(60),
+        };
+        assert_eq!(
+            grant.expires_at(now),
+            Some(now + chrono::Duration::seconds(60))
+        );
+    }
+}
@@ -2,11 +2,12 @@
 //!
 //! This module handles:
 //! - Loading model pricing from cached LiteLLM JSON
+//! - Matching recorded model names to pricing keys (aliases, then normalization)
 //! - Calculating costs from token usage
 //! - Only applies to "self" provider profiles
 
 use anyhow::{Context, Result};
-use ringlet_core::{CostBreakdown, LiteLLMModelPricing, RingletPaths, TokenUsage};
+use ringlet_core::{CostBreakdown, LiteLLMModelPricing, RingletPaths, TokenUsage, UserConfig};
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::sync::RwLock;
@@ -19,6 +20,8 @@ pub const LITELLM_PRICING_URL: &str =
 /// Pricing loader for LiteLLM model pricing data.
 pub struct PricingLoader {
     paths: RingletPaths,
+    /// Model name overrides from `[usage.model_aliases]`.
+    aliases: HashMap<String, String>,
     /// Cached pricing data (loaded lazily).
     cache: RwLock<Option<HashMap<String, LiteLLMModelPricing>>>,
 }
@@ -54,8 +57,12 @@ impl From<RawLiteLLMPricing> for LiteLLMModelPricing {
 impl PricingLoader {
     /// Create a new pricing loader.
     pub fn new(paths: RingletPaths) -> Self {
+        let aliases = UserConfig::load(&paths.config_file())
+            .map(|config| config.usage.model_aliases)
+            .unwrap_or_default();
         Self {
             paths,
+            aliases,
             cache: RwLock::new(None),
         }
     }
@@ -96,7 +103,7 @@ impl PricingLoader {
 
         if !cache_path.exists() {
             return Err(anyhow::anyhow!(
-                "LiteLLM pricing cache not found. Run 'ringlet registry sync' first."
+                "LiteLLM pricing cache not found. Run 'ringlet pricing sync' first."
             ));
         }
 
@@ -130,30 +137,31 @@ impl PricingLoader {
     }
 
     /// Get pricing for a specific model.
+    ///
+    /// A configured alias replaces the name before lookup.
     pub fn get_model_pricing(&self, model: &str) -> Option<LiteLLMModelPricing> {
         if let Err(e) = self.ensure_loaded() {
             warn!("Failed to load pricing data: {}", e);
             return None;
         }
 
-        if let Ok(cache) = self.cache.read()
-            && let Some(data) = cache.as_ref()
-        {
-            // Try exact match first
-            if let Some(pricing) = data.get(model) {
-                return Some(pricing.clone());
-            }
-
-            // Try with common prefixes/variations
-            // e.g., "claude-3-5-sonnet-20241022" might be stored as "claude-3-5-sonnet"
-            for (key, pricing) in data.iter() {
-                if model.starts_with(key) || key.starts_with(model) {
-                    return Some(pricing.clone());
-                }
-            }
-        }
+        let model = self.aliases.get(model).map_or(model, String::as_str);
+        let cache = self.cache.read().ok()?;
+        lookup(cache.as_ref()?, model).cloned()
+    }
 
-        None
+    /// Whether the pricing table has an entry for `model`, ignoring aliases.
+    ///
+    /// Fails when no pricing data has been synced yet.
+    pub fn has_model(&self, model: &str) -> Result<bool> {
+        self.ensure_loaded()?;
+        let cache = self
+            .cache
+            .read()
+            .map_err(|_| anyhow::anyhow!("Pricing cache lock poisoned"))?;
+        Ok(cache
+            .as_ref()
+            .is_some_and(|data| lookup(data, model).is_some()))
     }
 
     /// Calculate cost for token usage.
@@ -195,6 +203,75 @@ impl PricingLoader {
     }
 }
 
+/// Find the pricing entry for a model name.
+///
+/// Tries the name as recorded, then normalized forms of it, then the latest
+/// dated key for it. Only provider prefixes, dates and `-latest` are ignored,
+/// so `claude-3-5-sonnet-20241022` can match `claude-3-5-sonnet` but neither
+/// `gpt-4o` nor `gpt-4-turbo` matches `gpt-4`.
+fn lookup<'a>(
+    data: &'a HashMap<String, LiteLLMModelPricing>,
+    model: &str,
+) -> Option<&'a LiteLLMModelPricing> {
+    let variants = name_variants(model);
+    if let Some(pricing) = variants.iter().find_map(|name| data.get(name)) {
+        return Some(pricing);
+    }
+
+    variants.iter().find_map(|name| {
+        // The latest dated key for the name
+        data.keys()
+            .filter(|key| strip_version_suffix(key) == Some(name.as_str()))
+            .max()
+            .and_then(|key| data.get(key))
+    })
+}
+
+/// Normalized forms of a model name to look up, the name itself first.
+///
+/// Drops provider prefixes (`anthropic/claude-...`), Vertex-style `@` dates,
+/// `-latest` and date suffixes (`-20241022`, `-2024-10-22`).
+fn name_variants(model: &str) -> Vec<String> {
+    let mut variants = vec![model.to_string()];
+    let mut add = |name: &str| {
+        if !name.is_empty() && !variants.iter().any(|variant| variant == name) {
+            variants.push(name.to_string());
+        }
+    };
+
+    let unprefixed = model.rsplit('/').next().unwrap_or(model);
+    for name in [model, unprefixed] {
+        let dashed = name.replace('@', "-");
+        add(&dashed);
+        add(name);
+        if let Some(undated) = strip_version_suffix(&dashed) {
+            add(undated);
+        }
+    }
+    variants
+}
+
+/// The name without a trailing `-latest` or date, if it has one.
+fn strip_version_suffix(name: &str) -> Option<&str> {
+    if let Some(base) = name.strip_suffix("-latest") {
+        return Some(base);
+    }
+
+    let is_date = |suffix: &str| {
+        let digits = suffix.chars().filter(char::is_ascii_digit).count();
+        match suffix.len() {
+            8 => digits == 8,
+            10 => digits == 8 && suffix.as_bytes()[4] == b'-' && suffix.as_bytes()[7] == b'-',
+            _ => false,
+        }
+    };
+    [8, 10].into_iter().find_map(|len| {
+        let split = name.len().checked_sub(len + 1)?;
+        let (base, suffix) = name.split_at_checked(split)?;
+        (suffix.starts_with('-') && is_date(&suffix[1..])).then_some(base)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -284,4 +361,69 @@ mod tests {
         let cost = loader.calculate_cost(&tokens, "claude-3-5-sonnet-20241022", "anthropic");
         assert!(cost.is_none());
     }
+
+    #[test]
+    fn test_lookup_normalizes_model_names() {
+        let data: HashMap<String, LiteLLMModelPricing> = [
+            "claude-3-5-sonnet",
+            "claude-sonnet-4-20250514",
+            "claude-3-opus-20240229",
+            "gpt-4",
+            "gpt-4o-mini",
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(index, key)| {
+            let pricing = LiteLLMModelPricing {
+                input_cost_per_token: Some(index as f64),
+                ..Default::default()
+            };
+            (key.to_string(), pricing)
+        })
+        .collect();
+        let matched = |model: &str| {
+            lookup(&data, model).map(|pricing| pricing.input_cost_per_token.unwrap() as usize)
+        };
+
+        // Dated names against undated keys
+        assert_eq!(matched("claude-3-5-sonnet-20241022"), Some(0));
+        assert_eq!(matched("claude-3-5-sonnet-2024-10-22"), Some(0));
+        assert_eq!(matched("claude-3-5-sonnet-latest"), Some(0));
+        // Provider prefixes and Vertex-style versions
+        assert_eq!(matched("anthropic/claude-sonnet-4-20250514"), Some(1));
+        assert_eq!(matched("claude-sonnet-4@20250514"), Some(1));
+        // Undated names against dated keys
+        assert_eq!(matched("claude-3-opus"), Some(2));
+        assert_eq!(matched("gpt-4o-mini-2024-07-18"), Some(4));
+        assert_eq!(matched("gpt-4o"), None);
+        // Other suffixes name a different model
+        assert_eq!(matched("gpt-4-turbo"), None);
+        assert_eq!(matched("claude-3-5-sonnet-v2"), None);
+    }
+
+    #[test]
+    fn test_configured_alias() {
+        let dir = tempdir().unwrap();
+        let paths = RingletPaths {
+            config_dir: dir.path().to_path_buf(),
+            cache_dir: dir.path().join("cache"),
+            data_dir: dir.path().to_path_buf(),
+        };
+        paths.ensure_dirs().unwrap();
+
+        let cache_path = paths.litellm_pricing_cache();
+        std::fs::create_dir_all(cache_path.parent().unwrap()).unwrap();
+        std::fs::write(&cache_path, create_test_pricing_json()).unwrap();
+        std::fs::write(
+            paths.config_file(),
+            "[usage.model_aliases]\n\"ft:my-finetune\" = \"gpt-4o\"\n",
+        )
+        .unwrap();
+
+        let loader = PricingLoader::new(paths);
+        let pricing = loader.get_model_pricing("ft:my-finetune").unwrap();
+        assert_eq!(pricing.litellm_provider.as_deref(), Some("openai"));
+        assert!(loader.has_model("gpt-4o").unwrap());
+        assert!(!loader.has_model("ft:my-finetune").unwrap());
+    }
 }
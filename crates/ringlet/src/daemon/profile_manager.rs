@@ -9,7 +9,7 @@ use ringlet_core::{
     expand_template,
 };
 use std::collections::HashMap;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// Profile manager.
 pub struct ProfileManager {
@@ -19,10 +19,10 @@ pub struct ProfileManager {
 
 impl ProfileManager {
     /// Create a new profile manager.
-    pub fn new(paths: RingletPaths) -> Self {
+    pub fn new(paths: RingletPaths, secret_store: SecretStore) -> Self {
         Self {
             profile_store: ProfileStore::new(paths),
-            secret_store: SecretStore::new(),
+            secret_store,
         }
     }
 
@@ -53,6 +53,10 @@ impl ProfileManager {
         {
             env.insert("_RINGLET_KEYCHAIN_KEY".to_string(), keychain_key);
         }
+        if let Some(refresh_token) = &request.refresh_token {
+            self.secret_store
+                .store_refresh_token(&request.alias, refresh_token)?;
+        }
 
         // Create profile
         let profile = Profile {
@@ -75,12 +79,20 @@ impl ProfileManager {
                 enabled_hooks: request.hooks.clone(),
                 enabled_mcp_servers: request.mcp_servers.clone(),
                 hooks_config: None,
+                skip_default_hooks: false,
                 proxy_config: if request.proxy {
                     Some(ProfileProxyConfig::default())
                 } else {
                     None
                 },
                 alias_path: None,
+                pre_run: None,
+                post_run: None,
+                launch_wrapper: request.launch_wrapper.clone(),
+                token_expires_at: request.token_expires_at,
+                quota_daily_usd: None,
+                quota_monthly_usd: None,
+                capture_logs: false,
             },
         };
 
@@ -91,10 +103,129 @@ impl ProfileManager {
         Ok(profile)
     }
 
+    /// Rename a profile, moving its home directory and stored API key along with it.
+    ///
+    /// The home directory is only moved when it is named after the old alias,
+    /// so custom homes stay where they are. If a step fails, the steps before
+    /// it are undone so the profile keeps its old name.
+    pub fn rename(&self, old_alias: &str, new_alias: &str) -> Result<Profile> {
+        let mut profile = self.profile_store.rename(old_alias, new_alias)?;
+        let old_home = profile.metadata.home.clone();
+
+        let result = self.move_renamed(&mut profile, old_alias, new_alias);
+        if let Err(e) = result {
+            if profile.metadata.home != old_home
+                && let Err(undo) = std::fs::rename(&profile.metadata.home, &old_home)
+            {
+                warn!(
+                    "Failed to move profile home {:?} back: {}",
+                    profile.metadata.home, undo
+                );
+            }
+            if let Err(undo) = self.profile_store.rename(new_alias, old_alias) {
+                warn!("Failed to restore profile {}: {:#}", old_alias, undo);
+            }
+            return Err(e);
+        }
+
+        info!("Renamed profile: {} -> {}", old_alias, new_alias);
+        Ok(profile)
+    }
+
+    /// Move the home and secrets of a profile already renamed in the store.
+    ///
+    /// Updates `profile.metadata.home` as soon as the home has moved, so the
+    /// caller can move it back if a later step fails.
+    fn move_renamed(&self, profile: &mut Profile, old_alias: &str, new_alias: &str) -> Result<()> {
+        let home = profile.metadata.home.clone();
+        if home.file_name().is_some_and(|name| name == old_alias) && home.exists() {
+            let new_home = home.with_file_name(new_alias);
+            if new_home.exists() {
+                warn!(
+                    "Not moving profile home {:?}: {:?} already exists",
+                    home, new_home
+                );
+            } else {
+                std::fs::rename(&home, &new_home)
+                    .map_err(|e| anyhow!("Failed to move profile home {:?}: {}", home, e))?;
+                info!("Moved profile home: {:?} -> {:?}", home, new_home);
+                profile.metadata.home = new_home;
+            }
+        }
+
+        match self.secret_store.rename_api_key(old_alias, new_alias)? {
+            Some(keychain_key) => {
+                profile
+                    .env
+                    .insert("_RINGLET_KEYCHAIN_KEY".to_string(), keychain_key);
+            }
+            None => {
+                profile.env.remove("_RINGLET_KEYCHAIN_KEY");
+            }
+        }
+
+        if let Err(e) = self.profile_store.update(profile) {
+            if let Err(undo) = self.secret_store.rename_api_key(new_alias, old_alias) {
+                warn!("Failed to move API key back to {}: {:#}", old_alias, undo);
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Store a new API key for a profile.
+    pub fn set_api_key(&self, alias: &str, api_key: &str) -> Result<Profile> {
+        let mut profile = self
+            .profile_store
+            .get(alias)?
+            .ok_or_else(|| anyhow!("Profile not found: {}", alias))?;
+
+        match self.secret_store.store_api_key(alias, api_key)? {
+            Some(keychain_key) => {
+                profile
+                    .env
+                    .insert("_RINGLET_KEYCHAIN_KEY".to_string(), keychain_key);
+            }
+            None => {
+                profile.env.remove("_RINGLET_KEYCHAIN_KEY");
+            }
+        }
+
+        self.profile_store.update(&profile)?;
+
+        info!("Stored new API key for profile: {}", alias);
+        Ok(profile)
+    }
+
+    /// Save a profile restored from a configuration bundle, creating its home.
+    ///
+    /// A profile with the same alias is replaced, keeping its stored API key.
+    pub fn restore(&self, mut profile: Profile) -> Result<()> {
+        let home = &profile.metadata.home;
+        std::fs::create_dir_all(home)
+            .map_err(|e| anyhow!("Failed to create profile home {:?}: {}", home, e))?;
+
+        match self.profile_store.get(&profile.alias)? {
+            Some(existing) => {
+                if let Some(keychain_key) = existing.env.get("_RINGLET_KEYCHAIN_KEY") {
+                    profile
+                        .env
+                        .insert("_RINGLET_KEYCHAIN_KEY".to_string(), keychain_key.clone());
+                }
+                self.profile_store.update(&profile)?;
+            }
+            None => self.profile_store.save_new(&profile)?,
+        }
+
+        info!("Restored profile: {}", profile.alias);
+        Ok(())
+    }
+
     /// Delete a profile.
     pub fn delete(&self, alias: &str) -> Result<()> {
         let profile = self.profile_store.delete(alias)?;
         self.secret_store.delete_api_key(alias)?;
+        self.secret_store.delete_refresh_token(alias)?;
 
         // Optionally delete profile home (ask user first in real implementation)
         // For now, just log
@@ -104,3 +235,37 @@ impl ProfileManager {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::profile_store::{test_paths, test_profile};
+    use ringlet_core::config::SecretBackend;
+
+    #[test]
+    fn test_failed_rename_keeps_old_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = test_paths(dir.path());
+        paths.ensure_dirs().unwrap();
+        let secrets = SecretStore::new(SecretBackend::File, dir.path().join("secrets"));
+        let manager = ProfileManager::new(paths.clone(), secrets.clone());
+
+        let home = dir.path().join("homes").join("work");
+        std::fs::create_dir_all(&home).unwrap();
+        manager.restore(test_profile("work", &home)).unwrap();
+        secrets.store_api_key("work", "sk-test").unwrap();
+
+        // A directory where the new key file goes makes the key move fail
+        // after the profile and home have already been renamed.
+        std::fs::create_dir_all(dir.path().join("secrets").join("personal.key")).unwrap();
+
+        assert!(manager.rename("work", "personal").is_err());
+
+        let store = ProfileStore::new(paths);
+        assert!(store.get("work").unwrap().is_some());
+        assert!(store.get("personal").unwrap().is_none());
+        assert!(home.exists());
+        assert!(!dir.path().join("homes").join("personal").exists());
+        assert_eq!(secrets.get_api_key("work").unwrap(), "sk-test");
+    }
+}
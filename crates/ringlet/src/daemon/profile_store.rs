@@ -44,7 +44,11 @@ impl ProfileStore {
         Ok(self.paths.profiles_dir().join(format!("{}.json", alias)))
     }
 
-    pub fn list(&self, agent_id: Option<&str>) -> Result<Vec<ProfileInfo>> {
+    pub fn list(
+        &self,
+        agent_id: Option<&str>,
+        provider_id: Option<&str>,
+    ) -> Result<Vec<ProfileInfo>> {
         let profiles_dir = self.paths.profiles_dir();
         let mut profiles = Vec::new();
 
@@ -60,6 +64,7 @@ impl ProfileStore {
                 && let Ok(content) = std::fs::read_to_string(&path)
                 && let Ok(profile) = serde_json::from_str::<Profile>(&content)
                 && (agent_id.is_none() || agent_id == Some(profile.agent_id.as_str()))
+                && (provider_id.is_none() || provider_id == Some(profile.provider_id.as_str()))
             {
                 profiles.push(profile.to_info());
             }
@@ -69,6 +74,28 @@ impl ProfileStore {
         Ok(profiles)
     }
 
+    /// List profile aliases without reading the profiles themselves.
+    pub fn names(&self) -> Result<Vec<String>> {
+        let profiles_dir = self.paths.profiles_dir();
+        let mut names = Vec::new();
+
+        if !profiles_dir.exists() {
+            return Ok(names);
+        }
+
+        for entry in std::fs::read_dir(&profiles_dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "json")
+                && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
+            {
+                names.push(stem.to_string());
+            }
+        }
+
+        names.sort();
+        Ok(names)
+    }
+
     pub fn get(&self, alias: &str) -> Result<Option<Profile>> {
         let profile_file = self.profile_file(alias)?;
         if !profile_file.exists() {
@@ -121,6 +148,29 @@ impl ProfileStore {
         Ok(profile)
     }
 
+    /// Move a profile to a new alias, keeping everything else as-is.
+    pub fn rename(&self, old_alias: &str, new_alias: &str) -> Result<Profile> {
+        let old_file = self.profile_file(old_alias)?;
+        let new_file = self.profile_file(new_alias)?;
+
+        if !old_file.exists() {
+            return Err(anyhow!("Profile not found: {}", old_alias));
+        }
+        if new_file.exists() {
+            return Err(anyhow!("Profile already exists: {}", new_alias));
+        }
+
+        let content = std::fs::read_to_string(&old_file)?;
+        let mut profile: Profile = serde_json::from_str(&content)?;
+        profile.alias = new_alias.to_string();
+
+        std::fs::write(&new_file, serde_json::to_string_pretty(&profile)?)?;
+        std::fs::remove_file(&old_file)?;
+
+        debug!("Renamed profile: {} -> {}", old_alias, new_alias);
+        Ok(profile)
+    }
+
     pub fn mark_used(&self, alias: &str) -> Result<()> {
         let mut profile = self
             .get(alias)?
@@ -136,3 +186,47 @@ impl ProfileStore {
         Ok(profile.metadata.home)
     }
 }
+
+/// Paths rooted in a test's temporary directory.
+#[cfg(test)]
+pub(crate) fn test_paths(dir: &std::path::Path) -> RingletPaths {
+    RingletPaths {
+        config_dir: dir.join("config"),
+        cache_dir: dir.join("cache"),
+        data_dir: dir.join("data"),
+    }
+}
+
+/// A Claude profile with no environment or hooks, living in `home`.
+#[cfg(test)]
+pub(crate) fn test_profile(alias: &str, home: &std::path::Path) -> Profile {
+    Profile {
+        alias: alias.to_string(),
+        agent_id: "claude".to_string(),
+        provider_id: "anthropic".to_string(),
+        endpoint_id: "default".to_string(),
+        model: "claude-sonnet-4".to_string(),
+        env: Default::default(),
+        args: Vec::new(),
+        working_dir: None,
+        metadata: ringlet_core::ProfileMetadata {
+            home: home.to_path_buf(),
+            created_at: chrono::Utc::now(),
+            last_used: None,
+            total_runs: 0,
+            enabled_hooks: Vec::new(),
+            enabled_mcp_servers: Vec::new(),
+            hooks_config: None,
+            skip_default_hooks: false,
+            proxy_config: None,
+            alias_path: None,
+            pre_run: None,
+            post_run: None,
+            launch_wrapper: None,
+            token_expires_at: None,
+            quota_daily_usd: None,
+            quota_monthly_usd: None,
+            capture_logs: false,
+        },
+    }
+}
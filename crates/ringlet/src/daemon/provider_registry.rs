@@ -92,11 +92,21 @@ impl ProviderRegistry {
         self.providers.get(id)
     }
 
+    /// Add or replace a provider manifest.
+    pub fn insert(&mut self, manifest: ProviderManifest) {
+        self.providers.insert(manifest.id.clone(), manifest);
+    }
+
     /// Get all provider IDs.
     pub fn ids(&self) -> impl Iterator<Item = &str> {
         self.providers.keys().map(|s| s.as_str())
     }
 
+    /// Iterate over all provider manifests.
+    pub fn manifests(&self) -> impl Iterator<Item = &ProviderManifest> {
+        self.providers.values()
+    }
+
     /// List all providers.
     pub fn list_all(&self) -> Vec<ProviderInfo> {
         let mut infos: Vec<ProviderInfo> = self.providers.values().map(|m| m.to_info()).collect();
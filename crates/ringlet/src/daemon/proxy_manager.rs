@@ -1,19 +1,21 @@
 //! Proxy manager - spawns and manages ultrallm proxy processes per profile.
 
+use crate::daemon::profile_store::ProfileStore;
 use anyhow::{Context, Result, anyhow};
 use chrono::Utc;
 use ringlet_core::{
-    BinaryPaths, ProfileProxyConfig, ProxyInstanceInfo, ProxyStatus, RingletPaths, RoutingStrategy,
-    TokenUsage,
+    BinaryPaths, ProfileProxyConfig, ProxyConfig, ProxyInstanceInfo, ProxyStatus, RingletPaths,
+    RoutingStrategy, TokenUsage,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::Write;
-use std::path::PathBuf;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex as StdMutex};
 use std::time::Duration;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tracing::{debug, error, info, warn};
 
 /// Default base port for proxy instances.
@@ -22,6 +24,71 @@ const BASE_PORT: u16 = 8080;
 const MAX_PORT: u16 = 8180;
 /// Timeout for proxy API requests.
 const PROXY_API_TIMEOUT_SECS: u64 = 5;
+/// How long to wait for a newly spawned proxy to accept connections.
+const PROXY_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long `status` waits for each proxy's `/health` endpoint.
+const PROXY_HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+/// Name of the current log file in a proxy's `logs` directory.
+const LOG_FILE_NAME: &str = "proxy.log";
+/// Name of the file in a proxy's `.ultrallm` directory recording its process.
+const INSTANCE_FILE_NAME: &str = "instance.json";
+/// How long a new daemon waits for a recorded proxy to accept a connection.
+const ADOPT_CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Base URL of a proxy listening on `port`.
+fn proxy_base_url(port: u16) -> String {
+    format!("http://127.0.0.1:{}", port)
+}
+
+/// Probe a proxy's `/health` endpoint, returning why it is unhealthy.
+///
+/// Any answer below 500 counts as healthy: the proxy is up and serving, even
+/// if it wants credentials for the endpoint.
+async fn probe_health(port: u16) -> std::result::Result<(), String> {
+    let url = format!("{}/health", proxy_base_url(port));
+    let probe = tokio::task::spawn_blocking(move || {
+        match ureq::get(&url).timeout(PROXY_HEALTH_TIMEOUT).call() {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::Status(code, _)) if code < 500 => Ok(()),
+            Err(ureq::Error::Status(code, _)) => Err(format!("health check returned {}", code)),
+            Err(e) => Err(format!("health check failed: {}", e)),
+        }
+    });
+    probe
+        .await
+        .unwrap_or_else(|e| Err(format!("health check failed: {}", e)))
+}
+
+/// Status after a health probe. Only proxies believed to be serving are
+/// re-judged; an unhealthy proxy keeps the time it first failed.
+fn probed_status(current: &ProxyStatus, probe: std::result::Result<(), String>) -> ProxyStatus {
+    match (current, probe) {
+        (ProxyStatus::Running | ProxyStatus::Unhealthy { .. }, Ok(())) => ProxyStatus::Running,
+        (ProxyStatus::Unhealthy { since, .. }, Err(reason)) => ProxyStatus::Unhealthy {
+            since: *since,
+            reason,
+        },
+        (ProxyStatus::Running, Err(reason)) => ProxyStatus::Unhealthy {
+            since: Utc::now(),
+            reason,
+        },
+        (other, _) => other.clone(),
+    }
+}
+
+impl ProxyInstance {
+    fn info(&self) -> ProxyInstanceInfo {
+        ProxyInstanceInfo {
+            alias: self.alias.clone(),
+            port: self.port,
+            url: proxy_base_url(self.port),
+            pid: self.pid,
+            status: self.status.clone(),
+            started_at: self.started_at,
+            restart_count: self.restart_count,
+        }
+    }
+}
 
 /// Usage statistics from a proxy instance.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -55,6 +122,14 @@ pub struct ProxyManager {
     instances: RwLock<HashMap<String, ProxyInstance>>,
     /// Port allocator.
     port_allocator: RwLock<PortAllocator>,
+    /// Per-alias locks held across a whole start or restart, so concurrent
+    /// starts for one profile coalesce into a single process.
+    start_locks: StdMutex<HashMap<String, Arc<Mutex<()>>>>,
+    /// Runs holding an ephemeral proxy, by profile alias. The proxy is
+    /// stopped when the last of them is released.
+    ephemeral_runs: StdMutex<HashMap<String, HashSet<String>>>,
+    /// Log rotation settings.
+    config: ProxyConfig,
     /// Paths configuration.
     paths: RingletPaths,
 }
@@ -67,8 +142,11 @@ pub struct ProxyInstance {
     pub port: u16,
     /// Process ID.
     pub pid: u32,
-    /// The child process handle.
-    pub process: Child,
+    /// The child process handle; `None` for a proxy adopted from an earlier daemon.
+    pub process: Option<Child>,
+    /// When the process started, in clock ticks since boot. Tells the proxy
+    /// apart from a later process that reuses its PID.
+    pub process_start: Option<u64>,
     /// Path to the config file.
     pub config_path: PathBuf,
     /// Path to the log file.
@@ -81,6 +159,316 @@ pub struct ProxyInstance {
     pub restart_count: u32,
 }
 
+/// On-disk record of a running proxy, kept in its `.ultrallm` directory so a
+/// restarted daemon can adopt the process instead of starting another.
+#[derive(Debug, Serialize, Deserialize)]
+struct InstanceRecord {
+    port: u16,
+    pid: u32,
+    #[serde(default)]
+    process_start: Option<u64>,
+    started_at: chrono::DateTime<Utc>,
+    #[serde(default)]
+    restart_count: u32,
+}
+
+impl ProxyInstance {
+    /// Path of the instance record next to the proxy's config.
+    fn record_path(&self) -> PathBuf {
+        self.config_path.with_file_name(INSTANCE_FILE_NAME)
+    }
+
+    /// Record the running process so a restarted daemon can adopt it.
+    fn save_record(&self) {
+        let record = InstanceRecord {
+            port: self.port,
+            pid: self.pid,
+            process_start: self.process_start,
+            started_at: self.started_at,
+            restart_count: self.restart_count,
+        };
+        let path = self.record_path();
+        let result = serde_json::to_string_pretty(&record)
+            .map_err(std::io::Error::from)
+            .and_then(|content| std::fs::write(&path, content));
+        if let Err(e) = result {
+            warn!("Failed to write proxy instance record {:?}: {}", path, e);
+        }
+    }
+
+    /// Remove the instance record; a missing record is ignored.
+    fn remove_record(&self) {
+        remove_record(&self.record_path());
+    }
+
+    /// Whether the process has exited.
+    fn has_exited(&mut self) -> std::io::Result<bool> {
+        match self.process.as_mut() {
+            Some(process) => Ok(process.try_wait()?.is_some()),
+            None => Ok(!is_same_process(self.pid, self.process_start)),
+        }
+    }
+
+    /// Ask the process to exit.
+    ///
+    /// An adopted process is only signalled while its PID still belongs to it.
+    #[cfg(unix)]
+    fn terminate(&self) {
+        if self.process.is_some() || is_same_process(self.pid, self.process_start) {
+            unsafe {
+                libc::kill(self.pid as i32, libc::SIGTERM);
+            }
+        }
+    }
+
+    /// Kill the process outright.
+    fn kill(&mut self) {
+        match self.process.as_mut() {
+            Some(process) => {
+                let _ = process.kill();
+            }
+            None =>
+            {
+                #[cfg(unix)]
+                if is_same_process(self.pid, self.process_start) {
+                    unsafe {
+                        libc::kill(self.pid as i32, libc::SIGKILL);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn remove_record(path: &Path) {
+    if let Err(e) = std::fs::remove_file(path)
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        warn!("Failed to remove proxy instance record {:?}: {}", path, e);
+    }
+}
+
+/// Whether a process with this ID exists.
+#[cfg(unix)]
+fn pid_alive(pid: u32) -> bool {
+    unsafe { libc::kill(pid as i32, 0) == 0 }
+}
+
+#[cfg(not(unix))]
+fn pid_alive(_pid: u32) -> bool {
+    false
+}
+
+/// When a process started, in clock ticks since boot.
+#[cfg(target_os = "linux")]
+fn process_start_time(pid: u32) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name may contain spaces, so count fields after its closing
+    // parenthesis; the start time is field 22 and the state field 3.
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(22 - 3)?.parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_start_time(_pid: u32) -> Option<u64> {
+    None
+}
+
+/// Whether `pid` is still the process that started at `start`.
+///
+/// Without a start time to compare, the process can't be told apart from one
+/// that reused its PID, so it doesn't count.
+fn is_same_process(pid: u32, start: Option<u64>) -> bool {
+    start.is_some() && pid_alive(pid) && process_start_time(pid) == start
+}
+
+/// Adopt the proxies a previous daemon left running, by profile alias.
+///
+/// Each profile's instance record is checked against the live process, by
+/// PID and start time, and its port; records of proxies that are gone are
+/// removed.
+fn adopt_instances(paths: &RingletPaths) -> HashMap<String, ProxyInstance> {
+    let store = ProfileStore::new(paths.clone());
+    let aliases = match store.names() {
+        Ok(aliases) => aliases,
+        Err(e) => {
+            warn!("Failed to list profiles for proxy adoption: {}", e);
+            return HashMap::new();
+        }
+    };
+
+    let mut adopted = HashMap::new();
+    for alias in aliases {
+        let Ok(home) = store.get_home(&alias) else {
+            continue;
+        };
+        let ultrallm_dir = home.join(".ultrallm");
+        let record_path = ultrallm_dir.join(INSTANCE_FILE_NAME);
+        let Ok(content) = std::fs::read_to_string(&record_path) else {
+            continue;
+        };
+        let record: InstanceRecord = match serde_json::from_str(&content) {
+            Ok(record) => record,
+            Err(e) => {
+                warn!("Ignoring proxy instance record {:?}: {}", record_path, e);
+                remove_record(&record_path);
+                continue;
+            }
+        };
+
+        let listening = std::net::TcpStream::connect_timeout(
+            &std::net::SocketAddr::from(([127, 0, 0, 1], record.port)),
+            ADOPT_CONNECT_TIMEOUT,
+        )
+        .is_ok();
+        if !is_same_process(record.pid, record.process_start) || !listening {
+            debug!("Proxy for '{}' is no longer running", alias);
+            remove_record(&record_path);
+            continue;
+        }
+
+        info!(
+            "Adopted proxy for '{}' on port {} (PID {})",
+            alias, record.port, record.pid
+        );
+        adopted.insert(
+            alias.clone(),
+            ProxyInstance {
+                alias,
+                port: record.port,
+                pid: record.pid,
+                process: None,
+                process_start: record.process_start,
+                config_path: ultrallm_dir.join("config.yaml"),
+                log_path: ultrallm_dir.join("logs").join(LOG_FILE_NAME),
+                started_at: record.started_at,
+                status: ProxyStatus::Running,
+                restart_count: record.restart_count,
+            },
+        );
+    }
+    adopted
+}
+
+/// A proxy's log file, shifted to `proxy.log.1`, `proxy.log.2`, ... once it
+/// reaches the size limit.
+///
+/// Restarts append to the current file rather than truncating it, so the
+/// output leading up to a crash survives the restart; rotation bounds its size.
+struct RotatingLog {
+    /// Path of the current log file.
+    path: PathBuf,
+    /// Open handle to the current log file.
+    file: File,
+    /// Bytes in the current log file.
+    len: u64,
+    /// Size at which the current file is rotated.
+    max_bytes: u64,
+    /// Number of rotated files to keep.
+    keep: usize,
+}
+
+impl RotatingLog {
+    fn open(path: PathBuf, config: &ProxyConfig) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            len,
+            max_bytes: config.log_max_bytes,
+            keep: config.log_keep,
+        })
+    }
+
+    /// Path of the `n`th rotated file.
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    /// Shift rotated files up by one, dropping the oldest, and start a fresh file.
+    fn rotate(&mut self) -> std::io::Result<()> {
+        if self.keep == 0 {
+            std::fs::remove_file(&self.path)?;
+        } else {
+            let _ = std::fs::remove_file(self.rotated_path(self.keep));
+            for n in (1..self.keep).rev() {
+                let from = self.rotated_path(n);
+                if from.exists() {
+                    std::fs::rename(&from, self.rotated_path(n + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.len > 0 && self.len + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.len += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Copy a proxy's output stream into its log until the process closes it.
+fn pump_output(reader: impl Read + Send + 'static, log: Arc<StdMutex<RotatingLog>>) {
+    std::thread::spawn(move || {
+        let path = log.lock().unwrap_or_else(|e| e.into_inner()).path.clone();
+        copy_output(reader, &log, |e| {
+            warn!(
+                "Failed to write proxy log {:?}: {}; discarding output it can't take",
+                path, e
+            )
+        });
+    });
+}
+
+/// Copy `reader` into `log` until end of stream, calling `on_error` for the
+/// first failed write only.
+///
+/// Output that can't be written is dropped rather than left unread, since a
+/// proxy blocked on a full pipe stops serving requests.
+fn copy_output(
+    mut reader: impl Read,
+    log: &StdMutex<impl Write>,
+    on_error: impl FnOnce(std::io::Error),
+) {
+    let mut on_error = Some(on_error);
+    let mut buf = [0u8; 8192];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                let mut log = log.lock().unwrap_or_else(|e| e.into_inner());
+                if let Err(e) = log.write_all(&buf[..n])
+                    && let Some(on_error) = on_error.take()
+                {
+                    on_error(e);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(_) => break,
+        }
+    }
+}
+
 /// Port allocator for proxy instances.
 struct PortAllocator {
     /// Base port number.
@@ -137,6 +525,12 @@ impl PortAllocator {
         ))
     }
 
+    /// Record a port already in use by a profile's proxy, even outside the range.
+    fn reserve(&mut self, alias: &str, port: u16) {
+        self.allocated.insert(port);
+        self.assignments.insert(alias.to_string(), port);
+    }
+
     /// Release a port.
     fn release(&mut self, alias: &str) {
         if let Some(port) = self.assignments.remove(alias) {
@@ -146,8 +540,9 @@ impl PortAllocator {
 }
 
 impl ProxyManager {
-    /// Create a new proxy manager.
-    pub fn new(paths: RingletPaths) -> Self {
+    /// Create a new proxy manager, adopting the proxies a previous daemon
+    /// left running.
+    pub fn new(paths: RingletPaths, config: ProxyConfig) -> Self {
         // Try to find local ultrallm binary
         let binary_path = BinaryPaths::find_local_ultrallm();
 
@@ -157,14 +552,31 @@ impl ProxyManager {
             warn!("ultrallm binary not found - proxy features will be unavailable");
         }
 
+        let instances = adopt_instances(&paths);
+        let mut port_allocator = PortAllocator::new(BASE_PORT, MAX_PORT);
+        for instance in instances.values() {
+            port_allocator.reserve(&instance.alias, instance.port);
+        }
+
         Self {
             binary_path,
-            instances: RwLock::new(HashMap::new()),
-            port_allocator: RwLock::new(PortAllocator::new(BASE_PORT, MAX_PORT)),
+            instances: RwLock::new(instances),
+            port_allocator: RwLock::new(port_allocator),
+            start_locks: StdMutex::new(HashMap::new()),
+            ephemeral_runs: StdMutex::new(HashMap::new()),
+            config,
             paths,
         }
     }
 
+    /// Path of the current log file for a profile's proxy.
+    pub fn log_path(profile_home: &Path) -> PathBuf {
+        profile_home
+            .join(".ultrallm")
+            .join("logs")
+            .join(LOG_FILE_NAME)
+    }
+
     /// Check if ultrallm binary is available.
     pub fn is_available(&self) -> bool {
         self.binary_path.is_some()
@@ -175,12 +587,96 @@ impl ProxyManager {
         self.binary_path.as_ref()
     }
 
+    /// Lock serializing starts and restarts of one profile's proxy.
+    fn start_lock(&self, alias: &str) -> Arc<Mutex<()>> {
+        let mut locks = self.start_locks.lock().unwrap_or_else(|e| e.into_inner());
+        locks.entry(alias.to_string()).or_default().clone()
+    }
+
     /// Start a proxy for a profile.
+    ///
+    /// Concurrent calls for the same alias wait for each other, so only the
+    /// first spawns a process and the rest return its port.
     pub async fn start(
         &self,
         alias: &str,
         profile_home: &std::path::Path,
         config: &ProfileProxyConfig,
+    ) -> Result<u16> {
+        let lock = self.start_lock(alias);
+        let _guard = lock.lock().await;
+        let port = self.start_locked(alias, profile_home, config).await?;
+
+        // Someone wants this proxy to outlive the runs that started it
+        self.ephemeral_runs().remove(alias);
+        Ok(port)
+    }
+
+    /// Start a proxy for a single run of a profile.
+    ///
+    /// The proxy is stopped by [`release_run`](Self::release_run) once every
+    /// run holding it has finished. A proxy that was already running for other
+    /// reasons is shared with the run and left running afterwards.
+    pub async fn start_for_run(
+        &self,
+        alias: &str,
+        run_id: &str,
+        profile_home: &std::path::Path,
+        config: &ProfileProxyConfig,
+    ) -> Result<u16> {
+        let lock = self.start_lock(alias);
+        let _guard = lock.lock().await;
+
+        let persistent = self.instances.read().await.contains_key(alias)
+            && !self.ephemeral_runs().contains_key(alias);
+        let port = self.start_locked(alias, profile_home, config).await?;
+
+        if !persistent {
+            self.ephemeral_runs()
+                .entry(alias.to_string())
+                .or_default()
+                .insert(run_id.to_string());
+        }
+        Ok(port)
+    }
+
+    /// Release a run's hold on its profile's proxy.
+    ///
+    /// Returns whether this stopped the proxy, which happens when it was
+    /// started by [`start_for_run`](Self::start_for_run) and no other run
+    /// still holds it.
+    pub async fn release_run(&self, alias: &str, run_id: &str) -> bool {
+        let lock = self.start_lock(alias);
+        let _guard = lock.lock().await;
+
+        {
+            let mut ephemeral_runs = self.ephemeral_runs();
+            let Some(runs) = ephemeral_runs.get_mut(alias) else {
+                return false;
+            };
+            if !runs.remove(run_id) || !runs.is_empty() {
+                return false;
+            }
+            ephemeral_runs.remove(alias);
+        }
+
+        info!("Stopping ephemeral proxy for profile '{}'", alias);
+        self.shutdown(alias, true).await;
+        true
+    }
+
+    fn ephemeral_runs(&self) -> std::sync::MutexGuard<'_, HashMap<String, HashSet<String>>> {
+        self.ephemeral_runs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Start a proxy; the caller holds the alias's start lock.
+    async fn start_locked(
+        &self,
+        alias: &str,
+        profile_home: &std::path::Path,
+        config: &ProfileProxyConfig,
     ) -> Result<u16> {
         let binary_path = self
             .binary_path
@@ -188,13 +684,20 @@ impl ProxyManager {
             .ok_or_else(|| anyhow!("ultrallm binary not available"))?;
 
         // Check if already running
-        {
+        let stale = {
             let instances = self.instances.read().await;
-            if let Some(instance) = instances.get(alias)
-                && matches!(instance.status, ProxyStatus::Running)
-            {
-                return Ok(instance.port);
+            match instances.get(alias) {
+                Some(instance) if matches!(instance.status, ProxyStatus::Running) => {
+                    return Ok(instance.port);
+                }
+                Some(_) => true,
+                None => false,
             }
+        };
+
+        // Replace an instance that never became healthy, keeping its port
+        if stale {
+            self.shutdown(alias, false).await;
         }
 
         // Allocate port
@@ -203,9 +706,125 @@ impl ProxyManager {
             allocator.allocate(alias, config.port)?
         };
 
-        // Create .ultrallm directory in profile home
         let ultrallm_dir = profile_home.join(".ultrallm");
-        std::fs::create_dir_all(&ultrallm_dir).context("Failed to create .ultrallm directory")?;
+        let instance = self.spawn_instance(binary_path, alias, &ultrallm_dir, port, config)?;
+
+        self.instances
+            .write()
+            .await
+            .insert(alias.to_string(), instance);
+
+        let healthy = self.wait_for_health(port).await;
+        let mut instances = self.instances.write().await;
+        if let Some(instance) = instances.get_mut(alias) {
+            if healthy {
+                instance.status = ProxyStatus::Running;
+            }
+            instance.save_record();
+        }
+
+        Ok(port)
+    }
+
+    /// Restart a proxy for a profile on the port it already holds.
+    ///
+    /// The port assignment is kept across the stop so clients pointed at the
+    /// proxy keep working. With `verify`, the new configuration is first
+    /// brought up on a spare port and health-checked, and the running proxy is
+    /// left alone if that fails. The checked instance is then discarded; the
+    /// restart itself still briefly stops the proxy.
+    pub async fn restart(
+        &self,
+        alias: &str,
+        profile_home: &std::path::Path,
+        config: &ProfileProxyConfig,
+        verify: bool,
+    ) -> Result<u16> {
+        let binary_path = self
+            .binary_path
+            .as_ref()
+            .ok_or_else(|| anyhow!("ultrallm binary not available"))?;
+
+        let lock = self.start_lock(alias);
+        let _guard = lock.lock().await;
+
+        if verify {
+            self.check_candidate(binary_path, alias, profile_home, config)
+                .await?;
+        }
+
+        let restart_count = self
+            .instances
+            .read()
+            .await
+            .get(alias)
+            .map(|i| i.restart_count + 1)
+            .unwrap_or(0);
+
+        self.shutdown(alias, false).await;
+        let port = self.start_locked(alias, profile_home, config).await?;
+
+        if let Some(instance) = self.instances.write().await.get_mut(alias) {
+            instance.restart_count = restart_count;
+            instance.save_record();
+        }
+        Ok(port)
+    }
+
+    /// Start a throwaway instance on a spare port and wait for it to accept connections.
+    async fn check_candidate(
+        &self,
+        binary_path: &std::path::Path,
+        alias: &str,
+        profile_home: &std::path::Path,
+        config: &ProfileProxyConfig,
+    ) -> Result<()> {
+        // Aliases cannot contain '.', so this never collides with a profile
+        let candidate = format!("{}.candidate", alias);
+        let port = self
+            .port_allocator
+            .write()
+            .await
+            .allocate(&candidate, None)?;
+
+        let ultrallm_dir = profile_home.join(".ultrallm").join("candidate");
+        let result = match self.spawn_instance(binary_path, &candidate, &ultrallm_dir, port, config)
+        {
+            Ok(mut instance) => {
+                let healthy = self.wait_for_health(port).await;
+
+                if let Some(process) = instance.process.as_mut() {
+                    let _ = process.kill();
+                    let _ = process.wait();
+                }
+
+                if healthy {
+                    Ok(())
+                } else {
+                    Err(anyhow!(
+                        "New proxy for '{}' failed its health check; the running proxy was left as is (see {:?})",
+                        alias,
+                        instance.log_path
+                    ))
+                }
+            }
+            Err(e) => Err(e),
+        };
+
+        self.port_allocator.write().await.release(&candidate);
+        result
+    }
+
+    /// Spawn an ultrallm process whose config and logs live in `ultrallm_dir`.
+    fn spawn_instance(
+        &self,
+        binary_path: &std::path::Path,
+        alias: &str,
+        ultrallm_dir: &std::path::Path,
+        port: u16,
+        config: &ProfileProxyConfig,
+    ) -> Result<ProxyInstance> {
+        std::fs::create_dir_all(ultrallm_dir).context("Failed to create .ultrallm directory")?;
 
         let logs_dir = ultrallm_dir.join("logs");
         std::fs::create_dir_all(&logs_dir).context("Failed to create logs directory")?;
@@ -214,83 +833,79 @@ impl ProxyManager {
         let config_path = ultrallm_dir.join("config.yaml");
         self.generate_config(&config_path, port, config)?;
 
-        // Open log file
-        let log_path = logs_dir.join("proxy.log");
-        let log_file = File::create(&log_path).context("Failed to create log file")?;
+        // Open log file; output is piped through the daemon so it can be rotated
+        let log_path = logs_dir.join(LOG_FILE_NAME);
+        let log =
+            RotatingLog::open(log_path.clone(), &self.config).context("Failed to open log file")?;
+        let log = Arc::new(StdMutex::new(log));
 
         // Spawn ultrallm process
         info!("Starting proxy for profile '{}' on port {}", alias, port);
-        let process = Command::new(binary_path)
+        let mut process = Command::new(binary_path)
             .args(["serve", "--config", &config_path.to_string_lossy()])
-            .stdout(Stdio::from(log_file.try_clone()?))
-            .stderr(Stdio::from(log_file))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
             .spawn()
             .context("Failed to spawn ultrallm process")?;
 
+        if let Some(stdout) = process.stdout.take() {
+            pump_output(stdout, log.clone());
+        }
+        if let Some(stderr) = process.stderr.take() {
+            pump_output(stderr, log);
+        }
+
         let pid = process.id();
         info!("Proxy started for '{}' with PID {}", alias, pid);
 
-        // Store instance
-        let instance = ProxyInstance {
+        Ok(ProxyInstance {
             alias: alias.to_string(),
             port,
             pid,
-            process,
+            process: Some(process),
+            process_start: process_start_time(pid),
             config_path,
             log_path,
             started_at: Utc::now(),
             status: ProxyStatus::Starting,
             restart_count: 0,
-        };
-
-        self.instances
-            .write()
-            .await
-            .insert(alias.to_string(), instance);
-
-        // Wait a moment for the proxy to start
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-
-        // Check if it's running
-        if self.check_health(port).await {
-            let mut instances = self.instances.write().await;
-            if let Some(instance) = instances.get_mut(alias) {
-                instance.status = ProxyStatus::Running;
-            }
-        }
-
-        Ok(port)
+        })
     }
 
-    /// Stop a proxy for a profile.
+    /// Stop a proxy for a profile and remove its instance record.
     pub async fn stop(&self, alias: &str) -> Result<()> {
+        self.ephemeral_runs().remove(alias);
+        self.shutdown(alias, true).await;
+        Ok(())
+    }
+
+    /// Stop a proxy process, optionally keeping its port reserved for a restart.
+    ///
+    /// The instance record is removed, so a later daemon doesn't adopt it.
+    async fn shutdown(&self, alias: &str, release_port: bool) {
         let mut instances = self.instances.write().await;
 
         if let Some(mut instance) = instances.remove(alias) {
             instance.status = ProxyStatus::Stopping;
+            instance.remove_record();
             info!("Stopping proxy for profile '{}'", alias);
 
             // Try graceful shutdown first
             #[cfg(unix)]
-            {
-                // Send SIGTERM
-                unsafe {
-                    libc::kill(instance.pid as i32, libc::SIGTERM);
-                }
-            }
+            instance.terminate();
 
             // Wait for process to exit (with timeout)
             let timeout = tokio::time::Duration::from_secs(5);
             let start = std::time::Instant::now();
 
             loop {
-                match instance.process.try_wait() {
-                    Ok(Some(_)) => break, // Process exited
-                    Ok(None) => {
+                match instance.has_exited() {
+                    Ok(true) => break, // Process exited
+                    Ok(false) => {
                         if start.elapsed() > timeout {
                             // Force kill
                             warn!("Proxy for '{}' didn't exit gracefully, killing", alias);
-                            let _ = instance.process.kill();
+                            instance.kill();
                             break;
                         }
                         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
@@ -302,12 +917,11 @@ impl ProxyManager {
                 }
             }
 
-            // Release port
-            self.port_allocator.write().await.release(alias);
+            if release_port {
+                self.port_allocator.write().await.release(alias);
+            }
             info!("Proxy stopped for profile '{}'", alias);
         }
-
-        Ok(())
     }
 
     /// Stop all proxies.
@@ -328,31 +942,65 @@ impl ProxyManager {
 
     /// Get status of all proxies.
     pub async fn status(&self) -> Vec<ProxyInstanceInfo> {
+        let instances = self.instances.read().await;
+        instances.values().map(ProxyInstance::info).collect()
+    }
+
+    /// Get live status of all proxies, or just the one for `alias`.
+    ///
+    /// Running and unhealthy proxies are probed concurrently on `/health`, so
+    /// the call takes at most one probe timeout however many proxies run. The
+    /// results are recorded on the instances.
+    pub async fn probe_status(&self, alias: Option<&str>) -> Vec<ProxyInstanceInfo> {
+        let infos: Vec<ProxyInstanceInfo> = {
+            let instances = self.instances.read().await;
+            instances
+                .values()
+                .filter(|i| alias.is_none_or(|alias| i.alias == alias))
+                .map(ProxyInstance::info)
+                .collect()
+        };
+
+        let probes = infos.into_iter().map(|mut info| async move {
+            if matches!(
+                info.status,
+                ProxyStatus::Running | ProxyStatus::Unhealthy { .. }
+            ) {
+                info.status = probed_status(&info.status, probe_health(info.port).await);
+            }
+            info
+        });
+        let infos = futures_util::future::join_all(probes).await;
+
+        let mut instances = self.instances.write().await;
+        for info in &infos {
+            // Skip instances restarted or stopped while the probes ran
+            if let Some(instance) = instances.get_mut(&info.alias)
+                && instance.pid == info.pid
+                && matches!(
+                    instance.status,
+                    ProxyStatus::Running | ProxyStatus::Unhealthy { .. }
+                )
+            {
+                instance.status = info.status.clone();
+            }
+        }
+        infos
+    }
+
+    /// Number of proxy instances that haven't stopped.
+    pub async fn active_count(&self) -> usize {
         let instances = self.instances.read().await;
         instances
             .values()
-            .map(|i| ProxyInstanceInfo {
-                alias: i.alias.clone(),
-                port: i.port,
-                pid: i.pid,
-                status: i.status.clone(),
-                started_at: i.started_at,
-                restart_count: i.restart_count,
-            })
-            .collect()
+            .filter(|i| !matches!(i.status, ProxyStatus::Stopped))
+            .count()
     }
 
     /// Get status of a specific proxy.
     pub async fn status_for(&self, alias: &str) -> Option<ProxyInstanceInfo> {
         let instances = self.instances.read().await;
-        instances.get(alias).map(|i| ProxyInstanceInfo {
-            alias: i.alias.clone(),
-            port: i.port,
-            pid: i.pid,
-            status: i.status.clone(),
-            started_at: i.started_at,
-            restart_count: i.restart_count,
-        })
+        instances.get(alias).map(ProxyInstance::info)
     }
 
     /// Get the proxy URL for a profile if running.
@@ -360,13 +1008,25 @@ impl ProxyManager {
         let instances = self.instances.read().await;
         instances.get(alias).and_then(|i| {
             if matches!(i.status, ProxyStatus::Running) {
-                Some(format!("http://127.0.0.1:{}", i.port))
+                Some(proxy_base_url(i.port))
             } else {
                 None
             }
         })
     }
 
+    /// Poll a freshly spawned proxy until it accepts connections or the startup timeout passes.
+    async fn wait_for_health(&self, port: u16) -> bool {
+        let start = std::time::Instant::now();
+        while start.elapsed() < PROXY_STARTUP_TIMEOUT {
+            if self.check_health(port).await {
+                return true;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        false
+    }
+
     /// Check if a proxy is healthy.
     async fn check_health(&self, port: u16) -> bool {
         // Use a simple TCP connection check since we don't have reqwest
@@ -464,6 +1124,9 @@ router_settings:
     }
 
     /// Read proxy logs for a profile.
+    ///
+    /// Only the current log file is read; rotated files are left to be
+    /// inspected directly.
     pub async fn read_logs(&self, alias: &str, lines: Option<usize>) -> Result<String> {
         let instances = self.instances.read().await;
         let instance = instances
@@ -641,9 +1304,342 @@ impl Drop for ProxyManager {
         // Synchronous cleanup - try to kill all processes
         if let Ok(mut instances) = self.instances.try_write() {
             for (alias, mut instance) in instances.drain() {
-                warn!("Cleaning up proxy for '{}' on drop", alias);
-                let _ = instance.process.kill();
+                if let Some(process) = instance.process.as_mut() {
+                    warn!("Cleaning up proxy for '{}' on drop", alias);
+                    let _ = process.kill();
+                }
+            }
+        }
+    }
+}
+
+/// Manager running a stand-in for ultrallm that records each spawn in
+/// `dir/spawns` and stays alive, with `port` as its only port.
+#[cfg(all(test, unix))]
+pub(crate) fn fake_manager(dir: &Path, port: u16) -> ProxyManager {
+    use std::os::unix::fs::PermissionsExt;
+
+    let binary = dir.join("ultrallm");
+    std::fs::write(
+        &binary,
+        format!(
+            "#!/bin/sh\necho spawn >> {:?}\nexec sleep 30\n",
+            dir.join("spawns")
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    ProxyManager {
+        binary_path: Some(binary),
+        instances: RwLock::new(HashMap::new()),
+        port_allocator: RwLock::new(PortAllocator::new(port, port)),
+        start_locks: StdMutex::new(HashMap::new()),
+        ephemeral_runs: StdMutex::new(HashMap::new()),
+        config: ProxyConfig::default(),
+        paths: crate::daemon::profile_store::test_paths(dir),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::profile_store::test_profile;
+
+    #[test]
+    fn test_port_assignment_survives_until_released() {
+        let mut allocator = PortAllocator::new(BASE_PORT, MAX_PORT);
+        let port = allocator.allocate("work", None).unwrap();
+        let other = allocator.allocate("other", None).unwrap();
+        assert_ne!(port, other);
+
+        // A restart re-allocates without releasing first
+        assert_eq!(allocator.allocate("work", Some(MAX_PORT)).unwrap(), port);
+
+        allocator.release("work");
+        assert_eq!(allocator.allocate("next", None).unwrap(), port);
+    }
+
+    #[test]
+    fn test_log_rotation_keeps_configured_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOG_FILE_NAME);
+        let config = ProxyConfig {
+            log_max_bytes: 10,
+            log_keep: 2,
+        };
+
+        let mut log = RotatingLog::open(path.clone(), &config).unwrap();
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            log.write_all(line.as_bytes()).unwrap();
+        }
+
+        let read = |p: &Path| std::fs::read_to_string(p).unwrap();
+        assert_eq!(read(&path), "fourth\n");
+        assert_eq!(read(&log.rotated_path(1)), "third\n");
+        assert_eq!(read(&log.rotated_path(2)), "second\n");
+        assert!(!log.rotated_path(3).exists());
+
+        // Reopening appends to the current file
+        let mut log = RotatingLog::open(path.clone(), &config).unwrap();
+        log.write_all(b"5\n").unwrap();
+        assert_eq!(read(&path), "fourth\n5\n");
+    }
+
+    #[test]
+    fn test_output_is_drained_after_log_write_fails() {
+        struct FailingLog;
+        impl Write for FailingLog {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("disk full"))
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let output = vec![b'x'; 100_000];
+        let mut reader = std::io::Cursor::new(output);
+        let mut errors = 0;
+        copy_output(&mut reader, &StdMutex::new(FailingLog), |_| errors += 1);
+
+        assert_eq!(reader.position(), 100_000);
+        assert_eq!(errors, 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_concurrent_starts_spawn_one_proxy() {
+        let dir = tempfile::tempdir().unwrap();
+        let spawns = dir.path().join("spawns");
+
+        // The health check only needs something accepting on the proxy port
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let manager = fake_manager(dir.path(), port);
+
+        let home = dir.path().join("home");
+        let config = ProfileProxyConfig::default();
+        let (first, second) = tokio::join!(
+            manager.start("work", &home, &config),
+            manager.start("work", &home, &config)
+        );
+
+        assert_eq!(first.unwrap(), port);
+        assert_eq!(second.unwrap(), port);
+        assert_eq!(manager.status().await.len(), 1);
+
+        // The scripts record themselves asynchronously; give any second one time to show up
+        let spawn_count = || {
+            std::fs::read_to_string(&spawns)
+                .map(|s| s.lines().count())
+                .unwrap_or(0)
+        };
+        for _ in 0..50 {
+            if spawn_count() > 0 {
+                break;
             }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(spawn_count(), 1);
+
+        manager.stop_all().await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_ephemeral_proxy_stops_with_last_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let manager = fake_manager(dir.path(), port);
+
+        let home = dir.path().join("home");
+        let config = ProfileProxyConfig::default();
+        for run in ["a", "b"] {
+            let started = manager.start_for_run("work", run, &home, &config).await;
+            assert_eq!(started.unwrap(), port);
         }
+
+        // Shared by both runs until the last one finishes
+        assert!(!manager.release_run("work", "a").await);
+        assert_eq!(manager.status().await.len(), 1);
+        assert!(manager.release_run("work", "b").await);
+        assert!(manager.status().await.is_empty());
+
+        // A proxy the user started outlives the runs that use it
+        manager.start("work", &home, &config).await.unwrap();
+        manager
+            .start_for_run("work", "c", &home, &config)
+            .await
+            .unwrap();
+        assert!(!manager.release_run("work", "c").await);
+        assert_eq!(manager.status().await.len(), 1);
+
+        // Starting it explicitly also keeps a proxy a run started
+        manager.stop("work").await.unwrap();
+        manager
+            .start_for_run("work", "d", &home, &config)
+            .await
+            .unwrap();
+        manager.start("work", &home, &config).await.unwrap();
+        assert!(!manager.release_run("work", "d").await);
+        assert_eq!(manager.status().await.len(), 1);
+
+        manager.stop_all().await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stopped_proxies_are_not_adopted() {
+        let dir = tempfile::tempdir().unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let manager = fake_manager(dir.path(), port);
+        let paths = manager.paths.clone();
+        paths.ensure_dirs().unwrap();
+
+        let home = dir.path().join("home");
+        ProfileStore::new(paths.clone())
+            .save_new(&test_profile("work", &home))
+            .unwrap();
+
+        manager
+            .start("work", &home, &ProfileProxyConfig::default())
+            .await
+            .unwrap();
+        let record = home.join(".ultrallm").join(INSTANCE_FILE_NAME);
+        assert!(record.exists());
+
+        // A new daemon takes over the running proxy
+        let pid = manager.status_for("work").await.unwrap().pid;
+        let adopted = ProxyManager::new(paths.clone(), ProxyConfig::default());
+        let info = adopted.status_for("work").await.unwrap();
+        assert_eq!((info.port, info.pid), (port, pid));
+        drop(adopted);
+
+        manager.stop_all().await.unwrap();
+        assert!(!record.exists());
+        let fresh = ProxyManager::new(paths, ProxyConfig::default());
+        assert!(fresh.status().await.is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_reused_pid_is_not_adopted_or_killed() {
+        let dir = tempfile::tempdir().unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let paths = fake_manager(dir.path(), port).paths.clone();
+        paths.ensure_dirs().unwrap();
+
+        let home = dir.path().join("home");
+        ProfileStore::new(paths.clone())
+            .save_new(&test_profile("work", &home))
+            .unwrap();
+
+        // The recorded PID now belongs to an unrelated process
+        let mut unrelated = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = unrelated.id();
+        let start = process_start_time(pid).unwrap();
+        assert!(is_same_process(pid, Some(start)));
+        let record_path = home.join(".ultrallm").join(INSTANCE_FILE_NAME);
+        std::fs::create_dir_all(record_path.parent().unwrap()).unwrap();
+        let record = InstanceRecord {
+            port,
+            pid,
+            process_start: Some(start + 1),
+            started_at: Utc::now(),
+            restart_count: 0,
+        };
+        std::fs::write(&record_path, serde_json::to_string(&record).unwrap()).unwrap();
+
+        let manager = ProxyManager::new(paths, ProxyConfig::default());
+        assert!(manager.status().await.is_empty());
+        assert!(!record_path.exists());
+
+        let mut stale = ProxyInstance {
+            alias: "work".to_string(),
+            port,
+            pid,
+            process: None,
+            process_start: Some(start + 1),
+            config_path: record_path.with_file_name("config.yaml"),
+            log_path: record_path.with_file_name(LOG_FILE_NAME),
+            started_at: Utc::now(),
+            status: ProxyStatus::Running,
+            restart_count: 0,
+        };
+        assert!(stale.has_exited().unwrap());
+        stale.terminate();
+        stale.kill();
+        assert!(unrelated.try_wait().unwrap().is_none());
+        unrelated.kill().unwrap();
+        unrelated.wait().unwrap();
+    }
+
+    /// Answer every connection with a bare HTTP response of `status`.
+    async fn serve_status(status: u16) -> u16 {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf).await;
+                let reply = format!(
+                    "HTTP/1.1 {} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                );
+                let _ = stream.write_all(reply.as_bytes()).await;
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn test_probe_health() {
+        assert!(probe_health(serve_status(200).await).await.is_ok());
+        assert!(probe_health(serve_status(401).await).await.is_ok());
+        assert_eq!(
+            probe_health(serve_status(503).await).await,
+            Err("health check returned 503".to_string())
+        );
+
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = closed.local_addr().unwrap().port();
+        drop(closed);
+        assert!(probe_health(port).await.is_err());
+    }
+
+    #[test]
+    fn test_probed_status() {
+        let failed = || Err("down".to_string());
+
+        let unhealthy = probed_status(&ProxyStatus::Running, failed());
+        let ProxyStatus::Unhealthy { since, .. } = unhealthy else {
+            panic!("expected unhealthy, got {:?}", unhealthy);
+        };
+        // Still failing keeps the original time
+        assert_eq!(
+            probed_status(&unhealthy, failed()),
+            ProxyStatus::Unhealthy {
+                since,
+                reason: "down".to_string()
+            }
+        );
+        assert_eq!(probed_status(&unhealthy, Ok(())), ProxyStatus::Running);
+
+        // Proxies not believed to be serving are left alone
+        assert_eq!(
+            probed_status(&ProxyStatus::Starting, Ok(())),
+            ProxyStatus::Starting
+        );
+        assert_eq!(
+            probed_status(&ProxyStatus::Stopped, failed()),
+            ProxyStatus::Stopped
+        );
     }
 }
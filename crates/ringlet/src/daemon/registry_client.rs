@@ -7,22 +7,96 @@
 //! - Managing registry.lock (current commit/channel)
 //! - Syncing LiteLLM pricing data
 //! - Offline mode support
+//! - Retrying transient network failures, falling back to the cache
+//! - Verifying downloads against the registry's `manifest.sha256`
 
 use anyhow::{Context, Result, anyhow};
-use ringlet_core::RingletPaths;
+use ringlet_core::{RegistryConfig, RingletPaths};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::Duration;
 use tracing::{debug, info, warn};
 
-/// Default registry URL.
-const DEFAULT_REGISTRY_URL: &str =
-    "https://raw.githubusercontent.com/neul-labs/ringlet/main/manifests";
+/// GitHub repository hosting the registry.
+const REGISTRY_REPO: &str = "neul-labs/ringlet";
+
+/// Ref synced from when nothing is pinned.
+const DEFAULT_REF: &str = "main";
+
+/// GitHub REST API, used to resolve pinned refs.
+const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Attempts made for each download before giving up.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry; doubled for each later one.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Checksum file published alongside registry.json, in `sha256sum` format.
+const CHECKSUMS_FILE: &str = "manifest.sha256";
 
 /// Registry client for syncing from GitHub.
 pub struct RegistryClient {
     paths: RingletPaths,
-    base_url: String,
+    config: RegistryConfig,
+}
+
+/// SHA256 checksums from `manifest.sha256`, keyed by registry-relative path.
+#[derive(Debug, Default)]
+struct Checksums(HashMap<String, String>);
+
+impl Checksums {
+    /// Parse `sha256sum` output (`<hex>  <path>` per line).
+    fn parse(content: &str) -> Result<Self> {
+        let mut sums = HashMap::new();
+        for (n, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (hash, path) = line.split_once(char::is_whitespace).ok_or_else(|| {
+                anyhow!(
+                    "{} line {}: expected '<sha256>  <path>'",
+                    CHECKSUMS_FILE,
+                    n + 1
+                )
+            })?;
+            if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(anyhow!(
+                    "{} line {}: invalid SHA256 '{}'",
+                    CHECKSUMS_FILE,
+                    n + 1,
+                    hash
+                ));
+            }
+            // sha256sum marks binary-mode entries with a leading '*'
+            let path = path.trim_start().trim_start_matches('*');
+            sums.insert(path.to_string(), hash.to_ascii_lowercase());
+        }
+        Ok(Self(sums))
+    }
+
+    /// Check downloaded `content` against the checksum listed for `path`.
+    fn verify(&self, path: &str, content: &str) -> Result<()> {
+        use sha2::{Digest, Sha256};
+
+        let expected = self
+            .0
+            .get(path)
+            .ok_or_else(|| anyhow!("{} is not listed in {}", path, CHECKSUMS_FILE))?;
+        let computed = format!("{:x}", Sha256::digest(content.as_bytes()));
+        if &computed != expected {
+            return Err(anyhow!(
+                "Checksum mismatch for {}: {} lists {}, downloaded {}",
+                path,
+                CHECKSUMS_FILE,
+                expected,
+                computed
+            ));
+        }
+        Ok(())
+    }
 }
 
 /// Registry index loaded from registry.json.
@@ -85,6 +159,10 @@ pub struct RegistryLock {
 
     /// Pinned ref (if any).
     pub pinned_ref: Option<String>,
+
+    /// Commit SHA the pinned ref resolved to when it was pinned.
+    #[serde(default)]
+    pub pinned_commit: Option<String>,
 }
 
 /// Registry sync status.
@@ -97,19 +175,34 @@ pub struct SyncStatus {
     pub cached_agents: usize,
     pub cached_providers: usize,
     pub cached_scripts: usize,
+    /// Seconds since the last successful sync.
+    pub cache_age_secs: Option<u64>,
+    /// Configured cache TTL.
+    pub cache_ttl_secs: u64,
+    /// Whether the cache is older than the TTL (or was never synced).
+    pub stale: bool,
+    /// Whether `sync` skipped fetching because the cache was fresh.
+    pub from_cache: bool,
+    /// Why cached data is being served instead of a fresh sync.
+    pub warning: Option<String>,
 }
 
 impl RegistryClient {
     /// Create a new registry client.
-    pub fn new(paths: RingletPaths) -> Self {
-        Self {
-            paths,
-            base_url: DEFAULT_REGISTRY_URL.to_string(),
-        }
+    pub fn new(paths: RingletPaths, config: RegistryConfig) -> Self {
+        Self { paths, config }
     }
 
     /// Sync registry from remote.
-    pub fn sync(&self, force: bool, offline: bool) -> Result<SyncStatus> {
+    ///
+    /// `on_progress` is called with `(current, total, message)` as artifacts are
+    /// downloaded; `total` is `None` until the registry index has been fetched.
+    pub fn sync(
+        &self,
+        force: bool,
+        offline: bool,
+        on_progress: impl Fn(u64, Option<u64>, &str),
+    ) -> Result<SyncStatus> {
         if offline {
             return self.get_status(true);
         }
@@ -117,23 +210,46 @@ impl RegistryClient {
         // Check if we need to sync
         let lock = self.load_lock()?;
         if !force && !self.needs_sync(&lock) {
-            info!("Registry is up to date");
-            return self.get_status(false);
+            info!("Registry cache is fresh");
+            let mut status = self.get_status(false)?;
+            status.from_cache = true;
+            return Ok(status);
         }
 
-        // Fetch registry index
-        let index = self.fetch_index()?;
-
-        // Download artifacts
-        self.download_artifacts(&index)?;
+        // Fetch the index and artifacts; if the network stays down, keep using the cache
+        on_progress(0, None, "Fetching registry index");
+        let mut total = 1;
+        let mut current = 0;
+        let base_url = registry_url(lock.pinned_commit.as_deref().unwrap_or(DEFAULT_REF));
+        let fetched = self.fetch_index(&base_url).and_then(|(index, checksums)| {
+            total = (index.agents.len() + index.providers.len() + index.scripts.len() + 1) as u64;
+            self.download_artifacts(&base_url, &index, checksums.as_ref(), |message| {
+                on_progress(current, Some(total), message);
+                current += 1;
+            })?;
+            Ok(index)
+        });
+        let index = match fetched {
+            Ok(index) => index,
+            Err(e) if is_network_error(&e) => {
+                let warning = format!("Registry unreachable ({:#}); using cached data", e);
+                warn!("{}", warning);
+                let mut status = self.get_status(true)?;
+                status.warning = Some(warning);
+                return Ok(status);
+            }
+            Err(e) => return Err(e),
+        };
 
         // Sync LiteLLM pricing data
+        on_progress(current, Some(total), "Syncing LiteLLM pricing");
         if let Err(e) = self.sync_litellm_pricing() {
             warn!(
                 "Failed to sync LiteLLM pricing: {}. Cost tracking may be unavailable.",
                 e
             );
         }
+        on_progress(total, Some(total), "Registry synced");
 
         // Update lock file
         let new_lock = RegistryLock {
@@ -141,6 +257,7 @@ impl RegistryClient {
             commit: index.commit.clone(),
             last_sync: Some(chrono::Utc::now()),
             pinned_ref: lock.pinned_ref,
+            pinned_commit: lock.pinned_commit,
         };
         self.save_lock(&new_lock)?;
 
@@ -148,13 +265,44 @@ impl RegistryClient {
         self.get_status(false)
     }
 
-    /// Pin to a specific ref.
-    pub fn pin(&self, ref_: &str) -> Result<()> {
+    /// Pin to a specific ref, after checking it exists in the registry repository.
+    ///
+    /// Returns the commit SHA the ref resolved to; later syncs fetch that commit.
+    pub fn pin(&self, ref_: &str) -> Result<String> {
+        let commit = resolve_ref(ref_)?;
         let mut lock = self.load_lock()?;
         lock.pinned_ref = Some(ref_.to_string());
+        lock.pinned_commit = Some(commit.clone());
+        // Make the next sync fetch the pinned commit rather than waiting out the cache
+        lock.last_sync = None;
         self.save_lock(&lock)?;
-        info!("Pinned to ref: {}", ref_);
-        Ok(())
+        info!("Pinned to ref: {} ({})", ref_, commit);
+        Ok(commit)
+    }
+
+    /// The pinned ref and the commit it resolved to, if a ref is pinned.
+    pub fn pinned(&self) -> Result<Option<(String, Option<String>)>> {
+        let lock = self.load_lock()?;
+        Ok(lock.pinned_ref.map(|ref_| (ref_, lock.pinned_commit)))
+    }
+
+    /// Pin to a ref that was resolved on another machine, keeping its commit.
+    ///
+    /// Resolves the ref again only when the commit is unknown.
+    pub fn restore_pin(&self, ref_: &str, commit: Option<&str>) -> Result<String> {
+        let Some(commit) = commit else {
+            return self.pin(ref_);
+        };
+        let mut lock = self.load_lock()?;
+        if lock.pinned_ref.as_deref() != Some(ref_) || lock.pinned_commit.as_deref() != Some(commit)
+        {
+            lock.pinned_ref = Some(ref_.to_string());
+            lock.pinned_commit = Some(commit.to_string());
+            lock.last_sync = None;
+            self.save_lock(&lock)?;
+            info!("Restored pin to ref: {} ({})", ref_, commit);
+        }
+        Ok(commit.to_string())
     }
 
     /// Get current status.
@@ -165,6 +313,7 @@ impl RegistryClient {
         let cached_agents = count_files(&cache_dir.join("agents"));
         let cached_providers = count_files(&cache_dir.join("providers"));
         let cached_scripts = count_files(&cache_dir.join("scripts"));
+        let cache_age_secs = cache_age_secs(&lock);
 
         Ok(SyncStatus {
             channel: lock.channel,
@@ -174,62 +323,126 @@ impl RegistryClient {
             cached_agents,
             cached_providers,
             cached_scripts,
+            cache_age_secs,
+            cache_ttl_secs: self.config.cache_ttl_secs,
+            stale: is_stale(cache_age_secs, self.config.cache_ttl_secs),
+            from_cache: false,
+            warning: None,
         })
     }
 
-    /// Fetch the registry index.
-    fn fetch_index(&self) -> Result<RegistryIndex> {
-        let url = format!("{}/registry.json", self.base_url);
+    /// Fetch the registry index, plus the published checksums when verifying.
+    fn fetch_index(&self, base_url: &str) -> Result<(RegistryIndex, Option<Checksums>)> {
+        let checksums = if self.config.verify {
+            let url = format!("{}/{}", base_url, CHECKSUMS_FILE);
+            debug!("Fetching checksums from: {}", url);
+            let content = get_with_retry(&url)
+                .with_context(|| format!("Failed to fetch {}", CHECKSUMS_FILE))?
+                .into_string()
+                .with_context(|| format!("Failed to read {}", CHECKSUMS_FILE))?;
+            Some(Checksums::parse(&content)?)
+        } else {
+            None
+        };
+
+        let url = format!("{}/registry.json", base_url);
         debug!("Fetching registry index from: {}", url);
 
-        // Use a simple HTTP client (blocking for simplicity)
-        let response = ureq::get(&url)
-            .call()
-            .context("Failed to fetch registry.json")?;
+        let content = get_with_retry(&url)
+            .context("Failed to fetch registry.json")?
+            .into_string()
+            .context("Failed to read registry.json")?;
+        if let Some(checksums) = &checksums {
+            checksums.verify("registry.json", &content)?;
+        }
 
-        let index: RegistryIndex = response
-            .into_json()
-            .context("Failed to parse registry.json")?;
+        let index: RegistryIndex =
+            serde_json::from_str(&content).context("Failed to parse registry.json")?;
 
-        Ok(index)
+        Ok((index, checksums))
     }
 
     /// Download all artifacts from the registry.
-    fn download_artifacts(&self, index: &RegistryIndex) -> Result<()> {
-        let cache_dir = self
-            .paths
-            .registry_commits_dir()
-            .join(index.commit.as_deref().unwrap_or("latest"));
-        std::fs::create_dir_all(&cache_dir)?;
-
-        // Download agents
-        for (id, info) in &index.agents {
-            self.download_artifact(&cache_dir.join("agents"), id, info)?;
+    ///
+    /// Artifacts are staged next to the cache and only moved into place once
+    /// every download has been verified, so a failed sync leaves the cache as it was.
+    fn download_artifacts(
+        &self,
+        base_url: &str,
+        index: &RegistryIndex,
+        checksums: Option<&Checksums>,
+        mut on_artifact: impl FnMut(&str),
+    ) -> Result<()> {
+        let commit = index.commit.as_deref().unwrap_or("latest");
+        let commits_dir = self.paths.registry_commits_dir();
+        let cache_dir = commits_dir.join(commit);
+        let staging_dir = commits_dir.join(format!(".{}.partial", commit));
+        if staging_dir.exists() {
+            std::fs::remove_dir_all(&staging_dir)?;
         }
+        std::fs::create_dir_all(&staging_dir)?;
 
-        // Download providers
-        for (id, info) in &index.providers {
-            self.download_artifact(&cache_dir.join("providers"), id, info)?;
-        }
+        let downloaded = (|| {
+            // Download agents
+            for (id, info) in &index.agents {
+                on_artifact(&format!("agents/{}", id));
+                self.download_artifact(base_url, &staging_dir.join("agents"), id, info, checksums)?;
+            }
+
+            // Download providers
+            for (id, info) in &index.providers {
+                on_artifact(&format!("providers/{}", id));
+                self.download_artifact(
+                    base_url,
+                    &staging_dir.join("providers"),
+                    id,
+                    info,
+                    checksums,
+                )?;
+            }
 
-        // Download scripts
-        for (id, info) in &index.scripts {
-            self.download_artifact(&cache_dir.join("scripts"), id, info)?;
+            // Download scripts
+            for (id, info) in &index.scripts {
+                on_artifact(&format!("scripts/{}", id));
+                self.download_artifact(
+                    base_url,
+                    &staging_dir.join("scripts"),
+                    id,
+                    info,
+                    checksums,
+                )?;
+            }
+
+            Ok(())
+        })();
+        if let Err(e) = downloaded {
+            let _ = std::fs::remove_dir_all(&staging_dir);
+            return Err(e);
         }
 
+        if cache_dir.exists() {
+            std::fs::remove_dir_all(&cache_dir)?;
+        }
+        std::fs::rename(&staging_dir, &cache_dir)?;
         Ok(())
     }
 
     /// Download a single artifact.
-    fn download_artifact(&self, target_dir: &PathBuf, id: &str, info: &ArtifactInfo) -> Result<()> {
+    fn download_artifact(
+        &self,
+        base_url: &str,
+        target_dir: &PathBuf,
+        id: &str,
+        info: &ArtifactInfo,
+        checksums: Option<&Checksums>,
+    ) -> Result<()> {
         std::fs::create_dir_all(target_dir)?;
 
-        let url = format!("{}/{}", self.base_url, info.path);
+        let url = format!("{}/{}", base_url, info.path);
         debug!("Downloading artifact: {} from {}", id, url);
 
-        let response = ureq::get(&url)
-            .call()
-            .context(format!("Failed to fetch artifact: {}", id))?;
+        let response =
+            get_with_retry(&url).with_context(|| format!("Failed to fetch artifact: {}", id))?;
 
         let content = response
             .into_string()
@@ -249,6 +462,9 @@ impl RegistryClient {
             }
             debug!("Checksum verified for {}", id);
         }
+        if let Some(checksums) = checksums {
+            checksums.verify(&info.path, &content)?;
+        }
 
         let filename = std::path::Path::new(&info.path)
             .file_name()
@@ -261,16 +477,9 @@ impl RegistryClient {
         Ok(())
     }
 
-    /// Check if we need to sync.
+    /// Check if we need to sync: never synced, or older than the cache TTL.
     fn needs_sync(&self, lock: &RegistryLock) -> bool {
-        // Sync if no last_sync or older than 24 hours
-        match lock.last_sync {
-            Some(last) => {
-                let age = chrono::Utc::now().signed_duration_since(last);
-                age.num_hours() >= 24
-            }
-            None => true,
-        }
+        is_stale(cache_age_secs(lock), self.config.cache_ttl_secs)
     }
 
     /// Get the cache directory for current lock.
@@ -304,9 +513,8 @@ impl RegistryClient {
 
         debug!("Syncing LiteLLM pricing data");
 
-        let response = ureq::get(LITELLM_PRICING_URL)
-            .call()
-            .context("Failed to fetch LiteLLM pricing data")?;
+        let response =
+            get_with_retry(LITELLM_PRICING_URL).context("Failed to fetch LiteLLM pricing data")?;
 
         let content = response
             .into_string()
@@ -325,9 +533,219 @@ impl RegistryClient {
     }
 }
 
+/// Seconds since the last successful sync, if there was one.
+fn cache_age_secs(lock: &RegistryLock) -> Option<u64> {
+    lock.last_sync.map(|last| {
+        chrono::Utc::now()
+            .signed_duration_since(last)
+            .num_seconds()
+            .max(0) as u64
+    })
+}
+
+/// Whether a cache synced `age_secs` ago has outlived `ttl_secs`.
+fn is_stale(age_secs: Option<u64>, ttl_secs: u64) -> bool {
+    age_secs.is_none_or(|age| age >= ttl_secs)
+}
+
+/// Raw-content URL of the registry at `ref_`.
+fn registry_url(ref_: &str) -> String {
+    format!(
+        "https://raw.githubusercontent.com/{}/{}/manifests",
+        REGISTRY_REPO, ref_
+    )
+}
+
+/// Resolve a branch, tag or commit in the registry repository to a commit SHA.
+///
+/// Unknown refs are rejected with the repository's branches and tags listed.
+fn resolve_ref(ref_: &str) -> Result<String> {
+    if ref_.is_empty()
+        || ref_.contains("..")
+        || !ref_
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '/'))
+    {
+        return Err(anyhow!("Invalid ref: '{}'", ref_));
+    }
+
+    let url = format!(
+        "{}/repos/{}/commits/{}",
+        GITHUB_API_URL, REGISTRY_REPO, ref_
+    );
+    debug!("Resolving ref {} via {}", ref_, url);
+    match ureq::get(&url)
+        .set("Accept", "application/vnd.github.sha")
+        .call()
+    {
+        Ok(response) => Ok(response
+            .into_string()
+            .context("Failed to read resolved commit")?
+            .trim()
+            .to_string()),
+        Err(ureq::Error::Status(404 | 422, _)) => {
+            let available = list_refs().unwrap_or_else(|e| {
+                warn!("Failed to list registry refs: {:#}", e);
+                Vec::new()
+            });
+            Err(anyhow!(unknown_ref_message(ref_, &available)))
+        }
+        Err(e) => Err(e).with_context(|| format!("Failed to resolve ref '{}'", ref_)),
+    }
+}
+
+/// Branch and tag names in the registry repository, branches first.
+fn list_refs() -> Result<Vec<String>> {
+    #[derive(Deserialize)]
+    struct NamedRef {
+        name: String,
+    }
+
+    let mut names = Vec::new();
+    for kind in ["branches", "tags"] {
+        let url = format!(
+            "{}/repos/{}/{}?per_page=100",
+            GITHUB_API_URL, REGISTRY_REPO, kind
+        );
+        let refs: Vec<NamedRef> = get_with_retry(&url)?
+            .into_json()
+            .with_context(|| format!("Failed to parse {} list", kind))?;
+        names.extend(refs.into_iter().map(|r| r.name));
+    }
+    Ok(names)
+}
+
+fn unknown_ref_message(ref_: &str, available: &[String]) -> String {
+    if available.is_empty() {
+        format!("Unknown ref '{}' in {}", ref_, REGISTRY_REPO)
+    } else {
+        format!(
+            "Unknown ref '{}' in {}. Available branches and tags: {}",
+            ref_,
+            REGISTRY_REPO,
+            available.join(", ")
+        )
+    }
+}
+
+/// `GET` a URL, retrying transient failures with exponential backoff.
+fn get_with_retry(url: &str) -> Result<ureq::Response> {
+    let mut attempt = 1;
+    loop {
+        match ureq::get(url).call() {
+            Err(e) if attempt < MAX_ATTEMPTS && is_retryable(&e) => {
+                let delay = backoff_delay(attempt);
+                debug!(
+                    "Fetching {} failed (attempt {}/{}): {}; retrying in {:?}",
+                    url, attempt, MAX_ATTEMPTS, e, delay
+                );
+                std::thread::sleep(delay);
+                attempt += 1;
+            }
+            result => return Ok(result?),
+        }
+    }
+}
+
+/// Delay before retry number `attempt` (1-based).
+fn backoff_delay(attempt: u32) -> Duration {
+    INITIAL_BACKOFF * 2u32.pow(attempt - 1)
+}
+
+/// Whether a request error is worth retrying.
+///
+/// Connection failures, timeouts, rate limiting and server errors are; other
+/// statuses (auth failures, missing files) won't change on retry.
+fn is_retryable(error: &ureq::Error) -> bool {
+    match error {
+        ureq::Error::Transport(_) => true,
+        ureq::Error::Status(status, _) => *status == 429 || *status >= 500,
+    }
+}
+
+/// Whether a sync error came from the network rather than the registry's content.
+fn is_network_error(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<ureq::Error>()
+            .is_some_and(is_retryable)
+    })
+}
+
 /// Count files in a directory.
 fn count_files(dir: &PathBuf) -> usize {
     std::fs::read_dir(dir)
         .map(|entries| entries.filter_map(|e| e.ok()).count())
         .unwrap_or(0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_staleness() {
+        assert!(is_stale(None, 3600));
+        assert!(!is_stale(Some(0), 3600));
+        assert!(!is_stale(Some(3599), 3600));
+        assert!(is_stale(Some(3600), 3600));
+        // A zero TTL always refetches
+        assert!(is_stale(Some(0), 0));
+    }
+
+    #[test]
+    fn test_backoff_delay_doubles() {
+        assert_eq!(backoff_delay(1), Duration::from_millis(500));
+        assert_eq!(backoff_delay(2), Duration::from_secs(1));
+        assert_eq!(backoff_delay(3), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_checksums_verify() {
+        let checksums = Checksums::parse(
+            "# generated by scripts/generate-registry.sh\n\
+             2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  agents/claude.toml\n\
+             486ea46224d1bb4fb680f34f7c9ad96a8f24ec88be73ea8e5a6c65260e9cb8a7 *scripts/claude.rhai\n",
+        )
+        .unwrap();
+
+        assert!(checksums.verify("agents/claude.toml", "hello").is_ok());
+        assert!(checksums.verify("scripts/claude.rhai", "world").is_ok());
+        let err = checksums
+            .verify("agents/claude.toml", "tampered")
+            .unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"));
+        let err = checksums.verify("agents/codex.toml", "hello").unwrap_err();
+        assert!(err.to_string().contains("not listed"));
+
+        assert!(Checksums::parse("not-a-hash  agents/claude.toml").is_err());
+    }
+
+    #[test]
+    fn test_invalid_ref_rejected_before_lookup() {
+        for ref_ in ["", "../main", "main?x=1", "v1 .0"] {
+            let err = resolve_ref(ref_).unwrap_err();
+            assert!(err.to_string().starts_with("Invalid ref"), "{}", ref_);
+        }
+    }
+
+    #[test]
+    fn test_unknown_ref_message_lists_refs() {
+        let message = unknown_ref_message("mian", &["main".to_string(), "v0.1.0".to_string()]);
+        assert!(message.contains("'mian'"));
+        assert!(message.ends_with("Available branches and tags: main, v0.1.0"));
+    }
+
+    #[test]
+    fn test_retryable_statuses() {
+        let status = |code| ureq::Error::Status(code, ureq::Response::new(code, "", "").unwrap());
+        assert!(is_retryable(&status(503)));
+        assert!(is_retryable(&status(429)));
+        assert!(!is_retryable(&status(404)));
+        assert!(!is_retryable(&status(401)));
+
+        let err = anyhow::Error::from(status(502)).context("Failed to fetch registry.json");
+        assert!(is_network_error(&err));
+        assert!(!is_network_error(&anyhow!("Checksum mismatch")));
+    }
+}
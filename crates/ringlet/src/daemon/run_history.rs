@@ -0,0 +1,122 @@
+//! Append-only per-profile run history.
+//!
+//! Each profile gets a JSONL file under the data directory with one
+//! `RunHistoryEntry` per completed run.
+
+use crate::daemon::profile_store::validate_alias;
+use anyhow::{Context, Result};
+use ringlet_core::{RingletPaths, RunHistoryEntry};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use tracing::warn;
+
+/// Reads and appends profile run history logs.
+pub struct RunHistory {
+    paths: RingletPaths,
+}
+
+impl RunHistory {
+    pub fn new(paths: RingletPaths) -> Self {
+        Self { paths }
+    }
+
+    /// Append a completed run to the profile's log.
+    pub fn append(&self, alias: &str, entry: &RunHistoryEntry) -> Result<()> {
+        let path = self.log_path(alias)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create run history directory")?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open run history {:?}", path))?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+
+    /// Return up to `limit` of the profile's most recent runs, newest first.
+    pub fn recent(&self, alias: &str, limit: usize) -> Result<Vec<RunHistoryEntry>> {
+        let path = self.log_path(alias)?;
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read run history {:?}", path))?;
+        let entries = content
+            .lines()
+            .rev()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    warn!(
+                        "Skipping malformed run history entry for '{}': {}",
+                        alias, e
+                    );
+                    None
+                }
+            })
+            .take(limit)
+            .collect();
+        Ok(entries)
+    }
+
+    /// Move a profile's log to a new alias.
+    pub fn rename(&self, old_alias: &str, new_alias: &str) -> Result<()> {
+        let old_path = self.log_path(old_alias)?;
+        let new_path = self.log_path(new_alias)?;
+        if old_path.exists() {
+            std::fs::rename(&old_path, &new_path)
+                .with_context(|| format!("Failed to move run history {:?}", old_path))?;
+        }
+        Ok(())
+    }
+
+    fn log_path(&self, alias: &str) -> Result<PathBuf> {
+        validate_alias(alias)?;
+        Ok(self
+            .paths
+            .run_history_dir()
+            .join(format!("{}.jsonl", alias)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::profile_store::test_paths;
+    use chrono::Utc;
+
+    #[test]
+    fn test_recent_returns_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = test_paths(dir.path());
+        let history = RunHistory::new(paths);
+
+        for exit_code in 0..3 {
+            history
+                .append(
+                    "work",
+                    &RunHistoryEntry {
+                        started_at: Utc::now(),
+                        args: vec![format!("--run={}", exit_code)],
+                        exit_code,
+                        signal: None,
+                        duration_secs: 1,
+                    },
+                )
+                .unwrap();
+        }
+
+        let recent = history.recent("work", 2).unwrap();
+        assert_eq!(
+            recent.iter().map(|e| e.exit_code).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        assert!(history.recent("other", 10).unwrap().is_empty());
+    }
+}
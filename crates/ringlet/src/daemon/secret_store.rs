@@ -2,51 +2,248 @@
 
 use crate::daemon::profile_store::validate_alias;
 use anyhow::{Context, Result};
+use ringlet_core::config::SecretBackend;
+use std::path::{Path, PathBuf};
+use tracing::warn;
 
-/// Keychain-backed credential store for profile secrets.
-pub struct SecretStore;
+/// Whether this build links a platform keychain into `keyring`.
+///
+/// Without one, `keyring` falls back to an in-memory mock store that forgets
+/// every credential, so the file backend is used instead.
+const KEYCHAIN_SUPPORTED: bool = cfg!(any(
+    target_os = "macos",
+    windows,
+    feature = "secret-service"
+));
+
+/// Credential store for profile secrets.
+///
+/// Keys go to the OS keychain when `security.secret_backend = "keychain"` and a
+/// keychain is usable, and otherwise to owner-only files in `secrets_dir`.
+#[derive(Debug, Clone)]
+pub struct SecretStore {
+    backend: SecretBackend,
+    secrets_dir: PathBuf,
+}
 
 impl SecretStore {
-    pub fn new() -> Self {
-        Self
+    pub fn new(backend: SecretBackend, secrets_dir: PathBuf) -> Self {
+        Self {
+            backend,
+            secrets_dir,
+        }
     }
 
+    /// Store an API key, returning the keychain entry name when it went to the keychain.
     pub fn store_api_key(&self, alias: &str, api_key: &str) -> Result<Option<String>> {
         validate_alias(alias)?;
+        self.store(alias, api_key)
+    }
 
-        if api_key.is_empty() {
-            return Ok(None);
-        }
+    pub fn get_api_key(&self, alias: &str) -> Result<String> {
+        validate_alias(alias)?;
+        self.get(alias)
+    }
 
-        let keychain_key = Self::keychain_key(alias);
-        let entry = keyring::Entry::new("ringlet", &keychain_key)
-            .context("Failed to access system keychain")?;
-        entry
-            .set_password(api_key)
-            .context("Failed to store credential in keychain")?;
-        Ok(Some(keychain_key))
+    pub fn delete_api_key(&self, alias: &str) -> Result<()> {
+        validate_alias(alias)?;
+        self.delete(alias)
     }
 
-    pub fn get_api_key(&self, alias: &str) -> Result<String> {
+    /// Store the OAuth refresh token issued with a profile's access token.
+    pub fn store_refresh_token(&self, alias: &str, refresh_token: &str) -> Result<()> {
         validate_alias(alias)?;
+        self.store(&refresh_entry(alias), refresh_token)?;
+        Ok(())
+    }
 
-        let entry = keyring::Entry::new("ringlet", &Self::keychain_key(alias))
-            .context("Failed to access system keychain")?;
-        entry
-            .get_password()
-            .context("Failed to retrieve credential from keychain")
+    /// The profile's refresh token, if one was stored.
+    pub fn get_refresh_token(&self, alias: &str) -> Option<String> {
+        validate_alias(alias).ok()?;
+        self.get(&refresh_entry(alias)).ok()
     }
 
-    pub fn delete_api_key(&self, alias: &str) -> Result<()> {
+    pub fn delete_refresh_token(&self, alias: &str) -> Result<()> {
         validate_alias(alias)?;
+        self.delete(&refresh_entry(alias))
+    }
+
+    /// Move a stored API key, and refresh token if any, to a new alias.
+    ///
+    /// Returns the new keychain entry name, as `store_api_key` does. Profiles
+    /// without a stored key are left alone. The old entries are only removed
+    /// once both new ones are stored, so a failure leaves the old alias intact.
+    pub fn rename_api_key(&self, old_alias: &str, new_alias: &str) -> Result<Option<String>> {
+        validate_alias(new_alias)?;
+
+        let refresh_token = self.get_refresh_token(old_alias);
+        let api_key = self.get_api_key(old_alias).ok();
+
+        let keychain_key = match &api_key {
+            Some(api_key) => self.store_api_key(new_alias, api_key)?,
+            None => None,
+        };
+        if let Some(refresh_token) = &refresh_token
+            && let Err(e) = self.store_refresh_token(new_alias, refresh_token)
+        {
+            if api_key.is_some() {
+                let _ = self.delete_api_key(new_alias);
+            }
+            return Err(e);
+        }
+
+        if refresh_token.is_some() {
+            self.delete_refresh_token(old_alias)?;
+        }
+        if api_key.is_some() {
+            self.delete_api_key(old_alias)?;
+        }
+        Ok(keychain_key)
+    }
+
+    fn store(&self, name: &str, secret: &str) -> Result<Option<String>> {
+        if secret.is_empty() {
+            return Ok(None);
+        }
+
+        if self.use_keychain() {
+            match Self::store_in_keychain(name, secret) {
+                Ok(keychain_key) => return Ok(Some(keychain_key)),
+                Err(e) => warn!(
+                    "Keychain unavailable ({:#}); storing secret '{}' in {:?}",
+                    e, name, self.secrets_dir
+                ),
+            }
+        }
+
+        write_secret_file(&self.secret_file(name), secret)?;
+        Ok(None)
+    }
+
+    fn get(&self, name: &str) -> Result<String> {
+        let file = self.secret_file(name);
+        if !self.use_keychain() {
+            return read_secret_file(&file);
+        }
+
+        match Self::keychain_entry(name)?.get_password() {
+            Ok(secret) => Ok(secret),
+            // The secret was stored while the keychain was unavailable
+            Err(_) if file.exists() => read_secret_file(&file),
+            Err(e) => Err(e).context("Failed to retrieve credential from keychain"),
+        }
+    }
+
+    fn delete(&self, name: &str) -> Result<()> {
+        if KEYCHAIN_SUPPORTED {
+            let _ = Self::keychain_entry(name)?.delete_credential();
+        }
 
-        let entry = keyring::Entry::new("ringlet", &Self::keychain_key(alias))
-            .context("Failed to access system keychain")?;
-        let _ = entry.delete_credential();
+        let file = self.secret_file(name);
+        if file.exists() {
+            std::fs::remove_file(&file)
+                .with_context(|| format!("Failed to remove secret file {:?}", file))?;
+        }
         Ok(())
     }
 
-    fn keychain_key(alias: &str) -> String {
-        format!("ringlet-{}", alias)
+    fn use_keychain(&self) -> bool {
+        self.backend == SecretBackend::Keychain && KEYCHAIN_SUPPORTED
+    }
+
+    fn store_in_keychain(name: &str, secret: &str) -> Result<String> {
+        Self::keychain_entry(name)?
+            .set_password(secret)
+            .context("Failed to store credential in keychain")?;
+        Ok(Self::keychain_key(name))
+    }
+
+    fn keychain_entry(name: &str) -> Result<keyring::Entry> {
+        keyring::Entry::new("ringlet", &Self::keychain_key(name))
+            .context("Failed to access system keychain")
+    }
+
+    fn keychain_key(name: &str) -> String {
+        format!("ringlet-{}", name)
+    }
+
+    fn secret_file(&self, name: &str) -> PathBuf {
+        self.secrets_dir.join(format!("{}.key", name))
+    }
+}
+
+/// Secret name of a profile's refresh token. Aliases can't contain `.`, so it
+/// never collides with another profile's API key.
+fn refresh_entry(alias: &str) -> String {
+    format!("{}.refresh", alias)
+}
+
+/// Write a secret readable only by the current user.
+fn write_secret_file(path: &Path, secret: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create secrets directory {:?}", parent))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(parent, std::fs::Permissions::from_mode(0o700))?;
+        }
+    }
+
+    std::fs::write(path, secret).with_context(|| format!("Failed to write {:?}", path))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+
+    Ok(())
+}
+
+fn read_secret_file(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path)
+        .map(|s| s.trim_end_matches('\n').to_string())
+        .with_context(|| format!("Failed to read API key from {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_backend_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SecretStore::new(SecretBackend::File, dir.path().join("secrets"));
+
+        assert_eq!(store.store_api_key("work", "sk-test").unwrap(), None);
+        assert_eq!(store.get_api_key("work").unwrap(), "sk-test");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(store.secret_file("work"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        store.delete_api_key("work").unwrap();
+        assert!(store.get_api_key("work").is_err());
+    }
+
+    #[test]
+    fn test_file_backend_rename() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SecretStore::new(SecretBackend::File, dir.path().join("secrets"));
+
+        store.store_api_key("work", "sk-test").unwrap();
+        store.store_refresh_token("work", "rt-test").unwrap();
+        assert_eq!(store.rename_api_key("work", "job").unwrap(), None);
+        assert_eq!(store.get_api_key("job").unwrap(), "sk-test");
+        assert_eq!(store.get_refresh_token("job").as_deref(), Some("rt-test"));
+        assert!(store.get_api_key("work").is_err());
+        assert_eq!(store.get_refresh_token("work"), None);
     }
 }
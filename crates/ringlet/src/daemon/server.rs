@@ -1,10 +1,13 @@
 //! IPC server using nng (nanomsg next generation).
 
 use crate::daemon::agent_registry::AgentRegistry;
-use crate::daemon::agent_usage::UsageSnapshot;
+use crate::daemon::agent_usage::{AgentDataDirs, UsageSnapshot};
 use crate::daemon::events::EventBroadcaster;
 use crate::daemon::execution::ExecutionAdapter;
 use crate::daemon::handlers;
+use crate::daemon::handlers::usage::UsageRecordsCache;
+use crate::daemon::health::ErrorLog;
+use crate::daemon::http::auth::generate_token;
 use crate::daemon::profile_manager::ProfileManager;
 use crate::daemon::profile_store::ProfileStore;
 use crate::daemon::provider_registry::ProviderRegistry;
@@ -12,34 +15,41 @@ use crate::daemon::proxy_manager::ProxyManager;
 use crate::daemon::registry_client::RegistryClient;
 use crate::daemon::secret_store::SecretStore;
 use crate::daemon::telemetry::TelemetryCollector;
-use crate::daemon::terminal::TerminalSessionManager;
+use crate::daemon::terminal::{SessionStore, TerminalSessionManager};
 use crate::daemon::usage_watcher::UsageWatcher;
 use crate::daemon::workspace_service::WorkspaceService;
-use anyhow::{Context, Result};
-use nng::options::Options;
-use nng::{Protocol, Socket};
-use ringlet_core::{Event, Request, Response, RingletPaths};
+use anyhow::{Context, Result, anyhow};
+use nng::options::{LocalAddr, Options};
+use nng::{Listener, Protocol, Socket, SocketAddr};
+use ringlet_core::rpc::error_codes;
+use ringlet_core::{DaemonEndpoint, Event, Request, Response, RingletPaths, UserConfig};
 use std::collections::HashMap;
+use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
 use std::time::{Duration, Instant};
+use subtle::ConstantTimeEq;
 use tokio::sync::{Mutex, oneshot};
 use tracing::{debug, error, info, warn};
 
 /// Server state shared across request handlers.
 pub struct ServerState {
     pub paths: RingletPaths,
+    /// When the daemon started.
+    pub started_at: Instant,
     pub last_activity: Mutex<Instant>,
     pub agent_registry: Mutex<AgentRegistry>,
-    pub provider_registry: ProviderRegistry,
+    pub provider_registry: Mutex<ProviderRegistry>,
     pub profile_store: ProfileStore,
     pub secret_store: SecretStore,
     pub profile_manager: ProfileManager,
     pub execution_adapter: ExecutionAdapter,
     pub registry_client: RegistryClient,
     pub telemetry: TelemetryCollector,
-    pub proxy_manager: ProxyManager,
+    /// Shared with background tasks that stop ephemeral proxies after a run.
+    pub proxy_manager: Arc<ProxyManager>,
     pub workspace_service: WorkspaceService,
     /// Terminal session manager for remote terminal access.
     pub terminal_sessions: TerminalSessionManager,
@@ -49,6 +59,15 @@ pub struct ServerState {
     pub events: EventBroadcaster,
     /// Pending CLI-attached profile runs prepared by the daemon.
     pub pending_prepared_runs: Mutex<HashMap<String, PendingPreparedRun>>,
+    /// Last usage scan, reused while a client pages through usage records.
+    pub usage_records_cache: Mutex<Option<UsageRecordsCache>>,
+    /// Per-alias locks held while a profile's access token is refreshed, so
+    /// concurrent runs don't spend the same refresh token twice.
+    pub token_refresh_locks: std::sync::Mutex<HashMap<String, Arc<Mutex<()>>>>,
+    /// Whether the usage watcher thread is still running.
+    pub usage_watcher_running: Arc<AtomicBool>,
+    /// Recent subsystem errors reported by `/api/health`.
+    pub errors: ErrorLog,
 }
 
 /// Telemetry context held between `ProfilesPrepare` and CLI completion.
@@ -60,6 +79,12 @@ pub struct PendingPreparedRun {
     pub model: String,
     pub profile_home: PathBuf,
     pub usage_baseline: Option<UsageSnapshot>,
+    /// Arguments passed to `profiles run`, for the run history.
+    pub args: Vec<String>,
+    /// When the run was prepared.
+    pub prepared_at: Instant,
+    /// Process that spawns the agent, if the caller said.
+    pub owner_pid: Option<u32>,
 }
 
 impl ServerState {
@@ -67,27 +92,39 @@ impl ServerState {
         let agent_registry = AgentRegistry::new(&paths)?;
         let provider_registry = ProviderRegistry::new(&paths)?;
         let profile_store = ProfileStore::new(paths.clone());
-        let secret_store = SecretStore::new();
-        let profile_manager = ProfileManager::new(paths.clone());
+        let config = UserConfig::load(&paths.config_file()).unwrap_or_default();
+        let secret_store = SecretStore::new(config.security.secret_backend, paths.secrets_dir());
+        let profile_manager = ProfileManager::new(paths.clone(), secret_store.clone());
         let execution_adapter = ExecutionAdapter::new(paths.clone());
-        let registry_client = RegistryClient::new(paths.clone());
+        let registry_client = RegistryClient::new(paths.clone(), config.registry.clone());
         let telemetry = TelemetryCollector::new(paths.clone());
-        let proxy_manager = ProxyManager::new(paths.clone());
+        let proxy_manager = Arc::new(ProxyManager::new(paths.clone(), config.proxy.clone()));
         let workspace_service = WorkspaceService::new();
-        let terminal_sessions = TerminalSessionManager::new();
+        let terminal_sessions = TerminalSessionManager::new(
+            config.terminal.scrollback_bytes,
+            SessionStore::new(paths.terminal_sessions_dir()),
+        );
         let events = EventBroadcaster::default();
 
         // Start usage watcher for real-time agent usage tracking
-        let usage_watcher = UsageWatcher::new(Arc::new(events.clone()));
-        if let Err(e) = usage_watcher.start() {
-            warn!("Failed to start usage watcher: {}", e);
-        }
+        let usage_watcher =
+            UsageWatcher::new(Arc::new(events.clone()), AgentDataDirs::load(&paths));
+        let errors = ErrorLog::default();
+        let usage_watcher_running = match usage_watcher.start() {
+            Ok(running) => running,
+            Err(e) => {
+                warn!("Failed to start usage watcher: {}", e);
+                errors.record("usage_watcher", format!("Failed to start: {}", e));
+                Arc::new(AtomicBool::new(false))
+            }
+        };
 
         Ok(Self {
             paths,
+            started_at: Instant::now(),
             last_activity: Mutex::new(Instant::now()),
             agent_registry: Mutex::new(agent_registry),
-            provider_registry,
+            provider_registry: Mutex::new(provider_registry),
             profile_store,
             secret_store,
             profile_manager,
@@ -100,9 +137,35 @@ impl ServerState {
             shutdown_tx: Mutex::new(Some(shutdown_tx)),
             events,
             pending_prepared_runs: Mutex::new(HashMap::new()),
+            usage_records_cache: Mutex::new(None),
+            token_refresh_locks: std::sync::Mutex::new(HashMap::new()),
+            usage_watcher_running,
+            errors,
         })
     }
 
+    /// State rooted at `dir`, storing API keys in files, for handler tests.
+    #[cfg(test)]
+    pub(crate) fn for_tests(dir: &Path) -> Self {
+        let paths = crate::daemon::profile_store::test_paths(dir);
+        paths.ensure_dirs().unwrap();
+        std::fs::write(
+            paths.config_file(),
+            "[security]\nsecret_backend = \"file\"\n",
+        )
+        .unwrap();
+        Self::new(paths, oneshot::channel().0).unwrap()
+    }
+
+    /// The lock serializing token refreshes for `alias`.
+    pub fn token_refresh_lock(&self, alias: &str) -> Arc<Mutex<()>> {
+        let mut locks = self
+            .token_refresh_locks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        locks.entry(alias.to_string()).or_default().clone()
+    }
+
     pub async fn touch(&self) {
         *self.last_activity.lock().await = Instant::now();
     }
@@ -111,6 +174,13 @@ impl ServerState {
         self.last_activity.lock().await.elapsed()
     }
 
+    /// Whether terminal sessions or proxies are running, which an idle
+    /// shutdown would kill.
+    pub async fn has_active_work(&self) -> bool {
+        self.terminal_sessions.active_session_count().await > 0
+            || self.proxy_manager.active_count().await > 0
+    }
+
     /// Broadcast an event to all WebSocket subscribers.
     pub fn broadcast(&self, event: Event) {
         self.events.broadcast(event);
@@ -118,28 +188,46 @@ impl ServerState {
 }
 
 /// Run the IPC server.
+///
+/// Listens on `socket_path`, or on TCP loopback if the socket can't be
+/// created there, and records the chosen endpoint in the endpoint file.
 pub async fn run(
     socket_path: &Path,
     idle_timeout: Option<Duration>,
-    _paths: &RingletPaths,
+    paths: &RingletPaths,
     state: Arc<ServerState>,
     mut shutdown_rx: oneshot::Receiver<()>,
 ) -> Result<()> {
-    // Remove stale socket file if it exists
-    if socket_path.exists() {
-        std::fs::remove_file(socket_path).context("Failed to remove stale socket file")?;
-    }
+    let (socket, events_socket, endpoint) = match listen_ipc(socket_path) {
+        Ok(bound) => bound,
+        Err(e) => {
+            warn!(
+                "Can't listen on {}: {:#}. Falling back to TCP loopback; requests \
+                 need the token from the endpoint file, but other local users \
+                 can follow the event stream.",
+                socket_path.display(),
+                e
+            );
+            listen_tcp().context("Failed to listen on TCP loopback")?
+        }
+    };
 
-    // Create rep (reply) socket
-    let socket = Socket::new(Protocol::Rep0).context("Failed to create nng socket")?;
+    info!("IPC server listening on {}", endpoint.url());
 
-    // Build IPC URL
-    let url = format!("ipc://{}", socket_path.display());
-    socket
-        .listen(&url)
-        .context(format!("Failed to listen on {}", url))?;
+    // Write endpoint file for CLI discovery
+    write_endpoint_file(&paths.daemon_endpoint(), &endpoint)
+        .context("Failed to write endpoint file")?;
 
-    info!("IPC server listening on {}", url);
+    publish_events(&events_socket, &state);
+
+    // Abandon prepared runs whose CLI died, so their ephemeral proxies stop
+    let sweep_state = state.clone();
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            handlers::profiles::expire_pending_runs(&sweep_state).await;
+        }
+    });
 
     // Spawn idle timeout checker if configured
     let state_clone = state.clone();
@@ -150,6 +238,12 @@ pub async fn run(
         tokio::spawn(async move {
             loop {
                 tokio::time::sleep(Duration::from_secs(10)).await;
+                // Running sessions and proxies count as activity, so the
+                // timeout only starts once they are gone
+                if state_clone.has_active_work().await {
+                    state_clone.touch().await;
+                    continue;
+                }
                 let idle = state_clone.idle_duration().await;
                 if idle > timeout {
                     info!("Idle timeout reached ({:?}), initiating shutdown", timeout);
@@ -185,15 +279,19 @@ pub async fn run(
 
         state.touch().await;
 
+        let Some(body) = authenticate(&msg, &endpoint) else {
+            warn!("Rejected request without a valid token");
+            let response =
+                Response::error(error_codes::UNAUTHORIZED, "Missing or invalid daemon token");
+            send_response(&socket, &response)?;
+            continue;
+        };
+
         // Parse request
-        let request: Request = match serde_json::from_slice(&msg) {
+        let request: Request = match serde_json::from_slice(body) {
             Ok(req) => req,
             Err(e) => {
-                warn!("Failed to parse request: {}", e);
-                let response = Response::error(
-                    ringlet_core::rpc::error_codes::INTERNAL_ERROR,
-                    format!("Invalid request: {}", e),
-                );
+                let response = parse_error_response(body, &e);
                 send_response(&socket, &response)?;
                 continue;
             }
@@ -217,9 +315,149 @@ pub async fn run(
         send_response(&socket, &response)?;
     }
 
+    events_socket.close();
     Ok(())
 }
 
+/// Listen for requests on `socket_path` and publish events next to it.
+fn listen_ipc(socket_path: &Path) -> Result<(Socket, Socket, DaemonEndpoint)> {
+    let endpoint = DaemonEndpoint::Ipc(socket_path.to_path_buf());
+
+    // Remove stale socket files if they exist
+    for path in [
+        socket_path.to_path_buf(),
+        ringlet_core::events_socket(socket_path),
+    ] {
+        if path.exists() {
+            std::fs::remove_file(&path).context("Failed to remove stale socket file")?;
+        }
+    }
+
+    let socket = Socket::new(Protocol::Rep0).context("Failed to create nng socket")?;
+    let url = endpoint.url();
+    socket
+        .listen(&url)
+        .context(format!("Failed to listen on {}", url))?;
+
+    let events = Socket::new(Protocol::Pub0).context("Failed to create nng socket")?;
+    let url = endpoint.events_url();
+    events
+        .listen(&url)
+        .context(format!("Failed to listen on {}", url))?;
+
+    Ok((socket, events, endpoint))
+}
+
+/// Listen for requests and publish events on ephemeral TCP loopback ports.
+///
+/// The endpoint carries a fresh token that every request must present.
+fn listen_tcp() -> Result<(Socket, Socket, DaemonEndpoint)> {
+    let socket = Socket::new(Protocol::Rep0).context("Failed to create nng socket")?;
+    let events = Socket::new(Protocol::Pub0).context("Failed to create nng socket")?;
+    let endpoint = DaemonEndpoint::Tcp {
+        port: listen_ephemeral(&socket)?,
+        events_port: listen_ephemeral(&events)?,
+        token: generate_token().context("Failed to generate daemon token")?,
+    };
+    Ok((socket, events, endpoint))
+}
+
+/// Record the endpoint where only the current user can read it, as it may hold a token.
+fn write_endpoint_file(path: &Path, endpoint: &DaemonEndpoint) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // An endpoint file left by an older daemon keeps its mode otherwise
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    options
+        .open(path)?
+        .write_all(endpoint.to_string().as_bytes())
+}
+
+/// The request body of `msg`, or `None` if a TCP request lacks the endpoint's token.
+fn authenticate<'a>(msg: &'a [u8], endpoint: &DaemonEndpoint) -> Option<&'a [u8]> {
+    match endpoint {
+        DaemonEndpoint::Ipc(_) => Some(msg),
+        DaemonEndpoint::Tcp { token, .. } => {
+            let newline = msg.iter().position(|&b| b == b'\n')?;
+            let (presented, body) = (&msg[..newline], &msg[newline + 1..]);
+            bool::from(presented.ct_eq(token.as_bytes())).then_some(body)
+        }
+    }
+}
+
+/// Listen on an OS-assigned loopback port and return it.
+fn listen_ephemeral(socket: &Socket) -> Result<u16> {
+    let listener =
+        Listener::new(socket, "tcp://127.0.0.1:0").context("Failed to listen on 127.0.0.1")?;
+    match listener.get_opt::<LocalAddr>()? {
+        // nng hands the port back in network byte order without converting it
+        SocketAddr::Inet(addr) => Ok(u16::from_be(addr.port())),
+        addr => Err(anyhow!("Unexpected listener address {}", addr)),
+    }
+}
+
+/// Forward daemon events to the event stream socket.
+///
+/// The CLI subscribes to it to render progress of long-running requests.
+fn publish_events(socket: &Socket, state: &ServerState) {
+    let publisher = socket.clone();
+    let mut events = state.events.subscribe();
+    std::thread::spawn(move || {
+        loop {
+            let event = match events.blocking_recv() {
+                Ok(sequenced) => sequenced.event,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            };
+            let Ok(json) = serde_json::to_vec(&event) else {
+                continue;
+            };
+            if publisher.send(nng::Message::from(&json[..])).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Build the reply for a request that failed to parse.
+///
+/// A well-formed request whose `type` this daemon does not know comes from a
+/// newer client and gets `NOT_IMPLEMENTED`; anything else is malformed.
+fn parse_error_response(msg: &[u8], error: &serde_json::Error) -> Response {
+    let request_type = serde_json::from_slice::<serde_json::Value>(msg)
+        .ok()
+        .and_then(|value| value.get("type")?.as_str().map(str::to_string));
+
+    match request_type {
+        Some(request_type) if error.to_string().starts_with("unknown variant") => {
+            warn!("Unsupported request type '{}'", request_type);
+            Response::error(
+                error_codes::NOT_IMPLEMENTED,
+                format!(
+                    "Request '{}' is not supported by this daemon (ringlet {}). \
+                     Restart it with `ringlet daemon stop` and run the command again.",
+                    request_type,
+                    env!("CARGO_PKG_VERSION")
+                ),
+            )
+        }
+        _ => {
+            warn!("Failed to parse request: {}", error);
+            Response::error(
+                error_codes::INTERNAL_ERROR,
+                format!("Invalid request: {}", error),
+            )
+        }
+    }
+}
+
 /// Receive a message with timeout.
 fn recv_with_timeout(socket: &Socket, timeout: Duration) -> Result<Option<nng::Message>> {
     // Set receive timeout
@@ -241,3 +479,90 @@ fn send_response(socket: &Socket, response: &Response) -> Result<()> {
         .map_err(|(_, e)| anyhow::anyhow!("Send failed: {}", e))?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(msg: &str) -> Response {
+        let error = serde_json::from_str::<Request>(msg).unwrap_err();
+        parse_error_response(msg.as_bytes(), &error)
+    }
+
+    #[test]
+    fn test_unknown_request_type_is_not_implemented() {
+        match parse_error(r#"{"type":"profiles_teleport","alias":"work"}"#) {
+            Response::Error { code, message } => {
+                assert_eq!(code, error_codes::NOT_IMPLEMENTED);
+                assert!(message.contains("profiles_teleport"));
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_malformed_request_is_internal_error() {
+        match parse_error(r#"{"type":"profiles_inspect"}"#) {
+            Response::Error { code, .. } => assert_eq!(code, error_codes::INTERNAL_ERROR),
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_tcp_fallback_is_reachable() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(listen_ipc(&dir.path().join("missing").join("ringletd.sock")).is_err());
+
+        let (socket, _events, endpoint) = listen_tcp().unwrap();
+        assert!(matches!(endpoint, DaemonEndpoint::Tcp { .. }));
+
+        let client = Socket::new(Protocol::Req0).unwrap();
+        client.dial(&endpoint.url()).unwrap();
+        client
+            .send(nng::Message::from(&endpoint.frame_request(b"ping")[..]))
+            .unwrap();
+        let msg = socket.recv().unwrap();
+        assert_eq!(authenticate(&msg, &endpoint), Some(&b"ping"[..]));
+    }
+
+    #[test]
+    fn test_tcp_requests_need_the_token() {
+        let (socket, _events, endpoint) = listen_tcp().unwrap();
+        let client = Socket::new(Protocol::Req0).unwrap();
+        client.dial(&endpoint.url()).unwrap();
+
+        for msg in [&b"ping"[..], b"\nping", b"wrong-token\nping"] {
+            client.send(nng::Message::from(msg)).unwrap();
+            let received = socket.recv().unwrap();
+            assert_eq!(authenticate(&received, &endpoint), None);
+            send_response(&socket, &Response::error(error_codes::UNAUTHORIZED, "no")).unwrap();
+            client.recv().unwrap();
+        }
+
+        let ipc = DaemonEndpoint::Ipc(PathBuf::from("/tmp/ringletd.sock"));
+        assert_eq!(authenticate(b"ping", &ipc), Some(&b"ping"[..]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_endpoint_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon-endpoint");
+        std::fs::write(&path, "old").unwrap();
+        let endpoint = DaemonEndpoint::Tcp {
+            port: 1,
+            events_port: 2,
+            token: "secret".to_string(),
+        };
+
+        write_endpoint_file(&path, &endpoint).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(
+            DaemonEndpoint::parse(&std::fs::read_to_string(&path).unwrap()),
+            Some(endpoint)
+        );
+    }
+}
@@ -7,15 +7,28 @@
 //! - Aggregating statistics
 
 use anyhow::{Context, Result};
-use chrono::{DateTime, Utc};
-use ringlet_core::{CostBreakdown, DailyUsage, ModelUsage, ProfileUsage, RingletPaths, TokenUsage};
+use chrono::{DateTime, NaiveDate, Utc};
+use ringlet_core::{
+    CostBreakdown, DailyUsage, ModelUsage, ProfileUsage, RingletPaths, TokenUsage, UserConfig,
+};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::{File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
 use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
 use tracing::{debug, warn};
 
+/// Serializes writes to the sessions log and aggregates within the daemon.
+///
+/// Every `TelemetryCollector` shares the same files, so appending a session
+/// while `purge_sessions` rewrites the log would otherwise lose the session.
+static SESSIONS_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_sessions() -> MutexGuard<'static, ()> {
+    SESSIONS_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
 /// A recorded session.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
@@ -104,6 +117,9 @@ pub struct Aggregates {
     /// Total cost (only from "self" provider profiles).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub total_cost: Option<CostBreakdown>,
+    /// Cost in USD per profile and date (YYYY-MM-DD), for quota checks.
+    #[serde(default)]
+    pub profile_daily_cost: HashMap<String, BTreeMap<String, f64>>,
 }
 
 /// Per-agent statistics.
@@ -140,7 +156,19 @@ impl TelemetryCollector {
     }
 
     /// Record a session.
+    ///
+    /// Does nothing when `telemetry.enabled = false`.
     pub fn record_session(&self, session: &Session) -> Result<()> {
+        if !self.enabled() {
+            debug!(
+                "Telemetry disabled; not recording session for profile: {}",
+                session.profile
+            );
+            return Ok(());
+        }
+
+        let _guard = lock_sessions();
+
         // Append to sessions.jsonl
         let sessions_path = self.paths.sessions_log();
         let mut file = OpenOptions::new()
@@ -160,6 +188,13 @@ impl TelemetryCollector {
         Ok(())
     }
 
+    /// Whether session telemetry is enabled in config.toml.
+    fn enabled(&self) -> bool {
+        UserConfig::load(&self.paths.config_file())
+            .map(|config| config.telemetry.enabled)
+            .unwrap_or(true)
+    }
+
     /// Update aggregated statistics.
     fn update_aggregates(&self, session: &Session) -> Result<()> {
         let mut aggregates = self.load_aggregates()?;
@@ -206,6 +241,28 @@ impl TelemetryCollector {
         Ok(Self::aggregate_sessions(&filtered))
     }
 
+    /// A profile's recorded cost per date (YYYY-MM-DD), in USD.
+    ///
+    /// Read from the aggregates rather than the sessions log. Aggregates
+    /// saved before they tracked this are rebuilt from the log once.
+    pub fn profile_daily_costs(&self, alias: &str) -> Result<BTreeMap<String, f64>> {
+        let mut aggregates = self.load_aggregates()?;
+        let stale = aggregates
+            .by_profile
+            .get(alias)
+            .is_some_and(|profile| profile.cost.is_some())
+            && !aggregates.profile_daily_cost.contains_key(alias);
+        if stale {
+            let _guard = lock_sessions();
+            aggregates = Self::aggregate_sessions(&self.load_all_sessions()?);
+            self.save_aggregates(&aggregates)?;
+        }
+        Ok(aggregates
+            .profile_daily_cost
+            .remove(alias)
+            .unwrap_or_default())
+    }
+
     /// Load all recorded sessions.
     pub fn load_all_sessions(&self) -> Result<Vec<Session>> {
         let sessions_path = self.paths.sessions_log();
@@ -229,6 +286,43 @@ impl TelemetryCollector {
             .collect())
     }
 
+    /// Delete recorded sessions, keeping those that ended on or after `before`.
+    ///
+    /// Aggregates are rebuilt from the sessions kept. Returns the number removed.
+    pub fn purge_sessions(&self, before: Option<NaiveDate>) -> Result<usize> {
+        let _guard = lock_sessions();
+        let sessions = self.load_all_sessions()?;
+        let total = sessions.len();
+        let kept: Vec<Session> = match before {
+            Some(before) => sessions
+                .into_iter()
+                .filter(|session| {
+                    session.ended_at.unwrap_or(session.started_at).date_naive() >= before
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let sessions_path = self.paths.sessions_log();
+        if kept.is_empty() {
+            if sessions_path.exists() {
+                std::fs::remove_file(&sessions_path).context("Failed to remove sessions log")?;
+            }
+        } else {
+            let mut content = String::new();
+            for session in &kept {
+                content.push_str(&serde_json::to_string(session)?);
+                content.push('\n');
+            }
+            let tmp_path = sessions_path.with_extension("jsonl.tmp");
+            std::fs::write(&tmp_path, content).context("Failed to write sessions log")?;
+            std::fs::rename(&tmp_path, &sessions_path).context("Failed to replace sessions log")?;
+        }
+
+        self.save_aggregates(&Self::aggregate_sessions(&kept))?;
+        Ok(total - kept.len())
+    }
+
     /// Load recent sessions.
     pub fn load_recent_sessions(&self, limit: usize) -> Result<Vec<Session>> {
         let mut sessions = self.load_all_sessions()?;
@@ -343,6 +437,12 @@ impl TelemetryCollector {
             } else {
                 daily_stats.cost = Some(cost.clone());
             }
+            *aggregates
+                .profile_daily_cost
+                .entry(session.profile.clone())
+                .or_default()
+                .entry(date_key.clone())
+                .or_default() += cost.total_cost;
         }
 
         if let Some(ref model) = session.model {
@@ -355,6 +455,7 @@ impl TelemetryCollector {
                         ..Default::default()
                     });
             model_stats.sessions += 1;
+            model_stats.runtime_secs += duration;
             if let Some(ref tokens) = session.tokens {
                 model_stats.tokens += tokens.clone();
             }
@@ -368,3 +469,85 @@ impl TelemetryCollector {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::profile_store::test_paths;
+    use chrono::TimeZone;
+
+    fn session(profile: &str, day: u32) -> Session {
+        let ended_at = Utc.with_ymd_and_hms(2025, 1, day, 12, 0, 0).unwrap();
+        Session {
+            session_id: String::new(),
+            profile: profile.to_string(),
+            agent_id: "claude".to_string(),
+            provider_id: "self".to_string(),
+            started_at: ended_at,
+            ended_at: Some(ended_at),
+            duration_secs: Some(60),
+            exit_code: Some(0),
+            source: SessionSource::ProfileRun,
+            model: None,
+            tokens: None,
+            cost: None,
+        }
+    }
+
+    #[test]
+    fn test_purge_sessions_before_date() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = test_paths(dir.path());
+        paths.ensure_dirs().unwrap();
+        let telemetry = TelemetryCollector::new(paths);
+        for (profile, day) in [("old", 1), ("edge", 10), ("new", 20)] {
+            telemetry.record_session(&session(profile, day)).unwrap();
+        }
+
+        let before = NaiveDate::from_ymd_opt(2025, 1, 10).unwrap();
+        assert_eq!(telemetry.purge_sessions(Some(before)).unwrap(), 1);
+        let kept = telemetry.load_all_sessions().unwrap();
+        assert_eq!(kept.len(), 2);
+        let aggregates = telemetry.load_aggregates().unwrap();
+        assert_eq!(aggregates.total_sessions, 2);
+        assert!(!aggregates.by_profile.contains_key("old"));
+
+        assert_eq!(telemetry.purge_sessions(None).unwrap(), 2);
+        assert!(telemetry.load_all_sessions().unwrap().is_empty());
+        assert_eq!(telemetry.load_aggregates().unwrap().total_sessions, 0);
+    }
+
+    #[test]
+    fn test_profile_daily_costs() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = test_paths(dir.path());
+        paths.ensure_dirs().unwrap();
+        let telemetry = TelemetryCollector::new(paths.clone());
+        for (profile, day, cost) in [
+            ("work", 1, 1.5),
+            ("work", 1, 2.0),
+            ("work", 2, 1.0),
+            ("home", 1, 9.0),
+        ] {
+            let mut session = session(profile, day);
+            session.cost = Some(CostBreakdown {
+                total_cost: cost,
+                ..Default::default()
+            });
+            telemetry.record_session(&session).unwrap();
+        }
+
+        let expected = BTreeMap::from([
+            ("2025-01-01".to_string(), 3.5),
+            ("2025-01-02".to_string(), 1.0),
+        ]);
+        assert_eq!(telemetry.profile_daily_costs("work").unwrap(), expected);
+        assert!(telemetry.profile_daily_costs("none").unwrap().is_empty());
+
+        // Aggregates from before the per-profile breakdown are rebuilt
+        let mut aggregates = telemetry.load_aggregates().unwrap();
+        aggregates.profile_daily_cost.clear();
+        telemetry.save_aggregates(&aggregates).unwrap();
+        assert_eq!(telemetry.profile_daily_costs("work").unwrap(), expected);
+    }
+}
@@ -3,6 +3,7 @@
 //! Manages the lifecycle of terminal sessions, including creation,
 //! lookup, and cleanup.
 
+use super::persist::{SessionDescriptor, SessionStore};
 use super::pty_bridge::spawn_pty_session;
 use super::sandbox::SandboxConfig;
 use super::session::{
@@ -14,6 +15,7 @@ use portable_pty::PtySize;
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::sync::{RwLock, broadcast, mpsc};
 use tracing::{debug, info, warn};
 use uuid::Uuid;
@@ -24,20 +26,24 @@ pub struct TerminalSessionManager {
     sessions: RwLock<HashMap<SessionId, Arc<TerminalSession>>>,
     /// Maps profile alias to active session (one active session per profile).
     profile_sessions: RwLock<HashMap<String, SessionId>>,
-}
-
-impl Default for TerminalSessionManager {
-    fn default() -> Self {
-        Self::new()
-    }
+    /// Bytes of output kept per session for replay to new clients.
+    scrollback_bytes: usize,
+    /// Descriptors of persistent sessions, relaunched on daemon restart.
+    store: SessionStore,
+    /// Set once the daemon starts shutting down, so persistent sessions
+    /// ended by the shutdown keep their descriptors.
+    shutting_down: Arc<AtomicBool>,
 }
 
 impl TerminalSessionManager {
     /// Create a new session manager.
-    pub fn new() -> Self {
+    pub fn new(scrollback_bytes: usize, store: SessionStore) -> Self {
         Self {
             sessions: RwLock::new(HashMap::new()),
             profile_sessions: RwLock::new(HashMap::new()),
+            scrollback_bytes,
+            store,
+            shutting_down: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -50,6 +56,7 @@ impl TerminalSessionManager {
     ///
     /// Returns the session ID and a handle to the session.
     /// The `owner_token_hash` is used to verify session ownership on WebSocket connections.
+    /// With a `persist` descriptor the session is relaunched after a daemon restart.
     #[allow(clippy::too_many_arguments)]
     pub async fn create_session(
         &self,
@@ -62,6 +69,7 @@ impl TerminalSessionManager {
         sandbox_config: SandboxConfig,
         owner_token_hash: String,
         telemetry: Option<SessionTelemetryContext>,
+        persist: Option<SessionDescriptor>,
     ) -> Result<Arc<TerminalSession>> {
         // Check if there's already an active session for this profile
         // Skip this check for shell sessions (allow multiple shells)
@@ -82,6 +90,9 @@ impl TerminalSessionManager {
         }
 
         let session_id = Self::generate_session_id();
+        if let Some(descriptor) = &persist {
+            self.store.save(&session_id, descriptor)?;
+        }
         let size = initial_size.unwrap_or(PtySize {
             rows: 24,
             cols: 80,
@@ -102,6 +113,8 @@ impl TerminalSessionManager {
             input_tx,
             output_tx,
             size,
+            self.scrollback_bytes,
+            persist.is_some(),
         ));
 
         // Store the session
@@ -124,6 +137,8 @@ impl TerminalSessionManager {
         let command = command.to_string();
         let args = args.to_vec();
         let working_dir = working_dir.to_path_buf();
+        let store = self.store.clone();
+        let shutting_down = self.shutting_down.clone();
 
         tokio::spawn(async move {
             if let Err(e) = spawn_pty_session(
@@ -145,6 +160,10 @@ impl TerminalSessionManager {
                     .set_state(SessionState::Terminated { exit_code: None })
                     .await;
             }
+            // The session ended on its own, so there is nothing to relaunch
+            if session_clone.persist && !shutting_down.load(Ordering::SeqCst) {
+                store.remove(&session_clone.id);
+            }
         });
 
         Ok(session)
@@ -191,6 +210,7 @@ impl TerminalSessionManager {
         session
             .set_state(SessionState::Terminated { exit_code: None })
             .await;
+        self.store.remove(id);
 
         info!("Terminated session {}", id);
         Ok(())
@@ -229,8 +249,16 @@ impl TerminalSessionManager {
         count
     }
 
+    /// Take the descriptors of persistent sessions left by the last daemon run.
+    pub fn take_persisted(&self) -> Vec<(SessionId, SessionDescriptor)> {
+        self.store.take_all()
+    }
+
     /// Terminate all sessions (for shutdown).
+    ///
+    /// Persistent sessions keep their descriptors and are relaunched on restart.
     pub async fn terminate_all(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
         let sessions = self.sessions.read().await;
         for (id, session) in sessions.iter() {
             if !session.is_terminated().await {
@@ -5,11 +5,13 @@
 //! with the same session simultaneously.
 
 mod manager;
+pub mod persist;
 mod pty_bridge;
 pub mod sandbox;
 pub mod session;
 
 pub use crate::daemon::telemetry::SessionTelemetryContext;
 pub use manager::TerminalSessionManager;
+pub use persist::{SessionDescriptor, SessionStore};
 pub use sandbox::SandboxConfig;
-pub use session::{SessionId, SessionState, TerminalSessionInfo};
+pub use session::{ClientMode, SessionId, SessionState, TerminalSessionInfo};
@@ -0,0 +1,139 @@
+//! Descriptors of persistent terminal sessions.
+//!
+//! A PTY can't outlive the daemon, so sessions created with `persist: true`
+//! are recorded here and relaunched when the daemon starts again.
+
+use super::sandbox::SandboxConfig;
+use super::session::SessionId;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tracing::warn;
+
+/// What is needed to relaunch a profile session.
+///
+/// Holds the original request rather than the prepared command, so no
+/// credentials from the rendered environment are written to disk.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionDescriptor {
+    pub profile_alias: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Working directory requested at creation, if it overrode the profile's.
+    #[serde(default)]
+    pub working_dir: Option<PathBuf>,
+    pub cols: u16,
+    pub rows: u16,
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+    /// When the session was first created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Stores one descriptor per session as `<session id>.json`.
+#[derive(Debug, Clone)]
+pub struct SessionStore {
+    dir: PathBuf,
+}
+
+impl SessionStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    /// Record a descriptor for session `id`.
+    pub fn save(&self, id: &str, descriptor: &SessionDescriptor) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let path = self.path(id);
+        let content = serde_json::to_string_pretty(descriptor)?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Forget session `id`; missing descriptors are ignored.
+    pub fn remove(&self, id: &str) {
+        let path = self.path(id);
+        if let Err(e) = std::fs::remove_file(&path)
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            warn!("Failed to remove {}: {}", path.display(), e);
+        }
+    }
+
+    /// Remove and return every stored descriptor, oldest first.
+    ///
+    /// Unreadable descriptors are skipped with a warning and removed too.
+    pub fn take_all(&self) -> Vec<(SessionId, SessionDescriptor)> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        let mut descriptors: Vec<(SessionId, SessionDescriptor)> = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(id) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".json"))
+            else {
+                continue;
+            };
+            let id = id.to_string();
+            match std::fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| Ok(serde_json::from_str(&content)?))
+            {
+                Ok(descriptor) => descriptors.push((id.clone(), descriptor)),
+                Err(e) => warn!("Skipping terminal session {}: {}", path.display(), e),
+            }
+            self.remove(&id);
+        }
+        descriptors.sort_by_key(|(_, descriptor)| descriptor.created_at);
+        descriptors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn descriptor(alias: &str, created_at: DateTime<Utc>) -> SessionDescriptor {
+        SessionDescriptor {
+            profile_alias: alias.to_string(),
+            args: vec!["--resume".to_string()],
+            working_dir: None,
+            cols: 120,
+            rows: 40,
+            sandbox: SandboxConfig::disabled(),
+            created_at,
+        }
+    }
+
+    #[test]
+    fn test_take_all_drains_descriptors() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SessionStore::new(dir.path().join("sessions"));
+        assert!(store.take_all().is_empty());
+
+        let now = Utc::now();
+        let newer = descriptor("newer", now);
+        let older = descriptor("older", now - chrono::Duration::hours(1));
+        store.save("a", &newer).unwrap();
+        store.save("b", &older).unwrap();
+        store.save("c", &descriptor("removed", now)).unwrap();
+        store.remove("c");
+        std::fs::write(dir.path().join("sessions/bad.json"), "{").unwrap();
+
+        assert_eq!(
+            store.take_all(),
+            [("b".to_string(), older), ("a".to_string(), newer)]
+        );
+        assert!(store.take_all().is_empty());
+    }
+}
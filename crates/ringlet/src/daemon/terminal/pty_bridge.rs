@@ -4,7 +4,7 @@
 //! to integrate it with the async Tokio runtime.
 
 use super::sandbox::{SandboxConfig, prepare_command};
-use super::session::{SessionState, TerminalInput, TerminalOutput, TerminalSession};
+use super::session::{SessionState, TerminalInput, TerminalSession};
 use crate::daemon::telemetry::{Session, SessionTelemetryContext, TelemetryCollector};
 use anyhow::{Context, Result};
 use portable_pty::{CommandBuilder, PtySize, native_pty_system};
@@ -35,7 +35,6 @@ pub async fn spawn_pty_session(
     let command = sandboxed.command;
     let args = sandboxed.args;
     let working_dir = working_dir.to_path_buf();
-    let output_tx = session.output_sender();
 
     // Create PTY system
     let pty_system = native_pty_system();
@@ -99,18 +98,9 @@ pub async fn spawn_pty_session(
 
     let session_id = session.id.clone();
 
-    // Create a channel for passing data from the blocking reader to async scrollback writer
-    let (scrollback_tx, mut scrollback_rx) = mpsc::channel::<Vec<u8>>(256);
-    let session_for_scrollback = session.clone();
+    let session_for_reader = session.clone();
 
-    // Spawn async task to write to scrollback buffer
-    let scrollback_handle = tokio::spawn(async move {
-        while let Some(data) = scrollback_rx.recv().await {
-            session_for_scrollback.append_scrollback(&data).await;
-        }
-    });
-
-    // Spawn blocking reader task (PTY output -> broadcast + scrollback channel)
+    // Spawn blocking reader task (PTY output -> scrollback + broadcast)
     let reader_handle = tokio::task::spawn_blocking(move || {
         let mut reader = reader_master;
         let mut buffer = [0u8; 4096];
@@ -123,11 +113,7 @@ pub async fn spawn_pty_session(
                     break;
                 }
                 Ok(n) => {
-                    let data = buffer[..n].to_vec();
-                    // Send to scrollback channel (best effort, don't block)
-                    let _ = scrollback_tx.try_send(data.clone());
-                    // Broadcast to connected clients (ignore errors if no receivers)
-                    let _ = output_tx.send(TerminalOutput::Data(data));
+                    session_for_reader.push_output(buffer[..n].to_vec());
                 }
                 Err(e) => {
                     // Check if it's a "would block" or similar transient error
@@ -310,7 +296,6 @@ pub async fn spawn_pty_session(
     // Clean up tasks
     reader_handle.abort();
     writer_handle.abort();
-    scrollback_handle.abort();
 
     Ok(())
 }
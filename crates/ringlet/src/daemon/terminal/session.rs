@@ -3,12 +3,9 @@
 use chrono::{DateTime, Utc};
 use portable_pty::PtySize;
 use std::collections::VecDeque;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tokio::sync::{RwLock, broadcast, mpsc};
 
-/// Maximum scrollback buffer size (bytes).
-const MAX_SCROLLBACK_SIZE: usize = 1024 * 1024; // 1MB
-
 /// Unique identifier for a terminal session (UUID).
 pub type SessionId = String;
 
@@ -43,6 +40,33 @@ impl std::fmt::Display for SessionState {
     }
 }
 
+/// How a client is attached to a session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientMode {
+    /// Receives output and may send input.
+    #[default]
+    Interactive,
+    /// Receives output only; input is dropped.
+    View,
+}
+
+/// Connected client counts by mode.
+#[derive(Debug, Clone, Copy, Default)]
+struct ClientCounts {
+    interactive: usize,
+    view: usize,
+}
+
+impl ClientCounts {
+    fn get_mut(&mut self, mode: ClientMode) -> &mut usize {
+        match mode {
+            ClientMode::Interactive => &mut self.interactive,
+            ClientMode::View => &mut self.view,
+        }
+    }
+}
+
 /// Information about a terminal session (for API responses).
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TerminalSessionInfo {
@@ -62,6 +86,13 @@ pub struct TerminalSessionInfo {
     pub rows: u16,
     /// Number of connected clients.
     pub client_count: usize,
+    /// Connected clients that can send input.
+    pub interactive_clients: usize,
+    /// Connected view-only clients.
+    pub view_clients: usize,
+    /// Whether the session is relaunched after a daemon restart.
+    #[serde(default)]
+    pub persist: bool,
 }
 
 /// Input sent to the terminal.
@@ -94,6 +125,8 @@ pub struct TerminalSession {
     pub profile_alias: String,
     /// Working directory for the session.
     pub working_dir: String,
+    /// Whether the session is relaunched after a daemon restart.
+    pub persist: bool,
     /// SHA-256 hash of the auth token that created this session (for ownership verification).
     owner_token_hash: String,
     /// Current session state.
@@ -108,14 +141,38 @@ pub struct TerminalSession {
     size: Arc<RwLock<PtySize>>,
     /// Process ID if available.
     pid: Arc<RwLock<Option<u32>>>,
-    /// Number of connected clients.
-    client_count: Arc<RwLock<usize>>,
-    /// Scrollback buffer for terminal output history.
-    scrollback: Arc<RwLock<VecDeque<u8>>>,
+    /// Connected clients by mode.
+    clients: Arc<RwLock<ClientCounts>>,
+    /// Tail of terminal output replayed to newly attached clients.
+    scrollback: Mutex<Scrollback>,
+}
+
+/// Bounded tail of terminal output.
+struct Scrollback {
+    buffer: VecDeque<u8>,
+    limit: usize,
+}
+
+impl Scrollback {
+    fn new(limit: usize) -> Self {
+        Self {
+            buffer: VecDeque::new(),
+            limit,
+        }
+    }
+
+    fn append(&mut self, data: &[u8]) {
+        // Only the last `limit` bytes of a large chunk can survive
+        let data = &data[data.len().saturating_sub(self.limit)..];
+        let overflow = (self.buffer.len() + data.len()).saturating_sub(self.limit);
+        self.buffer.drain(..overflow);
+        self.buffer.extend(data);
+    }
 }
 
 impl TerminalSession {
     /// Create a new terminal session.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         id: SessionId,
         profile_alias: String,
@@ -124,11 +181,14 @@ impl TerminalSession {
         input_tx: mpsc::Sender<TerminalInput>,
         output_tx: broadcast::Sender<TerminalOutput>,
         initial_size: PtySize,
+        scrollback_bytes: usize,
+        persist: bool,
     ) -> Self {
         Self {
             id,
             profile_alias,
             working_dir,
+            persist,
             owner_token_hash,
             state: Arc::new(RwLock::new(SessionState::Starting)),
             created_at: Utc::now(),
@@ -136,8 +196,8 @@ impl TerminalSession {
             output_tx,
             size: Arc::new(RwLock::new(initial_size)),
             pid: Arc::new(RwLock::new(None)),
-            client_count: Arc::new(RwLock::new(0)),
-            scrollback: Arc::new(RwLock::new(VecDeque::with_capacity(MAX_SCROLLBACK_SIZE))),
+            clients: Arc::new(RwLock::new(ClientCounts::default())),
+            scrollback: Mutex::new(Scrollback::new(scrollback_bytes)),
         }
     }
 
@@ -146,23 +206,19 @@ impl TerminalSession {
         self.owner_token_hash == token_hash
     }
 
-    /// Append data to the scrollback buffer.
-    pub async fn append_scrollback(&self, data: &[u8]) {
-        let mut scrollback = self.scrollback.write().await;
-        // Add new data
-        for byte in data {
-            scrollback.push_back(*byte);
-        }
-        // Trim if over limit
-        while scrollback.len() > MAX_SCROLLBACK_SIZE {
-            scrollback.pop_front();
-        }
+    /// Record PTY output in the scrollback and broadcast it to clients.
+    pub fn push_output(&self, data: Vec<u8>) {
+        let mut scrollback = self.scrollback.lock().unwrap_or_else(|e| e.into_inner());
+        scrollback.append(&data);
+        // Broadcast while holding the lock so `attach` never sees a chunk twice or misses one
+        let _ = self.output_tx.send(TerminalOutput::Data(data));
     }
 
-    /// Get a copy of the scrollback buffer contents.
-    pub async fn get_scrollback(&self) -> Vec<u8> {
-        let scrollback = self.scrollback.read().await;
-        scrollback.iter().copied().collect()
+    /// Snapshot the scrollback and subscribe to live output from that point on.
+    pub fn attach(&self) -> (Vec<u8>, broadcast::Receiver<TerminalOutput>) {
+        let scrollback = self.scrollback.lock().unwrap_or_else(|e| e.into_inner());
+        let history = scrollback.buffer.iter().copied().collect();
+        (history, self.output_tx.subscribe())
     }
 
     /// Get the current session state.
@@ -209,37 +265,28 @@ impl TerminalSession {
         self.input_tx.send(input).await
     }
 
-    /// Subscribe to terminal output.
-    pub fn subscribe(&self) -> broadcast::Receiver<TerminalOutput> {
-        self.output_tx.subscribe()
-    }
-
-    /// Get the output sender (for PTY bridge to use).
-    pub fn output_sender(&self) -> broadcast::Sender<TerminalOutput> {
-        self.output_tx.clone()
-    }
-
-    /// Increment client count.
-    pub async fn add_client(&self) {
-        *self.client_count.write().await += 1;
+    /// Register a connected client.
+    pub async fn add_client(&self, mode: ClientMode) {
+        *self.clients.write().await.get_mut(mode) += 1;
     }
 
-    /// Decrement client count.
-    pub async fn remove_client(&self) {
-        let mut count = self.client_count.write().await;
-        if *count > 0 {
-            *count -= 1;
-        }
+    /// Unregister a connected client.
+    pub async fn remove_client(&self, mode: ClientMode) {
+        let mut clients = self.clients.write().await;
+        let count = clients.get_mut(mode);
+        *count = count.saturating_sub(1);
     }
 
     /// Get the number of connected clients.
     pub async fn client_count(&self) -> usize {
-        *self.client_count.read().await
+        let clients = self.clients.read().await;
+        clients.interactive + clients.view
     }
 
     /// Get session info for API responses.
     pub async fn info(&self) -> TerminalSessionInfo {
         let size = self.size.read().await;
+        let clients = *self.clients.read().await;
         TerminalSessionInfo {
             id: self.id.clone(),
             profile_alias: self.profile_alias.clone(),
@@ -248,7 +295,10 @@ impl TerminalSession {
             pid: *self.pid.read().await,
             cols: size.cols,
             rows: size.rows,
-            client_count: *self.client_count.read().await,
+            client_count: clients.interactive + clients.view,
+            interactive_clients: clients.interactive,
+            view_clients: clients.view,
+            persist: self.persist,
         }
     }
 
@@ -257,3 +307,59 @@ impl TerminalSession {
         matches!(*self.state.read().await, SessionState::Terminated { .. })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_client_counts_by_mode() {
+        let (input_tx, _input_rx) = mpsc::channel(1);
+        let (output_tx, _) = broadcast::channel(1);
+        let session = TerminalSession::new(
+            "id".to_string(),
+            "profile".to_string(),
+            "/tmp".to_string(),
+            "hash".to_string(),
+            input_tx,
+            output_tx,
+            PtySize::default(),
+            1024,
+            false,
+        );
+
+        session.add_client(ClientMode::Interactive).await;
+        session.add_client(ClientMode::View).await;
+        session.add_client(ClientMode::View).await;
+        session.remove_client(ClientMode::View).await;
+        // Removing a mode with no clients must not underflow.
+        session.remove_client(ClientMode::Interactive).await;
+        session.remove_client(ClientMode::Interactive).await;
+
+        let info = session.info().await;
+        assert_eq!(info.client_count, 1);
+        assert_eq!(info.interactive_clients, 0);
+        assert_eq!(info.view_clients, 1);
+    }
+
+    #[test]
+    fn test_scrollback_keeps_tail() {
+        let mut scrollback = Scrollback::new(4);
+        scrollback.append(b"abc");
+        scrollback.append(b"de");
+        assert_eq!(
+            scrollback.buffer.iter().copied().collect::<Vec<_>>(),
+            b"bcde"
+        );
+
+        scrollback.append(b"0123456789");
+        assert_eq!(
+            scrollback.buffer.iter().copied().collect::<Vec<_>>(),
+            b"6789"
+        );
+
+        let mut disabled = Scrollback::new(0);
+        disabled.append(b"abc");
+        assert!(disabled.buffer.is_empty());
+    }
+}
@@ -6,12 +6,14 @@
 //! - OpenCode: `~/.local/share/opencode/storage/message/**/*.json`
 //!
 //! When new entries are detected, broadcasts `UsageUpdated` events via WebSocket.
+//! Directories that are removed (e.g. an agent data reset) are re-watched once
+//! they reappear.
 //!
 //! Native agent files expose agent-local project/session hints, not Ringlet profile aliases.
 //! UsageUpdated events therefore only populate `profile` when Ringlet can attribute the usage
 //! to a real profile alias.
 
-use crate::daemon::agent_usage::{UsageEntry, claude, codex, opencode};
+use crate::daemon::agent_usage::{AgentDataDirs, UsageEntry};
 use crate::daemon::events::EventBroadcaster;
 use anyhow::Result;
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
@@ -20,9 +22,26 @@ use std::collections::{HashMap, HashSet};
 use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::{Duration, Instant};
 use tracing::{debug, info, warn};
 
+/// Quiet period after the last event for a file before it is read.
+///
+/// Agents flush frequently while streaming; waiting for writes to settle turns
+/// a burst of appends into one incremental read and one broadcast.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Longest a file with pending events waits before it is read.
+///
+/// A file written more often than every `DEBOUNCE` never goes quiet; this
+/// still reports its usage while the agent keeps streaming.
+const MAX_DEBOUNCE: Duration = Duration::from_millis(1500);
+
+/// How often watch registrations are checked against the filesystem.
+const REARM_INTERVAL: Duration = Duration::from_secs(5);
+
 /// Tracks file positions for incremental reading.
 #[derive(Debug, Default)]
 struct FilePositions {
@@ -36,104 +55,119 @@ struct FilePositions {
 pub struct UsageWatcher {
     /// Event broadcaster for WebSocket notifications.
     broadcaster: Arc<EventBroadcaster>,
+    /// Agent data directories to watch.
+    dirs: AgentDataDirs,
 }
 
 impl UsageWatcher {
     /// Create a new usage watcher.
-    pub fn new(broadcaster: Arc<EventBroadcaster>) -> Self {
-        Self { broadcaster }
+    pub fn new(broadcaster: Arc<EventBroadcaster>, dirs: AgentDataDirs) -> Self {
+        Self { broadcaster, dirs }
     }
 
     /// Start watching all agent directories.
     ///
     /// This spawns a background thread that monitors directories and broadcasts events.
     /// Returns immediately after starting the watcher.
-    pub fn start(self) -> Result<()> {
+    pub fn start(self) -> Result<Arc<AtomicBool>> {
         let broadcaster = self.broadcaster;
+        let dirs = self.dirs;
+        let running = Arc::new(AtomicBool::new(true));
+        let flag = running.clone();
 
         std::thread::spawn(move || {
-            if let Err(e) = run_watcher(broadcaster) {
+            if let Err(e) = run_watcher(broadcaster, dirs) {
                 warn!("Usage watcher error: {}", e);
             }
+            flag.store(false, Ordering::Relaxed);
         });
 
-        Ok(())
+        Ok(running)
     }
 }
 
 /// Run the file watcher loop.
-fn run_watcher(broadcaster: Arc<EventBroadcaster>) -> Result<()> {
+fn run_watcher(broadcaster: Arc<EventBroadcaster>, dirs: AgentDataDirs) -> Result<()> {
     let (tx, rx) = std::sync::mpsc::channel();
 
     let mut watcher = RecommendedWatcher::new(
         move |res: Result<notify::Event, notify::Error>| {
-            if let Ok(event) = res {
-                let _ = tx.send(event);
-            }
+            let _ = tx.send(res);
         },
         Config::default().with_poll_interval(Duration::from_secs(2)),
     )?;
 
     // Directories to watch
     let watch_dirs = [
+        (dirs.claude.join("projects"), AgentType::Claude, true), // JSONL
+        (dirs.codex.join("sessions"), AgentType::Codex, true),   // JSONL
         (
-            claude::get_data_dir().join("projects"),
-            AgentType::Claude,
-            true,
-        ), // JSONL
-        (
-            codex::get_data_dir().join("sessions"),
-            AgentType::Codex,
-            true,
-        ), // JSONL
-        (
-            opencode::get_data_dir().join("storage").join("message"),
+            dirs.opencode.join("storage").join("message"),
             AgentType::OpenCode,
             false,
         ), // JSON
     ];
 
-    // Start watching directories that exist
+    // Watch directories that exist; missing ones are picked up when they appear
     for (dir, agent, _) in &watch_dirs {
-        if dir.exists() {
-            if let Err(e) = watcher.watch(dir, RecursiveMode::Recursive) {
-                warn!("Failed to watch {:?} for {}: {}", dir, agent, e);
-            } else {
-                info!("Watching {} usage at {:?}", agent, dir);
-            }
-        } else {
+        if !dir.exists() {
             debug!("{} directory not found: {:?}", agent, dir);
         }
     }
+    let mut armed = vec![false; watch_dirs.len()];
+    sync_watches(&mut watcher, &watch_dirs, &mut armed);
+    let mut last_sync = Instant::now();
 
     // Track file positions for incremental reading
     let mut file_state = FilePositions::default();
+    let mut debouncer = Debouncer::default();
 
     info!("Usage watcher started");
 
-    // Process file events
-    for event in rx {
-        for path in event.paths {
-            // Determine which agent this file belongs to
-            let agent = determine_agent(&path, &watch_dirs);
-
-            if let Some(agent) = agent {
-                // Check if it's a relevant file type
-                let is_jsonl = path.extension().is_some_and(|ext| ext == "jsonl");
-                let is_json = path.extension().is_some_and(|ext| ext == "json");
-
-                if is_jsonl && matches!(agent, AgentType::Claude | AgentType::Codex) {
-                    // Read new entries from JSONL file
-                    if let Ok(entries) = read_new_jsonl_entries(&path, &mut file_state, agent) {
-                        broadcast_entries(&broadcaster, entries);
-                    }
-                } else if is_json && matches!(agent, AgentType::OpenCode) {
-                    // Parse JSON file
-                    if let Ok(Some(entry)) = parse_new_json_entry(&path, &mut file_state) {
-                        broadcast_entries(&broadcaster, vec![entry]);
+    // Process file events, reading each file once its writes have settled
+    loop {
+        let now = Instant::now();
+        let until_sync = REARM_INTERVAL.saturating_sub(now.duration_since(last_sync));
+        let wait = debouncer
+            .next_wait(now)
+            .map_or(until_sync, |wait| wait.min(until_sync));
+
+        match rx.recv_timeout(wait) {
+            Ok(Ok(event)) => {
+                // A removed root loses its watch even if it is recreated before
+                // the next periodic check, so force a re-arm.
+                if event.kind.is_remove() {
+                    disarm_removed(&mut watcher, &event.paths, &watch_dirs, &mut armed);
+                }
+
+                let now = Instant::now();
+                for path in event.paths {
+                    if determine_agent(&path, &watch_dirs).is_some() {
+                        debouncer.touch(path, now);
                     }
                 }
             }
+            Ok(Err(e)) => {
+                warn!("Usage watcher error: {}", e);
+                // An error without paths may have affected any watch
+                let paths = if e.paths.is_empty() {
+                    watch_dirs.iter().map(|(dir, _, _)| dir.clone()).collect()
+                } else {
+                    e.paths
+                };
+                disarm_removed(&mut watcher, &paths, &watch_dirs, &mut armed);
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        if last_sync.elapsed() >= REARM_INTERVAL {
+            sync_watches(&mut watcher, &watch_dirs, &mut armed);
+            last_sync = Instant::now();
+        }
+
+        for path in debouncer.take_due(Instant::now()) {
+            process_file(&path, &watch_dirs, &mut file_state, &broadcaster);
         }
     }
 
@@ -141,6 +175,141 @@ fn run_watcher(broadcaster: Arc<EventBroadcaster>) -> Result<()> {
     Ok(())
 }
 
+/// Register watches for directories that (re)appeared and drop ones that vanished.
+///
+/// `armed[i]` tracks whether `watch_dirs[i]` currently has a live watch.
+/// Failures are logged and retried on the next call.
+fn sync_watches<W: Watcher>(
+    watcher: &mut W,
+    watch_dirs: &[(PathBuf, AgentType, bool)],
+    armed: &mut [bool],
+) {
+    for ((dir, agent, _), armed) in watch_dirs.iter().zip(armed.iter_mut()) {
+        let exists = dir.exists();
+        if exists && !*armed {
+            match watcher.watch(dir, RecursiveMode::Recursive) {
+                Ok(()) => {
+                    info!("Watching {} usage at {:?}", agent, dir);
+                    *armed = true;
+                }
+                Err(e) => warn!("Failed to watch {:?} for {}: {}", dir, agent, e),
+            }
+        } else if !exists && *armed {
+            // The watch died with the directory; unwatch is best-effort cleanup.
+            let _ = watcher.unwatch(dir);
+            info!(
+                "{} usage directory removed, waiting for it to reappear: {:?}",
+                agent, dir
+            );
+            *armed = false;
+        }
+    }
+}
+
+/// Drop the watches on roots affected by a removal or watch error, so the
+/// next [`sync_watches`] registers them afresh.
+fn disarm_removed<W: Watcher>(
+    watcher: &mut W,
+    paths: &[PathBuf],
+    watch_dirs: &[(PathBuf, AgentType, bool)],
+    armed: &mut [bool],
+) {
+    for ((dir, agent, _), armed) in watch_dirs.iter().zip(armed.iter_mut()) {
+        if *armed && paths.iter().any(|path| dir.starts_with(path)) {
+            info!("Lost watch on {} usage directory {:?}", agent, dir);
+            // The watch may already be gone with the directory.
+            let _ = watcher.unwatch(dir);
+            *armed = false;
+        }
+    }
+}
+
+/// Coalesces bursts of events for the same file into a single read.
+#[derive(Debug, Default)]
+struct Debouncer {
+    /// Map from file path to its pending events.
+    pending: HashMap<PathBuf, PendingEvents>,
+}
+
+/// Times of the first and most recent unread events for a file.
+#[derive(Debug, Clone, Copy)]
+struct PendingEvents {
+    first: Instant,
+    last: Instant,
+}
+
+impl PendingEvents {
+    /// When the file should be read: once it has been quiet for `DEBOUNCE`,
+    /// or `MAX_DEBOUNCE` after its first unread event, whichever comes first.
+    fn due_at(&self) -> Instant {
+        (self.last + DEBOUNCE).min(self.first + MAX_DEBOUNCE)
+    }
+}
+
+impl Debouncer {
+    /// Record an event for a file, restarting its quiet period.
+    fn touch(&mut self, path: PathBuf, now: Instant) {
+        self.pending
+            .entry(path)
+            .and_modify(|events| events.last = now)
+            .or_insert(PendingEvents {
+                first: now,
+                last: now,
+            });
+    }
+
+    /// Remove and return files that are due to be read.
+    fn take_due(&mut self, now: Instant) -> Vec<PathBuf> {
+        let due: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, events)| events.due_at() <= now)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &due {
+            self.pending.remove(path);
+        }
+        due
+    }
+
+    /// How long to wait before the next pending file becomes due.
+    fn next_wait(&self, now: Instant) -> Option<Duration> {
+        self.pending
+            .values()
+            .map(|events| events.due_at().saturating_duration_since(now))
+            .min()
+    }
+}
+
+/// Read new usage from a changed file and broadcast it.
+fn process_file(
+    path: &PathBuf,
+    watch_dirs: &[(PathBuf, AgentType, bool)],
+    file_state: &mut FilePositions,
+    broadcaster: &EventBroadcaster,
+) {
+    // Determine which agent this file belongs to
+    let Some(agent) = determine_agent(path, watch_dirs) else {
+        return;
+    };
+
+    // Check if it's a relevant file type
+    let is_jsonl = path.extension().is_some_and(|ext| ext == "jsonl");
+    let is_json = path.extension().is_some_and(|ext| ext == "json");
+
+    if is_jsonl && matches!(agent, AgentType::Claude | AgentType::Codex) {
+        // Read new entries from JSONL file
+        if let Ok(entries) = read_new_jsonl_entries(path, file_state, agent) {
+            broadcast_entries(broadcaster, entries);
+        }
+    } else if is_json && matches!(agent, AgentType::OpenCode) {
+        // Parse JSON file
+        if let Ok(Some(entry)) = parse_new_json_entry(path, file_state) {
+            broadcast_entries(broadcaster, vec![entry]);
+        }
+    }
+}
+
 /// Determine which agent a file path belongs to.
 fn determine_agent(
     path: &std::path::Path,
@@ -277,6 +446,7 @@ fn parse_claude_line(line: &str, project_path: &str) -> Option<UsageEntry> {
         },
         cost_usd: entry.cost_usd,
         project_path: project_path.to_string(),
+        session_id: None,
     })
 }
 
@@ -372,6 +542,7 @@ fn parse_codex_line(
         },
         cost_usd: None,
         project_path: session_path.to_string(),
+        session_id: None,
     })
 }
 
@@ -450,6 +621,7 @@ fn parse_new_json_entry(path: &PathBuf, state: &mut FilePositions) -> Result<Opt
         },
         cost_usd: entry.cost_usd,
         project_path: entry.session_id.unwrap_or_else(|| "unknown".to_string()),
+        session_id: None,
     }))
 }
 
@@ -488,19 +660,39 @@ fn extract_project_path(path: &std::path::Path, agent: AgentType) -> String {
 }
 
 /// Broadcast usage entries as events.
+///
+/// Entries read together are summed into one event per agent so a burst of
+/// appends produces a single WebSocket update.
 fn broadcast_entries(broadcaster: &EventBroadcaster, entries: Vec<UsageEntry>) {
+    let mut batches: Vec<(AgentType, ringlet_core::TokenUsage, Option<f64>)> = Vec::new();
+
     for entry in entries {
-        debug!(
-            "Broadcasting usage update: {} {:?}",
-            entry.agent, entry.tokens
-        );
+        let index = match batches
+            .iter()
+            .position(|(agent, _, _)| *agent == entry.agent)
+        {
+            Some(index) => index,
+            None => {
+                batches.push((entry.agent, ringlet_core::TokenUsage::default(), None));
+                batches.len() - 1
+            }
+        };
+        let (_, tokens, cost) = &mut batches[index];
+        *tokens += entry.tokens;
+        if let Some(c) = entry.cost_usd {
+            *cost = Some(cost.unwrap_or(0.0) + c);
+        }
+    }
+
+    for (agent, tokens, cost) in batches {
+        debug!("Broadcasting usage update: {} {:?}", agent, tokens);
 
         let event = Event::UsageUpdated {
-            agent: entry.agent,
+            agent,
             // Agent-local project/session IDs are not Ringlet profile aliases.
             profile: None,
-            tokens: entry.tokens.clone(),
-            cost: entry.cost_usd.map(|c| ringlet_core::CostBreakdown {
+            tokens,
+            cost: cost.map(|c| ringlet_core::CostBreakdown {
                 input_cost: 0.0,
                 output_cost: 0.0,
                 cache_creation_cost: 0.0,
@@ -539,4 +731,211 @@ mod tests {
         assert_eq!(entry.tokens.input_tokens, 100);
         assert_eq!(entry.tokens.output_tokens, 50);
     }
+
+    #[test]
+    fn test_quick_appends_coalesce_into_one_broadcast() {
+        use std::io::Write;
+
+        let temp = tempfile::tempdir().unwrap();
+        let projects = temp.path().join("projects");
+        let file_path = projects.join("my-project").join("session.jsonl");
+        std::fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        let watch_dirs = [(projects, AgentType::Claude, true)];
+
+        let broadcaster = EventBroadcaster::default();
+        let mut rx = broadcaster.subscribe();
+        let mut file_state = FilePositions::default();
+        let mut debouncer = Debouncer::default();
+        let start = Instant::now();
+
+        for i in 0..3 {
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&file_path)
+                .unwrap();
+            writeln!(
+                file,
+                r#"{{"message":{{"usage":{{"input_tokens":100,"output_tokens":10}}}},"messageId":"msg_{}"}}"#,
+                i
+            )
+            .unwrap();
+            debouncer.touch(file_path.clone(), start + Duration::from_millis(i * 50));
+        }
+
+        // Still inside the quiet period of the last write.
+        assert!(
+            debouncer
+                .take_due(start + Duration::from_millis(150))
+                .is_empty()
+        );
+
+        let due = debouncer.take_due(start + Duration::from_millis(100) + DEBOUNCE);
+        assert_eq!(due.len(), 1);
+        for path in due {
+            process_file(&path, &watch_dirs, &mut file_state, &broadcaster);
+        }
+
+        match rx.try_recv().unwrap().event {
+            Event::UsageUpdated { agent, tokens, .. } => {
+                assert_eq!(agent, AgentType::Claude);
+                assert_eq!(tokens.input_tokens, 300);
+                assert_eq!(tokens.output_tokens, 30);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert!(rx.try_recv().is_err());
+        assert!(debouncer.next_wait(Instant::now()).is_none());
+    }
+
+    #[test]
+    fn test_continuous_appends_are_still_reported() {
+        use std::io::Write;
+
+        let temp = tempfile::tempdir().unwrap();
+        let projects = temp.path().join("projects");
+        let file_path = projects.join("my-project").join("session.jsonl");
+        std::fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        let watch_dirs = [(projects, AgentType::Claude, true)];
+
+        let broadcaster = EventBroadcaster::default();
+        let mut rx = broadcaster.subscribe();
+        let mut file_state = FilePositions::default();
+        let mut debouncer = Debouncer::default();
+        let start = Instant::now();
+
+        // Written every 50ms for 4s, so the file is never quiet for DEBOUNCE
+        let mut reported = 0;
+        for i in 0..80 {
+            let now = start + Duration::from_millis(i * 50);
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&file_path)
+                .unwrap();
+            writeln!(
+                file,
+                r#"{{"message":{{"usage":{{"input_tokens":1,"output_tokens":1}}}},"messageId":"msg_{}"}}"#,
+                i
+            )
+            .unwrap();
+            debouncer.touch(file_path.clone(), now);
+            assert!(debouncer.next_wait(now).unwrap() <= DEBOUNCE);
+
+            for path in debouncer.take_due(now) {
+                process_file(&path, &watch_dirs, &mut file_state, &broadcaster);
+            }
+            while let Ok(event) = rx.try_recv() {
+                assert!(matches!(event.event, Event::UsageUpdated { .. }));
+                reported += 1;
+            }
+        }
+
+        assert!(reported >= 2, "reported {} times", reported);
+    }
+
+    #[test]
+    fn test_sync_watches_rearms_recreated_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let projects = temp.path().join("projects");
+        let watch_dirs = [(projects.clone(), AgentType::Claude, true)];
+        let mut armed = vec![false];
+        let mut watcher =
+            RecommendedWatcher::new(|_: notify::Result<notify::Event>| {}, Config::default())
+                .unwrap();
+
+        // Missing directory stays unarmed.
+        sync_watches(&mut watcher, &watch_dirs, &mut armed);
+        assert!(!armed[0]);
+
+        std::fs::create_dir_all(&projects).unwrap();
+        sync_watches(&mut watcher, &watch_dirs, &mut armed);
+        assert!(armed[0]);
+
+        std::fs::remove_dir_all(&projects).unwrap();
+        sync_watches(&mut watcher, &watch_dirs, &mut armed);
+        assert!(!armed[0]);
+
+        std::fs::create_dir_all(&projects).unwrap();
+        sync_watches(&mut watcher, &watch_dirs, &mut armed);
+        assert!(armed[0]);
+
+        // A removal event for the root (or a parent) forces a re-arm.
+        disarm_removed(
+            &mut watcher,
+            &[temp.path().to_path_buf()],
+            &watch_dirs,
+            &mut armed,
+        );
+        assert!(!armed[0]);
+    }
+
+    /// Watcher that records registrations instead of watching anything.
+    #[derive(Default)]
+    struct RecordingWatcher {
+        watched: Vec<PathBuf>,
+        unwatched: Vec<PathBuf>,
+    }
+
+    impl Watcher for RecordingWatcher {
+        fn new<F: notify::EventHandler>(_: F, _: Config) -> notify::Result<Self> {
+            Ok(Self::default())
+        }
+
+        fn watch(&mut self, path: &std::path::Path, _: RecursiveMode) -> notify::Result<()> {
+            self.watched.push(path.to_path_buf());
+            Ok(())
+        }
+
+        fn unwatch(&mut self, path: &std::path::Path) -> notify::Result<()> {
+            self.unwatched.push(path.to_path_buf());
+            Ok(())
+        }
+
+        fn kind() -> notify::WatcherKind {
+            notify::WatcherKind::NullWatcher
+        }
+    }
+
+    #[test]
+    fn test_disarm_unwatches_before_rearming() {
+        let temp = tempfile::tempdir().unwrap();
+        let projects = temp.path().join("claude").join("projects");
+        let sessions = temp.path().join("codex").join("sessions");
+        std::fs::create_dir_all(&projects).unwrap();
+        std::fs::create_dir_all(&sessions).unwrap();
+        let watch_dirs = [
+            (projects.clone(), AgentType::Claude, true),
+            (sessions.clone(), AgentType::Codex, true),
+        ];
+        let mut armed = vec![false; 2];
+        let mut watcher = RecordingWatcher::default();
+
+        sync_watches(&mut watcher, &watch_dirs, &mut armed);
+        assert_eq!(watcher.watched, [projects.clone(), sessions.clone()]);
+
+        // Only the root under the removed path loses its watch
+        disarm_removed(
+            &mut watcher,
+            &[temp.path().join("claude")],
+            &watch_dirs,
+            &mut armed,
+        );
+        assert_eq!(armed, [false, true]);
+        assert_eq!(watcher.unwatched, std::slice::from_ref(&projects));
+
+        sync_watches(&mut watcher, &watch_dirs, &mut armed);
+        assert_eq!(armed, [true, true]);
+        assert_eq!(watcher.watched.last(), Some(&projects));
+
+        // Disarming an unarmed root leaves the watcher alone
+        armed[1] = false;
+        disarm_removed(
+            &mut watcher,
+            std::slice::from_ref(&sessions),
+            &watch_dirs,
+            &mut armed,
+        );
+        assert_eq!(watcher.unwatched, [projects]);
+    }
 }
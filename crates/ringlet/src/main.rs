@@ -7,10 +7,9 @@
 
 #![allow(dead_code)]
 
-use mimalloc::MiMalloc;
-
+#[cfg(feature = "mimalloc")]
 #[global_allocator]
-static GLOBAL: MiMalloc = MiMalloc;
+static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
 mod client;
 mod commands;
@@ -21,6 +20,7 @@ mod output;
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
+use ringlet_core::{AgentType, ProviderType, UsageImportFormat, UsagePeriod};
 use tracing_subscriber::EnvFilter;
 
 /// ringlet - CLI orchestrator for coding agents
@@ -53,6 +53,10 @@ struct Cli {
     #[arg(long, global = true, default_value = "warn")]
     log_level: String,
 
+    /// Seconds to wait for the daemon to answer a request (0 waits indefinitely)
+    #[arg(long, global = true, value_name = "SECS")]
+    timeout: Option<u64>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -101,6 +105,7 @@ EXAMPLES:
     /// Manage providers
     #[command(after_long_help = r#"EXAMPLES:
     ringlet providers list          List all available API providers
+    ringlet providers list --type openai   List only OpenAI providers
     ringlet providers inspect anthropic   Show provider details and endpoints
 "#)]
     Providers {
@@ -160,7 +165,15 @@ EXAMPLES:
 
         /// Time period (today, yesterday, week, month, 7d, 30d, all)
         #[arg(long, short, default_value = "today")]
-        period: String,
+        period: UsagePeriod,
+
+        /// Start date (YYYY-MM-DD) of a custom range; overrides --period
+        #[arg(long, requires = "to")]
+        from: Option<String>,
+
+        /// End date (YYYY-MM-DD) of a custom range; overrides --period
+        #[arg(long, requires = "from")]
+        to: Option<String>,
 
         /// Filter by profile
         #[arg(long)]
@@ -169,6 +182,16 @@ EXAMPLES:
         /// Filter by model
         #[arg(long)]
         model: Option<String>,
+
+        /// Filter by agent (claude, codex, opencode)
+        #[arg(long)]
+        agent: Option<AgentType>,
+    },
+
+    /// Match model names to pricing data
+    Pricing {
+        #[command(subcommand)]
+        command: PricingCommands,
     },
 
     /// Run daemon in-process, or manage a running daemon
@@ -194,6 +217,10 @@ EXAMPLES:
         /// Log level (trace, debug, info, warn, error)
         #[arg(long, default_value = "info")]
         daemon_log_level: String,
+
+        /// Log output format (text, json). Defaults to $RINGLETD_LOG_FORMAT, then text.
+        #[arg(long)]
+        log_format: Option<String>,
     },
 
     /// Run environment setup tasks
@@ -202,6 +229,62 @@ EXAMPLES:
         command: EnvCommands,
     },
 
+    /// Read or change settings in config.toml
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+
+    /// Check the daemon, registry, agents, proxies, shims and directories
+    #[command(after_long_help = r#"DESCRIPTION:
+    Runs every health check in one go and prints a pass/warn/fail report with
+    a hint for each problem. Exits non-zero when a check fails. The daemon is
+    not started; if it is not running, the checks that need it are skipped.
+
+EXAMPLES:
+    ringlet doctor
+    ringlet doctor --json
+"#)]
+    Doctor,
+
+    /// Write all profiles, config files and the registry pin to a bundle
+    #[command(after_long_help = r#"DESCRIPTION:
+    The bundle is a .tar.gz holding every profile (with its hooks and proxy
+    settings), config.toml, agents.d/, providers.d/, scripts/ and the registry
+    pin. API keys and other secrets are left out; set them again after
+    importing with `ringlet profiles set-api-key`.
+
+EXAMPLES:
+    ringlet export --out ringlet.tar.gz
+    ringlet import ringlet.tar.gz     Restore it on another machine
+"#)]
+    Export {
+        /// Bundle file to write
+        #[arg(long, short = 'o', value_name = "FILE")]
+        out: std::path::PathBuf,
+    },
+
+    /// Restore profiles, config files and the registry pin from a bundle
+    Import {
+        /// Bundle written by `ringlet export`
+        #[arg(value_name = "FILE")]
+        bundle: std::path::PathBuf,
+
+        /// Replace profiles and config files that already exist
+        #[arg(long)]
+        overwrite: bool,
+
+        /// Import hooks, scripts, provider manifests and other settings that need review
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Inspect the configuration script interface
+    Scripts {
+        #[command(subcommand)]
+        command: ScriptsCommands,
+    },
+
     /// Manage profile hooks
     Hooks {
         #[command(subcommand)]
@@ -220,6 +303,12 @@ EXAMPLES:
         command: TerminalCommands,
     },
 
+    /// Observe the daemon's event stream
+    Events {
+        #[command(subcommand)]
+        command: EventsCommands,
+    },
+
     /// Launch the Tauri desktop GUI
     #[cfg(feature = "gui")]
     Gui {
@@ -244,7 +333,11 @@ EXAMPLES:
 #[derive(Subcommand, Debug)]
 enum AgentsCommands {
     /// List all agents
-    List,
+    List {
+        /// Only show agents installed on this system
+        #[arg(long)]
+        installed_only: bool,
+    },
     /// Inspect an agent
     Inspect {
         /// Agent ID
@@ -255,12 +348,60 @@ enum AgentsCommands {
 #[derive(Subcommand, Debug)]
 enum ProvidersCommands {
     /// List all providers
-    List,
+    List {
+        /// Filter by provider type (anthropic, anthropic-compatible, openai, openai-compatible, self)
+        #[arg(long = "type", value_name = "TYPE")]
+        provider_type: Option<String>,
+        /// Filter by substring of provider ID or name
+        #[arg(long)]
+        search: Option<String>,
+    },
     /// Inspect a provider
     Inspect {
         /// Provider ID
         id: String,
     },
+    /// Check that a provider endpoint is reachable and accepts a key
+    Check {
+        /// Provider ID
+        id: String,
+        /// Endpoint ID (uses provider default if not specified)
+        #[arg(long, short)]
+        endpoint: Option<String>,
+        /// API key to test (prompted for on a terminal if not specified)
+        #[arg(long)]
+        api_key: Option<String>,
+    },
+    /// Register a self-hosted server (Ollama, vLLM, LM Studio, ...) as a provider
+    AddLocal {
+        /// Provider ID
+        id: String,
+        /// Base URL of the server's API (e.g. http://localhost:11434/v1)
+        #[arg(long)]
+        url: String,
+        /// Display name (defaults to the ID)
+        #[arg(long)]
+        name: Option<String>,
+        /// API type
+        #[arg(
+            long = "type",
+            value_name = "TYPE",
+            default_value = "openai-compatible"
+        )]
+        provider_type: ProviderType,
+        /// Header carrying the API key, if not the type's standard one
+        #[arg(long)]
+        auth_header: Option<String>,
+        /// Text placed before the key in the auth header (e.g. "Bearer ")
+        #[arg(long)]
+        auth_prefix: Option<String>,
+        /// The server needs no API key
+        #[arg(long, conflicts_with_all = ["auth_header", "auth_prefix"])]
+        no_auth: bool,
+        /// Default model
+        #[arg(long, short)]
+        model: Option<String>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -277,7 +418,7 @@ enum ProfilesCommands {
         /// Model (uses provider/agent default if not specified)
         #[arg(long, short)]
         model: Option<String>,
-        /// Endpoint ID (uses provider default if not specified)
+        /// Endpoint ID (uses provider default if not specified; `auto` picks the fastest)
         #[arg(long, short)]
         endpoint: Option<String>,
         /// API key (will prompt if not provided)
@@ -298,17 +439,34 @@ enum ProfilesCommands {
         /// Skip automatic alias installation
         #[arg(long)]
         no_alias: bool,
+        /// Launch the agent through a wrapper command, split like a shell
+        /// command line (e.g. "nice -n 10 {binary} {args}")
+        #[arg(long)]
+        wrapper: Option<String>,
+        /// Arguments passed to the agent on every run, split on whitespace
+        /// (e.g. "--model opus --verbose")
+        #[arg(long, allow_hyphen_values = true)]
+        default_args: Option<String>,
     },
     /// List profiles
     List {
         /// Filter by agent ID
         #[arg(long)]
         agent: Option<String>,
+        /// Filter by provider ID
+        #[arg(long)]
+        provider: Option<String>,
+        /// Print only aliases, one per line (for shell completion)
+        #[arg(long)]
+        names_only: bool,
     },
     /// Inspect a profile
     Inspect {
         /// Profile alias
         alias: String,
+        /// Show secret values (API keys, tokens) instead of masking them
+        #[arg(long)]
+        show_secrets: bool,
     },
     /// Run an agent with a profile
     Run {
@@ -329,20 +487,121 @@ enum ProfilesCommands {
         /// Custom bwrap flags (Linux only, comma-separated)
         #[arg(long)]
         bwrap_flags: Option<String>,
+        /// Relaunch the remote session when the daemon restarts
+        #[arg(long, requires = "remote")]
+        persist: bool,
+        /// Load extra environment variables from a dotenv file
+        #[arg(long, conflicts_with = "remote")]
+        env_file: Option<std::path::PathBuf>,
+        /// Stop the profile's proxy when the agent exits, unless it was already running
+        #[arg(long, conflicts_with = "remote")]
+        ephemeral_proxy: bool,
+        /// Copy the agent's output to this file while showing it
+        #[arg(long, value_name = "PATH", conflicts_with = "remote")]
+        log: Option<std::path::PathBuf>,
         /// Arguments to pass to the agent
         #[arg(trailing_var_arg = true)]
         args: Vec<String>,
     },
-    /// Delete a profile
+    /// Delete a profile, or every profile matching --agent/--provider
     Delete {
         /// Profile alias
-        alias: String,
+        #[arg(
+            required_unless_present_any = ["agent", "provider"],
+            conflicts_with_all = ["agent", "provider"]
+        )]
+        alias: Option<String>,
+        /// Delete all profiles for this agent
+        #[arg(long)]
+        agent: Option<String>,
+        /// Delete all profiles for this provider
+        #[arg(long)]
+        provider: Option<String>,
+        /// Skip the confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Rename a profile
+    Rename {
+        /// Current profile alias
+        old: String,
+        /// New profile alias
+        new: String,
     },
     /// Export environment variables for shell
     Env {
         /// Profile alias
         alias: String,
     },
+    /// Show recent runs of a profile
+    History {
+        /// Profile alias
+        alias: String,
+        /// Number of runs to show
+        #[arg(long, short, default_value = "20")]
+        limit: usize,
+    },
+    /// Set a command to run before or after the agent (omit the command to clear)
+    SetHook {
+        /// Profile alias
+        alias: String,
+        /// Hook stage (pre-run, post-run)
+        stage: String,
+        /// Shell command to run
+        command: Option<String>,
+    },
+    /// Set the arguments passed to the agent before those given to `run` (omit to clear)
+    SetArgs {
+        /// Profile alias
+        alias: String,
+        /// Default arguments
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Set the spend quotas a profile may not exceed (omit both to clear)
+    SetQuota {
+        /// Profile alias
+        alias: String,
+        /// Daily quota in USD
+        #[arg(long, value_name = "USD")]
+        daily: Option<f64>,
+        /// Monthly quota in USD
+        #[arg(long, value_name = "USD")]
+        monthly: Option<f64>,
+    },
+    /// Launch a profile's agent through a wrapper command (omit the command to clear)
+    SetWrapper {
+        /// Profile alias
+        alias: String,
+        /// Wrapper command, split like a shell command line
+        /// (e.g. "firejail --quiet {binary} {args}")
+        command: Option<String>,
+    },
+    /// Save the output of every run of a profile to a log file in its home
+    SetCaptureLogs {
+        /// Profile alias
+        alias: String,
+        /// on or off
+        #[arg(action = clap::ArgAction::Set, value_parser = clap::builder::BoolishValueParser::new())]
+        enabled: bool,
+    },
+    /// Store a new API key for a profile
+    SetApiKey {
+        /// Profile alias
+        alias: String,
+        /// API key (will prompt if not provided)
+        #[arg(long)]
+        api_key: Option<String>,
+    },
+    /// Find orphaned profile homes, dangling alias shims and proxies left by deleted profiles
+    Doctor {
+        /// Remove the dangling shims and stop the orphaned proxies that were found
+        #[arg(long)]
+        fix: bool,
+        /// With --fix, also delete orphaned homes without asking
+        #[arg(long, short, requires = "fix")]
+        yes: bool,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -389,6 +648,10 @@ enum DaemonCommands {
     Stop,
     /// Check daemon status
     Status,
+    /// Install a user service (systemd on Linux, launchd on macOS) that keeps the daemon running
+    InstallService,
+    /// Remove the user service installed by install-service
+    UninstallService,
 }
 
 #[derive(Subcommand, Debug)]
@@ -402,26 +665,60 @@ enum EnvCommands {
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum ConfigCommands {
+    /// Show a setting
+    Get {
+        /// Dotted key, e.g. telemetry.enabled
+        key: String,
+    },
+    /// Change a setting
+    Set {
+        /// Dotted key, e.g. telemetry.enabled
+        key: String,
+        /// New value
+        value: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ScriptsCommands {
+    /// Print JSON Schemas for the script context and return value
+    Schema,
+}
+
 #[derive(Subcommand, Debug)]
 pub enum UsageCommands {
     /// Show daily usage breakdown
     Daily {
         /// Time period
         #[arg(long, short, default_value = "week")]
-        period: String,
+        period: UsagePeriod,
     },
     /// Show usage by model
     Models,
     /// Show usage by profile
     Profiles,
+    /// Show the biggest contributors by cost (or tokens when cost is unavailable)
+    Top {
+        /// Rank by "model" or "profile"
+        #[arg(long, default_value = "model")]
+        by: String,
+        /// Number of entries to show
+        #[arg(long, short, default_value = "10")]
+        limit: usize,
+        /// Time period
+        #[arg(long, short, default_value = "month")]
+        period: UsagePeriod,
+    },
     /// Export usage data
     Export {
-        /// Output format (json, csv)
+        /// Output format (json, csv, jsonl)
         #[arg(long, short, default_value = "json")]
         format: String,
         /// Time period
         #[arg(long, short, default_value = "all")]
-        period: String,
+        period: UsagePeriod,
     },
     /// Import usage from Claude's native files
     ImportClaude {
@@ -429,6 +726,69 @@ pub enum UsageCommands {
         #[arg(long)]
         claude_dir: Option<std::path::PathBuf>,
     },
+    /// Import usage computed outside ringlet from a CSV or JSON file
+    Import {
+        /// File to import
+        file: std::path::PathBuf,
+        /// File format (csv, json)
+        #[arg(long, short, default_value = "csv")]
+        format: UsageImportFormat,
+    },
+    /// Show current spend against configured budgets
+    Budget,
+    /// Delete recorded usage data (telemetry sessions and the usage cache)
+    Reset {
+        /// Only delete telemetry sessions
+        #[arg(long)]
+        telemetry: bool,
+        /// Only clear cached usage data and budget alert state
+        #[arg(long)]
+        cache: bool,
+        /// Only delete telemetry sessions from before this date (YYYY-MM-DD)
+        #[arg(long)]
+        before: Option<chrono::NaiveDate>,
+        /// Skip the confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Estimate the cost of a run from token counts
+    Estimate {
+        /// Model to price (as named in the LiteLLM pricing table)
+        #[arg(long, short)]
+        model: String,
+        /// Input (prompt) tokens
+        #[arg(long, default_value = "0")]
+        input: u64,
+        /// Output (completion) tokens
+        #[arg(long, default_value = "0")]
+        output: u64,
+        /// Cache creation input tokens
+        #[arg(long, default_value = "0")]
+        cache_write: u64,
+        /// Cache read input tokens
+        #[arg(long, default_value = "0")]
+        cache_read: u64,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PricingCommands {
+    /// Download the latest LiteLLM pricing table
+    Sync,
+    /// Price a model as another model from the pricing table
+    Alias {
+        /// Model name as agents record it
+        from: String,
+        /// Pricing table key to use instead
+        to: String,
+    },
+    /// Remove a pricing alias
+    Unalias {
+        /// Model name the alias was set for
+        from: String,
+    },
+    /// List pricing aliases
+    Aliases,
 }
 
 #[derive(Subcommand, Debug)]
@@ -441,8 +801,14 @@ pub enum HooksCommands {
         event: String,
         /// Matcher pattern (e.g., "Bash|Write" or "*" for all)
         matcher: String,
-        /// Command to execute (use $EVENT for JSON event data)
+        /// Command to execute (use "$EVENT" for JSON event data)
         command: String,
+        /// Show the resulting hooks and generated agent config without saving
+        #[arg(long)]
+        dry_run: bool,
+        /// Save the command even if it looks unsafe
+        #[arg(long)]
+        force: bool,
     },
     /// List hooks for a profile
     List {
@@ -464,11 +830,53 @@ pub enum HooksCommands {
         alias: String,
         /// Path to JSON file with hooks configuration
         file: std::path::PathBuf,
+        /// Save commands even if they look unsafe
+        #[arg(long)]
+        force: bool,
     },
     /// Export hooks to JSON
     Export {
         /// Profile alias
         alias: String,
+        /// Output format: ringlet (default, re-importable), or an agent id
+        /// such as claude or codex for that agent's native config format
+        #[arg(long, default_value = "ringlet")]
+        format: String,
+    },
+    /// Manage default hooks applied to every profile of an agent
+    Defaults {
+        #[command(subcommand)]
+        command: HooksDefaultsCommands,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HooksDefaultsCommands {
+    /// List default hooks from config.toml
+    List,
+    /// Add a default hook for an agent
+    Add {
+        /// Agent ID, or "*" for every agent that supports hooks
+        agent: String,
+        /// Event type (PreToolUse, PostToolUse, Notification, Stop)
+        event: String,
+        /// Matcher pattern (e.g., "Bash|Write" or "*" for all)
+        matcher: String,
+        /// Command to execute (use $EVENT for JSON event data)
+        command: String,
+        /// Save the command even if it looks unsafe
+        #[arg(long)]
+        force: bool,
+    },
+    /// Leave the default hooks out of a profile's config
+    OptOut {
+        /// Profile alias
+        alias: String,
+    },
+    /// Include the default hooks in a profile's config again
+    OptIn {
+        /// Profile alias
+        alias: String,
     },
 }
 
@@ -496,10 +904,14 @@ pub enum ProxyCommands {
     },
     /// Stop all proxy instances
     StopAll,
-    /// Restart proxy instance
+    /// Restart proxy instance (keeps its port)
     Restart {
         /// Profile alias
         alias: String,
+        /// Health-check the new configuration on a spare port first, and keep the
+        /// running proxy if it fails
+        #[arg(long)]
+        verify: bool,
     },
     /// Show proxy status
     Status {
@@ -518,6 +930,14 @@ pub enum ProxyCommands {
         /// Number of lines to show
         #[arg(long, short, default_value = "50")]
         lines: usize,
+        /// Print the log file location instead of its contents
+        #[arg(long)]
+        path: bool,
+    },
+    /// Export environment variables pointing the profile's agent at its proxy
+    Env {
+        /// Profile alias
+        alias: String,
     },
     /// Manage routing rules
     Route {
@@ -607,6 +1027,17 @@ pub enum TerminalCommands {
     },
 }
 
+#[derive(Subcommand, Debug)]
+pub enum EventsCommands {
+    /// Print events as the daemon emits them, until interrupted
+    Watch {
+        /// Only show these kinds (profiles, proxy, registry, usage, progress),
+        /// or `<kind>:<alias>` for one profile's events
+        #[arg(long, value_delimiter = ',')]
+        kind: Vec<String>,
+    },
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Backward compat: if invoked as "ringletd" (symlink), run daemon directly
@@ -617,14 +1048,20 @@ async fn main() -> Result<()> {
 
     let cli = Cli::parse();
 
-    // Initialize logging
-    let filter =
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&cli.log_level));
-
-    tracing_subscriber::fmt()
-        .with_env_filter(filter)
-        .with_target(false)
-        .init();
+    // Initialize logging (structured output is only offered for the daemon itself)
+    let log_format = match &cli.command {
+        Commands::Daemon {
+            command: None,
+            log_format,
+            ..
+        } => LogFormat::resolve(log_format.as_deref())?,
+        _ => LogFormat::Text,
+    };
+    init_logging(&cli.log_level, log_format, false);
+
+    if let Some(secs) = cli.timeout {
+        client::set_request_timeout(secs);
+    }
 
     // Execute command
     let result = commands::execute(&cli.command, cli.json).await;
@@ -632,11 +1069,11 @@ async fn main() -> Result<()> {
     if let Err(e) = &result {
         if cli.json {
             let error = serde_json::json!({
-                "error": e.to_string()
+                "error": client::error_message(e)
             });
             println!("{}", serde_json::to_string_pretty(&error)?);
         } else {
-            eprintln!("Error: {}", e);
+            eprintln!("Error: {}", client::error_message(e));
         }
         std::process::exit(1);
     }
@@ -651,6 +1088,7 @@ async fn run_as_legacy_daemon() -> Result<()> {
     let mut foreground = false;
     let mut socket: Option<std::path::PathBuf> = None;
     let mut log_level = "info".to_string();
+    let mut log_format: Option<String> = None;
 
     let args: Vec<String> = std::env::args().skip(1).collect();
     let mut i = 0;
@@ -670,18 +1108,19 @@ async fn run_as_legacy_daemon() -> Result<()> {
                     log_level = args[i].clone();
                 }
             }
+            "--log-format" => {
+                i += 1;
+                if i < args.len() {
+                    log_format = Some(args[i].clone());
+                }
+            }
             _ => {}
         }
         i += 1;
     }
 
     // Initialize logging for daemon mode
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&log_level));
-
-    tracing_subscriber::fmt()
-        .with_env_filter(filter)
-        .with_target(true)
-        .init();
+    init_logging(&log_level, LogFormat::resolve(log_format.as_deref())?, true);
 
     daemon::run_daemon(daemon::DaemonArgs {
         stay_alive,
@@ -691,3 +1130,56 @@ async fn run_as_legacy_daemon() -> Result<()> {
     })
     .await
 }
+
+/// Log output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogFormat {
+    /// Human-readable lines.
+    Text,
+    /// One JSON object per event, for log pipelines.
+    Json,
+}
+
+impl LogFormat {
+    /// Resolve from an explicit flag, then `RINGLETD_LOG_FORMAT`, defaulting to text.
+    fn resolve(flag: Option<&str>) -> Result<Self> {
+        let value = match flag {
+            Some(value) => value.to_string(),
+            None => match std::env::var("RINGLETD_LOG_FORMAT") {
+                Ok(value) => value,
+                Err(_) => return Ok(Self::Text),
+            },
+        };
+
+        match value.to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            other => Err(anyhow::anyhow!(
+                "Unknown log format '{}': expected 'text' or 'json'",
+                other
+            )),
+        }
+    }
+}
+
+/// Install the global tracing subscriber.
+///
+/// `RUST_LOG` takes precedence over `level`. JSON output always includes the
+/// target and the active span stack so events can be correlated downstream.
+fn init_logging(level: &str, format: LogFormat, with_target: bool) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level));
+
+    match format {
+        LogFormat::Text => tracing_subscriber::fmt()
+            .with_env_filter(filter)
+            .with_target(with_target)
+            .init(),
+        LogFormat::Json => tracing_subscriber::fmt()
+            .json()
+            .with_env_filter(filter)
+            .with_target(true)
+            .with_current_span(true)
+            .with_span_list(true)
+            .init(),
+    }
+}
@@ -1,13 +1,17 @@
 //! Output formatting for CLI.
 
 use comfy_table::{Cell, Color, Table};
-use ringlet_core::UsageStatsResponse;
 use ringlet_core::agent::AgentInfo;
-use ringlet_core::profile::ProfileInfo;
-use ringlet_core::provider::ProviderInfo;
+use ringlet_core::profile::{ProfileDoctorReport, ProfileInfo, RunHistoryEntry};
+use ringlet_core::provider::{ProviderCheck, ProviderInfo};
 use ringlet_core::proxy::{
     ProfileProxyConfig, ProxyInstanceInfo, ProxyStatus, RoutingCondition, RoutingRule,
 };
+use ringlet_core::{
+    BudgetPeriod, BudgetStatus, BundleReport, CostBreakdown, Event, RegistryStatus, TokenUsage,
+    UsageStatsResponse,
+};
+use serde::Serialize;
 use std::collections::HashMap;
 
 /// Format agents as a table.
@@ -67,6 +71,21 @@ pub fn agent_detail(agent: &AgentInfo) -> String {
         lines.push(format!("Last Used: {}", last_used));
     }
 
+    if !agent.compatible_providers.is_empty() {
+        lines.push("Compatible Providers:".to_string());
+        for provider in &agent.compatible_providers {
+            let notes = provider
+                .notes
+                .as_ref()
+                .map(|n| format!(" - {}", n))
+                .unwrap_or_default();
+            lines.push(format!(
+                "  {} ({}){}",
+                provider.id, provider.provider_type, notes
+            ));
+        }
+    }
+
     lines.join("\n")
 }
 
@@ -106,6 +125,9 @@ pub fn provider_detail(provider: &ProviderInfo) -> String {
             "  {}: {}{}",
             endpoint.id, endpoint.url, default_marker
         ));
+        if let Some(ref models) = endpoint.models {
+            lines.push(format!("    Models: {}", models.join(", ")));
+        }
     }
 
     if let Some(ref model) = provider.default_model {
@@ -115,6 +137,45 @@ pub fn provider_detail(provider: &ProviderInfo) -> String {
     lines.join("\n")
 }
 
+/// Format the result of a provider endpoint check.
+pub fn provider_check(check: &ProviderCheck) -> String {
+    let auth = match check.auth_ok {
+        Some(true) => "ok",
+        Some(false) => "rejected",
+        None => "not checked",
+    };
+    let mut lines = vec![
+        format!("Provider: {} ({})", check.provider_id, check.endpoint_id),
+        format!("URL: {}", check.url),
+        format!("Reachable: {}", if check.reachable { "yes" } else { "no" }),
+        format!("Auth: {}", auth),
+        format!("Latency: {} ms", check.latency_ms),
+    ];
+
+    if let Some(status) = check.status {
+        lines.push(format!("Status: {}", status));
+    }
+    if let Some(ref error) = check.error {
+        lines.push(format!("Error: {}", error));
+    }
+
+    lines.join("\n")
+}
+
+/// Describe the registry cache's age against its TTL.
+pub fn registry_cache_age(status: &RegistryStatus) -> String {
+    let ttl = format_duration(status.cache_ttl_secs);
+    match status.cache_age_secs {
+        Some(age) => format!(
+            "{} ({}, TTL {})",
+            format_duration(age),
+            if status.stale { "stale" } else { "fresh" },
+            ttl
+        ),
+        None => format!("never synced (TTL {})", ttl),
+    }
+}
+
 /// Format profiles as a table.
 pub fn profiles_table(profiles: &[ProfileInfo]) -> Table {
     let mut table = Table::new();
@@ -138,6 +199,115 @@ pub fn profiles_table(profiles: &[ProfileInfo]) -> Table {
     table
 }
 
+/// Format a profile's run history as a table.
+pub fn run_history_table(entries: &[RunHistoryEntry]) -> Table {
+    let mut table = Table::new();
+    table.set_header(vec!["Started", "Duration", "Exit", "Args"]);
+
+    for entry in entries {
+        let exit = match entry.signal {
+            Some(signal) => match signal_name(signal) {
+                Some(name) => name.to_string(),
+                None => format!("signal {}", signal),
+            },
+            None => entry.exit_code.to_string(),
+        };
+        let exit_cell = if entry.exit_code == 0 && entry.signal.is_none() {
+            Cell::new(exit)
+        } else {
+            Cell::new(exit).fg(Color::Red)
+        };
+
+        table.add_row(vec![
+            Cell::new(entry.started_at.format("%Y-%m-%d %H:%M")),
+            Cell::new(format_duration(entry.duration_secs)),
+            exit_cell,
+            Cell::new(entry.args.join(" ")),
+        ]);
+    }
+
+    table
+}
+
+/// Format the leftovers found by `profiles doctor`.
+pub fn profile_doctor_report(report: &ProfileDoctorReport) -> String {
+    if report.is_clean() {
+        return "No leftovers from deleted profiles found".to_string();
+    }
+
+    let mut lines = Vec::new();
+    let sections = [
+        (
+            "Orphaned homes",
+            report
+                .orphaned_homes
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>(),
+        ),
+        (
+            "Dangling shims",
+            report
+                .dangling_shims
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect(),
+        ),
+        ("Orphaned proxies", report.orphaned_proxies.clone()),
+    ];
+    for (title, items) in sections {
+        if !items.is_empty() {
+            lines.push(format!("{}:", title));
+            lines.extend(items.iter().map(|item| format!("  {}", item)));
+        }
+    }
+
+    if report.fixed {
+        if report.failures.is_empty() {
+            lines.push("Cleaned up.".to_string());
+        }
+        if !report.homes_removed && !report.orphaned_homes.is_empty() {
+            lines.push("Kept orphaned homes; pass --fix --yes to delete them.".to_string());
+        }
+    } else {
+        lines.push("Run with --fix to remove them.".to_string());
+    }
+    lines.join("\n")
+}
+
+/// Format what `ringlet export` or `ringlet import` covered.
+pub fn bundle_report(report: &BundleReport, exported: bool) -> String {
+    let mut lines = vec![format!(
+        "{} {} profiles and {} config files {} {}",
+        if exported { "Exported" } else { "Imported" },
+        report.profiles.len(),
+        report.files.len(),
+        if exported { "to" } else { "from" },
+        report.path.display()
+    )];
+    if let Some(pin) = &report.registry_pin {
+        lines.push(format!("Registry pinned to: {}", pin));
+    }
+    if !report.skipped.is_empty() {
+        lines.push("Kept existing (use --overwrite to replace):".to_string());
+        lines.extend(report.skipped.iter().map(|item| format!("  {}", item)));
+    }
+    if !report.needs_api_key.is_empty() {
+        if exported {
+            lines.push("API keys are not included; set them again after importing:".to_string());
+        } else {
+            lines.push("Set API keys for:".to_string());
+        }
+        lines.extend(
+            report
+                .needs_api_key
+                .iter()
+                .map(|alias| format!("  ringlet profiles set-api-key {}", alias)),
+        );
+    }
+    lines.join("\n")
+}
+
 /// Format a single profile.
 pub fn profile_detail(profile: &ProfileInfo) -> String {
     let mut lines = vec![
@@ -152,6 +322,53 @@ pub fn profile_detail(profile: &ProfileInfo) -> String {
     if let Some(ref last_used) = profile.last_used {
         lines.push(format!("Last Used: {}", last_used));
     }
+    if let Some(ref pre_run) = profile.pre_run {
+        lines.push(format!("Pre-run: {}", pre_run));
+    }
+    if let Some(ref post_run) = profile.post_run {
+        lines.push(format!("Post-run: {}", post_run));
+    }
+    if let Some(ref wrapper) = profile.launch_wrapper {
+        lines.push(format!("Wrapper: {}", wrapper.join(" ")));
+    }
+    if !profile.args.is_empty() {
+        lines.push(format!("Default Args: {}", profile.args.join(" ")));
+    }
+    if profile.capture_logs {
+        lines.push("Capture Logs: on".to_string());
+    }
+    let quotas: Vec<_> = [
+        (profile.quota_daily_usd, "daily"),
+        (profile.quota_monthly_usd, "monthly"),
+    ]
+    .into_iter()
+    .filter_map(|(limit, period)| limit.map(|limit| format!("${:.2} {}", limit, period)))
+    .collect();
+    if !quotas.is_empty() {
+        lines.push(format!("Quota: {}", quotas.join(", ")));
+    }
+    if let Some(expires_at) = profile.token_expires_at {
+        let remaining = (expires_at - chrono::Utc::now()).num_seconds();
+        let validity = if remaining > 0 {
+            format!("valid for {}", format_duration(remaining as u64))
+        } else {
+            "expired".to_string()
+        };
+        lines.push(format!(
+            "Token: {} (expires {})",
+            validity,
+            expires_at.format("%Y-%m-%d %H:%M:%S UTC")
+        ));
+    }
+    if !profile.env.is_empty() {
+        let mut env: Vec<_> = profile.env.iter().collect();
+        env.sort();
+        lines.push("Env:".to_string());
+        lines.extend(
+            env.into_iter()
+                .map(|(key, value)| format!("  {}={}", key, value)),
+        );
+    }
 
     lines.join("\n")
 }
@@ -164,6 +381,68 @@ pub fn env_export(env: &std::collections::HashMap<String, String>) -> String {
         .join("\n")
 }
 
+/// Describe how many generated config files a run rewrote.
+///
+/// Returns `None` when the profile generates no files.
+pub fn config_write_summary(changed: usize, unchanged: usize) -> Option<String> {
+    match (changed, unchanged) {
+        (0, 0) => None,
+        (0, unchanged) => Some(format!(
+            "Config up to date ({} file{} unchanged)",
+            unchanged,
+            if unchanged == 1 { "" } else { "s" }
+        )),
+        (changed, unchanged) => Some(format!(
+            "Config updated: {} changed, {} unchanged",
+            changed, unchanged
+        )),
+    }
+}
+
+/// Describe how an agent run ended.
+///
+/// Returns `None` for a clean exit.
+pub fn run_exit_summary(exit_code: i32, signal: Option<i32>, duration_secs: u64) -> Option<String> {
+    let duration = format_duration(duration_secs);
+    match signal {
+        Some(signal) => {
+            let name = signal_name(signal)
+                .map(|name| format!(" ({})", name))
+                .unwrap_or_default();
+            // SIGKILL from outside the agent is most often the OOM killer
+            let hint = if signal == 9 {
+                "; it may have run out of memory"
+            } else {
+                ""
+            };
+            Some(format!(
+                "Agent terminated by signal {}{} after {}{}",
+                signal, name, duration, hint
+            ))
+        }
+        None if exit_code != 0 => Some(format!(
+            "Agent exited with code {} after {}",
+            exit_code, duration
+        )),
+        None => None,
+    }
+}
+
+/// Name of a common Unix signal.
+fn signal_name(signal: i32) -> Option<&'static str> {
+    match signal {
+        1 => Some("SIGHUP"),
+        2 => Some("SIGINT"),
+        3 => Some("SIGQUIT"),
+        6 => Some("SIGABRT"),
+        9 => Some("SIGKILL"),
+        11 => Some("SIGSEGV"),
+        13 => Some("SIGPIPE"),
+        15 => Some("SIGTERM"),
+        _ => None,
+    }
+}
+
 /// Print success message.
 pub fn success(message: &str) {
     println!("{}", message);
@@ -174,6 +453,51 @@ pub fn error(message: &str) {
     eprintln!("Error: {}", message);
 }
 
+/// Format a daemon event as one line: time, topic, event type and its fields.
+pub fn event_line(event: &Event, at: chrono::DateTime<chrono::Local>) -> String {
+    let value = serde_json::to_value(event).unwrap_or_default();
+    let kind = value["type"].as_str().unwrap_or("unknown");
+    let mut line = format!("{} {:<9} {}", at.format("%H:%M:%S"), event.topic(), kind);
+
+    if let Some(fields) = value["data"].as_object() {
+        for (key, field) in fields {
+            match field {
+                serde_json::Value::Null => {}
+                serde_json::Value::String(s) => line.push_str(&format!(" {}={}", key, s)),
+                other => line.push_str(&format!(" {}={}", key, other)),
+            }
+        }
+    }
+    line
+}
+
+/// Describe a JSON parse error with the offending line and a caret under the
+/// reported column.
+pub fn annotate_json_error(what: &str, source: &str, error: &serde_json::Error) -> String {
+    let (line, column) = (error.line(), error.column());
+    let full = error.to_string();
+    let message = full
+        .strip_suffix(&format!(" at line {} column {}", line, column))
+        .unwrap_or(&full);
+
+    let mut out = format!(
+        "Invalid {} at line {}, column {}: {}",
+        what, line, column, message
+    );
+    if let Some(text) = source.lines().nth(line.saturating_sub(1)) {
+        let gutter = line.to_string();
+        let caret = format!("{}^", " ".repeat(column.saturating_sub(1)));
+        out.push_str(&format!(
+            "\n{} | {}\n{} | {}",
+            console::style(&gutter).for_stderr().dim(),
+            text,
+            " ".repeat(gutter.len()),
+            console::style(caret).for_stderr().red().bold()
+        ));
+    }
+    out
+}
+
 /// Format proxy status as a table.
 pub fn proxy_status(instances: &[ProxyInstanceInfo]) {
     if instances.is_empty() {
@@ -183,7 +507,7 @@ pub fn proxy_status(instances: &[ProxyInstanceInfo]) {
 
     let mut table = Table::new();
     table.set_header(vec![
-        "Profile", "Port", "PID", "Status", "Restarts", "Started",
+        "Profile", "URL", "PID", "Status", "Restarts", "Started",
     ]);
 
     for instance in instances {
@@ -208,7 +532,7 @@ pub fn proxy_status(instances: &[ProxyInstanceInfo]) {
 
         table.add_row(vec![
             Cell::new(&instance.alias),
-            Cell::new(instance.port),
+            Cell::new(&instance.url),
             Cell::new(instance.pid),
             status_cell,
             Cell::new(instance.restart_count),
@@ -217,6 +541,14 @@ pub fn proxy_status(instances: &[ProxyInstanceInfo]) {
     }
 
     println!("{}", table);
+
+    // Show how to wire a client to a single proxy
+    if let [instance] = instances {
+        println!();
+        println!("Point an OpenAI-compatible client at the proxy:");
+        println!("  export OPENAI_API_BASE={}", instance.url);
+        println!("  curl {}/v1/models", instance.url);
+    }
 }
 
 /// Format proxy configuration.
@@ -327,34 +659,27 @@ pub fn usage_summary(usage: &UsageStatsResponse) {
         Cell::new(format_number(total_tokens)).fg(Color::Cyan),
     ]);
     println!("{}", token_table);
+    if let Some(ratio) = usage.cache_hit_ratio {
+        match usage.cache_savings_usd {
+            Some(savings) => println!(
+                "Cache hit ratio: {:.1}%  |  Saved: {}",
+                ratio * 100.0,
+                format_cost(savings)
+            ),
+            None => println!("Cache hit ratio: {:.1}%", ratio * 100.0),
+        }
+    }
     println!();
 
     // Cost breakdown (only if available)
     if let Some(ref cost) = usage.total_cost {
         println!("Cost:");
-        let mut cost_table = Table::new();
-        cost_table.set_header(vec!["Type", "Cost"]);
-        cost_table.add_row(vec![
-            Cell::new("Input"),
-            Cell::new(format_cost(cost.input_cost)),
-        ]);
-        cost_table.add_row(vec![
-            Cell::new("Output"),
-            Cell::new(format_cost(cost.output_cost)),
-        ]);
-        cost_table.add_row(vec![
-            Cell::new("Cache Creation"),
-            Cell::new(format_cost(cost.cache_creation_cost)),
-        ]);
-        cost_table.add_row(vec![
-            Cell::new("Cache Read"),
-            Cell::new(format_cost(cost.cache_read_cost)),
-        ]);
-        cost_table.add_row(vec![
-            Cell::new("Total").fg(Color::Green),
-            Cell::new(format_cost(cost.total_cost)).fg(Color::Green),
-        ]);
-        println!("{}", cost_table);
+        println!("{}", cost_table(cost));
+        println!();
+    }
+
+    if !usage.unpriced_models.is_empty() {
+        println!("{}", unpriced_warning(usage));
         println!();
     }
 
@@ -402,6 +727,220 @@ pub fn usage_summary(usage: &UsageStatsResponse) {
         }
         println!("{}", profile_table);
     }
+
+    // By model breakdown
+    if !usage.aggregates.by_model.is_empty() {
+        println!("By Model:");
+        let mut model_table = Table::new();
+        model_table.set_header(vec!["Model", "Sessions", "Runtime", "Tokens", "Cost"]);
+
+        let mut models: Vec<_> = usage.aggregates.by_model.iter().collect();
+        models.sort_by_key(|b| std::cmp::Reverse(b.1.sessions));
+
+        for (name, model_usage) in models {
+            let total_tokens = model_usage.tokens.input_tokens
+                + model_usage.tokens.output_tokens
+                + model_usage.tokens.cache_creation_input_tokens
+                + model_usage.tokens.cache_read_input_tokens;
+
+            let cost_str = model_usage
+                .cost
+                .as_ref()
+                .map(|c| format_cost(c.total_cost))
+                .unwrap_or_else(|| "-".to_string());
+
+            model_table.add_row(vec![
+                Cell::new(name),
+                Cell::new(model_usage.sessions),
+                Cell::new(format_duration(model_usage.runtime_secs)),
+                Cell::new(format_number(total_tokens)),
+                Cell::new(&cost_str),
+            ]);
+        }
+        println!("{}", model_table);
+    }
+}
+
+/// Dimension ranked by `usage top`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TopBy {
+    Model,
+    Profile,
+}
+
+/// A single ranked contributor in `usage top`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TopEntry {
+    pub name: String,
+    pub sessions: u64,
+    pub tokens: u64,
+    pub cost: Option<f64>,
+}
+
+/// Rank the biggest contributors by cost, or by tokens when no cost data is available.
+pub fn usage_top(usage: &UsageStatsResponse, by: TopBy, limit: usize) -> Vec<TopEntry> {
+    let mut entries: Vec<TopEntry> = match by {
+        TopBy::Model => usage
+            .aggregates
+            .by_model
+            .iter()
+            .map(|(name, model)| TopEntry {
+                name: name.clone(),
+                sessions: model.sessions,
+                tokens: all_tokens(&model.tokens),
+                cost: model.cost.as_ref().map(|c| c.total_cost),
+            })
+            .collect(),
+        TopBy::Profile => usage
+            .aggregates
+            .by_profile
+            .iter()
+            .map(|(name, profile)| TopEntry {
+                name: name.clone(),
+                sessions: profile.sessions,
+                tokens: all_tokens(&profile.tokens),
+                cost: profile.cost.as_ref().map(|c| c.total_cost),
+            })
+            .collect(),
+    };
+
+    let by_cost = entries.iter().any(|entry| entry.cost.is_some());
+    entries.sort_by(|a, b| {
+        let primary = if by_cost {
+            b.cost.unwrap_or(0.0).total_cmp(&a.cost.unwrap_or(0.0))
+        } else {
+            std::cmp::Ordering::Equal
+        };
+        primary
+            .then_with(|| b.tokens.cmp(&a.tokens))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    entries.truncate(limit);
+    entries
+}
+
+/// Format ranked contributors as a table.
+pub fn usage_top_table(entries: &[TopEntry], by: TopBy) -> Table {
+    let mut table = Table::new();
+    let label = match by {
+        TopBy::Model => "Model",
+        TopBy::Profile => "Profile",
+    };
+    table.set_header(vec!["#", label, "Sessions", "Tokens", "Cost"]);
+
+    for (rank, entry) in entries.iter().enumerate() {
+        table.add_row(vec![
+            Cell::new(rank + 1),
+            Cell::new(&entry.name),
+            Cell::new(entry.sessions),
+            Cell::new(format_number(entry.tokens)),
+            Cell::new(
+                entry
+                    .cost
+                    .map(format_cost)
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+        ]);
+    }
+
+    table
+}
+
+/// Total tokens including cache reads and writes.
+fn all_tokens(tokens: &TokenUsage) -> u64 {
+    tokens.input_tokens
+        + tokens.output_tokens
+        + tokens.cache_creation_input_tokens
+        + tokens.cache_read_input_tokens
+}
+
+/// Format a cost breakdown as a table.
+pub fn cost_table(cost: &CostBreakdown) -> Table {
+    let mut table = Table::new();
+    table.set_header(vec!["Type", "Cost"]);
+    table.add_row(vec![
+        Cell::new("Input"),
+        Cell::new(format_cost(cost.input_cost)),
+    ]);
+    table.add_row(vec![
+        Cell::new("Output"),
+        Cell::new(format_cost(cost.output_cost)),
+    ]);
+    table.add_row(vec![
+        Cell::new("Cache Creation"),
+        Cell::new(format_cost(cost.cache_creation_cost)),
+    ]);
+    table.add_row(vec![
+        Cell::new("Cache Read"),
+        Cell::new(format_cost(cost.cache_read_cost)),
+    ]);
+    // Agent-reported costs for models without pricing data have no split
+    let unattributed = cost.unattributed_cost();
+    if unattributed >= 0.0001 {
+        table.add_row(vec![
+            Cell::new("Unattributed"),
+            Cell::new(format_cost(unattributed)),
+        ]);
+    }
+    table.add_row(vec![
+        Cell::new("Total").fg(Color::Green),
+        Cell::new(format_cost(cost.total_cost)).fg(Color::Green),
+    ]);
+    table
+}
+
+/// Format budget spend vs. limits as a table.
+pub fn budget_table(statuses: &[BudgetStatus]) -> Table {
+    let mut table = Table::new();
+    table.set_header(vec!["Budget", "Spent", "Limit", "Used", "Status"]);
+
+    for status in statuses {
+        let used = if status.limit_usd > 0.0 {
+            format!("{:.0}%", status.spent_usd / status.limit_usd * 100.0)
+        } else {
+            "-".to_string()
+        };
+        let state = if status.exceeded {
+            Cell::new("exceeded").fg(Color::Red)
+        } else {
+            Cell::new("ok").fg(Color::Green)
+        };
+
+        table.add_row(vec![
+            Cell::new(status.period),
+            Cell::new(format_cost(status.spent_usd)),
+            Cell::new(format_cost(status.limit_usd)),
+            Cell::new(used),
+            state,
+        ]);
+    }
+
+    table
+}
+
+/// Warning that costs leave out usage of models without pricing data.
+fn unpriced_warning(usage: &UsageStatsResponse) -> String {
+    format!(
+        "Warning: {} {} using models without pricing data: {}. Costs are incomplete; \
+         run `ringlet pricing sync` to refresh pricing.",
+        usage.unpriced_entries,
+        if usage.unpriced_entries == 1 {
+            "entry"
+        } else {
+            "entries"
+        },
+        usage.unpriced_models.join(", ")
+    )
+}
+
+/// One-line warning for a budget that was crossed.
+pub fn budget_alert(period: BudgetPeriod, limit: f64, actual: f64) -> String {
+    format!(
+        "Warning: {} budget exceeded ({} spent of {} limit)",
+        period,
+        format_cost(actual),
+        format_cost(limit)
+    )
 }
 
 /// Format a number with thousands separators.
@@ -467,3 +1006,85 @@ fn format_condition(condition: &RoutingCondition) -> String {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ringlet_core::ModelUsage;
+
+    fn usage(models: &[(&str, u64, Option<f64>)]) -> UsageStatsResponse {
+        let mut aggregates = ringlet_core::UsageAggregates::default();
+        for &(name, tokens, cost) in models {
+            aggregates.by_model.insert(
+                name.to_string(),
+                ModelUsage {
+                    model: name.to_string(),
+                    tokens: TokenUsage {
+                        input_tokens: tokens,
+                        ..Default::default()
+                    },
+                    cost: cost.map(|total_cost| CostBreakdown {
+                        total_cost,
+                        ..Default::default()
+                    }),
+                    sessions: 1,
+                    runtime_secs: 0,
+                },
+            );
+        }
+        UsageStatsResponse {
+            period: "Today".to_string(),
+            aggregates,
+            total_tokens: TokenUsage::default(),
+            total_cost: None,
+            total_sessions: 0,
+            total_runtime_secs: 0,
+            cache_hit_ratio: None,
+            cache_savings_usd: None,
+            unpriced_models: Vec::new(),
+            unpriced_entries: 0,
+        }
+    }
+
+    fn names(entries: &[TopEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_usage_top_ranks_by_cost() {
+        let usage = usage(&[
+            ("haiku", 900, Some(0.5)),
+            ("opus", 100, Some(4.0)),
+            ("local", 5000, None),
+            ("sonnet", 300, Some(1.0)),
+        ]);
+
+        let top = usage_top(&usage, TopBy::Model, 10);
+        assert_eq!(names(&top), ["opus", "sonnet", "haiku", "local"]);
+
+        let top = usage_top(&usage, TopBy::Model, 2);
+        assert_eq!(names(&top), ["opus", "sonnet"]);
+        assert!(usage_top(&usage, TopBy::Profile, 10).is_empty());
+    }
+
+    #[test]
+    fn test_usage_top_falls_back_to_tokens_without_costs() {
+        let usage = usage(&[("b", 100, None), ("a", 100, None), ("c", 500, None)]);
+        let top = usage_top(&usage, TopBy::Model, 10);
+        assert_eq!(names(&top), ["c", "a", "b"]);
+        assert!(top.iter().all(|entry| entry.cost.is_none()));
+    }
+
+    #[test]
+    fn test_unpriced_warning_points_at_pricing_sync() {
+        let mut stats = usage(&[]);
+        stats.unpriced_models = vec!["gpt-x".to_string(), "my-finetune".to_string()];
+        stats.unpriced_entries = 12;
+
+        assert_eq!(
+            unpriced_warning(&stats),
+            "Warning: 12 entries using models without pricing data: gpt-x, my-finetune. \
+             Costs are incomplete; run `ringlet pricing sync` to refresh pricing."
+        );
+    }
+}
@@ -655,7 +655,13 @@ fn publish_to(ctx: &ReleaseContext, registry: &str) -> Result<()> {
 fn publish_cargo(ctx: &ReleaseContext) -> Result<()> {
     let token = env::var("CARGO_REGISTRY_TOKEN").context("CARGO_REGISTRY_TOKEN not set")?;
 
-    let crates = ["ringlet-core", "ringlet-scripting", "ringletd", "ringlet"];
+    let crates = [
+        "ringlet-core",
+        "ringlet-scripting",
+        "ringlet-client",
+        "ringletd",
+        "ringlet",
+    ];
 
     for (i, crate_name) in crates.iter().enumerate() {
         if i > 0 {
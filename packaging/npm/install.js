@@ -6,11 +6,11 @@ const { execSync } = require('child_process');
 const https = require('https');
 
 const PLATFORMS = {
-  'linux-x64': '@clown-cli/linux-x64',
-  'linux-arm64': '@clown-cli/linux-arm64',
-  'darwin-x64': '@clown-cli/darwin-x64',
-  'darwin-arm64': '@clown-cli/darwin-arm64',
-  'win32-x64': '@clown-cli/win32-x64'
+  'linux-x64': '@ringlet-cli/linux-x64',
+  'linux-arm64': '@ringlet-cli/linux-arm64',
+  'darwin-x64': '@ringlet-cli/darwin-x64',
+  'darwin-arm64': '@ringlet-cli/darwin-arm64',
+  'win32-x64': '@ringlet-cli/win32-x64'
 };
 
 function getPlatformKey() {
@@ -115,12 +115,11 @@ async function installFromGitHub() {
       execSync(`powershell -Command "Expand-Archive -Path '${archivePath}' -DestinationPath '${binDir}' -Force"`, { stdio: 'inherit' });
     } else {
       execSync(`tar -xzf "${archivePath}" -C "${binDir}"`, { stdio: 'inherit' });
-      // Make binaries executable
-      fs.chmodSync(path.join(binDir, 'clown'), 0o755);
-      fs.chmodSync(path.join(binDir, 'clownd'), 0o755);
+      // Make binary executable
+      fs.chmodSync(path.join(binDir, 'ringlet'), 0o755);
     }
 
-    console.log('clown binaries installed successfully');
+    console.log('ringlet binary installed successfully');
   } finally {
     // Cleanup
     fs.rmSync(tmpDir, { recursive: true, force: true });
@@ -132,26 +131,23 @@ async function main() {
   const binDir = path.join(__dirname, 'bin');
 
   // Try to find platform-specific package
-  const clownPath = findBinary(platformPackage, 'clown');
-  const clowndPath = findBinary(platformPackage, 'clownd');
+  // ringletd is a wrapper around `ringlet daemon`, so only one binary ships
+  const ringletPath = findBinary(platformPackage, 'ringlet');
 
-  if (clownPath && clowndPath && fs.existsSync(clownPath) && fs.existsSync(clowndPath)) {
-    // Create symlinks/copies in bin directory
+  if (ringletPath && fs.existsSync(ringletPath)) {
+    // Create a copy in bin directory
     fs.mkdirSync(binDir, { recursive: true });
 
     const ext = process.platform === 'win32' ? '.exe' : '';
-    const clownDest = path.join(binDir, `clown${ext}`);
-    const clowndDest = path.join(binDir, `clownd${ext}`);
+    const ringletDest = path.join(binDir, `ringlet${ext}`);
 
-    fs.copyFileSync(clownPath, clownDest);
-    fs.copyFileSync(clowndPath, clowndDest);
+    fs.copyFileSync(ringletPath, ringletDest);
 
     if (process.platform !== 'win32') {
-      fs.chmodSync(clownDest, 0o755);
-      fs.chmodSync(clowndDest, 0o755);
+      fs.chmodSync(ringletDest, 0o755);
     }
 
-    console.log('clown binaries installed successfully');
+    console.log('ringlet binary installed successfully');
   } else {
     // Fallback to GitHub release download
     await installFromGitHub();
@@ -17,6 +17,7 @@ import type {
   StatsResponse,
   RegistryStatus,
   UsageStatsResponse,
+  BudgetStatus,
   TerminalSessionInfo,
   CreateTerminalSessionRequest,
   CreateTerminalSessionResponse,
@@ -228,6 +229,7 @@ export const api = {
       const query = params.toString()
       return request<UsageStatsResponse>(`/usage${query ? `?${query}` : ''}`)
     },
+    budget: () => request<BudgetStatus[]>('/usage/budget'),
     importClaude: (claudeDir?: string) => {
       const params = new URLSearchParams()
       if (claudeDir) params.set('claude_dir', claudeDir)
@@ -14,6 +14,11 @@ export const useWebSocketStore = defineStore('websocket', () => {
   let socket: WebSocket | null = null
   let tauriWs: TauriWsHandle | null = null
   let reconnectTimeout: ReturnType<typeof setTimeout> | null = null
+  // Sequence number of the last broadcast event seen, so a reconnect can
+  // replay what was missed in between
+  let lastSeq: number | null = null
+
+  const wsPath = () => (lastSeq === null ? '/ws' : `/ws?since=${lastSeq}`)
 
   const recentEvents = computed(() => events.value.slice(-50))
 
@@ -30,7 +35,7 @@ export const useWebSocketStore = defineStore('websocket', () => {
 
     try {
       tauriWs = await tauriWsConnect(
-        '/ws',
+        wsPath(),
         (data: string) => {
           try {
             const msg: ServerMessage = JSON.parse(data)
@@ -62,7 +67,7 @@ export const useWebSocketStore = defineStore('websocket', () => {
     if (socket?.readyState === WebSocket.OPEN) return
 
     const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:'
-    const wsUrl = `${protocol}//${window.location.host}/ws`
+    const wsUrl = `${protocol}//${window.location.host}${wsPath()}`
 
     socket = new WebSocket(wsUrl)
 
@@ -117,8 +122,18 @@ export const useWebSocketStore = defineStore('websocket', () => {
   }
 
   function handleMessage(msg: ServerMessage) {
+    if (msg.type === 'resync') {
+      // Events from the gap are gone (or the daemon restarted), so reload
+      lastSeq = msg.seq
+      useProfilesStore().fetchProfiles()
+      useProxyStore().fetchStatus()
+      return
+    }
     if (msg.type === 'event' && msg.event) {
       const event = msg.event
+      if (msg.seq !== undefined) {
+        lastSeq = msg.seq
+      }
       events.value.push(event)
 
       // Keep only last 100 events
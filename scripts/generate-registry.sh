@@ -1,13 +1,14 @@
 #!/usr/bin/env bash
 set -euo pipefail
 
-# Generate registry.json from manifest files
+# Generate registry.json and manifest.sha256 from manifest files
 # Usage: ./scripts/generate-registry.sh
 
 SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
 ROOT_DIR="$(dirname "$SCRIPT_DIR")"
 MANIFESTS_DIR="$ROOT_DIR/manifests"
 OUTPUT_FILE="$MANIFESTS_DIR/registry.json"
+CHECKSUMS_FILE="$MANIFESTS_DIR/manifest.sha256"
 
 # Colors for output
 GREEN='\033[0;32m'
@@ -102,6 +103,20 @@ generate_artifacts() {
     echo -n "    }"
 }
 
+# Write manifest.sha256 covering registry.json and every artifact, in
+# sha256sum format. Clients verify downloads against it (registry.verify).
+generate_checksums() {
+    (
+        cd "$MANIFESTS_DIR"
+        local files=(registry.json agents/*.toml providers/*.toml scripts/*.rhai)
+        if command -v sha256sum &> /dev/null; then
+            sha256sum "${files[@]}"
+        else
+            shasum -a 256 "${files[@]}"
+        fi
+    ) > "$CHECKSUMS_FILE"
+}
+
 main() {
     info "Generating registry.json..."
 
@@ -128,6 +143,9 @@ main() {
 
     info "Generated: $OUTPUT_FILE"
 
+    generate_checksums
+    info "Generated: $CHECKSUMS_FILE"
+
     # Show summary
     local agent_count=$(find "$MANIFESTS_DIR/agents" -name "*.toml" 2>/dev/null | wc -l)
     local provider_count=$(find "$MANIFESTS_DIR/providers" -name "*.toml" 2>/dev/null | wc -l)